@@ -0,0 +1,243 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Error, Expr, Fields, Ident, Meta, NestedMeta, Result};
+
+/// Integer types allowed in `#[repr(..)]`.
+const INT_REPRS: &[&str] = &[
+    "u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize",
+];
+
+/// Parsed `#[mysql(..)]` attributes of a variant.
+#[derive(Default)]
+struct VariantAttrs {
+    fallback: bool,
+    other: bool,
+}
+
+impl VariantAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut output = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("mysql")) {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(Error::new_spanned(meta, "expected `#[mysql(..)]`")),
+            };
+
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("fallback") => {
+                        output.fallback = true;
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("other") => {
+                        output.other = true;
+                    }
+                    other => {
+                        return Err(Error::new_spanned(
+                            other,
+                            "unknown attribute (expected `fallback` or `other`)",
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Returns the integer type given in the `#[repr(..)]` attribute.
+fn parse_repr(input: &DeriveInput) -> Result<Ident> {
+    for attr in input.attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        if let Meta::List(list) = attr.parse_meta()? {
+            for nested in list.nested {
+                if let NestedMeta::Meta(Meta::Path(path)) = nested {
+                    if let Some(ident) = path.get_ident() {
+                        if INT_REPRS.iter().any(|repr| ident == repr) {
+                            return Ok(ident.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Err(Error::new_spanned(
+        &input.ident,
+        "`IntReprEnum` requires an integer `#[repr(..)]` attribute",
+    ))
+}
+
+pub fn impl_int_repr_enum(input: DeriveInput) -> Result<TokenStream> {
+    let data = match &input.data {
+        Data::Enum(data) if !data.variants.is_empty() => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`IntReprEnum` can only be derived for non-empty enums",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let repr = parse_repr(&input)?;
+
+    let mut variants: Vec<(&Ident, Option<&Expr>)> = Vec::with_capacity(data.variants.len());
+    let mut fallback = None;
+    let mut other = None;
+
+    for variant in &data.variants {
+        let attrs = VariantAttrs::parse(&variant.attrs)?;
+        match &variant.fields {
+            Fields::Unit if !attrs.other => {
+                if attrs.fallback {
+                    if fallback.is_some() {
+                        return Err(Error::new_spanned(variant, "duplicate fallback variant"));
+                    }
+                    fallback = Some(&variant.ident);
+                }
+                let discriminant = variant.discriminant.as_ref().map(|(_, expr)| expr);
+                variants.push((&variant.ident, discriminant));
+            }
+            Fields::Unnamed(fields) if attrs.other && fields.unnamed.len() == 1 => {
+                if other.is_some() {
+                    return Err(Error::new_spanned(variant, "duplicate other variant"));
+                }
+                other = Some(&variant.ident);
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    variant,
+                    "expected a unit variant or a `#[mysql(other)]` variant with a single field",
+                ))
+            }
+        }
+    }
+
+    let int_repr_enum = quote!(::mysql_common::value::convert::enums::IntReprEnum);
+    let names = variants.iter().map(|(name, _)| name).collect::<Vec<_>>();
+
+    let specific = match other {
+        Some(other) => {
+            if let Some(fallback) = fallback {
+                return Err(Error::new_spanned(
+                    fallback,
+                    "fallback variant can't be used along with the other variant",
+                ));
+            }
+            // unit variants can't be casted to integers, so discriminants are required
+            let values = variants
+                .iter()
+                .map(|(name, value)| {
+                    value.ok_or_else(|| {
+                        Error::new_spanned(
+                            name,
+                            "explicit discriminant is required if the other variant is given",
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            quote! {
+                impl #int_repr_enum for #ident {
+                    type Repr = #repr;
+
+                    fn from_repr(repr: #repr) -> ::std::option::Option<Self> {
+                        #(
+                            if repr == #values {
+                                return ::std::option::Option::Some(#ident::#names);
+                            }
+                        )*
+                        ::std::option::Option::Some(#ident::#other(repr))
+                    }
+
+                    fn to_repr(&self) -> #repr {
+                        match self {
+                            #( #ident::#names => #values, )*
+                            #ident::#other(repr) => *repr,
+                        }
+                    }
+                }
+
+                impl ::std::convert::From<#repr> for #ident {
+                    fn from(repr: #repr) -> Self {
+                        <#ident as #int_repr_enum>::from_repr(repr)
+                            .unwrap_or(#ident::#other(repr))
+                    }
+                }
+
+                impl ::std::convert::From<#ident> for #repr {
+                    fn from(x: #ident) -> #repr {
+                        <#ident as #int_repr_enum>::to_repr(&x)
+                    }
+                }
+            }
+        }
+        None => {
+            let fallback = fallback.map(|fallback| {
+                quote! {
+                    fn fallback() -> ::std::option::Option<Self> {
+                        ::std::option::Option::Some(#ident::#fallback)
+                    }
+                }
+            });
+
+            quote! {
+                impl #int_repr_enum for #ident {
+                    type Repr = #repr;
+
+                    fn from_repr(repr: #repr) -> ::std::option::Option<Self> {
+                        #(
+                            if repr == #ident::#names as #repr {
+                                return ::std::option::Option::Some(#ident::#names);
+                            }
+                        )*
+                        ::std::option::Option::None
+                    }
+
+                    fn to_repr(&self) -> #repr {
+                        match self {
+                            #( #ident::#names => #ident::#names as #repr, )*
+                        }
+                    }
+
+                    #fallback
+                }
+
+                impl ::std::convert::TryFrom<#repr> for #ident {
+                    type Error = ::mysql_common::value::convert::enums::UnknownDiscriminant<#repr>;
+
+                    fn try_from(repr: #repr) -> ::std::result::Result<Self, Self::Error> {
+                        <#ident as #int_repr_enum>::from_repr(repr)
+                            .or_else(<#ident as #int_repr_enum>::fallback)
+                            .ok_or(::mysql_common::value::convert::enums::UnknownDiscriminant(
+                                repr,
+                            ))
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(quote! {
+        #specific
+
+        impl ::mysql_common::value::convert::FromValue for #ident {
+            type Intermediate = ::mysql_common::value::convert::enums::IntReprEnumIr<#ident>;
+        }
+
+        impl ::std::convert::From<#ident> for ::mysql_common::value::Value {
+            fn from(x: #ident) -> ::mysql_common::value::Value {
+                ::mysql_common::value::Value::from(<#ident as #int_repr_enum>::to_repr(&x))
+            }
+        }
+    })
+}
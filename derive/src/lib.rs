@@ -0,0 +1,56 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Derive macros for the `mysql_common` crate.
+//!
+//! Use them via `mysql_common` (see the `derive` feature), since the generated code
+//! refers to `mysql_common` items.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod int_repr_enum;
+mod to_params;
+
+/// Implements `From<T> for Params` for a struct with named fields.
+///
+/// Every field is converted to a named parameter with the same name using
+/// `From<T> for Value` (so `Option<T>`, including nested options, is converted to `NULL`
+/// or to the inner value). The following field attributes are supported:
+///
+/// *   `#[mysql(rename = "name")]` – the field is bound to the parameter `name`;
+/// *   `#[mysql(skip)]` – the field is not converted.
+#[proc_macro_derive(ToParams, attributes(mysql))]
+pub fn derive_to_params(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_params::impl_to_params(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Implements conversion from/to `Value` for an enum with an integer representation.
+///
+/// The enum must have an integer `#[repr(..)]` attribute and only unit variants,
+/// except for an optional "other" variant (see below). Generated code implements
+/// `IntReprEnum`, `FromValue` and `From<T> for Value`. Conversion from a `Value` fails
+/// if the value is out of range of the representation. The following variant attributes
+/// are supported:
+///
+/// *   `#[mysql(fallback)]` – unknown discriminants are converted to this variant
+///     (otherwise the conversion fails). `TryFrom<Repr>` is also implemented
+///     (the error is `UnknownDiscriminant`);
+/// *   `#[mysql(other)]` – a variant with a single field of the `Repr` type, that keeps
+///     unknown discriminants. `From<Repr>` and `From<T> for Repr` are implemented instead
+///     of `TryFrom<Repr>`. Other variants must have explicit discriminants in this case.
+#[proc_macro_derive(IntReprEnum, attributes(mysql))]
+pub fn derive_int_repr_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    int_repr_enum::impl_int_repr_enum(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
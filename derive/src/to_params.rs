@@ -0,0 +1,123 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    ext::IdentExt, parse_quote, Attribute, Data, DeriveInput, Error, Fields, Lit, LitStr, Meta,
+    NestedMeta, Result,
+};
+
+/// Parsed `#[mysql(..)]` attributes of a field.
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<LitStr>,
+    skip: bool,
+}
+
+impl FieldAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut output = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("mysql")) {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(Error::new_spanned(meta, "expected `#[mysql(..)]`")),
+            };
+
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                        output.skip = true;
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("rename") => {
+                        output.rename = Some(parse_name(nv.lit)?);
+                    }
+                    other => {
+                        return Err(Error::new_spanned(
+                            other,
+                            "unknown attribute (expected `rename = \"..\"` or `skip`)",
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+fn parse_name(lit: Lit) -> Result<LitStr> {
+    match lit {
+        Lit::Str(name) => Ok(name),
+        lit => Err(Error::new_spanned(lit, "expected a string literal")),
+    }
+}
+
+pub fn impl_to_params(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "`ToParams` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`ToParams` can only be derived for structs",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    let mut inserts = Vec::with_capacity(fields.len());
+
+    for field in fields {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+
+        let field_ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let name = attrs
+            .rename
+            .unwrap_or_else(|| LitStr::new(&field_ident.unraw().to_string(), field_ident.span()));
+
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(::mysql_common::value::Value: ::std::convert::From<#ty>));
+        inserts.push(quote! {
+            map.insert(
+                ::std::vec::Vec::<u8>::from(#name),
+                ::mysql_common::value::Value::from(x.#field_ident),
+            );
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::std::convert::From<#ident #ty_generics>
+            for ::mysql_common::params::Params #where_clause
+        {
+            #[allow(unused_variables)]
+            fn from(x: #ident #ty_generics) -> Self {
+                let mut map = ::std::collections::HashMap::new();
+                #(#inserts)*
+                ::mysql_common::params::Params::Named(map)
+            }
+        }
+    })
+}
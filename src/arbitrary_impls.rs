@@ -0,0 +1,294 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `arbitrary::Arbitrary` implementations for packets and values
+//! (requires the `arbitrary` feature).
+//!
+//! Generated instances are well-formed, i.e. `parse(serialize(x)) == x` holds for them:
+//!
+//! *   `Value`s are valid MySql values (finite floats, in-range temporal values);
+//! *   byte strings, that are serialized as null-terminated strings, contain no `0x00` bytes;
+//! *   capabilities and flags consist of known bits only, and optional packet fields are present
+//!     iff corresponding capabilities are set;
+//! *   `OkPacket`s are meant to be written with `CLIENT_SESSION_TRACK` capability
+//!     (see [`crate::packets::server::ResultSetWriter::write_ok`]), and `ErrPacket`s are meant
+//!     to be parsed with `CLIENT_PROGRESS_OBSOLETE` capability.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use std::convert::TryFrom;
+
+use crate::{
+    binlog::events::BinlogEventHeader,
+    constants::{CapabilityFlags, ColumnFlags, ColumnType, MariadbCapabilities, StatusFlags},
+    io::ParseBuf,
+    packets::{
+        Column, ErrPacket, HandshakePacket, OkPacket, ProgressReport, ServerError, StmtPacket,
+    },
+    value::Value,
+};
+
+/// Arbitrary bytes without `0x00`.
+fn null_free_bytes(u: &mut Unstructured<'_>) -> Result<Vec<u8>> {
+    let mut bytes: Vec<u8> = u.arbitrary()?;
+    bytes.retain(|x| *x != 0);
+    Ok(bytes)
+}
+
+impl<'a> Arbitrary<'a> for Value {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=7_u8)? {
+            0 => Value::NULL,
+            1 => Value::Bytes(u.arbitrary()?),
+            2 => Value::Int(u.arbitrary()?),
+            3 => Value::UInt(u.arbitrary()?),
+            4 => {
+                let x: f32 = u.arbitrary()?;
+                Value::Float(if x.is_finite() { x } else { 0.0 })
+            }
+            5 => {
+                let x: f64 = u.arbitrary()?;
+                Value::Double(if x.is_finite() { x } else { 0.0 })
+            }
+            6 => Value::Date(
+                u.int_in_range(0..=9999)?,
+                u.int_in_range(0..=12)?,
+                u.int_in_range(0..=31)?,
+                u.int_in_range(0..=23)?,
+                u.int_in_range(0..=59)?,
+                u.int_in_range(0..=59)?,
+                u.int_in_range(0..=999_999)?,
+            ),
+            _ => {
+                let time = (
+                    u.int_in_range(0..=34)?,
+                    u.int_in_range(0..=23)?,
+                    u.int_in_range(0..=59)?,
+                    u.int_in_range(0..=59)?,
+                    u.int_in_range(0..=999_999)?,
+                );
+                // there is no negative zero
+                let is_neg = u.arbitrary::<bool>()? && time != (0, 0, 0, 0, 0);
+                Value::Time(is_neg, time.0, time.1, time.2, time.3, time.4)
+            }
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Column {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let column_types = (0..=u8::MAX)
+            .filter_map(|x| ColumnType::try_from(x).ok())
+            .collect::<Vec<_>>();
+        Ok(Column::new(*u.choose(&column_types)?)
+            .with_schema(u.arbitrary()?)
+            .with_table(u.arbitrary()?)
+            .with_org_table(u.arbitrary()?)
+            .with_name(u.arbitrary()?)
+            .with_org_name(u.arbitrary()?)
+            .with_character_set(u.arbitrary()?)
+            .with_column_length(u.arbitrary()?)
+            .with_flags(ColumnFlags::from_bits_truncate(u.arbitrary()?))
+            .with_decimals(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for OkPacket<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let affected_rows = u.arbitrary()?;
+        // zero is the same as no last insert id
+        let last_insert_id = u.arbitrary::<Option<u64>>()?.filter(|x| *x > 0);
+        let info: &[u8] = u.arbitrary()?;
+        let session_state_info: &[u8] = u.arbitrary()?;
+
+        let mut status_flags = StatusFlags::from_bits_truncate(u.arbitrary()?);
+        status_flags.set(
+            StatusFlags::SERVER_SESSION_STATE_CHANGED,
+            !session_state_info.is_empty(),
+        );
+
+        Ok(
+            OkPacket::new(affected_rows, last_insert_id, status_flags, u.arbitrary()?)
+                .with_info(info)
+                .with_session_state_info(session_state_info),
+        )
+    }
+}
+
+impl<'a> Arbitrary<'a> for ServerError<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // 0xFFFF marks a progress report
+        let code = u.int_in_range(0..=0xFFFE)?;
+        let message: &[u8] = u.arbitrary()?;
+        Ok(ServerError::new(code, u.arbitrary()?, message))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ProgressReport<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let stage = u.arbitrary()?;
+        let max_stage = u.arbitrary()?;
+        // progress is a 3-byte integer
+        let progress = u.int_in_range(0..=0x00FF_FFFF)?;
+        let stage_info: &[u8] = u.arbitrary()?;
+        Ok(ProgressReport::new(stage, max_stage, progress, stage_info))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ErrPacket<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        if u.arbitrary()? {
+            u.arbitrary().map(ErrPacket::Error)
+        } else {
+            u.arbitrary().map(ErrPacket::Progress)
+        }
+    }
+}
+
+impl<'a> Arbitrary<'a> for HandshakePacket<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let protocol_version = u.arbitrary()?;
+        let server_version = null_free_bytes(u)?;
+        let connection_id = u.arbitrary()?;
+        let scramble_1 = u.arbitrary()?;
+        let capabilities = CapabilityFlags::from_bits_truncate(u.arbitrary()?);
+        let plugin_auth = capabilities.contains(CapabilityFlags::CLIENT_PLUGIN_AUTH);
+
+        // scramble length is only known if CLIENT_PLUGIN_AUTH is set
+        let scramble_2 = if capabilities.contains(CapabilityFlags::CLIENT_SECURE_CONNECTION) {
+            let len = if plugin_auth {
+                u.int_in_range(13..=247)?
+            } else {
+                13
+            };
+            Some(u.bytes(len)?)
+        } else {
+            None
+        };
+        let auth_plugin_name = if plugin_auth {
+            Some(null_free_bytes(u)?)
+        } else {
+            None
+        };
+
+        Ok(HandshakePacket::new(
+            protocol_version,
+            server_version,
+            connection_id,
+            scramble_1,
+            scramble_2,
+            capabilities,
+            u.arbitrary()?,
+            StatusFlags::from_bits_truncate(u.arbitrary()?),
+            auth_plugin_name,
+        )
+        .with_mariadb_capabilities(MariadbCapabilities::from_bits_truncate(u.arbitrary()?)))
+    }
+}
+
+impl<'a> Arbitrary<'a> for StmtPacket {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(StmtPacket::new(
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for BinlogEventHeader {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // any bytes make a valid header (event type and flags are kept raw)
+        let bytes = u.bytes(BinlogEventHeader::LEN)?;
+        ParseBuf(bytes)
+            .parse(())
+            .map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        binlog::events::BinlogEventHeader,
+        constants::{CapabilityFlags, ColumnFlags, ColumnType},
+        io::ParseBuf,
+        packets::{
+            server::ResultSetWriter, Column, CommonOkPacket, ErrPacket, HandshakePacket, OkPacket,
+            OkPacketDeserializer, StmtPacket,
+        },
+        proto::{MyDeserialize, MySerialize},
+        value::{BinValue, Value, ValueDeserializer},
+    };
+
+    /// Asserts that `parse(serialize(x)) == x` for an arbitrary instance of `$ty`.
+    macro_rules! roundtrip {
+        ($ty:ident, $input:expr, $ctx:expr) => {
+            let mut u = Unstructured::new($input);
+            if let Ok(x) = $ty::arbitrary(&mut u) {
+                let mut output = Vec::new();
+                x.serialize(&mut output);
+                let parsed = $ty::deserialize($ctx, &mut ParseBuf(&output)).unwrap();
+                assert_eq!(parsed, x);
+            }
+        };
+    }
+
+    #[test]
+    fn should_generate_well_formed_instances() {
+        // fixed seed, so that failures are reproducible
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..256 {
+            let mut input = vec![0_u8; rng.gen_range(0..1024)];
+            rng.fill(&mut input[..]);
+
+            roundtrip!(Column, &input, ());
+            roundtrip!(HandshakePacket, &input, ());
+            roundtrip!(StmtPacket, &input, ());
+            roundtrip!(BinlogEventHeader, &input, ());
+            roundtrip!(ErrPacket, &input, CapabilityFlags::CLIENT_PROGRESS_OBSOLETE);
+
+            let mut u = Unstructured::new(&input);
+            if let Ok(ok) = OkPacket::arbitrary(&mut u) {
+                let mut output = Vec::new();
+                ResultSetWriter::new(CapabilityFlags::CLIENT_SESSION_TRACK)
+                    .write_ok(&ok, &mut output);
+                let parsed: OkPacketDeserializer<CommonOkPacket> = ParseBuf(&output)
+                    .parse(CapabilityFlags::CLIENT_SESSION_TRACK)
+                    .unwrap();
+                assert_eq!(parsed.into_inner(), ok);
+            }
+
+            let mut u = Unstructured::new(&input);
+            if let Ok(value) = Value::arbitrary(&mut u) {
+                let (column_type, flags) = match value {
+                    Value::NULL => continue,
+                    Value::Bytes(_) => (ColumnType::MYSQL_TYPE_BLOB, ColumnFlags::empty()),
+                    Value::Int(_) => (ColumnType::MYSQL_TYPE_LONGLONG, ColumnFlags::empty()),
+                    Value::UInt(_) => (ColumnType::MYSQL_TYPE_LONGLONG, ColumnFlags::UNSIGNED_FLAG),
+                    Value::Float(_) => (ColumnType::MYSQL_TYPE_FLOAT, ColumnFlags::empty()),
+                    Value::Double(_) => (ColumnType::MYSQL_TYPE_DOUBLE, ColumnFlags::empty()),
+                    Value::Date(..) => (ColumnType::MYSQL_TYPE_DATETIME, ColumnFlags::empty()),
+                    Value::Time(..) => (ColumnType::MYSQL_TYPE_TIME, ColumnFlags::empty()),
+                };
+                let mut output = Vec::new();
+                value.serialize(&mut output);
+                let parsed: ValueDeserializer<BinValue> =
+                    ParseBuf(&output).parse((column_type, flags)).unwrap();
+                match (parsed.0, value) {
+                    // unsigned values that fit into `i64` are parsed as `Value::Int`
+                    (Value::Int(x), Value::UInt(y)) => assert_eq!(x as u64, y),
+                    (parsed, value) => assert_eq!(parsed, value),
+                }
+            }
+        }
+    }
+}
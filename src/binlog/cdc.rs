@@ -0,0 +1,670 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Change data capture (CDC) representation of binlog rows events.
+//!
+//! [`RowChange`] could be rendered as a Debezium-style or a Maxwell-style JSON envelope,
+//! so that it is consumable by existing Kafka consumers. Note, that:
+//!
+//! *   `DECIMAL` values, temporal values and strings are rendered as JSON strings;
+//! *   non-UTF-8 byte strings are rendered as base64-encoded JSON strings;
+//! *   partial JSON updates (see `binlog_row_value_options`) can't be rendered.
+
+use std::{fmt::Write as _, io};
+
+use bitvec::slice::BitSlice;
+use serde_json::{json, Map, Value as Json};
+
+use crate::{
+    constants::{ColumnFlags, ColumnType},
+    packets::Column,
+    value::{
+        convert::temporal::{MysqlDate, MysqlDateTime, MysqlTime},
+        json::limits::JsonLimits,
+        Value,
+    },
+};
+
+use super::{
+    events::{GtidEvent, OptionalMetadataField, RowsEventData, TableMapEvent},
+    row::BinlogRow,
+    value::BinlogValue,
+};
+
+/// Kind of a row change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl ChangeKind {
+    /// Returns Debezium's `op` value.
+    pub fn debezium_op(self) -> &'static str {
+        match self {
+            ChangeKind::Insert => "c",
+            ChangeKind::Update => "u",
+            ChangeKind::Delete => "d",
+        }
+    }
+
+    /// Returns Maxwell's `type` value.
+    pub fn maxwell_type(self) -> &'static str {
+        match self {
+            ChangeKind::Insert => "insert",
+            ChangeKind::Update => "update",
+            ChangeKind::Delete => "delete",
+        }
+    }
+}
+
+/// Origin of a change within a binlog.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ChangeSource {
+    /// Server id of the originating server.
+    pub server_id: u32,
+    /// Timestamp of the event (seconds since the Unix epoch).
+    pub timestamp: u32,
+    /// Binlog file name.
+    pub log_file: String,
+    /// Position of the event within the binlog file.
+    pub log_pos: u64,
+    /// GTID of the transaction (`<uuid>:<gno>`), if any.
+    pub gtid: Option<String>,
+    /// XID of the transaction, if any.
+    pub xid: Option<u64>,
+}
+
+impl ChangeSource {
+    /// Creates a new source for the given binlog position.
+    pub fn new(server_id: u32, timestamp: u32, log_file: impl Into<String>, log_pos: u64) -> Self {
+        Self {
+            server_id,
+            timestamp,
+            log_file: log_file.into(),
+            log_pos,
+            gtid: None,
+            xid: None,
+        }
+    }
+
+    /// Defines the GTID using the given GTID event.
+    pub fn with_gtid(mut self, gtid: &GtidEvent) -> Self {
+        let mut output = String::with_capacity(36 + 21);
+        for (i, byte) in gtid.sid().iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                output.push('-');
+            }
+            let _ = write!(output, "{:02x}", byte);
+        }
+        let _ = write!(output, ":{}", gtid.gno());
+        self.gtid = Some(output);
+        self
+    }
+
+    /// Defines the XID of the transaction.
+    pub fn with_xid(mut self, xid: u64) -> Self {
+        self.xid = Some(xid);
+        self
+    }
+}
+
+/// Options of the Debezium-style output.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DebeziumOptions {
+    /// Logical name of the server (the `name` field of the source block).
+    pub server_name: String,
+    /// Whether to emit the `schema`/`payload` form (i.e. `schemas.enable=true`).
+    pub include_schema: bool,
+}
+
+impl Default for DebeziumOptions {
+    fn default() -> Self {
+        Self {
+            server_name: "mysql".into(),
+            include_schema: true,
+        }
+    }
+}
+
+/// Options of the Maxwell-style output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaxwellOptions {
+    /// Whether to emit the `position` field (`output_binlog_position`).
+    pub binlog_position: bool,
+    /// Whether to emit the `server_id` field (`output_server_id`).
+    pub server_id: bool,
+    /// Whether to emit the `gtid` field (`output_gtid_position`).
+    pub gtid: bool,
+}
+
+/// Change of a single row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowChange {
+    /// Kind of the change.
+    pub kind: ChangeKind,
+    /// Database name.
+    pub database: String,
+    /// Table name.
+    pub table: String,
+    /// Index of the row within the rows event.
+    pub row_index: usize,
+    /// Before image (`None` for inserts).
+    pub before: Option<BinlogRow>,
+    /// After image (`None` for deletes).
+    pub after: Option<BinlogRow>,
+}
+
+impl RowChange {
+    /// Decodes changes of the given rows event.
+    ///
+    /// Columns are named after the `COLUMN_NAME` optional metadata of the table map event
+    /// (see `binlog_row_metadata=FULL`), or `@<offset>` if it's not available.
+    pub fn from_rows_event<'a>(
+        event: &'a RowsEventData<'a>,
+        tme: &'a TableMapEvent<'a>,
+    ) -> io::Result<Vec<RowChange>> {
+        let kind = match event {
+            RowsEventData::WriteRowsEventV1(_) | RowsEventData::WriteRowsEvent(_) => {
+                ChangeKind::Insert
+            }
+            RowsEventData::UpdateRowsEventV1(_)
+            | RowsEventData::UpdateRowsEvent(_)
+            | RowsEventData::PartialUpdateRowsEvent(_) => ChangeKind::Update,
+            RowsEventData::DeleteRowsEventV1(_) | RowsEventData::DeleteRowsEvent(_) => {
+                ChangeKind::Delete
+            }
+        };
+
+        let names = column_names(tme)?;
+        let num_columns = event.num_columns() as usize;
+        let offsets = |image: Option<&BitSlice<u8>>| -> Vec<usize> {
+            image
+                .map(|cols| {
+                    (0..num_columns)
+                        .filter(|i| cols.get(*i).as_deref().copied().unwrap_or(false))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let before_offsets = offsets(event.columns_before_image());
+        let after_offsets = offsets(event.columns_after_image());
+
+        let mut changes = Vec::new();
+        for (row_index, row) in event.rows(tme).enumerate() {
+            let (before, after) = row?;
+            changes.push(RowChange {
+                kind,
+                database: tme.database_name().into_owned(),
+                table: tme.table_name().into_owned(),
+                row_index,
+                before: before.map(|row| rename_columns(row, &before_offsets, &names)),
+                after: after.map(|row| rename_columns(row, &after_offsets, &names)),
+            });
+        }
+
+        Ok(changes)
+    }
+
+    /// Renders this change as a Debezium-style JSON envelope.
+    pub fn to_debezium_json(
+        &self,
+        source: &ChangeSource,
+        options: &DebeziumOptions,
+    ) -> io::Result<Json> {
+        let ts_ms = u64::from(source.timestamp) * 1000;
+        let payload = json!({
+            "before": self.before.as_ref().map(row_to_json).transpose()?,
+            "after": self.after.as_ref().map(row_to_json).transpose()?,
+            "source": {
+                "version": env!("CARGO_PKG_VERSION"),
+                "connector": "mysql",
+                "name": options.server_name,
+                "ts_ms": ts_ms,
+                "snapshot": "false",
+                "db": self.database,
+                "sequence": null,
+                "table": self.table,
+                "server_id": source.server_id,
+                "gtid": source.gtid,
+                "file": source.log_file,
+                "pos": source.log_pos,
+                "row": self.row_index,
+                "thread": null,
+                "query": null,
+            },
+            "op": self.kind.debezium_op(),
+            "ts_ms": ts_ms,
+            "transaction": null,
+        });
+
+        if options.include_schema {
+            Ok(json!({
+                "schema": self.debezium_schema(&options.server_name),
+                "payload": payload,
+            }))
+        } else {
+            Ok(payload)
+        }
+    }
+
+    /// Renders this change as a Maxwell-style JSON object.
+    ///
+    /// For updates the `old` field contains previous values of changed columns.
+    pub fn to_maxwell_json(
+        &self,
+        source: &ChangeSource,
+        options: &MaxwellOptions,
+    ) -> io::Result<Json> {
+        let mut output = Map::new();
+        output.insert("database".into(), self.database.clone().into());
+        output.insert("table".into(), self.table.clone().into());
+        output.insert("type".into(), self.kind.maxwell_type().into());
+        output.insert("ts".into(), source.timestamp.into());
+        if let Some(xid) = source.xid {
+            output.insert("xid".into(), xid.into());
+        }
+        if options.binlog_position {
+            output.insert(
+                "position".into(),
+                format!("{}:{}", source.log_file, source.log_pos).into(),
+            );
+        }
+        if options.server_id {
+            output.insert("server_id".into(), source.server_id.into());
+        }
+        if options.gtid {
+            output.insert("gtid".into(), source.gtid.clone().into());
+        }
+
+        let data = match self.kind {
+            ChangeKind::Delete => self.before.as_ref(),
+            ChangeKind::Insert | ChangeKind::Update => self.after.as_ref(),
+        };
+        let data = data.map(row_to_json).transpose()?.unwrap_or_default();
+
+        if let (ChangeKind::Update, Some(before)) = (self.kind, self.before.as_ref()) {
+            let mut old = Map::new();
+            for (name, value) in row_to_json(before)? {
+                if data.get(&name) != Some(&value) {
+                    old.insert(name, value);
+                }
+            }
+            output.insert("data".into(), data.into());
+            output.insert("old".into(), old.into());
+        } else {
+            output.insert("data".into(), data.into());
+        }
+
+        Ok(output.into())
+    }
+
+    fn debezium_schema(&self, server_name: &str) -> Json {
+        let prefix = format!("{}.{}.{}", server_name, self.database, self.table);
+        let image = self.after.as_ref().or_else(|| self.before.as_ref());
+        let fields = image
+            .map(|row| {
+                row.columns_ref()
+                    .iter()
+                    .map(|column| {
+                        json!({
+                            "type": debezium_type(column),
+                            "optional": true,
+                            "field": column.name_str(),
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let value_schema = |field: &str| {
+            json!({
+                "type": "struct",
+                "fields": fields,
+                "optional": true,
+                "name": format!("{}.Value", prefix),
+                "field": field,
+            })
+        };
+
+        json!({
+            "type": "struct",
+            "fields": [
+                value_schema("before"),
+                value_schema("after"),
+                {
+                    "type": "struct",
+                    "fields": [
+                        schema_field("string", false, "version"),
+                        schema_field("string", false, "connector"),
+                        schema_field("string", false, "name"),
+                        schema_field("int64", false, "ts_ms"),
+                        schema_field("string", true, "snapshot"),
+                        schema_field("string", false, "db"),
+                        schema_field("string", true, "sequence"),
+                        schema_field("string", true, "table"),
+                        schema_field("int64", false, "server_id"),
+                        schema_field("string", true, "gtid"),
+                        schema_field("string", false, "file"),
+                        schema_field("int64", false, "pos"),
+                        schema_field("int32", false, "row"),
+                        schema_field("int64", true, "thread"),
+                        schema_field("string", true, "query"),
+                    ],
+                    "optional": false,
+                    "name": "io.debezium.connector.mysql.Source",
+                    "field": "source",
+                },
+                schema_field("string", false, "op"),
+                schema_field("int64", true, "ts_ms"),
+                {
+                    "type": "struct",
+                    "fields": [
+                        schema_field("string", false, "id"),
+                        schema_field("int64", false, "total_order"),
+                        schema_field("int64", false, "data_collection_order"),
+                    ],
+                    "optional": true,
+                    "field": "transaction",
+                },
+            ],
+            "optional": false,
+            "name": format!("{}.Envelope", prefix),
+        })
+    }
+}
+
+fn schema_field(ty: &str, optional: bool, field: &str) -> Json {
+    json!({ "type": ty, "optional": optional, "field": field })
+}
+
+/// Returns column names given by the optional metadata of a table map event.
+fn column_names<'a>(tme: &'a TableMapEvent<'a>) -> io::Result<Vec<String>> {
+    for field in tme.iter_optional_meta() {
+        if let OptionalMetadataField::ColumnName(names) = field? {
+            return names
+                .iter_names()
+                .map(|name| name.map(|name| name.name().into_owned()))
+                .collect();
+        }
+    }
+    Ok(Vec::new())
+}
+
+fn rename_columns(row: BinlogRow, offsets: &[usize], names: &[String]) -> BinlogRow {
+    let columns = row
+        .columns_ref()
+        .iter()
+        .zip(offsets)
+        .map(|(column, offset)| match names.get(*offset) {
+            Some(name) => column.clone().with_name(name.as_bytes()),
+            None => column.clone(),
+        })
+        .collect::<Vec<_>>();
+    let values = row.unwrap().into_iter().map(Some).collect();
+    BinlogRow::new(values, columns.into())
+}
+
+fn row_to_json(row: &BinlogRow) -> io::Result<Map<String, Json>> {
+    let mut output = Map::new();
+    for (i, column) in row.columns_ref().iter().enumerate() {
+        let value = match row.as_ref(i) {
+            Some(value) => binlog_value_to_json(value, column)?,
+            None => Json::Null,
+        };
+        output.insert(column.name_str().into_owned(), value);
+    }
+    Ok(output)
+}
+
+fn binlog_value_to_json(value: &BinlogValue<'_>, column: &Column) -> io::Result<Json> {
+    match value {
+        BinlogValue::Value(value) => Ok(value_to_json(value, column.column_type())),
+        BinlogValue::Jsonb(jsonb) => jsonb
+            .to_json_limited(&JsonLimits::default())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        BinlogValue::JsonDiff(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "partial JSON update can't be rendered as JSON",
+        )),
+    }
+}
+
+fn value_to_json(value: &Value, column_type: ColumnType) -> Json {
+    match *value {
+        Value::NULL => Json::Null,
+        Value::Int(x) => x.into(),
+        Value::UInt(x) => x.into(),
+        Value::Float(x) => serde_json::Number::from_f64(f64::from(x))
+            .map(Json::Number)
+            .unwrap_or(Json::Null),
+        Value::Double(x) => serde_json::Number::from_f64(x)
+            .map(Json::Number)
+            .unwrap_or(Json::Null),
+        Value::Bytes(ref bytes) => match std::str::from_utf8(bytes) {
+            Ok(string) => string.into(),
+            Err(_) => base64::encode(bytes).into(),
+        },
+        Value::Date(year, month, day, _, _, _, _) if column_type == ColumnType::MYSQL_TYPE_DATE => {
+            MysqlDate::new(year, month, day).to_string().into()
+        }
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            MysqlDateTime::new(year, month, day, hour, minute, second, micros)
+                .to_string()
+                .into()
+        }
+        Value::Time(is_neg, days, hours, minutes, seconds, micros) => {
+            MysqlTime::new(is_neg, days, hours, minutes, seconds, micros)
+                .to_string()
+                .into()
+        }
+    }
+}
+
+/// Returns Kafka Connect schema type of a column, given that values are rendered
+/// by this module.
+fn debezium_type(column: &Column) -> &'static str {
+    let is_unsigned = column.flags().contains(ColumnFlags::UNSIGNED_FLAG);
+    match column.column_type() {
+        ColumnType::MYSQL_TYPE_TINY | ColumnType::MYSQL_TYPE_SHORT if !is_unsigned => "int16",
+        ColumnType::MYSQL_TYPE_TINY | ColumnType::MYSQL_TYPE_SHORT => "int32",
+        ColumnType::MYSQL_TYPE_INT24 | ColumnType::MYSQL_TYPE_YEAR => "int32",
+        ColumnType::MYSQL_TYPE_LONG if !is_unsigned => "int32",
+        ColumnType::MYSQL_TYPE_LONG | ColumnType::MYSQL_TYPE_LONGLONG => "int64",
+        ColumnType::MYSQL_TYPE_FLOAT => "float32",
+        ColumnType::MYSQL_TYPE_DOUBLE => "float64",
+        _ => "string",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::binlog::{
+        consts::{BinlogVersion, EventType},
+        events::EventData,
+        BinlogFile,
+    };
+
+    fn row(values: Vec<Value>) -> BinlogRow {
+        let columns: Arc<[Column]> = Arc::from(vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"name"),
+            Column::new(ColumnType::MYSQL_TYPE_DATETIME).with_name(b"updated_at"),
+        ]);
+        BinlogRow::new(
+            values
+                .into_iter()
+                .map(|value| Some(BinlogValue::Value(value)))
+                .collect(),
+            columns,
+        )
+    }
+
+    fn change() -> RowChange {
+        RowChange {
+            kind: ChangeKind::Update,
+            database: "test".into(),
+            table: "users".into(),
+            row_index: 0,
+            before: Some(row(vec![
+                Value::Int(1),
+                Value::Bytes(b"foo".to_vec()),
+                Value::Date(2021, 1, 2, 3, 4, 5, 0),
+            ])),
+            after: Some(row(vec![
+                Value::Int(1),
+                Value::Bytes(b"bar".to_vec()),
+                Value::Date(2021, 1, 2, 3, 4, 6, 500),
+            ])),
+        }
+    }
+
+    fn source() -> ChangeSource {
+        ChangeSource::new(1, 1_600_000_000, "binlog.000001", 154)
+            .with_gtid(&GtidEvent::new([0xab; 16], 7))
+            .with_xid(42)
+    }
+
+    #[test]
+    fn should_render_debezium_envelope() {
+        let envelope = change()
+            .to_debezium_json(
+                &source(),
+                &DebeziumOptions {
+                    server_name: "dbserver1".into(),
+                    include_schema: true,
+                },
+            )
+            .unwrap();
+
+        let payload = &envelope["payload"];
+        assert_eq!(payload["op"], "u");
+        assert_eq!(payload["ts_ms"], 1_600_000_000_000_u64);
+        assert_eq!(
+            payload["before"],
+            json!({ "id": 1, "name": "foo", "updated_at": "2021-01-02 03:04:05" })
+        );
+        assert_eq!(
+            payload["after"],
+            json!({ "id": 1, "name": "bar", "updated_at": "2021-01-02 03:04:06.000500" })
+        );
+        assert_eq!(payload["source"]["name"], "dbserver1");
+        assert_eq!(payload["source"]["db"], "test");
+        assert_eq!(payload["source"]["table"], "users");
+        assert_eq!(payload["source"]["file"], "binlog.000001");
+        assert_eq!(payload["source"]["pos"], 154);
+        assert_eq!(
+            payload["source"]["gtid"],
+            "abababab-abab-abab-abab-abababababab:7"
+        );
+
+        let schema = &envelope["schema"];
+        assert_eq!(schema["name"], "dbserver1.test.users.Envelope");
+        assert_eq!(schema["fields"][0]["field"], "before");
+        assert_eq!(schema["fields"][0]["fields"][0]["type"], "int32");
+        assert_eq!(schema["fields"][0]["fields"][1]["type"], "string");
+
+        let payload_only = change()
+            .to_debezium_json(
+                &source(),
+                &DebeziumOptions {
+                    include_schema: false,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(payload_only["after"], payload["after"]);
+        assert!(payload_only.get("schema").is_none());
+    }
+
+    #[test]
+    fn should_render_maxwell_object() {
+        let output = change()
+            .to_maxwell_json(
+                &source(),
+                &MaxwellOptions {
+                    binlog_position: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            output,
+            json!({
+                "database": "test",
+                "table": "users",
+                "type": "update",
+                "ts": 1_600_000_000_u32,
+                "xid": 42,
+                "position": "binlog.000001:154",
+                "data": { "id": 1, "name": "bar", "updated_at": "2021-01-02 03:04:06.000500" },
+                "old": { "name": "foo", "updated_at": "2021-01-02 03:04:05" },
+            })
+        );
+
+        let mut delete = change();
+        delete.kind = ChangeKind::Delete;
+        delete.after = None;
+        let output = delete
+            .to_maxwell_json(&ChangeSource::default(), &Default::default())
+            .unwrap();
+        assert_eq!(output["type"], "delete");
+        assert_eq!(output["data"]["name"], "foo");
+        assert!(output.get("old").is_none());
+    }
+
+    #[test]
+    fn should_decode_changes_of_rows_event() -> io::Result<()> {
+        let data = std::fs::read("./test-data/binlogs/write-full-row.binlog")?;
+        let mut file = BinlogFile::new(BinlogVersion::Version4, &data[..])?;
+
+        let mut kinds = Vec::new();
+        while let Some(event) = file.next() {
+            let event = event?;
+            if let Some(EventData::RowsEvent(rows_event)) = event.read_data()? {
+                let expected_kind = match event.header().event_type() {
+                    Ok(EventType::WRITE_ROWS_EVENT_V1) | Ok(EventType::WRITE_ROWS_EVENT) => {
+                        ChangeKind::Insert
+                    }
+                    Ok(EventType::DELETE_ROWS_EVENT_V1) | Ok(EventType::DELETE_ROWS_EVENT) => {
+                        ChangeKind::Delete
+                    }
+                    _ => ChangeKind::Update,
+                };
+                let tme = file.reader().get_tme(rows_event.table_id()).unwrap();
+                for change in RowChange::from_rows_event(&rows_event, tme)? {
+                    assert_eq!(change.kind, expected_kind);
+                    match expected_kind {
+                        ChangeKind::Insert => {
+                            assert!(change.before.is_none() && change.after.is_some())
+                        }
+                        ChangeKind::Delete => {
+                            assert!(change.before.is_some() && change.after.is_none())
+                        }
+                        ChangeKind::Update => {
+                            assert!(change.before.is_some() && change.after.is_some())
+                        }
+                    }
+                    change
+                        .to_debezium_json(&ChangeSource::default(), &Default::default())
+                        .unwrap();
+                    kinds.push(change.kind);
+                }
+            }
+        }
+
+        assert!(kinds.contains(&ChangeKind::Insert));
+        assert!(kinds.contains(&ChangeKind::Delete));
+
+        Ok(())
+    }
+}
@@ -118,6 +118,11 @@ pub enum EventType {
     PARTIAL_UPDATE_ROWS_EVENT = 0x27,
     /// Total number of known events.
     ENUM_END_EVENT,
+    /// MariaDB GTID event, that starts an event group (a transaction or a standalone statement).
+    MARIADB_GTID_EVENT = 0xa2,
+    /// MariaDB GTID list event, that follows the format description event at the start
+    /// of a binlog and contains the last GTID of every replication domain.
+    MARIADB_GTID_LIST_EVENT = 0xa3,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
@@ -176,6 +181,8 @@ impl TryFrom<u8> for EventType {
             0x25 => Ok(Self::VIEW_CHANGE_EVENT),
             0x26 => Ok(Self::XA_PREPARE_LOG_EVENT),
             0x27 => Ok(Self::PARTIAL_UPDATE_ROWS_EVENT),
+            0xa2 => Ok(Self::MARIADB_GTID_EVENT),
+            0xa3 => Ok(Self::MARIADB_GTID_LIST_EVENT),
             x => Err(UnknownEventType(x)),
         }
     }
@@ -706,6 +713,33 @@ my_bitflags! {
     }
 }
 
+my_bitflags! {
+    MariadbGtidFlags,
+    #[error("Unknown flags in the raw value of MariadbGtidFlags (raw={:b})", _0)]
+    UnknownMariadbGtidFlags,
+    u8,
+
+    /// MariaDB GTID event flags.
+    pub struct MariadbGtidFlags: u8 {
+        /// Event group is a standalone statement (i.e. there is no `BEGIN` query).
+        const FL_STANDALONE = 0x01;
+        /// Event group is a part of a group commit (commit id is present).
+        const FL_GROUP_COMMIT_ID = 0x02;
+        /// Event group is transactional (can be safely rolled back).
+        const FL_TRANSACTIONAL = 0x04;
+        /// Event group can be applied in parallel.
+        const FL_ALLOW_PARALLEL = 0x08;
+        /// Event group waited for a row lock held by another transaction on the master.
+        const FL_WAITED = 0x10;
+        /// Event group contains DDL.
+        const FL_DDL = 0x20;
+        /// Event group is a prepared XA transaction (XID is present).
+        const FL_PREPARED_XA = 0x40;
+        /// Event group is a completed XA transaction (XID is present).
+        const FL_COMPLETED_XA = 0x80;
+    }
+}
+
 /// Group number of a Gtid event.
 ///
 /// Should be between `MIN_GNO` and `MAX_GNO` for GtidEvent and `0` for AnonymousGtidEvent.
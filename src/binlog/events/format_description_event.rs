@@ -123,6 +123,10 @@ impl<'a> FormatDescriptionEvent<'a> {
     pub const VIEW_CHANGE_HEADER_LEN: usize = 52;
     /// Length of a xa prepare event post-header.
     pub const XA_PREPARE_HEADER_LEN: usize = 0;
+    /// Length of a MariaDB gtid event post-header.
+    pub const MARIADB_GTID_HEADER_LEN: usize = 19;
+    /// Length of a MariaDB gtid list event post-header.
+    pub const MARIADB_GTID_LIST_HEADER_LEN: usize = 4;
 
     /// Creates new instance.
     pub fn new(binlog_version: BinlogVersion) -> Self {
@@ -272,6 +276,8 @@ impl<'a> FormatDescriptionEvent<'a> {
                 EventType::XA_PREPARE_LOG_EVENT => Self::XA_PREPARE_HEADER_LEN,
                 EventType::PARTIAL_UPDATE_ROWS_EVENT => Self::ROWS_HEADER_LEN_V2,
                 EventType::ENUM_END_EVENT => 0,
+                EventType::MARIADB_GTID_EVENT => Self::MARIADB_GTID_HEADER_LEN,
+                EventType::MARIADB_GTID_LIST_EVENT => Self::MARIADB_GTID_LIST_HEADER_LEN,
             } as u8)
     }
 
@@ -0,0 +1,188 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, cmp::min, io};
+
+use saturating::Saturating as S;
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType, MariadbGtidFlags},
+        mariadb_gtid::MariadbGtid,
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    misc::raw::{bytes::EofBytes, int::*, RawBytes, RawFlags, RawInt, Skip},
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::BinlogEventHeader;
+
+/// MariaDB GTID event.
+///
+/// Starts an event group. Note, that the server id part of the GTID is taken
+/// from the event header (see [`MariadbGtidEvent::gtid`]).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MariadbGtidEvent<'a> {
+    /// Sequence number of the event group within the replication domain.
+    sequence_number: RawInt<LeU64>,
+    /// Replication domain id.
+    domain_id: RawInt<LeU32>,
+    /// Raw flags value.
+    flags: RawFlags<MariadbGtidFlags, u8>,
+    /// Group commit id. Defined iff `FL_GROUP_COMMIT_ID` flag is set.
+    commit_id: Option<RawInt<LeU64>>,
+    /// Data that follows the post-header (XID of an XA transaction and extra flags
+    /// of MariaDB 10.5+). Kept raw.
+    extra: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> MariadbGtidEvent<'a> {
+    /// Creates a new instance.
+    pub fn new(domain_id: u32, sequence_number: u64) -> Self {
+        Self {
+            sequence_number: RawInt::new(sequence_number),
+            domain_id: RawInt::new(domain_id),
+            flags: Default::default(),
+            commit_id: None,
+            extra: Default::default(),
+        }
+    }
+
+    /// Defines the `flags` value (`FL_GROUP_COMMIT_ID` is defined by the `commit_id` value).
+    pub fn with_flags(mut self, mut flags: MariadbGtidFlags) -> Self {
+        flags.set(
+            MariadbGtidFlags::FL_GROUP_COMMIT_ID,
+            self.commit_id.is_some(),
+        );
+        self.flags = RawFlags::new(flags.bits());
+        self
+    }
+
+    /// Defines the `commit_id` value (sets or removes the `FL_GROUP_COMMIT_ID` flag).
+    pub fn with_commit_id(mut self, commit_id: Option<u64>) -> Self {
+        self.commit_id = commit_id.map(RawInt::new);
+        let mut flags = self.flags.0;
+        if commit_id.is_some() {
+            flags |= MariadbGtidFlags::FL_GROUP_COMMIT_ID.bits();
+        } else {
+            flags &= !MariadbGtidFlags::FL_GROUP_COMMIT_ID.bits();
+        }
+        self.flags = RawFlags::new(flags);
+        self
+    }
+
+    /// Defines the `extra` value.
+    pub fn with_extra(mut self, extra: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.extra = RawBytes::new(extra);
+        self
+    }
+
+    /// Returns the `sequence_number` value.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number.0
+    }
+
+    /// Returns the `domain_id` value.
+    pub fn domain_id(&self) -> u32 {
+        self.domain_id.0
+    }
+
+    /// Returns the raw `flags` value.
+    pub fn flags_raw(&self) -> u8 {
+        self.flags.0
+    }
+
+    /// Returns the `flags` value. Unknown bits will be truncated.
+    pub fn flags(&self) -> MariadbGtidFlags {
+        self.flags.get()
+    }
+
+    /// Returns the `commit_id` value, if defined.
+    pub fn commit_id(&self) -> Option<u64> {
+        self.commit_id.map(|x| x.0)
+    }
+
+    /// Returns the raw data, that follows the post-header.
+    pub fn extra(&'a self) -> &'a [u8] {
+        self.extra.as_bytes()
+    }
+
+    /// Returns the GTID of this event group given the server id from the event header.
+    pub fn gtid(&self, server_id: u32) -> MariadbGtid {
+        MariadbGtid::new(self.domain_id(), server_id, self.sequence_number())
+    }
+
+    pub fn into_owned(self) -> MariadbGtidEvent<'static> {
+        MariadbGtidEvent {
+            sequence_number: self.sequence_number,
+            domain_id: self.domain_id,
+            flags: self.flags,
+            commit_id: self.commit_id,
+            extra: self.extra.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for MariadbGtidEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let mut sbuf: ParseBuf = buf.parse(13)?;
+        let sequence_number = sbuf.parse_unchecked(())?;
+        let domain_id = sbuf.parse_unchecked(())?;
+        let flags: RawFlags<MariadbGtidFlags, u8> = sbuf.parse_unchecked(())?;
+
+        let commit_id = if flags.get().contains(MariadbGtidFlags::FL_GROUP_COMMIT_ID) {
+            Some(buf.parse(())?)
+        } else {
+            buf.parse::<Skip<6>>(())?;
+            None
+        };
+
+        Ok(Self {
+            sequence_number,
+            domain_id,
+            flags,
+            commit_id,
+            extra: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for MariadbGtidEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.sequence_number.serialize(&mut *buf);
+        self.domain_id.serialize(&mut *buf);
+        self.flags.serialize(&mut *buf);
+        match self.commit_id {
+            Some(commit_id) => commit_id.serialize(&mut *buf),
+            None => Skip::<6>::default().serialize(&mut *buf),
+        }
+        self.extra.serialize(buf);
+    }
+}
+
+impl<'a> BinlogStruct<'a> for MariadbGtidEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let mut len = S(0);
+
+        len += S(8); // sequence_number
+        len += S(4); // domain_id
+        len += S(1); // flags
+        len += S(if self.commit_id.is_some() { 8 } else { 6 }); // commit_id
+        len += S(self.extra.len());
+
+        min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
+
+impl<'a> BinlogEvent<'a> for MariadbGtidEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::MARIADB_GTID_EVENT;
+}
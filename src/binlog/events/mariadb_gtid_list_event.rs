@@ -0,0 +1,142 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, cmp::min, io};
+
+use bytes::BufMut;
+use saturating::Saturating as S;
+
+use crate::{
+    binlog::{
+        consts::{BinlogVersion, EventType},
+        mariadb_gtid::{MariadbGtid, MariadbGtidPos},
+        BinlogCtx, BinlogEvent, BinlogStruct,
+    },
+    io::ParseBuf,
+    misc::raw::{bytes::EofBytes, int::*, RawBytes, RawInt},
+    proto::{MyDeserialize, MySerialize},
+};
+
+use super::BinlogEventHeader;
+
+/// MariaDB GTID list event.
+///
+/// Logged at the start of every binlog (after the format description event), it contains
+/// the last GTID of every replication domain, i.e. the binlog state at the start of the file.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MariadbGtidListEvent<'a> {
+    /// Flags (the upper 4 bits of the GTID count).
+    flags: u8,
+    gtids: Vec<MariadbGtid>,
+    /// Data that follows the list (servers may pad the event). Kept raw.
+    extra: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> MariadbGtidListEvent<'a> {
+    /// Maximum number of GTIDs in the list (the count is a 28-bit value).
+    pub const MAX_GTIDS: usize = 0x0FFF_FFFF;
+
+    /// Creates a new instance.
+    pub fn new(gtids: Vec<MariadbGtid>) -> Self {
+        Self {
+            flags: 0,
+            gtids,
+            extra: Default::default(),
+        }
+    }
+
+    /// Defines the `flags` value (only the lower 4 bits are stored).
+    pub fn with_flags(mut self, flags: u8) -> Self {
+        self.flags = flags & 0x0F;
+        self
+    }
+
+    /// Defines the `extra` value.
+    pub fn with_extra(mut self, extra: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.extra = RawBytes::new(extra);
+        self
+    }
+
+    /// Returns the `flags` value.
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Returns GTIDs of the list.
+    pub fn gtids(&self) -> &[MariadbGtid] {
+        &self.gtids
+    }
+
+    /// Returns the replication position, described by this list.
+    pub fn pos(&self) -> MariadbGtidPos {
+        self.gtids.iter().copied().collect()
+    }
+
+    /// Returns data that follows the list.
+    pub fn extra(&'a self) -> &'a [u8] {
+        self.extra.as_bytes()
+    }
+
+    pub fn into_owned(self) -> MariadbGtidListEvent<'static> {
+        MariadbGtidListEvent {
+            flags: self.flags,
+            gtids: self.gtids,
+            extra: self.extra.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for MariadbGtidListEvent<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogCtx<'de>;
+
+    fn deserialize(_ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let count: RawInt<LeU32> = buf.parse(())?;
+        let flags = (count.0 >> 28) as u8;
+        let count = (count.0 & Self::MAX_GTIDS as u32) as usize;
+
+        let mut sbuf: ParseBuf = buf.parse(count.saturating_mul(MariadbGtid::LEN))?;
+        let mut gtids = Vec::with_capacity(count);
+        for _ in 0..count {
+            gtids.push(sbuf.parse_unchecked(())?);
+        }
+
+        Ok(Self {
+            flags,
+            gtids,
+            extra: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for MariadbGtidListEvent<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        let count = min(self.gtids.len(), Self::MAX_GTIDS);
+        buf.put_u32_le(count as u32 | u32::from(self.flags) << 28);
+        for gtid in &self.gtids[..count] {
+            gtid.serialize(&mut *buf);
+        }
+        self.extra.serialize(buf);
+    }
+}
+
+impl<'a> BinlogStruct<'a> for MariadbGtidListEvent<'a> {
+    fn len(&self, _version: BinlogVersion) -> usize {
+        let mut len = S(0);
+
+        len += S(4); // count and flags
+        len += S(min(self.gtids.len(), Self::MAX_GTIDS).saturating_mul(MariadbGtid::LEN));
+        len += S(self.extra.len());
+
+        min(len.0, u32::MAX as usize - BinlogEventHeader::LEN)
+    }
+}
+
+impl<'a> BinlogEvent<'a> for MariadbGtidListEvent<'a> {
+    const EVENT_TYPE: EventType = EventType::MARIADB_GTID_LIST_EVENT;
+}
@@ -27,6 +27,8 @@ pub use self::{
     gtid_event::GtidEvent,
     incident_event::IncidentEvent,
     intvar_event::IntvarEvent,
+    mariadb_gtid_event::MariadbGtidEvent,
+    mariadb_gtid_list_event::MariadbGtidListEvent,
     partial_update_rows_event::PartialUpdateRowsEvent,
     query_event::{QueryEvent, StatusVar, StatusVarVal, StatusVars, StatusVarsIterator},
     rand_event::RandEvent,
@@ -68,6 +70,8 @@ mod format_description_event;
 mod gtid_event;
 mod incident_event;
 mod intvar_event;
+mod mariadb_gtid_event;
+mod mariadb_gtid_list_event;
 mod partial_update_rows_event;
 mod query_event;
 mod rand_event;
@@ -324,6 +328,8 @@ impl Event {
             PARTIAL_UPDATE_ROWS_EVENT => {
                 EventData::RowsEvent(RowsEventData::PartialUpdateRowsEvent(self.read_event()?))
             }
+            MARIADB_GTID_EVENT => EventData::MariadbGtidEvent(self.read_event()?),
+            MARIADB_GTID_LIST_EVENT => EventData::MariadbGtidListEvent(self.read_event()?),
         };
 
         Ok(Some(event_data))
@@ -591,6 +597,8 @@ pub enum EventData<'a> {
     /// Not yet implemented.
     XaPrepareLogEvent(Cow<'a, [u8]>),
     RowsEvent(RowsEventData<'a>),
+    MariadbGtidEvent(MariadbGtidEvent<'a>),
+    MariadbGtidListEvent(MariadbGtidListEvent<'a>),
 }
 
 impl<'a> EventData<'a> {
@@ -642,6 +650,8 @@ impl<'a> EventData<'a> {
                 EventData::XaPrepareLogEvent(Cow::Owned(ev.into_owned()))
             }
             Self::RowsEvent(ev) => EventData::RowsEvent(ev.into_owned()),
+            Self::MariadbGtidEvent(ev) => EventData::MariadbGtidEvent(ev.into_owned()),
+            Self::MariadbGtidListEvent(ev) => EventData::MariadbGtidListEvent(ev.into_owned()),
         }
     }
 }
@@ -683,6 +693,8 @@ impl MySerialize for EventData<'_> {
             EventData::ViewChangeEvent(ev) => buf.put_slice(&*ev),
             EventData::XaPrepareLogEvent(ev) => buf.put_slice(&*ev),
             EventData::RowsEvent(ev) => ev.serialize(buf),
+            EventData::MariadbGtidEvent(ev) => ev.serialize(buf),
+            EventData::MariadbGtidListEvent(ev) => ev.serialize(buf),
         }
     }
 }
@@ -187,6 +187,112 @@ impl<'a> TableMapEvent<'a> {
         }
     }
 
+    /// Parses all the optional metadata fields and resolves them for every column.
+    ///
+    /// Errors if optional metadata is malformed or if there is a column of unknown type.
+    /// Fields that are not present in the event (see `binlog_row_metadata`) are `None`.
+    pub fn optional_metadata(&'a self) -> io::Result<OptionalMetadata> {
+        let columns_count = self.columns_type.0.len();
+        let mut column_types = Vec::with_capacity(columns_count);
+        for i in 0..columns_count {
+            let column_type = self
+                .get_column_type(i)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+                .expect("column index is in range");
+            column_types.push(column_type);
+        }
+
+        // indexes of columns of the given kind
+        let indexes = |f: fn(&ColumnType) -> bool| {
+            column_types
+                .iter()
+                .enumerate()
+                .filter(|(_, ty)| f(ty))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        };
+        let numeric = indexes(ColumnType::is_numeric_type);
+        let character = indexes(ColumnType::is_character_type);
+        let enum_or_set = indexes(ColumnType::is_enum_or_set_type);
+        let enums = indexes(ColumnType::is_enum_type);
+        let sets = indexes(ColumnType::is_set_type);
+        let geometry = indexes(ColumnType::is_geometry_type);
+
+        let mut output = OptionalMetadata {
+            columns: vec![ColumnOptionalMetadata::default(); columns_count],
+            primary_key: None,
+        };
+
+        for field in self.iter_optional_meta() {
+            let columns = &mut output.columns;
+            match field? {
+                OptionalMetadataField::Signedness(flags) => {
+                    for (&i, flag) in numeric.iter().zip(flags.iter()) {
+                        columns[i].is_unsigned = Some(*flag);
+                    }
+                }
+                OptionalMetadataField::DefaultCharset(charsets) => {
+                    set_default_charsets(columns, &character, &charsets)?;
+                }
+                OptionalMetadataField::EnumAndSetDefaultCharset(charsets) => {
+                    set_default_charsets(columns, &enum_or_set, &charsets)?;
+                }
+                OptionalMetadataField::ColumnCharset(charsets) => {
+                    for (&i, charset) in character.iter().zip(charsets.iter_charsets()) {
+                        columns[i].charset = Some(charset?);
+                    }
+                }
+                OptionalMetadataField::EnumAndSetColumnCharset(charsets) => {
+                    for (&i, charset) in enum_or_set.iter().zip(charsets.iter_charsets()) {
+                        columns[i].charset = Some(charset?);
+                    }
+                }
+                OptionalMetadataField::ColumnName(names) => {
+                    for (column, name) in columns.iter_mut().zip(names.iter_names()) {
+                        column.name = Some(name?.name_raw().to_vec());
+                    }
+                }
+                OptionalMetadataField::EnumStrValue(values) => {
+                    for (&i, values) in enums.iter().zip(values.iter_values()) {
+                        let values = values?;
+                        let values = values.values().iter().map(|x| x.value_raw().to_vec());
+                        columns[i].str_values = Some(values.collect());
+                    }
+                }
+                OptionalMetadataField::SetStrValue(values) => {
+                    for (&i, values) in sets.iter().zip(values.iter_values()) {
+                        let values = values?;
+                        let values = values.values().iter().map(|x| x.value_raw().to_vec());
+                        columns[i].str_values = Some(values.collect());
+                    }
+                }
+                OptionalMetadataField::GeometryType(types) => {
+                    for (&i, ty) in geometry.iter().zip(types.iter_geometry_types()) {
+                        columns[i].geometry_type = Some(ty?);
+                    }
+                }
+                OptionalMetadataField::SimplePrimaryKey(key) => {
+                    let key = key
+                        .iter_indexes()
+                        .map(|x| x.map(|i| PrimaryKeyWithPrefix::new(i, 0)))
+                        .collect::<io::Result<Vec<_>>>()?;
+                    output.primary_key = Some(key);
+                }
+                OptionalMetadataField::PrimaryKeyWithPrefix(key) => {
+                    let key = key.iter_keys().collect::<io::Result<Vec<_>>>()?;
+                    output.primary_key = Some(key);
+                }
+                OptionalMetadataField::ColumnVisibility(flags) => {
+                    for (column, flag) in columns.iter_mut().zip(flags.iter()) {
+                        column.is_visible = Some(*flag);
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+
     /// Returns a `'static` version of `self`.
     pub fn into_owned(self) -> TableMapEvent<'static> {
         TableMapEvent {
@@ -673,7 +779,7 @@ impl<'de> MyDeserialize<'de> for SetStrValues<'de> {
 
     fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
         let num_variants: RawInt<LenEnc> = buf.parse(())?;
-        let mut values = Vec::with_capacity(num_variants.0 as usize);
+        let mut values = Vec::with_capacity(min(num_variants.0 as usize, buf.len()));
         for _ in 0..num_variants.0 {
             values.push(buf.parse(())?);
         }
@@ -800,7 +906,7 @@ impl<'de> MyDeserialize<'de> for EnumStrValues<'de> {
 
     fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
         let num_variants: RawInt<LenEnc> = buf.parse(())?;
-        let mut values = Vec::with_capacity(num_variants.0 as usize);
+        let mut values = Vec::with_capacity(min(num_variants.0 as usize, buf.len()));
         for _ in 0..num_variants.0 {
             values.push(buf.parse(())?);
         }
@@ -954,7 +1060,7 @@ impl MySerialize for PrimaryKeysWithPrefix<'_> {
 }
 
 /// Info about primary key with a prefix (see [`PrimaryKeysWithPrefix`]).
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct PrimaryKeyWithPrefix {
     column_index: RawInt<LenEnc>,
     prefix_length: RawInt<LenEnc>,
@@ -1155,3 +1261,177 @@ impl<'a> Iterator for OptionalMetadataIter<'a> {
             .transpose()
     }
 }
+
+/// Applies [`DefaultCharset`] to the given columns (see [`TableMapEvent::optional_metadata`]).
+fn set_default_charsets(
+    columns: &mut [ColumnOptionalMetadata],
+    indexes: &[usize],
+    charsets: &DefaultCharset<'_>,
+) -> io::Result<()> {
+    for &i in indexes {
+        columns[i].charset = Some(charsets.default_charset());
+    }
+    for non_default in charsets.iter_non_default() {
+        let non_default = non_default?;
+        // column index is an index among columns of the corresponding kind
+        let i = usize::try_from(non_default.column_index())
+            .ok()
+            .and_then(|x| indexes.get(x))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "column index of a non-default charset is out of range",
+                )
+            })?;
+        columns[*i].charset = Some(non_default.charset());
+    }
+    Ok(())
+}
+
+/// Optional metadata of a [`TableMapEvent`] resolved for every column
+/// (see [`TableMapEvent::optional_metadata`]).
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct OptionalMetadata {
+    columns: Vec<ColumnOptionalMetadata>,
+    primary_key: Option<Vec<PrimaryKeyWithPrefix>>,
+}
+
+impl OptionalMetadata {
+    /// Returns metadata of every column of the table.
+    pub fn columns(&self) -> &[ColumnOptionalMetadata] {
+        &self.columns
+    }
+
+    /// Returns metadata of the given column (`None` if the index is out of range).
+    pub fn column(&self, col_idx: usize) -> Option<&ColumnOptionalMetadata> {
+        self.columns.get(col_idx)
+    }
+
+    /// Returns an index of a column with the given name.
+    ///
+    /// Column names are case-insensitive. Returns `None` if there is no such column
+    /// or if column names are not logged.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| {
+            column
+                .name_raw()
+                .map(|x| x.eq_ignore_ascii_case(name.as_bytes()))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Returns the primary key of the table, if logged.
+    ///
+    /// Prefix length is `0` if the whole column is a part of the key.
+    pub fn primary_key(&self) -> Option<&[PrimaryKeyWithPrefix]> {
+        self.primary_key.as_deref()
+    }
+}
+
+/// Optional metadata of a single column of a [`TableMapEvent`].
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ColumnOptionalMetadata {
+    name: Option<Vec<u8>>,
+    is_unsigned: Option<bool>,
+    charset: Option<u16>,
+    str_values: Option<Vec<Vec<u8>>>,
+    geometry_type: Option<GeometryType>,
+    is_visible: Option<bool>,
+}
+
+impl ColumnOptionalMetadata {
+    /// Returns the raw column name.
+    pub fn name_raw(&self) -> Option<&[u8]> {
+        self.name.as_deref()
+    }
+
+    /// Returns the column name as a string (lossy converted).
+    pub fn name(&self) -> Option<Cow<'_, str>> {
+        self.name.as_deref().map(String::from_utf8_lossy)
+    }
+
+    /// Returns `true` if this is an UNSIGNED column (defined for numeric columns).
+    pub fn is_unsigned(&self) -> Option<bool> {
+        self.is_unsigned
+    }
+
+    /// Returns charset+collation id of the column (defined for character,
+    /// ENUM and SET columns).
+    pub fn charset(&self) -> Option<u16> {
+        self.charset
+    }
+
+    /// Returns raw names of variants (defined for ENUM and SET columns).
+    pub fn str_values(&self) -> Option<&[Vec<u8>]> {
+        self.str_values.as_deref()
+    }
+
+    /// Returns the real geometry type (defined for geometry columns).
+    pub fn geometry_type(&self) -> Option<GeometryType> {
+        self.geometry_type
+    }
+
+    /// Returns `false` if this is an invisible column.
+    pub fn is_visible(&self) -> Option<bool> {
+        self.is_visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        binlog::{consts::BinlogVersion, events::FormatDescriptionEvent, BinlogCtx},
+        constants::GeometryType,
+        io::ParseBuf,
+        proto::MyDeserialize,
+    };
+
+    use super::{PrimaryKeyWithPrefix, TableMapEvent};
+
+    #[test]
+    fn should_resolve_optional_metadata() {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let data: &[u8] = b"\x2a\x00\x00\x00\x00\x00\x01\x00\x04test\x00\x01t\x00\
+            \x04\x03\x0f\xfe\xff\x05\x40\x00\xf7\x01\x04\x0e\
+            \x01\x01\x80\
+            \x02\x03\x21\x00\x3f\
+            \x04\x11\x02id\x04name\x04kind\x03geo\
+            \x06\x05\x02\x01a\x01b\
+            \x07\x01\x01\
+            \x08\x01\x00\
+            \x0a\x01\x2d\
+            \x0c\x01\xe0";
+        let ev = TableMapEvent::deserialize(BinlogCtx::new(data.len(), &fde), &mut ParseBuf(data))
+            .unwrap();
+        assert_eq!(ev.table_name(), "t");
+
+        let meta = ev.optional_metadata().unwrap();
+        assert_eq!(meta.columns().len(), 4);
+        assert_eq!(meta.column_index("NAME"), Some(1));
+        assert_eq!(meta.column_index("foo"), None);
+        assert_eq!(
+            meta.primary_key(),
+            Some(&[PrimaryKeyWithPrefix::new(0, 0)][..])
+        );
+
+        let id = meta.column(0).unwrap();
+        assert_eq!(id.name().as_deref(), Some("id"));
+        assert_eq!(id.is_unsigned(), Some(true));
+        assert_eq!(id.charset(), None);
+
+        let name = meta.column(1).unwrap();
+        assert_eq!(name.is_unsigned(), None);
+        assert_eq!(name.charset(), Some(63));
+
+        let kind = meta.column(2).unwrap();
+        assert_eq!(kind.charset(), Some(45));
+        assert_eq!(kind.str_values(), Some(&[b"a".to_vec(), b"b".to_vec()][..]));
+
+        let geo = meta.column(3).unwrap();
+        assert_eq!(geo.geometry_type(), Some(GeometryType::GEOM_POINT));
+        assert_eq!(geo.charset(), None);
+        assert_eq!(geo.is_visible(), Some(false));
+        assert_eq!(id.is_visible(), Some(true));
+        assert!(meta.column(4).is_none());
+    }
+}
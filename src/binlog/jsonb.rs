@@ -10,7 +10,7 @@
 
 use std::{
     borrow::Cow,
-    convert::{TryFrom, TryInto},
+    convert::TryFrom,
     fmt, io,
     marker::PhantomData,
     str::{from_utf8, Utf8Error},
@@ -24,6 +24,7 @@ use crate::{
         unexpected_buf_eof,
     },
     proto::{MyDeserialize, MySerialize},
+    value::json::limits::{JsonLimitError, JsonLimits},
 };
 
 impl fmt::Debug for Value<'_> {
@@ -310,36 +311,67 @@ impl<'a, T: StorageFormat, U: ComplexType> ComplexValue<'a, T, U> {
     }
 }
 
-impl<'a, T: StorageFormat> TryFrom<ComplexValue<'a, T, Array>> for serde_json::Value {
-    type Error = JsonbToJsonError;
-
-    fn try_from(value: ComplexValue<'a, T, Array>) -> Result<Self, Self::Error> {
-        let values = value
+impl<'a, T: StorageFormat> ComplexValue<'a, T, Array> {
+    fn to_json(
+        &self,
+        limits: &JsonLimits,
+        depth: usize,
+    ) -> Result<serde_json::Value, JsonbToJsonError> {
+        limits.check_depth(depth)?;
+        let values = self
             .iter()
             .map(|x| {
-                x.map_err(Self::Error::InvalidJsonb)
-                    .and_then(TryFrom::try_from)
+                x.map_err(JsonbToJsonError::InvalidJsonb)
+                    .and_then(|v| v.to_json(limits, depth))
             })
             .collect::<Result<Vec<_>, _>>()?;
         Ok(serde_json::Value::Array(values))
     }
 }
 
-impl<'a, T: StorageFormat> TryFrom<ComplexValue<'a, T, Object>> for serde_json::Value {
-    type Error = JsonbToJsonError;
-
-    fn try_from(value: ComplexValue<'a, T, Object>) -> Result<Self, Self::Error> {
-        let k_vs = value
+impl<'a, T: StorageFormat> ComplexValue<'a, T, Object> {
+    fn to_json(
+        &self,
+        limits: &JsonLimits,
+        depth: usize,
+    ) -> Result<serde_json::Value, JsonbToJsonError> {
+        limits.check_depth(depth)?;
+        let k_vs = self
             .iter()
             .map(|x| {
-                x.map_err(Self::Error::InvalidJsonb)
-                    .and_then(|(k, v)| Ok((from_utf8(k.value_raw())?.to_owned(), v.try_into()?)))
+                x.map_err(JsonbToJsonError::InvalidJsonb)
+                    .and_then(|(k, v)| {
+                        Ok((
+                            from_utf8(k.value_raw())?.to_owned(),
+                            v.to_json(limits, depth)?,
+                        ))
+                    })
             })
             .collect::<Result<serde_json::Map<_, _>, _>>()?;
         Ok(serde_json::Value::Object(k_vs))
     }
 }
 
+impl<'a, T: StorageFormat> TryFrom<ComplexValue<'a, T, Array>> for serde_json::Value {
+    type Error = JsonbToJsonError;
+
+    /// Default [`JsonLimits`] are applied to the conversion.
+    fn try_from(value: ComplexValue<'a, T, Array>) -> Result<Self, Self::Error> {
+        JsonLimits::default().check_size(value.data.len())?;
+        value.to_json(&JsonLimits::default(), 1)
+    }
+}
+
+impl<'a, T: StorageFormat> TryFrom<ComplexValue<'a, T, Object>> for serde_json::Value {
+    type Error = JsonbToJsonError;
+
+    /// Default [`JsonLimits`] are applied to the conversion.
+    fn try_from(value: ComplexValue<'a, T, Object>) -> Result<Self, Self::Error> {
+        JsonLimits::default().check_size(value.data.len())?;
+        value.to_json(&JsonLimits::default(), 1)
+    }
+}
+
 impl<'de, T: StorageFormat, U: ComplexType> MyDeserialize<'de> for ComplexValue<'de, T, U> {
     const SIZE: Option<usize> = None;
     type Ctx = ();
@@ -557,27 +589,58 @@ impl<'a> Value<'a> {
     }
 }
 
+impl<'a> Value<'a> {
+    /// Converts this value into a `serde_json::Value` applying the given limits.
+    ///
+    /// Size limit is applied to the binary representation of this value.
+    pub fn to_json_limited(
+        &self,
+        limits: &JsonLimits,
+    ) -> Result<serde_json::Value, JsonbToJsonError> {
+        let size = match self {
+            Value::SmallArray(x) => x.data.len(),
+            Value::LargeArray(x) => x.data.len(),
+            Value::SmallObject(x) => x.data.len(),
+            Value::LargeObject(x) => x.data.len(),
+            Value::String(x) => x.str_raw().len(),
+            _ => 0,
+        };
+        limits.check_size(size)?;
+        self.to_json(limits, 0)
+    }
+
+    /// `depth` is the nesting depth of the parent value.
+    fn to_json(
+        &self,
+        limits: &JsonLimits,
+        depth: usize,
+    ) -> Result<serde_json::Value, JsonbToJsonError> {
+        match self {
+            Value::Null => Ok(serde_json::Value::Null),
+            Value::Bool(x) => Ok(serde_json::Value::Bool(*x)),
+            Value::I16(x) => Ok((*x).into()),
+            Value::U16(x) => Ok((*x).into()),
+            Value::I32(x) => Ok((*x).into()),
+            Value::U32(x) => Ok((*x).into()),
+            Value::I64(x) => Ok((*x).into()),
+            Value::U64(x) => Ok((*x).into()),
+            Value::F64(x) => Ok((*x).into()),
+            Value::String(s) => Ok(from_utf8(s.str_raw())?.into()),
+            Value::SmallArray(x) => x.to_json(limits, depth + 1),
+            Value::LargeArray(x) => x.to_json(limits, depth + 1),
+            Value::SmallObject(x) => x.to_json(limits, depth + 1),
+            Value::LargeObject(x) => x.to_json(limits, depth + 1),
+            Value::Opaque(_) => Err(JsonbToJsonError::Opaque),
+        }
+    }
+}
+
 impl<'a> TryFrom<Value<'a>> for serde_json::Value {
     type Error = JsonbToJsonError;
 
+    /// Default [`JsonLimits`] are applied to the conversion.
     fn try_from(value: Value<'a>) -> Result<Self, Self::Error> {
-        match value {
-            Value::Null => Ok(serde_json::Value::Null),
-            Value::Bool(x) => Ok(serde_json::Value::Bool(x)),
-            Value::I16(x) => Ok(x.into()),
-            Value::U16(x) => Ok(x.into()),
-            Value::I32(x) => Ok(x.into()),
-            Value::U32(x) => Ok(x.into()),
-            Value::I64(x) => Ok(x.into()),
-            Value::U64(x) => Ok(x.into()),
-            Value::F64(x) => Ok(x.into()),
-            Value::String(s) => Ok(from_utf8(s.str_raw())?.into()),
-            Value::SmallArray(x) => x.try_into(),
-            Value::LargeArray(x) => x.try_into(),
-            Value::SmallObject(x) => x.try_into(),
-            Value::LargeObject(x) => x.try_into(),
-            Value::Opaque(_) => Err(Self::Error::Opaque),
-        }
+        value.to_json_limited(&JsonLimits::default())
     }
 }
 
@@ -589,6 +652,8 @@ pub enum JsonbToJsonError {
     Opaque,
     #[error("JSONB contains invalid UTF-8 char sequences: {}", _0)]
     InvalidUtf8(#[from] Utf8Error),
+    #[error(transparent)]
+    LimitExceeded(#[from] JsonLimitError),
 }
 
 impl<'de> MyDeserialize<'de> for Value<'de> {
@@ -0,0 +1,298 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! MariaDB GTID primitives.
+//!
+//! MariaDB GTID is a `<domain_id>-<server_id>-<sequence_number>` triple. A replication position
+//! is a list of GTIDs, one per replication domain (e.g. the value of `@@gtid_slave_pos`
+//! or `@slave_connect_state`, that a slave sets before requesting a binlog dump).
+
+use std::{collections::BTreeMap, fmt, io, iter::FromIterator, str::FromStr};
+
+use crate::{
+    io::ParseBuf,
+    misc::raw::{int::*, RawInt},
+    proto::{MyDeserialize, MySerialize},
+};
+
+/// MariaDB GTID.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct MariadbGtid {
+    domain_id: RawInt<LeU32>,
+    server_id: RawInt<LeU32>,
+    sequence_number: RawInt<LeU64>,
+}
+
+impl MariadbGtid {
+    /// Length of a binary representation of a GTID (see `GTID_LIST_EVENT`).
+    pub const LEN: usize = 16;
+
+    /// Creates a new instance.
+    pub fn new(domain_id: u32, server_id: u32, sequence_number: u64) -> Self {
+        Self {
+            domain_id: RawInt::new(domain_id),
+            server_id: RawInt::new(server_id),
+            sequence_number: RawInt::new(sequence_number),
+        }
+    }
+
+    /// Returns the replication domain id.
+    pub fn domain_id(&self) -> u32 {
+        self.domain_id.0
+    }
+
+    /// Returns the server id of the originating server.
+    pub fn server_id(&self) -> u32 {
+        self.server_id.0
+    }
+
+    /// Returns the sequence number of the event group within the replication domain.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number.0
+    }
+}
+
+/// Error of parsing a textual representation of a [`MariadbGtid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid MariaDB GTID (expected `<domain_id>-<server_id>-<sequence_number>`)")]
+pub struct ParseMariadbGtidError;
+
+impl FromStr for MariadbGtid {
+    type Err = ParseMariadbGtidError;
+
+    /// Parses `<domain_id>-<server_id>-<sequence_number>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().ok_or(ParseMariadbGtidError);
+        let domain_id = next()?.parse().map_err(|_| ParseMariadbGtidError)?;
+        let server_id = next()?.parse().map_err(|_| ParseMariadbGtidError)?;
+        let sequence_number = next()?.parse().map_err(|_| ParseMariadbGtidError)?;
+        Ok(Self::new(domain_id, server_id, sequence_number))
+    }
+}
+
+impl fmt::Display for MariadbGtid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}-{}",
+            self.domain_id(),
+            self.server_id(),
+            self.sequence_number()
+        )
+    }
+}
+
+impl MySerialize for MariadbGtid {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.domain_id.serialize(&mut *buf);
+        self.server_id.serialize(&mut *buf);
+        self.sequence_number.serialize(buf);
+    }
+}
+
+impl<'de> MyDeserialize<'de> for MariadbGtid {
+    const SIZE: Option<usize> = Some(Self::LEN);
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            domain_id: buf.parse_unchecked(())?,
+            server_id: buf.parse_unchecked(())?,
+            sequence_number: buf.parse_unchecked(())?,
+        })
+    }
+}
+
+/// MariaDB replication position, i.e. the last GTID of every replication domain.
+///
+/// Textual representation is a comma-separated list of GTIDs ordered by domain id
+/// (the `@slave_connect_state` format).
+///
+/// ```
+/// # use mysql_common::binlog::mariadb_gtid::{MariadbGtid, MariadbGtidPos};
+/// let mut pos: MariadbGtidPos = "0-1-100,1-2-5".parse().unwrap();
+/// pos.update(MariadbGtid::new(0, 3, 101));
+/// assert_eq!(pos.to_string(), "0-3-101,1-2-5");
+/// assert_eq!(
+///     format!("SET @slave_connect_state='{}'", pos),
+///     "SET @slave_connect_state='0-3-101,1-2-5'"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct MariadbGtidPos {
+    domains: BTreeMap<u32, MariadbGtid>,
+}
+
+impl MariadbGtidPos {
+    /// Creates an empty position.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if there are no GTIDs in this position.
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// Returns the last GTID of the given replication domain.
+    pub fn get(&self, domain_id: u32) -> Option<MariadbGtid> {
+        self.domains.get(&domain_id).copied()
+    }
+
+    /// Returns GTIDs of this position ordered by domain id.
+    pub fn gtids(&self) -> impl Iterator<Item = MariadbGtid> + '_ {
+        self.domains.values().copied()
+    }
+
+    /// Replaces the last GTID of the GTID's replication domain.
+    ///
+    /// Returns the replaced GTID, if any.
+    pub fn update(&mut self, gtid: MariadbGtid) -> Option<MariadbGtid> {
+        self.domains.insert(gtid.domain_id(), gtid)
+    }
+}
+
+impl FromIterator<MariadbGtid> for MariadbGtidPos {
+    /// Later GTIDs of a domain replace earlier ones.
+    fn from_iter<T: IntoIterator<Item = MariadbGtid>>(iter: T) -> Self {
+        let mut pos = Self::new();
+        for gtid in iter {
+            pos.update(gtid);
+        }
+        pos
+    }
+}
+
+impl FromStr for MariadbGtidPos {
+    type Err = ParseMariadbGtidError;
+
+    /// Parses a comma-separated list of GTIDs (empty string is an empty position).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .filter(|x| !x.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl fmt::Display for MariadbGtidPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, gtid) in self.gtids().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            fmt::Display::fmt(&gtid, f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MariadbGtid, MariadbGtidPos, ParseMariadbGtidError};
+    use crate::{
+        binlog::{
+            consts::{BinlogVersion, MariadbGtidFlags},
+            events::{FormatDescriptionEvent, MariadbGtidEvent, MariadbGtidListEvent},
+            BinlogCtx, BinlogStruct,
+        },
+        io::ParseBuf,
+        proto::{MyDeserialize, MySerialize},
+    };
+
+    #[test]
+    fn should_parse_mariadb_gtid_pos() {
+        let gtid: MariadbGtid = " 0-1-42".parse().unwrap();
+        assert_eq!(gtid, MariadbGtid::new(0, 1, 42));
+        assert_eq!(gtid.to_string(), "0-1-42");
+
+        assert_eq!("0-1".parse::<MariadbGtid>(), Err(ParseMariadbGtidError));
+        assert_eq!("0-1-x".parse::<MariadbGtid>(), Err(ParseMariadbGtidError));
+        assert_eq!("0-1-2-3".parse::<MariadbGtid>(), Err(ParseMariadbGtidError));
+
+        let pos: MariadbGtidPos = "2-1-7, 0-1-42,0-2-43".parse().unwrap();
+        assert_eq!(pos.to_string(), "0-2-43,2-1-7");
+        assert_eq!(pos.get(2), Some(MariadbGtid::new(2, 1, 7)));
+        assert_eq!(pos.get(1), None);
+
+        assert!("".parse::<MariadbGtidPos>().unwrap().is_empty());
+        assert!("0-1-2,foo".parse::<MariadbGtidPos>().is_err());
+    }
+
+    #[test]
+    fn should_handle_mariadb_gtid_events() {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+
+        let data: &[u8] = b"\x2a\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x0c\
+            \x00\x00\x00\x00\x00\x00";
+        let ev =
+            MariadbGtidEvent::deserialize(BinlogCtx::new(data.len(), &fde), &mut ParseBuf(data))
+                .unwrap();
+        assert_eq!(ev.sequence_number(), 42);
+        assert_eq!(ev.domain_id(), 1);
+        assert_eq!(
+            ev.flags(),
+            MariadbGtidFlags::FL_TRANSACTIONAL | MariadbGtidFlags::FL_ALLOW_PARALLEL
+        );
+        assert_eq!(ev.commit_id(), None);
+        assert_eq!(ev.gtid(7).to_string(), "1-7-42");
+
+        let mut output = Vec::new();
+        ev.serialize(&mut output);
+        assert_eq!(output, data);
+        assert_eq!(ev.len(BinlogVersion::Version4), data.len());
+
+        let ev = ev.with_commit_id(Some(100)).with_extra(&b"\x01"[..]);
+        assert!(ev.flags().contains(MariadbGtidFlags::FL_GROUP_COMMIT_ID));
+        let mut output = Vec::new();
+        ev.serialize(&mut output);
+        assert_eq!(output.len(), 8 + 4 + 1 + 8 + 1);
+        assert_eq!(ev.len(BinlogVersion::Version4), output.len());
+        let parsed = MariadbGtidEvent::deserialize(
+            BinlogCtx::new(output.len(), &fde),
+            &mut ParseBuf(&output),
+        )
+        .unwrap();
+        assert_eq!(parsed, ev);
+
+        let ev =
+            MariadbGtidListEvent::new(vec![MariadbGtid::new(0, 1, 10), MariadbGtid::new(1, 2, 5)])
+                .with_flags(0x01);
+        let mut output = Vec::new();
+        ev.serialize(&mut output);
+        assert_eq!(&output[..4], b"\x02\x00\x00\x10");
+        assert_eq!(ev.len(BinlogVersion::Version4), output.len());
+        let parsed = MariadbGtidListEvent::deserialize(
+            BinlogCtx::new(output.len(), &fde),
+            &mut ParseBuf(&output),
+        )
+        .unwrap();
+        assert_eq!(parsed, ev);
+        assert_eq!(parsed.pos().to_string(), "0-1-10,1-2-5");
+        assert!(MariadbGtidListEvent::deserialize(
+            BinlogCtx::new(output.len(), &fde),
+            &mut ParseBuf(&output[..20])
+        )
+        .is_err());
+
+        // servers may pad the list
+        let data: &[u8] = b"\x00\x00\x00\x00\x00\x00";
+        let ev = MariadbGtidListEvent::deserialize(
+            BinlogCtx::new(data.len(), &fde),
+            &mut ParseBuf(data),
+        )
+        .unwrap();
+        assert!(ev.gtids().is_empty());
+        assert_eq!(ev.extra(), b"\x00\x00");
+        assert_eq!(ev.len(BinlogVersion::Version4), data.len());
+        let mut output = Vec::new();
+        ev.serialize(&mut output);
+        assert_eq!(output, data);
+    }
+}
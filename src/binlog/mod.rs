@@ -26,23 +26,30 @@ use std::{
 };
 
 use crate::{
-    constants::ColumnType,
+    constants::{CapabilityFlags, ColumnType},
+    io::ParseBuf,
+    misc::raw::RawFlags,
+    packets::{first_byte::RowPacketKind, ErrPacket},
     proto::{MyDeserialize, MySerialize},
 };
 
 use self::{
-    consts::{BinlogVersion, EventType},
+    consts::{BinlogVersion, EventType, SemiSyncFlags},
     events::{Event, FormatDescriptionEvent, TableMapEvent},
 };
 
+pub mod cdc;
 pub mod consts;
 pub mod decimal;
 pub mod events;
 pub mod jsonb;
 pub mod jsondiff;
+pub mod mariadb_gtid;
 pub mod misc;
 pub mod row;
+pub mod transaction;
 pub mod value;
+pub mod watermark;
 
 pub struct BinlogCtx<'a> {
     pub event_size: usize,
@@ -98,6 +105,63 @@ impl BinlogFileHeader {
     }
 }
 
+/// Semi-sync header, that precedes every binlog event of a replication stream if semi-synchronous
+/// replication is negotiated (i.e. if the slave executed `SET @rpl_semi_sync_slave = 1`
+/// before requesting a binlog dump).
+///
+/// If [`SemiSyncHeader::is_ack_required`] then the slave must send
+/// [`crate::packets::SemiSyncAckPacket`] after processing the event.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SemiSyncHeader {
+    flags: RawFlags<SemiSyncFlags, u8>,
+}
+
+impl SemiSyncHeader {
+    /// Length of a semi-sync header.
+    pub const LEN: usize = 2;
+    /// Magic number, that starts a semi-sync header.
+    pub const MAGIC: u8 = 0xef;
+
+    /// Creates a new instance.
+    pub fn new(flags: SemiSyncFlags) -> Self {
+        Self {
+            flags: RawFlags::new(flags.bits()),
+        }
+    }
+
+    /// Returns the raw flags value.
+    pub fn flags_raw(&self) -> u8 {
+        self.flags.0
+    }
+
+    /// Returns parsed flags (unknown bits are truncated).
+    pub fn flags(&self) -> SemiSyncFlags {
+        self.flags.get()
+    }
+
+    /// Returns `true` if the master waits for an acknowledgement of this event.
+    pub fn is_ack_required(&self) -> bool {
+        self.flags().contains(SemiSyncFlags::SEMI_SYNC_ACK_REQ)
+    }
+
+    pub fn read<T: Read>(mut input: T) -> io::Result<Self> {
+        let mut buf = [0_u8; Self::LEN];
+        input.read_exact(&mut buf)?;
+
+        if buf[0] != Self::MAGIC {
+            return Err(Error::new(InvalidData, "invalid semi-sync header"));
+        }
+
+        Ok(Self {
+            flags: RawFlags::new(buf[1]),
+        })
+    }
+
+    pub fn write<T: Write>(&self, mut output: T) -> io::Result<()> {
+        output.write_all(&[Self::MAGIC, self.flags.0])
+    }
+}
+
 /// Reader for binlog events.
 ///
 /// It'll maintain actual fde and table map, and can be used
@@ -154,6 +218,14 @@ impl EventStreamReader {
 
         Ok(event)
     }
+
+    /// Will read next event, that is preceded by a semi-sync header, from the given stream
+    /// (see [`SemiSyncHeader`]).
+    pub fn read_semi_sync<T: Read>(&mut self, mut input: T) -> io::Result<(SemiSyncHeader, Event)> {
+        let header = SemiSyncHeader::read(&mut input)?;
+        let event = self.read(input)?;
+        Ok((header, event))
+    }
 }
 
 /// Binlog file.
@@ -193,6 +265,126 @@ impl<T: Read> Iterator for BinlogFile<T> {
     }
 }
 
+/// Reader for a replication stream, i.e. for packets, that server sends
+/// in response to `COM_BINLOG_DUMP` or `COM_BINLOG_DUMP_GTID`.
+///
+/// Every event packet starts with the `0x00` OK byte, optionally followed by a semi-sync header
+/// (see [`ReplicationStreamReader::with_semi_sync`]). The stream is terminated by an EOF packet
+/// (if `BINLOG_DUMP_NON_BLOCK` was requested) or by an ERR packet.
+///
+/// It'll maintain actual fde and table map (see [`EventStreamReader`]).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReplicationStreamReader {
+    reader: EventStreamReader,
+    capabilities: CapabilityFlags,
+    semi_sync: bool,
+}
+
+impl ReplicationStreamReader {
+    /// OK byte, that precedes every binlog event in a replication stream.
+    pub const OK_BYTE: u8 = 0x00;
+
+    /// Creates a new instance.
+    ///
+    /// `capabilities` are the capabilities negotiated for the connection.
+    pub fn new(version: BinlogVersion, capabilities: CapabilityFlags) -> Self {
+        Self {
+            reader: EventStreamReader::new(version),
+            capabilities,
+            semi_sync: false,
+        }
+    }
+
+    /// Defines whether events are preceded by a semi-sync header
+    /// (i.e. whether semi-synchronous replication was negotiated).
+    pub fn with_semi_sync(mut self, semi_sync: bool) -> Self {
+        self.semi_sync = semi_sync;
+        self
+    }
+
+    /// Returns a reference to the binlog stream reader.
+    pub fn reader(&self) -> &EventStreamReader {
+        &self.reader
+    }
+
+    /// Will read an event from the given packet payload.
+    ///
+    /// Returns `None` if the packet terminates the stream. The semi-sync header is only
+    /// returned if semi-sync is enabled (see [`ReplicationStreamReader::with_semi_sync`]).
+    /// ERR packet is returned as an error.
+    pub fn read_packet(
+        &mut self,
+        packet: &[u8],
+    ) -> io::Result<Option<(Option<SemiSyncHeader>, Event)>> {
+        match RowPacketKind::parse(self.capabilities, packet) {
+            RowPacketKind::Terminator => return Ok(None),
+            RowPacketKind::Err => {
+                return match ParseBuf(packet).parse::<ErrPacket>(self.capabilities)? {
+                    ErrPacket::Error(err) => {
+                        Err(Error::new(io::ErrorKind::Other, err.into_owned()))
+                    }
+                    ErrPacket::Progress(_) => Err(Error::new(
+                        InvalidData,
+                        "unexpected progress report in a replication stream",
+                    )),
+                };
+            }
+            RowPacketKind::Row => (),
+        }
+
+        match packet.split_first() {
+            Some((&Self::OK_BYTE, mut input)) => {
+                let semi_sync = if self.semi_sync {
+                    Some(SemiSyncHeader::read(&mut input)?)
+                } else {
+                    None
+                };
+                let event = self.reader.read(input)?;
+                Ok(Some((semi_sync, event)))
+            }
+            Some(_) => Err(Error::new(InvalidData, "invalid replication stream packet")),
+            None => Err(Error::new(UnexpectedEof, "empty replication stream packet")),
+        }
+    }
+
+    /// Wraps the given packet payloads into an iterator over events.
+    pub fn into_stream<I: IntoIterator>(self, packets: I) -> ReplicationStream<I::IntoIter> {
+        ReplicationStream {
+            reader: self,
+            packets: packets.into_iter(),
+        }
+    }
+}
+
+/// Replication stream.
+///
+/// It's an iterator over events of a replication stream (see [`ReplicationStreamReader`]).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReplicationStream<I> {
+    reader: ReplicationStreamReader,
+    packets: I,
+}
+
+impl<I> ReplicationStream<I> {
+    /// Returns a reference to the replication stream reader.
+    pub fn reader(&self) -> &ReplicationStreamReader {
+        &self.reader
+    }
+}
+
+impl<I, T> Iterator for ReplicationStream<I>
+where
+    I: Iterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    type Item = io::Result<(Option<SemiSyncHeader>, Event)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet = self.packets.next()?;
+        self.reader.read_packet(packet.as_ref()).transpose()
+    }
+}
+
 impl ColumnType {
     /// Returns type-specific metadata for this column type,
     /// as well as the total number of occupied bytes.
@@ -237,13 +429,15 @@ mod tests {
     use std::{collections::HashMap, io};
 
     use super::{
-        consts::{EventFlags, EventType},
+        consts::{EventFlags, EventType, SemiSyncFlags},
         events::{BinlogEventHeader, EventData, GtidEvent},
-        BinlogFile, BinlogFileHeader, BinlogVersion,
+        BinlogFile, BinlogFileHeader, BinlogVersion, EventStreamReader, ReplicationStreamReader,
+        SemiSyncHeader,
     };
 
     use crate::{
         binlog::{events::RowsEventData, value::BinlogValue},
+        constants::CapabilityFlags,
         proto::MySerialize,
         value::Value,
     };
@@ -375,6 +569,69 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_read_semi_sync_events() -> io::Result<()> {
+        let event_len = BINLOG_FILE[13] as usize;
+        let mut input = Vec::new();
+        SemiSyncHeader::new(SemiSyncFlags::SEMI_SYNC_ACK_REQ).write(&mut input)?;
+        input.extend_from_slice(&BINLOG_FILE[BinlogFileHeader::LEN..][..event_len]);
+        assert_eq!(&input[..SemiSyncHeader::LEN], &[0xef_u8, 0x01][..]);
+
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        let (header, event) = reader.read_semi_sync(&input[..])?;
+        assert!(header.is_ack_required());
+        assert_eq!(
+            event.header().event_type(),
+            Ok(EventType::FORMAT_DESCRIPTION_EVENT)
+        );
+
+        input[0] = 0x00;
+        assert!(reader.read_semi_sync(&input[..]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_replication_stream() -> io::Result<()> {
+        let event_len = BINLOG_FILE[13] as usize;
+        let event = &BINLOG_FILE[BinlogFileHeader::LEN..][..event_len];
+
+        let mut packet = vec![ReplicationStreamReader::OK_BYTE];
+        packet.extend_from_slice(event);
+
+        let mut semi_sync_packet = vec![ReplicationStreamReader::OK_BYTE];
+        SemiSyncHeader::new(SemiSyncFlags::SEMI_SYNC_ACK_REQ).write(&mut semi_sync_packet)?;
+        semi_sync_packet.extend_from_slice(event);
+
+        let eof: &[u8] = b"\xfe\x00\x00\x02\x00";
+        let err: &[u8] = b"\xff\xd4\x04#HY000Could not find first log file name";
+        let caps = CapabilityFlags::CLIENT_PROTOCOL_41;
+
+        let mut stream = ReplicationStreamReader::new(BinlogVersion::Version4, caps)
+            .into_stream(vec![&packet[..], eof]);
+        let (semi_sync, event) = stream.next().unwrap()?;
+        assert_eq!(semi_sync, None);
+        assert_eq!(
+            event.header().event_type(),
+            Ok(EventType::FORMAT_DESCRIPTION_EVENT)
+        );
+        assert!(stream.next().is_none());
+
+        let mut reader =
+            ReplicationStreamReader::new(BinlogVersion::Version4, caps).with_semi_sync(true);
+        let (semi_sync, _) = reader.read_packet(&semi_sync_packet)?.unwrap();
+        assert!(semi_sync.unwrap().is_ack_required());
+        assert!(reader.read_packet(&packet).is_err());
+
+        let err = reader.read_packet(err).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert!(err
+            .to_string()
+            .contains("Could not find first log file name"));
+
+        Ok(())
+    }
+
     #[test]
     fn binlog_file_iterator() -> io::Result<()> {
         let binlog_file = BinlogFile::new(BinlogVersion::Version4, BINLOG_FILE)?;
@@ -710,6 +967,8 @@ mod tests {
                         for meta in optional_meta {
                             meta.unwrap();
                         }
+                        let meta = ev.optional_metadata().unwrap();
+                        assert_eq!(meta.columns().len(), ev.columns_count() as usize);
                     }
                 }
 
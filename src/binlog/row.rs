@@ -176,10 +176,24 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
 
         let mut numeric_index = 0;
         for i in 0..(num_columns as usize) {
+            let column_type = table_info.get_column_type(i);
+
+            // signedness is logged for every numeric column of a table,
+            // even if it is not in the image
+            let is_numeric = matches!(column_type, Ok(Some(ref ty)) if ty.is_numeric_type());
+            let is_unsigned = if is_numeric {
+                let is_unsigned = signedness
+                    .as_ref()
+                    .and_then(|bits| bits.get(numeric_index).as_deref().copied())
+                    .unwrap_or_default();
+                numeric_index += 1;
+                is_unsigned
+            } else {
+                false
+            };
+
             // check if column is in columns list
             if cols.get(i).as_deref().copied().unwrap_or(false) {
-                let column_type = table_info.get_column_type(i);
-
                 // TableMapEvent must define column type for the current column.
                 let column_type = match column_type {
                     Ok(Some(ty)) => ty,
@@ -196,17 +210,6 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
                         .and_then(|bits| bits.next().as_deref().copied())
                         .unwrap_or(false);
 
-                let is_unsigned = if column_type.is_numeric_type() {
-                    let is_unsigned = signedness
-                        .as_ref()
-                        .and_then(|bits| bits.get(numeric_index).as_deref().copied())
-                        .unwrap_or_default();
-                    numeric_index += 1;
-                    is_unsigned
-                } else {
-                    false
-                };
-
                 let mut column_flags = ColumnFlags::empty();
                 if is_unsigned {
                     column_flags |= ColumnFlags::UNSIGNED_FLAG;
@@ -290,3 +293,114 @@ impl TryFrom<BinlogRow> for Row {
         Ok(new_row_raw(values, binlog_row.columns))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::{
+        binlog::{
+            consts::BinlogVersion,
+            events::{FormatDescriptionEvent, TableMapEvent, WriteRowsEvent},
+            BinlogCtx,
+        },
+        constants::ColumnFlags,
+        io::ParseBuf,
+        proto::MyDeserialize,
+        row::Row,
+        value::Value,
+    };
+
+    #[test]
+    fn should_decode_row_images() {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+
+        // `t` (LONG, VARCHAR(64), LONGLONG)
+        let tme_data: &[u8] = b"\x2a\x00\x00\x00\x00\x00\x01\x00\x04test\x00\x01t\x00\
+            \x03\x03\x0f\x08\x02\x40\x00\x07";
+        let tme = TableMapEvent::deserialize(
+            BinlogCtx::new(tme_data.len(), &fde),
+            &mut ParseBuf(tme_data),
+        )
+        .unwrap();
+
+        // only the first and the last columns are in the image, the last one is NULL in
+        // the first row
+        let ev_data: &[u8] = b"\x2a\x00\x00\x00\x00\x00\x01\x00\x02\x00\x03\x05\
+            \x02\x2a\x00\x00\x00\
+            \x00\xff\xff\xff\xff\x07\x00\x00\x00\x00\x00\x00\x00";
+        let ev = WriteRowsEvent::deserialize(
+            BinlogCtx::new(ev_data.len(), &fde),
+            &mut ParseBuf(ev_data),
+        )
+        .unwrap();
+
+        let rows = ev
+            .rows(&tme)
+            .map(|row| {
+                let (before, after) = row.unwrap();
+                assert!(before.is_none());
+                Row::try_from(after.unwrap()).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 2);
+
+        let names = rows[0]
+            .columns_ref()
+            .iter()
+            .map(|c| c.name_str().into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["@0", "@2"]);
+
+        assert_eq!(
+            rows[0].clone().unwrap_raw(),
+            vec![Some(Value::Int(42)), Some(Value::NULL)]
+        );
+        assert_eq!(
+            rows[1].clone().unwrap_raw(),
+            vec![Some(Value::Int(-1)), Some(Value::Int(7))]
+        );
+    }
+
+    #[test]
+    fn should_apply_signedness() {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+
+        // `t` (LONG, LONGLONG UNSIGNED, TINY UNSIGNED, LONG)
+        let tme_data: &[u8] = b"\x2a\x00\x00\x00\x00\x00\x01\x00\x04test\x00\x01t\x00\
+            \x04\x03\x08\x01\x03\x00\x0f\x01\x01\x60";
+        let tme = TableMapEvent::deserialize(
+            BinlogCtx::new(tme_data.len(), &fde),
+            &mut ParseBuf(tme_data),
+        )
+        .unwrap();
+
+        // the first numeric column is not in the image
+        let ev_data: &[u8] = b"\x2a\x00\x00\x00\x00\x00\x01\x00\x02\x00\x04\x0e\
+            \x00\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff";
+        let ev = WriteRowsEvent::deserialize(
+            BinlogCtx::new(ev_data.len(), &fde),
+            &mut ParseBuf(ev_data),
+        )
+        .unwrap();
+
+        let mut rows = ev.rows(&tme);
+        let row = Row::try_from(rows.next().unwrap().unwrap().1.unwrap()).unwrap();
+        assert!(rows.next().is_none());
+
+        let unsigned = row
+            .columns_ref()
+            .iter()
+            .map(|c| c.flags().contains(ColumnFlags::UNSIGNED_FLAG))
+            .collect::<Vec<_>>();
+        assert_eq!(unsigned, vec![true, true, false]);
+        assert_eq!(
+            row.unwrap_raw(),
+            vec![
+                Some(Value::UInt(u64::MAX)),
+                Some(Value::Int(255)),
+                Some(Value::Int(-1)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,459 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Grouping of binlog events into transactions.
+
+use std::io;
+
+use super::{
+    consts::{EventType, MariadbGtidFlags},
+    events::{Event, GtidEvent, MariadbGtidEvent, QueryEvent},
+    mariadb_gtid::MariadbGtid,
+};
+
+/// A group of binlog events that were committed together.
+///
+/// A transaction spans from a GTID event (or a `BEGIN` query, if GTIDs are disabled)
+/// through the terminating `XID` event or `COMMIT`/`ROLLBACK` query. A statement,
+/// that was logged outside of `BEGIN` (such as DDL) is a single-statement transaction.
+///
+/// MariaDB doesn't log `BEGIN` – its GTID event opens the group itself, unless the group
+/// is standalone (see `FL_STANDALONE`).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Transaction {
+    /// GTID of the transaction (`None` for anonymous transactions and for MariaDB,
+    /// see [`Transaction::mariadb_gtid`]).
+    pub gtid: Option<GtidEvent>,
+    /// MariaDB GTID of the transaction (the server id is taken from the event header).
+    pub mariadb_gtid: Option<MariadbGtid>,
+    /// Events of the transaction, including the opening and the terminating events.
+    ///
+    /// Heartbeats, rotates and other service events are not included.
+    pub events: Vec<Event>,
+    /// Timestamp of the first event of the transaction.
+    pub begin_ts: u32,
+    /// Timestamp of the terminating event of the transaction.
+    pub commit_ts: u32,
+}
+
+impl Transaction {
+    fn new(first_event: &Event) -> Self {
+        Self {
+            gtid: None,
+            mariadb_gtid: None,
+            events: Vec::new(),
+            begin_ts: first_event.header().timestamp(),
+            commit_ts: first_event.header().timestamp(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Begin,
+    Commit,
+    Other,
+}
+
+impl Marker {
+    fn of_query(query: &[u8]) -> Self {
+        let query = trim_ascii(query);
+        if query.eq_ignore_ascii_case(b"BEGIN") || starts_with_ignore_case(query, b"XA START") {
+            Marker::Begin
+        } else if query.eq_ignore_ascii_case(b"COMMIT") || query.eq_ignore_ascii_case(b"ROLLBACK") {
+            Marker::Commit
+        } else {
+            Marker::Other
+        }
+    }
+}
+
+fn trim_ascii(mut bytes: &[u8]) -> &[u8] {
+    while let [first, rest @ ..] = bytes {
+        if first.is_ascii_whitespace() {
+            bytes = rest;
+        } else {
+            break;
+        }
+    }
+    while let [rest @ .., last] = bytes {
+        if last.is_ascii_whitespace() {
+            bytes = rest;
+        } else {
+            break;
+        }
+    }
+    bytes
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+/// Adapter over a stream of binlog events, that groups them into [`Transaction`]s.
+///
+/// *   events outside of any transaction (i.e. if the stream starts in the middle
+///     of a transaction) are skipped;
+/// *   heartbeats, rotates, format description, previous GTIDs and GTID list events are skipped;
+/// *   an error is emitted if a transaction is interrupted by another one
+///     (the new transaction is then tracked as usual);
+/// *   an error is emitted if the stream ends in the middle of a transaction
+///     (see [`Transactions::pending`]).
+#[derive(Debug, Clone)]
+pub struct Transactions<I> {
+    events: I,
+    current: Option<Transaction>,
+    in_block: bool,
+    finished: bool,
+}
+
+impl<I> Transactions<I>
+where
+    I: Iterator<Item = io::Result<Event>>,
+{
+    /// Creates a new adapter over the given event stream.
+    pub fn new(events: I) -> Self {
+        Self {
+            events,
+            current: None,
+            in_block: false,
+            finished: false,
+        }
+    }
+
+    /// Returns the transaction, that is not yet terminated.
+    pub fn pending(&self) -> Option<&Transaction> {
+        self.current.as_ref()
+    }
+
+    /// Returns the wrapped event stream.
+    pub fn into_inner(self) -> I {
+        self.events
+    }
+
+    /// Starts a new transaction. Returns the interrupted transaction, if any.
+    fn start(&mut self, event: &Event) -> Option<Transaction> {
+        self.in_block = false;
+        self.current.replace(Transaction::new(event))
+    }
+
+    /// Terminates the current transaction with the given event.
+    fn finish(&mut self, event: Event) -> Option<Transaction> {
+        self.in_block = false;
+        let mut transaction = self.current.take()?;
+        transaction.commit_ts = event.header().timestamp();
+        transaction.events.push(event);
+        Some(transaction)
+    }
+
+    /// Returns `true` if the current transaction consists of a MariaDB GTID event only.
+    fn is_opened_by_mariadb_gtid(&self) -> bool {
+        match self.current.as_ref().map(|t| &t.events[..]) {
+            Some([event]) => event.header().event_type() == Ok(EventType::MARIADB_GTID_EVENT),
+            _ => false,
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        if let Some(transaction) = self.current.as_mut() {
+            transaction.events.push(event);
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> io::Result<Option<Transaction>> {
+        let event_type = match event.header().event_type() {
+            Ok(event_type) => event_type,
+            Err(_) => {
+                self.push(event);
+                return Ok(None);
+            }
+        };
+
+        match event_type {
+            EventType::GTID_EVENT
+            | EventType::ANONYMOUS_GTID_EVENT
+            | EventType::MARIADB_GTID_EVENT => {
+                let interrupted = self.start(&event);
+                if event_type == EventType::GTID_EVENT {
+                    let gtid = event.read_event::<GtidEvent>()?;
+                    if let Some(transaction) = self.current.as_mut() {
+                        transaction.gtid = Some(gtid);
+                    }
+                } else if event_type == EventType::MARIADB_GTID_EVENT {
+                    let gtid_event = event.read_event::<MariadbGtidEvent>()?;
+                    // MariaDB GTID event replaces the `BEGIN` query
+                    self.in_block = !gtid_event.flags().contains(MariadbGtidFlags::FL_STANDALONE);
+                    if let Some(transaction) = self.current.as_mut() {
+                        transaction.mariadb_gtid =
+                            Some(gtid_event.gtid(event.header().server_id()));
+                    }
+                }
+                self.push(event);
+                match interrupted {
+                    Some(transaction) => Err(interrupted_error(&transaction)),
+                    None => Ok(None),
+                }
+            }
+            EventType::QUERY_EVENT => {
+                let marker = Marker::of_query(event.read_event::<QueryEvent>()?.query_raw());
+                match marker {
+                    Marker::Begin => {
+                        let interrupted = if self.is_opened_by_mariadb_gtid() {
+                            None
+                        } else if self.in_block || self.current.is_none() {
+                            self.start(&event)
+                        } else {
+                            None
+                        };
+                        self.in_block = true;
+                        self.push(event);
+                        match interrupted {
+                            Some(transaction) => Err(interrupted_error(&transaction)),
+                            None => Ok(None),
+                        }
+                    }
+                    Marker::Commit if self.in_block => Ok(self.finish(event)),
+                    _ if self.in_block => {
+                        self.push(event);
+                        Ok(None)
+                    }
+                    // a statement outside of BEGIN is a transaction by itself
+                    _ => {
+                        if self.current.is_none() {
+                            self.start(&event);
+                        }
+                        Ok(self.finish(event))
+                    }
+                }
+            }
+            EventType::XID_EVENT | EventType::XA_PREPARE_LOG_EVENT => Ok(self.finish(event)),
+            EventType::HEARTBEAT_EVENT
+            | EventType::ROTATE_EVENT
+            | EventType::FORMAT_DESCRIPTION_EVENT
+            | EventType::PREVIOUS_GTIDS_EVENT
+            | EventType::MARIADB_GTID_LIST_EVENT
+            | EventType::STOP_EVENT => Ok(None),
+            _ => {
+                self.push(event);
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn interrupted_error(transaction: &Transaction) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "binlog transaction of {} events was interrupted by another transaction",
+            transaction.events.len()
+        ),
+    )
+}
+
+impl<I> Iterator for Transactions<I>
+where
+    I: Iterator<Item = io::Result<Event>>,
+{
+    type Item = io::Result<Transaction>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            match self.events.next() {
+                Some(Ok(event)) => match self.handle(event) {
+                    Ok(Some(transaction)) => return Some(Ok(transaction)),
+                    Ok(None) => (),
+                    Err(err) => return Some(Err(err)),
+                },
+                Some(Err(err)) => return Some(Err(err)),
+                None => {
+                    self.finished = true;
+                    return self.current.as_ref().map(|transaction| {
+                        Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            format!(
+                                "binlog stream ended in the middle of a transaction of {} events",
+                                transaction.events.len()
+                            ),
+                        ))
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+    use crate::{
+        binlog::{
+            consts::{BinlogVersion, EventFlags},
+            events::{BinlogEventHeader, FormatDescriptionEvent, XidEvent},
+            BinlogFile,
+        },
+        proto::MySerialize,
+    };
+
+    fn read_transactions(file_name: &str) -> io::Result<Vec<io::Result<Transaction>>> {
+        let data = std::fs::read(format!("./test-data/binlogs/{}", file_name))?;
+        let file = BinlogFile::new(BinlogVersion::Version4, io::Cursor::new(data))?;
+        Ok(Transactions::new(file).collect())
+    }
+
+    fn event(event_type: EventType, data: impl MySerialize) -> Event {
+        let mut body = Vec::new();
+        data.serialize(&mut body);
+        let len = (BinlogEventHeader::LEN + body.len()) as u32;
+        let header = BinlogEventHeader::new(1, event_type, 7, len, 0, EventFlags::empty());
+        let mut bytes = Vec::new();
+        header.serialize(&mut bytes);
+        bytes.extend_from_slice(&body);
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        Event::read(&fde, &bytes[..]).unwrap()
+    }
+
+    fn query(query: &'static str) -> Event {
+        let data = QueryEvent::new(&[][..], &b"test"[..]).with_query(query.as_bytes());
+        event(EventType::QUERY_EVENT, data)
+    }
+
+    fn queries(transaction: &Transaction) -> Vec<String> {
+        transaction
+            .events
+            .iter()
+            .filter_map(|event| event.read_event::<QueryEvent>().ok())
+            .map(|event| event.query().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn should_group_gtid_transactions() -> io::Result<()> {
+        let transactions = read_transactions("binlog_transaction_with_GTID.000001")?
+            .into_iter()
+            .collect::<io::Result<Vec<_>>>()?;
+
+        assert_eq!(transactions.len(), 3);
+        for transaction in &transactions {
+            assert!(transaction.gtid.is_some());
+            assert_eq!(transaction.begin_ts, 1423657933);
+        }
+        assert_eq!(queries(&transactions[0]), vec!["CREATE TABLE t1 (c1 INT)"]);
+        assert_eq!(
+            queries(&transactions[1]),
+            vec!["BEGIN", "INSERT INTO t1 VALUES (1)", "COMMIT"]
+        );
+        assert_eq!(transactions[1].events.len(), 4);
+        assert!(queries(&transactions[2])[0].starts_with("DROP TABLE"));
+        assert_eq!(
+            transactions[0].gtid.as_ref().unwrap().gno(),
+            transactions[1].gtid.as_ref().unwrap().gno() - 1
+        );
+
+        let transactions = read_transactions("binlog_transaction_with_anonymous_GTID.000001")?
+            .into_iter()
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(transactions.len(), 2);
+        assert!(transactions.iter().all(|t| t.gtid.is_none()));
+        assert_eq!(transactions[1].events.len(), 4);
+        assert_eq!(
+            transactions[1].events[3].header().event_type(),
+            Ok(EventType::XID_EVENT)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_group_transactions_without_gtid() -> io::Result<()> {
+        let transactions = read_transactions("binlog_transaction.000001")?
+            .into_iter()
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let lengths = transactions
+            .iter()
+            .map(|t| t.events.len())
+            .collect::<Vec<_>>();
+        assert_eq!(lengths, vec![1, 1, 4, 1, 1, 4, 1, 1, 4]);
+        assert_eq!(queries(&transactions[5]).last().unwrap(), "ROLLBACK");
+        assert_eq!(queries(&transactions[8]).last().unwrap(), "COMMIT");
+
+        // `ROLLBACK TO` doesn't terminate a transaction
+        let transactions = read_transactions("binlog_savepoint.000001")?
+            .into_iter()
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].events.len(), 9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_incomplete_transaction() -> io::Result<()> {
+        let data = std::fs::read("./test-data/binlogs/binlog_transaction.000001")?;
+        let file = BinlogFile::new(BinlogVersion::Version4, io::Cursor::new(data))?;
+        // drop the final COMMIT and rotate events
+        let events = file.take(18).collect::<Vec<_>>();
+        let mut transactions = Transactions::new(events.into_iter());
+        let results = transactions.by_ref().collect::<Vec<_>>();
+        assert_eq!(results.len(), 9);
+        assert_eq!(
+            results[8].as_ref().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        assert_eq!(transactions.pending().unwrap().events.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn should_group_mariadb_transactions() -> io::Result<()> {
+        let gtid = |seq_no, flags| {
+            let data = MariadbGtidEvent::new(0, seq_no).with_flags(flags);
+            event(EventType::MARIADB_GTID_EVENT, data)
+        };
+        let events = vec![
+            gtid(1, MariadbGtidFlags::FL_TRANSACTIONAL),
+            query("INSERT INTO t1 VALUES (1)"),
+            event(EventType::XID_EVENT, XidEvent { xid: 10 }),
+            gtid(2, MariadbGtidFlags::FL_STANDALONE),
+            query("CREATE TABLE t2 (c1 INT)"),
+            gtid(3, MariadbGtidFlags::empty()),
+            query("BEGIN"),
+            query("INSERT INTO t2 VALUES (1)"),
+            query("COMMIT"),
+        ];
+        let transactions =
+            Transactions::new(events.into_iter().map(Ok)).collect::<io::Result<Vec<_>>>()?;
+
+        let lengths = transactions
+            .iter()
+            .map(|t| t.events.len())
+            .collect::<Vec<_>>();
+        assert_eq!(lengths, vec![3, 2, 4]);
+        assert_eq!(
+            transactions[0].events[2].header().event_type(),
+            Ok(EventType::XID_EVENT)
+        );
+        assert_eq!(queries(&transactions[1]), vec!["CREATE TABLE t2 (c1 INT)"]);
+
+        let gtids = transactions
+            .iter()
+            .map(|t| t.mariadb_gtid.unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(gtids, vec!["0-7-1", "0-7-2", "0-7-3"]);
+        assert!(transactions.iter().all(|t| t.gtid.is_none()));
+
+        Ok(())
+    }
+}
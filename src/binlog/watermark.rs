@@ -0,0 +1,214 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Event-time watermarks of binlog streams.
+
+use std::{collections::HashMap, io};
+
+use super::{
+    consts::{EventFlags, EventType},
+    events::{BinlogEventHeader, Event},
+};
+
+/// Tracks the maximum event timestamp seen per originating server and derives watermarks.
+///
+/// A watermark is a timestamp, such that no more events older than it are expected
+/// (an event is considered late if it is older than the maximum timestamp seen minus
+/// the allowed lateness). Streaming pipelines could close time windows once the watermark
+/// passes their end.
+///
+/// Events without a meaningful timestamp (artificial events, heartbeats and events
+/// with zero timestamp) are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatermarkTracker {
+    allowed_lateness: u32,
+    max_timestamps: HashMap<u32, u32>,
+}
+
+impl WatermarkTracker {
+    /// Creates a new tracker with the given allowed lateness (in seconds).
+    pub fn new(allowed_lateness: u32) -> Self {
+        Self {
+            allowed_lateness,
+            max_timestamps: HashMap::new(),
+        }
+    }
+
+    /// Returns the allowed lateness (in seconds).
+    pub fn allowed_lateness(&self) -> u32 {
+        self.allowed_lateness
+    }
+
+    /// Returns the maximum event timestamp seen for the given server.
+    pub fn max_timestamp(&self, server_id: u32) -> Option<u32> {
+        self.max_timestamps.get(&server_id).copied()
+    }
+
+    /// Returns the watermark of the given server.
+    pub fn server_watermark(&self, server_id: u32) -> Option<u32> {
+        self.max_timestamp(server_id)
+            .map(|ts| ts.saturating_sub(self.allowed_lateness))
+    }
+
+    /// Returns the watermark of the stream, i.e. the minimum of watermarks of all the servers
+    /// (`None` if no events were observed).
+    pub fn watermark(&self) -> Option<u32> {
+        self.max_timestamps
+            .values()
+            .min()
+            .map(|ts| ts.saturating_sub(self.allowed_lateness))
+    }
+
+    /// Returns `true` if an event is older than the watermark of its server.
+    pub fn is_late(&self, header: &BinlogEventHeader) -> bool {
+        match self.server_watermark(header.server_id()) {
+            Some(watermark) => is_timed(header) && header.timestamp() < watermark,
+            None => false,
+        }
+    }
+
+    /// Observes an event header. Returns the new watermark of the stream, if it was advanced.
+    pub fn observe(&mut self, header: &BinlogEventHeader) -> Option<u32> {
+        if !is_timed(header) {
+            return None;
+        }
+
+        let before = self.watermark();
+        let max_timestamp = self.max_timestamps.entry(header.server_id()).or_insert(0);
+        if header.timestamp() <= *max_timestamp {
+            return None;
+        }
+        *max_timestamp = header.timestamp();
+
+        let after = self.watermark();
+        if after > before {
+            after
+        } else {
+            None
+        }
+    }
+
+    /// Forgets the given server (e.g. if it left the replication topology),
+    /// so that it no longer holds the watermark back.
+    pub fn forget_server(&mut self, server_id: u32) {
+        self.max_timestamps.remove(&server_id);
+    }
+}
+
+/// Returns `false` for events without a meaningful timestamp.
+fn is_timed(header: &BinlogEventHeader) -> bool {
+    header.timestamp() != 0
+        && !header.flags().contains(EventFlags::LOG_EVENT_ARTIFICIAL_F)
+        && header.event_type() != Ok(EventType::HEARTBEAT_EVENT)
+}
+
+/// An adapter, that feeds events of a binlog event stream to a [`WatermarkTracker`].
+///
+/// Events are passed through as is, so the current watermark could be inspected
+/// via [`Watermarked::tracker`] after every event.
+#[derive(Debug, Clone)]
+pub struct Watermarked<I> {
+    events: I,
+    tracker: WatermarkTracker,
+}
+
+impl<I> Watermarked<I>
+where
+    I: Iterator<Item = io::Result<Event>>,
+{
+    /// Creates a new adapter over the given event stream.
+    pub fn new(events: I, tracker: WatermarkTracker) -> Self {
+        Self { events, tracker }
+    }
+
+    /// Returns the tracker.
+    pub fn tracker(&self) -> &WatermarkTracker {
+        &self.tracker
+    }
+
+    /// Returns the current watermark of the stream.
+    pub fn watermark(&self) -> Option<u32> {
+        self.tracker.watermark()
+    }
+
+    /// Returns the wrapped event stream and the tracker.
+    pub fn into_inner(self) -> (I, WatermarkTracker) {
+        (self.events, self.tracker)
+    }
+}
+
+impl<I> Iterator for Watermarked<I>
+where
+    I: Iterator<Item = io::Result<Event>>,
+{
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.events.next()?;
+        if let Ok(ref event) = event {
+            self.tracker.observe(&event.header());
+        }
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(timestamp: u32, server_id: u32) -> BinlogEventHeader {
+        BinlogEventHeader::new(
+            timestamp,
+            EventType::QUERY_EVENT,
+            server_id,
+            0,
+            0,
+            EventFlags::empty(),
+        )
+    }
+
+    #[test]
+    fn should_track_watermarks() {
+        let mut tracker = WatermarkTracker::new(5);
+        assert_eq!(tracker.watermark(), None);
+        assert!(!tracker.is_late(&header(1, 1)));
+
+        assert_eq!(tracker.observe(&header(100, 1)), Some(95));
+        assert_eq!(tracker.observe(&header(90, 1)), None);
+        assert_eq!(tracker.observe(&header(110, 1)), Some(105));
+
+        // the second server holds the watermark back
+        assert_eq!(tracker.observe(&header(50, 2)), None);
+        assert_eq!(tracker.watermark(), Some(45));
+        assert_eq!(tracker.server_watermark(1), Some(105));
+        assert_eq!(tracker.observe(&header(60, 2)), Some(55));
+
+        assert!(tracker.is_late(&header(104, 1)));
+        assert!(!tracker.is_late(&header(105, 1)));
+        assert!(!tracker.is_late(&header(104, 3)));
+
+        // events without a meaningful timestamp are ignored
+        let heartbeat =
+            BinlogEventHeader::new(0, EventType::HEARTBEAT_EVENT, 2, 0, 0, EventFlags::empty());
+        assert_eq!(tracker.observe(&heartbeat), None);
+        assert!(!tracker.is_late(&heartbeat));
+        let rotate = BinlogEventHeader::new(
+            200,
+            EventType::ROTATE_EVENT,
+            2,
+            0,
+            0,
+            EventFlags::LOG_EVENT_ARTIFICIAL_F,
+        );
+        assert_eq!(tracker.observe(&rotate), None);
+        assert_eq!(tracker.max_timestamp(2), Some(60));
+
+        tracker.forget_server(2);
+        assert_eq!(tracker.watermark(), Some(105));
+    }
+}
@@ -14,6 +14,7 @@ pub static MIN_COMPRESS_LENGTH: usize = 50;
 
 pub static UTF8_GENERAL_CI: u16 = 33;
 pub static UTF8MB4_GENERAL_CI: u16 = 45;
+pub static BINARY_COLLATION: u16 = 63;
 
 my_bitflags! {
     StatusFlags,
@@ -429,6 +430,36 @@ impl Hasher for FxHasher {
     }
 }
 
+my_bitflags! {
+    MariadbCapabilities,
+    #[error("Unknown flags in the raw value of MariadbCapabilities (raw={:b})", _0)]
+    UnknownMariadbCapabilities,
+    u32,
+
+    /// MariaDB extended capability flags.
+    ///
+    /// These are sent in the last four bytes of the reserved area of a handshake packet
+    /// (and a handshake response) and are only meaningful if `CLIENT_LONG_PASSWORD`
+    /// (`CLIENT_MYSQL` in terms of MariaDB) is not set by the server.
+    pub struct MariadbCapabilities: u32 {
+        /// Client supports progress indicator.
+        const MARIADB_CLIENT_PROGRESS                = 0x0000_0001;
+
+        /// Permit COM_MULTI protocol.
+        const MARIADB_CLIENT_COM_MULTI               = 0x0000_0002;
+
+        /// Permit bulk insert (`COM_STMT_BULK_EXECUTE`).
+        const MARIADB_CLIENT_STMT_BULK_OPERATIONS    = 0x0000_0004;
+
+        /// Add extended metadata information (such as `UUID`, `INET6` or `JSON` pseudo-types)
+        /// to column definitions.
+        const MARIADB_CLIENT_EXTENDED_TYPE_INFO      = 0x0000_0008;
+
+        /// Permit skipping metadata.
+        const MARIADB_CLIENT_CACHE_METADATA          = 0x0000_0010;
+    }
+}
+
 my_bitflags! {
     CursorType,
     #[error("Unknown flags in the raw value of CursorType (raw={:b})", _0)]
@@ -441,6 +472,9 @@ my_bitflags! {
         const CURSOR_TYPE_READ_ONLY  = 1_u8;
         const CURSOR_TYPE_FOR_UPDATE = 2_u8;
         const CURSOR_TYPE_SCROLLABLE = 4_u8;
+        /// Parameter count is sent even if the statement has no parameters
+        /// (used to send query attributes, see `CLIENT_QUERY_ATTRIBUTES`).
+        const PARAMETER_COUNT_AVAILABLE = 8_u8;
     }
 }
 
@@ -566,6 +600,37 @@ pub enum Command {
     COM_END,
 }
 
+/// Option of the `COM_SET_OPTION` command.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash)]
+#[repr(u16)]
+pub enum SetOption {
+    MYSQL_OPTION_MULTI_STATEMENTS_ON = 0,
+    MYSQL_OPTION_MULTI_STATEMENTS_OFF,
+}
+
+impl From<SetOption> for u16 {
+    fn from(x: SetOption) -> u16 {
+        x as u16
+    }
+}
+
+impl TryFrom<u16> for SetOption {
+    type Error = UnknownSetOption;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SetOption::MYSQL_OPTION_MULTI_STATEMENTS_ON),
+            1 => Ok(SetOption::MYSQL_OPTION_MULTI_STATEMENTS_OFF),
+            x => Err(UnknownSetOption(x)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Unknown COM_SET_OPTION option {}", _0)]
+pub struct UnknownSetOption(pub u16);
+
 /// Type of state change information (part of MySql's Ok packet).
 #[allow(non_camel_case_types)]
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
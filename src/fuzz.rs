@@ -0,0 +1,175 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Fuzzing entry points (requires the `fuzz` feature).
+//!
+//! Every entry point feeds arbitrary input to parsers of this crate. Parse errors are expected
+//! and ignored, but panics are caught and reported as [`FuzzPanic`], so that fuzz targets
+//! (e.g. `cargo fuzz` or OSS-Fuzz) could be as simple as:
+//!
+//! ```ignore
+//! fuzz_target!(|data: &[u8]| {
+//!     mysql_common::fuzz::fuzz_entry_packet(data).unwrap();
+//! });
+//! ```
+//!
+//! Inputs longer than [`MAX_INPUT_LEN`] are ignored to keep allocations bounded
+//! (parsers never preallocate more items than there are bytes left in the input,
+//! whatever the length prefix says).
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{
+    binlog::{consts::BinlogVersion, EventStreamReader},
+    constants::CapabilityFlags,
+    io::ParseBuf,
+    packets::{
+        AuthMoreData, AuthNextFactor, AuthSwitchRequest, Column, ComBinlogDumpGtid, CommonOkPacket,
+        ErrPacket, HandshakePacket, HandshakeResponse, LocalInfilePacket, OkPacketDeserializer,
+        ResultSetTerminator, StmtPacket,
+    },
+    proto::MyDeserialize,
+    value::{convert::from_value_opt, TextValue, Value, ValueDeserializer},
+};
+
+/// Inputs longer than this are ignored.
+pub const MAX_INPUT_LEN: usize = 1024 * 1024;
+
+/// Maximum number of binlog events or values decoded from a single input.
+const MAX_ITEMS: usize = 1024;
+
+/// A parser panicked on the given input.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Parser panicked: {}", _0)]
+pub struct FuzzPanic(pub String);
+
+/// Runs `f` on the input, catching panics.
+fn run<F: FnOnce(&[u8])>(data: &[u8], f: F) -> Result<(), FuzzPanic> {
+    if data.len() > MAX_INPUT_LEN {
+        return Ok(());
+    }
+    panic::catch_unwind(AssertUnwindSafe(|| f(data)))
+        .map_err(|payload| FuzzPanic(message(&*payload)))
+}
+
+fn message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => (*message).into(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "unknown panic".into(),
+        },
+    }
+}
+
+/// Parses the input as server and client packets.
+///
+/// The first four bytes of the input are capability flags (little endian),
+/// the rest is a packet payload.
+pub fn fuzz_entry_packet(data: &[u8]) -> Result<(), FuzzPanic> {
+    run(data, |data| {
+        let (capabilities, payload) = match data {
+            [a, b, c, d, payload @ ..] => (
+                CapabilityFlags::from_bits_truncate(u32::from_le_bytes([*a, *b, *c, *d])),
+                payload,
+            ),
+            _ => return,
+        };
+
+        if let Ok(ok) =
+            ParseBuf(payload).parse::<OkPacketDeserializer<CommonOkPacket>>(capabilities)
+        {
+            if let Ok(infos) = ok.into_inner().session_state_info() {
+                for info in infos {
+                    let _ = info.decode();
+                }
+            }
+        }
+        let _ = ParseBuf(payload).parse::<OkPacketDeserializer<ResultSetTerminator>>(capabilities);
+        let _ = ParseBuf(payload).parse::<ErrPacket>(capabilities);
+        let _ = ParseBuf(payload).parse::<Column>(());
+        let _ = ParseBuf(payload).parse::<HandshakePacket>(());
+        let _ = ParseBuf(payload).parse::<HandshakeResponse>(());
+        let _ = ParseBuf(payload).parse::<AuthSwitchRequest>(());
+        let _ = ParseBuf(payload).parse::<AuthNextFactor>(());
+        let _ = ParseBuf(payload).parse::<AuthMoreData>(());
+        let _ = ParseBuf(payload).parse::<LocalInfilePacket>(());
+        let _ = ParseBuf(payload).parse::<StmtPacket>(());
+        let _ = ParseBuf(payload).parse::<ComBinlogDumpGtid>(());
+    })
+}
+
+/// Parses the input as a sequence of binlog events (without the binlog file header).
+pub fn fuzz_entry_binlog_event(data: &[u8]) -> Result<(), FuzzPanic> {
+    run(data, |mut data| {
+        let mut reader = EventStreamReader::new(BinlogVersion::Version4);
+        for _ in 0..MAX_ITEMS {
+            if data.is_empty() {
+                break;
+            }
+            match reader.read(&mut data) {
+                Ok(event) => {
+                    let _ = event.read_data();
+                }
+                Err(_) => break,
+            }
+        }
+    })
+}
+
+/// Parses the input as a sequence of text protocol values, then converts and renders them.
+pub fn fuzz_entry_value_text(data: &[u8]) -> Result<(), FuzzPanic> {
+    run(data, |data| {
+        let mut buf = ParseBuf(data);
+        for _ in 0..MAX_ITEMS {
+            let value = match ValueDeserializer::<TextValue>::deserialize((), &mut buf) {
+                Ok(value) => value.0,
+                Err(_) => break,
+            };
+            let _ = from_value_opt::<i64>(value.clone());
+            let _ = from_value_opt::<u64>(value.clone());
+            let _ = from_value_opt::<f64>(value.clone());
+            let _ = from_value_opt::<bool>(value.clone());
+            let _ = from_value_opt::<String>(value.clone());
+            let _ = value.as_sql(false);
+            let _ = value.as_sql(true);
+            let _ = value.decimal_precision_scale();
+            let _ = from_value_opt::<Value>(value);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_survive_arbitrary_input() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"\x00",
+            b"\xff\xff\xff\xff\x00\x00\x00\x02\x00\x00\x00",
+            b"\x00\x02\x00\x00\xff\x10\x04#HY000error",
+            b"\x00\x00\x00\x00\xfe\xff\xff\xff\xff\xff\xff\xff\xff",
+            b"\x0212\xfb\x0a-1.5e3\x00\xfc",
+            // COM_BINLOG_DUMP_GTID with a SID block of u64::MAX SIDs
+            b"\x00\x00\x00\x00\x1e\x04\x00\x01\x00\x00\x00\x00\x00\x00\x00\
+              \x04\x00\x00\x00\x00\x00\x00\x00\x08\x00\x00\x00\xff\xff\xff\xff\xff\xff\xff\xff",
+        ];
+        for input in inputs {
+            fuzz_entry_packet(input).unwrap();
+            fuzz_entry_binlog_event(input).unwrap();
+            fuzz_entry_value_text(input).unwrap();
+        }
+
+        assert_eq!(run(b"", |_| panic!("boom")), Err(FuzzPanic("boom".into())));
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Client-side interpolation of statement parameters (emulation of prepared statements).
+//!
+//! Parameters are rendered as SQL literals (see [`Value::sql_literal`]) and substituted
+//! into the query text, so that the statement could be executed using a single `COM_QUERY`.
+//!
+//! Note, that string literals are escaped according to the `no_backslash_escape` argument,
+//! so it must reflect the `NO_BACKSLASH_ESCAPES` SQL mode of the session (see
+//! `StatusFlags::SERVER_STATUS_NO_BACKSLASH_ESCAPES`). Also the character set of the
+//! connection must be ASCII compatible and must not be a multibyte character set, whose
+//! characters may contain a backslash or a quote byte (e.g. `sjis` or `gbk`).
+
+use std::io::Write;
+
+use crate::{
+    named_params::{
+        parse_named_params_with_options, positional_placeholders, NamedParamsError, ParseOptions,
+    },
+    params::{MissingNamedParameterError, Params, ParamsValidationError},
+    value::Value,
+};
+
+/// Error of [`interpolate`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum InterpolationError {
+    /// Query is invalid.
+    #[error(transparent)]
+    NamedParams(#[from] NamedParamsError),
+    /// Value of a named parameter is not given.
+    #[error(transparent)]
+    MissingNamedParameter(#[from] MissingNamedParameterError),
+    /// Parameters don't match the query.
+    #[error(transparent)]
+    Params(#[from] ParamsValidationError),
+}
+
+/// Substitutes parameters into the query.
+///
+/// Both named (`:name`) and positional (`?`) parameters are supported. Placeholders
+/// within string literals, quoted identifiers and comments are left as is.
+/// The query is lexed according to `no_backslash_escape` while the `ANSI_QUOTES` SQL mode
+/// is assumed to be disabled (see [`ParseOptions`]).
+///
+/// ```
+/// # use mysql_common::{interpolation::interpolate, params, params::Params};
+/// let query = interpolate(
+///     b"SELECT * FROM t WHERE name = :name AND id > :id",
+///     params! { "name" => "it's", "id" => 42 },
+///     false,
+/// )
+/// .unwrap();
+/// assert_eq!(query, b"SELECT * FROM t WHERE name = 'it\\'s' AND id > 42");
+///
+/// let query = interpolate(b"SELECT ?, '?'", Params::from((None::<u8>,)), true).unwrap();
+/// assert_eq!(query, b"SELECT NULL, '?'");
+/// ```
+pub fn interpolate(
+    query: &[u8],
+    params: Params,
+    no_backslash_escape: bool,
+) -> Result<Vec<u8>, InterpolationError> {
+    let options = ParseOptions::new().with_no_backslash_escapes(no_backslash_escape);
+    let (names, query) = parse_named_params_with_options(query, options)?;
+    let params = match names {
+        Some(names) => params.into_positional(&names)?,
+        None => params,
+    };
+
+    let values: &[Value] = match params {
+        Params::Empty => &[],
+        Params::Positional(ref values) => values,
+        Params::Named(_) => return Err(ParamsValidationError::NamedParams.into()),
+    };
+
+    let positions = positional_placeholders(&query, options);
+    if positions.len() != values.len() {
+        return Err(ParamsValidationError::WrongCount {
+            expected: positions.len(),
+            given: values.len(),
+        }
+        .into());
+    }
+
+    let mut output = Vec::with_capacity(query.len() + values.len() * 8);
+    let mut last = 0;
+    for (position, value) in positions.into_iter().zip(values) {
+        output.extend_from_slice(&query[last..position]);
+        write!(output, "{}", value.sql_literal(no_backslash_escape))
+            .expect("writing to a vector never fails");
+        last = position + 1;
+    }
+    output.extend_from_slice(&query[last..]);
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_interpolate_params() {
+        let params = Params::from((
+            Value::from("a'b\\c"),
+            Value::Bytes(vec![0xff, 0x00]),
+            Value::Double(1.5),
+            Value::Date(2021, 1, 2, 3, 4, 5, 0),
+        ));
+        assert_eq!(
+            interpolate(b"SELECT ?, ? /* ? */, ?, `?`, ?", params.clone(), false).unwrap(),
+            &b"SELECT 'a\\'b\\\\c', 0xFF00 /* ? */, 1.5, `?`, '2021-01-02 03:04:05'"[..]
+        );
+        assert_eq!(
+            interpolate(b"SELECT ?, ?, ?, ?", params, true).unwrap(),
+            &b"SELECT 'a''b\\c', 0xFF00, 1.5, '2021-01-02 03:04:05'"[..]
+        );
+
+        let params = Params::from(vec![("a", Value::Int(-1)), ("b", Value::from("x"))]);
+        assert_eq!(
+            interpolate(b"SELECT :a, :b, :a", params.clone(), false).unwrap(),
+            &b"SELECT -1, 'x', -1"[..]
+        );
+        assert_eq!(
+            interpolate(b"SELECT 1", Params::Empty, false).unwrap(),
+            &b"SELECT 1"[..]
+        );
+
+        assert_eq!(
+            interpolate(b"SELECT :a, :c", params.clone(), false),
+            Err(MissingNamedParameterError(b"c".to_vec()).into())
+        );
+        assert_eq!(
+            interpolate(b"SELECT ?", params, false),
+            Err(ParamsValidationError::NamedParams.into())
+        );
+        assert_eq!(
+            interpolate(b"SELECT ?, ?", Params::from((1,)), false),
+            Err(ParamsValidationError::WrongCount {
+                expected: 2,
+                given: 1
+            }
+            .into())
+        );
+        assert!(matches!(
+            interpolate(b"SELECT :a, ?", Params::Empty, false),
+            Err(InterpolationError::NamedParams(_))
+        ));
+    }
+}
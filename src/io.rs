@@ -10,7 +10,24 @@ use byteorder::{LittleEndian as LE, ReadBytesExt, WriteBytesExt};
 use bytes::BufMut;
 use std::{cmp::min, io};
 
-use crate::proto::MyDeserialize;
+use crate::proto::{DeserializeCtx, FromDeserializeCtx, MyDeserialize};
+
+macro_rules! checked_put {
+    ($checked:ident, $name:ident, $size:literal, $t:ident) => {
+        #[doc = concat!("Same as `", stringify!($name), "`.")]
+        ///
+        /// Returns `false` and writes nothing if the number doesn't fit.
+        fn $checked(&mut self, x: $t) -> bool {
+            const SHIFT: u32 = $t::BITS - 8 * $size;
+            if (x << SHIFT) >> SHIFT == x {
+                self.$name(x);
+                true
+            } else {
+                false
+            }
+        }
+    };
+}
 
 pub trait BufMutExt: BufMut {
     /// Writes an unsigned integer to self as MySql length-encoded integer.
@@ -45,11 +62,26 @@ pub trait BufMutExt: BufMut {
         self.put_int_le(x as i64, 3);
     }
 
+    /// Writes a 5-bytes unsigned integer.
+    fn put_u40_le(&mut self, x: u64) {
+        self.put_uint_le(x, 5);
+    }
+
+    /// Writes a 5-bytes signed integer.
+    fn put_i40_le(&mut self, x: i64) {
+        self.put_int_le(x, 5);
+    }
+
     /// Writes a 6-bytes unsigned integer.
     fn put_u48_le(&mut self, x: u64) {
         self.put_uint_le(x, 6);
     }
 
+    /// Writes a 6-bytes signed integer.
+    fn put_i48_le(&mut self, x: i64) {
+        self.put_int_le(x, 6);
+    }
+
     /// Writes a 7-bytes unsigned integer.
     fn put_u56_le(&mut self, x: u64) {
         self.put_uint_le(x, 7);
@@ -60,6 +92,15 @@ pub trait BufMutExt: BufMut {
         self.put_int_le(x, 7);
     }
 
+    checked_put!(checked_put_u24_le, put_u24_le, 3, u32);
+    checked_put!(checked_put_i24_le, put_i24_le, 3, i32);
+    checked_put!(checked_put_u40_le, put_u40_le, 5, u64);
+    checked_put!(checked_put_i40_le, put_i40_le, 5, i64);
+    checked_put!(checked_put_u48_le, put_u48_le, 6, u64);
+    checked_put!(checked_put_i48_le, put_i48_le, 6, i64);
+    checked_put!(checked_put_u56_le, put_u56_le, 7, u64);
+    checked_put!(checked_put_i56_le, put_i56_le, 7, i64);
+
     /// Writes a string with u8 length prefix. Truncates, if the length is greater that `u8::MAX`.
     fn put_u8_str(&mut self, s: &[u8]) {
         let len = std::cmp::min(s.len(), u8::MAX as usize);
@@ -107,18 +148,38 @@ macro_rules! eat_num {
             }
         }
     };
-    ($name:ident, $checked:ident, $size:literal, $offset:literal, $t:ident::$fn:ident) => {
+    ($name:ident, $checked:ident, $size:literal, le, $t:ident) => {
         #[doc = "Consumes a number from the head of the buffer."]
+        ///
+        /// Signed numbers are sign-extended.
         pub fn $name(&mut self) -> $t {
-            const SIZE: usize = $size;
+            const SHIFT: u32 = $t::BITS - 8 * $size;
             let mut x: $t = 0;
-            let bytes = self.eat(SIZE);
-            for (i, b) in bytes.iter().enumerate() {
-                x |= (*b as $t) << ((8 * i) + (8 * $offset));
+            for (i, b) in self.eat($size).iter().enumerate() {
+                x |= (*b as $t) << (8 * i);
+            }
+            (x << SHIFT) >> SHIFT
+        }
+
+        eat_num!(@checked $name, $checked, $size, $t);
+    };
+    ($name:ident, $checked:ident, $size:literal, be, $t:ident) => {
+        #[doc = "Consumes a number from the head of the buffer."]
+        ///
+        /// Signed numbers are sign-extended.
+        pub fn $name(&mut self) -> $t {
+            const SHIFT: u32 = $t::BITS - 8 * $size;
+            let mut x: $t = 0;
+            for b in self.eat($size) {
+                x = (x << 8) | *b as $t;
             }
-            $t::$fn(x)
+            (x << SHIFT) >> SHIFT
         }
 
+        eat_num!(@checked $name, $checked, $size, $t);
+    };
+    (@checked $name:ident, $checked:ident, $size:literal, $t:ident) => {
+
         #[doc = "Consumes a number from the head of the buffer. Returns `None` if buffer is too small."]
         pub fn $checked(&mut self) -> Option<$t> {
             if self.len() >= $size {
@@ -157,6 +218,19 @@ impl<'a> ParseBuf<'a> {
         }
     }
 
+    /// Checked `parse` of a payload in the context of a connection.
+    ///
+    /// `T::Ctx` is extracted from the given `ctx` and the payload length
+    /// is checked against `ctx.max_lengths`.
+    pub fn parse_in<T>(&mut self, ctx: &DeserializeCtx) -> io::Result<T>
+    where
+        T: MyDeserialize<'a>,
+        T::Ctx: FromDeserializeCtx,
+    {
+        ctx.check_payload_len(self.len())?;
+        self.parse(T::Ctx::from_deserialize_ctx(ctx))
+    }
+
     /// Returns true if buffer is empty.
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -224,26 +298,26 @@ impl<'a> ParseBuf<'a> {
     eat_num!(eat_i16_le, checked_eat_i16_le, i16::from_le_bytes);
     eat_num!(eat_u16_be, checked_eat_u16_be, u16::from_be_bytes);
     eat_num!(eat_i16_be, checked_eat_i16_be, i16::from_be_bytes);
-    eat_num!(eat_u24_le, checked_eat_u24_le, 3, 0, u32::from_le);
-    eat_num!(eat_i24_le, checked_eat_i24_le, 3, 0, i32::from_le);
-    eat_num!(eat_u24_be, checked_eat_u24_be, 3, 1, u32::from_be);
-    eat_num!(eat_i24_be, checked_eat_i24_be, 3, 1, i32::from_be);
+    eat_num!(eat_u24_le, checked_eat_u24_le, 3, le, u32);
+    eat_num!(eat_i24_le, checked_eat_i24_le, 3, le, i32);
+    eat_num!(eat_u24_be, checked_eat_u24_be, 3, be, u32);
+    eat_num!(eat_i24_be, checked_eat_i24_be, 3, be, i32);
     eat_num!(eat_u32_le, checked_eat_u32_le, u32::from_le_bytes);
     eat_num!(eat_i32_le, checked_eat_i32_le, i32::from_le_bytes);
     eat_num!(eat_u32_be, checked_eat_u32_be, u32::from_be_bytes);
     eat_num!(eat_i32_be, checked_eat_i32_be, i32::from_be_bytes);
-    eat_num!(eat_u40_le, checked_eat_u40_le, 5, 0, u64::from_le);
-    eat_num!(eat_i40_le, checked_eat_i40_le, 5, 0, i64::from_le);
-    eat_num!(eat_u40_be, checked_eat_u40_be, 5, 3, u64::from_be);
-    eat_num!(eat_i40_be, checked_eat_i40_be, 5, 3, i64::from_be);
-    eat_num!(eat_u48_le, checked_eat_u48_le, 6, 0, u64::from_le);
-    eat_num!(eat_i48_le, checked_eat_i48_le, 6, 0, i64::from_le);
-    eat_num!(eat_u48_be, checked_eat_u48_be, 6, 2, u64::from_be);
-    eat_num!(eat_i48_be, checked_eat_i48_be, 6, 2, i64::from_be);
-    eat_num!(eat_u56_le, checked_eat_u56_le, 7, 0, u64::from_le);
-    eat_num!(eat_i56_le, checked_eat_i56_le, 7, 0, i64::from_le);
-    eat_num!(eat_u56_be, checked_eat_u56_be, 7, 1, u64::from_be);
-    eat_num!(eat_i56_be, checked_eat_i56_be, 7, 1, i64::from_be);
+    eat_num!(eat_u40_le, checked_eat_u40_le, 5, le, u64);
+    eat_num!(eat_i40_le, checked_eat_i40_le, 5, le, i64);
+    eat_num!(eat_u40_be, checked_eat_u40_be, 5, be, u64);
+    eat_num!(eat_i40_be, checked_eat_i40_be, 5, be, i64);
+    eat_num!(eat_u48_le, checked_eat_u48_le, 6, le, u64);
+    eat_num!(eat_i48_le, checked_eat_i48_le, 6, le, i64);
+    eat_num!(eat_u48_be, checked_eat_u48_be, 6, be, u64);
+    eat_num!(eat_i48_be, checked_eat_i48_be, 6, be, i64);
+    eat_num!(eat_u56_le, checked_eat_u56_le, 7, le, u64);
+    eat_num!(eat_i56_le, checked_eat_i56_le, 7, le, i64);
+    eat_num!(eat_u56_be, checked_eat_u56_be, 7, be, u64);
+    eat_num!(eat_i56_be, checked_eat_i56_be, 7, be, i64);
     eat_num!(eat_u64_le, checked_eat_u64_le, u64::from_le_bytes);
     eat_num!(eat_i64_le, checked_eat_i64_le, i64::from_le_bytes);
     eat_num!(eat_u64_be, checked_eat_u64_be, u64::from_be_bytes);
@@ -414,4 +488,51 @@ mod tests {
         assert_eq!(buf.clone().eat_u56_le(), 0x0006050403020100);
         assert_eq!(buf.clone().eat_u56_be(), 0x0000010203040506);
     }
+
+    #[test]
+    fn odd_width_signed() {
+        let buf = ParseBuf(&[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]);
+        assert_eq!(buf.clone().eat_i24_le(), -2);
+        assert_eq!(buf.clone().eat_u24_le(), 0x00fffffe);
+        assert_eq!(buf.clone().eat_i40_be(), -0x1_0000_0001);
+        assert_eq!(buf.clone().eat_i48_le(), -2);
+        assert_eq!(buf.clone().eat_i56_le(), 0x7ffffffffffffe);
+        assert_eq!(ParseBuf(&[0x80, 0, 0]).eat_i24_le(), 0x80);
+        assert_eq!(ParseBuf(&[0, 0, 0x80]).eat_i24_le(), -0x800000);
+        assert_eq!(
+            ParseBuf(&[0x80, 0, 0, 0, 0, 1]).eat_i48_be(),
+            -0x7fffffffffff
+        );
+        assert_eq!(ParseBuf(&[1, 2]).checked_eat_i24_le(), None);
+    }
+
+    #[test]
+    fn odd_width_write() {
+        let mut buf = Vec::new();
+        buf.put_i24_le(-2);
+        buf.put_u48_le(0x060504030201);
+        buf.put_i48_le(-0x800000000000);
+        assert_eq!(
+            buf,
+            [0xfe, 0xff, 0xff, 1, 2, 3, 4, 5, 6, 0, 0, 0, 0, 0, 0x80]
+        );
+
+        let mut parse_buf = ParseBuf(&buf);
+        assert_eq!(parse_buf.eat_i24_le(), -2);
+        assert_eq!(parse_buf.eat_u48_le(), 0x060504030201);
+        assert_eq!(parse_buf.eat_i48_le(), -0x800000000000);
+
+        let mut buf = Vec::new();
+        assert!(buf.checked_put_u24_le(0xffffff));
+        assert!(!buf.checked_put_u24_le(0x1000000));
+        assert!(buf.checked_put_i24_le(-0x800000));
+        assert!(!buf.checked_put_i24_le(0x800000));
+        assert!(buf.checked_put_i48_le(-1));
+        assert!(!buf.checked_put_u48_le(u64::MAX));
+        assert!(!buf.checked_put_i56_le(i64::MIN));
+        assert_eq!(
+            buf,
+            [0xff, 0xff, 0xff, 0, 0, 0x80, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+    }
 }
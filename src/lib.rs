@@ -61,8 +61,9 @@
 //! | ----------------------------------------------- | ------------------------------------------------- |
 //! | `Row`                                           | Trivial conversion for `Row` itself.              |
 //! | `T: FromValue`                                  | For rows with a single column.                    |
-//! | `(T1: FromValue [, ..., T12: FromValue])`       | Row to a tuple of arity 1-12.                     |
+//! | `(T1: FromValue [, ..., T32: FromValue])`       | Row to a tuple of arity 1-32.                     |
 //! | [`frunk::Hlist!`] types                         | Usefull to overcome tuple arity limitation        |
+//! | Structs defined using `from_row_struct!`        | Fields are taken from columns by name.            |
 //!
 //! ### Crate features
 //!
@@ -71,11 +72,23 @@
 //! | `bigdecimal`   | Enables `bigdecimal` v0.2.x types support   | 🔴      |
 //! | `bigdecimal03` | Enables `bigdecimal` v0.3.x types support   | 🟢      |
 //! | `chrono`       | Enables `chrono` types support              | 🔴      |
+//! | `chrono-tz`    | Enables time zone names validation          | 🔴      |
+//! | `compact_str`  | Enables `CompactString` type support        | 🔴      |
 //! | `rust_decimal` | Enables `rust_decimal` types support        | 🟢      |
 //! | `time`         | Enables `time` v0.2.x types support         | 🔴      |
 //! | `time03`       | Enables `time` v0.3.x types support         | 🟢      |
 //! | `uuid`         | Enables `Uuid` type support                 | 🟢      |
 //! | `frunk`        | Enables `FromRow` for `frunk::Hlist!` types | 🟢      |
+//! | `geo`          | Enables `geo_types` geometries support      | 🔴      |
+//! | `wkt`          | Enables WKT geometries support              | 🔴      |
+//! | `log`          | Emits parse warnings using the `log` crate  | 🔴      |
+//! | `stmt_registry` | Enables checks of `StmtIdRegistry`        | 🔴      |
+//! | `fuzz`         | Enables fuzzing entry points (`fuzz` module) | 🔴      |
+//! | `zstd`         | Enables zstd protocol compression           | 🔴      |
+//! | `legacy`       | Enables pre-4.1 protocol packets (`packets::legacy`) | 🔴 |
+//! | `serde_packets` | Enables `Serialize`/`Deserialize` for parsed packets | 🔴    |
+//! | `arbitrary`    | Enables `arbitrary::Arbitrary` for packets and values | 🔴  |
+//! | `derive`       | Enables `#[derive(ToParams, IntReprEnum)]`  | 🟢      |
 //!
 //! [1]: https://dev.mysql.com/doc/internals/en/binary-protocol-value.html
 #![cfg_attr(feature = "nightly", feature(test, const_fn))]
@@ -101,9 +114,18 @@ pub use bigdecimal03;
 #[cfg(feature = "chrono")]
 pub use chrono;
 
+#[cfg(feature = "chrono-tz")]
+pub use chrono_tz;
+
+#[cfg(feature = "compact_str")]
+pub use compact_str;
+
 #[cfg(feature = "frunk")]
 pub use frunk;
 
+#[cfg(feature = "geo")]
+pub use geo_types;
+
 #[cfg(feature = "rust_decimal")]
 pub use rust_decimal;
 
@@ -120,6 +142,13 @@ pub use num_bigint;
 pub use serde;
 pub use serde_json;
 
+#[cfg(feature = "derive")]
+pub use mysql_common_derive::{IntReprEnum, ToParams};
+
+// Derived impls refer to `::mysql_common`, so it should also resolve within this crate.
+#[cfg(feature = "derive")]
+extern crate self as mysql_common;
+
 /// This macro is a convenient way to pass named parameters to a statement.
 ///
 /// ```ignore
@@ -129,9 +158,25 @@ pub use serde_json;
 ///     "foo2x" => foo * 2,
 /// });
 /// ```
+///
+/// `None` passed using `=>` is bound as `NULL`. Use `=>?` to pass an `Option` that is
+/// not bound at all if it's `None` (so that the parameter is reported as missing, see
+/// [`Params::check_named`](crate::params::Params::check_named)):
+///
+/// ```
+/// # use mysql_common::{params, params::Params, value::Value};
+/// let email: Option<&str> = None;
+/// let params = params! { "id" => 1, "note" => None::<String>, "email" =>? email };
+/// assert_eq!(params, Params::from(vec![("id", Value::Int(1)), ("note", Value::NULL)]));
+/// ```
 #[macro_export]
 macro_rules! params {
     () => {};
+    (@to_pair $map:expr, $name:expr =>? $value:expr) => (
+        if let std::option::Option::Some(value) = $value {
+            params!(@to_pair $map, $name => value);
+        }
+    );
     (@to_pair $map:expr, $name:expr => $value:expr) => (
         let entry = $map.entry(std::vec::Vec::<u8>::from($name));
         if let std::collections::hash_map::Entry::Occupied(_) = entry {
@@ -149,6 +194,14 @@ macro_rules! params {
         }
     );
     (@expand $map:expr;) => {};
+    (@expand $map:expr; $name:expr =>? $value:expr, $($tail:tt)*) => {
+        params!(@to_pair $map, $name =>? $value);
+        params!(@expand $map; $($tail)*);
+    };
+    (@expand $map:expr; $name:expr =>? $value:expr $(, $tail:tt)*) => {
+        params!(@to_pair $map, $name =>? $value);
+        params!(@expand $map; $($tail)*);
+    };
     (@expand $map:expr; $name:expr => $value:expr, $($tail:tt)*) => {
         params!(@to_pair $map, $name => $value);
         params!(@expand $map; $($tail)*);
@@ -188,8 +241,24 @@ macro_rules! params {
     }
 }
 
+/// Emits a warning about a recoverable protocol issue (e.g. ignored trailing bytes
+/// or unknown flags). It's a no-op unless the `log` feature is enabled.
+macro_rules! parse_warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::warn!(target: "mysql_common::parse", $($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
 pub mod constants;
 pub mod crypto;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod interpolation;
 pub mod io;
 pub mod misc;
 pub mod named_params;
@@ -199,6 +268,7 @@ pub mod params;
 pub mod proto;
 pub mod row;
 pub mod scramble;
+mod serde_impls;
 pub mod value;
 
 pub mod binlog;
@@ -275,6 +345,21 @@ fn params_macro_test() {
         ]),
         params! { "foo" => foo, bar, }
     );
+
+    let some = Some(1);
+    let none: Option<u8> = None;
+    assert_eq!(
+        Params::from(vec![
+            (String::from("foo"), Value::Int(42)),
+            (String::from("some"), Value::Int(1)),
+            (String::from("null"), Value::NULL),
+        ]),
+        params! { foo, "some" =>? some, "none" =>? none, "null" => none }
+    );
+    assert_eq!(
+        Params::Named(Default::default()),
+        params! { "none" =>? none }
+    );
 }
 
 #[test]
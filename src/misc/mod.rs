@@ -6,9 +6,15 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::io::{self};
+use std::{
+    borrow::Cow,
+    io::{self},
+};
 
 pub mod raw;
+pub mod retry;
+#[cfg(feature = "chrono-tz")]
+pub mod time_zone;
 
 /// Returns length of length-encoded-integer representation of `x`.
 pub fn lenenc_int_len(x: u64) -> u64 {
@@ -63,6 +69,50 @@ pub fn split_version<T: AsRef<[u8]>>(version_str: T) -> (u8, u8, u8) {
     (nums[0], nums[1], nums[2])
 }
 
+/// Compares two identifiers, that are case-insensitive on every platform
+/// (column names and aliases, index, stored routine and event names).
+pub fn ident_eq_ignore_case(a: &str, b: &str) -> bool {
+    if a.is_ascii() && b.is_ascii() {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a.to_lowercase() == b.to_lowercase()
+    }
+}
+
+/// Compares two database, table or trigger names (or table aliases) according to the value
+/// of the `lower_case_table_names` server variable:
+///
+/// *   `0` — names are case-sensitive;
+/// *   `1` — names are stored in lowercase and compared case-insensitively;
+/// *   `2` — names are stored as given and compared case-insensitively.
+pub fn ident_eq(a: &str, b: &str, lower_case_table_names: u8) -> bool {
+    if lower_case_table_names == 0 {
+        a == b
+    } else {
+        ident_eq_ignore_case(a, b)
+    }
+}
+
+/// Normalizes a database, table or trigger name, so that names equal in terms of [`ident_eq`]
+/// are normalized to the same value (i.e. to use as a key of a metadata cache).
+pub fn normalize_ident(name: &str, lower_case_table_names: u8) -> Cow<'_, str> {
+    if lower_case_table_names == 0 {
+        Cow::Borrowed(name)
+    } else {
+        normalize_ident_ignore_case(name)
+    }
+}
+
+/// Normalizes an identifier, that is case-insensitive on every platform
+/// (see [`ident_eq_ignore_case`]).
+pub fn normalize_ident_ignore_case(name: &str) -> Cow<'_, str> {
+    if name.chars().any(char::is_uppercase) {
+        Cow::Owned(name.to_lowercase())
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,4 +125,19 @@ mod tests {
         assert_eq!((0, 0, 0), split_version("100.200foo"));
         assert_eq!((0, 0, 0), split_version("1,2.3"));
     }
+
+    #[test]
+    fn should_compare_idents() {
+        assert!(ident_eq("Users", "Users", 0));
+        assert!(!ident_eq("Users", "users", 0));
+        assert!(ident_eq("Users", "users", 1));
+        assert!(ident_eq("Пользователи", "пользователи", 2));
+        assert!(ident_eq_ignore_case("ID", "id"));
+        assert!(!ident_eq_ignore_case("id", "idx"));
+
+        assert_eq!(normalize_ident("Users", 0), "Users");
+        assert_eq!(normalize_ident("Users", 2), "users");
+        assert!(matches!(normalize_ident("users", 1), Cow::Borrowed(_)));
+        assert_eq!(normalize_ident_ignore_case("ÄÖ"), "äö");
+    }
 }
@@ -171,6 +171,8 @@ def_end_repr! {
     LeI24, i32, Some(3), put_i24_le, eat_i24_le;
     /// Little-endian i32.
     LeI32, i32, Some(4), put_i32_le, eat_i32_le;
+    /// Little-endian i48.
+    LeI48, i64, Some(6), put_i48_le, eat_i48_le;
     /// Little-endian i56.
     LeI56, i64, Some(7), put_i56_le, eat_i56_le;
     /// Little-endian i64.
@@ -8,13 +8,13 @@
 
 //! Various parsing/serialization primitives.
 
-use std::io;
+use std::{io, sync::Arc};
 
 use ::bytes::BufMut;
 use smallvec::{Array, SmallVec};
 
 use crate::{
-    io::ParseBuf,
+    io::{BufMutExt, ParseBuf},
     proto::{MyDeserialize, MySerialize},
 };
 
@@ -121,6 +121,23 @@ where
     }
 }
 
+/// Parses a length-encoded string into a shared byte slice.
+impl<'de> MyDeserialize<'de> for Arc<[u8]> {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let s: RawBytes<'de, LenEnc> = buf.parse(())?;
+        Ok(Arc::from(s.as_bytes()))
+    }
+}
+
+impl MySerialize for Arc<[u8]> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        buf.put_lenenc_str(&self[..])
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Either<T, U> {
     Left(T),
@@ -6,7 +6,7 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::{borrow::Cow, convert::TryFrom, fmt, io, marker::PhantomData, ops::Deref};
+use std::{borrow::Cow, cmp::min, convert::TryFrom, fmt, io, marker::PhantomData, ops::Deref};
 
 use bytes::BufMut;
 
@@ -135,10 +135,11 @@ macro_rules! impl_seq_repr {
                 T: MyDeserialize<'de, Ctx = ()>,
             {
                 let len = *buf.parse::<RawInt<$name>>(())? as usize;
-                let mut seq = Vec::with_capacity(len);
+                // the length comes from the input, so it's bounded by the remaining bytes
+                let mut seq = Vec::with_capacity(min(len, buf.len()));
                 match T::SIZE {
                     Some(count) => {
-                        let mut buf: ParseBuf = buf.parse(count * len)?;
+                        let mut buf: ParseBuf = buf.parse(count.saturating_mul(len))?;
                         for _ in 0..len {
                             seq.push(buf.parse(())?);
                         }
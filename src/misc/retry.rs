@@ -0,0 +1,145 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Classification of server and client errors for retry purposes.
+
+const ER_CON_COUNT_ERROR: u16 = 1040;
+const ER_SERVER_SHUTDOWN: u16 = 1053;
+const ER_ABORTING_CONNECTION: u16 = 1152;
+const ER_NET_READ_ERROR: u16 = 1158;
+const ER_NET_READ_INTERRUPTED: u16 = 1159;
+const ER_NET_ERROR_ON_WRITE: u16 = 1160;
+const ER_NET_WRITE_INTERRUPTED: u16 = 1161;
+const ER_LOCK_WAIT_TIMEOUT: u16 = 1205;
+const ER_LOCK_DEADLOCK: u16 = 1213;
+const ER_OPTION_PREVENTS_STATEMENT: u16 = 1290;
+const ER_TABLE_DEF_CHANGED: u16 = 1412;
+const ER_XA_RBDEADLOCK: u16 = 1614;
+const ER_TOO_MANY_CONCURRENT_TRXS: u16 = 1637;
+const ER_READ_ONLY_MODE: u16 = 1836;
+const ER_CONNECTION_KILLED: u16 = 1927;
+const ER_TRANSACTION_ROLLBACK_DURING_COMMIT: u16 = 3101;
+const ER_SESSION_WAS_KILLED: u16 = 3169;
+const ER_CLIENT_INTERACTION_TIMEOUT: u16 = 4031;
+const CR_CONNECTION_ERROR: u16 = 2002;
+const CR_CONN_HOST_ERROR: u16 = 2003;
+const CR_SERVER_GONE_ERROR: u16 = 2006;
+const CR_SERVER_LOST: u16 = 2013;
+
+/// What should be retried after an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryClass {
+    /// Only the failed statement was rolled back, so it could be retried
+    /// within the current transaction.
+    RetryInTransaction,
+    /// The whole transaction was rolled back (or the connection is lost),
+    /// so the transaction should be retried from the beginning
+    /// (on a new connection, if the connection is lost).
+    RetryNewTransaction,
+    /// Retry won't help.
+    DontRetry,
+}
+
+impl RetryClass {
+    /// Returns `true` if it makes sense to retry anything.
+    pub fn is_retryable(self) -> bool {
+        self != RetryClass::DontRetry
+    }
+}
+
+/// Retry classifier.
+///
+/// Some errors change their meaning depending on the server configuration,
+/// so the classification is parameterized (see [`RetryClassifier::rollback_on_timeout`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RetryClassifier {
+    rollback_on_timeout: bool,
+}
+
+impl RetryClassifier {
+    /// Creates a classifier for the default server configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines the value of the `innodb_rollback_on_timeout` server variable (defaults to `false`).
+    ///
+    /// If it is set, then a lock wait timeout rolls back the whole transaction
+    /// rather than the failed statement.
+    pub fn rollback_on_timeout(mut self, rollback_on_timeout: bool) -> Self {
+        self.rollback_on_timeout = rollback_on_timeout;
+        self
+    }
+
+    /// Classifies a server error code or a client error code (`CR_*`).
+    pub fn classify(&self, error_code: u16) -> RetryClass {
+        match error_code {
+            ER_LOCK_WAIT_TIMEOUT if !self.rollback_on_timeout => RetryClass::RetryInTransaction,
+            ER_LOCK_WAIT_TIMEOUT
+            | ER_LOCK_DEADLOCK
+            | ER_XA_RBDEADLOCK
+            | ER_TABLE_DEF_CHANGED
+            | ER_TOO_MANY_CONCURRENT_TRXS
+            | ER_TRANSACTION_ROLLBACK_DURING_COMMIT => RetryClass::RetryNewTransaction,
+            // read-only server (i.e. a former primary after a failover)
+            ER_OPTION_PREVENTS_STATEMENT | ER_READ_ONLY_MODE => RetryClass::RetryNewTransaction,
+            // connection-level errors
+            ER_CON_COUNT_ERROR
+            | ER_SERVER_SHUTDOWN
+            | ER_ABORTING_CONNECTION
+            | ER_NET_READ_ERROR
+            | ER_NET_READ_INTERRUPTED
+            | ER_NET_ERROR_ON_WRITE
+            | ER_NET_WRITE_INTERRUPTED
+            | ER_CONNECTION_KILLED
+            | ER_SESSION_WAS_KILLED
+            | ER_CLIENT_INTERACTION_TIMEOUT
+            | CR_CONNECTION_ERROR
+            | CR_CONN_HOST_ERROR
+            | CR_SERVER_GONE_ERROR
+            | CR_SERVER_LOST => RetryClass::RetryNewTransaction,
+            _ => RetryClass::DontRetry,
+        }
+    }
+}
+
+/// Classifies an error code using the default [`RetryClassifier`].
+///
+/// ```
+/// # use mysql_common::misc::retry::{retry_class, RetryClass};
+/// assert_eq!(retry_class(1205), RetryClass::RetryInTransaction);
+/// assert_eq!(retry_class(1213), RetryClass::RetryNewTransaction);
+/// assert_eq!(retry_class(1064), RetryClass::DontRetry);
+/// ```
+pub fn retry_class(error_code: u16) -> RetryClass {
+    RetryClassifier::new().classify(error_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::ServerError;
+
+    #[test]
+    fn should_classify_errors() {
+        assert_eq!(retry_class(1205), RetryClass::RetryInTransaction);
+        assert_eq!(
+            RetryClassifier::new()
+                .rollback_on_timeout(true)
+                .classify(1205),
+            RetryClass::RetryNewTransaction
+        );
+        for code in [1213, 1290, 1836, 2006, 2013, 4031] {
+            assert_eq!(retry_class(code), RetryClass::RetryNewTransaction);
+        }
+        assert!(!retry_class(1062).is_retryable());
+
+        let error = ServerError::new(1213, *b"40001", &b"Deadlock found"[..]);
+        assert_eq!(error.retry_class(), RetryClass::RetryNewTransaction);
+    }
+}
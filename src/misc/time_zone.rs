@@ -0,0 +1,180 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Client-side validation of `time_zone` values (requires the `chrono-tz` feature).
+//!
+//! Named time zones are validated against the IANA database bundled with `chrono-tz`,
+//! so the server's time zone tables are not consulted (the server may still reject a valid
+//! name if its tables are not loaded).
+
+use chrono_tz::{Tz, TZ_VARIANTS};
+
+use std::{fmt, str::FromStr};
+
+use crate::packets::session_state_change::SystemVariable;
+
+/// Minimum offset supported by MySql (`-13:59`) in minutes.
+const MIN_OFFSET_MINUTES: i16 = -(13 * 60 + 59);
+/// Maximum offset supported by MySql (`+14:00`) in minutes.
+const MAX_OFFSET_MINUTES: i16 = 14 * 60;
+
+/// Invalid value of the `time_zone` variable.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid time zone `{}`", _0)]
+pub struct InvalidTimeZone(pub String);
+
+/// A valid value of the `time_zone` variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeZone {
+    /// `SYSTEM`.
+    System,
+    /// Offset from UTC in minutes (`-13:59` through `+14:00`).
+    Offset(i16),
+    /// Named time zone.
+    Named(Tz),
+}
+
+impl TimeZone {
+    /// Parses a `time_zone` value reported by the session state tracker.
+    ///
+    /// Returns `None` if the variable is not `time_zone`.
+    pub fn from_system_variable(
+        variable: &SystemVariable<'_>,
+    ) -> Option<Result<Self, InvalidTimeZone>> {
+        if variable.name_bytes().eq_ignore_ascii_case(b"time_zone") {
+            Some(variable.value_str().parse())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the `SET time_zone = ..` statement for this time zone.
+    pub fn set_statement(&self) -> String {
+        format!("SET time_zone = '{}'", self)
+    }
+}
+
+fn parse_offset(value: &str) -> Option<i16> {
+    let (sign, rest) = match value.as_bytes().first()? {
+        b'+' => (1, &value[1..]),
+        b'-' => (-1, &value[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = rest.split_once(':')?;
+    if hours.is_empty() || hours.len() > 2 || minutes.len() != 2 {
+        return None;
+    }
+    if !hours
+        .bytes()
+        .chain(minutes.bytes())
+        .all(|x| x.is_ascii_digit())
+    {
+        return None;
+    }
+    let minutes: i16 = minutes.parse().ok()?;
+    if minutes > 59 {
+        return None;
+    }
+    let offset = sign * (hours.parse::<i16>().ok()? * 60 + minutes);
+    if (MIN_OFFSET_MINUTES..=MAX_OFFSET_MINUTES).contains(&offset) {
+        Some(offset)
+    } else {
+        None
+    }
+}
+
+impl FromStr for TimeZone {
+    type Err = InvalidTimeZone;
+
+    /// Case-insensitive (the same way as MySql).
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let trimmed = value.trim();
+        if trimmed.eq_ignore_ascii_case("SYSTEM") {
+            return Ok(TimeZone::System);
+        }
+        if let Some(offset) = parse_offset(trimmed) {
+            return Ok(TimeZone::Offset(offset));
+        }
+        trimmed
+            .parse::<Tz>()
+            .ok()
+            .or_else(|| {
+                TZ_VARIANTS
+                    .iter()
+                    .find(|tz| tz.name().eq_ignore_ascii_case(trimmed))
+                    .copied()
+            })
+            .map(TimeZone::Named)
+            .ok_or_else(|| InvalidTimeZone(value.into()))
+    }
+}
+
+/// Formats the normalized value (`SYSTEM`, `+hh:mm` or the canonical zone name).
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TimeZone::System => f.write_str("SYSTEM"),
+            TimeZone::Offset(offset) => write!(
+                f,
+                "{}{:02}:{:02}",
+                if offset < 0 { '-' } else { '+' },
+                offset.abs() / 60,
+                offset.abs() % 60
+            ),
+            TimeZone::Named(tz) => f.write_str(tz.name()),
+        }
+    }
+}
+
+/// Validates and normalizes a `time_zone` value.
+///
+/// ```
+/// # use mysql_common::misc::time_zone::normalize_time_zone;
+/// assert_eq!(normalize_time_zone("europe/moscow").unwrap(), "Europe/Moscow");
+/// assert_eq!(normalize_time_zone("+5:30").unwrap(), "+05:30");
+/// assert_eq!(normalize_time_zone("system").unwrap(), "SYSTEM");
+/// assert!(normalize_time_zone("Mars/Olympus_Mons").is_err());
+/// ```
+pub fn normalize_time_zone(value: &str) -> Result<String, InvalidTimeZone> {
+    value.parse::<TimeZone>().map(|tz| tz.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_validate_time_zones() {
+        assert_eq!("UTC".parse(), Ok(TimeZone::Named(Tz::UTC)));
+        assert_eq!(
+            " america/new_york ".parse(),
+            Ok(TimeZone::Named(Tz::America__New_York))
+        );
+        assert_eq!("-13:59".parse(), Ok(TimeZone::Offset(-839)));
+        assert_eq!("+14:00".parse(), Ok(TimeZone::Offset(840)));
+        for invalid in ["+14:01", "-14:00", "+1:60", "+01", "01:00", "", "'UTC'"] {
+            assert_eq!(
+                invalid.parse::<TimeZone>(),
+                Err(InvalidTimeZone(invalid.into()))
+            );
+        }
+
+        assert_eq!(
+            TimeZone::Offset(-90).set_statement(),
+            "SET time_zone = '-01:30'"
+        );
+
+        let variable = SystemVariable::new(&b"time_zone"[..], &b"europe/berlin"[..]);
+        assert_eq!(
+            TimeZone::from_system_variable(&variable),
+            Some(Ok(TimeZone::Named(Tz::Europe__Berlin)))
+        );
+        let variable = SystemVariable::new(&b"autocommit"[..], &b"ON"[..]);
+        assert_eq!(TimeZone::from_system_variable(&variable), None);
+    }
+}
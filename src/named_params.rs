@@ -6,101 +6,512 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap, ops::Range};
 
-/// Appears if a statement have both named and positional parameters.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub struct MixedParamsError;
+use crate::{
+    params::{List, MissingNamedParameterError, Params},
+    value::Value,
+};
 
-enum ParserState {
-    TopLevel,
-    // (string_delimiter, last_char)
-    InStringLiteral(u8, u8),
-    MaybeInNamedParam,
-    InNamedParam,
+/// Error of parsing named parameters of a statement.
+///
+/// Offsets are in bytes from the start of the query.
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum NamedParamsError {
+    /// Statement has both named and positional parameters.
+    #[error(
+        "Named and positional parameters are mixed (positional parameters at offsets {:?})",
+        positions
+    )]
+    MixedPlaceholders {
+        /// Offsets of positional parameters.
+        positions: Vec<usize>,
+    },
+    /// Name of a named parameter is invalid (names must start with a lowercase ASCII letter
+    /// or an underscore, followed by lowercase ASCII letters, digits and underscores).
+    #[error("Invalid parameter name at offsets {}..{}", span.start, span.end)]
+    InvalidName {
+        /// Offsets of the name (including the leading colon).
+        span: Range<usize>,
+    },
+}
+
+/// SQL mode dependent options of the query lexer
+/// (see [`parse_named_params_with_options`]).
+///
+/// ```
+/// # use mysql_common::named_params::{parse_named_params, parse_named_params_with_options};
+/// # use mysql_common::named_params::ParseOptions;
+/// let query = br#"SELECT "a\", :b"#;
+/// assert_eq!(parse_named_params(query).unwrap().0, None);
+///
+/// let options = ParseOptions::new().with_ansi_quotes(true);
+/// let (names, _) = parse_named_params_with_options(query, options).unwrap();
+/// assert_eq!(names, Some(vec![b"b".to_vec()]));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    ansi_quotes: bool,
+    no_backslash_escapes: bool,
+}
+
+impl ParseOptions {
+    /// Creates default options (both SQL modes are disabled).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines whether the `ANSI_QUOTES` SQL mode is enabled,
+    /// i.e. whether `"` quotes identifiers rather than string literals.
+    pub fn with_ansi_quotes(mut self, ansi_quotes: bool) -> Self {
+        self.ansi_quotes = ansi_quotes;
+        self
+    }
+
+    /// Defines whether the `NO_BACKSLASH_ESCAPES` SQL mode is enabled,
+    /// i.e. whether the backslash is an ordinary character within string literals.
+    pub fn with_no_backslash_escapes(mut self, no_backslash_escapes: bool) -> Self {
+        self.no_backslash_escapes = no_backslash_escapes;
+        self
+    }
+
+    /// Returns `true` if the `ANSI_QUOTES` SQL mode is enabled.
+    pub fn ansi_quotes(&self) -> bool {
+        self.ansi_quotes
+    }
+
+    /// Returns `true` if the `NO_BACKSLASH_ESCAPES` SQL mode is enabled.
+    pub fn no_backslash_escapes(&self) -> bool {
+        self.no_backslash_escapes
+    }
+}
+
+/// Placeholder found in a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Placeholder {
+    /// Named parameter (the range includes the leading colon).
+    Named(Range<usize>),
+    /// Positional parameter (offset of the `?`).
+    Positional(usize),
+    /// Invalid named parameter (the range includes the leading colon).
+    InvalidNamed(Range<usize>),
+}
+
+/// Finds placeholders in a query skipping string literals, quoted identifiers, comments
+/// and variables.
+fn scan(query: &[u8], options: ParseOptions) -> Vec<Placeholder> {
+    let backslash_escapes = !options.no_backslash_escapes;
+    let mut placeholders = Vec::new();
+    let mut i = 0;
+    while i < query.len() {
+        i = match query[i] {
+            b'"' if options.ansi_quotes => skip_quoted(query, i + 1, b'"', false),
+            quote @ b'\'' | quote @ b'"' => skip_quoted(query, i + 1, quote, backslash_escapes),
+            b'`' => skip_quoted(query, i + 1, b'`', false),
+            b'#' => skip_line(query, i + 1),
+            // `--` starts a comment only if followed by a whitespace or a control character
+            b'-' if query.get(i + 1) == Some(&b'-')
+                && query
+                    .get(i + 2)
+                    .map_or(true, |x| x.is_ascii_whitespace() || x.is_ascii_control()) =>
+            {
+                skip_line(query, i + 2)
+            }
+            // `/*! .. */` is not a comment but a code for MySql
+            b'/' if query.get(i + 1) == Some(&b'*') && query.get(i + 2) != Some(&b'!') => {
+                skip_block_comment(query, i + 2)
+            }
+            b'@' => skip_variable(query, i + 1),
+            b'?' => {
+                placeholders.push(Placeholder::Positional(i));
+                i + 1
+            }
+            // assignment operator
+            b':' if query.get(i + 1) == Some(&b'=') => i + 2,
+            b':' => match name_len(&query[i + 1..]) {
+                0 => match invalid_name_len(&query[i + 1..]) {
+                    0 => i + 1,
+                    len => {
+                        placeholders.push(Placeholder::InvalidNamed(i..i + 1 + len));
+                        i + 1 + len
+                    }
+                },
+                len => {
+                    placeholders.push(Placeholder::Named(i..i + 1 + len));
+                    i + 1 + len
+                }
+            },
+            _ => i + 1,
+        };
+    }
+    placeholders
+}
+
+/// Returns the length of a parameter name at the start of the input (`0` if there is none).
+fn name_len(input: &[u8]) -> usize {
+    match input.first() {
+        Some(b'a'..=b'z') | Some(b'_') => input
+            .iter()
+            .position(|x| !matches!(x, b'a'..=b'z' | b'0'..=b'9' | b'_'))
+            .unwrap_or_else(|| input.len()),
+        _ => 0,
+    }
+}
+
+/// Returns the length of something, that looks like an invalid parameter name
+/// (i.e. an identifier starting with an uppercase ASCII letter or a non-ASCII character).
+fn invalid_name_len(input: &[u8]) -> usize {
+    match input.first() {
+        Some(b'A'..=b'Z') | Some(0x80..=0xFF) => input
+            .iter()
+            .position(|x| !(x.is_ascii_alphanumeric() || *x == b'_' || *x == b'$' || *x >= 0x80))
+            .unwrap_or_else(|| input.len()),
+        _ => 0,
+    }
+}
+
+/// Skips a name of a user variable (`@var`) or a system variable (`@@var`, `@@session.var`),
+/// `pos` is right after the first `@`.
+///
+/// Quoted names (e.g. `@'var'`) are left to the caller.
+fn skip_variable(query: &[u8], mut pos: usize) -> usize {
+    if query.get(pos) == Some(&b'@') {
+        pos += 1;
+    }
+    match query[pos..]
+        .iter()
+        .position(|x| !(x.is_ascii_alphanumeric() || matches!(x, b'_' | b'$' | b'.') || *x >= 0x80))
+    {
+        Some(offset) => pos + offset,
+        None => query.len(),
+    }
+}
+
+/// Skips a quoted string or identifier (`pos` is right after the opening quote).
+///
+/// Returns the offset right after the closing quote.
+fn skip_quoted(query: &[u8], mut pos: usize, quote: u8, backslash_escapes: bool) -> usize {
+    while pos < query.len() {
+        match query[pos] {
+            b'\\' if backslash_escapes => pos += 2,
+            x if x == quote => {
+                // quote is escaped by doubling it
+                if query.get(pos + 1) == Some(&quote) {
+                    pos += 2;
+                } else {
+                    return pos + 1;
+                }
+            }
+            _ => pos += 1,
+        }
+    }
+    query.len()
+}
+
+/// Skips a single-line comment. Returns the offset right after the line end.
+fn skip_line(query: &[u8], pos: usize) -> usize {
+    match query[pos..].iter().position(|x| *x == b'\n') {
+        Some(offset) => pos + offset + 1,
+        None => query.len(),
+    }
 }
 
-use self::ParserState::*;
+/// Skips a multi-line comment. Returns the offset right after the `*/`.
+fn skip_block_comment(query: &[u8], pos: usize) -> usize {
+    match query[pos..].windows(2).position(|x| x == b"*/") {
+        Some(offset) => pos + offset + 2,
+        None => query.len(),
+    }
+}
 
 /// Returns pair of:
 ///
 /// * names of named parameters (if any) in order of appearance in `query`. Same name may
 ///   appear multiple times if named parameter used more than once.
 /// * query string to pass to MySql (named parameters replaced with `?`).
+///
+/// String literals, quoted identifiers, comments, names of user and system variables
+/// (`@var`, `@@var`) and the assignment operator (`:=`) are skipped.
+///
+/// Fails if named parameters are mixed with positional ones or if `query` uses named
+/// parameters and contains an invalid parameter name, e.g. `:Name` (see [`NamedParamsError`]).
+///
+/// Query is lexed as if both `ANSI_QUOTES` and `NO_BACKSLASH_ESCAPES` SQL modes are disabled
+/// (see [`parse_named_params_with_options`]).
 pub fn parse_named_params(
     query: &[u8],
-) -> Result<(Option<Vec<Vec<u8>>>, Cow<'_, [u8]>), MixedParamsError> {
-    let mut state = TopLevel;
-    let mut have_positional = false;
-    let mut cur_param = 0;
-    // Vec<(start_offset, end_offset, name)>
-    let mut params = Vec::new();
-    for (i, c) in query.iter().enumerate() {
-        let mut rematch = false;
-        match state {
-            TopLevel => match c {
-                b':' => state = MaybeInNamedParam,
-                b'\'' => state = InStringLiteral(b'\'', b'\''),
-                b'"' => state = InStringLiteral(b'"', b'"'),
-                b'?' => have_positional = true,
-                _ => (),
-            },
-            InStringLiteral(separator, prev_char) => match c {
-                x if *x == separator && prev_char != b'\\' => state = TopLevel,
-                x => state = InStringLiteral(separator, *x),
-            },
-            MaybeInNamedParam => match c {
-                b'a'..=b'z' | b'_' => {
-                    params.push((i - 1, 0, Vec::with_capacity(16)));
-                    params[cur_param].2.push(*c);
-                    state = InNamedParam;
-                }
-                _ => rematch = true,
-            },
-            InNamedParam => match c {
-                b'a'..=b'z' | b'0'..=b'9' | b'_' => params[cur_param].2.push(*c),
-                _ => {
-                    params[cur_param].1 = i;
-                    cur_param += 1;
-                    rematch = true;
-                }
-            },
+) -> Result<(Option<Vec<Vec<u8>>>, Cow<'_, [u8]>), NamedParamsError> {
+    parse_named_params_with_options(query, ParseOptions::new())
+}
+
+/// Same as [`parse_named_params`] but lexes the query according to the given SQL mode options.
+pub fn parse_named_params_with_options(
+    query: &[u8],
+    options: ParseOptions,
+) -> Result<(Option<Vec<Vec<u8>>>, Cow<'_, [u8]>), NamedParamsError> {
+    let parsed = parse_named_params_spanned_with_options(query, options)?;
+    if parsed.params.is_empty() {
+        return Ok((None, parsed.query));
+    }
+    let names = parsed.params.into_iter().map(|param| param.name).collect();
+    Ok((Some(names), parsed.query))
+}
+
+/// Occurrence of a named parameter in a query (see [`parse_named_params_spanned`]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NamedParamSpan {
+    /// Name of the parameter (without the leading colon).
+    pub name: Vec<u8>,
+    /// Byte range of the `:name` in the original query.
+    pub span: Range<usize>,
+    /// Offset of the replacing `?` in the rewritten query.
+    pub position: usize,
+}
+
+/// Result of [`parse_named_params_spanned`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpannedNamedParams<'a> {
+    /// Query string to pass to MySql (named parameters replaced with `?`).
+    pub query: Cow<'a, [u8]>,
+    /// Named parameters in order of appearance, i.e. the index of an item is the index
+    /// of the corresponding positional parameter.
+    pub params: Vec<NamedParamSpan>,
+}
+
+/// Same as [`parse_named_params`] but also returns byte ranges of named parameters
+/// in the original query and offsets of corresponding `?` in the rewritten query
+/// (e.g. to annotate or reconstruct the original query).
+///
+/// ```
+/// # use mysql_common::named_params::{parse_named_params_spanned, NamedParamSpan};
+/// let parsed = parse_named_params_spanned(b"SELECT :foo, :b").unwrap();
+/// assert_eq!(&*parsed.query, b"SELECT ?, ?");
+/// assert_eq!(
+///     parsed.params,
+///     vec![
+///         NamedParamSpan { name: b"foo".to_vec(), span: 7..11, position: 7 },
+///         NamedParamSpan { name: b"b".to_vec(), span: 13..15, position: 10 },
+///     ],
+/// );
+/// ```
+pub fn parse_named_params_spanned(
+    query: &[u8],
+) -> Result<SpannedNamedParams<'_>, NamedParamsError> {
+    parse_named_params_spanned_with_options(query, ParseOptions::new())
+}
+
+/// Same as [`parse_named_params_spanned`] but lexes the query according to the given
+/// SQL mode options.
+pub fn parse_named_params_spanned_with_options(
+    query: &[u8],
+    options: ParseOptions,
+) -> Result<SpannedNamedParams<'_>, NamedParamsError> {
+    let placeholders = scan(query, options);
+    if !check_placeholders(&placeholders)? {
+        return Ok(SpannedNamedParams {
+            query: query.into(),
+            params: Vec::new(),
+        });
+    }
+
+    let mut real_query = Vec::with_capacity(query.len());
+    let mut last = 0;
+    let mut params = Vec::with_capacity(placeholders.len());
+    for placeholder in placeholders {
+        if let Placeholder::Named(range) = placeholder {
+            real_query.extend(&query[last..range.start]);
+            params.push(NamedParamSpan {
+                name: query[range.start + 1..range.end].to_vec(),
+                span: range.clone(),
+                position: real_query.len(),
+            });
+            real_query.push(b'?');
+            last = range.end;
         }
-        if rematch {
-            match c {
-                b':' => state = MaybeInNamedParam,
-                b'\'' => state = InStringLiteral(b'\'', b'\''),
-                b'"' => state = InStringLiteral(b'"', b'"'),
-                _ => state = TopLevel,
+    }
+    real_query.extend(&query[last..]);
+    Ok(SpannedNamedParams {
+        query: real_query.into(),
+        params,
+    })
+}
+
+/// Returns offsets of positional parameters (`?`) of the query.
+pub(crate) fn positional_placeholders(query: &[u8], options: ParseOptions) -> Vec<usize> {
+    scan(query, options)
+        .into_iter()
+        .filter_map(|placeholder| match placeholder {
+            Placeholder::Positional(offset) => Some(offset),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns `true` if there are named parameters, fails if they're invalid or mixed
+/// with positional ones.
+///
+/// Invalid names are only reported if the query uses named parameters, so that things like
+/// `lbl: BEGIN` or `':É'` in a query with positional parameters are left intact.
+fn check_placeholders(placeholders: &[Placeholder]) -> Result<bool, NamedParamsError> {
+    let mut positions = Vec::new();
+    let mut have_named = false;
+    let mut invalid = None;
+    for placeholder in placeholders {
+        match placeholder {
+            Placeholder::Named(_) => have_named = true,
+            Placeholder::Positional(offset) => positions.push(*offset),
+            Placeholder::InvalidNamed(span) => {
+                invalid.get_or_insert_with(|| span.clone());
             }
         }
     }
-    if let InNamedParam = state {
-        params[cur_param].1 = query.len();
+
+    if !have_named {
+        return Ok(false);
     }
-    if !params.is_empty() {
-        if have_positional {
-            return Err(MixedParamsError);
-        }
-        let mut real_query = Vec::with_capacity(query.len());
-        let mut last = 0;
-        let mut out_params = Vec::with_capacity(params.len());
-        for (start, end, name) in params.into_iter() {
-            real_query.extend(&query[last..start]);
-            real_query.push(b'?');
-            last = end;
-            out_params.push(name);
+    if let Some(span) = invalid {
+        return Err(NamedParamsError::InvalidName { span });
+    }
+    if !positions.is_empty() {
+        return Err(NamedParamsError::MixedPlaceholders { positions });
+    }
+    Ok(have_named)
+}
+
+/// Error of [`expand_named_params`].
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum ExpandParamsError {
+    /// Query is invalid.
+    #[error(transparent)]
+    NamedParams(#[from] NamedParamsError),
+    /// Value of a named parameter is not given.
+    #[error(transparent)]
+    MissingNamedParameter(#[from] MissingNamedParameterError),
+}
+
+/// Parses named parameters of the `query` and binds them, expanding lists.
+///
+/// Every occurrence of a named parameter, that is bound to a [`List`] in `lists`, is replaced
+/// with as many `?` as there are values in the list (an empty list is replaced with `NULL`,
+/// so that `IN (:ids)` matches nothing). Other named parameters are taken from `params`
+/// (see [`Params::into_positional`]).
+///
+/// Returns the query to pass to MySql and positional parameters. If there are no named
+/// parameters in the `query`, then it is returned as is along with the given `params`.
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use mysql_common::{named_params::expand_named_params, params, params::{List, Params}};
+/// # use mysql_common::value::Value;
+/// let mut lists = HashMap::new();
+/// lists.insert(b"ids".to_vec(), List::from(vec![1, 2, 3]));
+///
+/// let (query, params) = expand_named_params(
+///     b"SELECT * FROM t WHERE kind = :kind AND id IN (:ids)",
+///     params! { "kind" => "foo" },
+///     &lists,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(&*query, &b"SELECT * FROM t WHERE kind = ? AND id IN (?, ?, ?)"[..]);
+/// assert_eq!(
+///     params,
+///     Params::Positional(vec![Value::from("foo"), Value::Int(1), Value::Int(2), Value::Int(3)]),
+/// );
+/// ```
+///
+/// Query is lexed as if both `ANSI_QUOTES` and `NO_BACKSLASH_ESCAPES` SQL modes are disabled
+/// (see [`expand_named_params_with_options`]).
+pub fn expand_named_params<'a>(
+    query: &'a [u8],
+    params: Params,
+    lists: &HashMap<Vec<u8>, List>,
+) -> Result<(Cow<'a, [u8]>, Params), ExpandParamsError> {
+    expand_named_params_with_options(query, params, lists, ParseOptions::new())
+}
+
+/// Same as [`expand_named_params`] but lexes the query according to the given SQL mode options.
+pub fn expand_named_params_with_options<'a>(
+    query: &'a [u8],
+    params: Params,
+    lists: &HashMap<Vec<u8>, List>,
+    options: ParseOptions,
+) -> Result<(Cow<'a, [u8]>, Params), ExpandParamsError> {
+    let placeholders = scan(query, options);
+    if !check_placeholders(&placeholders)? {
+        return Ok((query.into(), params));
+    }
+
+    let mut real_query = Vec::with_capacity(query.len());
+    let mut last = 0;
+    let mut names = Vec::with_capacity(placeholders.len());
+    let mut values: Vec<Option<Value>> = Vec::with_capacity(placeholders.len());
+    for placeholder in placeholders {
+        let range = match placeholder {
+            Placeholder::Named(range) => range,
+            _ => continue,
+        };
+        real_query.extend(&query[last..range.start]);
+        last = range.end;
+
+        let name = &query[range.start + 1..range.end];
+        match lists.get(name) {
+            Some(List(list)) if list.is_empty() => real_query.extend(b"NULL"),
+            Some(List(list)) => {
+                for (i, value) in list.iter().enumerate() {
+                    if i > 0 {
+                        real_query.extend(b", ");
+                    }
+                    real_query.push(b'?');
+                    values.push(Some(value.clone()));
+                }
+            }
+            None => {
+                real_query.push(b'?');
+                names.push(name.to_vec());
+                // will be bound later
+                values.push(None);
+            }
         }
-        real_query.extend(&query[last..]);
-        Ok((Some(out_params), real_query.into()))
-    } else {
-        Ok((None, query.into()))
     }
+    real_query.extend(&query[last..]);
+
+    let bound = match params {
+        Params::Named(_) => params.into_positional(&names)?,
+        _ if names.is_empty() => Params::Empty,
+        _ => return Err(MissingNamedParameterError(names[0].clone()).into()),
+    };
+    let mut bound = match bound {
+        Params::Positional(bound) => bound.into_iter(),
+        _ => Vec::new().into_iter(),
+    };
+    // `into_positional` binds every name, so there is a value for every `None`
+    let values = values
+        .into_iter()
+        .filter_map(|value| value.or_else(|| bound.next()))
+        .collect::<Vec<_>>();
+
+    let params = if values.is_empty() {
+        Params::Empty
+    } else {
+        Params::Positional(values)
+    };
+    Ok((real_query.into(), params))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::named_params::parse_named_params;
+    use std::collections::HashMap;
+
+    use crate::{
+        named_params::{
+            expand_named_params, expand_named_params_with_options, parse_named_params,
+            parse_named_params_spanned, parse_named_params_with_options, NamedParamsError,
+            ParseOptions,
+        },
+        params::{List, MissingNamedParameterError, Params},
+        value::Value,
+    };
 
     #[test]
     fn should_parse_named_params() {
@@ -143,6 +554,30 @@ mod test {
         parse_named_params(br":a ?").unwrap_err();
     }
 
+    #[test]
+    fn should_report_errors() {
+        assert_eq!(
+            parse_named_params(b"SELECT :a, ?, ':b', ?"),
+            Err(NamedParamsError::MixedPlaceholders {
+                positions: vec![11, 20]
+            })
+        );
+        assert_eq!(
+            parse_named_params(b"SELECT :a, :Name1"),
+            Err(NamedParamsError::InvalidName { span: 11..17 })
+        );
+        assert_eq!(
+            parse_named_params("SELECT :a, :été".as_bytes()),
+            Err(NamedParamsError::InvalidName { span: 11..17 })
+        );
+        assert_eq!(parse_named_params(b"SELECT ':Name', :1").unwrap().0, None);
+
+        // invalid names are only reported if the query uses named parameters
+        for query in [&b"lbl: BEGIN SELECT ?; END"[..], "SELECT ?, a:É".as_bytes()] {
+            assert_eq!(parse_named_params(query), Ok((None, query.into())));
+        }
+    }
+
     #[test]
     fn should_allow_numbers_in_param_name() {
         let result = parse_named_params(b":a1 :a2").unwrap();
@@ -158,6 +593,95 @@ mod test {
         assert_eq!((None, (&b":1a :2a"[..]).into()), result);
     }
 
+    #[test]
+    fn should_skip_literals_identifiers_and_comments() {
+        let query = b"SELECT ':a', \"b\"\":b\", `:c``:c`, x->'$.:d', 'it''s :e'\n\
+                      -- :f ?\n\
+                      # :g\n\
+                      /* :h ? */ :i, ':j\\':j' /*! :k */ --:l";
+        let (params, real_query) = parse_named_params(query).unwrap();
+        assert_eq!(
+            params,
+            Some(vec![b"i".to_vec(), b"k".to_vec(), b"l".to_vec()])
+        );
+        assert_eq!(
+            &*real_query,
+            &b"SELECT ':a', \"b\"\":b\", `:c``:c`, x->'$.:d', 'it''s :e'\n\
+               -- :f ?\n\
+               # :g\n\
+               /* :h ? */ ?, ':j\\':j' /*! ? */ --?"[..]
+        );
+
+        // placeholders in comments don't count
+        let result = parse_named_params(b"SELECT :a /* ? */").unwrap();
+        assert_eq!(result.0, Some(vec![b"a".to_vec()]));
+        let result = parse_named_params(b"SELECT ':a', ?").unwrap();
+        assert_eq!(result, (None, (&b"SELECT ':a', ?"[..]).into()));
+
+        // unterminated literals and comments
+        assert_eq!(parse_named_params(b"SELECT ':a").unwrap().0, None);
+        assert_eq!(parse_named_params(b"SELECT /* :a").unwrap().0, None);
+    }
+
+    #[test]
+    fn should_skip_variables_and_assignments() {
+        let result = parse_named_params(b"SELECT @a:=:b, @@session.sql_mode, @`c:d`:=1").unwrap();
+        assert_eq!(
+            result,
+            (
+                Some(vec![b"b".to_vec()]),
+                (&b"SELECT @a:=?, @@session.sql_mode, @`c:d`:=1"[..]).into()
+            )
+        );
+
+        let result = parse_named_params(b"SET @x := 1, @y:=@x").unwrap();
+        assert_eq!(result, (None, (&b"SET @x := 1, @y:=@x"[..]).into()));
+
+        let query = b"CREATE PROCEDURE p(IN lim INT) \
+                      BEGIN \
+                          DECLARE i INT DEFAULT 0; \
+                          SET @total := 0; \
+                          l: LOOP \
+                              SET i := i + 1, @total := @total + :step; \
+                              IF i >= lim OR @@SESSION.max_execution_time > :limit THEN \
+                                  LEAVE l; \
+                              END IF; \
+                          END LOOP l; \
+                          SELECT @total, @'it''s :x'; \
+                      END";
+        let parsed = parse_named_params_spanned(query).unwrap();
+        let names = parsed
+            .params
+            .iter()
+            .map(|x| &x.name[..])
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![&b"step"[..], b"limit"]);
+        assert_eq!(
+            parsed.query.len(),
+            query.len() - "step".len() - "limit".len()
+        );
+    }
+
+    #[test]
+    fn should_respect_sql_mode() {
+        let ansi_quotes = ParseOptions::new().with_ansi_quotes(true);
+        let no_backslash_escapes = ParseOptions::new().with_no_backslash_escapes(true);
+        let b = Some(vec![b"b".to_vec()]);
+
+        let parse =
+            |query: &[u8], options| parse_named_params_with_options(query, options).unwrap().0;
+        assert_eq!(parse(br#"SELECT "a\", :b"#, ParseOptions::new()), None);
+        assert_eq!(parse(br#"SELECT "a\", :b"#, ansi_quotes), b);
+        assert_eq!(parse(br#"SELECT "a\", :b"#, no_backslash_escapes), b);
+        assert_eq!(parse(br"SELECT 'a\', :b", ParseOptions::new()), None);
+        assert_eq!(parse(br"SELECT 'a\', :b", ansi_quotes), None);
+        assert_eq!(parse(br"SELECT 'a\', :b", no_backslash_escapes), b);
+        assert_eq!(
+            parse(br#"SELECT "a"":b", :c"#, ansi_quotes),
+            Some(vec![b"c".to_vec()])
+        );
+    }
+
     #[test]
     fn special_characters_in_query() {
         let result =
@@ -171,6 +695,89 @@ mod test {
         );
     }
 
+    #[test]
+    fn should_return_spans() {
+        let query = b"SELECT :a, '?', :bb /* :c */, :a";
+        let parsed = parse_named_params_spanned(query).unwrap();
+        assert_eq!(&*parsed.query, &b"SELECT ?, '?', ? /* :c */, ?"[..]);
+
+        let names = parsed
+            .params
+            .iter()
+            .map(|x| &x.name[..])
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![&b"a"[..], b"bb", b"a"]);
+        for param in &parsed.params {
+            assert_eq!(query[param.span.start], b':');
+            assert_eq!(&query[param.span.start + 1..param.span.end], &*param.name);
+            assert_eq!(parsed.query[param.position], b'?');
+        }
+        assert_eq!(parsed.params[1].span, 16..19);
+
+        let parsed = parse_named_params_spanned(b"SELECT ?").unwrap();
+        assert!(parsed.params.is_empty());
+        assert_eq!(&*parsed.query, b"SELECT ?");
+    }
+
+    #[test]
+    fn should_expand_lists() {
+        let mut lists = HashMap::new();
+        lists.insert(b"ids".to_vec(), List::from(vec![1, 2]));
+        lists.insert(b"none".to_vec(), List::default());
+
+        let (query, params) = expand_named_params(
+            b"SELECT :a, ':ids' WHERE id IN (:ids) AND x IN (:none) OR y = :a OR y IN (:ids)",
+            Params::from(vec![("a", 0)]),
+            &lists,
+        )
+        .unwrap();
+        assert_eq!(
+            &*query,
+            &b"SELECT ?, ':ids' WHERE id IN (?, ?) AND x IN (NULL) OR y = ? OR y IN (?, ?)"[..]
+        );
+        let values = [0, 1, 2, 0, 1, 2]
+            .iter()
+            .map(|x| Value::from(*x))
+            .collect::<Vec<_>>();
+        assert_eq!(params, Params::Positional(values));
+
+        // lists only
+        let (query, params) = expand_named_params(b"SELECT :none", Params::Empty, &lists).unwrap();
+        assert_eq!(&*query, &b"SELECT NULL"[..]);
+        assert_eq!(params, Params::Empty);
+
+        // no named parameters
+        let params = Params::Positional(vec![Value::Int(1)]);
+        let (query, same) = expand_named_params(b"SELECT ?", params.clone(), &lists).unwrap();
+        assert_eq!(&*query, &b"SELECT ?"[..]);
+        assert_eq!(same, params);
+
+        assert_eq!(
+            expand_named_params(b"SELECT :a, :ids", Params::Empty, &lists).unwrap_err(),
+            MissingNamedParameterError(b"a".to_vec()).into()
+        );
+        assert_eq!(
+            expand_named_params(b"SELECT :ids, ?", Params::Empty, &lists).unwrap_err(),
+            NamedParamsError::MixedPlaceholders {
+                positions: vec![13]
+            }
+            .into()
+        );
+
+        // sql mode options
+        let query = br#"SELECT "a\", :ids"#;
+        let (expanded, _) = expand_named_params(query, Params::Empty, &lists).unwrap();
+        assert_eq!(&*expanded, &query[..]);
+        let options = ParseOptions::new().with_no_backslash_escapes(true);
+        let (expanded, params) =
+            expand_named_params_with_options(query, Params::Empty, &lists, options).unwrap();
+        assert_eq!(&*expanded, &br#"SELECT "a\", ?, ?"#[..]);
+        assert_eq!(
+            params,
+            Params::Positional(vec![Value::Int(1), Value::Int(2)])
+        );
+    }
+
     #[cfg(feature = "nightly")]
     mod bench {
         use crate::named_params::parse_named_params;
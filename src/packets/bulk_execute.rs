@@ -0,0 +1,256 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Complete response to MariaDB's `COM_STMT_BULK_EXECUTE` (see [`ComStmtBulkExecuteRequest`]).
+//!
+//! The response is a single OK packet (affected rows are summed up, last insert id is the id
+//! of the first inserted row) or, if [`StmtBulkFlags::SEND_UNIT_RESULTS`] is set,
+//! a binary protocol result set of two `LONGLONG` columns – an auto-increment id and a number
+//! of affected rows – with a row for every row of parameters.
+//!
+//! [`ComStmtBulkExecuteRequest`]: super::ComStmtBulkExecuteRequest
+//! [`StmtBulkFlags::SEND_UNIT_RESULTS`]: super::StmtBulkFlags::SEND_UNIT_RESULTS
+
+use std::io;
+
+use super::{server::ResultSetWriter, Column, OkPacket};
+use crate::{
+    constants::{CapabilityFlags, ColumnFlags, ColumnType},
+    proto::Binary,
+    row::{
+        convert::from_row_opt,
+        result_set::{ResultSetDecoder, ResultSetEvent},
+    },
+    value::Value,
+};
+
+/// Result of a single row of parameters of a `COM_STMT_BULK_EXECUTE`
+/// (see [`StmtBulkFlags::SEND_UNIT_RESULTS`](super::StmtBulkFlags::SEND_UNIT_RESULTS)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BulkUnitResult {
+    insert_id: u64,
+    affected_rows: u64,
+}
+
+impl BulkUnitResult {
+    pub fn new(insert_id: u64, affected_rows: u64) -> Self {
+        Self {
+            insert_id,
+            affected_rows,
+        }
+    }
+
+    /// Auto-increment id generated for this row (`0` if there is none).
+    pub fn insert_id(&self) -> u64 {
+        self.insert_id
+    }
+
+    /// Number of rows affected by this row of parameters.
+    pub fn affected_rows(&self) -> u64 {
+        self.affected_rows
+    }
+}
+
+/// Typed response to `COM_STMT_BULK_EXECUTE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkExecuteResponse {
+    unit_results: Option<Vec<BulkUnitResult>>,
+    ok_packet: OkPacket<'static>,
+}
+
+impl BulkExecuteResponse {
+    /// Creates a response without unit results (e.g. to send it from a server).
+    pub fn new(ok_packet: OkPacket<'static>) -> Self {
+        Self {
+            unit_results: None,
+            ok_packet,
+        }
+    }
+
+    /// Defines per-row results (sent as a result set terminated by the OK packet).
+    pub fn with_unit_results(mut self, unit_results: Vec<BulkUnitResult>) -> Self {
+        self.unit_results = Some(unit_results);
+        self
+    }
+
+    /// Per-row results, if the response is a result set.
+    pub fn unit_results(&self) -> Option<&[BulkUnitResult]> {
+        self.unit_results.as_deref()
+    }
+
+    /// The OK packet of the response (it terminates the result set of unit results).
+    pub fn ok_packet(&self) -> &OkPacket<'static> {
+        &self.ok_packet
+    }
+
+    /// Returns payloads of all packets of this response.
+    pub fn packets(&self, capabilities: CapabilityFlags) -> Vec<Vec<u8>> {
+        let writer = ResultSetWriter::new(capabilities);
+        match &self.unit_results {
+            Some(unit_results) => {
+                let rows = unit_results
+                    .iter()
+                    .map(|x| [Value::UInt(x.insert_id), Value::UInt(x.affected_rows)]);
+                writer
+                    .binary_result_set(&unit_results_columns(), rows, &self.ok_packet)
+                    .expect("unsigned values are valid for LONGLONG columns")
+            }
+            None => {
+                let mut buf = Vec::new();
+                writer.write_ok(&self.ok_packet, &mut buf);
+                vec![buf]
+            }
+        }
+    }
+}
+
+/// Columns of the unit results result set.
+fn unit_results_columns() -> [Column; 2] {
+    let column = |name: &[u8]| {
+        Column::new(ColumnType::MYSQL_TYPE_LONGLONG)
+            .with_name(name)
+            .with_flags(ColumnFlags::NOT_NULL_FLAG | ColumnFlags::UNSIGNED_FLAG)
+    };
+    [column(b"Id"), column(b"Affected_rows")]
+}
+
+/// Sans-io decoder of a [`BulkExecuteResponse`].
+///
+/// Feed it with payloads of packets, that follow `COM_STMT_BULK_EXECUTE`.
+/// An ERR packet is returned as an error (the decoder is ready to decode the next
+/// response after that).
+#[derive(Debug, Clone)]
+pub struct BulkExecuteResponseDecoder {
+    capabilities: CapabilityFlags,
+    decoder: ResultSetDecoder<Binary>,
+    unit_results: Option<Vec<BulkUnitResult>>,
+}
+
+impl BulkExecuteResponseDecoder {
+    /// Creates a decoder for a connection with the given capabilities.
+    pub fn new(capabilities: CapabilityFlags) -> Self {
+        Self {
+            capabilities,
+            decoder: ResultSetDecoder::new(capabilities),
+            unit_results: None,
+        }
+    }
+
+    /// Pushes the next packet payload.
+    ///
+    /// Returns the response, if the given packet is the last one
+    /// (decoder is ready to decode the next response after that).
+    pub fn push(&mut self, payload: &[u8]) -> io::Result<Option<BulkExecuteResponse>> {
+        let event = match self.decoder.push(payload) {
+            Ok(event) => event,
+            Err(err) => {
+                self.reset();
+                return Err(err);
+            }
+        };
+
+        match event {
+            None => Ok(None),
+            Some(ResultSetEvent::Columns(columns)) => {
+                if columns.len() != 2 {
+                    self.reset();
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unit results of COM_STMT_BULK_EXECUTE must have two columns",
+                    ));
+                }
+                self.unit_results.get_or_insert_with(Vec::new);
+                Ok(None)
+            }
+            Some(ResultSetEvent::Row(row)) => match from_row_opt::<(u64, u64)>(row) {
+                Ok((insert_id, affected_rows)) => {
+                    self.unit_results
+                        .get_or_insert_with(Vec::new)
+                        .push(BulkUnitResult::new(insert_id, affected_rows));
+                    Ok(None)
+                }
+                Err(_) => {
+                    self.reset();
+                    Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "invalid unit result of COM_STMT_BULK_EXECUTE",
+                    ))
+                }
+            },
+            Some(ResultSetEvent::End(_)) if self.decoder.more_results_exists() => Ok(None),
+            Some(ResultSetEvent::End(ok_packet)) => {
+                let unit_results = self.unit_results.take();
+                self.reset();
+                Ok(Some(BulkExecuteResponse {
+                    unit_results,
+                    ok_packet,
+                }))
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.decoder = ResultSetDecoder::new(self.capabilities);
+        self.unit_results = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::StatusFlags,
+        packets::{ComStmtBulkExecuteRequest, ServerError, StmtBulkFlags},
+        value::ValueRef,
+    };
+
+    #[test]
+    fn should_decode_bulk_execute_response() -> io::Result<()> {
+        let rows = vec![vec![ValueRef::Int(1)], vec![ValueRef::Int(2)]];
+        let request = ComStmtBulkExecuteRequest::new(1, rows)
+            .unwrap()
+            .with_unit_results(true);
+        assert!(request.flags().contains(StmtBulkFlags::SEND_UNIT_RESULTS));
+
+        let ok_packet = OkPacket::new(2, Some(10), StatusFlags::SERVER_STATUS_AUTOCOMMIT, 0);
+        let plain = BulkExecuteResponse::new(ok_packet.clone());
+        let unit = BulkExecuteResponse::new(OkPacket::new(
+            0,
+            None,
+            StatusFlags::SERVER_STATUS_AUTOCOMMIT,
+            0,
+        ))
+        .with_unit_results(vec![BulkUnitResult::new(10, 1), BulkUnitResult::new(11, 1)]);
+
+        for capabilities in [
+            CapabilityFlags::CLIENT_PROTOCOL_41,
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_DEPRECATE_EOF,
+        ] {
+            let mut decoder = BulkExecuteResponseDecoder::new(capabilities);
+            // decoder is reusable
+            for response in [&plain, &unit, &plain] {
+                let packets = response.packets(capabilities);
+                let (last, rest) = packets.split_last().unwrap();
+                for packet in rest {
+                    assert_eq!(decoder.push(packet)?, None);
+                }
+                assert_eq!(decoder.push(last)?.as_ref(), Some(response));
+            }
+        }
+
+        let mut decoder = BulkExecuteResponseDecoder::new(CapabilityFlags::CLIENT_PROTOCOL_41);
+        let mut err = Vec::new();
+        let error = ServerError::new(1062, *b"23000", &b"Duplicate entry"[..]);
+        ResultSetWriter::new(CapabilityFlags::CLIENT_PROTOCOL_41).write_err(&error, &mut err);
+        assert!(decoder.push(&err).is_err());
+        let packets = plain.packets(CapabilityFlags::CLIENT_PROTOCOL_41);
+        assert_eq!(decoder.push(&packets[0])?, Some(plain));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Helpers for ambiguous first bytes of packets and fields.
+//!
+//! *   `0xFB` is the text protocol `NULL` marker in place of a length-encoded string,
+//!     and the LOCAL INFILE request header in place of a column count;
+//! *   `0xFE` is the EOF/OK packet header in place of a row, and the prefix of an 8-byte
+//!     length-encoded integer otherwise;
+//! *   `0x00` is the OK packet header in place of a column count,
+//!     and `0xFF` is the ERR packet header.
+
+use std::io;
+
+use crate::{
+    constants::{CapabilityFlags, MAX_PAYLOAD_LEN},
+    io::ParseBuf,
+    misc::unexpected_buf_eof,
+};
+
+/// Text protocol `NULL` marker.
+pub const NULL_MARKER: u8 = 0xFB;
+
+/// Header of an OK packet.
+pub const OK_HEADER: u8 = 0x00;
+
+/// Header of a LOCAL INFILE request.
+pub const LOCAL_INFILE_HEADER: u8 = 0xFB;
+
+/// Header of an EOF packet (and of an OK packet that terminates a result set).
+pub const EOF_HEADER: u8 = 0xFE;
+
+/// Header of an ERR packet.
+pub const ERR_HEADER: u8 = 0xFF;
+
+/// Consumes a text protocol value (a length-encoded string or the `NULL` marker).
+///
+/// Returns `None` for `NULL`.
+///
+/// ```
+/// # use mysql_common::{io::ParseBuf, packets::first_byte::eat_text_value};
+/// let mut buf = ParseBuf(b"\xfb\x03foo\x00");
+/// assert_eq!(eat_text_value(&mut buf).unwrap(), None);
+/// assert_eq!(eat_text_value(&mut buf).unwrap(), Some(&b"foo"[..]));
+/// assert_eq!(eat_text_value(&mut buf).unwrap(), Some(&b""[..]));
+/// assert!(eat_text_value(&mut buf).is_err());
+/// ```
+pub fn eat_text_value<'a>(buf: &mut ParseBuf<'a>) -> io::Result<Option<&'a [u8]>> {
+    match buf.0.first() {
+        Some(&NULL_MARKER) => {
+            buf.skip(1);
+            Ok(None)
+        }
+        Some(&ERR_HEADER) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid length-encoded string (starts with 0xff)",
+        )),
+        Some(_) => buf
+            .checked_eat_lenenc_str()
+            .map(Some)
+            .ok_or_else(unexpected_buf_eof),
+        None => Err(unexpected_buf_eof()),
+    }
+}
+
+/// Kind of a response to `COM_QUERY` (or `COM_STMT_EXECUTE`) given its first packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryResponseKind {
+    /// OK packet (no result set).
+    Ok,
+    /// ERR packet.
+    Err,
+    /// LOCAL INFILE request.
+    LocalInfile,
+    /// Result set with the given number of columns.
+    ResultSet(u64),
+}
+
+impl QueryResponseKind {
+    /// Determines the kind of a response given its first packet payload.
+    ///
+    /// ```
+    /// # use mysql_common::packets::first_byte::QueryResponseKind::{self, *};
+    /// assert_eq!(QueryResponseKind::parse(b"\x00\x00\x00\x02\x00\x00\x00").unwrap(), Ok);
+    /// assert_eq!(QueryResponseKind::parse(b"\xfbdata.csv").unwrap(), LocalInfile);
+    /// assert_eq!(QueryResponseKind::parse(b"\xfc\xfb\x00").unwrap(), ResultSet(251));
+    /// ```
+    pub fn parse(payload: &[u8]) -> io::Result<Self> {
+        match payload.first() {
+            Some(&OK_HEADER) => Ok(QueryResponseKind::Ok),
+            Some(&ERR_HEADER) => Ok(QueryResponseKind::Err),
+            // `0xFB` is `NULL` in the length-encoded integer representation,
+            // so it can't be a column count
+            Some(&LOCAL_INFILE_HEADER) => Ok(QueryResponseKind::LocalInfile),
+            Some(_) => ParseBuf(payload)
+                .checked_eat_lenenc_int()
+                .map(QueryResponseKind::ResultSet)
+                .ok_or_else(unexpected_buf_eof),
+            None => Err(unexpected_buf_eof()),
+        }
+    }
+}
+
+/// Kind of a packet within the rows part of a result set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RowPacketKind {
+    /// Row.
+    Row,
+    /// Packet, that terminates the result set (an EOF packet or, if `CLIENT_DEPRECATE_EOF`
+    /// is set, an OK packet with the `0xFE` header).
+    Terminator,
+    /// ERR packet.
+    Err,
+}
+
+impl RowPacketKind {
+    /// Determines the kind of a packet given its payload.
+    ///
+    /// A text protocol row may start with `0xFE` (an 8-byte length of the first value)
+    /// but such a row is never shorter than `MAX_PAYLOAD_LEN`. Same way an EOF packet is
+    /// shorter than 9 bytes (if `CLIENT_DEPRECATE_EOF` is not set).
+    ///
+    /// ```
+    /// # use mysql_common::{constants::CapabilityFlags, packets::first_byte::RowPacketKind::*};
+    /// # use mysql_common::packets::first_byte::RowPacketKind;
+    /// let caps = CapabilityFlags::CLIENT_PROTOCOL_41;
+    /// assert_eq!(RowPacketKind::parse(caps, b"\xfe\x00\x00\x02\x00"), Terminator);
+    /// assert_eq!(RowPacketKind::parse(caps, b"\xfb\x03foo"), Row);
+    /// ```
+    pub fn parse(capabilities: CapabilityFlags, payload: &[u8]) -> Self {
+        let max_terminator_len = if capabilities.contains(CapabilityFlags::CLIENT_DEPRECATE_EOF) {
+            MAX_PAYLOAD_LEN
+        } else {
+            9
+        };
+        match payload.first() {
+            Some(&EOF_HEADER) if payload.len() < max_terminator_len => RowPacketKind::Terminator,
+            Some(&ERR_HEADER) => RowPacketKind::Err,
+            _ => RowPacketKind::Row,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_disambiguate_first_bytes() {
+        assert_eq!(
+            QueryResponseKind::parse(b"\xff\x48\x04#HY000foo").unwrap(),
+            QueryResponseKind::Err
+        );
+        assert_eq!(
+            QueryResponseKind::parse(b"\x01").unwrap(),
+            QueryResponseKind::ResultSet(1)
+        );
+        assert_eq!(
+            QueryResponseKind::parse(b"\xfe\x01\x00\x00\x00\x00\x00\x00\x00").unwrap(),
+            QueryResponseKind::ResultSet(1)
+        );
+        assert!(QueryResponseKind::parse(b"").is_err());
+        assert!(QueryResponseKind::parse(b"\xfc\x01").is_err());
+
+        let caps = CapabilityFlags::CLIENT_PROTOCOL_41;
+        let deprecate_eof = caps | CapabilityFlags::CLIENT_DEPRECATE_EOF;
+        let ok = b"\xfe\x00\x00\x02\x00\x00\x00\x05hello";
+        assert_eq!(RowPacketKind::parse(caps, ok), RowPacketKind::Row);
+        assert_eq!(
+            RowPacketKind::parse(deprecate_eof, ok),
+            RowPacketKind::Terminator
+        );
+        assert_eq!(
+            RowPacketKind::parse(caps, b"\xff\x48\x04#HY000foo"),
+            RowPacketKind::Err
+        );
+        let mut long_row = vec![0xfe];
+        long_row.resize(MAX_PAYLOAD_LEN, 0);
+        assert_eq!(
+            RowPacketKind::parse(deprecate_eof, &long_row),
+            RowPacketKind::Row
+        );
+
+        let mut buf = ParseBuf(b"\xfc\x01\x00x\xfb\xff");
+        assert_eq!(eat_text_value(&mut buf).unwrap(), Some(&b"x"[..]));
+        assert_eq!(eat_text_value(&mut buf).unwrap(), None);
+        assert!(eat_text_value(&mut buf).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! GTID set representation.
+//!
+//! GTID set has a textual form (e.g. `Executed_Gtid_Set` of `SHOW MASTER STATUS`)
+//! and a binary form (sid block of `COM_BINLOG_DUMP_GTID` and `PREVIOUS_GTIDS_EVENT` data).
+
+use std::{collections::BTreeMap, fmt, io, str::FromStr};
+
+use super::{Interval, ParseSidError, Sid, SID_LEN};
+use crate::{
+    io::ParseBuf,
+    misc::raw::{int::LeU64, seq::Seq},
+    proto::{MyDeserialize, MySerialize},
+};
+
+/// Set of GTIDs, i.e. a set of GNO intervals for every source id.
+///
+/// The set is always normalized – sources are ordered, intervals of a source are ordered,
+/// non-empty and neither overlapping nor adjacent.
+///
+/// ```
+/// # use mysql_common::packets::gtid_set::GtidSet;
+/// let executed: GtidSet = "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:7".parse().unwrap();
+/// let received: GtidSet = "3e11fa47-71ca-11e1-9e33-c80aa9429562:3-6".parse().unwrap();
+///
+/// let all = executed.union(&received);
+/// assert_eq!(all.to_string(), "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-7");
+/// assert!(all.contains(&executed));
+///
+/// let missing = all.subtract(&executed);
+/// assert_eq!(missing.to_string(), "3e11fa47-71ca-11e1-9e33-c80aa9429562:6");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct GtidSet {
+    sids: BTreeMap<[u8; SID_LEN], Vec<Interval>>,
+}
+
+impl GtidSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a set from the given sid blocks (the result is normalized).
+    pub fn from_sids<'a>(sids: impl IntoIterator<Item = Sid<'a>>) -> Self {
+        let mut set = Self::new();
+        for sid in sids {
+            set.insert_intervals(sid.sid(), sid.intervals());
+        }
+        set
+    }
+
+    /// Returns `true` if this set contains no GTIDs.
+    pub fn is_empty(&self) -> bool {
+        self.sids.is_empty()
+    }
+
+    /// Returns normalized intervals of the given source id (empty if there is no such source).
+    pub fn intervals(&self, sid: [u8; SID_LEN]) -> &[Interval] {
+        self.sids.get(&sid).map(|x| &x[..]).unwrap_or_default()
+    }
+
+    /// Returns the binary form of this set, i.e. the sid blocks
+    /// (see [`super::ComBinlogDumpGtid::with_sids`]).
+    pub fn sids(&self) -> Vec<Sid<'static>> {
+        self.sids
+            .iter()
+            .map(|(sid, intervals)| Sid::new(*sid).with_intervals(intervals.clone()))
+            .collect()
+    }
+
+    /// Returns `true` if this set contains the given GTID.
+    pub fn contains_gtid(&self, sid: [u8; SID_LEN], gno: u64) -> bool {
+        self.intervals(sid)
+            .iter()
+            .any(|x| x.start() <= gno && gno < x.end())
+    }
+
+    /// Adds the given GTID to this set.
+    pub fn insert_gtid(&mut self, sid: [u8; SID_LEN], gno: u64) {
+        self.insert_intervals(sid, &[Interval::new(gno, gno.saturating_add(1))]);
+    }
+
+    /// Adds the given intervals of the given source id to this set.
+    ///
+    /// Empty intervals are ignored.
+    pub fn insert_intervals(&mut self, sid: [u8; SID_LEN], intervals: &[Interval]) {
+        let mut merged = self.sids.remove(&sid).unwrap_or_default();
+        merged.extend_from_slice(intervals);
+        let merged = normalize(merged);
+        if !merged.is_empty() {
+            self.sids.insert(sid, merged);
+        }
+    }
+
+    /// Returns the union of this set and the `other` set.
+    pub fn union(&self, other: &GtidSet) -> GtidSet {
+        let mut output = self.clone();
+        for (sid, intervals) in other.sids.iter() {
+            output.insert_intervals(*sid, intervals);
+        }
+        output
+    }
+
+    /// Returns GTIDs of this set, that are not in the `other` set.
+    pub fn subtract(&self, other: &GtidSet) -> GtidSet {
+        let mut output = GtidSet::new();
+        for (sid, intervals) in self.sids.iter() {
+            let difference = subtract(intervals, other.intervals(*sid));
+            if !difference.is_empty() {
+                output.sids.insert(*sid, difference);
+            }
+        }
+        output
+    }
+
+    /// Returns `true` if this set contains every GTID of the `other` set.
+    pub fn contains(&self, other: &GtidSet) -> bool {
+        other.subtract(self).is_empty()
+    }
+}
+
+/// Sorts intervals, removes empty ones and merges overlapping or adjacent ones.
+fn normalize(mut intervals: Vec<Interval>) -> Vec<Interval> {
+    intervals.retain(|x| x.start() < x.end());
+    intervals.sort_unstable_by_key(|x| (x.start(), x.end()));
+
+    let mut output: Vec<Interval> = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        match output.last_mut() {
+            Some(last) if interval.start() <= last.end() => {
+                *last = Interval::new(last.start(), last.end().max(interval.end()));
+            }
+            _ => output.push(interval),
+        }
+    }
+    output
+}
+
+/// Subtracts normalized `right` intervals from normalized `left` intervals.
+fn subtract(left: &[Interval], right: &[Interval]) -> Vec<Interval> {
+    let mut output = Vec::new();
+    for interval in left {
+        let mut start = interval.start();
+        for hole in right {
+            if hole.end() <= start {
+                continue;
+            }
+            if hole.start() >= interval.end() {
+                break;
+            }
+            if hole.start() > start {
+                output.push(Interval::new(start, hole.start()));
+            }
+            start = hole.end();
+            if start >= interval.end() {
+                break;
+            }
+        }
+        if start < interval.end() {
+            output.push(Interval::new(start, interval.end()));
+        }
+    }
+    output
+}
+
+impl FromStr for GtidSet {
+    type Err = ParseSidError;
+
+    /// Parses the textual form of a GTID set, i.e. comma-separated GTID sets of a single source
+    /// (see `FromStr` for [`Sid`]). The result is normalized.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sids = s
+            .split(',')
+            .filter(|x| !x.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Sid<'static>>, _>>()?;
+        Ok(Self::from_sids(sids))
+    }
+}
+
+impl fmt::Display for GtidSet {
+    /// Formats the canonical textual form of a GTID set (e.g. `<uuid>:1-5:7,<uuid2>:1-3`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, sid) in self.sids().iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            fmt::Display::fmt(sid, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl MySerialize for GtidSet {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        Seq::<Sid<'_>, LeU64>::new(self.sids()).serialize(buf);
+    }
+}
+
+impl<'de> MyDeserialize<'de> for GtidSet {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    /// Parses the binary form of a GTID set. The result is normalized.
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let sids: Seq<'de, Sid<'de>, LeU64> = buf.parse(())?;
+        Ok(Self::from_sids(sids.0.into_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GtidSet;
+    use crate::{
+        io::ParseBuf,
+        packets::{Interval, ParseSidError},
+        proto::{MyDeserialize, MySerialize},
+    };
+
+    const SID_1: &str = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+    const SID_2: &str = "4e11fa47-71ca-11e1-9e33-c80aa9429562";
+
+    fn parse(s: &str) -> GtidSet {
+        s.replace("SID_1", SID_1)
+            .replace("SID_2", SID_2)
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_parse_and_format_gtid_set() {
+        let set = parse("SID_2:1-3, SID_1:7:1-5:3-6,SID_2:4");
+        assert_eq!(set.to_string(), format!("{}:1-7,{}:1-4", SID_1, SID_2));
+        assert_eq!(parse("").to_string(), "");
+        assert!(parse(" ").is_empty());
+
+        let sid = set.sids()[0].sid();
+        assert_eq!(set.intervals(sid), &[Interval::new(1, 8)]);
+        assert!(set.contains_gtid(sid, 7));
+        assert!(!set.contains_gtid(sid, 8));
+
+        assert_eq!(
+            "3e11fa47:1".parse::<GtidSet>(),
+            Err(ParseSidError::InvalidUuid)
+        );
+        assert_eq!(
+            format!("{}:5-1", SID_1).parse::<GtidSet>(),
+            Err(ParseSidError::InvalidInterval)
+        );
+    }
+
+    #[test]
+    fn should_handle_binary_gtid_set() {
+        let set = parse("SID_1:1-5:7,SID_2:1");
+
+        let mut output = Vec::new();
+        set.serialize(&mut output);
+        assert_eq!(output.len(), 8 + (16 + 8 + 2 * 16) + (16 + 8 + 16));
+        assert_eq!(&output[..8], b"\x02\x00\x00\x00\x00\x00\x00\x00");
+
+        let parsed = GtidSet::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert_eq!(parsed, set);
+        assert!(GtidSet::deserialize((), &mut ParseBuf(&output[..20])).is_err());
+    }
+
+    #[test]
+    fn should_compute_gtid_set_algebra() {
+        let a = parse("SID_1:1-10:20-30,SID_2:1-5");
+        let b = parse("SID_1:5-25");
+
+        assert_eq!(a.union(&b), parse("SID_1:1-30,SID_2:1-5"));
+        assert_eq!(a.subtract(&b), parse("SID_1:1-4:26-30,SID_2:1-5"));
+        assert_eq!(b.subtract(&a), parse("SID_1:11-19"));
+        assert_eq!(a.subtract(&a), GtidSet::new());
+
+        assert!(a.contains(&parse("SID_1:2-3:25,SID_2:5")));
+        assert!(!a.contains(&b));
+        assert!(a.contains(&GtidSet::new()));
+        assert!(a.union(&b).contains(&b));
+
+        let mut set = GtidSet::new();
+        let sid = a.sids()[0].sid();
+        set.insert_gtid(sid, 2);
+        set.insert_gtid(sid, 1);
+        set.insert_gtid(sid, 4);
+        assert_eq!(set, parse("SID_1:1-2:4"));
+        set.insert_gtid(sid, 3);
+        assert_eq!(set.intervals(sid), &[Interval::new(1, 5)]);
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Pre-4.1 protocol packets (requires the `legacy` feature).
+//!
+//! Useful to talk to ancient servers or to decode old capture files.
+
+use bytes::BufMut;
+
+use std::{borrow::Cow, io};
+
+use crate::{
+    constants::CapabilityFlags,
+    io::ParseBuf,
+    misc::raw::{
+        bytes::{EofBytes, NullBytes},
+        int::{LeU24, LeU32LowerHalf},
+        Const, RawBytes, RawConst, RawInt,
+    },
+    proto::{MyDeserialize, MySerialize},
+    scramble::scramble_323,
+};
+
+/// Old handshake response packet (`Protocol::HandshakeResponse320`).
+///
+/// Sent by a client, that doesn't support `CLIENT_PROTOCOL_41`. Only the lower 16 bits
+/// of capabilities are sent.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HandshakeResponse320<'a> {
+    capabilities: Const<CapabilityFlags, LeU32LowerHalf>,
+    max_packet_size: RawInt<LeU24>,
+    user: RawBytes<'a, NullBytes>,
+    auth_response: RawBytes<'a, EofBytes>,
+    db_name: Option<RawBytes<'a, NullBytes>>,
+}
+
+impl<'a> HandshakeResponse320<'a> {
+    /// Creates a new response.
+    ///
+    /// `auth_response` is the old password scramble (see [`HandshakeResponse320::scramble`]).
+    /// `CLIENT_CONNECT_WITH_DB` capability is set (or removed) depending on `db_name`.
+    pub fn new(
+        mut capabilities: CapabilityFlags,
+        user: impl Into<Cow<'a, [u8]>>,
+        auth_response: impl Into<Cow<'a, [u8]>>,
+        db_name: Option<impl Into<Cow<'a, [u8]>>>,
+    ) -> Self {
+        capabilities.set(CapabilityFlags::CLIENT_CONNECT_WITH_DB, db_name.is_some());
+        capabilities.remove(CapabilityFlags::CLIENT_PROTOCOL_41);
+        Self {
+            capabilities: Const::new(CapabilityFlags::from_bits_truncate(
+                capabilities.bits() & 0x0000_FFFF,
+            )),
+            max_packet_size: RawInt::new(0x00FF_FFFF),
+            user: RawBytes::new(user),
+            auth_response: RawBytes::new(auth_response),
+            db_name: db_name.map(RawBytes::new),
+        }
+    }
+
+    /// Computes the old password scramble (`mysql_old_password`) for the given nonce
+    /// (the first 8 bytes of the server scramble are used).
+    ///
+    /// Empty password gives an empty auth response.
+    pub fn scramble(nonce: &[u8], password: &[u8]) -> Vec<u8> {
+        let nonce = &nonce[..nonce.len().min(8)];
+        scramble_323(nonce, password)
+            .map(|x| x.to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Defines the maximum size of a command packet, that the client wants to send
+    /// to the server (at most 16MiB - 1).
+    pub fn with_max_packet_size(mut self, max_packet_size: u32) -> Self {
+        self.max_packet_size = RawInt::new(max_packet_size.min(0x00FF_FFFF));
+        self
+    }
+
+    /// Client capabilities (lower 16 bits only).
+    pub fn capabilities(&self) -> CapabilityFlags {
+        self.capabilities.0
+    }
+
+    pub fn max_packet_size(&self) -> u32 {
+        self.max_packet_size.0
+    }
+
+    pub fn user(&self) -> &[u8] {
+        self.user.as_bytes()
+    }
+
+    pub fn auth_response(&self) -> &[u8] {
+        self.auth_response.as_bytes()
+    }
+
+    pub fn db_name(&self) -> Option<&[u8]> {
+        self.db_name.as_ref().map(|x| x.as_bytes())
+    }
+
+    pub fn into_owned(self) -> HandshakeResponse320<'static> {
+        HandshakeResponse320 {
+            capabilities: self.capabilities,
+            max_packet_size: self.max_packet_size,
+            user: self.user.into_owned(),
+            auth_response: self.auth_response.into_owned(),
+            db_name: self.db_name.map(RawBytes::into_owned),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for HandshakeResponse320<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let mut sbuf: ParseBuf = buf.parse(5)?;
+        let capabilities: RawConst<LeU32LowerHalf, CapabilityFlags> = sbuf.parse_unchecked(())?;
+        let max_packet_size = sbuf.parse_unchecked(())?;
+        let user = buf.parse(())?;
+
+        let capabilities = CapabilityFlags::from_bits_truncate(capabilities.0);
+        let (auth_response, db_name) =
+            if capabilities.contains(CapabilityFlags::CLIENT_CONNECT_WITH_DB) {
+                let auth_response: RawBytes<'de, NullBytes> = buf.parse(())?;
+                (RawBytes::new(auth_response.0), Some(buf.parse(())?))
+            } else {
+                (buf.parse(())?, None)
+            };
+
+        Ok(Self {
+            capabilities: Const::new(capabilities),
+            max_packet_size,
+            user,
+            auth_response,
+            db_name,
+        })
+    }
+}
+
+impl MySerialize for HandshakeResponse320<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.capabilities.serialize(&mut *buf);
+        self.max_packet_size.serialize(&mut *buf);
+        self.user.serialize(&mut *buf);
+        match &self.db_name {
+            Some(db_name) => {
+                buf.put_slice(self.auth_response.as_bytes());
+                buf.put_u8(0);
+                db_name.serialize(buf);
+            }
+            None => self.auth_response.serialize(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HandshakeResponse320;
+    use crate::{
+        constants::CapabilityFlags,
+        io::ParseBuf,
+        proto::{MyDeserialize, MySerialize},
+    };
+
+    #[test]
+    fn should_handle_handshake_response_320() {
+        let scramble = HandshakeResponse320::scramble(b"ABCDEFGH12345", b"password");
+        assert_eq!(scramble.len(), 8);
+        assert!(HandshakeResponse320::scramble(b"ABCDEFGH", b"").is_empty());
+
+        let response = HandshakeResponse320::new(
+            CapabilityFlags::CLIENT_LONG_PASSWORD | CapabilityFlags::CLIENT_TRANSACTIONS,
+            &b"root"[..],
+            &scramble[..],
+            Some(&b"test"[..]),
+        );
+        assert!(response
+            .capabilities()
+            .contains(CapabilityFlags::CLIENT_CONNECT_WITH_DB));
+
+        let mut output = Vec::new();
+        response.serialize(&mut output);
+        assert_eq!(&output[..5], b"\x09\x20\xff\xff\xff");
+        assert_eq!(&output[5..10], b"root\x00");
+        assert_eq!(&output[output.len() - 6..], b"\x00test\x00");
+
+        let parsed = HandshakeResponse320::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert_eq!(parsed, response);
+
+        let response = HandshakeResponse320::new(
+            CapabilityFlags::CLIENT_LONG_PASSWORD,
+            &b"root"[..],
+            &scramble[..],
+            None::<&[u8]>,
+        )
+        .with_max_packet_size(1024);
+        let mut output = Vec::new();
+        response.serialize(&mut output);
+        assert_eq!(output.len(), 5 + 5 + 8);
+
+        let parsed = HandshakeResponse320::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert_eq!(parsed.max_packet_size(), 1024);
+        assert_eq!(parsed.auth_response(), &scramble[..]);
+        assert_eq!(parsed.db_name(), None);
+    }
+}
@@ -9,17 +9,17 @@
 use bytes::BufMut;
 use lexical::parse;
 use regex::bytes::Regex;
-use smallvec::SmallVec;
 
 use std::{
     borrow::Cow, cmp::max, collections::HashMap, convert::TryFrom, fmt, io, marker::PhantomData,
+    str::Utf8Error, sync::Arc,
 };
 
 use crate::{
     constants::{
-        CapabilityFlags, ColumnFlags, ColumnType, Command, CursorType, SessionStateType,
-        StatusFlags, StmtExecuteParamFlags, StmtExecuteParamsFlags, MAX_PAYLOAD_LEN,
-        UTF8MB4_GENERAL_CI, UTF8_GENERAL_CI,
+        CapabilityFlags, ColumnFlags, ColumnType, Command, CursorType, MariadbCapabilities,
+        SessionStateType, SetOption, StatusFlags, StmtExecuteParamFlags, StmtExecuteParamsFlags,
+        BINARY_COLLATION, MAX_PAYLOAD_LEN, UTF8MB4_GENERAL_CI, UTF8_GENERAL_CI,
     },
     io::{BufMutExt, ParseBuf},
     misc::{
@@ -31,15 +31,20 @@ use crate::{
             },
             int::{ConstU32, ConstU8, LeU16, LeU24, LeU32, LeU32LowerHalf, LeU32UpperHalf, LeU64},
             seq::Seq,
-            Const, Either, RawBytes, RawConst, RawInt, Skip,
+            Const, Either, RawBytes, RawConst, RawFlags, RawInt, Skip,
         },
+        retry::{retry_class, RetryClass},
         unexpected_buf_eof,
     },
     proto::{MyDeserialize, MySerialize},
-    value::{ClientSide, SerializationSide, Value},
+    row::{
+        convert::{FromRow, FromRowError},
+        NoCase, Row,
+    },
+    value::{convert::FromValue, ClientSide, SerializationSide, Value, ValueRef},
 };
 
-use self::session_state_change::SessionStateChange;
+use self::{ok_info::OkPacketInfo, session_state_change::SessionStateChange};
 
 lazy_static::lazy_static! {
     static ref MARIADB_VERSION_RE: Regex =
@@ -90,6 +95,15 @@ macro_rules! define_const_bytes {
 }
 
 pub mod binlog_request;
+pub mod bulk_execute;
+pub mod first_byte;
+pub mod gtid_set;
+#[cfg(feature = "legacy")]
+pub mod legacy;
+pub mod ok_info;
+pub mod prepare;
+pub mod server;
+pub mod session_reset;
 pub mod session_state_change;
 
 define_const_bytes!(
@@ -107,15 +121,165 @@ define_const!(
     0x0c
 );
 
+/// MariaDB pseudo-type of a column, that is reported via the extended type info
+/// (see `MARIADB_CLIENT_EXTENDED_TYPE_INFO`).
+///
+/// Such columns are otherwise reported as plain text or blob columns.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MariadbExtendedType {
+    Uuid,
+    Inet4,
+    Inet6,
+    Json,
+}
+
+impl MariadbExtendedType {
+    fn from_type_info(type_name: Option<&[u8]>, format_name: Option<&[u8]>) -> Option<Self> {
+        match (type_name, format_name) {
+            (Some(x), _) if x.eq_ignore_ascii_case(b"uuid") => Some(Self::Uuid),
+            (Some(x), _) if x.eq_ignore_ascii_case(b"inet4") => Some(Self::Inet4),
+            (Some(x), _) if x.eq_ignore_ascii_case(b"inet6") => Some(Self::Inet6),
+            (_, Some(x)) if x.eq_ignore_ascii_case(b"json") => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// MariaDB extended type info of a column definition.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+struct MariadbExtTypeInfo {
+    type_name: Option<Arc<[u8]>>,
+    format_name: Option<Arc<[u8]>>,
+}
+
+impl MariadbExtTypeInfo {
+    const TYPE_NAME: u8 = 0x00;
+    const FORMAT_NAME: u8 = 0x01;
+}
+
+impl<'de> MyDeserialize<'de> for MariadbExtTypeInfo {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let data: RawBytes<'de, LenEnc> = buf.parse(())?;
+        let mut data = ParseBuf(data.as_bytes());
+        let mut info = Self::default();
+        while !data.is_empty() {
+            let key: RawInt<u8> = data.parse(())?;
+            let value: Arc<[u8]> = data.parse(())?;
+            match *key {
+                Self::TYPE_NAME => info.type_name = Some(value),
+                Self::FORMAT_NAME => info.format_name = Some(value),
+                // unknown keys are skipped
+                _ => (),
+            }
+        }
+        Ok(info)
+    }
+}
+
+impl MySerialize for MariadbExtTypeInfo {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        let mut data = Vec::new();
+        if let Some(type_name) = &self.type_name {
+            data.put_u8(Self::TYPE_NAME);
+            type_name.serialize(&mut data);
+        }
+        if let Some(format_name) = &self.format_name {
+            data.put_u8(Self::FORMAT_NAME);
+            format_name.serialize(&mut data);
+        }
+        buf.put_lenenc_str(&data);
+    }
+}
+
+/// Names of a column definition (schema, table, org_table, name and org_name).
+///
+/// All five names are stored back to back in a single shared buffer, so a column costs
+/// one allocation for its names and cloning it (e.g. to cache statement metadata)
+/// doesn't copy them.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct ColumnNames {
+    buf: Arc<[u8]>,
+    /// End offset of each name within `buf`.
+    ends: [usize; 5],
+}
+
+impl ColumnNames {
+    const SCHEMA: usize = 0;
+    const TABLE: usize = 1;
+    const ORG_TABLE: usize = 2;
+    const NAME: usize = 3;
+    const ORG_NAME: usize = 4;
+
+    fn new(names: [&[u8]; 5]) -> Self {
+        let mut ends = [0; 5];
+        let mut end = 0;
+        for (i, name) in names.iter().enumerate() {
+            end += name.len();
+            ends[i] = end;
+        }
+        // chained slice iterators have exact size, so this allocates once
+        let buf = names[0]
+            .iter()
+            .chain(names[1])
+            .chain(names[2])
+            .chain(names[3])
+            .chain(names[4])
+            .copied()
+            .collect();
+        Self { buf, ends }
+    }
+
+    fn get(&self, i: usize) -> &[u8] {
+        let start = if i == 0 { 0 } else { self.ends[i - 1] };
+        &self.buf[start..self.ends[i]]
+    }
+
+    fn with(&self, i: usize, value: &[u8]) -> Self {
+        let mut names = [&[][..]; 5];
+        for (j, name) in names.iter_mut().enumerate() {
+            *name = if j == i { value } else { self.get(j) };
+        }
+        Self::new(names)
+    }
+}
+
+impl<'de> MyDeserialize<'de> for ColumnNames {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let schema: RawBytes<'de, LenEnc> = buf.parse(())?;
+        let table: RawBytes<'de, LenEnc> = buf.parse(())?;
+        let org_table: RawBytes<'de, LenEnc> = buf.parse(())?;
+        let name: RawBytes<'de, LenEnc> = buf.parse(())?;
+        let org_name: RawBytes<'de, LenEnc> = buf.parse(())?;
+        Ok(Self::new([
+            schema.as_bytes(),
+            table.as_bytes(),
+            org_table.as_bytes(),
+            name.as_bytes(),
+            org_name.as_bytes(),
+        ]))
+    }
+}
+
+impl MySerialize for ColumnNames {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        for i in 0..self.ends.len() {
+            buf.put_lenenc_str(self.get(i));
+        }
+    }
+}
+
 /// Represents MySql Column (column packet).
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Column {
     catalog: ColumnDefinitionCatalog,
-    schema: SmallVec<[u8; 16]>,
-    table: SmallVec<[u8; 16]>,
-    org_table: SmallVec<[u8; 16]>,
-    name: SmallVec<[u8; 16]>,
-    org_name: SmallVec<[u8; 16]>,
+    names: ColumnNames,
+    mariadb_ext_type_info: Option<MariadbExtTypeInfo>,
     fixed_length_fields_len: FixedLengthFieldsLen,
     column_length: RawInt<LeU32>,
     character_set: RawInt<LeU16>,
@@ -123,7 +287,6 @@ pub struct Column {
     flags: Const<ColumnFlags, LeU16>,
     decimals: RawInt<u8>,
     __filler: Skip<2>,
-    // COM_FIELD_LIST is deprecated, so we won't support it
 }
 
 impl<'de> MyDeserialize<'de> for Column {
@@ -131,43 +294,20 @@ impl<'de> MyDeserialize<'de> for Column {
     type Ctx = ();
 
     fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
-        let catalog = buf.parse(())?;
-        let schema = buf.parse_unchecked(())?;
-        let table = buf.parse_unchecked(())?;
-        let org_table = buf.parse_unchecked(())?;
-        let name = buf.parse_unchecked(())?;
-        let org_name = buf.parse_unchecked(())?;
-        let mut buf: ParseBuf = buf.parse(13)?;
-
-        Ok(Column {
-            catalog,
-            schema,
-            table,
-            org_table,
-            name,
-            org_name,
-            fixed_length_fields_len: buf.parse_unchecked(())?,
-            character_set: buf.parse_unchecked(())?,
-            column_length: buf.parse_unchecked(())?,
-            column_type: buf.parse_unchecked(())?,
-            flags: buf.parse_unchecked(())?,
-            decimals: buf.parse_unchecked(())?,
-            __filler: buf.parse_unchecked(())?,
-        })
+        Self::parse(MariadbCapabilities::empty(), buf)
     }
 }
 
 impl MySerialize for Column {
     fn serialize(&self, buf: &mut Vec<u8>) {
         self.catalog.serialize(&mut *buf);
-        self.schema.serialize(&mut *buf);
-        self.table.serialize(&mut *buf);
-        self.org_table.serialize(&mut *buf);
-        self.name.serialize(&mut *buf);
-        self.org_name.serialize(&mut *buf);
+        self.names.serialize(&mut *buf);
+        if let Some(info) = &self.mariadb_ext_type_info {
+            info.serialize(&mut *buf);
+        }
         self.fixed_length_fields_len.serialize(&mut *buf);
-        self.column_length.serialize(&mut *buf);
         self.character_set.serialize(&mut *buf);
+        self.column_length.serialize(&mut *buf);
         self.column_type.serialize(&mut *buf);
         self.flags.serialize(&mut *buf);
         self.decimals.serialize(&mut *buf);
@@ -175,15 +315,64 @@ impl MySerialize for Column {
     }
 }
 
+/// Deserializes a [`Column`] taking MariaDB extended capabilities into account
+/// (i.e. parses the extended type info if `MARIADB_CLIENT_EXTENDED_TYPE_INFO` is set).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ColumnDeserializer(Column);
+
+impl ColumnDeserializer {
+    pub fn into_inner(self) -> Column {
+        self.0
+    }
+}
+
+impl From<ColumnDeserializer> for Column {
+    fn from(x: ColumnDeserializer) -> Self {
+        x.0
+    }
+}
+
+impl<'de> MyDeserialize<'de> for ColumnDeserializer {
+    const SIZE: Option<usize> = None;
+    type Ctx = MariadbCapabilities;
+
+    fn deserialize(mariadb_capabilities: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Column::parse(mariadb_capabilities, buf).map(Self)
+    }
+}
+
 impl Column {
+    fn parse(
+        mariadb_capabilities: MariadbCapabilities,
+        buf: &mut ParseBuf<'_>,
+    ) -> io::Result<Self> {
+        let catalog = buf.parse(())?;
+        let names = buf.parse(())?;
+        let mut mariadb_ext_type_info = None;
+        if mariadb_capabilities.contains(MariadbCapabilities::MARIADB_CLIENT_EXTENDED_TYPE_INFO) {
+            mariadb_ext_type_info = buf.parse(()).map(Some)?;
+        }
+        let mut buf: ParseBuf = buf.parse(13)?;
+
+        Ok(Column {
+            catalog,
+            names,
+            mariadb_ext_type_info,
+            fixed_length_fields_len: buf.parse_unchecked(())?,
+            character_set: buf.parse_unchecked(())?,
+            column_length: buf.parse_unchecked(())?,
+            column_type: buf.parse_unchecked(())?,
+            flags: buf.parse_unchecked(())?,
+            decimals: buf.parse_unchecked(())?,
+            __filler: buf.parse_unchecked(())?,
+        })
+    }
+
     pub fn new(column_type: ColumnType) -> Self {
         Self {
             catalog: Default::default(),
-            schema: Default::default(),
-            table: Default::default(),
-            org_table: Default::default(),
-            name: Default::default(),
-            org_name: Default::default(),
+            names: ColumnNames::new([&[][..]; 5]),
+            mariadb_ext_type_info: None,
             fixed_length_fields_len: Default::default(),
             column_length: Default::default(),
             character_set: Default::default(),
@@ -195,27 +384,27 @@ impl Column {
     }
 
     pub fn with_schema(mut self, schema: &[u8]) -> Self {
-        self.schema = schema.into();
+        self.names = self.names.with(ColumnNames::SCHEMA, schema);
         self
     }
 
     pub fn with_table(mut self, table: &[u8]) -> Self {
-        self.table = table.into();
+        self.names = self.names.with(ColumnNames::TABLE, table);
         self
     }
 
     pub fn with_org_table(mut self, org_table: &[u8]) -> Self {
-        self.org_table = org_table.into();
+        self.names = self.names.with(ColumnNames::ORG_TABLE, org_table);
         self
     }
 
     pub fn with_name(mut self, name: &[u8]) -> Self {
-        self.name = name.into();
+        self.names = self.names.with(ColumnNames::NAME, name);
         self
     }
 
     pub fn with_org_name(mut self, org_name: &[u8]) -> Self {
-        self.org_name = org_name.into();
+        self.names = self.names.with(ColumnNames::ORG_NAME, org_name);
         self
     }
 
@@ -239,6 +428,49 @@ impl Column {
         self
     }
 
+    /// Defines the MariaDB extended type name (e.g. `uuid`, `inet6`, `point`).
+    ///
+    /// It is serialized only if either type name or format name is defined, so it should
+    /// only be defined if the client has set the `MARIADB_CLIENT_EXTENDED_TYPE_INFO`.
+    pub fn with_mariadb_ext_type_name(mut self, type_name: &[u8]) -> Self {
+        self.mariadb_ext_type_info
+            .get_or_insert_with(Default::default)
+            .type_name = Some(type_name.into());
+        self
+    }
+
+    /// Defines the MariaDB extended format name (e.g. `json`).
+    ///
+    /// See [`Column::with_mariadb_ext_type_name`].
+    pub fn with_mariadb_ext_format_name(mut self, format_name: &[u8]) -> Self {
+        self.mariadb_ext_type_info
+            .get_or_insert_with(Default::default)
+            .format_name = Some(format_name.into());
+        self
+    }
+
+    /// Returns the MariaDB extended type name, if any (see [`ColumnDeserializer`]).
+    pub fn mariadb_ext_type_name_ref(&self) -> Option<&[u8]> {
+        self.mariadb_ext_type_info
+            .as_ref()
+            .and_then(|x| x.type_name.as_deref())
+    }
+
+    /// Returns the MariaDB extended format name, if any (see [`ColumnDeserializer`]).
+    pub fn mariadb_ext_format_name_ref(&self) -> Option<&[u8]> {
+        self.mariadb_ext_type_info
+            .as_ref()
+            .and_then(|x| x.format_name.as_deref())
+    }
+
+    /// Returns the MariaDB pseudo-type of this column, if it is known.
+    pub fn mariadb_ext_type(&self) -> Option<MariadbExtendedType> {
+        MariadbExtendedType::from_type_info(
+            self.mariadb_ext_type_name_ref(),
+            self.mariadb_ext_format_name_ref(),
+        )
+    }
+
     /// Returns value of the column_length field of a column packet.
     ///
     /// Can be used for text-output formatting.
@@ -274,7 +506,7 @@ impl Column {
 
     /// Returns value of the schema field of a column packet as a byte slice.
     pub fn schema_ref(&self) -> &[u8] {
-        &*self.schema
+        self.names.get(ColumnNames::SCHEMA)
     }
 
     /// Returns value of the schema field of a column packet as a string (lossy converted).
@@ -284,7 +516,7 @@ impl Column {
 
     /// Returns value of the table field of a column packet as a byte slice.
     pub fn table_ref(&self) -> &[u8] {
-        &*self.table
+        self.names.get(ColumnNames::TABLE)
     }
 
     /// Returns value of the table field of a column packet as a string (lossy converted).
@@ -296,7 +528,7 @@ impl Column {
     ///
     /// "org_table" is for original table name.
     pub fn org_table_ref(&self) -> &[u8] {
-        &*self.org_table
+        self.names.get(ColumnNames::ORG_TABLE)
     }
 
     /// Returns value of the org_table field of a column packet as a string (lossy converted).
@@ -306,7 +538,7 @@ impl Column {
 
     /// Returns value of the name field of a column packet as a byte slice.
     pub fn name_ref(&self) -> &[u8] {
-        &*self.name
+        self.names.get(ColumnNames::NAME)
     }
 
     /// Returns value of the name field of a column packet as a string (lossy converted).
@@ -318,13 +550,91 @@ impl Column {
     ///
     /// "org_name" is for original column name.
     pub fn org_name_ref(&self) -> &[u8] {
-        &*self.org_name
+        self.names.get(ColumnNames::ORG_NAME)
     }
 
     /// Returns value of the org_name field of a column packet as a string (lossy converted).
     pub fn org_name_str(&self) -> Cow<'_, str> {
         String::from_utf8_lossy(self.org_name_ref())
     }
+
+    /// Returns value of the schema field of a column packet as a UTF-8 string.
+    ///
+    /// Note, that metadata is encoded using `character_set_results` of a connection
+    /// (not the character set of a column), so this will fail if it is not a UTF-8 encoding.
+    pub fn schema_utf8(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.schema_ref())
+    }
+
+    /// Returns value of the table field of a column packet as a UTF-8 string
+    /// (see [`Column::schema_utf8`]).
+    pub fn table_utf8(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.table_ref())
+    }
+
+    /// Returns value of the org_table field of a column packet as a UTF-8 string
+    /// (see [`Column::schema_utf8`]).
+    pub fn org_table_utf8(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.org_table_ref())
+    }
+
+    /// Returns value of the name field of a column packet as a UTF-8 string
+    /// (see [`Column::schema_utf8`]).
+    pub fn name_utf8(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.name_ref())
+    }
+
+    /// Returns value of the org_name field of a column packet as a UTF-8 string
+    /// (see [`Column::schema_utf8`]).
+    pub fn org_name_utf8(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.org_name_ref())
+    }
+
+    /// Returns `true` if the column is `UNSIGNED`.
+    pub fn is_unsigned(&self) -> bool {
+        self.flags().contains(ColumnFlags::UNSIGNED_FLAG)
+    }
+
+    /// Returns `true` if the column is nullable.
+    pub fn is_nullable(&self) -> bool {
+        !self.flags().contains(ColumnFlags::NOT_NULL_FLAG)
+    }
+
+    /// Returns `true` if the column is a part of the primary key.
+    pub fn is_primary_key(&self) -> bool {
+        self.flags().contains(ColumnFlags::PRI_KEY_FLAG)
+    }
+
+    /// Returns `true` if values of the column use the `binary` collation
+    /// (i.e. it's a numeric, temporal or a binary string column).
+    pub fn is_binary_collation(&self) -> bool {
+        self.character_set() == BINARY_COLLATION
+    }
+
+    /// Returns the maximum length of a value in characters.
+    ///
+    /// `column_length` is in bytes, so it's divided by the maximum length of a character
+    /// in the column character set. Returns `None` if the character set is unknown.
+    pub fn display_length(&self) -> Option<u32> {
+        max_char_len(self.character_set()).map(|len| self.column_length() / len)
+    }
+}
+
+/// Returns the maximum length of a character (in bytes) for the given collation id.
+fn max_char_len(collation: u16) -> Option<u32> {
+    match collation {
+        // binary, latin1, ascii
+        63 | 5 | 8 | 15 | 31 | 47 | 48 | 49 | 94 | 11 | 65 => Some(1),
+        // ucs2
+        35 | 90 | 128..=151 | 159 => Some(2),
+        // utf8mb3
+        33 | 76 | 83 | 192..=215 | 223 => Some(3),
+        // utf8mb4, utf16, utf16le, utf32
+        45 | 46 | 224..=247 | 255..=323 | 54 | 55 | 56 | 62 | 101..=124 | 60 | 61 | 160..=183 => {
+            Some(4)
+        }
+        _ => None,
+    }
 }
 
 /// Represents change in session state (part of MySql's Ok packet).
@@ -480,6 +790,10 @@ impl OkPacketKind for CommonOkPacket {
                 (RawBytes::default(), RawBytes::default())
             };
 
+        if !buf.is_empty() {
+            parse_warn!("{} trailing bytes ignored in an OK packet", buf.len());
+        }
+
         Ok(OkPacketBody {
             affected_rows,
             last_insert_id,
@@ -530,6 +844,39 @@ pub struct OkPacket<'a> {
 }
 
 impl<'a> OkPacket<'a> {
+    /// Creates an OK packet without info and session state info
+    /// (e.g. to send it using [`server::ResultSetWriter`]).
+    pub fn new(
+        affected_rows: u64,
+        last_insert_id: Option<u64>,
+        status_flags: StatusFlags,
+        warnings: u16,
+    ) -> Self {
+        Self {
+            affected_rows,
+            last_insert_id,
+            status_flags,
+            warnings,
+            info: None,
+            session_state_info: None,
+        }
+    }
+
+    /// Defines the info field of this OK packet (empty info means no info).
+    pub fn with_info(mut self, info: impl Into<Cow<'a, [u8]>>) -> Self {
+        let info = RawBytes::new(info);
+        self.info = if info.is_empty() { None } else { Some(info) };
+        self
+    }
+
+    /// Defines raw session state info of this OK packet, i.e. a sequence of serialized
+    /// [`SessionStateInfo`] entries (empty data means no session state info).
+    pub fn with_session_state_info(mut self, data: impl Into<Cow<'a, [u8]>>) -> Self {
+        let data = RawBytes::new(data);
+        self.session_state_info = if data.is_empty() { None } else { Some(data) };
+        self
+    }
+
     pub fn into_owned(self) -> OkPacket<'static> {
         OkPacket {
             affected_rows: self.affected_rows,
@@ -571,6 +918,13 @@ impl<'a> OkPacket<'a> {
         self.info.as_ref().map(|x| x.as_str())
     }
 
+    /// Parsed value of the info field of an Ok packet (see [`OkPacketInfo`]).
+    ///
+    /// Returns `None` if there is no info or it is not a known statement summary.
+    pub fn info_parsed(&self) -> Option<OkPacketInfo> {
+        self.info_str().and_then(|info| OkPacketInfo::parse(&info))
+    }
+
     /// Returns raw reference to a session state info.
     pub fn session_state_info_ref(&self) -> Option<&[u8]> {
         self.session_state_info.as_ref().map(|x| x.as_bytes())
@@ -590,6 +944,19 @@ impl<'a> OkPacket<'a> {
             .transpose()
             .map(|x| x.unwrap_or_default())
     }
+
+    /// Tries to parse and decode session state info, if any (see [`SessionStateInfo::decode`]).
+    pub fn session_state_changes(&self) -> io::Result<Vec<SessionStateChange<'_>>> {
+        let mut data = ParseBuf(self.session_state_info_ref().unwrap_or_default());
+        let mut changes = Vec::new();
+        while !data.is_empty() {
+            let data_type: Const<SessionStateType, u8> = data.parse(())?;
+            let data_len: RawInt<LenEnc> = data.parse(())?;
+            let mut entry: ParseBuf = data.parse(data_len.0 as usize)?;
+            changes.push(entry.parse(*data_type)?);
+        }
+        Ok(changes)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -722,8 +1089,8 @@ impl<'a> fmt::Display for ProgressReport<'a> {
             "Stage: {} of {} '{}'  {:.2}% of stage done",
             self.stage(),
             self.max_stage(),
-            self.progress(),
-            self.stage_info_str()
+            self.stage_info_str(),
+            f64::from(self.progress()) / 1000.0,
         )
     }
 }
@@ -780,7 +1147,8 @@ impl<'de> MyDeserialize<'de> for ErrPacket<'de> {
         sbuf.parse_unchecked::<ErrPacketHeader>(())?;
         let code: RawInt<LeU16> = sbuf.parse_unchecked(())?;
 
-        // We assume that CLIENT_PROTOCOL_41 was set
+        // We assume that CLIENT_PROTOCOL_41 was set.
+        // Note, that `MARIADB_CLIENT_PROGRESS` maps to this flag (see `FromDeserializeCtx`).
         if *code == 0xFFFF && capabilities.contains(CapabilityFlags::CLIENT_PROGRESS_OBSOLETE) {
             buf.parse(()).map(ErrPacket::Progress)
         } else {
@@ -858,6 +1226,11 @@ impl<'a> ServerError<'a> {
         self.message.as_str()
     }
 
+    /// Returns what should be retried after this error (see [`crate::misc::retry`]).
+    pub fn retry_class(&self) -> RetryClass {
+        retry_class(self.error_code())
+    }
+
     pub fn into_owned(self) -> ServerError<'static> {
         ServerError {
             code: self.code,
@@ -873,8 +1246,8 @@ impl<'de> MyDeserialize<'de> for ServerError<'de> {
     type Ctx = u16;
 
     fn deserialize(code: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
-        match buf.0[0] {
-            b'#' => {
+        match buf.0.first() {
+            Some(b'#') => {
                 buf.skip(1);
                 Ok(ServerError {
                     code: RawInt::new(code),
@@ -911,6 +1284,8 @@ impl fmt::Display for ServerError<'_> {
     }
 }
 
+impl std::error::Error for ServerError<'_> {}
+
 define_header!(
     LocalInfileHeader,
     InvalidLocalInfileHeader("Invalid LOCAL_INFILE header"),
@@ -1111,6 +1486,30 @@ define_header!(
     0x01
 );
 
+/// Status of the `caching_sha2_password` authentication
+/// (the only byte of an [`AuthMoreData`] packet).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum CachingSha2PasswordStatus {
+    /// Scramble matches the cached password hash, the OK packet follows.
+    FastAuthSuccess = 0x03,
+    /// Password is not cached, so the client must send the password (in clear text
+    /// over a secure connection, or encrypted with server's RSA public key).
+    PerformFullAuthentication = 0x04,
+}
+
+impl TryFrom<u8> for CachingSha2PasswordStatus {
+    type Error = u8;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0x03 => Ok(CachingSha2PasswordStatus::FastAuthSuccess),
+            0x04 => Ok(CachingSha2PasswordStatus::PerformFullAuthentication),
+            x => Err(x),
+        }
+    }
+}
+
 /// Extra auth-data beyond the initial challenge.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct AuthMoreData<'a> {
@@ -1130,6 +1529,14 @@ impl<'a> AuthMoreData<'a> {
         self.data.as_bytes()
     }
 
+    /// Returns the `caching_sha2_password` status, if this packet contains one.
+    pub fn caching_sha2_password_status(&self) -> Option<CachingSha2PasswordStatus> {
+        match self.data() {
+            [byte] => CachingSha2PasswordStatus::try_from(*byte).ok(),
+            _ => None,
+        }
+    }
+
     pub fn into_owned(self) -> AuthMoreData<'static> {
         AuthMoreData {
             __header: self.__header,
@@ -1157,103 +1564,287 @@ impl MySerialize for AuthMoreData<'_> {
     }
 }
 
+impl From<CachingSha2PasswordStatus> for AuthMoreData<'static> {
+    fn from(status: CachingSha2PasswordStatus) -> Self {
+        AuthMoreData::new(vec![status as u8])
+    }
+}
+
 define_header!(
-    AuthSwitchRequestHeader,
-    InvalidAuthSwithRequestHeader("Invalid auth switch request header"),
-    0xFE
+    PublicKeyRequestHeader,
+    InvalidPublicKeyRequestHeader("Invalid public key request header"),
+    0x02
 );
 
-/// Old Authentication Method Switch Request Packet.
+/// Public key request.
 ///
-/// Used for It is sent by server to request client to switch to Old Password Authentication
-/// if `CLIENT_PLUGIN_AUTH` capability is not supported (by either the client or the server).
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct OldAuthSwitchRequest {
-    __header: AuthSwitchRequestHeader,
+/// It is sent by client during the `caching_sha2_password` full authentication
+/// (over insecure connection) to request server's RSA public key.
+///
+/// Note, that `sha256_password` plugin uses `0x01` as a request byte, so it's
+/// not represented by this packet.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct PublicKeyRequest {
+    __header: PublicKeyRequestHeader,
 }
 
-impl OldAuthSwitchRequest {
+impl PublicKeyRequest {
     pub fn new() -> Self {
         Self {
-            __header: AuthSwitchRequestHeader::new(),
+            __header: PublicKeyRequestHeader::new(),
         }
     }
-
-    pub const fn auth_plugin(&self) -> AuthPlugin<'static> {
-        AuthPlugin::MysqlOldPassword
-    }
 }
 
-impl<'de> MyDeserialize<'de> for OldAuthSwitchRequest {
+impl<'de> MyDeserialize<'de> for PublicKeyRequest {
     const SIZE: Option<usize> = Some(1);
     type Ctx = ();
 
     fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
         Ok(Self {
-            __header: buf.parse(())?,
+            __header: buf.parse_unchecked(())?,
         })
     }
 }
 
-impl MySerialize for OldAuthSwitchRequest {
+impl MySerialize for PublicKeyRequest {
     fn serialize(&self, buf: &mut Vec<u8>) {
         self.__header.serialize(&mut *buf);
     }
 }
 
-/// Authentication Method Switch Request Packet.
+/// Error returned if server's public key is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum InvalidPublicKeyError {
+    #[error(
+        "Public key is too long ({0} bytes, max is {})",
+        PublicKeyResponse::MAX_LEN
+    )]
+    TooLong(usize),
+    #[error("Public key is not a PEM-encoded RSA public key")]
+    NotPem,
+}
+
+/// Server's RSA public key (PEM-encoded).
 ///
-/// If both server and client support `CLIENT_PLUGIN_AUTH` capability, server can send this packet
-/// to ask client to use another authentication method.
+/// It is sent by server within the `AuthMoreData` packet as a response to
+/// the `PublicKeyRequest` (`caching_sha2_password`) or to the `0x01` request
+/// byte (`sha256_password`).
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub struct AuthSwitchRequest<'a> {
-    __header: AuthSwitchRequestHeader,
-    auth_plugin: RawBytes<'a, NullBytes>,
-    plugin_data: RawBytes<'a, EofBytes>,
+pub struct PublicKeyResponse<'a> {
+    __header: AuthMoreDataHeader,
+    pem: RawBytes<'a, EofBytes>,
 }
 
-impl<'a> AuthSwitchRequest<'a> {
-    pub fn new(
-        auth_plugin: impl Into<Cow<'a, [u8]>>,
-        plugin_data: impl Into<Cow<'a, [u8]>>,
-    ) -> Self {
-        Self {
-            __header: AuthSwitchRequestHeader::new(),
-            auth_plugin: RawBytes::new(auth_plugin),
-            plugin_data: RawBytes::new(plugin_data),
-        }
+impl<'a> PublicKeyResponse<'a> {
+    /// Maximum length of a PEM-encoded public key (enough for a 16384 bit key).
+    pub const MAX_LEN: usize = 4096;
+
+    /// Creates new response validating the given PEM.
+    pub fn new(pem: impl Into<Cow<'a, [u8]>>) -> Result<Self, InvalidPublicKeyError> {
+        let pem = pem.into();
+        Self::validate(&pem)?;
+        Ok(Self {
+            __header: AuthMoreDataHeader::new(),
+            pem: RawBytes::new(pem),
+        })
     }
 
-    pub fn auth_plugin(&self) -> AuthPlugin<'_> {
-        ParseBuf(self.auth_plugin.as_bytes())
-            .parse(())
-            .expect("infallible")
+    /// Returns the PEM-encoded public key.
+    pub fn pem(&self) -> &[u8] {
+        self.pem.as_bytes()
     }
 
-    pub fn plugin_data(&self) -> &[u8] {
-        match self.plugin_data.as_bytes() {
-            [head @ .., 0] => head,
-            all => all,
-        }
+    /// Returns the PEM-encoded public key as a string (lossy converted).
+    pub fn pem_str(&self) -> Cow<'_, str> {
+        self.pem.as_str()
     }
 
-    pub fn into_owned(self) -> AuthSwitchRequest<'static> {
-        AuthSwitchRequest {
+    pub fn into_owned(self) -> PublicKeyResponse<'static> {
+        PublicKeyResponse {
             __header: self.__header,
-            auth_plugin: self.auth_plugin.into_owned(),
-            plugin_data: self.plugin_data.into_owned(),
+            pem: self.pem.into_owned(),
+        }
+    }
+
+    fn validate(pem: &[u8]) -> Result<(), InvalidPublicKeyError> {
+        const MARKERS: [(&[u8], &[u8]); 2] = [
+            (b"-----BEGIN PUBLIC KEY-----", b"-----END PUBLIC KEY-----"),
+            (
+                b"-----BEGIN RSA PUBLIC KEY-----",
+                b"-----END RSA PUBLIC KEY-----",
+            ),
+        ];
+
+        if pem.len() > Self::MAX_LEN {
+            return Err(InvalidPublicKeyError::TooLong(pem.len()));
+        }
+
+        let start = pem
+            .iter()
+            .position(|x| !x.is_ascii_whitespace())
+            .unwrap_or(pem.len());
+        let end = pem
+            .iter()
+            .rposition(|x| !x.is_ascii_whitespace())
+            .map(|x| x + 1)
+            .unwrap_or(start);
+        let pem = &pem[start..end];
+
+        for (prefix, suffix) in MARKERS.iter() {
+            if pem.starts_with(prefix) && pem.ends_with(suffix) {
+                let body = &pem[prefix.len()..pem.len() - suffix.len()];
+                let is_base64 = !body.is_empty()
+                    && body
+                        .iter()
+                        .all(|x| x.is_ascii_alphanumeric() || b"+/=\r\n\t ".contains(x));
+                if is_base64 {
+                    return Ok(());
+                }
+            }
         }
+
+        Err(InvalidPublicKeyError::NotPem)
     }
 }
 
-impl<'de> MyDeserialize<'de> for AuthSwitchRequest<'de> {
+impl<'a> TryFrom<AuthMoreData<'a>> for PublicKeyResponse<'a> {
+    type Error = InvalidPublicKeyError;
+
+    fn try_from(packet: AuthMoreData<'a>) -> Result<Self, Self::Error> {
+        PublicKeyResponse::new(packet.data.0)
+    }
+}
+
+impl<'de> MyDeserialize<'de> for PublicKeyResponse<'de> {
     const SIZE: Option<usize> = None;
     type Ctx = ();
 
     fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
-        Ok(Self {
-            __header: buf.parse(())?,
-            auth_plugin: buf.parse(())?,
+        let __header = buf.parse(())?;
+        let pem: RawBytes<'de, EofBytes> = buf.parse(())?;
+        Self::validate(pem.as_bytes())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(Self { __header, pem })
+    }
+}
+
+impl MySerialize for PublicKeyResponse<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+        self.pem.serialize(buf);
+    }
+}
+
+define_header!(
+    AuthSwitchRequestHeader,
+    InvalidAuthSwithRequestHeader("Invalid auth switch request header"),
+    0xFE
+);
+
+/// Old Authentication Method Switch Request Packet.
+///
+/// Used for It is sent by server to request client to switch to Old Password Authentication
+/// if `CLIENT_PLUGIN_AUTH` capability is not supported (by either the client or the server).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OldAuthSwitchRequest {
+    __header: AuthSwitchRequestHeader,
+}
+
+impl OldAuthSwitchRequest {
+    pub fn new() -> Self {
+        Self {
+            __header: AuthSwitchRequestHeader::new(),
+        }
+    }
+
+    pub const fn auth_plugin(&self) -> AuthPlugin<'static> {
+        AuthPlugin::MysqlOldPassword
+    }
+}
+
+impl<'de> MyDeserialize<'de> for OldAuthSwitchRequest {
+    const SIZE: Option<usize> = Some(1);
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            __header: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for OldAuthSwitchRequest {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+    }
+}
+
+/// Authentication Method Switch Request Packet.
+///
+/// If both server and client support `CLIENT_PLUGIN_AUTH` capability, server can send this packet
+/// to ask client to use another authentication method.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AuthSwitchRequest<'a> {
+    __header: AuthSwitchRequestHeader,
+    auth_plugin: RawBytes<'a, NullBytes>,
+    plugin_data: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> AuthSwitchRequest<'a> {
+    pub fn new(
+        auth_plugin: impl Into<Cow<'a, [u8]>>,
+        plugin_data: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        Self {
+            __header: AuthSwitchRequestHeader::new(),
+            auth_plugin: RawBytes::new(auth_plugin),
+            plugin_data: RawBytes::new(plugin_data),
+        }
+    }
+
+    /// Creates a request to switch to the given plugin.
+    ///
+    /// The `nonce` (a new scramble) is sent with a terminating null character,
+    /// as servers do (see [`AuthSwitchRequest::plugin_data`]).
+    pub fn for_plugin(auth_plugin: &AuthPlugin<'_>, nonce: &[u8]) -> Self {
+        let mut plugin_data = Vec::with_capacity(nonce.len() + 1);
+        plugin_data.extend_from_slice(nonce);
+        plugin_data.push(0);
+        Self::new(auth_plugin.as_bytes().to_vec(), plugin_data)
+    }
+
+    pub fn auth_plugin(&self) -> AuthPlugin<'_> {
+        ParseBuf(self.auth_plugin.as_bytes())
+            .parse(())
+            .expect("infallible")
+    }
+
+    /// Returns plugin data (without the terminating null character, if any).
+    pub fn plugin_data(&self) -> &[u8] {
+        match self.plugin_data.as_bytes() {
+            [head @ .., 0] => head,
+            all => all,
+        }
+    }
+
+    pub fn into_owned(self) -> AuthSwitchRequest<'static> {
+        AuthSwitchRequest {
+            __header: self.__header,
+            auth_plugin: self.auth_plugin.into_owned(),
+            plugin_data: self.plugin_data.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for AuthSwitchRequest<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            __header: buf.parse(())?,
+            auth_plugin: buf.parse(())?,
             plugin_data: buf.parse(())?,
         })
     }
@@ -1267,6 +1858,96 @@ impl MySerialize for AuthSwitchRequest<'_> {
     }
 }
 
+define_header!(
+    AuthNextFactorHeader,
+    InvalidAuthNextFactorHeader("Invalid AuthNextFactor header"),
+    0x02
+);
+
+/// Authentication Next Factor Packet (MySql 8.0.27+).
+///
+/// If both server and client support `MULTI_FACTOR_AUTHENTICATION` capability, server sends
+/// this packet after every but the last successful authentication factor to ask client
+/// to authenticate using the next factor (i.e. the second or the third password).
+///
+/// Client should respond with the initial data of the given plugin (it's similar
+/// to the [`AuthSwitchRequest`] handling).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AuthNextFactor<'a> {
+    __header: AuthNextFactorHeader,
+    auth_plugin: RawBytes<'a, NullBytes>,
+    plugin_data: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> AuthNextFactor<'a> {
+    pub fn new(
+        auth_plugin: impl Into<Cow<'a, [u8]>>,
+        plugin_data: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        Self {
+            __header: AuthNextFactorHeader::new(),
+            auth_plugin: RawBytes::new(auth_plugin),
+            plugin_data: RawBytes::new(plugin_data),
+        }
+    }
+
+    /// Authentication plugin of the next factor.
+    pub fn auth_plugin(&self) -> AuthPlugin<'_> {
+        ParseBuf(self.auth_plugin.as_bytes())
+            .parse(())
+            .expect("infallible")
+    }
+
+    /// Returns plugin data (without the terminating null character, if any).
+    pub fn plugin_data(&self) -> &[u8] {
+        match self.plugin_data.as_bytes() {
+            [head @ .., 0] => head,
+            all => all,
+        }
+    }
+
+    pub fn into_owned(self) -> AuthNextFactor<'static> {
+        AuthNextFactor {
+            __header: self.__header,
+            auth_plugin: self.auth_plugin.into_owned(),
+            plugin_data: self.plugin_data.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for AuthNextFactor<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            __header: buf.parse(())?,
+            auth_plugin: buf.parse(())?,
+            plugin_data: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for AuthNextFactor<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+        self.auth_plugin.serialize(&mut *buf);
+        self.plugin_data.serialize(buf);
+    }
+}
+
+/// Emits a parse warning if the given raw capabilities contain unknown flags.
+fn warn_unknown_capabilities(packet: &str, raw: u32) {
+    let unknown = raw & !CapabilityFlags::all().bits();
+    if unknown != 0 {
+        parse_warn!(
+            "Unknown capability flags ignored in the {} packet (unknown={:#010x})",
+            packet,
+            unknown
+        );
+    }
+}
+
 /// Represents MySql's initial handshake packet.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct HandshakePacket<'a> {
@@ -1282,7 +1963,9 @@ pub struct HandshakePacket<'a> {
     // upper 16 bytes
     capabilities_2: Const<CapabilityFlags, LeU32UpperHalf>,
     auth_plugin_data_len: RawInt<u8>,
-    __reserved: Skip<10>,
+    __reserved: Skip<6>,
+    // MariaDB extended capabilities
+    mariadb_capabilities: RawFlags<MariadbCapabilities, LeU32>,
     scramble_2: Option<RawBytes<'a, BareBytes<{ (u8::MAX as usize) - 8 }>>>,
     auth_plugin_name: Option<RawBytes<'a, NullBytes>>,
 }
@@ -1306,11 +1989,13 @@ impl<'de> MyDeserialize<'de> for HandshakePacket<'de> {
         let capabilities_2: RawConst<LeU32UpperHalf, CapabilityFlags> = sbuf.parse_unchecked(())?;
         let auth_plugin_data_len: RawInt<u8> = sbuf.parse_unchecked(())?;
         let __reserved = sbuf.parse_unchecked(())?;
+        let mariadb_capabilities = sbuf.parse_unchecked(())?;
         let mut scramble_2 = None;
         if capabilities_1.0 & CapabilityFlags::CLIENT_SECURE_CONNECTION.bits() > 0 {
-            let len = max(13, auth_plugin_data_len.0 as i8 - 8) as usize;
+            let len = max(13, usize::from(auth_plugin_data_len.0).saturating_sub(8));
             scramble_2 = buf.parse(len).map(Some)?;
         }
+        warn_unknown_capabilities("handshake", capabilities_1.0 | capabilities_2.0);
         let mut auth_plugin_name = None;
         if capabilities_2.0 & CapabilityFlags::CLIENT_PLUGIN_AUTH.bits() > 0 {
             auth_plugin_name = match buf.eat_all() {
@@ -1332,6 +2017,7 @@ impl<'de> MyDeserialize<'de> for HandshakePacket<'de> {
             capabilities_2: Const::new(CapabilityFlags::from_bits_truncate(capabilities_2.0)),
             auth_plugin_data_len,
             __reserved,
+            mariadb_capabilities,
             scramble_2,
             auth_plugin_name,
         })
@@ -1364,7 +2050,8 @@ impl MySerialize for HandshakePacket<'_> {
             buf.put_u8(0);
         }
 
-        buf.put_slice(&[0_u8; 10][..]);
+        buf.put_slice(&[0_u8; 6][..]);
+        self.mariadb_capabilities.serialize(&mut *buf);
 
         // Assume that the packet is well formed:
         // * the CLIENT_SECURE_CONNECTION is set.
@@ -1414,13 +2101,16 @@ impl<'a> HandshakePacket<'a> {
             default_collation: RawInt::new(default_collation),
             status_flags: Const::new(status_flags),
             capabilities_2: Const::new(capabilities_2),
+            // same as in `serialize`
             auth_plugin_data_len: RawInt::new(
                 scramble_2
                     .as_ref()
-                    .map(|x| x.len() as u8)
+                    .filter(|_| capabilities.contains(CapabilityFlags::CLIENT_PLUGIN_AUTH))
+                    .map(|x| (x.len() + 8) as u8)
                     .unwrap_or_default(),
             ),
             __reserved: Skip,
+            mariadb_capabilities: RawFlags::new(0),
             scramble_2,
             auth_plugin_name: auth_plugin_name.map(RawBytes::new),
         }
@@ -1439,6 +2129,7 @@ impl<'a> HandshakePacket<'a> {
             capabilities_2: self.capabilities_2,
             auth_plugin_data_len: self.auth_plugin_data_len,
             __reserved: self.__reserved,
+            mariadb_capabilities: self.mariadb_capabilities,
             scramble_2: self.scramble_2.map(|x| x.into_owned()),
             auth_plugin_name: self.auth_plugin_name.map(RawBytes::into_owned),
         }
@@ -1523,6 +2214,29 @@ impl<'a> HandshakePacket<'a> {
         self.capabilities_1.0 | self.capabilities_2.0
     }
 
+    /// Value of MariaDB extended server capabilities.
+    ///
+    /// Always empty for MySql servers or if `CLIENT_LONG_PASSWORD` capability is set
+    /// (MariaDB uses it as the `CLIENT_MYSQL` flag).
+    pub fn mariadb_capabilities(&self) -> MariadbCapabilities {
+        if self
+            .capabilities()
+            .contains(CapabilityFlags::CLIENT_LONG_PASSWORD)
+        {
+            MariadbCapabilities::empty()
+        } else {
+            self.mariadb_capabilities.get()
+        }
+    }
+
+    /// Defines MariaDB extended server capabilities.
+    ///
+    /// Note, that client will ignore them unless `CLIENT_LONG_PASSWORD` capability is removed.
+    pub fn with_mariadb_capabilities(mut self, mariadb_capabilities: MariadbCapabilities) -> Self {
+        self.mariadb_capabilities = RawFlags::new(mariadb_capabilities.bits());
+        self
+    }
+
     /// Value of the default_collation field of an initial handshake packet.
     pub fn default_collation(&self) -> u8 {
         self.default_collation.0
@@ -1560,12 +2274,16 @@ type ScrambleBuf<'a> =
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HandshakeResponse<'a> {
     capabilities: Const<CapabilityFlags, LeU32>,
+    max_packet_size: RawInt<LeU32>,
     collation: RawInt<u8>,
+    // MariaDB extended capabilities
+    mariadb_capabilities: RawFlags<MariadbCapabilities, LeU32>,
     scramble_buf: ScrambleBuf<'a>,
     user: RawBytes<'a, NullBytes>,
     db_name: Option<RawBytes<'a, NullBytes>>,
     auth_plugin: Option<AuthPlugin<'a>>,
-    connect_attributes: Option<HashMap<RawBytes<'a, LenEnc>, RawBytes<'a, LenEnc>>>,
+    connect_attributes: Option<ConnectAttributes<'a>>,
+    zstd_compression_level: Option<RawInt<u8>>,
 }
 
 impl<'a> HandshakeResponse<'a> {
@@ -1611,8 +2329,12 @@ impl<'a> HandshakeResponse<'a> {
             capabilities.remove(CapabilityFlags::CLIENT_CONNECT_ATTRS);
         }
 
+        // see `HandshakeResponse::with_zstd_compression_level`
+        capabilities.remove(CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM);
+
         Self {
             scramble_buf,
+            max_packet_size: RawInt::new(MAX_PAYLOAD_LEN as u32 + 1),
             collation: if server_version >= (5, 5, 3) {
                 RawInt::new(UTF8MB4_GENERAL_CI as u8)
             } else {
@@ -1622,12 +2344,9 @@ impl<'a> HandshakeResponse<'a> {
             db_name: db_name.map(RawBytes::new),
             auth_plugin,
             capabilities: Const::new(capabilities),
-            connect_attributes: connect_attributes.map(|attrs| {
-                attrs
-                    .into_iter()
-                    .map(|(k, v)| (RawBytes::new(k.into_bytes()), RawBytes::new(v.into_bytes())))
-                    .collect()
-            }),
+            connect_attributes: connect_attributes.map(connect_attributes_from_map),
+            zstd_compression_level: None,
+            mariadb_capabilities: RawFlags::new(0),
         }
     }
 
@@ -1635,6 +2354,78 @@ impl<'a> HandshakeResponse<'a> {
         self.capabilities.0
     }
 
+    /// Returns the zstd compression level, if `CLIENT_ZSTD_COMPRESSION_ALGORITHM` is set.
+    pub fn zstd_compression_level(&self) -> Option<u8> {
+        self.zstd_compression_level.map(|x| x.0)
+    }
+
+    /// Requests zstd compression with the given level (MySql 8.0.18+).
+    ///
+    /// Sets (or removes) the `CLIENT_ZSTD_COMPRESSION_ALGORITHM` capability flag accordingly.
+    /// Note, that the compression itself is negotiated using the `CLIENT_COMPRESS`
+    /// or the `CLIENT_ZSTD_COMPRESSION_ALGORITHM` flag, so the server should support it.
+    pub fn with_zstd_compression_level(mut self, level: Option<u8>) -> Self {
+        self.capabilities.0.set(
+            CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM,
+            level.is_some(),
+        );
+        self.zstd_compression_level = level.map(RawInt::new);
+        self
+    }
+
+    /// Sets connection attributes (see [`ConnectAttrs`]).
+    ///
+    /// Sets (or removes) the `CLIENT_CONNECT_ATTRS` capability flag accordingly.
+    pub fn with_connect_attributes(
+        mut self,
+        connect_attributes: Option<HashMap<String, String>>,
+    ) -> Self {
+        self.capabilities.0.set(
+            CapabilityFlags::CLIENT_CONNECT_ATTRS,
+            connect_attributes.is_some(),
+        );
+        self.connect_attributes = connect_attributes.map(connect_attributes_from_map);
+        self
+    }
+
+    /// Enables (or disables) multi-factor authentication (MySql 8.0.27+).
+    ///
+    /// Sets (or removes) the `MULTI_FACTOR_AUTHENTICATION` capability flag. Note, that
+    /// the handshake response carries the first factor only — passwords of the second
+    /// and the third factors are used to answer the [`AuthNextFactor`] requests.
+    pub fn with_multi_factor_authentication(mut self, enabled: bool) -> Self {
+        self.capabilities
+            .0
+            .set(CapabilityFlags::MULTI_FACTOR_AUTHENTICATION, enabled);
+        self
+    }
+
+    /// MariaDB extended client capabilities.
+    pub fn mariadb_capabilities(&self) -> MariadbCapabilities {
+        self.mariadb_capabilities.get()
+    }
+
+    /// Defines MariaDB extended client capabilities.
+    ///
+    /// Server will ignore them unless `CLIENT_LONG_PASSWORD` capability is removed
+    /// (MariaDB uses it as the `CLIENT_MYSQL` flag).
+    pub fn with_mariadb_capabilities(mut self, mariadb_capabilities: MariadbCapabilities) -> Self {
+        self.mariadb_capabilities = RawFlags::new(mariadb_capabilities.bits());
+        self
+    }
+
+    /// Maximum size of a command packet, that the client wants to send to the server.
+    pub fn max_packet_size(&self) -> u32 {
+        self.max_packet_size.0
+    }
+
+    /// Defines the maximum size of a command packet, that the client wants to send
+    /// to the server (defaults to 16MiB).
+    pub fn with_max_packet_size(mut self, max_packet_size: u32) -> Self {
+        self.max_packet_size = RawInt::new(max_packet_size);
+        self
+    }
+
     pub fn collation(&self) -> u8 {
         self.collation.0
     }
@@ -1679,9 +2470,10 @@ impl<'de> MyDeserialize<'de> for HandshakeResponse<'de> {
     fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
         let mut sbuf: ParseBuf = buf.parse(4 + 4 + 1 + 23)?;
         let client_flags: RawConst<LeU32, CapabilityFlags> = sbuf.parse_unchecked(())?;
-        sbuf.parse_unchecked::<Skip<4>>(())?;
+        let max_packet_size = sbuf.parse_unchecked(())?;
         let collation = sbuf.parse_unchecked(())?;
-        sbuf.parse_unchecked::<Skip<23>>(())?;
+        sbuf.parse_unchecked::<Skip<19>>(())?;
+        let mariadb_capabilities = sbuf.parse_unchecked(())?;
 
         let user = buf.parse(())?;
         let scramble_buf =
@@ -1706,25 +2498,27 @@ impl<'de> MyDeserialize<'de> for HandshakeResponse<'de> {
 
         let mut connect_attributes = None;
         if client_flags.0 & CapabilityFlags::CLIENT_CONNECT_ATTRS.bits() > 0 {
-            let data_len = buf.parse::<RawInt<LenEnc>>(())?;
-            let mut data: ParseBuf = buf.parse(data_len.0 as usize)?;
-            let mut attrs = HashMap::new();
-            while !data.is_empty() {
-                let key = data.parse::<RawBytes<LenEnc>>(())?;
-                let value = data.parse::<RawBytes<LenEnc>>(())?;
-                attrs.insert(key, value);
-            }
-            connect_attributes = Some(attrs);
+            connect_attributes = Some(parse_connect_attributes(buf)?);
+        }
+
+        let mut zstd_compression_level = None;
+        if client_flags.0 & CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM.bits() > 0 {
+            zstd_compression_level = Some(buf.parse(())?);
         }
 
+        warn_unknown_capabilities("handshake response", client_flags.0);
+
         Ok(Self {
             capabilities: Const::new(CapabilityFlags::from_bits_truncate(client_flags.0)),
+            max_packet_size,
             collation,
+            mariadb_capabilities,
             scramble_buf,
             user,
             db_name,
             auth_plugin,
             connect_attributes,
+            zstd_compression_level,
         })
     }
 }
@@ -1732,9 +2526,10 @@ impl<'de> MyDeserialize<'de> for HandshakeResponse<'de> {
 impl MySerialize for HandshakeResponse<'_> {
     fn serialize(&self, buf: &mut Vec<u8>) {
         self.capabilities.serialize(&mut *buf);
-        buf.put_slice(&[0, 0, 0, 1]);
+        self.max_packet_size.serialize(&mut *buf);
         self.collation.serialize(&mut *buf);
-        buf.put_slice(&[0; 23]);
+        buf.put_slice(&[0; 19]);
+        self.mariadb_capabilities.serialize(&mut *buf);
         self.user.serialize(&mut *buf);
         self.scramble_buf.serialize(&mut *buf);
 
@@ -1747,49 +2542,237 @@ impl MySerialize for HandshakeResponse<'_> {
         }
 
         if let Some(attrs) = &self.connect_attributes {
-            let len = attrs
-                .iter()
-                .map(|(k, v)| lenenc_str_len(k.as_bytes()) + lenenc_str_len(v.as_bytes()))
-                .sum::<u64>();
-            buf.put_lenenc_int(len);
+            serialize_connect_attributes(attrs, &mut *buf);
+        }
 
-            for (name, value) in attrs {
-                name.serialize(&mut *buf);
-                value.serialize(&mut *buf);
-            }
+        if let Some(level) = &self.zstd_compression_level {
+            level.serialize(&mut *buf);
         }
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct SslRequest {
-    capabilities: Const<CapabilityFlags, LeU32>,
-    max_packet_size: RawInt<LeU32>,
-    character_set: RawInt<u8>,
-    __skip: Skip<23>,
+type ConnectAttributes<'a> = HashMap<RawBytes<'a, LenEnc>, RawBytes<'a, LenEnc>>;
+
+fn connect_attributes_from_map<'a>(attrs: HashMap<String, String>) -> ConnectAttributes<'a> {
+    attrs
+        .into_iter()
+        .map(|(k, v)| (RawBytes::new(k.into_bytes()), RawBytes::new(v.into_bytes())))
+        .collect()
 }
 
-impl SslRequest {
-    pub fn new(capabilities: CapabilityFlags, max_packet_size: u32, character_set: u8) -> Self {
-        Self {
-            capabilities: Const::new(capabilities),
-            max_packet_size: RawInt::new(max_packet_size),
-            character_set: RawInt::new(character_set),
-            __skip: Skip,
-        }
+fn parse_connect_attributes<'de>(buf: &mut ParseBuf<'de>) -> io::Result<ConnectAttributes<'de>> {
+    let data_len = buf.parse::<RawInt<LenEnc>>(())?;
+    let mut data: ParseBuf = buf.parse(data_len.0 as usize)?;
+    let mut attrs = HashMap::new();
+    while !data.is_empty() {
+        let key = data.parse::<RawBytes<LenEnc>>(())?;
+        let value = data.parse::<RawBytes<LenEnc>>(())?;
+        attrs.insert(key, value);
     }
+    Ok(attrs)
+}
 
-    pub fn capabilities(&self) -> CapabilityFlags {
-        self.capabilities.0
-    }
+fn serialize_connect_attributes(attrs: &ConnectAttributes<'_>, buf: &mut Vec<u8>) {
+    let len = attrs
+        .iter()
+        .map(|(k, v)| lenenc_str_len(k.as_bytes()) + lenenc_str_len(v.as_bytes()))
+        .sum::<u64>();
+    buf.put_lenenc_int(len);
 
-    pub fn max_packet_size(&self) -> u32 {
-        self.max_packet_size.0
+    for (name, value) in attrs {
+        name.serialize(&mut *buf);
+        value.serialize(&mut *buf);
     }
+}
 
-    pub fn character_set(&self) -> u8 {
-        self.character_set.0
-    }
+/// Connection attribute violates server-side length limits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum ConnectAttrsError {
+    /// Attribute name is longer than [`ConnectAttrs::MAX_KEY_LEN`].
+    #[error("Connection attribute name `{key}` is longer than {max} bytes")]
+    KeyTooLong {
+        /// Attribute name.
+        key: String,
+        /// Maximum length.
+        max: usize,
+    },
+    /// Attribute value is longer than [`ConnectAttrs::MAX_VALUE_LEN`].
+    #[error("Value of the `{key}` connection attribute is longer than {max} bytes")]
+    ValueTooLong {
+        /// Attribute name.
+        key: String,
+        /// Maximum length.
+        max: usize,
+    },
+    /// Encoded attributes are longer than [`ConnectAttrs::MAX_TOTAL_LEN`].
+    #[error("Encoded connection attributes take {len} bytes (max {max})")]
+    TooLong {
+        /// Encoded length.
+        len: u64,
+        /// Maximum length.
+        max: u64,
+    },
+}
+
+/// Connection attributes (sent if `CLIENT_CONNECT_ATTRS` is negotiated).
+///
+/// [`ConnectAttrs::new`] pre-populates standard attributes (`_client_name`, `_client_version`,
+/// `_os`, `_platform`, `_pid`). Server truncates longer attributes, so length limits are
+/// enforced here to avoid surprises.
+///
+/// Use `HashMap::from` to pass it to [`HandshakeResponse::with_connect_attributes`]
+/// or [`ComChangeUser::with_connect_attributes`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectAttrs {
+    attrs: HashMap<String, String>,
+}
+
+impl ConnectAttrs {
+    /// Maximum length of an attribute name in bytes.
+    pub const MAX_KEY_LEN: usize = 32;
+    /// Maximum length of an attribute value in bytes.
+    pub const MAX_VALUE_LEN: usize = 1024;
+    /// Maximum length of encoded attributes in bytes (not including the length prefix).
+    pub const MAX_TOTAL_LEN: u64 = 65535;
+
+    /// Creates connection attributes with standard attributes pre-populated
+    /// (client name and version are of this crate, use [`ConnectAttrs::with_client`]
+    /// to override them).
+    pub fn new() -> Self {
+        let mut attrs = HashMap::new();
+        attrs.insert("_os".into(), std::env::consts::OS.into());
+        attrs.insert("_platform".into(), std::env::consts::ARCH.into());
+        attrs.insert("_pid".into(), std::process::id().to_string());
+        Self { attrs }.with_client(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+    }
+
+    /// Creates empty connection attributes.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Defines `_client_name` and `_client_version` attributes.
+    ///
+    /// # Panic
+    ///
+    /// Will panic if any of given values violates length limits
+    /// (see [`ConnectAttrs::MAX_VALUE_LEN`]).
+    pub fn with_client(mut self, name: &str, version: &str) -> Self {
+        self.insert("_client_name", name)
+            .expect("client name is too long");
+        self.insert("_client_version", version)
+            .expect("client version is too long");
+        self
+    }
+
+    /// Defines an attribute.
+    pub fn with_attr(mut self, key: &str, value: &str) -> Result<Self, ConnectAttrsError> {
+        self.insert(key, value)?;
+        Ok(self)
+    }
+
+    /// Inserts an attribute, replacing the previous value, if any.
+    ///
+    /// Returns an error (and leaves attributes untouched) if length limits are violated.
+    pub fn insert(&mut self, key: &str, value: &str) -> Result<(), ConnectAttrsError> {
+        if key.len() > Self::MAX_KEY_LEN {
+            return Err(ConnectAttrsError::KeyTooLong {
+                key: key.into(),
+                max: Self::MAX_KEY_LEN,
+            });
+        }
+        if value.len() > Self::MAX_VALUE_LEN {
+            return Err(ConnectAttrsError::ValueTooLong {
+                key: key.into(),
+                max: Self::MAX_VALUE_LEN,
+            });
+        }
+
+        let mut len = self.encoded_len() + attr_len(key, value);
+        if let Some(old) = self.attrs.get(key) {
+            len -= attr_len(key, old);
+        }
+        if len > Self::MAX_TOTAL_LEN {
+            return Err(ConnectAttrsError::TooLong {
+                len,
+                max: Self::MAX_TOTAL_LEN,
+            });
+        }
+
+        self.attrs.insert(key.into(), value.into());
+        Ok(())
+    }
+
+    /// Removes an attribute returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.attrs.remove(key)
+    }
+
+    /// Returns the value of an attribute.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attrs.get(key).map(String::as_str)
+    }
+
+    /// Returns an iterator over `(name, value)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attrs.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns the length of encoded attributes (not including the length prefix).
+    pub fn encoded_len(&self) -> u64 {
+        self.attrs.iter().map(|(k, v)| attr_len(k, v)).sum()
+    }
+}
+
+fn attr_len(key: &str, value: &str) -> u64 {
+    lenenc_str_len(key.as_bytes()) + lenenc_str_len(value.as_bytes())
+}
+
+impl From<ConnectAttrs> for HashMap<String, String> {
+    fn from(attrs: ConnectAttrs) -> Self {
+        attrs.attrs
+    }
+}
+
+impl MySerialize for ConnectAttrs {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        buf.put_lenenc_int(self.encoded_len());
+        for (name, value) in &self.attrs {
+            buf.put_lenenc_str(name.as_bytes());
+            buf.put_lenenc_str(value.as_bytes());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SslRequest {
+    capabilities: Const<CapabilityFlags, LeU32>,
+    max_packet_size: RawInt<LeU32>,
+    character_set: RawInt<u8>,
+    __skip: Skip<23>,
+}
+
+impl SslRequest {
+    pub fn new(capabilities: CapabilityFlags, max_packet_size: u32, character_set: u8) -> Self {
+        Self {
+            capabilities: Const::new(capabilities),
+            max_packet_size: RawInt::new(max_packet_size),
+            character_set: RawInt::new(character_set),
+            __skip: Skip,
+        }
+    }
+
+    pub fn capabilities(&self) -> CapabilityFlags {
+        self.capabilities.0
+    }
+
+    pub fn max_packet_size(&self) -> u32 {
+        self.max_packet_size.0
+    }
+
+    pub fn character_set(&self) -> u8 {
+        self.character_set.0
+    }
 }
 
 impl<'de> MyDeserialize<'de> for SslRequest {
@@ -1799,6 +2782,7 @@ impl<'de> MyDeserialize<'de> for SslRequest {
     fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
         let mut buf: ParseBuf = buf.parse(Self::SIZE.unwrap())?;
         let raw_capabilities = buf.parse_unchecked::<RawConst<LeU32, CapabilityFlags>>(())?;
+        warn_unknown_capabilities("SSL request", raw_capabilities.0);
         Ok(Self {
             capabilities: Const::new(CapabilityFlags::from_bits_truncate(raw_capabilities.0)),
             max_packet_size: buf.parse_unchecked(())?,
@@ -1861,6 +2845,17 @@ impl MySerialize for StmtPacket {
 }
 
 impl StmtPacket {
+    pub fn new(statement_id: u32, num_columns: u16, num_params: u16, warning_count: u16) -> Self {
+        Self {
+            status: ConstU8::new(),
+            statement_id: RawInt::new(statement_id),
+            num_columns: RawInt::new(num_columns),
+            num_params: RawInt::new(num_params),
+            __skip: Skip,
+            warning_count: RawInt::new(warning_count),
+        }
+    }
+
     /// Value of the statement_id field of a statement packet.
     pub fn statement_id(&self) -> u32 {
         *self.statement_id
@@ -1962,6 +2957,151 @@ impl<T, U: AsRef<[u8]>> AsRef<[u8]> for NullBitmap<T, U> {
     }
 }
 
+/// Query attribute (MySql 8.0.23+, see `CLIENT_QUERY_ATTRIBUTES`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryAttribute<'a> {
+    name: RawBytes<'a, LenEnc>,
+    value: ValueRef<'a>,
+}
+
+impl<'a> QueryAttribute<'a> {
+    /// Creates a new query attribute.
+    pub fn new(name: impl Into<Cow<'a, [u8]>>, value: impl Into<ValueRef<'a>>) -> Self {
+        Self {
+            name: RawBytes::new(name),
+            value: value.into(),
+        }
+    }
+
+    /// Returns the raw name of the attribute.
+    pub fn name_ref(&self) -> &[u8] {
+        self.name.as_bytes()
+    }
+
+    /// Returns the name of the attribute as a UTF-8 string (lossy converted).
+    pub fn name_str(&self) -> Cow<'_, str> {
+        self.name.as_str()
+    }
+
+    /// Returns the value of the attribute.
+    pub fn value(&self) -> ValueRef<'a> {
+        self.value
+    }
+}
+
+/// Serializes parameters in the `CLIENT_QUERY_ATTRIBUTES` layout, i.e. null bitmap,
+/// new params bound flag, types with names and values (parameter count is not written).
+///
+/// `skip_value` defines parameters, which value should not be written (see long data).
+fn serialize_named_params<'a>(
+    params: impl Iterator<Item = (&'a [u8], ValueRef<'a>)> + Clone,
+    skip_value: impl Fn(usize) -> bool,
+    buf: &mut Vec<u8>,
+) {
+    // NaN and infinity can't be stored in MySql
+    let params = params.map(|(name, value)| (name, value.finite_or_null()));
+    let num_params = params.clone().count();
+    if num_params == 0 {
+        return;
+    }
+
+    let mut bitmap_bytes = vec![0; NullBitmap::<ClientSide>::bitmap_len(num_params)];
+    let mut bitmap = NullBitmap::<ClientSide, _>::from_bytes(&mut bitmap_bytes);
+    for (i, (_, value)) in params.clone().enumerate() {
+        bitmap.set(i, value == ValueRef::NULL && !skip_value(i));
+    }
+    buf.put_slice(&bitmap_bytes);
+    buf.put_u8(StmtExecuteParamsFlags::NEW_PARAMS_BOUND.bits());
+
+    for (i, (name, value)) in params.clone().enumerate() {
+        let (column_type, flags) = match value {
+            ValueRef::NULL if skip_value(i) => {
+                (ColumnType::MYSQL_TYPE_BLOB, StmtExecuteParamFlags::empty())
+            }
+            _ => param_type(&value),
+        };
+        buf.put_slice(&[column_type as u8, flags.bits()]);
+        buf.put_lenenc_str(name);
+    }
+
+    for (i, (_, value)) in params.enumerate() {
+        if !skip_value(i) {
+            value.serialize(&mut *buf);
+        }
+    }
+}
+
+define_header!(ComQueryHeader, COM_QUERY, InvalidComQueryHeader);
+
+/// Executes a text-based query.
+///
+/// Layout of the packet depends on capabilities negotiated for the connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComQuery<'a> {
+    __header: ComQueryHeader,
+    capabilities: CapabilityFlags,
+    query: RawBytes<'a, EofBytes>,
+    query_attributes: Vec<QueryAttribute<'a>>,
+}
+
+impl<'a> ComQuery<'a> {
+    /// Creates a new packet for the given capabilities.
+    pub fn new(capabilities: CapabilityFlags, query: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            __header: ComQueryHeader::new(),
+            capabilities,
+            query: RawBytes::new(query),
+            query_attributes: Vec::new(),
+        }
+    }
+
+    /// Defines query attributes for this query.
+    ///
+    /// # Note
+    ///
+    /// Attributes are only sent if `CLIENT_QUERY_ATTRIBUTES` is negotiated for the connection.
+    pub fn with_query_attributes(mut self, query_attributes: Vec<QueryAttribute<'a>>) -> Self {
+        self.query_attributes = query_attributes;
+        self
+    }
+
+    pub fn capabilities(&self) -> CapabilityFlags {
+        self.capabilities
+    }
+
+    pub fn query(&self) -> &[u8] {
+        self.query.as_bytes()
+    }
+
+    pub fn query_attributes(&self) -> &[QueryAttribute<'a>] {
+        &self.query_attributes
+    }
+}
+
+impl MySerialize for ComQuery<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+
+        if self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_QUERY_ATTRIBUTES)
+        {
+            buf.put_lenenc_int(self.query_attributes.len() as u64);
+            // parameter set count (always 1)
+            buf.put_lenenc_int(1);
+            serialize_named_params(
+                self.query_attributes
+                    .iter()
+                    .map(|attr| (attr.name.as_bytes(), attr.value)),
+                |_| false,
+                &mut *buf,
+            );
+        }
+
+        self.query.serialize(&mut *buf);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ComStmtExecuteRequestBuilder {
     pub stmt_id: u32,
@@ -1976,12 +3116,37 @@ impl ComStmtExecuteRequestBuilder {
 }
 
 impl ComStmtExecuteRequestBuilder {
+    /// Builds the request for the given parameters.
+    ///
+    /// NaN and infinite floats are sent as `NULL` (same as in the text protocol).
     pub fn build(self, params: &[Value]) -> (ComStmtExecuteRequest<'_>, bool) {
+        self.build_inner(
+            params
+                .iter()
+                .map(|param| ValueRef::from(param).finite_or_null())
+                .collect(),
+            params.iter().collect(),
+        )
+    }
+
+    /// Same as [`ComStmtExecuteRequestBuilder::build`] but takes borrowed values,
+    /// so that byte strings are not cloned (see [`ValueRef`]).
+    pub fn build_ref<'a>(self, params: &[ValueRef<'a>]) -> (ComStmtExecuteRequest<'a>, bool) {
+        self.build_inner(
+            params.iter().map(ValueRef::finite_or_null).collect(),
+            Vec::new(),
+        )
+    }
+
+    fn build_inner<'a>(
+        self,
+        params: Vec<ValueRef<'a>>,
+        values: Vec<&'a Value>,
+    ) -> (ComStmtExecuteRequest<'a>, bool) {
         let bitmap_len = NullBitmap::<ClientSide>::bitmap_len(params.len());
 
         let mut bitmap_bytes = vec![0; bitmap_len];
         let mut bitmap = NullBitmap::<ClientSide, _>::from_bytes(&mut bitmap_bytes);
-        let params = params.iter().collect::<Vec<_>>();
 
         let meta_len = params.len() * 2;
 
@@ -2005,6 +3170,10 @@ impl ComStmtExecuteRequestBuilder {
                 iteration_count: ConstU32::new(),
                 params_flags: Const::new(StmtExecuteParamsFlags::NEW_PARAMS_BOUND),
                 bitmap: RawBytes::new(bitmap_bytes),
+                long_data_sent: vec![false; params.len()],
+                values,
+                capabilities: CapabilityFlags::empty(),
+                query_attributes: Vec::new(),
                 params,
                 as_long_data,
             },
@@ -2035,8 +3204,13 @@ pub struct ComStmtExecuteRequest<'a> {
     // max params / bits per byte = 8192
     bitmap: RawBytes<'a, BareBytes<8192>>,
     params_flags: Const<StmtExecuteParamsFlags, u8>,
-    params: Vec<&'a Value>,
+    params: Vec<ValueRef<'a>>,
+    /// Parameters given to [`ComStmtExecuteRequestBuilder::build`].
+    values: Vec<&'a Value>,
     as_long_data: bool,
+    long_data_sent: Vec<bool>,
+    capabilities: CapabilityFlags,
+    query_attributes: Vec<QueryAttribute<'a>>,
 }
 
 impl<'a> ComStmtExecuteRequest<'a> {
@@ -2056,19 +3230,97 @@ impl<'a> ComStmtExecuteRequest<'a> {
         self.params_flags.0
     }
 
+    /// Returns parameters given to [`ComStmtExecuteRequestBuilder::build`].
+    ///
+    /// It's empty if the request is built using [`ComStmtExecuteRequestBuilder::build_ref`]
+    /// (see [`ComStmtExecuteRequest::params_ref`]).
     pub fn params(&self) -> &[&'a Value] {
+        self.values.as_ref()
+    }
+
+    /// Returns parameters of this request.
+    pub fn params_ref(&self) -> &[ValueRef<'a>] {
         self.params.as_ref()
     }
 
     pub fn as_long_data(&self) -> bool {
         self.as_long_data
     }
+
+    /// Marks the parameter as sent using `COM_STMT_SEND_LONG_DATA`
+    /// (see [`ComStmtSendLongData::chunked`]), so that its value won't be serialized.
+    ///
+    /// # Panic
+    ///
+    /// Will panic if `param_index` is out of bounds.
+    pub fn with_long_data_sent(mut self, param_index: usize) -> Self {
+        self.long_data_sent[param_index] = true;
+        // server ignores the value, but NULL parameter should not hide the long data
+        NullBitmap::<ClientSide, _>::from_bytes(self.bitmap.0.to_mut()).set(param_index, false);
+        self
+    }
+
+    /// Defines capabilities negotiated for the connection (empty by default).
+    ///
+    /// Layout of the packet depends on `CLIENT_QUERY_ATTRIBUTES`.
+    pub fn with_capabilities(mut self, capabilities: CapabilityFlags) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Defines query attributes for this execution.
+    ///
+    /// # Note
+    ///
+    /// Attributes are only sent if `CLIENT_QUERY_ATTRIBUTES` is negotiated for the connection
+    /// (see [`ComStmtExecuteRequest::with_capabilities`]).
+    pub fn with_query_attributes(mut self, query_attributes: Vec<QueryAttribute<'a>>) -> Self {
+        self.query_attributes = query_attributes;
+        self
+    }
+
+    pub fn capabilities(&self) -> CapabilityFlags {
+        self.capabilities
+    }
+
+    pub fn query_attributes(&self) -> &[QueryAttribute<'a>] {
+        &self.query_attributes
+    }
+
+    /// Returns `true` if value of the parameter is sent using `COM_STMT_SEND_LONG_DATA`,
+    /// i.e. if it was marked using [`ComStmtExecuteRequest::with_long_data_sent`] or if
+    /// it is a byte string and [`ComStmtExecuteRequest::as_long_data`] is `true`.
+    pub fn is_long_data_sent(&self, param_index: usize) -> bool {
+        match self.params.get(param_index) {
+            Some(ValueRef::Bytes(_)) if self.as_long_data => true,
+            Some(_) => self.long_data_sent[param_index],
+            None => false,
+        }
+    }
 }
 
 impl MySerialize for ComStmtExecuteRequest<'_> {
     fn serialize(&self, buf: &mut Vec<u8>) {
         self.com_stmt_execute.serialize(&mut *buf);
         self.stmt_id.serialize(&mut *buf);
+
+        if self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_QUERY_ATTRIBUTES)
+        {
+            let attrs = &self.query_attributes;
+            buf.put_u8((self.flags.0 | CursorType::PARAMETER_COUNT_AVAILABLE).bits());
+            self.iteration_count.serialize(&mut *buf);
+            buf.put_lenenc_int((self.params.len() + attrs.len()) as u64);
+            let params = self
+                .params
+                .iter()
+                .map(|param| (&[][..], *param))
+                .chain(attrs.iter().map(|attr| (attr.name.as_bytes(), attr.value)));
+            serialize_named_params(params, |i| self.is_long_data_sent(i), &mut *buf);
+            return;
+        }
+
         self.flags.serialize(&mut *buf);
         self.iteration_count.serialize(&mut *buf);
 
@@ -2077,127 +3329,1078 @@ impl MySerialize for ComStmtExecuteRequest<'_> {
             self.params_flags.serialize(&mut *buf);
         }
 
-        for param in &self.params {
+        for (i, param) in self.params.iter().enumerate() {
             let (column_type, flags) = match param {
-                Value::NULL => (ColumnType::MYSQL_TYPE_NULL, StmtExecuteParamFlags::empty()),
-                Value::Bytes(_) => (
-                    ColumnType::MYSQL_TYPE_VAR_STRING,
-                    StmtExecuteParamFlags::empty(),
-                ),
-                Value::Int(_) => (
-                    ColumnType::MYSQL_TYPE_LONGLONG,
-                    StmtExecuteParamFlags::empty(),
-                ),
-                Value::UInt(_) => (
-                    ColumnType::MYSQL_TYPE_LONGLONG,
-                    StmtExecuteParamFlags::UNSIGNED,
-                ),
-                Value::Float(_) => (ColumnType::MYSQL_TYPE_FLOAT, StmtExecuteParamFlags::empty()),
-                Value::Double(_) => (
-                    ColumnType::MYSQL_TYPE_DOUBLE,
-                    StmtExecuteParamFlags::empty(),
-                ),
-                Value::Date(..) => (
-                    ColumnType::MYSQL_TYPE_DATETIME,
-                    StmtExecuteParamFlags::empty(),
-                ),
-                Value::Time(..) => (ColumnType::MYSQL_TYPE_TIME, StmtExecuteParamFlags::empty()),
+                ValueRef::NULL if self.long_data_sent[i] => {
+                    (ColumnType::MYSQL_TYPE_BLOB, StmtExecuteParamFlags::empty())
+                }
+                _ => param_type(param),
             };
 
             buf.put_slice(&[column_type as u8, flags.bits()]);
         }
 
-        for param in &self.params {
-            match **param {
-                Value::Int(_)
-                | Value::UInt(_)
-                | Value::Float(_)
-                | Value::Double(_)
-                | Value::Date(..)
-                | Value::Time(..) => {
+        for (i, param) in self.params.iter().enumerate() {
+            if self.long_data_sent[i] {
+                continue;
+            }
+
+            match *param {
+                ValueRef::Int(_)
+                | ValueRef::UInt(_)
+                | ValueRef::Float(_)
+                | ValueRef::Double(_)
+                | ValueRef::Date(..)
+                | ValueRef::Time(..) => {
                     param.serialize(buf);
                 }
-                Value::Bytes(_) if !self.as_long_data => {
+                ValueRef::Bytes(_) if !self.as_long_data => {
                     param.serialize(buf);
                 }
-                Value::Bytes(_) | Value::NULL => {}
+                ValueRef::Bytes(_) | ValueRef::NULL => {}
             }
         }
     }
 }
 
-define_header!(
-    ComStmtSendLongDataHeader,
-    COM_STMT_SEND_LONG_DATA,
-    InvalidComStmtSendLongDataHeader
-);
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct ComStmtSendLongData<'a> {
-    __header: ComStmtSendLongDataHeader,
-    stmt_id: RawInt<LeU32>,
-    param_index: RawInt<LeU16>,
-    data: RawBytes<'a, EofBytes>,
+/// Returns the type of a parameter of a prepared statement, that corresponds to the given value.
+fn param_type(value: &ValueRef<'_>) -> (ColumnType, StmtExecuteParamFlags) {
+    match value {
+        ValueRef::NULL => (ColumnType::MYSQL_TYPE_NULL, StmtExecuteParamFlags::empty()),
+        ValueRef::Bytes(_) => (
+            ColumnType::MYSQL_TYPE_VAR_STRING,
+            StmtExecuteParamFlags::empty(),
+        ),
+        ValueRef::Int(_) => (
+            ColumnType::MYSQL_TYPE_LONGLONG,
+            StmtExecuteParamFlags::empty(),
+        ),
+        ValueRef::UInt(_) => (
+            ColumnType::MYSQL_TYPE_LONGLONG,
+            StmtExecuteParamFlags::UNSIGNED,
+        ),
+        ValueRef::Float(_) => (ColumnType::MYSQL_TYPE_FLOAT, StmtExecuteParamFlags::empty()),
+        ValueRef::Double(_) => (
+            ColumnType::MYSQL_TYPE_DOUBLE,
+            StmtExecuteParamFlags::empty(),
+        ),
+        ValueRef::Date(..) => (
+            ColumnType::MYSQL_TYPE_DATETIME,
+            StmtExecuteParamFlags::empty(),
+        ),
+        ValueRef::Time(..) => (ColumnType::MYSQL_TYPE_TIME, StmtExecuteParamFlags::empty()),
+    }
 }
 
-impl<'a> ComStmtSendLongData<'a> {
-    pub fn new(stmt_id: u32, param_index: u16, data: impl Into<Cow<'a, [u8]>>) -> Self {
-        Self {
-            __header: ComStmtSendLongDataHeader::new(),
-            stmt_id: RawInt::new(stmt_id),
-            param_index: RawInt::new(param_index),
-            data: RawBytes::new(data),
-        }
-    }
+my_bitflags! {
+    StmtBulkFlags,
+    #[error("Unknown flags in the raw value of StmtBulkFlags (raw={:b})", _0)]
+    UnknownStmtBulkFlags,
+    u16,
 
-    pub fn into_owned(self) -> ComStmtSendLongData<'static> {
-        ComStmtSendLongData {
-            __header: self.__header,
-            stmt_id: self.stmt_id,
-            param_index: self.param_index,
-            data: self.data.into_owned(),
-        }
+    /// Flags of a MariaDB's `COM_STMT_BULK_EXECUTE`.
+    pub struct StmtBulkFlags: u16 {
+        /// Server should return a result set with affected rows and auto-increment id
+        /// for every row of parameters.
+        const SEND_UNIT_RESULTS = 0x0040;
+        /// Parameter types are sent before the first row of parameters.
+        const SEND_TYPES_TO_SERVER = 0x0080;
     }
 }
 
-impl MySerialize for ComStmtSendLongData<'_> {
-    fn serialize(&self, buf: &mut Vec<u8>) {
-        self.__header.serialize(&mut *buf);
-        self.stmt_id.serialize(&mut *buf);
-        self.param_index.serialize(&mut *buf);
-        self.data.serialize(&mut *buf);
-    }
+/// Indicator, that precedes every parameter value of a `COM_STMT_BULK_EXECUTE`.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum StmtBulkIndicator {
+    /// Parameter value follows.
+    STMT_INDICATOR_NONE = 0,
+    /// Parameter is `NULL`.
+    STMT_INDICATOR_NULL = 1,
+    /// Default value of a column should be used.
+    STMT_INDICATOR_DEFAULT = 2,
+    /// Parameter should be ignored (i.e. column is not updated by an `UPDATE`).
+    STMT_INDICATOR_IGNORE = 3,
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub struct ComStmtClose {
-    pub stmt_id: u32,
+/// Parameter of a `COM_STMT_BULK_EXECUTE` row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BulkParam<'a> {
+    /// Parameter value (`NULL` values are sent as `STMT_INDICATOR_NULL`).
+    Value(ValueRef<'a>),
+    /// Default value of a column (`STMT_INDICATOR_DEFAULT`).
+    Default,
+    /// Parameter should be ignored (`STMT_INDICATOR_IGNORE`).
+    Ignore,
 }
 
-impl ComStmtClose {
-    pub fn new(stmt_id: u32) -> Self {
-        Self { stmt_id }
+impl BulkParam<'_> {
+    /// Returns the indicator of this parameter.
+    pub fn indicator(&self) -> StmtBulkIndicator {
+        match self {
+            BulkParam::Value(ValueRef::NULL) => StmtBulkIndicator::STMT_INDICATOR_NULL,
+            BulkParam::Value(_) => StmtBulkIndicator::STMT_INDICATOR_NONE,
+            BulkParam::Default => StmtBulkIndicator::STMT_INDICATOR_DEFAULT,
+            BulkParam::Ignore => StmtBulkIndicator::STMT_INDICATOR_IGNORE,
+        }
     }
 }
 
-impl MySerialize for ComStmtClose {
-    fn serialize(&self, buf: &mut Vec<u8>) {
-        buf.put_u8(Command::COM_STMT_CLOSE as u8);
-        buf.put_u32_le(self.stmt_id);
+impl<'a> From<ValueRef<'a>> for BulkParam<'a> {
+    fn from(value: ValueRef<'a>) -> Self {
+        BulkParam::Value(value)
+    }
+}
+
+impl<'a> From<&'a Value> for BulkParam<'a> {
+    fn from(value: &'a Value) -> Self {
+        BulkParam::Value(value.into())
     }
 }
 
+/// Error of [`ComStmtBulkExecuteRequest::new`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum BulkExecuteError {
+    /// There are no rows of parameters.
+    #[error("COM_STMT_BULK_EXECUTE requires at least one row of parameters")]
+    NoRows,
+    /// Number of parameters in a row differs from the first row.
+    #[error("Row {} has {} parameters but {} expected", row, given, expected)]
+    WrongLength {
+        /// Index of the row.
+        row: usize,
+        /// Number of parameters in the first row.
+        expected: usize,
+        /// Number of parameters in the row.
+        given: usize,
+    },
+    /// Parameter type differs between rows (types are sent once for all rows).
+    #[error(
+        "Parameter {} of row {} has type {:?} but {:?} expected",
+        index,
+        row,
+        given,
+        expected
+    )]
+    TypeMismatch {
+        /// Index of the row.
+        row: usize,
+        /// Index of the parameter.
+        index: usize,
+        /// Type of the parameter defined by previous rows.
+        expected: (ColumnType, StmtExecuteParamFlags),
+        /// Type of the parameter in the row.
+        given: (ColumnType, StmtExecuteParamFlags),
+    },
+}
+
 define_header!(
-    ComRegisterSlaveHeader,
-    COM_REGISTER_SLAVE,
-    InvalidComRegisterSlaveHeader
+    ComStmtBulkExecuteHeader,
+    InvalidComStmtBulkExecuteHeader("Invalid COM_STMT_BULK_EXECUTE header"),
+    0xFA
 );
 
-/// Registers a slave at the master. Should be sent before requesting a binlog events
-/// with `COM_BINLOG_DUMP`.
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub struct ComRegisterSlave<'a> {
-    header: ComRegisterSlaveHeader,
+/// MariaDB's `COM_STMT_BULK_EXECUTE` (MariaDB 10.2.7+), that executes a prepared statement
+/// once for every given row of parameters.
+///
+/// Requires the `MARIADB_CLIENT_STMT_BULK_OPERATIONS` extended capability. Use
+/// [`bulk_execute::BulkExecuteResponseDecoder`] to decode the response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComStmtBulkExecuteRequest<'a> {
+    __header: ComStmtBulkExecuteHeader,
+    stmt_id: RawInt<LeU32>,
+    flags: Const<StmtBulkFlags, LeU16>,
+    param_types: Vec<(ColumnType, StmtExecuteParamFlags)>,
+    rows: Vec<Vec<BulkParam<'a>>>,
+}
+
+impl<'a> ComStmtBulkExecuteRequest<'a> {
+    /// Creates a new request for the given rows of parameters.
+    ///
+    /// Parameter types are sent once for all rows, so values of a parameter must have the same
+    /// type in every row (`NULL`, `Default` and `Ignore` are compatible with any type).
+    pub fn new<I, R, P>(stmt_id: u32, rows: I) -> Result<Self, BulkExecuteError>
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoIterator<Item = P>,
+        P: Into<BulkParam<'a>>,
+    {
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|param| match param.into() {
+                        // NaN and infinity can't be stored in MySql
+                        BulkParam::Value(value) => BulkParam::Value(value.finite_or_null()),
+                        param => param,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let num_params = rows.first().ok_or(BulkExecuteError::NoRows)?.len();
+        let mut param_types: Vec<Option<(ColumnType, StmtExecuteParamFlags)>> =
+            vec![None; num_params];
+
+        for (row_index, row) in rows.iter().enumerate() {
+            if row.len() != num_params {
+                return Err(BulkExecuteError::WrongLength {
+                    row: row_index,
+                    expected: num_params,
+                    given: row.len(),
+                });
+            }
+
+            for (index, param) in row.iter().enumerate() {
+                let value = match param {
+                    BulkParam::Value(ValueRef::NULL) | BulkParam::Default | BulkParam::Ignore => {
+                        continue
+                    }
+                    BulkParam::Value(value) => value,
+                };
+
+                let ty = param_type(value);
+                match param_types[index] {
+                    Some(expected) if expected != ty => {
+                        return Err(BulkExecuteError::TypeMismatch {
+                            row: row_index,
+                            index,
+                            expected,
+                            given: ty,
+                        })
+                    }
+                    Some(_) => (),
+                    None => param_types[index] = Some(ty),
+                }
+            }
+        }
+
+        Ok(Self {
+            __header: ComStmtBulkExecuteHeader::new(),
+            stmt_id: RawInt::new(stmt_id),
+            flags: Const::new(StmtBulkFlags::SEND_TYPES_TO_SERVER),
+            param_types: param_types
+                .into_iter()
+                .map(|ty| ty.unwrap_or_else(|| param_type(&ValueRef::NULL)))
+                .collect(),
+            rows,
+        })
+    }
+
+    /// Requests a result set with per-row results (see [`StmtBulkFlags::SEND_UNIT_RESULTS`]).
+    pub fn with_unit_results(mut self, unit_results: bool) -> Self {
+        self.flags
+            .0
+            .set(StmtBulkFlags::SEND_UNIT_RESULTS, unit_results);
+        self
+    }
+
+    pub fn stmt_id(&self) -> u32 {
+        self.stmt_id.0
+    }
+
+    pub fn flags(&self) -> StmtBulkFlags {
+        self.flags.0
+    }
+
+    /// Returns types of parameters, that will be sent to the server.
+    pub fn param_types(&self) -> &[(ColumnType, StmtExecuteParamFlags)] {
+        &self.param_types
+    }
+
+    pub fn rows(&self) -> &[Vec<BulkParam<'a>>] {
+        &self.rows
+    }
+}
+
+impl MySerialize for ComStmtBulkExecuteRequest<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+        self.stmt_id.serialize(&mut *buf);
+        self.flags.serialize(&mut *buf);
+
+        for (column_type, flags) in &self.param_types {
+            buf.put_slice(&[*column_type as u8, flags.bits()]);
+        }
+
+        for row in &self.rows {
+            for param in row {
+                buf.put_u8(param.indicator() as u8);
+                if let BulkParam::Value(value) = param {
+                    value.serialize(&mut *buf);
+                }
+            }
+        }
+    }
+}
+
+define_header!(
+    ComStmtSendLongDataHeader,
+    COM_STMT_SEND_LONG_DATA,
+    InvalidComStmtSendLongDataHeader
+);
+
+/// Sends a chunk of a parameter value to the server (see [`ComStmtSendLongData::chunked`]).
+///
+/// Chunks are accumulated on the server side, so the value of the parameter should be omitted
+/// from the following `COM_STMT_EXECUTE` (see [`ComStmtExecuteRequest::with_long_data_sent`]).
+/// Note, that the server does not respond to this command.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ComStmtSendLongData<'a> {
+    __header: ComStmtSendLongDataHeader,
+    stmt_id: RawInt<LeU32>,
+    param_index: RawInt<LeU16>,
+    data: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> ComStmtSendLongData<'a> {
+    /// Length of the packet without data.
+    pub const HEADER_LEN: usize = 7;
+
+    /// Max length of data, that fits into a single packet.
+    pub const MAX_CHUNK_LEN: usize = MAX_PAYLOAD_LEN - Self::HEADER_LEN;
+
+    pub fn new(stmt_id: u32, param_index: u16, data: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            __header: ComStmtSendLongDataHeader::new(),
+            stmt_id: RawInt::new(stmt_id),
+            param_index: RawInt::new(param_index),
+            data: RawBytes::new(data),
+        }
+    }
+
+    /// Splits the given value into packets with no more than `chunk_len` bytes of data each.
+    ///
+    /// Empty value results in a single empty packet, so that the parameter is still considered
+    /// as sent. `chunk_len` will be clamped to `1..=MAX_CHUNK_LEN`.
+    ///
+    /// ```
+    /// # use mysql_common::packets::ComStmtSendLongData;
+    /// let chunks = ComStmtSendLongData::chunked(1, 0, &[0_u8; 10][..], 4).collect::<Vec<_>>();
+    /// assert_eq!(chunks.len(), 3);
+    /// assert_eq!(chunks[2].data(), &[0_u8; 2][..]);
+    /// ```
+    pub fn chunked(
+        stmt_id: u32,
+        param_index: u16,
+        data: &'a [u8],
+        chunk_len: usize,
+    ) -> impl Iterator<Item = ComStmtSendLongData<'a>> + 'a {
+        let chunk_len = chunk_len.clamp(1, Self::MAX_CHUNK_LEN);
+        let empty = if data.is_empty() { Some(data) } else { None };
+        data.chunks(chunk_len)
+            .chain(empty)
+            .map(move |chunk| Self::new(stmt_id, param_index, chunk))
+    }
+
+    pub fn stmt_id(&self) -> u32 {
+        self.stmt_id.0
+    }
+
+    pub fn param_index(&self) -> u16 {
+        self.param_index.0
+    }
+
+    pub fn data(&self) -> &[u8] {
+        self.data.as_bytes()
+    }
+
+    pub fn into_owned(self) -> ComStmtSendLongData<'static> {
+        ComStmtSendLongData {
+            __header: self.__header,
+            stmt_id: self.stmt_id,
+            param_index: self.param_index,
+            data: self.data.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for ComStmtSendLongData<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            __header: buf.parse(())?,
+            stmt_id: buf.parse(())?,
+            param_index: buf.parse(())?,
+            data: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for ComStmtSendLongData<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+        self.stmt_id.serialize(&mut *buf);
+        self.param_index.serialize(&mut *buf);
+        self.data.serialize(&mut *buf);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ComStmtClose {
+    pub stmt_id: u32,
+}
+
+impl ComStmtClose {
+    pub fn new(stmt_id: u32) -> Self {
+        Self { stmt_id }
+    }
+}
+
+impl MySerialize for ComStmtClose {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        buf.put_u8(Command::COM_STMT_CLOSE as u8);
+        buf.put_u32_le(self.stmt_id);
+    }
+}
+
+define_header!(
+    ComSetOptionHeader,
+    COM_SET_OPTION,
+    InvalidComSetOptionHeader
+);
+
+/// Sets an option for the current connection.
+///
+/// Server responds with an EOF packet (or an OK packet if `CLIENT_DEPRECATE_EOF`
+/// is negotiated), or with an ERR packet.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ComSetOption {
+    __header: ComSetOptionHeader,
+    option: Const<SetOption, LeU16>,
+}
+
+impl ComSetOption {
+    pub fn new(option: SetOption) -> Self {
+        Self {
+            __header: ComSetOptionHeader::new(),
+            option: Const::new(option),
+        }
+    }
+
+    pub fn option(&self) -> SetOption {
+        self.option.0
+    }
+}
+
+impl<'de> MyDeserialize<'de> for ComSetOption {
+    const SIZE: Option<usize> = Some(3);
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let mut buf: ParseBuf = buf.parse(3)?;
+        Ok(Self {
+            __header: buf.parse_unchecked(())?,
+            option: buf.parse_unchecked(())?,
+        })
+    }
+}
+
+impl MySerialize for ComSetOption {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+        self.option.serialize(buf);
+    }
+}
+
+define_header!(
+    ComFieldListHeader,
+    COM_FIELD_LIST,
+    InvalidComFieldListHeader
+);
+
+/// Requests column definitions of a table (deprecated since MySql 5.7.11).
+///
+/// Server responds with a sequence of [`FieldListColumn`] packets terminated
+/// by an EOF packet (or an OK packet if `CLIENT_DEPRECATE_EOF` is negotiated),
+/// or with an ERR packet.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ComFieldList<'a> {
+    __header: ComFieldListHeader,
+    table: RawBytes<'a, NullBytes>,
+    field_wildcard: RawBytes<'a, EofBytes>,
+}
+
+impl<'a> ComFieldList<'a> {
+    /// Creates a new command. Column names are filtered using the `LIKE`-style
+    /// `field_wildcard` (empty wildcard means all columns).
+    pub fn new(table: impl Into<Cow<'a, [u8]>>, field_wildcard: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            __header: ComFieldListHeader::new(),
+            table: RawBytes::new(table),
+            field_wildcard: RawBytes::new(field_wildcard),
+        }
+    }
+
+    pub fn table(&self) -> &[u8] {
+        self.table.as_bytes()
+    }
+
+    pub fn field_wildcard(&self) -> &[u8] {
+        self.field_wildcard.as_bytes()
+    }
+
+    pub fn into_owned(self) -> ComFieldList<'static> {
+        ComFieldList {
+            __header: self.__header,
+            table: self.table.into_owned(),
+            field_wildcard: self.field_wildcard.into_owned(),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for ComFieldList<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self {
+            __header: buf.parse(())?,
+            table: buf.parse(())?,
+            field_wildcard: buf.parse(())?,
+        })
+    }
+}
+
+impl MySerialize for ComFieldList<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+        self.table.serialize(&mut *buf);
+        self.field_wildcard.serialize(buf);
+    }
+}
+
+/// Column definition with a default value (a response to the `COM_FIELD_LIST` command).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FieldListColumn {
+    column: Column,
+    default_value: Option<Arc<[u8]>>,
+}
+
+impl FieldListColumn {
+    pub fn new(column: Column, default_value: Option<&[u8]>) -> Self {
+        Self {
+            column,
+            default_value: default_value.map(Into::into),
+        }
+    }
+
+    pub fn column(&self) -> &Column {
+        &self.column
+    }
+
+    /// Returns the default value of the column in text format (`None` means `NULL`
+    /// or no default).
+    pub fn default_value(&self) -> Option<&[u8]> {
+        self.default_value.as_deref()
+    }
+
+    pub fn into_column(self) -> Column {
+        self.column
+    }
+}
+
+impl<'de> MyDeserialize<'de> for FieldListColumn {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let column = buf.parse(())?;
+        let default_value = match buf.0.first() {
+            // some servers omit the default value
+            None => None,
+            Some(0xFB) => {
+                buf.skip(1);
+                None
+            }
+            Some(_) => buf.parse(()).map(Some)?,
+        };
+        Ok(Self {
+            column,
+            default_value,
+        })
+    }
+}
+
+impl MySerialize for FieldListColumn {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.column.serialize(&mut *buf);
+        match &self.default_value {
+            Some(value) => value.serialize(buf),
+            None => buf.put_u8(0xFB),
+        }
+    }
+}
+
+/// Defines a command without arguments.
+macro_rules! define_plain_command {
+    ($(#[$meta:meta])* $name:ident, $header:ident, $cmd:ident, $err:ident) => {
+        define_header!($header, $cmd, $err);
+
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+        pub struct $name {
+            __header: $header,
+        }
+
+        impl $name {
+            pub fn new() -> Self {
+                Self::default()
+            }
+        }
+
+        impl<'de> MyDeserialize<'de> for $name {
+            const SIZE: Option<usize> = Some(1);
+            type Ctx = ();
+
+            fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+                Ok(Self {
+                    __header: buf.parse(())?,
+                })
+            }
+        }
+
+        impl MySerialize for $name {
+            fn serialize(&self, buf: &mut Vec<u8>) {
+                self.__header.serialize(buf);
+            }
+        }
+    };
+}
+
+define_plain_command!(
+    /// Asks the server to dump debug information to its error log
+    /// (requires the `SUPER` privilege).
+    ///
+    /// Server responds with an EOF packet (or an OK packet if `CLIENT_DEPRECATE_EOF`
+    /// is negotiated), or with an ERR packet.
+    ComDebug,
+    ComDebugHeader,
+    COM_DEBUG,
+    InvalidComDebugHeader
+);
+
+define_plain_command!(
+    /// Asks the server for a human-readable statistics string.
+    ///
+    /// Server responds with a plain-text packet (see [`ServerStatistics`]).
+    ComStatistics,
+    ComStatisticsHeader,
+    COM_STATISTICS,
+    InvalidComStatisticsHeader
+);
+
+/// Parsed response to the `COM_STATISTICS` command, e.g.:
+///
+/// `Uptime: 1234  Threads: 1  Questions: 10  Slow queries: 0  Opens: 12  Flush tables: 1
+/// Open tables: 6  Queries per second avg: 0.008`.
+///
+/// Fields, that are absent in the response, are `None`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ServerStatistics {
+    /// Server uptime in seconds.
+    pub uptime: Option<u64>,
+    /// Number of currently open connections.
+    pub threads: Option<u64>,
+    /// Number of statements executed by the server.
+    pub questions: Option<u64>,
+    /// Number of slow queries.
+    pub slow_queries: Option<u64>,
+    /// Number of tables the server has opened.
+    pub opens: Option<u64>,
+    /// Number of flush tables (and similar) commands executed by the server.
+    pub flush_tables: Option<u64>,
+    /// Number of currently open tables.
+    pub open_tables: Option<u64>,
+    /// Average number of queries per second.
+    pub queries_per_second_avg: Option<f64>,
+}
+
+impl ServerStatistics {
+    /// Parses the given `COM_STATISTICS` response text (unknown labels are ignored).
+    pub fn parse(text: &str) -> Self {
+        let mut stats = Self::default();
+        for (label, value) in ok_info::labeled_values(text) {
+            let field = match &*label {
+                "Uptime" => &mut stats.uptime,
+                "Threads" => &mut stats.threads,
+                "Questions" => &mut stats.questions,
+                "Slow queries" => &mut stats.slow_queries,
+                "Opens" => &mut stats.opens,
+                "Flush tables" => &mut stats.flush_tables,
+                "Open tables" => &mut stats.open_tables,
+                "Queries per second avg" => {
+                    stats.queries_per_second_avg = value.parse().ok();
+                    continue;
+                }
+                _ => continue,
+            };
+            *field = value.parse().ok();
+        }
+        stats
+    }
+}
+
+impl<'de> MyDeserialize<'de> for ServerStatistics {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Ok(Self::parse(&String::from_utf8_lossy(buf.eat_all())))
+    }
+}
+
+define_plain_command!(
+    /// Requests the list of active threads (deprecated since MySql 5.7.11).
+    ///
+    /// Server responds with a text result set (see [`ProcessListEntry`]) or with an ERR packet.
+    ComProcessInfo,
+    ComProcessInfoHeader,
+    COM_PROCESS_INFO,
+    InvalidComProcessInfoHeader
+);
+
+define_header!(
+    ComProcessKillHeader,
+    COM_PROCESS_KILL,
+    InvalidComProcessKillHeader
+);
+
+/// Asks the server to terminate a connection (deprecated since MySql 5.7.11
+/// in favor of the `KILL` statement).
+///
+/// Server responds with an OK packet or with an ERR packet.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ComProcessKill {
+    __header: ComProcessKillHeader,
+    connection_id: RawInt<LeU32>,
+}
+
+impl ComProcessKill {
+    pub fn new(connection_id: u32) -> Self {
+        Self {
+            __header: ComProcessKillHeader::new(),
+            connection_id: RawInt::new(connection_id),
+        }
+    }
+
+    pub fn connection_id(&self) -> u32 {
+        self.connection_id.0
+    }
+}
+
+impl<'de> MyDeserialize<'de> for ComProcessKill {
+    const SIZE: Option<usize> = Some(5);
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let mut buf: ParseBuf = buf.parse(5)?;
+        Ok(Self {
+            __header: buf.parse_unchecked(())?,
+            connection_id: buf.parse_unchecked(())?,
+        })
+    }
+}
+
+impl MySerialize for ComProcessKill {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+        self.connection_id.serialize(buf);
+    }
+}
+
+/// A row of a process list, i.e. of a `COM_PROCESS_INFO` response, `SHOW PROCESSLIST`
+/// or `SELECT * FROM information_schema.PROCESSLIST`.
+///
+/// Columns are looked up by name (case insensitive), so that all the mentioned sources
+/// are supported. `db`, `State` and `Info` columns are optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessListEntry {
+    /// Connection id (suitable for [`ComProcessKill`] and the `KILL` statement).
+    pub id: u64,
+    pub user: String,
+    pub host: String,
+    pub db: Option<String>,
+    pub command: String,
+    /// Time in seconds the thread has been in its current state.
+    pub time: i64,
+    pub state: Option<String>,
+    /// The statement the thread is executing, if any.
+    pub info: Option<String>,
+}
+
+impl ProcessListEntry {
+    fn from_row_ref(row: &Row) -> Option<Self> {
+        fn column<T: FromValue>(row: &Row, name: &str) -> Option<T> {
+            row.get_opt(NoCase(name))?.ok()
+        }
+
+        Some(Self {
+            id: column(row, "Id")?,
+            user: column(row, "User")?,
+            host: column(row, "Host")?,
+            db: column(row, "db").flatten(),
+            command: column(row, "Command")?,
+            time: column(row, "Time")?,
+            state: column(row, "State").flatten(),
+            info: column(row, "Info").flatten(),
+        })
+    }
+}
+
+impl FromRow for ProcessListEntry {
+    fn from_row_opt(row: Row) -> Result<Self, FromRowError> {
+        match Self::from_row_ref(&row) {
+            Some(entry) => Ok(entry),
+            None => Err(FromRowError(row)),
+        }
+    }
+}
+
+define_header!(
+    ComChangeUserHeader,
+    COM_CHANGE_USER,
+    InvalidComChangeUserHeader
+);
+
+/// Changes the user of the current connection and resets the session state.
+///
+/// Layout of the packet depends on capabilities negotiated for the connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComChangeUser<'a> {
+    __header: ComChangeUserHeader,
+    capabilities: CapabilityFlags,
+    user: RawBytes<'a, NullBytes>,
+    /// `u8`-prefixed if `CLIENT_SECURE_CONNECTION` is negotiated, null-terminated otherwise.
+    auth_plugin_data: Either<RawBytes<'a, U8Bytes>, RawBytes<'a, NullBytes>>,
+    database: RawBytes<'a, NullBytes>,
+    charset: RawInt<LeU16>,
+    auth_plugin: Option<AuthPlugin<'a>>,
+    connect_attributes: Option<ConnectAttributes<'a>>,
+}
+
+impl<'a> ComChangeUser<'a> {
+    /// Creates a new packet for the given capabilities.
+    ///
+    /// Auth plugin data, database and connection attributes will be empty,
+    /// charset will be `utf8mb4_general_ci`.
+    pub fn new(capabilities: CapabilityFlags, user: impl Into<Cow<'a, [u8]>>) -> Self {
+        let auth_plugin_data = if capabilities.contains(CapabilityFlags::CLIENT_SECURE_CONNECTION) {
+            Either::Left(RawBytes::default())
+        } else {
+            Either::Right(RawBytes::default())
+        };
+
+        Self {
+            __header: ComChangeUserHeader::new(),
+            capabilities,
+            user: RawBytes::new(user),
+            auth_plugin_data,
+            database: RawBytes::default(),
+            charset: RawInt::new(UTF8MB4_GENERAL_CI),
+            auth_plugin: None,
+            connect_attributes: None,
+        }
+    }
+
+    /// Sets the auth plugin data (i.e. the scrambled password).
+    ///
+    /// # Note
+    ///
+    /// Serialization will truncate this value if length is greater than 255 bytes.
+    pub fn with_auth_plugin_data(mut self, data: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.auth_plugin_data = match self.auth_plugin_data {
+            Either::Left(_) => Either::Left(RawBytes::new(data)),
+            Either::Right(_) => Either::Right(RawBytes::new(data)),
+        };
+        self
+    }
+
+    /// Sets the default database (empty means no default database).
+    pub fn with_database(mut self, database: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.database = RawBytes::new(database);
+        self
+    }
+
+    /// Sets the connection charset (collation id).
+    pub fn with_charset(mut self, charset: u16) -> Self {
+        self.charset = RawInt::new(charset);
+        self
+    }
+
+    /// Sets the auth plugin (only sent if `CLIENT_PLUGIN_AUTH` is negotiated).
+    pub fn with_auth_plugin(mut self, auth_plugin: Option<AuthPlugin<'a>>) -> Self {
+        self.auth_plugin = auth_plugin;
+        self
+    }
+
+    /// Sets connection attributes (only sent if `CLIENT_CONNECT_ATTRS` is negotiated).
+    pub fn with_connect_attributes(
+        mut self,
+        connect_attributes: Option<HashMap<String, String>>,
+    ) -> Self {
+        self.connect_attributes = connect_attributes.map(connect_attributes_from_map);
+        self
+    }
+
+    /// Capabilities, that define the layout of this packet.
+    pub fn capabilities(&self) -> CapabilityFlags {
+        self.capabilities
+    }
+
+    /// Returns the user name.
+    pub fn user(&self) -> &[u8] {
+        self.user.as_bytes()
+    }
+
+    /// Returns the auth plugin data (i.e. the scrambled password).
+    pub fn auth_plugin_data(&self) -> &[u8] {
+        match &self.auth_plugin_data {
+            Either::Left(x) => x.as_bytes(),
+            Either::Right(x) => x.as_bytes(),
+        }
+    }
+
+    /// Returns the default database (empty means no default database).
+    pub fn database(&self) -> &[u8] {
+        self.database.as_bytes()
+    }
+
+    /// Returns the connection charset (collation id).
+    pub fn charset(&self) -> u16 {
+        self.charset.0
+    }
+
+    /// Returns the auth plugin.
+    pub fn auth_plugin(&self) -> Option<&AuthPlugin<'a>> {
+        self.auth_plugin.as_ref()
+    }
+
+    /// Returns connection attributes.
+    #[must_use = "entails computation"]
+    pub fn connect_attributes(&self) -> Option<HashMap<String, String>> {
+        self.connect_attributes.as_ref().map(|attrs| {
+            attrs
+                .iter()
+                .map(|(k, v)| (k.as_str().into_owned(), v.as_str().into_owned()))
+                .collect()
+        })
+    }
+
+    pub fn into_owned(self) -> ComChangeUser<'static> {
+        ComChangeUser {
+            __header: self.__header,
+            capabilities: self.capabilities,
+            user: self.user.into_owned(),
+            auth_plugin_data: match self.auth_plugin_data {
+                Either::Left(x) => Either::Left(x.into_owned()),
+                Either::Right(x) => Either::Right(x.into_owned()),
+            },
+            database: self.database.into_owned(),
+            charset: self.charset,
+            auth_plugin: self.auth_plugin.map(AuthPlugin::into_owned),
+            connect_attributes: self.connect_attributes.map(|attrs| {
+                attrs
+                    .into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for ComChangeUser<'de> {
+    const SIZE: Option<usize> = None;
+    /// Capabilities negotiated for the connection.
+    type Ctx = CapabilityFlags;
+
+    fn deserialize(capabilities: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let __header = buf.parse(())?;
+        let user = buf.parse(())?;
+        let auth_plugin_data = if capabilities.contains(CapabilityFlags::CLIENT_SECURE_CONNECTION) {
+            Either::Left(buf.parse(())?)
+        } else {
+            Either::Right(buf.parse(())?)
+        };
+        let database = buf.parse(())?;
+
+        // following fields are optional
+        let mut charset = RawInt::new(0);
+        if !buf.is_empty() {
+            charset = buf.parse(())?;
+        }
+
+        let mut auth_plugin = None;
+        if capabilities.contains(CapabilityFlags::CLIENT_PLUGIN_AUTH) && !buf.is_empty() {
+            let name = buf.eat_null_str();
+            // skip the terminating null byte
+            buf.checked_skip(1);
+            auth_plugin = match name {
+                [] => None,
+                name => Some(AuthPlugin::from_bytes(name)),
+            };
+        }
+
+        let mut connect_attributes = None;
+        if capabilities.contains(CapabilityFlags::CLIENT_CONNECT_ATTRS) && !buf.is_empty() {
+            connect_attributes = Some(parse_connect_attributes(buf)?);
+        }
+
+        Ok(Self {
+            __header,
+            capabilities,
+            user,
+            auth_plugin_data,
+            database,
+            charset,
+            auth_plugin,
+            connect_attributes,
+        })
+    }
+}
+
+impl MySerialize for ComChangeUser<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.__header.serialize(&mut *buf);
+        self.user.serialize(&mut *buf);
+        self.auth_plugin_data.serialize(&mut *buf);
+        self.database.serialize(&mut *buf);
+        self.charset.serialize(&mut *buf);
+
+        if self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_PLUGIN_AUTH)
+        {
+            match &self.auth_plugin {
+                Some(auth_plugin) => auth_plugin.serialize(&mut *buf),
+                None => buf.put_u8(0),
+            }
+        }
+
+        if self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_CONNECT_ATTRS)
+        {
+            if let Some(attrs) = &self.connect_attributes {
+                serialize_connect_attributes(attrs, &mut *buf);
+            }
+        }
+    }
+}
+
+define_header!(
+    ComRegisterSlaveHeader,
+    COM_REGISTER_SLAVE,
+    InvalidComRegisterSlaveHeader
+);
+
+/// Registers a slave at the master. Should be sent before requesting a binlog events
+/// with `COM_BINLOG_DUMP`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ComRegisterSlave<'a> {
+    header: ComRegisterSlaveHeader,
     /// The slaves server-id.
     server_id: RawInt<LeU32>,
     /// The host name or IP address of the slave to be reported to the master during slave
@@ -2216,13 +4419,9 @@ pub struct ComRegisterSlave<'a> {
     /// # Note
     ///
     /// Serialization will truncate this value if length is greater than 255 bytes.
-    password: RawBytes<'a, U8Bytes>,
-    /// The TCP/IP port number for connecting to the slave, to be reported to the master during
-    /// slave registration. Usually empty.
-    ///
-    /// # Note
-    ///
-    /// Serialization will truncate this value if length is greater than 255 bytes.
+    password: RawBytes<'a, U8Bytes>,
+    /// The TCP/IP port number for connecting to the slave, to be reported to the master during
+    /// slave registration. Usually empty.
     port: RawInt<LeU16>,
     /// Ignored.
     replication_rank: RawInt<LeU32>,
@@ -2288,32 +4487,32 @@ impl<'a> ComRegisterSlave<'a> {
     }
 
     /// Returns the raw `hostname` field value.
-    pub fn hostname_raw(&'a self) -> &[u8] {
+    pub fn hostname_raw(&self) -> &[u8] {
         self.hostname.as_bytes()
     }
 
     /// Returns the `hostname` field as a UTF-8 string (lossy converted).
-    pub fn hostname(&'a self) -> Cow<'a, str> {
+    pub fn hostname(&self) -> Cow<'_, str> {
         self.hostname.as_str()
     }
 
     /// Returns the raw `user` field value.
-    pub fn user_raw(&'a self) -> &[u8] {
+    pub fn user_raw(&self) -> &[u8] {
         self.user.as_bytes()
     }
 
     /// Returns the `user` field as a UTF-8 string (lossy converted).
-    pub fn user(&'a self) -> Cow<'a, str> {
+    pub fn user(&self) -> Cow<'_, str> {
         self.user.as_str()
     }
 
     /// Returns the raw `password` field value.
-    pub fn password_raw(&'a self) -> &[u8] {
+    pub fn password_raw(&self) -> &[u8] {
         self.password.as_bytes()
     }
 
     /// Returns the `password` field as a UTF-8 string (lossy converted).
-    pub fn password(&'a self) -> Cow<'a, str> {
+    pub fn password(&self) -> Cow<'_, str> {
         self.password.as_str()
     }
 
@@ -2590,6 +4789,16 @@ impl Interval {
             end: RawInt::new(end),
         }
     }
+
+    /// Returns the first GNO of this interval.
+    pub fn start(&self) -> u64 {
+        self.start.0
+    }
+
+    /// Returns the GNO right after the last GNO of this interval (i.e. `end` is exclusive).
+    pub fn end(&self) -> u64 {
+        self.end.0
+    }
 }
 
 impl MySerialize for Interval {
@@ -2663,6 +4872,88 @@ impl<'a> Sid<'a> {
     }
 }
 
+/// Error of parsing a textual representation of a [`Sid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ParseSidError {
+    /// Source id isn't a valid UUID.
+    #[error("Invalid source UUID of a GTID set")]
+    InvalidUuid,
+    /// Interval isn't a valid GNO interval.
+    #[error("Invalid GNO interval of a GTID set")]
+    InvalidInterval,
+}
+
+impl std::str::FromStr for Sid<'static> {
+    type Err = ParseSidError;
+
+    /// Parses a GTID set of a single source, i.e. `<uuid>:<start>[-<end>][:<start>[-<end>]]*`
+    /// (interval bounds are inclusive, UUID is in the `8-4-4-4-12` hexadecimal form).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const UUID_GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+
+        let mut parts = s.trim().split(':');
+        let uuid = parts.next().unwrap_or_default().trim();
+
+        if !uuid
+            .split('-')
+            .map(str::len)
+            .eq(UUID_GROUPS.iter().copied())
+            || !uuid.bytes().all(|x| x == b'-' || x.is_ascii_hexdigit())
+        {
+            return Err(ParseSidError::InvalidUuid);
+        }
+        let digits = uuid.bytes().filter(|x| *x != b'-').collect::<Vec<_>>();
+        let mut sid = [0_u8; SID_LEN];
+        for (byte, pair) in sid.iter_mut().zip(digits.chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| ParseSidError::InvalidUuid)?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| ParseSidError::InvalidUuid)?;
+        }
+
+        let mut intervals = Vec::new();
+        for interval in parts {
+            // `u64::from_str` also accepts a leading `+`
+            let mut bounds = interval.trim().splitn(2, '-').map(|x| {
+                Some(x.trim())
+                    .filter(|x| x.bytes().all(|x| x.is_ascii_digit()))
+                    .and_then(|x| x.parse::<u64>().ok())
+            });
+            let start = match bounds.next() {
+                Some(Some(start)) => start,
+                _ => return Err(ParseSidError::InvalidInterval),
+            };
+            let end = match bounds.next() {
+                Some(Some(end)) => end,
+                Some(None) => return Err(ParseSidError::InvalidInterval),
+                None => start,
+            };
+            if start == 0 || end < start || end == u64::MAX {
+                return Err(ParseSidError::InvalidInterval);
+            }
+            intervals.push(Interval::new(start, end + 1));
+        }
+
+        Ok(Sid::new(sid).with_intervals(intervals))
+    }
+}
+
+impl fmt::Display for Sid<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.sid.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        for interval in self.intervals() {
+            match interval.end().saturating_sub(1) {
+                last if last > interval.start() => write!(f, ":{}-{}", interval.start(), last)?,
+                _ => write!(f, ":{}", interval.start())?,
+            }
+        }
+        Ok(())
+    }
+}
+
 impl MySerialize for Sid<'_> {
     fn serialize(&self, buf: &mut Vec<u8>) {
         self.sid.serialize(&mut *buf);
@@ -2807,6 +5098,28 @@ impl<'a> ComBinlogDumpGtid<'a> {
         self
     }
 
+    /// Sets the `sid_block` field value using a textual representation of a GTID set
+    /// (e.g. `Executed_Gtid_Set` of `SHOW MASTER STATUS`).
+    ///
+    /// ```
+    /// # use mysql_common::packets::{BinlogDumpFlags, ComBinlogDumpGtid};
+    /// let cmd = ComBinlogDumpGtid::new(42)
+    ///     .with_gtid_set("3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:7,\n\
+    ///         4e11fa47-71ca-11e1-9e33-c80aa9429562:1")
+    ///     .unwrap();
+    /// assert_eq!(cmd.sids().len(), 2);
+    /// assert_eq!(cmd.sids()[0].intervals()[0].end(), 6);
+    /// assert!(cmd.flags().contains(BinlogDumpFlags::BINLOG_THROUGH_GTID));
+    /// ```
+    pub fn with_gtid_set(self, gtid_set: &str) -> Result<Self, ParseSidError> {
+        let sids = gtid_set
+            .split(',')
+            .filter(|x| !x.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Sid<'static>>, _>>()?;
+        Ok(self.with_sids(sids))
+    }
+
     fn sid_block_len(&self) -> u32 {
         use saturating::Saturating as S;
         let mut len = S(8); // n_sids
@@ -2866,6 +5179,7 @@ define_header!(
 
 /// Each Semi Sync Binlog Event with the `SEMI_SYNC_ACK_REQ` flag set the slave has to acknowledge
 /// with Semi-Sync ACK packet.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct SemiSyncAckPacket<'a> {
     header: SemiSyncAckPacketPacketHeader,
     position: RawInt<LeU64>,
@@ -3195,6 +5509,84 @@ mod test {
         assert_eq!(column.column_type(), ColumnType::MYSQL_TYPE_DECIMAL);
         assert_eq!(column.flags(), ColumnFlags::NOT_NULL_FLAG);
         assert_eq!(column.decimals(), 8);
+        assert_eq!(column.name_utf8(), Ok("name"));
+        assert_eq!(column.org_table_utf8(), Ok("org_table"));
+        assert_eq!(column.display_length(), Some(5));
+        assert!(!column.is_nullable());
+        assert!(!column.is_unsigned());
+        assert!(!column.is_binary_collation());
+
+        let mut output = Vec::new();
+        column.serialize(&mut output);
+        assert_eq!(output, COLUMN_PACKET);
+
+        let cloned = column.clone();
+        assert!(Arc::ptr_eq(&column.names.buf, &cloned.names.buf));
+        assert_eq!(&*column.names.buf, &b"schematableorg_tablenameorg_name"[..]);
+
+        let renamed = column.with_name(b"alias");
+        assert_eq!(renamed.name_ref(), b"alias");
+        assert_eq!(renamed.org_name_ref(), b"org_name");
+        assert_eq!(renamed.table_ref(), b"table");
+    }
+
+    #[test]
+    fn should_parse_mariadb_ext_type_info() {
+        const COLUMN_PACKET: &[u8] = b"\x03def\x00\x01t\x01t\x02id\x02id\x06\x00\x04uuid\
+              \x0c\x08\x00\x28\x00\x00\x00\xfe\x00\x00\x00\x00\x00";
+        let ext_caps = MariadbCapabilities::MARIADB_CLIENT_EXTENDED_TYPE_INFO;
+
+        let column: Column = ParseBuf(COLUMN_PACKET)
+            .parse::<ColumnDeserializer>(ext_caps)
+            .unwrap()
+            .into();
+        assert_eq!(column.name_str(), "id");
+        assert_eq!(column.column_type(), ColumnType::MYSQL_TYPE_STRING);
+        assert_eq!(column.mariadb_ext_type_name_ref(), Some(&b"uuid"[..]));
+        assert_eq!(column.mariadb_ext_format_name_ref(), None);
+        assert_eq!(column.mariadb_ext_type(), Some(MariadbExtendedType::Uuid));
+
+        let mut output = Vec::new();
+        column.serialize(&mut output);
+        assert_eq!(output, COLUMN_PACKET);
+
+        // extended type info is not expected without the capability
+        assert!(Column::deserialize((), &mut ParseBuf(COLUMN_PACKET)).is_err());
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_BLOB).with_mariadb_ext_format_name(b"json");
+        let mut output = Vec::new();
+        column.serialize(&mut output);
+        let parsed: Column = ParseBuf(&output)
+            .parse::<ColumnDeserializer>(ext_caps)
+            .unwrap()
+            .into();
+        assert_eq!(parsed, column);
+        assert_eq!(parsed.mariadb_ext_type(), Some(MariadbExtendedType::Json));
+        assert_eq!(
+            Column::new(ColumnType::MYSQL_TYPE_BLOB).mariadb_ext_type(),
+            None
+        );
+    }
+
+    #[test]
+    fn should_inspect_column() {
+        let column = Column::new(ColumnType::MYSQL_TYPE_LONG)
+            .with_name(b"\xff")
+            .with_flags(ColumnFlags::UNSIGNED_FLAG | ColumnFlags::PRI_KEY_FLAG)
+            .with_character_set(BINARY_COLLATION)
+            .with_column_length(10);
+        assert!(column.name_utf8().is_err());
+        assert!(column.is_unsigned());
+        assert!(column.is_primary_key());
+        assert!(column.is_nullable());
+        assert!(column.is_binary_collation());
+        assert_eq!(column.display_length(), Some(10));
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+            .with_character_set(UTF8MB4_GENERAL_CI)
+            .with_column_length(40);
+        assert_eq!(column.display_length(), Some(10));
+        assert_eq!(column.with_character_set(1000).display_length(), None);
     }
 
     #[test]
@@ -3212,6 +5604,245 @@ mod test {
         const PAYLOAD: &[u8] = b"\x01\x04";
         let packet = AuthMoreData::deserialize((), &mut ParseBuf(PAYLOAD)).unwrap();
         assert_eq!(packet.data(), b"\x04",);
+        assert_eq!(
+            packet.caching_sha2_password_status(),
+            Some(CachingSha2PasswordStatus::PerformFullAuthentication)
+        );
+    }
+
+    #[test]
+    fn should_serialize_auth_packets() {
+        let mut output = Vec::new();
+        AuthMoreData::from(CachingSha2PasswordStatus::FastAuthSuccess).serialize(&mut output);
+        assert_eq!(output, b"\x01\x03");
+        let packet = AuthMoreData::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert_eq!(
+            packet.caching_sha2_password_status(),
+            Some(CachingSha2PasswordStatus::FastAuthSuccess)
+        );
+        assert_eq!(
+            AuthMoreData::new(&b"\x05"[..]).caching_sha2_password_status(),
+            None
+        );
+        assert_eq!(
+            AuthMoreData::new(&b"\x03\x04"[..]).caching_sha2_password_status(),
+            None
+        );
+
+        let mut output = Vec::new();
+        AuthSwitchRequest::for_plugin(&AuthPlugin::CachingSha2Password, b"0123456789abcdefghij")
+            .serialize(&mut output);
+        assert_eq!(
+            &output[..],
+            &b"\xfecaching_sha2_password\x000123456789abcdefghij\x00"[..]
+        );
+        let packet = AuthSwitchRequest::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert_eq!(packet.auth_plugin(), AuthPlugin::CachingSha2Password);
+        assert_eq!(packet.plugin_data(), b"0123456789abcdefghij");
+    }
+
+    #[test]
+    fn should_handle_com_field_list() {
+        let mut output = Vec::new();
+        ComFieldList::new(&b"users"[..], &b"na%"[..]).serialize(&mut output);
+        assert_eq!(output, b"\x04users\x00na%");
+        let packet = ComFieldList::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert_eq!(packet.table(), b"users");
+        assert_eq!(packet.field_wildcard(), b"na%");
+
+        const FIELD: &[u8] = b"\x03def\x04test\x01t\x01t\x04name\x04name\x0c\x21\x00\x2d\x00\
+            \x00\x00\xfd\x00\x00\x00\x00\x00\x05guest";
+        let field = FieldListColumn::deserialize((), &mut ParseBuf(FIELD)).unwrap();
+        assert_eq!(field.column().name_str(), "name");
+        assert_eq!(
+            field.column().column_type(),
+            ColumnType::MYSQL_TYPE_VAR_STRING
+        );
+        assert_eq!(field.default_value(), Some(&b"guest"[..]));
+        let mut output = Vec::new();
+        field.serialize(&mut output);
+        assert_eq!(output, FIELD);
+
+        let null_default = FieldListColumn::new(Column::new(ColumnType::MYSQL_TYPE_LONG), None);
+        let mut output = Vec::new();
+        null_default.serialize(&mut output);
+        assert_eq!(output.last(), Some(&0xFB));
+        let field = FieldListColumn::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert_eq!(field, null_default);
+
+        let field =
+            FieldListColumn::deserialize((), &mut ParseBuf(&FIELD[..FIELD.len() - 6])).unwrap();
+        assert_eq!(field.default_value(), None);
+    }
+
+    #[test]
+    fn should_handle_process_commands() {
+        let mut output = Vec::new();
+        ComProcessInfo::new().serialize(&mut output);
+        ComProcessKill::new(0x01020304).serialize(&mut output);
+        assert_eq!(output, b"\x0a\x0c\x04\x03\x02\x01");
+        let mut buf = ParseBuf(&output);
+        buf.parse::<ComProcessInfo>(()).unwrap();
+        let kill = buf.parse::<ComProcessKill>(()).unwrap();
+        assert_eq!(kill.connection_id(), 0x01020304);
+
+        let columns: Arc<[Column]> = ["Id", "User", "Host", "db", "Command", "Time", "State"]
+            .iter()
+            .map(|name| Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(name.as_bytes()))
+            .collect::<Vec<_>>()
+            .into();
+        let values = vec![
+            Value::from("42"),
+            Value::from("root"),
+            Value::from("localhost:51234"),
+            Value::NULL,
+            Value::from("Query"),
+            Value::from("3"),
+            Value::from("executing"),
+        ];
+        let row = crate::row::new_row(values, columns.clone());
+        assert_eq!(
+            crate::row::convert::from_row::<ProcessListEntry>(row),
+            ProcessListEntry {
+                id: 42,
+                user: "root".into(),
+                host: "localhost:51234".into(),
+                db: None,
+                command: "Query".into(),
+                time: 3,
+                state: Some("executing".into()),
+                info: None,
+            }
+        );
+
+        let values = vec![Value::from("not a number"); columns.len()];
+        let row = crate::row::new_row(values, columns);
+        let err = crate::row::convert::from_row_opt::<ProcessListEntry>(row).unwrap_err();
+        assert_eq!(err.0.len(), 7);
+    }
+
+    #[test]
+    fn should_handle_utility_commands() {
+        let mut output = Vec::new();
+        ComSetOption::new(SetOption::MYSQL_OPTION_MULTI_STATEMENTS_OFF).serialize(&mut output);
+        assert_eq!(output, b"\x1b\x01\x00");
+        let packet = ComSetOption::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert_eq!(
+            packet.option(),
+            SetOption::MYSQL_OPTION_MULTI_STATEMENTS_OFF
+        );
+        assert!(ComSetOption::deserialize((), &mut ParseBuf(b"\x1b\x02\x00")).is_err());
+
+        let mut output = Vec::new();
+        ComDebug::new().serialize(&mut output);
+        ComStatistics::new().serialize(&mut output);
+        assert_eq!(output, b"\x0d\x09");
+        let mut buf = ParseBuf(&output);
+        buf.parse::<ComDebug>(()).unwrap();
+        buf.parse::<ComStatistics>(()).unwrap();
+        assert!(ComDebug::deserialize((), &mut ParseBuf(b"\x09")).is_err());
+
+        const STATISTICS: &[u8] = b"Uptime: 3600  Threads: 2  Questions: 120  Slow queries: 1  \
+            Opens: 40  Flush tables: 3  Open tables: 33  Queries per second avg: 0.033";
+        let stats = ServerStatistics::deserialize((), &mut ParseBuf(STATISTICS)).unwrap();
+        assert_eq!(
+            stats,
+            ServerStatistics {
+                uptime: Some(3600),
+                threads: Some(2),
+                questions: Some(120),
+                slow_queries: Some(1),
+                opens: Some(40),
+                flush_tables: Some(3),
+                open_tables: Some(33),
+                queries_per_second_avg: Some(0.033),
+            }
+        );
+        assert_eq!(
+            ServerStatistics::parse("Uptime: 5"),
+            ServerStatistics {
+                uptime: Some(5),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn should_handle_auth_next_factor() {
+        const PAYLOAD: &[u8] = b"\x02authentication_ldap_sasl_client\x00SCRAM-SHA-1";
+        let packet = AuthNextFactor::deserialize((), &mut ParseBuf(PAYLOAD)).unwrap();
+        assert_eq!(
+            packet.auth_plugin(),
+            AuthPlugin::Other(Cow::Borrowed(&b"authentication_ldap_sasl_client"[..]))
+        );
+        assert_eq!(packet.plugin_data(), b"SCRAM-SHA-1");
+
+        let mut output = Vec::new();
+        packet.into_owned().serialize(&mut output);
+        assert_eq!(output, PAYLOAD);
+
+        // AuthSwitchRequest has a different header
+        assert!(AuthSwitchRequest::deserialize((), &mut ParseBuf(PAYLOAD)).is_err());
+
+        let response = HandshakeResponse::new(
+            Some(&b"scramble"[..]),
+            (8, 0, 27),
+            Some(&b"root"[..]),
+            None::<&'static [u8]>,
+            None,
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_SECURE_CONNECTION,
+            None,
+        )
+        .with_multi_factor_authentication(true);
+        let mut output = Vec::new();
+        response.serialize(&mut output);
+        let parsed = HandshakeResponse::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert!(parsed
+            .capabilities()
+            .contains(CapabilityFlags::MULTI_FACTOR_AUTHENTICATION));
+        assert_eq!(parsed, response);
+    }
+
+    #[test]
+    fn should_handle_public_key_exchange() {
+        const PEM: &[u8] = b"-----BEGIN PUBLIC KEY-----\n\
+                             MFwwDQYJKoZIhvcNAQEBBQADSwAwSAJBAKj34GkxFhD90vcNLYLInFEX6Ppy1tPf\n\
+                             9Cnzj4p4WGeKLs1Pt8QuKUpRKfFLfRYC9AIKjbJTWit+CqvjWYzvQwECAwEAAQ==\n\
+                             -----END PUBLIC KEY-----\n";
+
+        let mut output = Vec::new();
+        PublicKeyRequest::new().serialize(&mut output);
+        assert_eq!(output, b"\x02");
+        PublicKeyRequest::deserialize((), &mut ParseBuf(b"\x02")).unwrap();
+        PublicKeyRequest::deserialize((), &mut ParseBuf(b"\x01")).unwrap_err();
+
+        let mut payload = vec![0x01];
+        payload.extend_from_slice(PEM);
+        let packet = PublicKeyResponse::deserialize((), &mut ParseBuf(&payload)).unwrap();
+        assert_eq!(packet.pem(), PEM);
+
+        let more_data = AuthMoreData::deserialize((), &mut ParseBuf(&payload)).unwrap();
+        assert_eq!(PublicKeyResponse::try_from(more_data).unwrap(), packet);
+
+        let mut output = Vec::new();
+        packet.serialize(&mut output);
+        assert_eq!(output, payload);
+
+        assert_eq!(
+            PublicKeyResponse::new(&b"\x00\x01garbage"[..]),
+            Err(InvalidPublicKeyError::NotPem)
+        );
+        assert_eq!(
+            PublicKeyResponse::new(&b"-----BEGIN PUBLIC KEY----------END PUBLIC KEY-----"[..]),
+            Err(InvalidPublicKeyError::NotPem)
+        );
+        assert_eq!(
+            PublicKeyResponse::new(vec![b'A'; PublicKeyResponse::MAX_LEN + 1]),
+            Err(InvalidPublicKeyError::TooLong(
+                PublicKeyResponse::MAX_LEN + 1
+            ))
+        );
+        PublicKeyResponse::deserialize((), &mut ParseBuf(b"\x01garbage")).unwrap_err();
     }
 
     #[test]
@@ -3347,6 +5978,144 @@ mod test {
         assert_eq!(ok_packet.session_state_info_ref(), None);
     }
 
+    #[test]
+    fn should_decode_session_state_changes() {
+        const GTID_SET: &[u8] = b"3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5";
+
+        let mut gtids = Vec::new();
+        session_state_change::Gtids::new(GTID_SET).serialize(&mut gtids);
+        assert_eq!(gtids[0], 0);
+
+        let mut state = Vec::new();
+        state.put_u8(SessionStateType::SESSION_TRACK_GTIDS as u8);
+        state.put_lenenc_str(&gtids);
+        state.put_u8(SessionStateType::SESSION_TRACK_TRANSACTION_STATE as u8);
+        state.put_lenenc_str(b"\x08T_R_W_S_");
+
+        let mut packet = vec![0x00, 0x00, 0x00, 0x02, 0x40, 0x00, 0x00, 0x00];
+        packet.put_lenenc_str(&state);
+
+        let ok_packet: OkPacket = OkPacketDeserializer::<CommonOkPacket>::deserialize(
+            CapabilityFlags::CLIENT_SESSION_TRACK,
+            &mut ParseBuf(&packet),
+        )
+        .unwrap()
+        .into();
+        let mut changes = ok_packet.session_state_changes().unwrap();
+        assert_eq!(changes.len(), 2);
+
+        match changes.pop().unwrap() {
+            SessionStateChange::TransactionState(state) => {
+                assert!(state.is_in_transaction());
+                assert!(state.is_explicit());
+                assert!(!state.is_implicit());
+                assert!(!state.has_non_transactional_reads());
+                assert!(state.has_transactional_reads());
+                assert!(state.has_transactional_writes());
+                assert!(state.has_result_set());
+                assert!(!state.has_locked_tables());
+            }
+            _ => panic!(),
+        }
+
+        match changes.pop().unwrap() {
+            SessionStateChange::Gtids(gtids) => {
+                assert_eq!(gtids.encoding(), 0);
+                assert_eq!(gtids.as_bytes(), GTID_SET);
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn should_send_zstd_compression_level() {
+        let capabilities = CapabilityFlags::CLIENT_PROTOCOL_41
+            | CapabilityFlags::CLIENT_SECURE_CONNECTION
+            | CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM;
+        let response = HandshakeResponse::new(
+            Some(&b"scramble"[..]),
+            (8, 0, 30),
+            Some(&b"root"[..]),
+            None::<&'static [u8]>,
+            None,
+            capabilities,
+            None,
+        );
+        assert!(!response
+            .capabilities()
+            .contains(CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM));
+
+        let response = response.with_zstd_compression_level(Some(3));
+        assert!(response
+            .capabilities()
+            .contains(CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM));
+
+        let mut buf = Vec::new();
+        response.serialize(&mut buf);
+        assert_eq!(buf.last(), Some(&3));
+
+        let parsed = HandshakeResponse::deserialize((), &mut ParseBuf(&buf)).unwrap();
+        assert_eq!(parsed.zstd_compression_level(), Some(3));
+        assert_eq!(parsed, response);
+    }
+
+    #[test]
+    fn should_build_connect_attrs() {
+        let attrs = ConnectAttrs::new().with_client("my_driver", "1.0.0");
+        assert_eq!(attrs.get("_client_name"), Some("my_driver"));
+        assert_eq!(attrs.get("_client_version"), Some("1.0.0"));
+        assert_eq!(attrs.get("_os"), Some(std::env::consts::OS));
+        assert_eq!(attrs.get("_platform"), Some(std::env::consts::ARCH));
+        assert_eq!(attrs.get("_pid"), Some(&*std::process::id().to_string()));
+
+        let mut attrs = ConnectAttrs::empty().with_attr("foo", "bar").unwrap();
+        let mut output = Vec::new();
+        attrs.serialize(&mut output);
+        assert_eq!(output, b"\x08\x03foo\x03bar");
+
+        assert_eq!(
+            attrs.insert(&"k".repeat(33), "v"),
+            Err(ConnectAttrsError::KeyTooLong {
+                key: "k".repeat(33),
+                max: ConnectAttrs::MAX_KEY_LEN,
+            })
+        );
+        assert!(matches!(
+            attrs.insert("foo", &"v".repeat(1025)),
+            Err(ConnectAttrsError::ValueTooLong { .. })
+        ));
+        for i in 0..63 {
+            attrs.insert(&i.to_string(), &"v".repeat(1024)).unwrap();
+        }
+        assert!(matches!(
+            attrs.insert("last", &"v".repeat(1024)),
+            Err(ConnectAttrsError::TooLong { .. })
+        ));
+        assert_eq!(attrs.get("foo"), Some("bar"));
+        assert_eq!(attrs.get("last"), None);
+
+        let response = HandshakeResponse::new(
+            Some(&b"scramble"[..]),
+            (8, 0, 30),
+            Some(&b"root"[..]),
+            None::<&'static [u8]>,
+            None,
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_SECURE_CONNECTION,
+            None,
+        )
+        .with_connect_attributes(Some(ConnectAttrs::new().into()));
+        assert!(response
+            .capabilities()
+            .contains(CapabilityFlags::CLIENT_CONNECT_ATTRS));
+        let mut output = Vec::new();
+        response.serialize(&mut output);
+        let parsed = HandshakeResponse::deserialize((), &mut ParseBuf(&output)).unwrap();
+        assert_eq!(
+            parsed.connect_attributes(),
+            Some(HashMap::from(ConnectAttrs::new()))
+        );
+    }
+
     #[test]
     fn should_build_handshake_response() {
         let flags_without_db_name = CapabilityFlags::from_bits_truncate(0x81aea205);
@@ -3446,4 +6215,311 @@ mod test {
         .to_vec();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn should_parse_com_change_user() {
+        let mut attrs = HashMap::new();
+        attrs.insert("_client_name".to_string(), "mysql_common".to_string());
+
+        let capabilities = CapabilityFlags::CLIENT_PROTOCOL_41
+            | CapabilityFlags::CLIENT_SECURE_CONNECTION
+            | CapabilityFlags::CLIENT_PLUGIN_AUTH
+            | CapabilityFlags::CLIENT_CONNECT_ATTRS;
+        let packet = ComChangeUser::new(capabilities, &b"root"[..])
+            .with_auth_plugin_data(&b"scramble"[..])
+            .with_database(&b"mydb"[..])
+            .with_charset(33)
+            .with_auth_plugin(Some(AuthPlugin::CachingSha2Password))
+            .with_connect_attributes(Some(attrs.clone()));
+
+        let mut buf = Vec::new();
+        packet.serialize(&mut buf);
+        let parsed = ComChangeUser::deserialize(capabilities, &mut ParseBuf(&buf)).unwrap();
+        assert_eq!(parsed, packet);
+        assert_eq!(parsed.user(), b"root");
+        assert_eq!(parsed.auth_plugin_data(), b"scramble");
+        assert_eq!(parsed.database(), b"mydb");
+        assert_eq!(parsed.charset(), 33);
+        assert_eq!(parsed.auth_plugin(), Some(&AuthPlugin::CachingSha2Password));
+        assert_eq!(parsed.connect_attributes(), Some(attrs));
+
+        // pre-4.1 layout without optional trailing fields
+        let capabilities = CapabilityFlags::empty();
+        let buf = b"\x11root\x00pass\x00\x00";
+        let parsed = ComChangeUser::deserialize(capabilities, &mut ParseBuf(buf)).unwrap();
+        assert_eq!(parsed.user(), b"root");
+        assert_eq!(parsed.auth_plugin_data(), b"pass");
+        assert_eq!(parsed.database(), b"");
+        assert_eq!(parsed.charset(), 0);
+        assert_eq!(parsed.auth_plugin(), None);
+        assert_eq!(parsed.connect_attributes(), None);
+    }
+
+    #[test]
+    fn should_send_long_data() {
+        let data = vec![0x42_u8; 10];
+        let chunks = ComStmtSendLongData::chunked(1, 1, &data, 4).collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.iter().map(|x| x.data().len()).sum::<usize>(), 10);
+        assert_eq!(
+            ComStmtSendLongData::chunked(1, 1, &[], 4)
+                .map(|x| x.data().len())
+                .collect::<Vec<_>>(),
+            vec![0]
+        );
+
+        let mut buf = Vec::new();
+        chunks[0].serialize(&mut buf);
+        assert_eq!(buf, b"\x18\x01\x00\x00\x00\x01\x00\x42\x42\x42\x42");
+        let parsed = ComStmtSendLongData::deserialize((), &mut ParseBuf(&buf)).unwrap();
+        assert_eq!(parsed, chunks[0]);
+
+        let params = [Value::Int(1), Value::NULL, Value::Bytes(b"foo".to_vec())];
+        let (request, _) = ComStmtExecuteRequestBuilder::new(1).build(&params);
+        let request = request.with_long_data_sent(1).with_long_data_sent(2);
+        assert!(!request.is_long_data_sent(0));
+        assert!(request.is_long_data_sent(1));
+        assert!(request.is_long_data_sent(2));
+
+        let mut buf = Vec::new();
+        request.serialize(&mut buf);
+        let expected: &[u8] = &[
+            0x17, // COM_STMT_EXECUTE
+            0x01, 0x00, 0x00, 0x00, // stmt id
+            0x00, // flags
+            0x01, 0x00, 0x00, 0x00, // iteration count
+            0x00, // null bitmap
+            0x01, // new params bound
+            0x08, 0x00, 0xfc, 0x00, 0xfd, 0x00, // param types
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // first param
+        ];
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn should_serialize_com_stmt_bulk_execute() {
+        let rows = vec![
+            vec![BulkParam::from(ValueRef::Int(1)), BulkParam::Default],
+            vec![
+                BulkParam::Value(ValueRef::NULL),
+                ValueRef::from("foo").into(),
+            ],
+            vec![ValueRef::Int(2).into(), BulkParam::Ignore],
+        ];
+        let request = ComStmtBulkExecuteRequest::new(1, rows)
+            .unwrap()
+            .with_unit_results(true);
+        assert_eq!(
+            request.param_types(),
+            &[
+                (
+                    ColumnType::MYSQL_TYPE_LONGLONG,
+                    StmtExecuteParamFlags::empty()
+                ),
+                (
+                    ColumnType::MYSQL_TYPE_VAR_STRING,
+                    StmtExecuteParamFlags::empty()
+                ),
+            ]
+        );
+
+        let mut buf = Vec::new();
+        request.serialize(&mut buf);
+        let expected: &[u8] = &[
+            0xfa, // COM_STMT_BULK_EXECUTE
+            0x01, 0x00, 0x00, 0x00, // stmt id
+            0xc0, 0x00, // flags
+            0x08, 0x00, 0xfd, 0x00, // param types
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, // first row
+            0x01, 0x00, 0x03, b'f', b'o', b'o', // second row
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, // third row
+        ];
+        assert_eq!(buf, expected);
+
+        assert_eq!(
+            ComStmtBulkExecuteRequest::new(1, Vec::<Vec<BulkParam>>::new()),
+            Err(BulkExecuteError::NoRows)
+        );
+        assert_eq!(
+            ComStmtBulkExecuteRequest::new(1, vec![vec![ValueRef::Int(1)], vec![]]),
+            Err(BulkExecuteError::WrongLength {
+                row: 1,
+                expected: 1,
+                given: 0,
+            })
+        );
+        assert_eq!(
+            ComStmtBulkExecuteRequest::new(
+                1,
+                vec![vec![ValueRef::Int(1)], vec![ValueRef::UInt(1)]]
+            ),
+            Err(BulkExecuteError::TypeMismatch {
+                row: 1,
+                index: 0,
+                expected: (
+                    ColumnType::MYSQL_TYPE_LONGLONG,
+                    StmtExecuteParamFlags::empty()
+                ),
+                given: (
+                    ColumnType::MYSQL_TYPE_LONGLONG,
+                    StmtExecuteParamFlags::UNSIGNED
+                ),
+            })
+        );
+    }
+
+    #[test]
+    fn should_build_com_stmt_execute_from_borrowed_values() {
+        let params = [Value::Int(1), Value::NULL, Value::Bytes(b"foo".to_vec())];
+        let (request, _) = ComStmtExecuteRequestBuilder::new(1).build(&params);
+        assert_eq!(request.params(), params.iter().collect::<Vec<_>>());
+
+        let refs = params.iter().map(ValueRef::from).collect::<Vec<_>>();
+        let (request_ref, _) = ComStmtExecuteRequestBuilder::new(1).build_ref(&refs);
+        assert_eq!(request_ref.params_ref(), request.params_ref());
+        assert!(request_ref.params().is_empty());
+
+        let mut buf = Vec::new();
+        request.serialize(&mut buf);
+        let mut buf_ref = Vec::new();
+        request_ref.serialize(&mut buf_ref);
+        assert_eq!(buf, buf_ref);
+    }
+
+    #[test]
+    fn should_send_non_finite_floats_as_null() {
+        let serialize = |x: &dyn MySerialize| {
+            let mut buf = Vec::new();
+            x.serialize(&mut buf);
+            buf
+        };
+
+        assert_eq!(Value::Double(f64::NAN).bin_len(), 0);
+        assert!(serialize(&Value::Float(f32::INFINITY)).is_empty());
+
+        let params = [Value::Double(f64::NAN), Value::Float(f32::NEG_INFINITY)];
+        let (request, _) = ComStmtExecuteRequestBuilder::new(1).build(&params);
+        assert_eq!(request.params_ref(), [ValueRef::NULL, ValueRef::NULL]);
+        let (expected, _) = ComStmtExecuteRequestBuilder::new(1).build(&[Value::NULL, Value::NULL]);
+        assert_eq!(serialize(&request), serialize(&expected));
+
+        let rows = vec![
+            vec![ValueRef::Double(f64::INFINITY)],
+            vec![ValueRef::Double(0.5)],
+        ];
+        let expected = vec![vec![ValueRef::NULL], vec![ValueRef::Double(0.5)]];
+        assert_eq!(
+            serialize(&ComStmtBulkExecuteRequest::new(1, rows).unwrap()),
+            serialize(&ComStmtBulkExecuteRequest::new(1, expected).unwrap()),
+        );
+    }
+
+    #[test]
+    fn should_serialize_query_attributes() {
+        const CAPS: CapabilityFlags = CapabilityFlags::CLIENT_QUERY_ATTRIBUTES;
+        let attrs = vec![QueryAttribute::new(&b"traceparent"[..], "foo")];
+
+        let mut buf = Vec::new();
+        ComQuery::new(CapabilityFlags::empty(), &b"SELECT 1"[..])
+            .with_query_attributes(attrs.clone())
+            .serialize(&mut buf);
+        assert_eq!(buf, b"\x03SELECT 1");
+
+        // parameter count and parameter set count are required even without attributes
+        let mut buf = Vec::new();
+        ComQuery::new(CAPS, &b"SELECT 1"[..]).serialize(&mut buf);
+        assert_eq!(buf, b"\x03\x00\x01SELECT 1");
+
+        let mut buf = Vec::new();
+        ComQuery::new(CAPS, &b"SELECT 1"[..])
+            .with_query_attributes(attrs.clone())
+            .serialize(&mut buf);
+        assert_eq!(
+            buf,
+            &b"\x03\x01\x01\x00\x01\xfd\x00\x0btraceparent\x03fooSELECT 1"[..]
+        );
+
+        let (request, _) = ComStmtExecuteRequestBuilder::new(1).build(&[]);
+        let mut buf = Vec::new();
+        request.with_capabilities(CAPS).serialize(&mut buf);
+        let expected: &[u8] = &[
+            0x17, // COM_STMT_EXECUTE
+            0x01, 0x00, 0x00, 0x00, // stmt id
+            0x08, // PARAMETER_COUNT_AVAILABLE
+            0x01, 0x00, 0x00, 0x00, // iteration count
+            0x00, // parameter count
+        ];
+        assert_eq!(buf, expected);
+
+        let params = [Value::NULL];
+        let (request, _) = ComStmtExecuteRequestBuilder::new(1).build(&params);
+        let mut buf = Vec::new();
+        request
+            .with_capabilities(CAPS)
+            .with_query_attributes(attrs)
+            .serialize(&mut buf);
+        let expected: &[u8] = &[
+            0x17, // COM_STMT_EXECUTE
+            0x01, 0x00, 0x00, 0x00, // stmt id
+            0x08, // PARAMETER_COUNT_AVAILABLE
+            0x01, 0x00, 0x00, 0x00, // iteration count
+            0x02, // parameter count
+            0x01, // null bitmap
+            0x01, // new params bound
+            0x06, 0x00, 0x00, // first param
+            0xfd, 0x00, 0x0b, b't', b'r', b'a', b'c', b'e', b'p', b'a', b'r', b'e', b'n', b't',
+            0x03, b'f', b'o', b'o', // attribute
+        ];
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn should_parse_gtid_set() {
+        let sid: Sid = "3E11FA47-71CA-11E1-9E33-C80AA9429562:1-5:7:9-10"
+            .parse()
+            .unwrap();
+        assert_eq!(sid.sid()[..4], [0x3e, 0x11, 0xfa, 0x47]);
+        assert_eq!(
+            sid.intervals(),
+            &[
+                Interval::new(1, 6),
+                Interval::new(7, 8),
+                Interval::new(9, 11)
+            ]
+        );
+        assert_eq!(
+            sid.to_string(),
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:7:9-10"
+        );
+
+        assert_eq!("3e11fa47:1".parse::<Sid>(), Err(ParseSidError::InvalidUuid));
+        for uuid in [
+            "3e11fa4771ca11e19e33c80aa9429562",
+            "3e11fa47-71ca11e1-9e33-c80aa942-9562",
+            "3e11-fa47-71ca-11e1-9e33-c80aa9429562",
+            "+e11fa47-71ca-11e1-9e33-c80aa9429562",
+            "3e11fa47-71ca-11e1-9e33-c80aa942956g",
+        ] {
+            assert_eq!(
+                format!("{}:1", uuid).parse::<Sid>(),
+                Err(ParseSidError::InvalidUuid)
+            );
+        }
+        assert_eq!(
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562:+1-5".parse::<Sid>(),
+            Err(ParseSidError::InvalidInterval)
+        );
+        assert_eq!(
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562:5-1".parse::<Sid>(),
+            Err(ParseSidError::InvalidInterval)
+        );
+        assert_eq!(
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562:0".parse::<Sid>(),
+            Err(ParseSidError::InvalidInterval)
+        );
+
+        let cmd = ComBinlogDumpGtid::new(1).with_gtid_set("").unwrap();
+        assert!(cmd.sids().is_empty());
+        assert!(!cmd.flags().contains(BinlogDumpFlags::BINLOG_THROUGH_GTID));
+    }
 }
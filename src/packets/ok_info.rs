@@ -0,0 +1,140 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Parser for the human-readable info field of an OK packet.
+
+/// Typed representation of the info field of an OK packet.
+///
+/// Server reports some statement summaries only as a human-readable string:
+///
+/// *   `UPDATE` — `Rows matched: 1  Changed: 0  Warnings: 0`;
+/// *   multi-row `INSERT`, `ALTER TABLE` — `Records: 3  Duplicates: 1  Warnings: 0`;
+/// *   `LOAD DATA` — `Records: 3  Deleted: 0  Skipped: 0  Warnings: 0`.
+///
+/// Fields, that are absent in the info string, are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OkPacketInfo {
+    /// `Rows matched` (`UPDATE`).
+    pub rows_matched: Option<u64>,
+    /// `Changed` (`UPDATE`).
+    pub changed: Option<u64>,
+    /// `Records` (`INSERT`, `ALTER TABLE`, `LOAD DATA`).
+    pub records: Option<u64>,
+    /// `Duplicates` (`INSERT`, `ALTER TABLE`).
+    pub duplicates: Option<u64>,
+    /// `Deleted` (`LOAD DATA`).
+    pub deleted: Option<u64>,
+    /// `Skipped` (`LOAD DATA`).
+    pub skipped: Option<u64>,
+    /// `Warnings`.
+    pub warnings: Option<u64>,
+}
+
+impl OkPacketInfo {
+    /// Parses the given info string.
+    ///
+    /// Returns `None` if the info string contains no known `Label: number` pairs.
+    /// Unknown labels are ignored.
+    pub fn parse(info: &str) -> Option<Self> {
+        let mut result = Self::default();
+        let mut known = false;
+
+        for (label, value) in labeled_values(info) {
+            let field = match &*label {
+                "Rows matched" => &mut result.rows_matched,
+                "Changed" => &mut result.changed,
+                "Records" => &mut result.records,
+                "Duplicates" => &mut result.duplicates,
+                "Deleted" => &mut result.deleted,
+                "Skipped" => &mut result.skipped,
+                "Warnings" => &mut result.warnings,
+                _ => continue,
+            };
+            if let Ok(value) = value.parse() {
+                *field = Some(value);
+                known = true;
+            }
+        }
+
+        if known {
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+/// Splits a human-readable `Label: value  Other label: value` summary
+/// into `(label, value)` pairs.
+pub(super) fn labeled_values(text: &str) -> Vec<(String, &str)> {
+    let mut output = Vec::new();
+    let mut label = String::new();
+    let mut tokens = text.split_whitespace();
+
+    while let Some(token) = tokens.next() {
+        let last = token.strip_suffix(':');
+        if !label.is_empty() {
+            label.push(' ');
+        }
+        label.push_str(last.unwrap_or(token));
+
+        if last.is_some() {
+            let value = tokens.next().unwrap_or_default();
+            output.push((std::mem::take(&mut label), value));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OkPacketInfo;
+
+    #[test]
+    fn should_parse_ok_packet_info() {
+        assert_eq!(
+            OkPacketInfo::parse("Rows matched: 5  Changed: 3  Warnings: 1"),
+            Some(OkPacketInfo {
+                rows_matched: Some(5),
+                changed: Some(3),
+                warnings: Some(1),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            OkPacketInfo::parse("Records: 3  Duplicates: 1  Warnings: 0"),
+            Some(OkPacketInfo {
+                records: Some(3),
+                duplicates: Some(1),
+                warnings: Some(0),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            OkPacketInfo::parse("Records: 10  Deleted: 0  Skipped: 2  Warnings: 2"),
+            Some(OkPacketInfo {
+                records: Some(10),
+                deleted: Some(0),
+                skipped: Some(2),
+                warnings: Some(2),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            OkPacketInfo::parse("Foo: 1  Rows matched: 18446744073709551615"),
+            Some(OkPacketInfo {
+                rows_matched: Some(u64::MAX),
+                ..Default::default()
+            })
+        );
+        assert_eq!(OkPacketInfo::parse(""), None);
+        assert_eq!(OkPacketInfo::parse("Rows matched: x"), None);
+        assert_eq!(OkPacketInfo::parse("something else"), None);
+    }
+}
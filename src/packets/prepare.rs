@@ -0,0 +1,356 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Complete response to `COM_STMT_PREPARE`.
+//!
+//! The response consists of a [`StmtPacket`] followed by `num_params` parameter definitions
+//! and `num_columns` column definitions. Each non-empty block of definitions is terminated
+//! by an EOF packet unless `CLIENT_DEPRECATE_EOF` is negotiated.
+
+use std::{convert::TryFrom, io};
+
+use super::{
+    server::ResultSetWriter, Column, ColumnDeserializer, OkPacketDeserializer, ResultSetTerminator,
+    ServerError, StmtPacket,
+};
+use crate::{
+    constants::{CapabilityFlags, MariadbCapabilities, StatusFlags},
+    io::ParseBuf,
+    proto::MySerialize,
+};
+
+/// Server error, that requires the client to prepare the statement again
+/// (the statement metadata was changed by a DDL statement).
+pub const ER_NEED_REPREPARE: u16 = 1615;
+
+/// Returns `true` if the given error means that the statement must be prepared again
+/// (see [`PrepareResponse::resync`]).
+pub fn is_need_reprepare(error: &ServerError<'_>) -> bool {
+    error.error_code() == ER_NEED_REPREPARE
+}
+
+/// Typed response to `COM_STMT_PREPARE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrepareResponse {
+    stmt_packet: StmtPacket,
+    params: Vec<Column>,
+    columns: Vec<Column>,
+}
+
+impl PrepareResponse {
+    /// Creates a new response (e.g. to send it from a server).
+    ///
+    /// # Panic
+    ///
+    /// Will panic if there are more than `u16::MAX` params or columns.
+    pub fn new(statement_id: u32, params: Vec<Column>, columns: Vec<Column>) -> Self {
+        let num_params = u16::try_from(params.len()).expect("too many params");
+        let num_columns = u16::try_from(columns.len()).expect("too many columns");
+        Self {
+            stmt_packet: StmtPacket::new(statement_id, num_columns, num_params, 0),
+            params,
+            columns,
+        }
+    }
+
+    /// Defines the number of warnings.
+    pub fn with_warning_count(mut self, warning_count: u16) -> Self {
+        self.stmt_packet = StmtPacket::new(
+            self.statement_id(),
+            self.stmt_packet.num_columns(),
+            self.stmt_packet.num_params(),
+            warning_count,
+        );
+        self
+    }
+
+    /// Returns the statement packet (the first packet of the response).
+    pub fn stmt_packet(&self) -> &StmtPacket {
+        &self.stmt_packet
+    }
+
+    pub fn statement_id(&self) -> u32 {
+        self.stmt_packet.statement_id()
+    }
+
+    pub fn warning_count(&self) -> u16 {
+        self.stmt_packet.warning_count()
+    }
+
+    /// Parameter definitions.
+    pub fn params(&self) -> &[Column] {
+        &self.params
+    }
+
+    /// Column definitions (empty if the statement doesn't produce a result set).
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Replaces the column definitions.
+    ///
+    /// Use it if `SERVER_STATUS_METADATA_CHANGED` flag is set in a `COM_STMT_EXECUTE` response,
+    /// i.e. the server has re-prepared the statement itself and the result set
+    /// carries new column definitions.
+    ///
+    /// # Panic
+    ///
+    /// Will panic if there are more than `u16::MAX` columns.
+    pub fn update_columns(&mut self, columns: Vec<Column>) {
+        let num_columns = u16::try_from(columns.len()).expect("too many columns");
+        self.stmt_packet = StmtPacket::new(
+            self.statement_id(),
+            num_columns,
+            self.stmt_packet.num_params(),
+            self.warning_count(),
+        );
+        self.columns = columns;
+    }
+
+    /// Re-syncs the metadata with a response to the repeated `COM_STMT_PREPARE`
+    /// (after [`ER_NEED_REPREPARE`]).
+    ///
+    /// Returns `true` if params or columns were changed (note that the statement id
+    /// is always taken from the new response).
+    pub fn resync(&mut self, reprepared: PrepareResponse) -> bool {
+        let changed = self.params != reprepared.params || self.columns != reprepared.columns;
+        *self = reprepared;
+        changed
+    }
+
+    /// Returns payloads of all packets of this response.
+    ///
+    /// `status_flags` are written into EOF packets (if `CLIENT_DEPRECATE_EOF` is not set).
+    pub fn packets(
+        &self,
+        capabilities: CapabilityFlags,
+        status_flags: StatusFlags,
+    ) -> Vec<Vec<u8>> {
+        let writer = ResultSetWriter::new(capabilities);
+        let mut packets = Vec::new();
+
+        let mut buf = Vec::new();
+        self.stmt_packet.serialize(&mut buf);
+        packets.push(buf);
+
+        for defs in [&self.params, &self.columns] {
+            for def in defs.iter() {
+                let mut buf = Vec::new();
+                writer.write_column(def, &mut buf);
+                packets.push(buf);
+            }
+            if !defs.is_empty() && !capabilities.contains(CapabilityFlags::CLIENT_DEPRECATE_EOF) {
+                let mut buf = Vec::new();
+                writer.write_eof(0, status_flags, &mut buf);
+                packets.push(buf);
+            }
+        }
+
+        packets
+    }
+}
+
+/// The next packet expected by a [`PrepareResponseDecoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Expected {
+    StmtPacket,
+    Param,
+    ParamsEof,
+    Column,
+    ColumnsEof,
+    /// Response is complete.
+    Nothing,
+}
+
+/// Sans-io decoder of a [`PrepareResponse`].
+///
+/// Feed it with payloads of packets, that follow `COM_STMT_PREPARE`.
+/// Note, that an ERR packet (the first packet of a failed prepare) must be handled
+/// by the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrepareResponseDecoder {
+    capabilities: CapabilityFlags,
+    mariadb_capabilities: MariadbCapabilities,
+    stmt_packet: Option<StmtPacket>,
+    params: Vec<Column>,
+    params_eof: bool,
+    columns: Vec<Column>,
+    columns_eof: bool,
+}
+
+impl PrepareResponseDecoder {
+    /// Creates a decoder for a connection with the given capabilities.
+    pub fn new(capabilities: CapabilityFlags) -> Self {
+        Self {
+            capabilities,
+            mariadb_capabilities: MariadbCapabilities::empty(),
+            stmt_packet: None,
+            params: Vec::new(),
+            params_eof: false,
+            columns: Vec::new(),
+            columns_eof: false,
+        }
+    }
+
+    /// Defines MariaDB extended capabilities (affects parsing of column definitions).
+    pub fn with_mariadb_capabilities(mut self, mariadb_capabilities: MariadbCapabilities) -> Self {
+        self.mariadb_capabilities = mariadb_capabilities;
+        self
+    }
+
+    /// Pushes the next packet payload.
+    ///
+    /// Returns the response, if the given packet is the last one
+    /// (decoder is ready to decode the next response after that).
+    pub fn push(&mut self, payload: &[u8]) -> io::Result<Option<PrepareResponse>> {
+        let mut buf = ParseBuf(payload);
+        match self.expected() {
+            Expected::StmtPacket => self.stmt_packet = Some(buf.parse(())?),
+            Expected::Param => {
+                let param: ColumnDeserializer = buf.parse(self.mariadb_capabilities)?;
+                self.params.push(param.into());
+            }
+            Expected::Column => {
+                let column: ColumnDeserializer = buf.parse(self.mariadb_capabilities)?;
+                self.columns.push(column.into());
+            }
+            Expected::ParamsEof => {
+                buf.parse::<OkPacketDeserializer<ResultSetTerminator>>(self.capabilities)?;
+                self.params_eof = true;
+            }
+            Expected::ColumnsEof => {
+                buf.parse::<OkPacketDeserializer<ResultSetTerminator>>(self.capabilities)?;
+                self.columns_eof = true;
+            }
+            Expected::Nothing => unreachable!("the decoder is reset after the last packet"),
+        }
+
+        match (self.expected(), self.stmt_packet.take()) {
+            (Expected::Nothing, Some(stmt_packet)) => {
+                self.params_eof = false;
+                self.columns_eof = false;
+                Ok(Some(PrepareResponse {
+                    stmt_packet,
+                    params: std::mem::take(&mut self.params),
+                    columns: std::mem::take(&mut self.columns),
+                }))
+            }
+            (_, stmt_packet) => {
+                self.stmt_packet = stmt_packet;
+                Ok(None)
+            }
+        }
+    }
+
+    fn expected(&self) -> Expected {
+        let stmt_packet = match &self.stmt_packet {
+            Some(stmt_packet) => stmt_packet,
+            None => return Expected::StmtPacket,
+        };
+        let num_params = usize::from(stmt_packet.num_params());
+        let num_columns = usize::from(stmt_packet.num_columns());
+        let with_eof = !self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_DEPRECATE_EOF);
+
+        if self.params.len() < num_params {
+            Expected::Param
+        } else if with_eof && num_params > 0 && !self.params_eof {
+            Expected::ParamsEof
+        } else if self.columns.len() < num_columns {
+            Expected::Column
+        } else if with_eof && num_columns > 0 && !self.columns_eof {
+            Expected::ColumnsEof
+        } else {
+            Expected::Nothing
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::ColumnType;
+
+    #[test]
+    fn should_decode_prepare_response() -> io::Result<()> {
+        let response = PrepareResponse::new(
+            7,
+            vec![
+                Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"?"),
+                Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"?"),
+            ],
+            vec![Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id")],
+        );
+
+        for capabilities in [
+            CapabilityFlags::CLIENT_PROTOCOL_41,
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_DEPRECATE_EOF,
+        ] {
+            let packets = response.packets(capabilities, StatusFlags::SERVER_STATUS_AUTOCOMMIT);
+            let with_eof = !capabilities.contains(CapabilityFlags::CLIENT_DEPRECATE_EOF);
+            assert_eq!(packets.len(), if with_eof { 6 } else { 4 });
+
+            let mut decoder = PrepareResponseDecoder::new(capabilities);
+            // decoder is reusable
+            for _ in 0..2 {
+                let (last, rest) = packets.split_last().unwrap();
+                for packet in rest {
+                    assert_eq!(decoder.push(packet)?, None);
+                }
+                assert_eq!(decoder.push(last)?, Some(response.clone()));
+            }
+        }
+
+        let empty = PrepareResponse::new(1, vec![], vec![]);
+        let packets = empty.packets(CapabilityFlags::CLIENT_PROTOCOL_41, StatusFlags::empty());
+        assert_eq!(packets.len(), 1);
+        let mut decoder = PrepareResponseDecoder::new(CapabilityFlags::CLIENT_PROTOCOL_41);
+        assert_eq!(decoder.push(&packets[0])?, Some(empty));
+
+        // unexpected column definition instead of the EOF packet
+        let packets = response.packets(CapabilityFlags::CLIENT_PROTOCOL_41, StatusFlags::empty());
+        let mut decoder = PrepareResponseDecoder::new(CapabilityFlags::CLIENT_PROTOCOL_41);
+        for packet in &packets[..3] {
+            decoder.push(packet)?;
+        }
+        assert!(decoder.push(&packets[1]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_resync_prepare_response() {
+        let mut response = PrepareResponse::new(
+            1,
+            vec![],
+            vec![Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id")],
+        );
+
+        let columns = vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_BLOB).with_name(b"data"),
+        ];
+        response.update_columns(columns.clone());
+        assert_eq!(response.columns(), &columns[..]);
+        assert_eq!(response.stmt_packet().num_columns(), 2);
+        assert_eq!(response.statement_id(), 1);
+
+        let error = ServerError::new(
+            ER_NEED_REPREPARE,
+            *b"HY000",
+            &b"Prepared statement needs to be re-prepared"[..],
+        );
+        assert!(is_need_reprepare(&error));
+
+        assert!(!response.resync(PrepareResponse::new(2, vec![], columns.clone())));
+        assert_eq!(response.statement_id(), 2);
+        assert!(response.resync(PrepareResponse::new(3, vec![], vec![])));
+        assert!(response.columns().is_empty());
+    }
+}
@@ -0,0 +1,684 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Server side of the protocol: the initial handshake (see [`HandshakeBuilder`]
+//! and [`ClientHandshake`]) and responses to commands (see [`ResultSetWriter`]).
+//!
+//! Useful for MySql-compatible proxies, test doubles and federated servers.
+
+use bytes::BufMut;
+
+use std::{borrow::Cow, io};
+
+use crate::{
+    constants::{CapabilityFlags, StatusFlags, UTF8MB4_GENERAL_CI},
+    io::{BufMutExt, ParseBuf},
+    misc::unexpected_buf_eof,
+    packets::{
+        first_byte::{EOF_HEADER, OK_HEADER},
+        AuthPlugin, Column, ErrPacket, HandshakePacket, HandshakeResponse, OkPacket, ServerError,
+        SslRequest,
+    },
+    proto::{Binary, MyDeserialize, MySerialize, Text},
+    row::RowSerializer,
+    value::Value,
+};
+
+pub use crate::row::RowWriteError;
+
+/// Builder of the initial handshake packet (`HandshakeV10`), that a server sends
+/// to a client.
+///
+/// ```
+/// # use mysql_common::{io::ParseBuf, proto::MySerialize};
+/// # use mysql_common::packets::{server::HandshakeBuilder, AuthPlugin, HandshakePacket};
+/// let nonce = *b"0123456789abcdefghij";
+/// let handshake = HandshakeBuilder::new(&b"8.0.27-proxy"[..], 42, nonce)
+///     .with_auth_plugin(AuthPlugin::CachingSha2Password)
+///     .build();
+///
+/// let mut buf = Vec::new();
+/// handshake.serialize(&mut buf);
+///
+/// let parsed: HandshakePacket = ParseBuf(&buf).parse(()).unwrap();
+/// assert_eq!(parsed.nonce(), nonce);
+/// assert_eq!(parsed.connection_id(), 42);
+/// assert_eq!(parsed.server_version_parsed(), Some((8, 0, 27)));
+/// assert_eq!(parsed.auth_plugin(), Some(AuthPlugin::CachingSha2Password));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandshakeBuilder<'a> {
+    server_version: Cow<'a, [u8]>,
+    connection_id: u32,
+    nonce: [u8; 20],
+    capabilities: CapabilityFlags,
+    default_collation: u8,
+    status_flags: StatusFlags,
+    auth_plugin: AuthPlugin<'a>,
+}
+
+impl<'a> HandshakeBuilder<'a> {
+    /// Creates a builder.
+    ///
+    /// `nonce` is the auth plugin data (a random scramble), that will be split into
+    /// the two scramble chunks of the packet.
+    ///
+    /// Defaults are: common capabilities of modern servers (including `CLIENT_DEPRECATE_EOF`
+    /// and `CLIENT_SESSION_TRACK`), `utf8mb4_general_ci` collation, `SERVER_STATUS_AUTOCOMMIT`
+    /// status flag and the `mysql_native_password` auth plugin.
+    pub fn new(
+        server_version: impl Into<Cow<'a, [u8]>>,
+        connection_id: u32,
+        nonce: [u8; 20],
+    ) -> Self {
+        Self {
+            server_version: server_version.into(),
+            connection_id,
+            nonce,
+            capabilities: CapabilityFlags::CLIENT_LONG_PASSWORD
+                | CapabilityFlags::CLIENT_FOUND_ROWS
+                | CapabilityFlags::CLIENT_LONG_FLAG
+                | CapabilityFlags::CLIENT_CONNECT_WITH_DB
+                | CapabilityFlags::CLIENT_PROTOCOL_41
+                | CapabilityFlags::CLIENT_TRANSACTIONS
+                | CapabilityFlags::CLIENT_SECURE_CONNECTION
+                | CapabilityFlags::CLIENT_MULTI_STATEMENTS
+                | CapabilityFlags::CLIENT_MULTI_RESULTS
+                | CapabilityFlags::CLIENT_PS_MULTI_RESULTS
+                | CapabilityFlags::CLIENT_PLUGIN_AUTH
+                | CapabilityFlags::CLIENT_CONNECT_ATTRS
+                | CapabilityFlags::CLIENT_PLUGIN_AUTH_LENENC_CLIENT_DATA
+                | CapabilityFlags::CLIENT_SESSION_TRACK
+                | CapabilityFlags::CLIENT_DEPRECATE_EOF,
+            default_collation: UTF8MB4_GENERAL_CI as u8,
+            status_flags: StatusFlags::SERVER_STATUS_AUTOCOMMIT,
+            auth_plugin: AuthPlugin::MysqlNativePassword,
+        }
+    }
+
+    /// Defines server capabilities.
+    ///
+    /// `CLIENT_PROTOCOL_41`, `CLIENT_SECURE_CONNECTION` and `CLIENT_PLUGIN_AUTH`
+    /// are always set.
+    pub fn with_capabilities(mut self, capabilities: CapabilityFlags) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Defines the default collation of the server.
+    pub fn with_default_collation(mut self, default_collation: u8) -> Self {
+        self.default_collation = default_collation;
+        self
+    }
+
+    /// Defines server status flags.
+    pub fn with_status_flags(mut self, status_flags: StatusFlags) -> Self {
+        self.status_flags = status_flags;
+        self
+    }
+
+    /// Defines the auth plugin, that the client should use.
+    pub fn with_auth_plugin(mut self, auth_plugin: AuthPlugin<'a>) -> Self {
+        self.auth_plugin = auth_plugin;
+        self
+    }
+
+    /// Builds the packet.
+    pub fn build(self) -> HandshakePacket<'a> {
+        let capabilities = self.capabilities
+            | CapabilityFlags::CLIENT_PROTOCOL_41
+            | CapabilityFlags::CLIENT_SECURE_CONNECTION
+            | CapabilityFlags::CLIENT_PLUGIN_AUTH;
+
+        let mut scramble_1 = [0_u8; 8];
+        scramble_1.copy_from_slice(&self.nonce[..8]);
+        // the second chunk is null-terminated
+        let mut scramble_2 = self.nonce[8..].to_vec();
+        scramble_2.push(0);
+
+        let auth_plugin_name = match self.auth_plugin {
+            AuthPlugin::Other(name) => name,
+            plugin => Cow::Owned(plugin.as_bytes().to_vec()),
+        };
+
+        HandshakePacket::new(
+            10,
+            self.server_version,
+            self.connection_id,
+            scramble_1,
+            Some(scramble_2),
+            capabilities,
+            self.default_collation,
+            self.status_flags,
+            Some(auth_plugin_name),
+        )
+    }
+}
+
+/// The first packet, that a client sends in response to the initial handshake.
+///
+/// It is either an SSL request (if the client wants to switch to TLS before sending
+/// credentials) or a handshake response (`HandshakeResponse41`). Pre-4.1 handshake
+/// responses are not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientHandshake<'a> {
+    /// Client requests a TLS connection. The handshake response will follow
+    /// after the TLS handshake.
+    SslRequest(SslRequest),
+    /// Handshake response.
+    Response(HandshakeResponse<'a>),
+}
+
+impl<'de> MyDeserialize<'de> for ClientHandshake<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let capabilities = match buf.0 {
+            [a, b, c, d, ..] => {
+                CapabilityFlags::from_bits_truncate(u32::from_le_bytes([*a, *b, *c, *d]))
+            }
+            _ => return Err(unexpected_buf_eof()),
+        };
+
+        if !capabilities.contains(CapabilityFlags::CLIENT_PROTOCOL_41) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "pre-4.1 handshake responses are not supported",
+            ));
+        }
+
+        if capabilities.contains(CapabilityFlags::CLIENT_SSL) && Some(buf.len()) == SslRequest::SIZE
+        {
+            buf.parse(()).map(ClientHandshake::SslRequest)
+        } else {
+            buf.parse(()).map(ClientHandshake::Response)
+        }
+    }
+}
+
+/// Writes server responses (OK, ERR and EOF packets and result sets) according to
+/// capabilities of a connection.
+///
+/// Every `write_*` method appends a single packet payload (without the packet header)
+/// to the given buffer.
+///
+/// ```
+/// # use mysql_common::constants::{CapabilityFlags, ColumnType, StatusFlags};
+/// # use mysql_common::packets::{server::ResultSetWriter, Column, OkPacket};
+/// # use mysql_common::{proto::Text, row::result_set::ResultSet, value::Value};
+/// let capabilities = CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_DEPRECATE_EOF;
+/// let writer = ResultSetWriter::new(capabilities);
+///
+/// let columns = vec![Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id")];
+/// let rows = vec![vec![Value::Int(1)], vec![Value::NULL]];
+/// let ok = OkPacket::new(0, None, StatusFlags::SERVER_STATUS_AUTOCOMMIT, 0);
+/// let packets = writer.text_result_set(&columns, &rows, &ok).unwrap();
+///
+/// let result_set = ResultSet::from_packets::<Text, _>(capabilities, packets).unwrap();
+/// assert_eq!(result_set.rows()[0][0], Value::Bytes(b"1".to_vec()));
+/// assert_eq!(result_set.rows()[1][0], Value::NULL);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResultSetWriter {
+    capabilities: CapabilityFlags,
+}
+
+impl ResultSetWriter {
+    /// Creates a writer for a connection with the given capabilities
+    /// (`CLIENT_PROTOCOL_41` is assumed).
+    pub fn new(capabilities: CapabilityFlags) -> Self {
+        Self { capabilities }
+    }
+
+    /// Returns capabilities of the connection.
+    pub fn capabilities(&self) -> CapabilityFlags {
+        self.capabilities
+    }
+
+    /// Writes an OK packet.
+    ///
+    /// Info and session state info are written only if `CLIENT_SESSION_TRACK` capability
+    /// is set (info is also written if it isn't empty). `SERVER_SESSION_STATE_CHANGED`
+    /// status flag is set if there is session state info to write.
+    pub fn write_ok(&self, ok: &OkPacket<'_>, buf: &mut Vec<u8>) {
+        self.write_ok_with_header(OK_HEADER, ok, buf);
+    }
+
+    /// Writes an ERR packet.
+    pub fn write_err(&self, err: &ServerError<'_>, buf: &mut Vec<u8>) {
+        ErrPacket::Error(err.clone()).serialize(buf);
+    }
+
+    /// Writes a legacy EOF packet (used only if `CLIENT_DEPRECATE_EOF` is not set).
+    pub fn write_eof(&self, warnings: u16, status_flags: StatusFlags, buf: &mut Vec<u8>) {
+        buf.put_u8(EOF_HEADER);
+        buf.put_u16_le(warnings);
+        buf.put_u16_le(status_flags.bits());
+    }
+
+    /// Writes the first packet of a result set (the number of columns).
+    ///
+    /// If `CLIENT_OPTIONAL_RESULTSET_METADATA` is set, then it is followed by the
+    /// `RESULTSET_METADATA_FULL` marker (i.e. column definitions are always sent).
+    pub fn write_column_count(&self, column_count: u64, buf: &mut Vec<u8>) {
+        buf.put_lenenc_int(column_count);
+        if self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_OPTIONAL_RESULTSET_METADATA)
+        {
+            buf.put_u8(1);
+        }
+    }
+
+    /// Writes a column definition.
+    pub fn write_column(&self, column: &Column, buf: &mut Vec<u8>) {
+        column.serialize(buf);
+    }
+
+    /// Writes a text protocol row.
+    pub fn write_text_row(
+        &self,
+        columns: &[Column],
+        row: &[Value],
+        buf: &mut Vec<u8>,
+    ) -> Result<(), RowWriteError> {
+        RowSerializer::<Text>::new(columns, row)?.write(buf)
+    }
+
+    /// Writes a binary protocol row.
+    ///
+    /// Values are converted to the representation defined by the column type
+    /// (e.g. `Value::Int` is written as four bytes for a `MYSQL_TYPE_LONG` column).
+    pub fn write_binary_row(
+        &self,
+        columns: &[Column],
+        row: &[Value],
+        buf: &mut Vec<u8>,
+    ) -> Result<(), RowWriteError> {
+        RowSerializer::<Binary>::new(columns, row)?.write(buf)
+    }
+
+    /// Writes a packet that terminates a result set, i.e. an OK packet with `0xFE` header,
+    /// if `CLIENT_DEPRECATE_EOF` is set, and an EOF packet otherwise.
+    pub fn write_terminator(&self, ok: &OkPacket<'_>, buf: &mut Vec<u8>) {
+        if self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_DEPRECATE_EOF)
+        {
+            self.write_ok_with_header(EOF_HEADER, ok, buf);
+        } else {
+            self.write_eof(ok.warnings(), ok.status_flags(), buf);
+        }
+    }
+
+    /// Returns payloads of all packets of a text protocol result set.
+    ///
+    /// `ok` is the OK packet that terminates the result set.
+    pub fn text_result_set<I>(
+        &self,
+        columns: &[Column],
+        rows: I,
+        ok: &OkPacket<'_>,
+    ) -> Result<Vec<Vec<u8>>, RowWriteError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[Value]>,
+    {
+        self.result_set(columns, rows, ok, Self::write_text_row)
+    }
+
+    /// Returns payloads of all packets of a binary protocol result set.
+    ///
+    /// `ok` is the OK packet that terminates the result set.
+    pub fn binary_result_set<I>(
+        &self,
+        columns: &[Column],
+        rows: I,
+        ok: &OkPacket<'_>,
+    ) -> Result<Vec<Vec<u8>>, RowWriteError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[Value]>,
+    {
+        self.result_set(columns, rows, ok, Self::write_binary_row)
+    }
+
+    fn result_set<I, F>(
+        &self,
+        columns: &[Column],
+        rows: I,
+        ok: &OkPacket<'_>,
+        write_row: F,
+    ) -> Result<Vec<Vec<u8>>, RowWriteError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[Value]>,
+        F: Fn(&Self, &[Column], &[Value], &mut Vec<u8>) -> Result<(), RowWriteError>,
+    {
+        let mut packets = Vec::new();
+
+        let mut packet = Vec::new();
+        self.write_column_count(columns.len() as u64, &mut packet);
+        packets.push(packet);
+
+        for column in columns {
+            let mut packet = Vec::new();
+            self.write_column(column, &mut packet);
+            packets.push(packet);
+        }
+
+        if !self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_DEPRECATE_EOF)
+        {
+            let mut packet = Vec::new();
+            self.write_eof(ok.warnings(), ok.status_flags(), &mut packet);
+            packets.push(packet);
+        }
+
+        for row in rows {
+            let mut packet = Vec::new();
+            write_row(self, columns, row.as_ref(), &mut packet)?;
+            packets.push(packet);
+        }
+
+        let mut packet = Vec::new();
+        self.write_terminator(ok, &mut packet);
+        packets.push(packet);
+
+        Ok(packets)
+    }
+
+    fn write_ok_with_header(&self, header: u8, ok: &OkPacket<'_>, buf: &mut Vec<u8>) {
+        let session_track = self
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_SESSION_TRACK);
+        let session_state_info = ok
+            .session_state_info_ref()
+            .filter(|_| session_track)
+            .unwrap_or_default();
+
+        let mut status_flags = ok.status_flags();
+        status_flags.set(
+            StatusFlags::SERVER_SESSION_STATE_CHANGED,
+            !session_state_info.is_empty(),
+        );
+
+        buf.put_u8(header);
+        buf.put_lenenc_int(ok.affected_rows());
+        buf.put_lenenc_int(ok.last_insert_id().unwrap_or_default());
+        buf.put_u16_le(status_flags.bits());
+        buf.put_u16_le(ok.warnings());
+
+        let info = ok.info_ref().unwrap_or_default();
+        if session_track {
+            buf.put_lenenc_str(info);
+            if !session_state_info.is_empty() {
+                buf.put_lenenc_str(session_state_info);
+            }
+        } else if !info.is_empty() {
+            buf.put_lenenc_str(info);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::{ColumnFlags, ColumnType},
+        packets::{CommonOkPacket, OkPacketDeserializer},
+        row::result_set::ResultSet,
+    };
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_DOUBLE).with_name(b"price"),
+            Column::new(ColumnType::MYSQL_TYPE_DATETIME).with_name(b"created"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"name"),
+            Column::new(ColumnType::MYSQL_TYPE_TINY)
+                .with_name(b"flag")
+                .with_flags(ColumnFlags::UNSIGNED_FLAG),
+        ]
+    }
+
+    #[test]
+    fn should_write_column() {
+        // column definition of `SELECT 1` sent by MySql 8.0
+        const COLUMN_PACKET: &[u8] =
+            b"\x03def\x00\x00\x00\x011\x00\x0c\x3f\x00\x01\x00\x00\x00\x08\x81\x00\x00\x00\x00";
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_LONGLONG)
+            .with_name(b"1")
+            .with_character_set(63)
+            .with_column_length(1)
+            .with_flags(ColumnFlags::NOT_NULL_FLAG | ColumnFlags::BINARY_FLAG);
+
+        let mut output = Vec::new();
+        ResultSetWriter::new(CapabilityFlags::CLIENT_PROTOCOL_41)
+            .write_column(&column, &mut output);
+        assert_eq!(output, COLUMN_PACKET);
+        assert_eq!(
+            Column::deserialize((), &mut ParseBuf(&output)).unwrap(),
+            column
+        );
+    }
+
+    #[test]
+    fn should_write_result_sets() {
+        let columns = columns();
+        let rows = vec![
+            vec![
+                Value::Int(-1),
+                Value::from("1.5"),
+                Value::Date(2021, 1, 2, 3, 4, 5, 0),
+                Value::from("foo"),
+                Value::NULL,
+            ],
+            vec![
+                Value::UInt(2),
+                Value::Double(f64::NAN),
+                Value::from("2021-01-02"),
+                Value::Int(3),
+                Value::from("255"),
+            ],
+        ];
+        let ok = OkPacket::new(0, None, StatusFlags::SERVER_STATUS_AUTOCOMMIT, 1);
+
+        for capabilities in &[
+            CapabilityFlags::CLIENT_PROTOCOL_41,
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_DEPRECATE_EOF,
+        ] {
+            let writer = ResultSetWriter::new(*capabilities);
+
+            let packets = writer.binary_result_set(&columns, &rows, &ok).unwrap();
+            let result_set = ResultSet::from_packets::<Binary, _>(*capabilities, packets).unwrap();
+            assert_eq!(result_set.columns_ref(), &columns[..]);
+            assert_eq!(result_set.ok_packet().warnings(), 1);
+            let values = result_set
+                .into_iter()
+                .map(|row| row.unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                values,
+                vec![
+                    vec![
+                        Value::Int(-1),
+                        Value::Double(1.5),
+                        Value::Date(2021, 1, 2, 3, 4, 5, 0),
+                        Value::from("foo"),
+                        Value::NULL,
+                    ],
+                    vec![
+                        Value::Int(2),
+                        Value::NULL,
+                        Value::Date(2021, 1, 2, 0, 0, 0, 0),
+                        Value::from("3"),
+                        Value::Int(255),
+                    ],
+                ]
+            );
+
+            let packets = writer.text_result_set(&columns, &rows, &ok).unwrap();
+            let result_set = ResultSet::from_packets::<Text, _>(*capabilities, packets).unwrap();
+            assert_eq!(result_set.len(), 2);
+            assert_eq!(result_set.rows()[0][0], Value::from("-1"));
+            assert_eq!(result_set.rows()[0][2], Value::from("2021-01-02 03:04:05"));
+            assert_eq!(result_set.rows()[1][1], Value::NULL);
+        }
+    }
+
+    #[test]
+    fn should_reject_incompatible_rows() {
+        let writer = ResultSetWriter::new(CapabilityFlags::CLIENT_PROTOCOL_41);
+        let columns = columns();
+        let mut buf = Vec::new();
+
+        assert_eq!(
+            writer.write_text_row(&columns, &[Value::NULL], &mut buf),
+            Err(RowWriteError::WrongLength {
+                expected: 5,
+                given: 1
+            })
+        );
+
+        let mut row = vec![Value::NULL; 5];
+        row[4] = Value::Int(256);
+        assert_eq!(
+            writer.write_binary_row(&columns, &row, &mut buf),
+            Err(RowWriteError::IncompatibleValue {
+                index: 4,
+                value: Value::Int(256),
+                column_type: ColumnType::MYSQL_TYPE_TINY,
+            })
+        );
+        row[4] = Value::NULL;
+        row[2] = Value::from("foo");
+        assert!(writer.write_binary_row(&columns, &row, &mut buf).is_err());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn should_write_ok_and_err_packets() {
+        let capabilities =
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_SESSION_TRACK;
+        let ok = OkPacket::new(2, Some(10), StatusFlags::empty(), 0)
+            .with_info(&b"Rows matched: 2  Changed: 2  Warnings: 0"[..])
+            .with_session_state_info(&b"\x01\x05\x04test"[..]);
+
+        let mut buf = Vec::new();
+        ResultSetWriter::new(capabilities).write_ok(&ok, &mut buf);
+        let parsed = ParseBuf(&buf)
+            .parse::<OkPacketDeserializer<CommonOkPacket>>(capabilities)
+            .unwrap()
+            .into_inner();
+        assert_eq!(parsed.affected_rows(), 2);
+        assert_eq!(parsed.last_insert_id(), Some(10));
+        assert_eq!(parsed.info_ref(), ok.info_ref());
+        assert!(parsed
+            .status_flags()
+            .contains(StatusFlags::SERVER_SESSION_STATE_CHANGED));
+        assert_eq!(parsed.session_state_info().unwrap().len(), 1);
+
+        // session state info is not sent without `CLIENT_SESSION_TRACK`
+        let mut buf = Vec::new();
+        ResultSetWriter::new(CapabilityFlags::CLIENT_PROTOCOL_41).write_ok(&ok, &mut buf);
+        let parsed = ParseBuf(&buf)
+            .parse::<OkPacketDeserializer<CommonOkPacket>>(CapabilityFlags::CLIENT_PROTOCOL_41)
+            .unwrap()
+            .into_inner();
+        assert_eq!(parsed.info_ref(), ok.info_ref());
+        assert_eq!(parsed.session_state_info_ref(), None);
+        assert_eq!(parsed.status_flags(), StatusFlags::empty());
+
+        let mut buf = Vec::new();
+        let err = ServerError::new(1045, *b"28000", &b"Access denied"[..]);
+        ResultSetWriter::new(capabilities).write_err(&err, &mut buf);
+        assert_eq!(
+            ParseBuf(&buf).parse::<ErrPacket>(capabilities).unwrap(),
+            ErrPacket::Error(err)
+        );
+    }
+
+    #[test]
+    fn should_build_handshake() {
+        let nonce = *b"abcdefghijklmnopqrst";
+        let handshake = HandshakeBuilder::new(&b"5.7.36"[..], 7, nonce)
+            .with_capabilities(CapabilityFlags::CLIENT_SSL)
+            .with_status_flags(StatusFlags::empty())
+            .with_auth_plugin(AuthPlugin::Other(Cow::Borrowed(b"auth_test_plugin")))
+            .build();
+
+        let mut buf = Vec::new();
+        handshake.serialize(&mut buf);
+        let parsed: HandshakePacket = ParseBuf(&buf).parse(()).unwrap();
+        assert_eq!(parsed, handshake);
+        assert_eq!(parsed.protocol_version(), 10);
+        assert_eq!(parsed.scramble_1_ref(), b"abcdefgh");
+        assert_eq!(parsed.scramble_2_ref(), Some(&b"ijklmnopqrst\0"[..]));
+        assert_eq!(parsed.nonce(), nonce);
+        assert_eq!(
+            parsed.capabilities(),
+            CapabilityFlags::CLIENT_SSL
+                | CapabilityFlags::CLIENT_PROTOCOL_41
+                | CapabilityFlags::CLIENT_SECURE_CONNECTION
+                | CapabilityFlags::CLIENT_PLUGIN_AUTH
+        );
+        assert_eq!(parsed.default_collation(), UTF8MB4_GENERAL_CI as u8);
+        assert_eq!(
+            parsed.auth_plugin_name_ref(),
+            Some(&b"auth_test_plugin"[..])
+        );
+    }
+
+    #[test]
+    fn should_parse_client_handshake() {
+        let capabilities = CapabilityFlags::CLIENT_PROTOCOL_41
+            | CapabilityFlags::CLIENT_SECURE_CONNECTION
+            | CapabilityFlags::CLIENT_SSL;
+
+        let mut buf = Vec::new();
+        SslRequest::new(capabilities, 1024, 45).serialize(&mut buf);
+        match ParseBuf(&buf).parse(()).unwrap() {
+            ClientHandshake::SslRequest(request) => {
+                assert_eq!(request.capabilities(), capabilities);
+                assert_eq!(request.max_packet_size(), 1024);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let response = HandshakeResponse::new(
+            Some(&[0x01_u8; 20][..]),
+            (8, 0, 27),
+            Some(&b"root"[..]),
+            Some(&b"mysql"[..]),
+            Some(AuthPlugin::MysqlNativePassword),
+            capabilities,
+            None,
+        )
+        .with_max_packet_size(1024);
+        let mut buf = Vec::new();
+        response.serialize(&mut buf);
+        match ParseBuf(&buf).parse(()).unwrap() {
+            ClientHandshake::Response(parsed) => {
+                assert_eq!(parsed, response);
+                assert_eq!(parsed.user(), b"root");
+                assert_eq!(parsed.db_name(), Some(&b"mysql"[..]));
+                assert_eq!(parsed.scramble_buf(), &[0x01_u8; 20][..]);
+                assert_eq!(parsed.max_packet_size(), 1024);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        buf[1] = 0; // remove CLIENT_PROTOCOL_41
+        assert!(ParseBuf(&buf).parse::<ClientHandshake>(()).is_err());
+        assert!(ParseBuf(&buf[..2]).parse::<ClientHandshake>(()).is_err());
+    }
+}
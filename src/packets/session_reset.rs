@@ -0,0 +1,271 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Sequence of commands, that brings a pooled connection back to a clean session state.
+
+use bytes::BufMut;
+
+use std::{borrow::Cow, collections::HashMap};
+
+use super::{AuthPlugin, ComChangeUser, HandshakePacket};
+use crate::{
+    constants::{CapabilityFlags, Command, UTF8MB4_GENERAL_CI},
+    proto::{ctx::ServerDialect, MySerialize},
+};
+
+/// The way to reset a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResetStrategy {
+    /// `COM_RESET_CONNECTION` (MySql 5.7.3+, MariaDB 10.2.4+). Doesn't require re-authentication.
+    ResetConnection,
+    /// `COM_CHANGE_USER` for the same user. Requires re-authentication.
+    ChangeUser,
+}
+
+impl ResetStrategy {
+    /// Chooses the strategy supported by the given server.
+    pub fn for_server(version: (u16, u16, u16), dialect: ServerDialect) -> Self {
+        let min_version = match dialect {
+            ServerDialect::MySql => (5, 7, 3),
+            ServerDialect::MariaDb => (10, 2, 4),
+        };
+        if version >= min_version {
+            ResetStrategy::ResetConnection
+        } else {
+            ResetStrategy::ChangeUser
+        }
+    }
+
+    /// Chooses the strategy supported by the server, that sent the given handshake.
+    pub fn for_handshake(handshake: &HandshakePacket<'_>) -> Self {
+        match handshake.maria_db_server_version_parsed() {
+            Some(version) => Self::for_server(version, ServerDialect::MariaDb),
+            None => Self::for_server(
+                handshake.server_version_parsed().unwrap_or_default(),
+                ServerDialect::MySql,
+            ),
+        }
+    }
+}
+
+/// A single command of a session reset sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResetCommand<'a> {
+    /// `COM_RESET_CONNECTION`.
+    ResetConnection,
+    /// `COM_INIT_DB`.
+    InitDb(Cow<'a, [u8]>),
+    /// `COM_QUERY`.
+    Query(Cow<'a, [u8]>),
+    /// `COM_CHANGE_USER`.
+    ChangeUser(ComChangeUser<'a>),
+}
+
+impl ResetCommand<'_> {
+    /// Returns `true` if the server will respond to this command with an authentication exchange
+    /// rather than an OK packet.
+    pub fn requires_auth(&self) -> bool {
+        matches!(self, ResetCommand::ChangeUser(_))
+    }
+}
+
+impl MySerialize for ResetCommand<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        match self {
+            ResetCommand::ResetConnection => buf.put_u8(Command::COM_RESET_CONNECTION as u8),
+            ResetCommand::InitDb(database) => {
+                buf.put_u8(Command::COM_INIT_DB as u8);
+                buf.put_slice(database);
+            }
+            ResetCommand::Query(query) => {
+                buf.put_u8(Command::COM_QUERY as u8);
+                buf.put_slice(query);
+            }
+            ResetCommand::ChangeUser(packet) => packet.serialize(buf),
+        }
+    }
+}
+
+/// Builds the sequence of commands a pooler sends to fully reset a session
+/// before giving the connection to another client.
+///
+/// *   [`ResetStrategy::ResetConnection`] — `COM_RESET_CONNECTION`, followed by `COM_INIT_DB`
+///     (if the database is set) and `SET NAMES` (server resets charset to the global default).
+///     Connection attributes survive the reset.
+/// *   [`ResetStrategy::ChangeUser`] — a single `COM_CHANGE_USER` for the same user
+///     with the database, charset and connection attributes of the original handshake.
+///     The caller is responsible for computing the auth plugin data using the
+///     scramble of the original handshake.
+///
+/// ```
+/// # use mysql_common::constants::CapabilityFlags;
+/// # use mysql_common::packets::session_reset::{ResetCommand, ResetStrategy, SessionReset};
+/// let reset = SessionReset::new(CapabilityFlags::CLIENT_SECURE_CONNECTION, &b"root"[..])
+///     .with_database(&b"test"[..]);
+///
+/// let commands = reset.clone().build(ResetStrategy::ResetConnection);
+/// assert_eq!(commands[0], ResetCommand::ResetConnection);
+/// assert_eq!(commands[1], ResetCommand::InitDb(b"test"[..].into()));
+/// assert_eq!(commands[2], ResetCommand::Query(b"SET NAMES utf8mb4"[..].into()));
+///
+/// let commands = reset.build(ResetStrategy::ChangeUser);
+/// assert_eq!(commands.len(), 1);
+/// assert!(commands[0].requires_auth());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionReset<'a> {
+    capabilities: CapabilityFlags,
+    user: Cow<'a, [u8]>,
+    auth_plugin_data: Cow<'a, [u8]>,
+    auth_plugin: Option<AuthPlugin<'a>>,
+    database: Option<Cow<'a, [u8]>>,
+    collation: u16,
+    charset_name: Cow<'a, str>,
+    connect_attributes: Option<HashMap<String, String>>,
+}
+
+impl<'a> SessionReset<'a> {
+    /// Creates a new builder for the given connection capabilities and user.
+    ///
+    /// Charset defaults to `utf8mb4` (`utf8mb4_general_ci`).
+    pub fn new(capabilities: CapabilityFlags, user: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            capabilities,
+            user: user.into(),
+            auth_plugin_data: Cow::Borrowed(&[]),
+            auth_plugin: None,
+            database: None,
+            collation: UTF8MB4_GENERAL_CI,
+            charset_name: Cow::Borrowed("utf8mb4"),
+            connect_attributes: None,
+        }
+    }
+
+    /// Sets the auth plugin and its data for the `COM_CHANGE_USER` strategy.
+    pub fn with_auth(
+        mut self,
+        auth_plugin: Option<AuthPlugin<'a>>,
+        auth_plugin_data: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        self.auth_plugin = auth_plugin;
+        self.auth_plugin_data = auth_plugin_data.into();
+        self
+    }
+
+    /// Sets the default database, that the session should have after the reset.
+    pub fn with_database(mut self, database: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// Sets the charset, that the session should have after the reset.
+    ///
+    /// `collation` is sent within `COM_CHANGE_USER`, `charset_name` is used
+    /// for `SET NAMES` (it is not escaped).
+    pub fn with_charset(mut self, collation: u16, charset_name: impl Into<Cow<'a, str>>) -> Self {
+        self.collation = collation;
+        self.charset_name = charset_name.into();
+        self
+    }
+
+    /// Sets connection attributes to re-send with `COM_CHANGE_USER`.
+    pub fn with_connect_attributes(
+        mut self,
+        connect_attributes: Option<HashMap<String, String>>,
+    ) -> Self {
+        self.connect_attributes = connect_attributes;
+        self
+    }
+
+    /// Builds the sequence of commands for the given strategy.
+    pub fn build(self, strategy: ResetStrategy) -> Vec<ResetCommand<'a>> {
+        match strategy {
+            ResetStrategy::ResetConnection => {
+                let mut commands = vec![ResetCommand::ResetConnection];
+                if let Some(database) = self.database {
+                    commands.push(ResetCommand::InitDb(database));
+                }
+                let query = format!("SET NAMES {}", self.charset_name);
+                commands.push(ResetCommand::Query(Cow::Owned(query.into_bytes())));
+                commands
+            }
+            ResetStrategy::ChangeUser => {
+                let packet = ComChangeUser::new(self.capabilities, self.user)
+                    .with_auth_plugin_data(self.auth_plugin_data)
+                    .with_database(self.database.unwrap_or_default())
+                    .with_charset(self.collation)
+                    .with_auth_plugin(self.auth_plugin)
+                    .with_connect_attributes(self.connect_attributes);
+                vec![ResetCommand::ChangeUser(packet)]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialize(commands: Vec<ResetCommand<'_>>) -> Vec<Vec<u8>> {
+        commands
+            .into_iter()
+            .map(|command| {
+                let mut buf = Vec::new();
+                command.serialize(&mut buf);
+                buf
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_choose_reset_strategy() {
+        assert_eq!(
+            ResetStrategy::for_server((5, 7, 2), ServerDialect::MySql),
+            ResetStrategy::ChangeUser
+        );
+        assert_eq!(
+            ResetStrategy::for_server((8, 0, 28), ServerDialect::MySql),
+            ResetStrategy::ResetConnection
+        );
+        assert_eq!(
+            ResetStrategy::for_server((10, 1, 48), ServerDialect::MariaDb),
+            ResetStrategy::ChangeUser
+        );
+    }
+
+    #[test]
+    fn should_build_reset_sequence() {
+        let mut attrs = HashMap::new();
+        attrs.insert("_client_name".into(), "foo".into());
+
+        let reset = SessionReset::new(
+            CapabilityFlags::CLIENT_PROTOCOL_41
+                | CapabilityFlags::CLIENT_SECURE_CONNECTION
+                | CapabilityFlags::CLIENT_PLUGIN_AUTH
+                | CapabilityFlags::CLIENT_CONNECT_ATTRS,
+            &b"root"[..],
+        )
+        .with_auth(Some(AuthPlugin::MysqlNativePassword), &b"\x01\x02"[..])
+        .with_charset(33, "utf8")
+        .with_connect_attributes(Some(attrs));
+
+        assert_eq!(
+            serialize(reset.clone().build(ResetStrategy::ResetConnection)),
+            vec![b"\x1f".to_vec(), b"\x03SET NAMES utf8".to_vec()]
+        );
+
+        assert_eq!(
+            serialize(reset.build(ResetStrategy::ChangeUser)),
+            vec![
+                b"\x11root\x00\x02\x01\x02\x00\x21\x00mysql_native_password\x00\
+                   \x11\x0c_client_name\x03foo"
+                    .to_vec()
+            ]
+        );
+    }
+}
@@ -3,7 +3,7 @@ use std::{borrow::Cow, io};
 use crate::{
     constants::SessionStateType,
     io::ParseBuf,
-    misc::raw::{bytes::EofBytes, int::LenEnc, RawBytes},
+    misc::raw::{int::LenEnc, RawBytes, RawInt},
     proto::{MyDeserialize, MySerialize},
 };
 
@@ -71,7 +71,6 @@ impl<'de> MyDeserialize<'de> for SessionStateChange<'de> {
                 let is_tracked: RawBytes<'_, LenEnc> = buf.parse_unchecked(())?;
                 Ok(SessionStateChange::IsTracked(is_tracked.as_bytes() == b"1"))
             }
-            // Layout isn't specified in the documentation
             SessionStateType::SESSION_TRACK_GTIDS => {
                 Ok(SessionStateChange::Gtids(buf.parse_unchecked(())?))
             }
@@ -111,28 +110,44 @@ impl MySerialize for SessionStateChange<'_> {
 
 /// This tracker type indicates that GTIDs are available and contains the GTID string.
 ///
-/// The GTID string is in the standard format for specifying a set of GTID values.
+/// The GTID string is in the standard format for specifying a set of GTID values
+/// (see `session_track_gtids`).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Gtids<'a>(RawBytes<'a, EofBytes>);
+pub struct Gtids<'a> {
+    /// Encoding specification (only `0` – the GTID string – is defined).
+    encoding: RawInt<u8>,
+    gtid_set: RawBytes<'a, LenEnc>,
+}
 
 impl<'a> Gtids<'a> {
     pub fn new(gtid_set: impl Into<Cow<'a, [u8]>>) -> Self {
-        Self(RawBytes::new(gtid_set))
+        Self {
+            encoding: RawInt::new(0),
+            gtid_set: RawBytes::new(gtid_set),
+        }
+    }
+
+    /// Returns the encoding specification (`0` means the GTID string).
+    pub fn encoding(&self) -> u8 {
+        self.encoding.0
     }
 
     /// Returns a raw GTID string.
     pub fn as_bytes(&self) -> &[u8] {
-        self.0.as_bytes()
+        self.gtid_set.as_bytes()
     }
 
     /// Returns a GTID string (lossy converted).
     pub fn as_str(&self) -> Cow<'_, str> {
-        self.0.as_str()
+        self.gtid_set.as_str()
     }
 
     /// Returns a `'static` version of self.
     pub fn into_owned(self) -> Gtids<'static> {
-        Gtids(self.0.into_owned())
+        Gtids {
+            encoding: self.encoding,
+            gtid_set: self.gtid_set.into_owned(),
+        }
     }
 }
 
@@ -141,13 +156,17 @@ impl<'de> MyDeserialize<'de> for Gtids<'de> {
     type Ctx = ();
 
     fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
-        buf.parse_unchecked(()).map(Self)
+        Ok(Self {
+            encoding: buf.parse(())?,
+            gtid_set: buf.parse(())?,
+        })
     }
 }
 
 impl MySerialize for Gtids<'_> {
     fn serialize(&self, buf: &mut Vec<u8>) {
-        self.0.serialize(buf);
+        self.encoding.serialize(&mut *buf);
+        self.gtid_set.serialize(buf);
     }
 }
 
@@ -322,6 +341,63 @@ impl<'a> TransactionState<'a> {
         self.0.as_str()
     }
 
+    /// Returns `true` if the character at the given position of the state string
+    /// equals to `flag`.
+    fn has_flag(&self, position: usize, flag: u8) -> bool {
+        self.as_bytes().get(position) == Some(&flag)
+    }
+
+    /// Returns `true` if there is an active transaction (explicit or implicit).
+    pub fn is_in_transaction(&self) -> bool {
+        self.is_explicit() || self.is_implicit()
+    }
+
+    /// Returns `true` if there is an explicitly started transaction (`T`).
+    pub fn is_explicit(&self) -> bool {
+        self.has_flag(0, b'T')
+    }
+
+    /// Returns `true` if there is an implicitly started transaction,
+    /// i.e. `autocommit` is disabled (`I`).
+    pub fn is_implicit(&self) -> bool {
+        self.has_flag(0, b'I')
+    }
+
+    /// Returns `true` if non-transactional tables were read in the transaction (`r`).
+    pub fn has_non_transactional_reads(&self) -> bool {
+        self.has_flag(1, b'r')
+    }
+
+    /// Returns `true` if transactional tables were read in the transaction (`R`).
+    pub fn has_transactional_reads(&self) -> bool {
+        self.has_flag(2, b'R')
+    }
+
+    /// Returns `true` if non-transactional tables were written in the transaction (`w`).
+    pub fn has_non_transactional_writes(&self) -> bool {
+        self.has_flag(3, b'w')
+    }
+
+    /// Returns `true` if transactional tables were written in the transaction (`W`).
+    pub fn has_transactional_writes(&self) -> bool {
+        self.has_flag(4, b'W')
+    }
+
+    /// Returns `true` if unsafe statements were executed in the transaction (`s`).
+    pub fn has_unsafe_statements(&self) -> bool {
+        self.has_flag(5, b's')
+    }
+
+    /// Returns `true` if a result set was sent to the client during the transaction (`S`).
+    pub fn has_result_set(&self) -> bool {
+        self.has_flag(6, b'S')
+    }
+
+    /// Returns `true` if there are tables locked using `LOCK TABLES` (`L`).
+    pub fn has_locked_tables(&self) -> bool {
+        self.has_flag(7, b'L')
+    }
+
     /// Returns a `'static` version of `self`.
     pub fn into_owned(self) -> TransactionState<'static> {
         TransactionState(self.0.into_owned())
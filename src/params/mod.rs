@@ -0,0 +1,588 @@
+// Copyright (c) 2017 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{
+    borrow::Cow,
+    collections::{
+        hash_map::{Entry, Entry::Occupied},
+        HashMap,
+    },
+    error::Error,
+    fmt,
+    iter::FromIterator,
+};
+
+use crate::value::{convert::ToValue, Value, ValueRef};
+
+pub use self::{serialize::ParamsSerializeError, validate::ParamsValidationError};
+
+mod serialize;
+mod validate;
+
+/// `FromValue` conversion error.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MissingNamedParameterError(pub Vec<u8>);
+
+impl fmt::Display for MissingNamedParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Missing named parameter `{}` for statement",
+            String::from_utf8_lossy(&self.0)
+        )
+    }
+}
+
+impl Error for MissingNamedParameterError {
+    fn description(&self) -> &str {
+        "Missing named parameter for statement"
+    }
+}
+
+/// Named parameters don't match the parameters of a statement (see [`Params::check_named`]).
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct NamedParamsMismatchError {
+    /// Parameters of the statement, that are not given (in order of appearance).
+    pub missing: Vec<Vec<u8>>,
+    /// Given parameters, that are not used by the statement (sorted).
+    pub extra: Vec<Vec<u8>>,
+}
+
+impl fmt::Display for NamedParamsMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |names: &[Vec<u8>]| {
+            names
+                .iter()
+                .map(|name| format!("`{}`", String::from_utf8_lossy(name)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        f.write_str("Named parameters don't match the statement")?;
+        if !self.missing.is_empty() {
+            write!(f, " (missing: {})", join(&self.missing))?;
+        }
+        if !self.extra.is_empty() {
+            write!(f, " (extra: {})", join(&self.extra))?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for NamedParamsMismatchError {}
+
+/// Representations of parameters of a prepared statement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Params {
+    Empty,
+    Named(HashMap<Vec<u8>, Value>),
+    Positional(Vec<Value>),
+}
+
+impl Params {
+    /// Checks these parameters against named parameters of a statement
+    /// (see [`parse_named_params`]).
+    ///
+    /// Reports parameters, that are used by the statement but not given, and parameters,
+    /// that are given but not used (`NULL` is a given value, see [`params!`] for a way
+    /// to leave an optional parameter out). Positional parameters are never considered
+    /// to be named.
+    ///
+    /// ```
+    /// # use mysql_common::{named_params::parse_named_params, params};
+    /// let (names, _) = parse_named_params(b"SELECT :a, :b").unwrap();
+    /// let names = names.unwrap();
+    /// assert!(params! { "a" => 1, "b" => None::<u8> }.check_named(&names).is_ok());
+    ///
+    /// let err = params! { "a" => 1, "c" => 3 }.check_named(&names).unwrap_err();
+    /// assert_eq!(err.missing, vec![b"b".to_vec()]);
+    /// assert_eq!(err.extra, vec![b"c".to_vec()]);
+    /// ```
+    ///
+    /// [`parse_named_params`]: crate::named_params::parse_named_params
+    /// [`params!`]: crate::params!
+    pub fn check_named(&self, named_params: &[Vec<u8>]) -> Result<(), NamedParamsMismatchError> {
+        let mut error = NamedParamsMismatchError::default();
+        let given = match self {
+            Params::Named(map) => Some(map),
+            Params::Empty | Params::Positional(_) => None,
+        };
+
+        for name in named_params {
+            let is_given = given.map_or(false, |map| map.contains_key(name));
+            if !is_given && !error.missing.contains(name) {
+                error.missing.push(name.clone());
+            }
+        }
+        if let Some(map) = given {
+            error.extra = map
+                .keys()
+                .filter(|name| !named_params.contains(name))
+                .cloned()
+                .collect();
+            error.extra.sort();
+        }
+
+        if error.missing.is_empty() && error.extra.is_empty() {
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+
+    /// Will convert named parameters into positional assuming order passed in `named_params`
+    /// attribute.
+    ///
+    /// Same name may appear in `named_params` multiple times (see [`parse_named_params`]) –
+    /// every occurrence is bound to the same value, so it needs to be given only once.
+    ///
+    /// ```
+    /// # use mysql_common::{named_params::parse_named_params, params, params::Params};
+    /// # use mysql_common::value::Value;
+    /// let (names, query) = parse_named_params(b"SELECT :a, :b, :a").unwrap();
+    /// assert_eq!(&*query, b"SELECT ?, ?, ?");
+    ///
+    /// let params = params! { "a" => 1, "b" => 2 };
+    /// assert_eq!(
+    ///     params.into_positional(&names.unwrap()).unwrap(),
+    ///     Params::Positional(vec![Value::Int(1), Value::Int(2), Value::Int(1)]),
+    /// );
+    /// ```
+    ///
+    /// [`parse_named_params`]: crate::named_params::parse_named_params
+    pub fn into_positional(
+        self,
+        named_params: &[Vec<u8>],
+    ) -> Result<Params, MissingNamedParameterError> {
+        match self {
+            Params::Named(mut map) => {
+                // number of remaining occurrences of every name
+                let mut remaining: HashMap<&[u8], usize> = HashMap::new();
+                for name in named_params {
+                    *remaining.entry(name.as_slice()).or_default() += 1;
+                }
+
+                let mut params: Vec<Value> = Vec::with_capacity(named_params.len());
+                for name in named_params {
+                    let count = remaining.entry(name.as_slice()).or_default();
+                    *count -= 1;
+                    match map.entry(name.clone()) {
+                        // the last occurrence takes the value, others clone it
+                        Occupied(entry) if *count == 0 => params.push(entry.remove()),
+                        Occupied(entry) => params.push(entry.get().clone()),
+                        _ => return Err(MissingNamedParameterError(name.clone())),
+                    }
+                }
+                Ok(Params::Positional(params))
+            }
+            params => Ok(params),
+        }
+    }
+}
+
+/// List of values bound to a single named parameter, e.g. `WHERE id IN (:ids)`
+/// (see [`expand_named_params`]).
+///
+/// It's a distinct type, so that lists are not confused with byte strings.
+///
+/// [`expand_named_params`]: crate::named_params::expand_named_params
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct List(pub Vec<Value>);
+
+impl<T: Into<Value>> From<Vec<T>> for List {
+    fn from(values: Vec<T>) -> Self {
+        List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> FromIterator<T> for List {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        List(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Borrowed representation of parameters of a prepared statement (see [`Params`]).
+///
+/// Values are borrowed, so large byte strings are not cloned before they are serialized
+/// into `COM_STMT_EXECUTE` (see `ComStmtExecuteRequestBuilder::build_ref`).
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use mysql_common::{params::ParamsRef, value::ValueRef};
+/// let blob = vec![0_u8; 1024];
+/// let mut map = HashMap::new();
+/// map.insert(b"id"[..].into(), ValueRef::Int(1));
+/// map.insert(b"data"[..].into(), ValueRef::from(&blob[..]));
+///
+/// let params = ParamsRef::Named(map).into_positional(&[b"data".to_vec(), b"id".to_vec()]);
+/// assert_eq!(
+///     params.unwrap(),
+///     ParamsRef::Positional(vec![ValueRef::Bytes(&blob), ValueRef::Int(1)]),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamsRef<'a> {
+    Empty,
+    Named(HashMap<Cow<'a, [u8]>, ValueRef<'a>>),
+    Positional(Vec<ValueRef<'a>>),
+}
+
+impl<'a> ParamsRef<'a> {
+    /// Will convert named parameters into positional assuming order passed in `named_params`
+    /// attribute (see [`Params::into_positional`]).
+    pub fn into_positional(
+        self,
+        named_params: &[Vec<u8>],
+    ) -> Result<ParamsRef<'a>, MissingNamedParameterError> {
+        match self {
+            ParamsRef::Named(map) => {
+                let mut params = Vec::with_capacity(named_params.len());
+                for name in named_params {
+                    match map.get(name.as_slice()) {
+                        Some(value) => params.push(*value),
+                        None => return Err(MissingNamedParameterError(name.clone())),
+                    }
+                }
+                Ok(ParamsRef::Positional(params))
+            }
+            params => Ok(params),
+        }
+    }
+
+    /// Returns owned parameters (values are cloned).
+    pub fn to_params(&self) -> Params {
+        match self {
+            ParamsRef::Empty => Params::Empty,
+            ParamsRef::Named(map) => Params::Named(
+                map.iter()
+                    .map(|(name, value)| (name.to_vec(), value.to_value()))
+                    .collect(),
+            ),
+            ParamsRef::Positional(values) => {
+                Params::Positional(values.iter().map(ValueRef::to_value).collect())
+            }
+        }
+    }
+}
+
+impl<'a> From<&'a Params> for ParamsRef<'a> {
+    fn from(params: &'a Params) -> Self {
+        match params {
+            Params::Empty => ParamsRef::Empty,
+            Params::Named(map) => ParamsRef::Named(
+                map.iter()
+                    .map(|(name, value)| (Cow::Borrowed(&name[..]), ValueRef::from(value)))
+                    .collect(),
+            ),
+            Params::Positional(values) => {
+                ParamsRef::Positional(values.iter().map(ValueRef::from).collect())
+            }
+        }
+    }
+}
+
+impl<'a> From<Vec<ValueRef<'a>>> for ParamsRef<'a> {
+    fn from(values: Vec<ValueRef<'a>>) -> Self {
+        if values.is_empty() {
+            ParamsRef::Empty
+        } else {
+            ParamsRef::Positional(values)
+        }
+    }
+}
+
+impl<'a, T: Into<Params> + Clone> From<&'a T> for Params {
+    fn from(x: &'a T) -> Params {
+        x.clone().into()
+    }
+}
+
+impl<T> From<Vec<T>> for Params
+where
+    Value: From<T>,
+{
+    fn from(x: Vec<T>) -> Params {
+        let mut raw_params: Vec<Value> = Vec::new();
+        for v in x.into_iter() {
+            raw_params.push(Value::from(v));
+        }
+        if raw_params.is_empty() {
+            Params::Empty
+        } else {
+            Params::Positional(raw_params)
+        }
+    }
+}
+
+impl<N, V> From<Vec<(N, V)>> for Params
+where
+    Vec<u8>: From<N>,
+    Value: From<V>,
+{
+    fn from(x: Vec<(N, V)>) -> Params {
+        let mut map = HashMap::default();
+        for (name, value) in x.into_iter() {
+            let name: Vec<u8> = name.into();
+            match map.entry(name) {
+                Entry::Vacant(entry) => entry.insert(Value::from(value)),
+                Entry::Occupied(entry) => {
+                    panic!(
+                        "Redefinition of named parameter `{}'",
+                        String::from_utf8_lossy(entry.key())
+                    );
+                }
+            };
+        }
+        Params::Named(map)
+    }
+}
+
+impl<'a> From<&'a [&'a dyn ToValue]> for Params {
+    fn from(x: &'a [&'a dyn ToValue]) -> Params {
+        let mut raw_params: Vec<Value> = Vec::new();
+        for v in x {
+            raw_params.push(v.to_value());
+        }
+        if raw_params.is_empty() {
+            Params::Empty
+        } else {
+            Params::Positional(raw_params)
+        }
+    }
+}
+
+impl From<()> for Params {
+    fn from(_: ()) -> Params {
+        Params::Empty
+    }
+}
+
+macro_rules! into_params_impl {
+    ($([$A:ident,$a:ident]),*) => (
+        impl<$($A: Into<Value>,)*> From<($($A,)*)> for Params {
+            fn from(x: ($($A,)*)) -> Params {
+                let ($($a,)*) = x;
+                Params::Positional(vec![
+                    $($a.into(),)*
+                ])
+            }
+        }
+    );
+}
+
+into_params_impl!([A, a]);
+into_params_impl!([A, a], [B, b]);
+into_params_impl!([A, a], [B, b], [C, c]);
+into_params_impl!([A, a], [B, b], [C, c], [D, d]);
+into_params_impl!([A, a], [B, b], [C, c], [D, d], [E, e]);
+into_params_impl!([A, a], [B, b], [C, c], [D, d], [E, e], [F, f]);
+into_params_impl!([A, a], [B, b], [C, c], [D, d], [E, e], [F, f], [G, g]);
+into_params_impl!(
+    [A, a],
+    [B, b],
+    [C, c],
+    [D, d],
+    [E, e],
+    [F, f],
+    [G, g],
+    [H, h]
+);
+into_params_impl!(
+    [A, a],
+    [B, b],
+    [C, c],
+    [D, d],
+    [E, e],
+    [F, f],
+    [G, g],
+    [H, h],
+    [I, i]
+);
+into_params_impl!(
+    [A, a],
+    [B, b],
+    [C, c],
+    [D, d],
+    [E, e],
+    [F, f],
+    [G, g],
+    [H, h],
+    [I, i],
+    [J, j]
+);
+into_params_impl!(
+    [A, a],
+    [B, b],
+    [C, c],
+    [D, d],
+    [E, e],
+    [F, f],
+    [G, g],
+    [H, h],
+    [I, i],
+    [J, j],
+    [K, k]
+);
+into_params_impl!(
+    [A, a],
+    [B, b],
+    [C, c],
+    [D, d],
+    [E, e],
+    [F, f],
+    [G, g],
+    [H, h],
+    [I, i],
+    [J, j],
+    [K, k],
+    [L, l]
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "derive")]
+    #[derive(Debug, Clone, PartialEq, crate::ToParams)]
+    struct Entity {
+        id: u32,
+        #[mysql(rename = "name")]
+        title: Option<Option<String>>,
+        note: Option<String>,
+        #[mysql(skip)]
+        secret: Vec<u8>,
+    }
+
+    #[cfg(feature = "derive")]
+    #[derive(crate::ToParams)]
+    struct Generic<T> {
+        r#type: T,
+    }
+
+    #[test]
+    fn should_bind_repeated_named_params() {
+        let names = vec![b"a".to_vec(), b"b".to_vec(), b"a".to_vec(), b"a".to_vec()];
+        let params = Params::from(vec![("a", 1), ("b", 2)]);
+        assert_eq!(
+            params.clone().into_positional(&names).unwrap(),
+            Params::Positional(vec![
+                Value::Int(1),
+                Value::Int(2),
+                Value::Int(1),
+                Value::Int(1),
+            ])
+        );
+
+        assert_eq!(
+            params.into_positional(&[b"a".to_vec(), b"c".to_vec(), b"c".to_vec()]),
+            Err(MissingNamedParameterError(b"c".to_vec()))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn should_derive_to_params() {
+        let entity = Entity {
+            id: 1,
+            title: Some(Some("foo".into())),
+            note: None,
+            secret: vec![1, 2, 3],
+        };
+        assert_eq!(
+            Params::from(&entity),
+            Params::from(vec![
+                ("id", Value::UInt(1)),
+                ("name", Value::from("foo")),
+                ("note", Value::NULL),
+            ])
+        );
+
+        let entity = Entity {
+            id: 2,
+            title: Some(None),
+            note: Some("bar".into()),
+            secret: vec![],
+        };
+        assert_eq!(
+            Params::from(entity),
+            Params::from(vec![
+                ("id", Value::UInt(2)),
+                ("name", Value::NULL),
+                ("note", Value::from("bar")),
+            ])
+        );
+
+        assert_eq!(
+            Params::from(Generic { r#type: 42_u8 }),
+            Params::from(vec![("type", Value::UInt(42))])
+        );
+    }
+
+    #[test]
+    fn should_borrow_params() {
+        let params = Params::from(vec![("a", Value::from("foo")), ("b", Value::Int(1))]);
+        let params_ref = ParamsRef::from(&params);
+        assert_eq!(params_ref.to_params(), params);
+
+        let names = [b"a".to_vec(), b"b".to_vec(), b"a".to_vec()];
+        let positional = params_ref.into_positional(&names).unwrap();
+        match positional {
+            ParamsRef::Positional(ref values) => {
+                assert_eq!(values.len(), 3);
+                // the value is borrowed, not cloned
+                match (values[2], &params) {
+                    (ValueRef::Bytes(bytes), Params::Named(map)) => match map.get(&b"a"[..]) {
+                        Some(Value::Bytes(original)) => assert!(std::ptr::eq(bytes, &original[..])),
+                        _ => unreachable!(),
+                    },
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(
+            positional.to_params(),
+            params.into_positional(&names).unwrap()
+        );
+
+        assert_eq!(ParamsRef::from(Vec::new()), ParamsRef::Empty);
+        assert_eq!(
+            ParamsRef::Positional(vec![ValueRef::Int(1)])
+                .into_positional(&names)
+                .unwrap(),
+            ParamsRef::Positional(vec![ValueRef::Int(1)])
+        );
+    }
+
+    #[test]
+    fn should_check_named_params() {
+        let names = [b"a".to_vec(), b"b".to_vec(), b"a".to_vec(), b"c".to_vec()];
+
+        let params = Params::from(vec![("a", 1), ("b", 2), ("c", 3)]);
+        assert_eq!(params.check_named(&names), Ok(()));
+
+        let params = Params::from(vec![("b", 2), ("e", 5), ("d", 4)]);
+        let err = params.check_named(&names).unwrap_err();
+        assert_eq!(err.missing, vec![b"a".to_vec(), b"c".to_vec()]);
+        assert_eq!(err.extra, vec![b"d".to_vec(), b"e".to_vec()]);
+        assert_eq!(
+            err.to_string(),
+            "Named parameters don't match the statement (missing: `a`, `c`) (extra: `d`, `e`)"
+        );
+
+        let err = Params::Empty.check_named(&names).unwrap_err();
+        assert_eq!(err.missing.len(), 3);
+        assert_eq!(Params::Empty.check_named(&[]), Ok(()));
+        assert_eq!(
+            Params::from(vec![("a", 1)])
+                .check_named(&[])
+                .unwrap_err()
+                .extra,
+            vec![b"a".to_vec()]
+        );
+    }
+}
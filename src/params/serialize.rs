@@ -0,0 +1,785 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `serde::Serializer`, that builds statement parameters (see [`Params::from_serialize`]).
+
+use serde::ser::{self, Impossible, Serialize, Serializer};
+use serde_json::{value::Serializer as JsonSerializer, Value as Json};
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+};
+
+use super::Params;
+use crate::value::Value;
+
+/// Error of [`Params::from_serialize`].
+#[derive(Debug, thiserror::Error)]
+pub enum ParamsSerializeError {
+    /// Top-level value is not a struct, a map or a sequence.
+    #[error(
+        "Can't convert {} to parameters (struct, map or sequence expected)",
+        _0
+    )]
+    NotParams(&'static str),
+    /// Key of a map is not a string.
+    #[error("Parameter name must be a string")]
+    KeyMustBeAString,
+    /// Map contains the same key twice.
+    #[error("Redefinition of named parameter `{}'", String::from_utf8_lossy(_0))]
+    DuplicateName(Vec<u8>),
+    /// Nested value can't be serialized as JSON.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// Error reported by a `Serialize` implementation.
+    #[error("{}", _0)]
+    Custom(String),
+}
+
+impl ser::Error for ParamsSerializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ParamsSerializeError::Custom(msg.to_string())
+    }
+}
+
+impl Params {
+    /// Builds parameters from any `T: Serialize`.
+    ///
+    /// *   structs and maps are converted to `Params::Named` (map keys must be strings);
+    /// *   sequences, tuples and tuple structs are converted to `Params::Positional`
+    ///     (empty sequence is converted to `Params::Empty`, same way as for `Vec<T>`);
+    /// *   `()`, unit structs and `None` are converted to `Params::Empty`.
+    ///
+    /// Fields are converted to values as follows:
+    ///
+    /// *   `None` and units are converted to `NULL`, booleans – to `0` and `1`;
+    /// *   numbers, strings and bytes are converted as is (see `From<T> for Value`);
+    /// *   unit variants are converted to their names (suitable for `ENUM` columns);
+    /// *   other values (nested structs, sequences, maps and variants) are serialized as JSON
+    ///     (see [`Serialized`]).
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use mysql_common::{params::Params, value::Value};
+    /// let params = Params::from_serialize(&(42, "foo", None::<u8>)).unwrap();
+    /// assert_eq!(
+    ///     params,
+    ///     Params::Positional(vec![Value::Int(42), Value::from("foo"), Value::NULL]),
+    /// );
+    ///
+    /// let mut map = BTreeMap::new();
+    /// map.insert("tags", vec!["a", "b"]);
+    /// let params = Params::from_serialize(&map).unwrap();
+    /// assert_eq!(params, Params::from(vec![("tags", Value::from(r#"["a","b"]"#))]));
+    /// ```
+    ///
+    /// [`Serialized`]: crate::value::json::Serialized
+    pub fn from_serialize<T>(value: &T) -> Result<Params, ParamsSerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(ParamsSerializer)
+    }
+}
+
+/// Serializes the top-level value into `Params`.
+struct ParamsSerializer;
+
+impl ParamsSerializer {
+    fn not_params(name: &'static str) -> Result<Params, ParamsSerializeError> {
+        Err(ParamsSerializeError::NotParams(name))
+    }
+}
+
+impl Serializer for ParamsSerializer {
+    type Ok = Params;
+    type Error = ParamsSerializeError;
+
+    type SerializeSeq = PositionalParams;
+    type SerializeTuple = PositionalParams;
+    type SerializeTupleStruct = PositionalParams;
+    type SerializeTupleVariant = Impossible<Params, ParamsSerializeError>;
+    type SerializeMap = NamedParams;
+    type SerializeStruct = NamedParams;
+    type SerializeStructVariant = Impossible<Params, ParamsSerializeError>;
+
+    fn serialize_bool(self, _: bool) -> Result<Params, Self::Error> {
+        Self::not_params("bool")
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<Params, Self::Error> {
+        Self::not_params("i8")
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<Params, Self::Error> {
+        Self::not_params("i16")
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<Params, Self::Error> {
+        Self::not_params("i32")
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<Params, Self::Error> {
+        Self::not_params("i64")
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<Params, Self::Error> {
+        Self::not_params("u8")
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<Params, Self::Error> {
+        Self::not_params("u16")
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<Params, Self::Error> {
+        Self::not_params("u32")
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<Params, Self::Error> {
+        Self::not_params("u64")
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<Params, Self::Error> {
+        Self::not_params("f32")
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<Params, Self::Error> {
+        Self::not_params("f64")
+    }
+
+    fn serialize_char(self, _: char) -> Result<Params, Self::Error> {
+        Self::not_params("char")
+    }
+
+    fn serialize_str(self, _: &str) -> Result<Params, Self::Error> {
+        Self::not_params("string")
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Params, Self::Error> {
+        Self::not_params("bytes")
+    }
+
+    fn serialize_none(self) -> Result<Params, Self::Error> {
+        Ok(Params::Empty)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Params, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Params, Self::Error> {
+        Ok(Params::Empty)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Params, Self::Error> {
+        Ok(Params::Empty)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<Params, Self::Error> {
+        Self::not_params("enum")
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Params, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Params, Self::Error> {
+        Self::not_params("enum")
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(PositionalParams(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(ParamsSerializeError::NotParams("enum"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(NamedParams {
+            map: HashMap::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(ParamsSerializeError::NotParams("enum"))
+    }
+}
+
+/// Collects positional parameters.
+struct PositionalParams(Vec<Value>);
+
+impl PositionalParams {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ParamsSerializeError> {
+        self.0.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Params {
+        if self.0.is_empty() {
+            Params::Empty
+        } else {
+            Params::Positional(self.0)
+        }
+    }
+}
+
+impl ser::SerializeSeq for PositionalParams {
+    type Ok = Params;
+    type Error = ParamsSerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Params, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for PositionalParams {
+    type Ok = Params;
+    type Error = ParamsSerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Params, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for PositionalParams {
+    type Ok = Params;
+    type Error = ParamsSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Params, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+/// Collects named parameters.
+struct NamedParams {
+    map: HashMap<Vec<u8>, Value>,
+    key: Option<Vec<u8>>,
+}
+
+impl NamedParams {
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        name: Vec<u8>,
+        value: &T,
+    ) -> Result<(), ParamsSerializeError> {
+        match self.map.entry(name) {
+            Entry::Vacant(entry) => {
+                entry.insert(value.serialize(ValueSerializer)?);
+                Ok(())
+            }
+            Entry::Occupied(entry) => Err(ParamsSerializeError::DuplicateName(entry.key().clone())),
+        }
+    }
+}
+
+impl ser::SerializeMap for NamedParams {
+    type Ok = Params;
+    type Error = ParamsSerializeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        match key.serialize(ValueSerializer)? {
+            Value::Bytes(name) => {
+                self.key = Some(name);
+                Ok(())
+            }
+            _ => Err(ParamsSerializeError::KeyMustBeAString),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let name = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.insert(name, value)
+    }
+
+    fn end(self) -> Result<Params, Self::Error> {
+        Ok(Params::Named(self.map))
+    }
+}
+
+impl ser::SerializeStruct for NamedParams {
+    type Ok = Params;
+    type Error = ParamsSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.insert(key.as_bytes().to_vec(), value)
+    }
+
+    fn end(self) -> Result<Params, Self::Error> {
+        Ok(Params::Named(self.map))
+    }
+}
+
+/// Serializes a single parameter into a `Value`.
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = ParamsSerializeError;
+
+    type SerializeSeq = Nested<<JsonSerializer as Serializer>::SerializeSeq>;
+    type SerializeTuple = Nested<<JsonSerializer as Serializer>::SerializeTuple>;
+    type SerializeTupleStruct = Nested<<JsonSerializer as Serializer>::SerializeTupleStruct>;
+    type SerializeTupleVariant = Nested<<JsonSerializer as Serializer>::SerializeTupleVariant>;
+    type SerializeMap = Nested<<JsonSerializer as Serializer>::SerializeMap>;
+    type SerializeStruct = Nested<<JsonSerializer as Serializer>::SerializeStruct>;
+    type SerializeStructVariant = Nested<<JsonSerializer as Serializer>::SerializeStructVariant>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Self::Error> {
+        Ok(Value::from(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Self::Error> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_none(self) -> Result<Value, Self::Error> {
+        Ok(Value::NULL)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Self::Error> {
+        Ok(Value::NULL)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, Self::Error> {
+        Ok(Value::NULL)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Value, Self::Error> {
+        Ok(Value::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        JsonSerializer
+            .serialize_newtype_variant(name, variant_index, variant, value)
+            .map(Value::from)
+            .map_err(Into::into)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(Nested(JsonSerializer.serialize_seq(len)?))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(Nested(JsonSerializer.serialize_tuple(len)?))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(Nested(JsonSerializer.serialize_tuple_struct(name, len)?))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(Nested(JsonSerializer.serialize_tuple_variant(
+            name,
+            variant_index,
+            variant,
+            len,
+        )?))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(Nested(JsonSerializer.serialize_map(len)?))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(Nested(JsonSerializer.serialize_struct(name, len)?))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(Nested(JsonSerializer.serialize_struct_variant(
+            name,
+            variant_index,
+            variant,
+            len,
+        )?))
+    }
+}
+
+/// Nested compound value, that is serialized as JSON.
+struct Nested<S>(S);
+
+impl<S> ser::SerializeSeq for Nested<S>
+where
+    S: ser::SerializeSeq<Ok = Json, Error = serde_json::Error>,
+{
+    type Ok = Value;
+    type Error = ParamsSerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_element(value).map_err(Into::into)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        self.0.end().map(Value::from).map_err(Into::into)
+    }
+}
+
+impl<S> ser::SerializeTuple for Nested<S>
+where
+    S: ser::SerializeTuple<Ok = Json, Error = serde_json::Error>,
+{
+    type Ok = Value;
+    type Error = ParamsSerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_element(value).map_err(Into::into)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        self.0.end().map(Value::from).map_err(Into::into)
+    }
+}
+
+impl<S> ser::SerializeTupleStruct for Nested<S>
+where
+    S: ser::SerializeTupleStruct<Ok = Json, Error = serde_json::Error>,
+{
+    type Ok = Value;
+    type Error = ParamsSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_field(value).map_err(Into::into)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        self.0.end().map(Value::from).map_err(Into::into)
+    }
+}
+
+impl<S> ser::SerializeTupleVariant for Nested<S>
+where
+    S: ser::SerializeTupleVariant<Ok = Json, Error = serde_json::Error>,
+{
+    type Ok = Value;
+    type Error = ParamsSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_field(value).map_err(Into::into)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        self.0.end().map(Value::from).map_err(Into::into)
+    }
+}
+
+impl<S> ser::SerializeMap for Nested<S>
+where
+    S: ser::SerializeMap<Ok = Json, Error = serde_json::Error>,
+{
+    type Ok = Value;
+    type Error = ParamsSerializeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.0.serialize_key(key).map_err(Into::into)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.serialize_value(value).map_err(Into::into)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        self.0.end().map(Value::from).map_err(Into::into)
+    }
+}
+
+impl<S> ser::SerializeStruct for Nested<S>
+where
+    S: ser::SerializeStruct<Ok = Json, Error = serde_json::Error>,
+{
+    type Ok = Value;
+    type Error = ParamsSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.0.serialize_field(key, value).map_err(Into::into)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        self.0.end().map(Value::from).map_err(Into::into)
+    }
+}
+
+impl<S> ser::SerializeStructVariant for Nested<S>
+where
+    S: ser::SerializeStructVariant<Ok = Json, Error = serde_json::Error>,
+{
+    type Ok = Value;
+    type Error = ParamsSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.0.serialize_field(key, value).map_err(Into::into)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        self.0.end().map(Value::from).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+    use std::collections::BTreeMap;
+
+    use crate::{params::Params, value::Value};
+
+    use super::ParamsSerializeError;
+
+    #[derive(Debug)]
+    enum Status {
+        Active,
+    }
+
+    impl Serialize for Status {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                Status::Active => serializer.serialize_unit_variant("Status", 0, "Active"),
+            }
+        }
+    }
+
+    struct User {
+        id: u32,
+        name: &'static str,
+        email: Option<String>,
+        admin: bool,
+        status: Status,
+        tags: Vec<&'static str>,
+    }
+
+    impl Serialize for User {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("User", 6)?;
+            state.serialize_field("id", &self.id)?;
+            state.serialize_field("name", &self.name)?;
+            state.serialize_field("email", &self.email)?;
+            state.serialize_field("admin", &self.admin)?;
+            state.serialize_field("status", &self.status)?;
+            state.serialize_field("tags", &self.tags)?;
+            state.end()
+        }
+    }
+
+    #[test]
+    fn should_convert_serializable_to_params() {
+        let user = User {
+            id: 1,
+            name: "foo",
+            email: None,
+            admin: true,
+            status: Status::Active,
+            tags: vec!["a", "b"],
+        };
+        assert_eq!(
+            Params::from_serialize(&user).unwrap(),
+            Params::from(vec![
+                ("id", Value::UInt(1)),
+                ("name", Value::from("foo")),
+                ("email", Value::NULL),
+                ("admin", Value::Int(1)),
+                ("status", Value::from("Active")),
+                ("tags", Value::from(r#"["a","b"]"#)),
+            ])
+        );
+
+        assert_eq!(
+            Params::from_serialize(&vec![1.5_f64, 2.5]).unwrap(),
+            Params::Positional(vec![Value::Double(1.5), Value::Double(2.5)])
+        );
+        assert_eq!(
+            Params::from_serialize(&Vec::<u8>::new()).unwrap(),
+            Params::Empty
+        );
+        assert_eq!(Params::from_serialize(&()).unwrap(), Params::Empty);
+
+        assert!(matches!(
+            Params::from_serialize(&42),
+            Err(ParamsSerializeError::NotParams("i32"))
+        ));
+        let mut map = BTreeMap::new();
+        map.insert(1, 2);
+        assert!(matches!(
+            Params::from_serialize(&map),
+            Err(ParamsSerializeError::KeyMustBeAString)
+        ));
+    }
+}
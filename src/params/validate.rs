@@ -0,0 +1,193 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Client side validation of statement parameters (see [`Params::validate`]).
+
+use super::Params;
+use crate::{constants::ColumnType, packets::Column, value::Value};
+
+/// Error of [`Params::validate`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ParamsValidationError {
+    /// Number of parameters doesn't match the statement.
+    #[error("Statement takes {} parameters but {} given", expected, given)]
+    WrongCount {
+        /// Number of parameters of the statement.
+        expected: usize,
+        /// Number of given parameters.
+        given: usize,
+    },
+    /// Named parameters are given (they should be converted to positional first,
+    /// see [`Params::into_positional`]).
+    #[error("Named parameters given, but positional expected")]
+    NamedParams,
+    /// Value is obviously incompatible with the type of a parameter.
+    #[error(
+        "Parameter {} ({:?}) is incompatible with the parameter type {:?}",
+        index,
+        value,
+        column_type
+    )]
+    IncompatibleType {
+        /// Index of the parameter.
+        index: usize,
+        /// Value of the parameter.
+        value: Value,
+        /// Type of the parameter.
+        column_type: ColumnType,
+    },
+}
+
+impl Params {
+    /// Checks positional parameters against a prepared statement, i.e. against its number
+    /// of parameters (see `StmtPacket::num_params`) and, if available, against definitions
+    /// of its parameters.
+    ///
+    /// Servers convert parameter values quite liberally, so only obviously incompatible
+    /// values are reported:
+    ///
+    /// *   temporal values for numeric parameters;
+    /// *   floating point values for temporal parameters;
+    /// *   negative integers for unsigned parameters;
+    /// *   non-string values for geometry parameters.
+    ///
+    /// Note, that servers usually don't report precise definitions of parameters
+    /// (e.g. MySql reports every parameter as `MYSQL_TYPE_VAR_STRING`).
+    ///
+    /// ```
+    /// # use mysql_common::params::{Params, ParamsValidationError};
+    /// let params = Params::from((1, "foo"));
+    /// assert!(params.validate(2, None).is_ok());
+    /// assert_eq!(
+    ///     params.validate(3, None),
+    ///     Err(ParamsValidationError::WrongCount { expected: 3, given: 2 }),
+    /// );
+    /// ```
+    pub fn validate(
+        &self,
+        num_params: u16,
+        definitions: Option<&[Column]>,
+    ) -> Result<(), ParamsValidationError> {
+        let values: &[Value] = match self {
+            Params::Empty => &[],
+            Params::Positional(values) => values,
+            Params::Named(_) => return Err(ParamsValidationError::NamedParams),
+        };
+
+        if values.len() != usize::from(num_params) {
+            return Err(ParamsValidationError::WrongCount {
+                expected: usize::from(num_params),
+                given: values.len(),
+            });
+        }
+
+        for (index, (value, column)) in values.iter().zip(definitions.unwrap_or(&[])).enumerate() {
+            if !is_compatible(value, column) {
+                return Err(ParamsValidationError::IncompatibleType {
+                    index,
+                    value: value.clone(),
+                    column_type: column.column_type(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_temporal_type(column_type: ColumnType) -> bool {
+    use ColumnType::*;
+    matches!(
+        column_type,
+        MYSQL_TYPE_DATE
+            | MYSQL_TYPE_NEWDATE
+            | MYSQL_TYPE_TIME
+            | MYSQL_TYPE_TIME2
+            | MYSQL_TYPE_DATETIME
+            | MYSQL_TYPE_DATETIME2
+            | MYSQL_TYPE_TIMESTAMP
+            | MYSQL_TYPE_TIMESTAMP2
+    )
+}
+
+/// Returns `false` if the value is obviously incompatible with the parameter definition.
+fn is_compatible(value: &Value, column: &Column) -> bool {
+    let column_type = column.column_type();
+    let is_numeric = column_type.is_numeric_type()
+        || matches!(
+            column_type,
+            ColumnType::MYSQL_TYPE_YEAR | ColumnType::MYSQL_TYPE_BIT
+        );
+
+    match value {
+        Value::NULL | Value::Bytes(_) => true,
+        Value::Date(..) | Value::Time(..) => !is_numeric && !column_type.is_geometry_type(),
+        Value::Float(_) | Value::Double(_) => {
+            !is_temporal_type(column_type) && !column_type.is_geometry_type()
+        }
+        Value::Int(x) => {
+            !(*x < 0 && is_numeric && column.is_unsigned()) && !column_type.is_geometry_type()
+        }
+        Value::UInt(_) => !column_type.is_geometry_type(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constants::{ColumnFlags, ColumnType},
+        packets::Column,
+        params::{Params, ParamsValidationError},
+        value::Value,
+    };
+
+    #[test]
+    fn should_validate_params() {
+        let columns = [
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_flags(ColumnFlags::UNSIGNED_FLAG),
+            Column::new(ColumnType::MYSQL_TYPE_DATETIME),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING),
+        ];
+
+        let params = Params::from((1, Value::Date(2021, 1, 1, 0, 0, 0, 0), 0.5));
+        assert_eq!(params.validate(3, Some(&columns)), Ok(()));
+        assert_eq!(params.validate(3, None), Ok(()));
+        assert_eq!(
+            Params::Empty.validate(1, None),
+            Err(ParamsValidationError::WrongCount {
+                expected: 1,
+                given: 0
+            })
+        );
+        assert_eq!(Params::Empty.validate(0, Some(&[])), Ok(()));
+        assert_eq!(
+            Params::from(vec![("a", 1)]).validate(1, None),
+            Err(ParamsValidationError::NamedParams)
+        );
+
+        let params = Params::from((-1, "2021-01-01", "foo"));
+        assert_eq!(
+            params.validate(3, Some(&columns)),
+            Err(ParamsValidationError::IncompatibleType {
+                index: 0,
+                value: Value::Int(-1),
+                column_type: ColumnType::MYSQL_TYPE_LONG,
+            })
+        );
+
+        let params = Params::from((1, 1.5, Value::Time(false, 0, 1, 2, 3, 0)));
+        assert_eq!(
+            params.validate(3, Some(&columns)),
+            Err(ParamsValidationError::IncompatibleType {
+                index: 1,
+                value: Value::Double(1.5),
+                column_type: ColumnType::MYSQL_TYPE_DATETIME,
+            })
+        );
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Opt-in payload integrity checks.
+//!
+//! This is not a part of the MySql protocol — it is meant for proxies, that need to find out
+//! where exactly payloads get silently corrupted. A sending hop computes a [`PayloadChecksum`]
+//! for every forwarded payload and sends it via a side channel, a receiving hop verifies
+//! payloads against received checksums using a [`ChecksumVerifier`]. Payloads are never
+//! modified.
+
+use std::{collections::VecDeque, io};
+
+use crate::{
+    io::ParseBuf,
+    misc::raw::{int::LeU32, RawInt},
+    proto::{MyDeserialize, MySerialize},
+};
+
+/// Checksum of a single payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PayloadChecksum {
+    /// Sequence id of the packet (of the first packet, if the payload was split).
+    pub seq_id: u8,
+    /// Length of the payload (truncated to `u32`).
+    pub len: u32,
+    /// CRC-32 of the payload.
+    pub crc32: u32,
+}
+
+impl PayloadChecksum {
+    /// Length of a serialized checksum.
+    pub const LEN: usize = 9;
+
+    /// Computes a checksum of the given payload.
+    pub fn compute(seq_id: u8, payload: &[u8]) -> Self {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(payload);
+        Self {
+            seq_id,
+            len: payload.len() as u32,
+            crc32: hasher.finalize(),
+        }
+    }
+
+    /// Verifies the given payload against this checksum.
+    pub fn verify(&self, seq_id: u8, payload: &[u8]) -> Result<(), ChecksumError> {
+        let actual = Self::compute(seq_id, payload);
+        if actual == *self {
+            Ok(())
+        } else {
+            Err(ChecksumError::Mismatch {
+                expected: *self,
+                actual,
+            })
+        }
+    }
+}
+
+impl<'de> MyDeserialize<'de> for PayloadChecksum {
+    const SIZE: Option<usize> = Some(Self::LEN);
+    type Ctx = ();
+
+    fn deserialize((): Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let seq_id = buf.parse_unchecked::<RawInt<u8>>(())?;
+        let len = buf.parse_unchecked::<RawInt<LeU32>>(())?;
+        let crc32 = buf.parse_unchecked::<RawInt<LeU32>>(())?;
+        Ok(Self {
+            seq_id: *seq_id,
+            len: *len,
+            crc32: *crc32,
+        })
+    }
+}
+
+impl MySerialize for PayloadChecksum {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        RawInt::<u8>::new(self.seq_id).serialize(&mut *buf);
+        RawInt::<LeU32>::new(self.len).serialize(&mut *buf);
+        RawInt::<LeU32>::new(self.crc32).serialize(&mut *buf);
+    }
+}
+
+/// Payload integrity error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum ChecksumError {
+    #[error(
+        "Payload checksum mismatch (expected seq_id={}, len={}, crc32={:08x}; \
+         got seq_id={}, len={}, crc32={:08x})",
+        expected.seq_id,
+        expected.len,
+        expected.crc32,
+        actual.seq_id,
+        actual.len,
+        actual.crc32
+    )]
+    Mismatch {
+        expected: PayloadChecksum,
+        actual: PayloadChecksum,
+    },
+    #[error("No checksum received for a payload (seq_id={})", actual.seq_id)]
+    Missing { actual: PayloadChecksum },
+}
+
+impl From<ChecksumError> for io::Error {
+    fn from(err: ChecksumError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Verifies payloads in order against checksums received via a side channel.
+#[derive(Debug, Clone, Default)]
+pub struct ChecksumVerifier {
+    pending: VecDeque<PayloadChecksum>,
+    verified: u64,
+}
+
+impl ChecksumVerifier {
+    /// Creates a new verifier.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a checksum received via the side channel.
+    pub fn push(&mut self, checksum: PayloadChecksum) {
+        self.pending.push_back(checksum);
+    }
+
+    /// Returns the number of received checksums, that wasn't yet used.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the number of successfully verified payloads.
+    pub fn verified(&self) -> u64 {
+        self.verified
+    }
+
+    /// Verifies the next payload against the next received checksum.
+    pub fn verify(&mut self, seq_id: u8, payload: &[u8]) -> Result<(), ChecksumError> {
+        match self.pending.pop_front() {
+            Some(expected) => {
+                expected.verify(seq_id, payload)?;
+                self.verified += 1;
+                Ok(())
+            }
+            None => Err(ChecksumError::Missing {
+                actual: PayloadChecksum::compute(seq_id, payload),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_verify_payloads() {
+        let checksum = PayloadChecksum::compute(3, b"\x03SELECT 1");
+        let mut buf = Vec::new();
+        checksum.serialize(&mut buf);
+        assert_eq!(buf.len(), PayloadChecksum::LEN);
+        let received: PayloadChecksum = ParseBuf(&buf).parse(()).unwrap();
+        assert_eq!(received, checksum);
+
+        let mut verifier = ChecksumVerifier::new();
+        verifier.push(received);
+        verifier.push(received);
+        verifier.verify(3, b"\x03SELECT 1").unwrap();
+        assert_eq!(verifier.verified(), 1);
+
+        match verifier.verify(3, b"\x03SELECT 2").unwrap_err() {
+            ChecksumError::Mismatch { expected, actual } => {
+                assert_eq!(expected, checksum);
+                assert_eq!(actual.len, checksum.len);
+                assert_ne!(actual.crc32, checksum.crc32);
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+
+        let err = verifier.verify(4, b"").unwrap_err();
+        assert_eq!(
+            err,
+            ChecksumError::Missing {
+                actual: PayloadChecksum::compute(4, b"")
+            }
+        );
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Splitting of MySql packets into chunks and reassembling them back
+//! (sans-io and synchronous variants).
+//!
+//! A packet, which payload is at least `MAX_PAYLOAD_LEN` bytes long, is sent as a sequence
+//! of chunks, where every chunk but the last one is exactly `MAX_PAYLOAD_LEN` bytes long.
+//! If the payload length is a multiple of `MAX_PAYLOAD_LEN` (including zero), then the last
+//! chunk is empty.
+
+use std::io::{Read, Write};
+
+use super::error::PacketCodecError;
+use crate::constants::MAX_PAYLOAD_LEN;
+
+/// Length of a chunk header.
+pub const CHUNK_HEADER_LEN: usize = 4;
+
+/// Returns a header of a chunk with the given length and sequence id.
+///
+/// # Panic
+///
+/// Will panic if `chunk_len` is greater than `MAX_PAYLOAD_LEN`.
+pub fn chunk_header(seq_id: u8, chunk_len: usize) -> [u8; CHUNK_HEADER_LEN] {
+    assert!(chunk_len <= MAX_PAYLOAD_LEN);
+    (chunk_len as u32 | (u32::from(seq_id) << 24)).to_le_bytes()
+}
+
+/// Splits the given payload into chunks (see [`PacketChunks`]).
+///
+/// Packet codecs split packets using this function as well (see [`packet_to_chunks`]).
+///
+/// [`packet_to_chunks`]: super::packet_to_chunks
+///
+/// ```
+/// # use mysql_common::{constants::MAX_PAYLOAD_LEN, proto::codec::chunks::split_packet};
+/// let payload = vec![0_u8; MAX_PAYLOAD_LEN];
+/// let chunks = split_packet(0, &payload).map(|(id, x)| (id, x.len())).collect::<Vec<_>>();
+/// assert_eq!(chunks, vec![(0, MAX_PAYLOAD_LEN), (1, 0)]);
+/// ```
+pub fn split_packet(seq_id: u8, payload: &[u8]) -> PacketChunks<'_> {
+    PacketChunks {
+        payload: Some(payload),
+        seq_id,
+    }
+}
+
+/// Iterator over `(sequence id, chunk payload)` pairs of a packet (see [`split_packet`]).
+///
+/// Use [`PacketChunks::next_seq_id`] to get the sequence id of the next packet.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PacketChunks<'a> {
+    /// `None` if the last chunk was emitted.
+    payload: Option<&'a [u8]>,
+    seq_id: u8,
+}
+
+impl PacketChunks<'_> {
+    /// Returns the sequence id of the next chunk.
+    pub fn next_seq_id(&self) -> u8 {
+        self.seq_id
+    }
+}
+
+impl<'a> Iterator for PacketChunks<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let payload = self.payload?;
+        let (chunk, rest) = payload.split_at(payload.len().min(MAX_PAYLOAD_LEN));
+
+        // the empty chunk terminates the packet if the last chunk is of the max length
+        self.payload = if chunk.len() == MAX_PAYLOAD_LEN {
+            Some(rest)
+        } else {
+            None
+        };
+
+        let seq_id = self.seq_id;
+        self.seq_id = self.seq_id.wrapping_add(1);
+        Some((seq_id, chunk))
+    }
+}
+
+/// Sans-io reassembler of a chunked packet.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PacketReassembler {
+    seq_id: u8,
+    max_allowed_packet: usize,
+    payload: Vec<u8>,
+}
+
+impl PacketReassembler {
+    /// Creates a new reassembler, that expects the first chunk with the given sequence id.
+    pub fn new(seq_id: u8, max_allowed_packet: usize) -> Self {
+        Self {
+            seq_id,
+            max_allowed_packet,
+            payload: Vec::new(),
+        }
+    }
+
+    /// Returns the sequence id of the next expected chunk.
+    pub fn next_seq_id(&self) -> u8 {
+        self.seq_id
+    }
+
+    /// Pushes the next chunk of the packet.
+    ///
+    /// Returns the packet payload if the given chunk is the last one
+    /// (next packet will be expected after that).
+    pub fn push(&mut self, seq_id: u8, chunk: &[u8]) -> Result<Option<Vec<u8>>, PacketCodecError> {
+        if seq_id != self.seq_id {
+            return Err(PacketCodecError::PacketsOutOfSync);
+        }
+        if chunk.len() > MAX_PAYLOAD_LEN
+            || self.payload.len() + chunk.len() > self.max_allowed_packet
+        {
+            return Err(PacketCodecError::PacketTooLarge);
+        }
+
+        self.seq_id = self.seq_id.wrapping_add(1);
+        self.payload.extend_from_slice(chunk);
+
+        if chunk.len() < MAX_PAYLOAD_LEN {
+            Ok(Some(std::mem::take(&mut self.payload)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Writes the given payload as a sequence of chunks.
+///
+/// Returns the sequence id of the next packet.
+pub fn write_packet<T: Write>(
+    mut output: T,
+    seq_id: u8,
+    payload: &[u8],
+) -> Result<u8, PacketCodecError> {
+    let mut chunks = split_packet(seq_id, payload);
+    for (seq_id, chunk) in &mut chunks {
+        output.write_all(&chunk_header(seq_id, chunk.len()))?;
+        output.write_all(chunk)?;
+    }
+    Ok(chunks.next_seq_id())
+}
+
+/// Reads a chunked packet, that starts with the given sequence id.
+///
+/// Returns the packet payload and the sequence id of the next packet.
+pub fn read_packet<T: Read>(
+    mut input: T,
+    seq_id: u8,
+    max_allowed_packet: usize,
+) -> Result<(Vec<u8>, u8), PacketCodecError> {
+    let mut reassembler = PacketReassembler::new(seq_id, max_allowed_packet);
+    let mut chunk = Vec::new();
+    loop {
+        let mut header = [0_u8; CHUNK_HEADER_LEN];
+        input.read_exact(&mut header)?;
+        let chunk_len = u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize;
+
+        chunk.resize(chunk_len, 0);
+        input.read_exact(&mut chunk)?;
+
+        if let Some(payload) = reassembler.push(header[3], &chunk)? {
+            return Ok((payload, reassembler.next_seq_id()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_split_and_reassemble_packets() -> Result<(), PacketCodecError> {
+        let lengths = [
+            0,
+            1,
+            MAX_PAYLOAD_LEN - 1,
+            MAX_PAYLOAD_LEN,
+            MAX_PAYLOAD_LEN + 1,
+            MAX_PAYLOAD_LEN * 2,
+        ];
+        let expected_chunks = [1, 1, 1, 2, 2, 3];
+
+        let mut output = Vec::new();
+        let mut seq_id = 254;
+        for (len, num_chunks) in lengths.iter().zip(expected_chunks.iter()) {
+            assert_eq!(split_packet(0, &vec![0; *len]).count(), *num_chunks);
+            seq_id = write_packet(&mut output, seq_id, &vec![0x42; *len])?;
+        }
+        assert_eq!(seq_id, 254_u8.wrapping_add(10));
+
+        let mut input = &output[..];
+        let mut seq_id = 254;
+        for len in lengths.iter() {
+            let (payload, next_seq_id) = read_packet(&mut input, seq_id, MAX_PAYLOAD_LEN * 2)?;
+            assert_eq!(payload.len(), *len);
+            assert!(payload.iter().all(|x| *x == 0x42));
+            seq_id = next_seq_id;
+        }
+        assert!(input.is_empty());
+
+        let mut reassembler = PacketReassembler::new(0, 1024);
+        assert!(matches!(
+            reassembler.push(1, b""),
+            Err(PacketCodecError::PacketsOutOfSync)
+        ));
+        assert_eq!(reassembler.push(0, b"foo")?, Some(b"foo".to_vec()));
+        assert!(matches!(
+            reassembler.push(1, &[0; 1025]),
+            Err(PacketCodecError::PacketTooLarge)
+        ));
+
+        Ok(())
+    }
+}
@@ -22,50 +22,101 @@ use std::{
     ptr::slice_from_raw_parts_mut,
 };
 
-use self::error::PacketCodecError;
+use self::{
+    chunks::{chunk_header, split_packet, CHUNK_HEADER_LEN},
+    error::PacketCodecError,
+};
 use crate::constants::{DEFAULT_MAX_ALLOWED_PACKET, MAX_PAYLOAD_LEN, MIN_COMPRESS_LENGTH};
 
+pub mod chunks;
 pub mod error;
 
+/// Compression algorithm of the compressed protocol.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CompressionAlgorithm {
+    /// zlib with the given level (`CLIENT_COMPRESS`).
+    Zlib(Compression),
+    /// zstd with the given level (`CLIENT_ZSTD_COMPRESSION_ALGORITHM`, MySql 8.0.18+).
+    ///
+    /// Level should be the one sent in the handshake response
+    /// (see `HandshakeResponse::with_zstd_compression_level`).
+    #[cfg(feature = "zstd")]
+    Zstd(u8),
+}
+
+impl CompressionAlgorithm {
+    /// Returns `true` if this algorithm won't compress any data.
+    fn is_none(&self) -> bool {
+        match self {
+            CompressionAlgorithm::Zlib(level) => *level == Compression::none(),
+            #[cfg(feature = "zstd")]
+            CompressionAlgorithm::Zstd(_) => false,
+        }
+    }
+}
+
+impl From<Compression> for CompressionAlgorithm {
+    fn from(level: Compression) -> Self {
+        CompressionAlgorithm::Zlib(level)
+    }
+}
+
 /// Will split given `packet` to MySql packet chunks and write into `dst`.
 ///
 /// Chunk ids will start with given `seq_id`.
 ///
 /// Resulting sequence id will be returned.
-pub fn packet_to_chunks<T: Buf>(mut seq_id: u8, packet: &mut T, dst: &mut BytesMut) -> u8 {
-    let extra_packet = packet.remaining() % MAX_PAYLOAD_LEN == 0;
-    dst.reserve(packet.remaining() + (packet.remaining() / MAX_PAYLOAD_LEN) * 4 + 4);
-
-    while packet.has_remaining() {
-        let mut chunk_len = min(packet.remaining(), MAX_PAYLOAD_LEN);
-        dst.put_u32_le(chunk_len as u32 | (u32::from(seq_id) << 24));
-        while chunk_len > 0 {
-            let chunk = packet.chunk();
-            let count = min(chunk.len(), chunk_len);
-            dst.put(&chunk[..count]);
-            chunk_len -= count;
-            packet.advance(count);
-        }
-        seq_id = seq_id.wrapping_add(1);
-    }
-
-    if extra_packet {
-        dst.put_u32_le(u32::from(seq_id) << 24);
-        seq_id = seq_id.wrapping_add(1);
-    }
+pub fn packet_to_chunks<T: Buf>(seq_id: u8, packet: &mut T, dst: &mut BytesMut) -> u8 {
+    let len = packet.remaining();
+    dst.reserve(len + (len / MAX_PAYLOAD_LEN) * CHUNK_HEADER_LEN + CHUNK_HEADER_LEN);
+
+    // chunks are split by `split_packet`, that needs a contiguous payload
+    let seq_id = if packet.chunk().len() == len {
+        put_chunks(seq_id, packet.chunk(), dst)
+    } else {
+        put_chunks(seq_id, &packet.copy_to_bytes(len), dst)
+    };
+    packet.advance(packet.remaining());
 
     seq_id
 }
 
+/// Writes chunks of the given payload into `dst`. Returns the sequence id of the next packet.
+fn put_chunks(seq_id: u8, payload: &[u8], dst: &mut BytesMut) -> u8 {
+    let mut chunks = split_packet(seq_id, payload);
+    for (seq_id, chunk) in &mut chunks {
+        dst.put_slice(&chunk_header(seq_id, chunk.len()));
+        dst.put_slice(chunk);
+    }
+    chunks.next_seq_id()
+}
+
 /// Will compress all data from `src` to `dst`.
 ///
 /// Compressed packets will start with given `seq_id`. Resulting sequence id will be returned.
 pub fn compress(
-    mut seq_id: u8,
+    seq_id: u8,
     compression: Compression,
     max_allowed_packet: usize,
     src: &mut BytesMut,
     dst: &mut BytesMut,
+) -> Result<u8, PacketCodecError> {
+    compress_with(
+        seq_id,
+        CompressionAlgorithm::Zlib(compression),
+        max_allowed_packet,
+        src,
+        dst,
+    )
+}
+
+/// Same as [`compress`] but uses the given compression algorithm.
+pub fn compress_with(
+    mut seq_id: u8,
+    algorithm: CompressionAlgorithm,
+    max_allowed_packet: usize,
+    src: &mut BytesMut,
+    dst: &mut BytesMut,
 ) -> Result<u8, PacketCodecError> {
     if src.is_empty() {
         return Ok(0);
@@ -74,8 +125,14 @@ pub fn compress(
     for chunk in src.chunks(min(MAX_PAYLOAD_LEN, max_allowed_packet)) {
         dst.reserve(7 + chunk.len());
 
-        if compression != Compression::none() && chunk.len() >= MIN_COMPRESS_LENGTH {
-            unsafe {
+        match algorithm {
+            _ if algorithm.is_none() || chunk.len() < MIN_COMPRESS_LENGTH => {
+                dst.put_uint_le(chunk.len() as u64, 3);
+                dst.put_u8(seq_id);
+                dst.put_uint_le(0, 3);
+                dst.put_slice(chunk);
+            }
+            CompressionAlgorithm::Zlib(compression) => unsafe {
                 let mut encoder = ZlibEncoder::new(chunk, compression);
                 let mut read = 0;
                 loop {
@@ -94,12 +151,15 @@ pub fn compress(
                 dst.put_u8(seq_id);
                 dst.put_uint_le(chunk.len() as u64, 3);
                 dst.advance_mut(read);
+            },
+            #[cfg(feature = "zstd")]
+            CompressionAlgorithm::Zstd(level) => {
+                let compressed = zstd::bulk::compress(chunk, i32::from(level))?;
+                dst.put_uint_le(compressed.len() as u64, 3);
+                dst.put_u8(seq_id);
+                dst.put_uint_le(chunk.len() as u64, 3);
+                dst.put_slice(&compressed);
             }
-        } else {
-            dst.put_uint_le(chunk.len() as u64, 3);
-            dst.put_u8(seq_id);
-            dst.put_uint_le(0, 3);
-            dst.put_slice(chunk);
         }
 
         seq_id = seq_id.wrapping_add(1);
@@ -293,6 +353,23 @@ impl CompDecoder {
         src: &mut BytesMut,
         dst: &mut BytesMut,
         max_allowed_packet: usize,
+    ) -> Result<Option<ChunkInfo>, PacketCodecError> {
+        self.decode_with(
+            CompressionAlgorithm::Zlib(Compression::default()),
+            src,
+            dst,
+            max_allowed_packet,
+        )
+    }
+
+    /// Same as [`CompDecoder::decode`] but uses the given compression algorithm
+    /// (compression level is ignored).
+    pub fn decode_with(
+        &mut self,
+        algorithm: CompressionAlgorithm,
+        src: &mut BytesMut,
+        dst: &mut BytesMut,
+        max_allowed_packet: usize,
     ) -> Result<Option<ChunkInfo>, PacketCodecError> {
         match *self {
             CompDecoder::Idle => {
@@ -307,7 +384,7 @@ impl CompDecoder {
                     match CompData::new(compressed_len, uncompressed_len, max_allowed_packet)? {
                         Some(needed) => {
                             *self = CompDecoder::Packet { seq_id, needed };
-                            self.decode(src, dst, max_allowed_packet)
+                            self.decode_with(algorithm, src, dst, max_allowed_packet)
                         }
                         None => {
                             src.advance(7);
@@ -323,6 +400,17 @@ impl CompDecoder {
                         CompData::Uncompressed(needed) => {
                             dst.extend_from_slice(&src[..needed.get()]);
                         }
+                        #[cfg(feature = "zstd")]
+                        CompData::Compressed(needed, plain_len)
+                            if matches!(algorithm, CompressionAlgorithm::Zstd(_)) =>
+                        {
+                            let plain =
+                                zstd::bulk::decompress(&src[..needed.get()], plain_len.get())?;
+                            if plain.len() != plain_len.get() {
+                                return Err(PacketCodecError::BadCompressedPacketHeader);
+                            }
+                            dst.extend_from_slice(&plain);
+                        }
                         CompData::Compressed(needed, plain_len) => {
                             dst.reserve(plain_len.get());
                             unsafe {
@@ -371,7 +459,13 @@ impl PacketCodec {
 
     /// Turns compression on.
     pub fn compress(&mut self, level: Compression) {
-        self.inner.compress(level);
+        self.inner.compress(CompressionAlgorithm::Zlib(level));
+    }
+
+    /// Turns compression on using the given algorithm
+    /// (the one negotiated during the handshake).
+    pub fn compress_with(&mut self, algorithm: CompressionAlgorithm) {
+        self.inner.compress(algorithm);
     }
 
     /// Will try to decode a packet from `src` into `dst`.
@@ -434,11 +528,11 @@ impl PacketCodecInner {
     }
 
     /// Turns compression on.
-    fn compress(&mut self, level: Compression) {
+    fn compress(&mut self, algorithm: CompressionAlgorithm) {
         match self {
             PacketCodecInner::Plain(c) => {
                 *self = PacketCodecInner::Comp(CompPacketCodec {
-                    level,
+                    algorithm,
                     comp_seq_id: 0,
                     in_buf: BytesMut::with_capacity(DEFAULT_MAX_ALLOWED_PACKET),
                     out_buf: BytesMut::with_capacity(DEFAULT_MAX_ALLOWED_PACKET),
@@ -446,7 +540,7 @@ impl PacketCodecInner {
                     plain_codec: mem::take(c),
                 })
             }
-            PacketCodecInner::Comp(c) => c.level = level,
+            PacketCodecInner::Comp(c) => c.algorithm = algorithm,
         }
     }
 
@@ -571,8 +665,8 @@ impl PlainPacketCodec {
 /// Codec for compressed MySql protocol.
 #[derive(Debug)]
 struct CompPacketCodec {
-    /// Compression level for this codec.
-    level: Compression,
+    /// Compression algorithm for this codec.
+    algorithm: CompressionAlgorithm,
     /// Compressed packet sequence id.
     comp_seq_id: u8,
     /// Buffer for decompressed input data.
@@ -626,10 +720,12 @@ impl CompPacketCodec {
             return Ok(true);
         }
 
-        match self
-            .comp_decoder
-            .decode(src, &mut self.in_buf, max_allowed_packet)?
-        {
+        match self.comp_decoder.decode_with(
+            self.algorithm,
+            src,
+            &mut self.in_buf,
+            max_allowed_packet,
+        )? {
             Some(chunk_info) => {
                 if self.comp_seq_id != chunk_info.seq_id() {
                     return Err(PacketCodecError::PacketsOutOfSync);
@@ -653,9 +749,9 @@ impl CompPacketCodec {
         self.plain_codec
             .encode(packet, &mut self.out_buf, max_allowed_packet)?;
 
-        self.comp_seq_id = compress(
+        self.comp_seq_id = compress_with(
             self.comp_seq_id,
-            self.level,
+            self.algorithm,
             max_allowed_packet,
             &mut self.out_buf,
             dst,
@@ -678,6 +774,20 @@ mod tests {
         0xb7, 0xb0, 0xc4, 0xcd, 0x52, 0x02, 0x00, 0x0c, 0xd1, 0x0a, 0x6c,
     ];
 
+    #[test]
+    fn should_split_non_contiguous_packet_to_chunks() {
+        let mut contiguous = BytesMut::new();
+        let seq_id = packet_to_chunks(1, &mut &b"foobar"[..], &mut contiguous);
+        assert_eq!(seq_id, 2);
+        assert_eq!(&contiguous[..], b"\x06\x00\x00\x01foobar");
+
+        let mut packet = Buf::chain(&b"foo"[..], &b"bar"[..]);
+        let mut chained = BytesMut::new();
+        assert_eq!(packet_to_chunks(1, &mut packet, &mut chained), seq_id);
+        assert_eq!(chained, contiguous);
+        assert!(!packet.has_remaining());
+    }
+
     const PLAIN: [u8; 46] = [
         0x03, 0x73, 0x65, 0x6c, 0x65, 0x63, 0x74, 0x20, 0x22, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35,
         0x36, 0x37, 0x38, 0x39, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x30,
@@ -779,6 +889,28 @@ mod tests {
         assert_eq!(&*dst, PLAIN);
     }
 
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn zstd_compressed_roundtrip() -> Result<(), error::PacketCodecError> {
+        let mut encoder = PacketCodec::default();
+        let mut decoder = PacketCodec::default();
+        let mut src = BytesMut::new();
+
+        encoder.compress_with(CompressionAlgorithm::Zstd(3));
+        decoder.compress_with(CompressionAlgorithm::Zstd(3));
+
+        for len in [0, 10, MIN_COMPRESS_LENGTH, 1024] {
+            encoder.encode(&mut &*vec![0x42_u8; len], &mut src)?;
+            let mut dst = vec![];
+            decoder.reset_seq_id();
+            encoder.reset_seq_id();
+            assert!(decoder.decode(&mut src, &mut dst)?);
+            assert_eq!(dst, vec![0x42; len]);
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn compression_none() {
         let mut encoder = PacketCodec::default();
@@ -0,0 +1,285 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Connection-level deserialization context.
+//!
+//! Every [`MyDeserialize`](super::MyDeserialize) implementation declares what it depends on
+//! via its `Ctx` associated type. [`DeserializeCtx`] holds everything that was negotiated
+//! for a connection, so that it could be threaded uniformly through the parsing code using
+//! [`ParseBuf::parse_in`](crate::io::ParseBuf::parse_in) — the parser takes only the part
+//! of the context it needs (see [`FromDeserializeCtx`]). MariaDB-specific parts of the context
+//! (progress reports in ERR packets, extended type info in column definitions) are only
+//! taken into account for the [`ServerDialect::MariaDb`] dialect.
+//!
+//! See [`ResultSetDecoder::with_ctx`](crate::row::result_set::ResultSetDecoder::with_ctx).
+
+use std::io;
+
+use crate::{
+    constants::{CapabilityFlags, MariadbCapabilities},
+    packets::HandshakePacket,
+};
+
+/// Default value of the [`MaxLengths::payload`] (default `max_allowed_packet` of MySql 8.0).
+pub const DEFAULT_MAX_PAYLOAD_LEN: usize = 64 * 1024 * 1024;
+
+/// Server flavor, some packet layouts depend on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerDialect {
+    MySql,
+    MariaDb,
+}
+
+impl Default for ServerDialect {
+    fn default() -> Self {
+        ServerDialect::MySql
+    }
+}
+
+/// Length limits applied during deserialization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaxLengths {
+    /// Maximum length of a (reassembled) payload in bytes.
+    pub payload: usize,
+}
+
+impl Default for MaxLengths {
+    fn default() -> Self {
+        Self {
+            payload: DEFAULT_MAX_PAYLOAD_LEN,
+        }
+    }
+}
+
+/// Payload is longer than [`MaxLengths::payload`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Payload of {len} bytes exceeds the limit of {max} bytes")]
+pub struct PayloadTooLongError {
+    pub len: usize,
+    pub max: usize,
+}
+
+/// Deserialization context negotiated for a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeserializeCtx {
+    /// Capabilities negotiated for the connection.
+    pub capabilities: CapabilityFlags,
+    /// Server flavor.
+    pub dialect: ServerDialect,
+    /// MariaDB extended capabilities negotiated for the connection.
+    pub mariadb_capabilities: MariadbCapabilities,
+    /// Length limits.
+    pub max_lengths: MaxLengths,
+}
+
+impl Default for DeserializeCtx {
+    fn default() -> Self {
+        Self::new(CapabilityFlags::empty())
+    }
+}
+
+impl DeserializeCtx {
+    /// Creates a context for the given capabilities (other fields are defaulted).
+    pub fn new(capabilities: CapabilityFlags) -> Self {
+        Self {
+            capabilities,
+            dialect: ServerDialect::default(),
+            mariadb_capabilities: MariadbCapabilities::empty(),
+            max_lengths: MaxLengths::default(),
+        }
+    }
+
+    /// Creates a context for a connection established with the given handshake packet.
+    ///
+    /// Capabilities are intersected with the `client_capabilities` and MariaDB extended
+    /// capabilities are intersected with the `client_mariadb_capabilities`.
+    pub fn from_handshake(
+        handshake: &HandshakePacket<'_>,
+        client_capabilities: CapabilityFlags,
+        client_mariadb_capabilities: MariadbCapabilities,
+    ) -> Self {
+        let dialect = if handshake.maria_db_server_version_parsed().is_some() {
+            ServerDialect::MariaDb
+        } else {
+            ServerDialect::MySql
+        };
+        Self::new(handshake.capabilities() & client_capabilities)
+            .with_dialect(dialect)
+            .with_mariadb_capabilities(
+                handshake.mariadb_capabilities() & client_mariadb_capabilities,
+            )
+    }
+
+    /// Defines the server dialect.
+    pub fn with_dialect(mut self, dialect: ServerDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Defines negotiated MariaDB extended capabilities.
+    pub fn with_mariadb_capabilities(mut self, mariadb_capabilities: MariadbCapabilities) -> Self {
+        self.mariadb_capabilities = mariadb_capabilities;
+        self
+    }
+
+    /// Defines length limits.
+    pub fn with_max_lengths(mut self, max_lengths: MaxLengths) -> Self {
+        self.max_lengths = max_lengths;
+        self
+    }
+
+    /// Returns an error if a payload of the given length exceeds [`MaxLengths::payload`].
+    pub fn check_payload_len(&self, len: usize) -> io::Result<()> {
+        if len > self.max_lengths.payload {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                PayloadTooLongError {
+                    len,
+                    max: self.max_lengths.payload,
+                },
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// `MyDeserialize::Ctx` types, that could be extracted from a [`DeserializeCtx`].
+pub trait FromDeserializeCtx {
+    fn from_deserialize_ctx(ctx: &DeserializeCtx) -> Self;
+}
+
+impl FromDeserializeCtx for () {
+    fn from_deserialize_ctx(_: &DeserializeCtx) -> Self {}
+}
+
+/// `MARIADB_CLIENT_PROGRESS` extended capability of a MariaDB server is reported
+/// as `CLIENT_PROGRESS_OBSOLETE` (as MariaDB server does), so that progress reports
+/// are recognized in error packets.
+impl FromDeserializeCtx for CapabilityFlags {
+    fn from_deserialize_ctx(ctx: &DeserializeCtx) -> Self {
+        let mut capabilities = ctx.capabilities;
+        if MariadbCapabilities::from_deserialize_ctx(ctx)
+            .contains(MariadbCapabilities::MARIADB_CLIENT_PROGRESS)
+        {
+            capabilities.insert(CapabilityFlags::CLIENT_PROGRESS_OBSOLETE);
+        }
+        capabilities
+    }
+}
+
+/// Always empty for the [`ServerDialect::MySql`] dialect.
+impl FromDeserializeCtx for MariadbCapabilities {
+    fn from_deserialize_ctx(ctx: &DeserializeCtx) -> Self {
+        match ctx.dialect {
+            ServerDialect::MariaDb => ctx.mariadb_capabilities,
+            ServerDialect::MySql => MariadbCapabilities::empty(),
+        }
+    }
+}
+
+impl FromDeserializeCtx for ServerDialect {
+    fn from_deserialize_ctx(ctx: &DeserializeCtx) -> Self {
+        ctx.dialect
+    }
+}
+
+impl FromDeserializeCtx for DeserializeCtx {
+    fn from_deserialize_ctx(ctx: &DeserializeCtx) -> Self {
+        *ctx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::StatusFlags,
+        io::ParseBuf,
+        packets::{CommonOkPacket, ErrPacket, OkPacketDeserializer, ResultSetTerminator},
+    };
+
+    #[test]
+    fn should_thread_deserialize_ctx() {
+        const HSP: &[u8] = b"\x0a5.5.5-10.0.17-MariaDB-log\x00\x0b\x00\
+                             \x00\x00\x64\x76\x48\x40\x49\x2d\x43\x4a\x00\xff\xf7\x08\x02\x00\
+                             \x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x2a\x34\x64\
+                             \x7c\x63\x5a\x77\x6b\x34\x5e\x5d\x3a\x00";
+        const PLAIN_OK: &[u8] = b"\x00\x01\x00\x02\x00\x00\x00";
+        const PROGRESS: &[u8] = b"\xff\xff\xff\x01\x01\x0a\xcc\x5b\x00\x0astage name";
+
+        let hsp: HandshakePacket = ParseBuf(HSP).parse(()).unwrap();
+        let ctx = DeserializeCtx::from_handshake(
+            &hsp,
+            CapabilityFlags::CLIENT_PROTOCOL_41,
+            MariadbCapabilities::all(),
+        );
+        assert_eq!(ctx.dialect, ServerDialect::MariaDb);
+        assert_eq!(ctx.capabilities, CapabilityFlags::CLIENT_PROTOCOL_41);
+        // `CLIENT_LONG_PASSWORD` is set, so there are no extended capabilities
+        assert_eq!(ctx.mariadb_capabilities, MariadbCapabilities::empty());
+
+        let ext_hsp = HandshakePacket::new(
+            10,
+            &b"5.5.5-10.6.5-MariaDB"[..],
+            1,
+            [0; 8],
+            None::<&[u8]>,
+            CapabilityFlags::CLIENT_PROTOCOL_41,
+            33,
+            StatusFlags::empty(),
+            None::<&[u8]>,
+        )
+        .with_mariadb_capabilities(
+            MariadbCapabilities::MARIADB_CLIENT_PROGRESS
+                | MariadbCapabilities::MARIADB_CLIENT_EXTENDED_TYPE_INFO,
+        );
+        let ext_ctx = DeserializeCtx::from_handshake(
+            &ext_hsp,
+            CapabilityFlags::CLIENT_PROTOCOL_41,
+            MariadbCapabilities::MARIADB_CLIENT_EXTENDED_TYPE_INFO
+                | MariadbCapabilities::MARIADB_CLIENT_STMT_BULK_OPERATIONS,
+        );
+        // only capabilities supported by both sides are negotiated
+        assert_eq!(
+            MariadbCapabilities::from_deserialize_ctx(&ext_ctx),
+            MariadbCapabilities::MARIADB_CLIENT_EXTENDED_TYPE_INFO
+        );
+
+        let ok: OkPacketDeserializer<CommonOkPacket> = ParseBuf(PLAIN_OK).parse_in(&ctx).unwrap();
+        assert_eq!(ok.into_inner().affected_rows(), 1);
+        ParseBuf(PLAIN_OK)
+            .parse_in::<OkPacketDeserializer<ResultSetTerminator>>(&ctx)
+            .unwrap_err();
+
+        // progress reports are only recognized if negotiated
+        let err: ErrPacket = ParseBuf(PROGRESS).parse_in(&ctx).unwrap();
+        assert!(err.is_error());
+        let ctx = DeserializeCtx::new(CapabilityFlags::CLIENT_PROGRESS_OBSOLETE);
+        let err: ErrPacket = ParseBuf(PROGRESS).parse_in(&ctx).unwrap();
+        assert!(err.is_progress_report());
+        let mariadb_ctx = DeserializeCtx::new(CapabilityFlags::CLIENT_PROTOCOL_41)
+            .with_dialect(ServerDialect::MariaDb)
+            .with_mariadb_capabilities(MariadbCapabilities::MARIADB_CLIENT_PROGRESS);
+        let err: ErrPacket = ParseBuf(PROGRESS).parse_in(&mariadb_ctx).unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Stage: 1 of 10 'stage name'  23.50% of stage done"
+        );
+        // MariaDB extended capabilities are ignored for MySql
+        let mysql_ctx = mariadb_ctx.with_dialect(ServerDialect::MySql);
+        let err: ErrPacket = ParseBuf(PROGRESS).parse_in(&mysql_ctx).unwrap();
+        assert!(err.is_error());
+
+        let ctx = ctx.with_max_lengths(MaxLengths { payload: 4 });
+        let err = ParseBuf(PLAIN_OK)
+            .parse_in::<OkPacketDeserializer<CommonOkPacket>>(&ctx)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -10,9 +10,14 @@ use std::io;
 
 use crate::io::ParseBuf;
 
+pub mod checksum;
 pub mod codec;
+pub mod ctx;
+pub mod stmt_registry;
 pub mod sync_framed;
 
+pub use self::ctx::{DeserializeCtx, FromDeserializeCtx, MaxLengths, ServerDialect};
+
 /// Text protocol marker.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Text;
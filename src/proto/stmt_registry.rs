@@ -0,0 +1,214 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Tracking of prepared statement ids of a connection.
+//!
+//! [`StmtIdRegistry`] catches driver bugs such as double `COM_STMT_CLOSE` or executing
+//! a closed statement. Checks are only performed if the `stmt_registry` feature is enabled,
+//! otherwise the registry is a zero-sized type and every check is a no-op, so drivers
+//! could use it unconditionally.
+
+#[cfg(feature = "stmt_registry")]
+use std::collections::HashSet;
+
+use crate::constants::Command;
+
+/// Misuse of a statement id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum StmtIdError {
+    /// The server returned an id of a statement, that is still open.
+    #[error("Statement {} is already prepared", _0)]
+    AlreadyPrepared(u32),
+    /// The statement was closed twice.
+    #[error("Statement {} is already closed", _0)]
+    DoubleClose(u32),
+    /// The statement was used after it was closed.
+    #[error("{:?} uses statement {}, that is already closed", command, stmt_id)]
+    UseAfterClose { stmt_id: u32, command: Command },
+    /// The statement was never prepared on this connection.
+    #[error("{:?} uses statement {}, that is not prepared", command, stmt_id)]
+    Unknown { stmt_id: u32, command: Command },
+}
+
+/// Tracks live statement ids of a connection (see the [module level docs](self)).
+///
+/// Note, that ids of closed statements are remembered until they are reused by the server.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StmtIdRegistry {
+    #[cfg(feature = "stmt_registry")]
+    live: HashSet<u32>,
+    #[cfg(feature = "stmt_registry")]
+    closed: HashSet<u32>,
+}
+
+impl StmtIdRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if checks are performed (i.e. the `stmt_registry` feature is enabled).
+    pub const fn is_enabled() -> bool {
+        cfg!(feature = "stmt_registry")
+    }
+
+    /// Returns the number of live statements.
+    #[cfg(feature = "stmt_registry")]
+    pub fn live(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Returns the number of live statements (always `0` because checks are disabled).
+    #[cfg(not(feature = "stmt_registry"))]
+    pub fn live(&self) -> usize {
+        0
+    }
+
+    /// Registers a statement id returned by the server in response to `COM_STMT_PREPARE`.
+    #[inline]
+    pub fn on_prepare(&mut self, stmt_id: u32) -> Result<(), StmtIdError> {
+        #[cfg(feature = "stmt_registry")]
+        {
+            if !self.live.insert(stmt_id) {
+                return Err(StmtIdError::AlreadyPrepared(stmt_id));
+            }
+            self.closed.remove(&stmt_id);
+        }
+        let _ = stmt_id;
+        Ok(())
+    }
+
+    /// Checks a statement id of an outgoing command.
+    ///
+    /// `COM_STMT_CLOSE` closes the statement, other commands require the statement to be live.
+    #[inline]
+    pub fn on_command(&mut self, command: Command, stmt_id: u32) -> Result<(), StmtIdError> {
+        #[cfg(feature = "stmt_registry")]
+        {
+            let is_live = if command == Command::COM_STMT_CLOSE {
+                self.live.remove(&stmt_id)
+            } else {
+                self.live.contains(&stmt_id)
+            };
+
+            if !is_live {
+                return Err(match (command, self.closed.contains(&stmt_id)) {
+                    (Command::COM_STMT_CLOSE, true) => StmtIdError::DoubleClose(stmt_id),
+                    (_, true) => StmtIdError::UseAfterClose { stmt_id, command },
+                    (_, false) => StmtIdError::Unknown { stmt_id, command },
+                });
+            }
+
+            if command == Command::COM_STMT_CLOSE {
+                self.closed.insert(stmt_id);
+            }
+        }
+        let _ = (command, stmt_id);
+        Ok(())
+    }
+
+    /// Inspects an outgoing command payload.
+    ///
+    /// Payloads of `COM_STMT_EXECUTE`, `COM_STMT_SEND_LONG_DATA`, `COM_STMT_CLOSE`,
+    /// `COM_STMT_RESET` and `COM_STMT_FETCH` are checked using [`StmtIdRegistry::on_command`],
+    /// `COM_RESET_CONNECTION` and `COM_CHANGE_USER` forget every statement
+    /// (see [`StmtIdRegistry::on_session_reset`]), other payloads are ignored.
+    #[inline]
+    pub fn on_payload(&mut self, payload: &[u8]) -> Result<(), StmtIdError> {
+        #[cfg(feature = "stmt_registry")]
+        {
+            const STMT_COMMANDS: [Command; 5] = [
+                Command::COM_STMT_EXECUTE,
+                Command::COM_STMT_SEND_LONG_DATA,
+                Command::COM_STMT_CLOSE,
+                Command::COM_STMT_RESET,
+                Command::COM_STMT_FETCH,
+            ];
+
+            let first = match payload.first() {
+                Some(first) => *first,
+                None => return Ok(()),
+            };
+
+            if first == Command::COM_RESET_CONNECTION as u8
+                || first == Command::COM_CHANGE_USER as u8
+            {
+                self.on_session_reset();
+            } else if let Some(command) = STMT_COMMANDS.iter().find(|x| **x as u8 == first) {
+                if let Some(id) = payload.get(1..5) {
+                    let stmt_id = u32::from_le_bytes([id[0], id[1], id[2], id[3]]);
+                    return self.on_command(*command, stmt_id);
+                }
+            }
+        }
+        let _ = payload;
+        Ok(())
+    }
+
+    /// Forgets every statement (statements are closed by the server
+    /// on `COM_RESET_CONNECTION` and `COM_CHANGE_USER`).
+    #[inline]
+    pub fn on_session_reset(&mut self) {
+        #[cfg(feature = "stmt_registry")]
+        {
+            self.live.clear();
+            self.closed.clear();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "stmt_registry"))]
+mod tests {
+    use super::*;
+    use crate::{packets::ComStmtClose, proto::MySerialize};
+
+    #[test]
+    fn should_track_stmt_ids() {
+        let mut registry = StmtIdRegistry::new();
+        assert!(StmtIdRegistry::is_enabled());
+
+        registry.on_prepare(1).unwrap();
+        registry.on_prepare(2).unwrap();
+        assert_eq!(registry.on_prepare(1), Err(StmtIdError::AlreadyPrepared(1)));
+        assert_eq!(registry.live(), 2);
+
+        registry
+            .on_payload(b"\x17\x01\x00\x00\x00\x00\x01\x00\x00\x00")
+            .unwrap();
+
+        let mut close = Vec::new();
+        ComStmtClose::new(1).serialize(&mut close);
+        registry.on_payload(&close).unwrap();
+        assert_eq!(
+            registry.on_payload(&close),
+            Err(StmtIdError::DoubleClose(1))
+        );
+        assert_eq!(
+            registry.on_command(Command::COM_STMT_EXECUTE, 1),
+            Err(StmtIdError::UseAfterClose {
+                stmt_id: 1,
+                command: Command::COM_STMT_EXECUTE
+            })
+        );
+        assert_eq!(
+            registry.on_command(Command::COM_STMT_RESET, 3),
+            Err(StmtIdError::Unknown {
+                stmt_id: 3,
+                command: Command::COM_STMT_RESET
+            })
+        );
+
+        // the server reuses the id
+        registry.on_prepare(1).unwrap();
+        registry.on_command(Command::COM_STMT_FETCH, 1).unwrap();
+
+        registry.on_payload(b"\x1f").unwrap();
+        assert_eq!(registry.live(), 0);
+        assert!(registry.on_command(Command::COM_STMT_CLOSE, 2).is_err());
+    }
+}
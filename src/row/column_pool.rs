@@ -0,0 +1,161 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Interning of result set metadata.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use crate::packets::Column;
+
+/// Default value of the [`ColumnPool::max_sets`].
+pub const DEFAULT_MAX_COLUMN_SETS: usize = 1024;
+
+/// Pool of interned column sets.
+///
+/// Result sets of the same statement share identical column definitions, so a driver
+/// could intern them, so that every result set of the statement holds the same
+/// `Arc<[Column]>` rather than a fresh copy (see [`ResultSetStream::with_column_pool`]).
+///
+/// The pool is thread-safe. It is cleared before adding a new column set if it already holds
+/// [`ColumnPool::max_sets`] distinct column sets.
+///
+/// [`ResultSetStream::with_column_pool`]: super::result_set::ResultSetStream::with_column_pool
+#[derive(Debug)]
+pub struct ColumnPool {
+    sets: Mutex<HashMap<u64, Vec<Arc<[Column]>>>>,
+    max_sets: usize,
+}
+
+impl Default for ColumnPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_COLUMN_SETS)
+    }
+}
+
+impl ColumnPool {
+    /// Creates a new pool, that holds at most `max_sets` column sets.
+    pub fn new(max_sets: usize) -> Self {
+        Self {
+            sets: Mutex::new(HashMap::new()),
+            max_sets,
+        }
+    }
+
+    /// Maximum number of column sets in the pool.
+    pub fn max_sets(&self) -> usize {
+        self.max_sets
+    }
+
+    /// Returns the number of column sets in the pool.
+    pub fn len(&self) -> usize {
+        self.lock().values().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all column sets from the pool.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the interned copy of the given columns.
+    pub fn intern(&self, columns: Vec<Column>) -> Arc<[Column]> {
+        self.intern_with(columns)
+    }
+
+    /// Returns the interned copy of the given columns (the given `Arc` is interned,
+    /// if there is no such column set in the pool).
+    pub fn intern_arc(&self, columns: Arc<[Column]>) -> Arc<[Column]> {
+        self.intern_with(columns)
+    }
+
+    fn intern_with<T>(&self, columns: T) -> Arc<[Column]>
+    where
+        T: AsRef<[Column]> + Into<Arc<[Column]>>,
+    {
+        let hash = hash_columns(columns.as_ref());
+        let mut sets = self.lock();
+
+        if let Some(interned) = sets
+            .get(&hash)
+            .and_then(|bucket| bucket.iter().find(|set| set[..] == *columns.as_ref()))
+        {
+            return interned.clone();
+        }
+
+        if sets.values().map(Vec::len).sum::<usize>() >= self.max_sets {
+            sets.clear();
+        }
+
+        let interned = columns.into();
+        sets.entry(hash).or_default().push(interned.clone());
+        interned
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Vec<Arc<[Column]>>>> {
+        // the map stays consistent even if a thread panicked while holding the lock
+        self.sets.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn hash_columns(columns: &[Column]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    columns.len().hash(&mut hasher);
+    for column in columns {
+        column.schema_ref().hash(&mut hasher);
+        column.table_ref().hash(&mut hasher);
+        column.org_table_ref().hash(&mut hasher);
+        column.name_ref().hash(&mut hasher);
+        column.org_name_ref().hash(&mut hasher);
+        (column.column_type() as u8).hash(&mut hasher);
+        column.flags().bits().hash(&mut hasher);
+        column.character_set().hash(&mut hasher);
+        column.column_length().hash(&mut hasher);
+        column.decimals().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::ColumnType;
+
+    fn columns(name: &[u8]) -> Vec<Column> {
+        vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(name),
+        ]
+    }
+
+    #[test]
+    fn should_intern_columns() {
+        let pool = ColumnPool::new(2);
+
+        let foo = pool.intern(columns(b"foo"));
+        assert!(Arc::ptr_eq(&foo, &pool.intern(columns(b"foo"))));
+        assert!(Arc::ptr_eq(&foo, &pool.intern_arc(columns(b"foo").into())));
+
+        let bar = pool.intern(columns(b"bar"));
+        assert!(!Arc::ptr_eq(&foo, &bar));
+        assert_eq!(pool.len(), 2);
+
+        // the pool is full, so it is cleared
+        let baz = pool.intern(columns(b"baz"));
+        assert_eq!(&*baz, &columns(b"baz")[..]);
+        assert_eq!(pool.len(), 1);
+        assert!(!Arc::ptr_eq(&foo, &pool.intern(columns(b"foo"))));
+    }
+}
@@ -0,0 +1,445 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Conversion of rows into maps keyed by column name and of two-column result sets
+//! into key-value maps.
+
+use std::{
+    any::type_name,
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash},
+};
+
+use super::{FromRow, FromRowColumnError, FromRowError};
+use crate::{
+    packets::Column,
+    row::{new_row_raw, Row},
+    value::convert::{ConvIr, FromValue, FromValueError},
+};
+
+/// Defines what to do if a row contains several columns with the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DuplicateColumns {
+    /// Conversion fails (this is what `FromRow` implementations do).
+    Error,
+    /// The leftmost column wins.
+    KeepFirst,
+    /// The rightmost column wins.
+    KeepLast,
+    /// Every column is keyed as `<table>.<name>` (or `<name>` if the table is unknown).
+    /// Conversion fails if qualified names are still ambiguous.
+    Qualify,
+}
+
+impl Default for DuplicateColumns {
+    fn default() -> Self {
+        DuplicateColumns::Error
+    }
+}
+
+impl DuplicateColumns {
+    fn key(self, column: &Column) -> String {
+        match self {
+            DuplicateColumns::Qualify if !column.table_ref().is_empty() => {
+                format!("{}.{}", column.table_str(), column.name_str())
+            }
+            _ => column.name_str().into_owned(),
+        }
+    }
+}
+
+/// Map, that a row could be converted to (see [`row_into_map`]).
+pub trait ColumnMap: Default {
+    /// Type of a value (every column is converted to this type).
+    type Value: FromValue;
+
+    /// Returns `true` if the map contains the given column.
+    fn contains_column(&self, name: &str) -> bool;
+    /// Inserts a value of a column replacing the previous one.
+    fn insert_column(&mut self, name: String, value: Self::Value);
+}
+
+impl<T: FromValue, S: BuildHasher + Default> ColumnMap for HashMap<String, T, S> {
+    type Value = T;
+
+    fn contains_column(&self, name: &str) -> bool {
+        self.contains_key(name)
+    }
+
+    fn insert_column(&mut self, name: String, value: T) {
+        self.insert(name, value);
+    }
+}
+
+impl<T: FromValue> ColumnMap for BTreeMap<String, T> {
+    type Value = T;
+
+    fn contains_column(&self, name: &str) -> bool {
+        self.contains_key(name)
+    }
+
+    fn insert_column(&mut self, name: String, value: T) {
+        self.insert(name, value);
+    }
+}
+
+/// Converts a row into a map keyed by column name using the given policy for duplicate names.
+///
+/// Every value is converted to `M::Value` (use `Option<T>` for nullable columns).
+/// Columns taken using `Row::take` are skipped. The row is returned intact
+/// (within the error) if the conversion fails.
+///
+/// ```
+/// # use std::{collections::BTreeMap, sync::Arc};
+/// # use mysql_common::{constants::ColumnType, packets::Column, row::{new_row, Row}};
+/// # use mysql_common::row::convert::map::{row_into_map, DuplicateColumns};
+/// # use mysql_common::value::Value;
+/// let columns = Arc::from(vec![
+///     Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id").with_table(b"a"),
+///     Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id").with_table(b"b"),
+/// ]);
+/// let row = new_row(vec![Value::Int(1), Value::Int(2)], columns);
+///
+/// let map: BTreeMap<String, Value> = row_into_map(row.clone(), DuplicateColumns::Qualify)?;
+/// assert_eq!(map["a.id"], Value::Int(1));
+/// assert_eq!(map["b.id"], Value::Int(2));
+///
+/// let map: BTreeMap<String, Value> = row_into_map(row.clone(), DuplicateColumns::KeepLast)?;
+/// assert_eq!(map["id"], Value::Int(2));
+///
+/// let map: BTreeMap<String, u8> = row_into_map(row.clone(), DuplicateColumns::KeepFirst)?;
+/// assert_eq!(map["id"], 1);
+///
+/// assert!(row_into_map::<BTreeMap<String, Value>>(row, DuplicateColumns::Error).is_err());
+/// # Ok::<(), mysql_common::row::convert::FromRowColumnError>(())
+/// ```
+pub fn row_into_map<M: ColumnMap>(
+    row: Row,
+    policy: DuplicateColumns,
+) -> Result<M, FromRowColumnError> {
+    let mut keys: Vec<Option<String>> = Vec::with_capacity(row.len());
+    let columns = row.columns();
+    for (index, column) in columns.iter().enumerate() {
+        if row.as_ref(index).is_none() {
+            keys.push(None);
+            continue;
+        }
+
+        let key = policy.key(column);
+        let seen = keys.iter().flatten().any(|x| *x == key);
+        match policy {
+            _ if !seen => keys.push(Some(key)),
+            DuplicateColumns::KeepFirst => keys.push(None),
+            DuplicateColumns::KeepLast => {
+                for x in keys.iter_mut() {
+                    if x.as_deref() == Some(&*key) {
+                        *x = None;
+                    }
+                }
+                keys.push(Some(key));
+            }
+            DuplicateColumns::Error | DuplicateColumns::Qualify => {
+                return Err(FromRowColumnError::new(row, Some(index), type_name::<M>()));
+            }
+        }
+    }
+
+    let mut values = row.unwrap_raw();
+    let mut irs = Vec::with_capacity(values.len());
+    for index in 0..values.len() {
+        let value = match (&keys[index], values[index].take()) {
+            (Some(_), Some(value)) => value,
+            (_, value) => {
+                values[index] = value;
+                irs.push(None);
+                continue;
+            }
+        };
+        match <M::Value as FromValue>::get_intermediate(value) {
+            Ok(ir) => irs.push(Some(ir)),
+            Err(FromValueError(value)) => {
+                values[index] = Some(value);
+                for (slot, ir) in values.iter_mut().zip(irs) {
+                    if let Some(ir) = ir {
+                        *slot = Some(ir.rollback());
+                    }
+                }
+                let row = new_row_raw(values, columns);
+                return Err(FromRowColumnError::new(
+                    row,
+                    Some(index),
+                    type_name::<M::Value>(),
+                ));
+            }
+        }
+    }
+
+    let mut map = M::default();
+    for (key, ir) in keys.into_iter().zip(irs) {
+        if let (Some(key), Some(ir)) = (key, ir) {
+            debug_assert!(!map.contains_column(&key));
+            map.insert_column(key, ir.commit());
+        }
+    }
+
+    Ok(map)
+}
+
+/// Fails on duplicate column names (see [`row_into_map`] for other options)
+/// or if any of the values couldn't be converted to `T`.
+impl<T: FromValue, S: BuildHasher + Default> FromRow for HashMap<String, T, S> {
+    fn from_row_opt(row: Row) -> Result<Self, FromRowError> {
+        Self::from_row_checked(row).map_err(Into::into)
+    }
+
+    fn from_row_checked(row: Row) -> Result<Self, FromRowColumnError> {
+        row_into_map(row, DuplicateColumns::Error)
+    }
+}
+
+/// Fails on duplicate column names (see [`row_into_map`] for other options)
+/// or if any of the values couldn't be converted to `T`.
+impl<T: FromValue> FromRow for BTreeMap<String, T> {
+    fn from_row_opt(row: Row) -> Result<Self, FromRowError> {
+        Self::from_row_checked(row).map_err(Into::into)
+    }
+
+    fn from_row_checked(row: Row) -> Result<Self, FromRowColumnError> {
+        row_into_map(row, DuplicateColumns::Error)
+    }
+}
+
+/// Defines what to do if several rows have the same key (see [`collect_pairs`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DuplicateKeys {
+    /// Collection fails.
+    Error,
+    /// The first row wins.
+    KeepFirst,
+    /// The last row wins.
+    KeepLast,
+}
+
+impl Default for DuplicateKeys {
+    fn default() -> Self {
+        DuplicateKeys::Error
+    }
+}
+
+/// Error of [`collect_pairs`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum CollectPairsError {
+    /// A row couldn't be converted to a key-value pair.
+    #[error("Couldn't convert row {index}: {error}")]
+    Row {
+        /// Index of the row.
+        index: usize,
+        error: FromRowColumnError,
+    },
+    /// Key of a row is already in the map (only for [`DuplicateKeys::Error`]).
+    #[error("Row {index} has a duplicate key")]
+    DuplicateKey {
+        /// Index of the row.
+        index: usize,
+    },
+}
+
+/// Map, that a two-column result set could be collected to (see [`collect_pairs`]).
+pub trait PairMap<K, V>: Default {
+    /// Returns `true` if the map contains the given key.
+    fn contains_pair_key(&self, key: &K) -> bool;
+    /// Inserts a pair replacing the previous value.
+    fn insert_pair(&mut self, key: K, value: V);
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> PairMap<K, V> for HashMap<K, V, S> {
+    fn contains_pair_key(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+
+    fn insert_pair(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+}
+
+impl<K: Ord, V> PairMap<K, V> for BTreeMap<K, V> {
+    fn contains_pair_key(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+
+    fn insert_pair(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+}
+
+/// Collects two-column rows into a map (the first column is a key, the second is a value).
+///
+/// ```
+/// # use std::{collections::HashMap, sync::Arc};
+/// # use mysql_common::{constants::ColumnType, packets::Column, row::new_row, value::Value};
+/// # use mysql_common::row::convert::map::{collect_pairs, DuplicateKeys};
+/// let columns: Arc<[Column]> = Arc::from(vec![
+///     Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+///     Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"name"),
+/// ]);
+/// let rows = vec![
+///     new_row(vec![Value::Int(1), Value::from("foo")], columns.clone()),
+///     new_row(vec![Value::Int(2), Value::from("bar")], columns.clone()),
+/// ];
+///
+/// let map: HashMap<u32, String> = collect_pairs(rows, DuplicateKeys::Error).unwrap();
+/// assert_eq!(map[&2], "bar");
+/// ```
+pub fn collect_pairs<M, K, V, I>(rows: I, policy: DuplicateKeys) -> Result<M, CollectPairsError>
+where
+    M: PairMap<K, V>,
+    K: FromValue,
+    V: FromValue,
+    I: IntoIterator<Item = Row>,
+{
+    let mut map = M::default();
+    for (index, row) in rows.into_iter().enumerate() {
+        let (key, value) = <(K, V)>::from_row_checked(row)
+            .map_err(|error| CollectPairsError::Row { index, error })?;
+        if map.contains_pair_key(&key) {
+            match policy {
+                DuplicateKeys::Error => return Err(CollectPairsError::DuplicateKey { index }),
+                DuplicateKeys::KeepFirst => continue,
+                DuplicateKeys::KeepLast => (),
+            }
+        }
+        map.insert_pair(key, value);
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        constants::ColumnType,
+        row::{convert::from_row_checked, new_row},
+        value::Value,
+    };
+
+    fn row() -> Row {
+        new_row(
+            vec![
+                Value::Int(1),
+                Value::Bytes(b"foo".to_vec()),
+                Value::Int(2),
+                Value::NULL,
+            ],
+            Arc::from(vec![
+                Column::new(ColumnType::MYSQL_TYPE_LONG)
+                    .with_name(b"id")
+                    .with_table(b"a"),
+                Column::new(ColumnType::MYSQL_TYPE_VARCHAR)
+                    .with_name(b"name")
+                    .with_table(b"a"),
+                Column::new(ColumnType::MYSQL_TYPE_LONG)
+                    .with_name(b"id")
+                    .with_table(b"b"),
+                Column::new(ColumnType::MYSQL_TYPE_NULL).with_name(b"extra"),
+            ]),
+        )
+    }
+
+    #[test]
+    fn should_convert_row_into_map() {
+        let err = from_row_checked::<HashMap<String, Value>>(row()).unwrap_err();
+        assert_eq!(err.index(), Some(2));
+        assert_eq!(err.into_row(), row());
+
+        let map: BTreeMap<String, Value> =
+            row_into_map(row(), DuplicateColumns::KeepFirst).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["id"], Value::Int(1));
+        assert_eq!(map["name"], Value::Bytes(b"foo".to_vec()));
+        assert_eq!(map["extra"], Value::NULL);
+
+        let map: HashMap<String, Value> = row_into_map(row(), DuplicateColumns::KeepLast).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["id"], Value::Int(2));
+
+        let map: HashMap<String, Value> = row_into_map(row(), DuplicateColumns::Qualify).unwrap();
+        assert_eq!(map.len(), 4);
+        assert_eq!(map["a.id"], Value::Int(1));
+        assert_eq!(map["b.id"], Value::Int(2));
+        assert_eq!(map["extra"], Value::NULL);
+
+        // taken columns are skipped
+        let mut row = row();
+        let _: Option<i64> = row.take(2);
+        let map: BTreeMap<String, Value> = from_row_checked(row).unwrap();
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["extra", "id", "name"]);
+        assert_eq!(map["id"], Value::Int(1));
+    }
+
+    #[test]
+    fn should_convert_row_into_typed_map() {
+        let columns: Arc<[Column]> = Arc::from(vec![
+            Column::new(ColumnType::MYSQL_TYPE_DOUBLE).with_name(b"jan"),
+            Column::new(ColumnType::MYSQL_TYPE_DOUBLE).with_name(b"feb"),
+            Column::new(ColumnType::MYSQL_TYPE_DOUBLE).with_name(b"mar"),
+        ]);
+        let row = new_row(
+            vec![Value::Double(1.5), Value::from("2"), Value::NULL],
+            columns,
+        );
+
+        let map: BTreeMap<String, Option<f64>> = from_row_checked(row.clone()).unwrap();
+        assert_eq!(map["jan"], Some(1.5));
+        assert_eq!(map["feb"], Some(2.0));
+        assert_eq!(map["mar"], None);
+
+        // the row is returned intact
+        let err = from_row_checked::<HashMap<String, f64>>(row.clone()).unwrap_err();
+        assert_eq!(err.index(), Some(2));
+        assert_eq!(err.target_type(), "f64");
+        assert_eq!(err.into_row(), row);
+    }
+
+    #[test]
+    fn should_collect_pairs() {
+        let columns: Arc<[Column]> = Arc::from(vec![
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"key"),
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"value"),
+        ]);
+        let rows = || {
+            vec![
+                new_row(vec![Value::from("a"), Value::Int(1)], columns.clone()),
+                new_row(vec![Value::from("b"), Value::Int(2)], columns.clone()),
+                new_row(vec![Value::from("a"), Value::Int(3)], columns.clone()),
+            ]
+        };
+
+        assert_eq!(
+            collect_pairs::<BTreeMap<String, u8>, _, _, _>(rows(), DuplicateKeys::Error),
+            Err(CollectPairsError::DuplicateKey { index: 2 })
+        );
+        let map: BTreeMap<String, u8> = collect_pairs(rows(), DuplicateKeys::KeepFirst).unwrap();
+        assert_eq!(map["a"], 1);
+        let map: HashMap<String, u8> = collect_pairs(rows(), DuplicateKeys::KeepLast).unwrap();
+        assert_eq!(map["a"], 3);
+        assert_eq!(map.len(), 2);
+
+        let err =
+            collect_pairs::<HashMap<String, String>, _, _, _>(rows(), DuplicateKeys::KeepLast)
+                .unwrap_err();
+        match err {
+            CollectPairsError::Row { index, error } => {
+                assert_eq!(index, 0);
+                assert_eq!(error.index(), Some(1));
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+    }
+}
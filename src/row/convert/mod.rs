@@ -7,13 +7,23 @@
 // modified, or distributed except according to those terms.
 
 use crate::{
+    constants::ColumnType,
+    packets::Column,
     row::Row,
-    value::convert::{ConvIr, FromValue, FromValueError},
+    value::{
+        convert::{ConvIr, FromValue, FromValueError},
+        Value,
+    },
 };
 
 use std::{any::type_name, error::Error, fmt};
 
 pub mod frunk;
+pub mod map;
+mod structs;
+
+#[doc(hidden)]
+pub use self::structs::{restore_prefixed, take_prefixed};
 
 /// `FromRow` conversion error.
 #[derive(Debug, Clone, PartialEq)]
@@ -35,6 +45,111 @@ impl Error for FromRowError {
     }
 }
 
+/// `FromRow` conversion error, that carries the context of a failed column.
+///
+/// ```ignore
+/// // Couldn't convert column `created_at` (index 3, MYSQL_TYPE_DATETIME) to chrono::NaiveTime ..
+/// println!("{}", from_row_checked::<(u64, String, u8, NaiveTime)>(row).unwrap_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromRowColumnError {
+    row: Row,
+    index: Option<usize>,
+    target_type: &'static str,
+}
+
+impl FromRowColumnError {
+    /// Creates a new error.
+    ///
+    /// `index` is the index of the failed column (`None` if the row as a whole
+    /// doesn't match the target type). `target_type` is the name of the target type.
+    pub fn new(row: Row, index: Option<usize>, target_type: &'static str) -> Self {
+        Self {
+            row,
+            index,
+            target_type,
+        }
+    }
+
+    /// Returns the index of the failed column (`None` if the row as a whole
+    /// doesn't match the target type, e.g. because of the number of columns).
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Returns the definition of the failed column (if any).
+    pub fn column(&self) -> Option<&Column> {
+        self.index.and_then(|i| self.row.columns_ref().get(i))
+    }
+
+    /// Returns the name of the failed column (if any).
+    pub fn column_name(&self) -> Option<std::borrow::Cow<'_, str>> {
+        self.column().map(Column::name_str)
+    }
+
+    /// Returns the type of the failed column (if any).
+    pub fn column_type(&self) -> Option<ColumnType> {
+        self.column().map(Column::column_type)
+    }
+
+    /// Returns the value of the failed column (`None` if there is no such column
+    /// or if it was taken from the row).
+    pub fn value(&self) -> Option<&Value> {
+        self.index.and_then(|i| self.row.as_ref(i))
+    }
+
+    /// Returns the name of the type the conversion was performed to.
+    ///
+    /// It is the type of the column for a column error and the type of the row otherwise.
+    pub fn target_type(&self) -> &'static str {
+        self.target_type
+    }
+
+    /// Returns the original row.
+    pub fn row(&self) -> &Row {
+        &self.row
+    }
+
+    /// Returns the original row.
+    pub fn into_row(self) -> Row {
+        self.row
+    }
+}
+
+impl fmt::Display for FromRowColumnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.index, self.column()) {
+            (Some(index), Some(column)) => write!(
+                f,
+                "Couldn't convert column `{}` (index {}, {:?}) to {}: {:?}",
+                column.name_str(),
+                index,
+                column.column_type(),
+                self.target_type,
+                self.value(),
+            ),
+            (Some(index), None) => write!(
+                f,
+                "Couldn't convert column {} to {}: no such column",
+                index, self.target_type,
+            ),
+            (None, _) => write!(
+                f,
+                "Couldn't convert the row `{:?}` to {}",
+                self.row, self.target_type
+            ),
+        }
+    }
+}
+
+impl Error for FromRowColumnError {}
+
+impl From<FromRowColumnError> for FromRowError {
+    fn from(x: FromRowColumnError) -> Self {
+        FromRowError(x.row)
+    }
+}
+
 /// Will *panic* if could not convert `row` to `T`.
 pub fn from_row<T: FromRow>(row: Row) -> T {
     FromRow::from_row(row)
@@ -45,7 +160,12 @@ pub fn from_row_opt<T: FromRow>(row: Row) -> Result<T, FromRowError> {
     FromRow::from_row_opt(row)
 }
 
-/// Trait to convert `Row` into a tuple of `FromValue` implementors up to arity 12.
+/// Will return an error with the context of a failed column if could not convert `row` to `T`.
+pub fn from_row_checked<T: FromRow>(row: Row) -> Result<T, FromRowColumnError> {
+    FromRow::from_row_checked(row)
+}
+
+/// Trait to convert `Row` into a tuple of `FromValue` implementors up to arity 32.
 ///
 /// This trait is convenient way to convert mysql row to a tuple or rust types and relies on
 /// `FromValue` trait, i.e. calling `from_row::<(T, U)>(row)` is similar to calling
@@ -76,19 +196,25 @@ pub trait FromRow {
     where
         Self: Sized,
     {
-        match Self::from_row_opt(row) {
+        match Self::from_row_checked(row) {
             Ok(x) => x,
-            Err(FromRowError(row)) => panic!(
-                "Couldn't convert {:?} to type {}. (see FromRow documentation)",
-                row,
-                type_name::<Self>(),
-            ),
+            Err(err) => panic!("{}. (see FromRow documentation)", err),
         }
     }
 
     fn from_row_opt(row: Row) -> Result<Self, FromRowError>
     where
         Self: Sized;
+
+    /// Same as `from_row_opt`, but the error will carry the context of a failed column
+    /// (if the implementation is able to provide it).
+    fn from_row_checked(row: Row) -> Result<Self, FromRowColumnError>
+    where
+        Self: Sized,
+    {
+        Self::from_row_opt(row)
+            .map_err(|FromRowError(row)| FromRowColumnError::new(row, None, type_name::<Self>()))
+    }
 }
 
 macro_rules! take_or_place {
@@ -99,11 +225,11 @@ macro_rules! take_or_place {
                     Ok(ir) => ir,
                     Err(FromValueError(value)) => {
                         $row.place($index, value);
-                        return Err(FromRowError($row));
+                        return Err(FromRowColumnError::new($row, Some($index), type_name::<$t>()));
                     },
                 }
             },
-            None => return Err(FromRowError($row)),
+            None => return Err(FromRowColumnError::new($row, Some($index), type_name::<$t>())),
         }
     );
     ($row:expr, $index:expr, $t:ident, $( [$idx:expr, $ir:expr] ),*) => (
@@ -114,11 +240,14 @@ macro_rules! take_or_place {
                     Err(FromValueError(value)) => {
                         $($row.place($idx, $ir.rollback());)*
                         $row.place($index, value);
-                        return Err(FromRowError($row));
+                        return Err(FromRowColumnError::new($row, Some($index), type_name::<$t>()));
                     },
                 }
             },
-            None => return Err(FromRowError($row)),
+            None => {
+                $($row.place($idx, $ir.rollback());)*
+                return Err(FromRowColumnError::new($row, Some($index), type_name::<$t>()));
+            }
         }
     );
 }
@@ -140,11 +269,15 @@ impl<T> FromRow for T
 where
     T: FromValue,
 {
-    fn from_row_opt(mut row: Row) -> Result<T, FromRowError> {
+    fn from_row_opt(row: Row) -> Result<T, FromRowError> {
+        Self::from_row_checked(row).map_err(Into::into)
+    }
+
+    fn from_row_checked(mut row: Row) -> Result<T, FromRowColumnError> {
         if row.len() == 1 {
             Ok(take_or_place!(row, 0, T).commit())
         } else {
-            Err(FromRowError(row))
+            Err(FromRowColumnError::new(row, None, type_name::<T>()))
         }
     }
 }
@@ -156,609 +289,275 @@ where
     fn from_row_opt(row: Row) -> Result<(T1,), FromRowError> {
         T1::from_row_opt(row).map(|t| (t,))
     }
-}
 
-impl<T1, T2> FromRow for (T1, T2)
-where
-    T1: FromValue,
-    T2: FromValue,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2), FromRowError> {
-        if row.len() != 2 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        Ok((ir1.commit(), ir2.commit()))
+    fn from_row_checked(row: Row) -> Result<(T1,), FromRowColumnError> {
+        T1::from_row_checked(row).map(|t| (t,))
     }
 }
 
-impl<T1, T2, T3> FromRow for (T1, T2, T3)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3), FromRowError> {
-        if row.len() != 3 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        Ok((ir1.commit(), ir2.commit(), ir3.commit()))
-    }
+/// Takes intermediate values one by one, rolling back the already taken ones on error.
+macro_rules! take_tuple_irs {
+    ($row:ident, [$($prev_idx:tt $prev_ir:ident)*],) => {};
+    ($row:ident, [$($prev_idx:tt $prev_ir:ident)*], $t:ident $idx:tt $ir:ident, $($rest:tt)*) => {
+        let $ir = take_or_place!($row, $idx, $t, $([$prev_idx, $prev_ir]),*);
+        take_tuple_irs!($row, [$($prev_idx $prev_ir)* $idx $ir], $($rest)*);
+    };
 }
 
-impl<T1, T2, T3, T4> FromRow for (T1, T2, T3, T4)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4), FromRowError> {
-        if row.len() != 4 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        Ok((ir1.commit(), ir2.commit(), ir3.commit(), ir4.commit()))
-    }
-}
+macro_rules! impl_from_row_for_tuple {
+    ($arity:literal: $($t:ident $idx:tt $ir:ident),+) => {
+        impl<$($t,)+> FromRow for ($($t,)+)
+        where
+            $($t: FromValue,)+
+        {
+            fn from_row_opt(row: Row) -> Result<($($t,)+), FromRowError> {
+                Self::from_row_checked(row).map_err(Into::into)
+            }
 
-impl<T1, T2, T3, T4, T5> FromRow for (T1, T2, T3, T4, T5)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5), FromRowError> {
-        if row.len() != 5 {
-            return Err(FromRowError(row));
+            fn from_row_checked(mut row: Row) -> Result<($($t,)+), FromRowColumnError> {
+                if row.len() != $arity {
+                    return Err(FromRowColumnError::new(row, None, type_name::<Self>()));
+                }
+                take_tuple_irs!(row, [], $($t $idx $ir,)+);
+                Ok(($($ir.commit(),)+))
+            }
         }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        Ok((
-            ir1.commit(),
-            ir2.commit(),
-            ir3.commit(),
-            ir4.commit(),
-            ir5.commit(),
-        ))
-    }
+    };
 }
 
-impl<T1, T2, T3, T4, T5, T6> FromRow for (T1, T2, T3, T4, T5, T6)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5, T6), FromRowError> {
-        if row.len() != 6 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        Ok((
-            ir1.commit(),
-            ir2.commit(),
-            ir3.commit(),
-            ir4.commit(),
-            ir5.commit(),
-            ir6.commit(),
-        ))
-    }
-}
+impl_from_row_for_tuple!(2: T1 0 ir1, T2 1 ir2);
+impl_from_row_for_tuple!(3: T1 0 ir1, T2 1 ir2, T3 2 ir3);
+impl_from_row_for_tuple!(4: T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4);
+impl_from_row_for_tuple!(5: T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5);
+impl_from_row_for_tuple!(6: T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6);
+impl_from_row_for_tuple!(7: T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7);
+impl_from_row_for_tuple!(
+    8:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8
+);
+impl_from_row_for_tuple!(
+    9:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9
+);
+impl_from_row_for_tuple!(
+    10:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10
+);
+impl_from_row_for_tuple!(
+    11:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11
+);
+impl_from_row_for_tuple!(
+    12:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12
+);
+impl_from_row_for_tuple!(
+    13:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13
+);
+impl_from_row_for_tuple!(
+    14:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14
+);
+impl_from_row_for_tuple!(
+    15:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15
+);
+impl_from_row_for_tuple!(
+    16:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16
+);
+impl_from_row_for_tuple!(
+    17:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17
+);
+impl_from_row_for_tuple!(
+    18:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18
+);
+impl_from_row_for_tuple!(
+    19:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19
+);
+impl_from_row_for_tuple!(
+    20:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20
+);
+impl_from_row_for_tuple!(
+    21:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21
+);
+impl_from_row_for_tuple!(
+    22:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22
+);
+impl_from_row_for_tuple!(
+    23:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23
+);
+impl_from_row_for_tuple!(
+    24:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23,
+    T24 23 ir24
+);
+impl_from_row_for_tuple!(
+    25:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23,
+    T24 23 ir24, T25 24 ir25
+);
+impl_from_row_for_tuple!(
+    26:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23,
+    T24 23 ir24, T25 24 ir25, T26 25 ir26
+);
+impl_from_row_for_tuple!(
+    27:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23,
+    T24 23 ir24, T25 24 ir25, T26 25 ir26, T27 26 ir27
+);
+impl_from_row_for_tuple!(
+    28:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23,
+    T24 23 ir24, T25 24 ir25, T26 25 ir26, T27 26 ir27, T28 27 ir28
+);
+impl_from_row_for_tuple!(
+    29:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23,
+    T24 23 ir24, T25 24 ir25, T26 25 ir26, T27 26 ir27, T28 27 ir28, T29 28 ir29
+);
+impl_from_row_for_tuple!(
+    30:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23,
+    T24 23 ir24, T25 24 ir25, T26 25 ir26, T27 26 ir27, T28 27 ir28, T29 28 ir29, T30 29 ir30
+);
+impl_from_row_for_tuple!(
+    31:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23,
+    T24 23 ir24, T25 24 ir25, T26 25 ir26, T27 26 ir27, T28 27 ir28, T29 28 ir29, T30 29 ir30,
+    T31 30 ir31
+);
+impl_from_row_for_tuple!(
+    32:
+    T1 0 ir1, T2 1 ir2, T3 2 ir3, T4 3 ir4, T5 4 ir5, T6 5 ir6, T7 6 ir7, T8 7 ir8, T9 8 ir9,
+    T10 9 ir10, T11 10 ir11, T12 11 ir12, T13 12 ir13, T14 13 ir14, T15 14 ir15, T16 15 ir16,
+    T17 16 ir17, T18 17 ir18, T19 18 ir19, T20 19 ir20, T21 20 ir21, T22 21 ir22, T23 22 ir23,
+    T24 23 ir24, T25 24 ir25, T26 25 ir26, T27 26 ir27, T28 27 ir28, T29 28 ir29, T30 29 ir30,
+    T31 30 ir31, T32 31 ir32
+);
 
-impl<T1, T2, T3, T4, T5, T6, T7> FromRow for (T1, T2, T3, T4, T5, T6, T7)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5, T6, T7), FromRowError> {
-        if row.len() != 7 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        Ok((
-            ir1.commit(),
-            ir2.commit(),
-            ir3.commit(),
-            ir4.commit(),
-            ir5.commit(),
-            ir6.commit(),
-            ir7.commit(),
-        ))
-    }
-}
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8> FromRow for (T1, T2, T3, T4, T5, T6, T7, T8)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8), FromRowError> {
-        if row.len() != 8 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
-        );
-        Ok((
-            ir1.commit(),
-            ir2.commit(),
-            ir3.commit(),
-            ir4.commit(),
-            ir5.commit(),
-            ir6.commit(),
-            ir7.commit(),
-            ir8.commit(),
-        ))
-    }
-}
+    use super::*;
+    use crate::row::new_row;
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8, T9> FromRow for (T1, T2, T3, T4, T5, T6, T7, T8, T9)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-    T9: FromValue,
-{
-    fn from_row_opt(mut row: Row) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8, T9), FromRowError> {
-        if row.len() != 9 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
-        );
-        let ir9 = take_or_place!(
-            row,
-            8,
-            T9,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8]
-        );
-        Ok((
-            ir1.commit(),
-            ir2.commit(),
-            ir3.commit(),
-            ir4.commit(),
-            ir5.commit(),
-            ir6.commit(),
-            ir7.commit(),
-            ir8.commit(),
-            ir9.commit(),
-        ))
+    fn row() -> Row {
+        new_row(
+            vec![Value::Int(1), Value::Bytes(b"foo".to_vec())],
+            Arc::from(vec![
+                Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+                Column::new(ColumnType::MYSQL_TYPE_DATETIME).with_name(b"created_at"),
+            ]),
+        )
     }
-}
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10> FromRow for (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-    T9: FromValue,
-    T10: FromValue,
-{
-    fn from_row_opt(
-        mut row: Row,
-    ) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10), FromRowError> {
-        if row.len() != 10 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
+    #[test]
+    fn should_carry_column_context() {
+        let err = from_row_checked::<(u8, u8)>(row()).unwrap_err();
+        assert_eq!(err.index(), Some(1));
+        assert_eq!(err.column_name().as_deref(), Some("created_at"));
+        assert_eq!(err.column_type(), Some(ColumnType::MYSQL_TYPE_DATETIME));
+        assert_eq!(err.value(), Some(&Value::Bytes(b"foo".to_vec())));
+        assert_eq!(err.target_type(), "u8");
+        assert_eq!(
+            err.to_string(),
+            "Couldn't convert column `created_at` (index 1, MYSQL_TYPE_DATETIME) to u8: \
+             Some(Bytes(\"foo\"))"
         );
-        let ir9 = take_or_place!(
-            row,
-            8,
-            T9,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8]
-        );
-        let ir10 = take_or_place!(
-            row,
-            9,
-            T10,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9]
-        );
-        Ok((
-            ir1.commit(),
-            ir2.commit(),
-            ir3.commit(),
-            ir4.commit(),
-            ir5.commit(),
-            ir6.commit(),
-            ir7.commit(),
-            ir8.commit(),
-            ir9.commit(),
-            ir10.commit(),
-        ))
-    }
-}
+        // the row is rolled back
+        assert_eq!(err.into_row(), row());
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11> FromRow
-    for (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-    T9: FromValue,
-    T10: FromValue,
-    T11: FromValue,
-{
-    fn from_row_opt(
-        mut row: Row,
-    ) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11), FromRowError> {
-        if row.len() != 11 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
-        );
-        let ir9 = take_or_place!(
-            row,
-            8,
-            T9,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8]
-        );
-        let ir10 = take_or_place!(
-            row,
-            9,
-            T10,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9]
-        );
-        let ir11 = take_or_place!(
-            row,
-            10,
-            T11,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9],
-            [9, ir10]
+        let err = from_row_checked::<(u8,)>(row()).unwrap_err();
+        assert_eq!(err.index(), None);
+        assert_eq!(err.column(), None);
+
+        let err: FromRowError = from_row_checked::<(u8, u8)>(row()).unwrap_err().into();
+        assert_eq!(err, FromRowError(row()));
+        assert_eq!(
+            from_row_opt::<(u8, String)>(row()).unwrap(),
+            (1, "foo".into())
         );
-        Ok((
-            ir1.commit(),
-            ir2.commit(),
-            ir3.commit(),
-            ir4.commit(),
-            ir5.commit(),
-            ir6.commit(),
-            ir7.commit(),
-            ir8.commit(),
-            ir9.commit(),
-            ir10.commit(),
-            ir11.commit(),
-        ))
     }
-}
 
-impl<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12> FromRow
-    for (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12)
-where
-    T1: FromValue,
-    T2: FromValue,
-    T3: FromValue,
-    T4: FromValue,
-    T5: FromValue,
-    T6: FromValue,
-    T7: FromValue,
-    T8: FromValue,
-    T9: FromValue,
-    T10: FromValue,
-    T11: FromValue,
-    T12: FromValue,
-{
-    fn from_row_opt(
-        mut row: Row,
-    ) -> Result<(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12), FromRowError> {
-        if row.len() != 12 {
-            return Err(FromRowError(row));
-        }
-        let ir1 = take_or_place!(row, 0, T1);
-        let ir2 = take_or_place!(row, 1, T2, [0, ir1]);
-        let ir3 = take_or_place!(row, 2, T3, [0, ir1], [1, ir2]);
-        let ir4 = take_or_place!(row, 3, T4, [0, ir1], [1, ir2], [2, ir3]);
-        let ir5 = take_or_place!(row, 4, T5, [0, ir1], [1, ir2], [2, ir3], [3, ir4]);
-        let ir6 = take_or_place!(row, 5, T6, [0, ir1], [1, ir2], [2, ir3], [3, ir4], [4, ir5]);
-        let ir7 = take_or_place!(
-            row,
-            6,
-            T7,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6]
-        );
-        let ir8 = take_or_place!(
-            row,
-            7,
-            T8,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7]
-        );
-        let ir9 = take_or_place!(
-            row,
-            8,
-            T9,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8]
-        );
-        let ir10 = take_or_place!(
-            row,
-            9,
-            T10,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9]
-        );
-        let ir11 = take_or_place!(
-            row,
-            10,
-            T11,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9],
-            [9, ir10]
-        );
-        let ir12 = take_or_place!(
-            row,
-            11,
-            T12,
-            [0, ir1],
-            [1, ir2],
-            [2, ir3],
-            [3, ir4],
-            [4, ir5],
-            [5, ir6],
-            [6, ir7],
-            [7, ir8],
-            [8, ir9],
-            [9, ir10],
-            [10, ir11]
+    #[test]
+    fn should_convert_wide_tuples() {
+        let wide_row = |len: usize| {
+            new_row(
+                (0..len).map(|i| Value::Int(i as i64)).collect(),
+                (0..len)
+                    .map(|_| Column::new(ColumnType::MYSQL_TYPE_LONG))
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+        };
+
+        // tuples of arity > 12 don't implement `Debug`, so `unwrap_err` is not available
+        #[rustfmt::skip]
+        type Wide = (
+            u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8,
+            u8, u8, u8, u8, u8, u8, u8, u8, u8, u16,
         );
-        Ok((
-            ir1.commit(),
-            ir2.commit(),
-            ir3.commit(),
-            ir4.commit(),
-            ir5.commit(),
-            ir6.commit(),
-            ir7.commit(),
-            ir8.commit(),
-            ir9.commit(),
-            ir10.commit(),
-            ir11.commit(),
-            ir12.commit(),
-        ))
+        let wide: Wide = from_row(wide_row(32));
+        assert_eq!(wide.0, 0);
+        assert_eq!(wide.12, 12);
+        assert_eq!(wide.31, 31);
+
+        let err = from_row_checked::<Wide>(wide_row(31)).err().unwrap();
+        assert_eq!(err.index(), None);
+
+        let mut row = wide_row(32);
+        row.place(31, Value::Int(-1));
+        let err = from_row_checked::<Wide>(row.clone()).err().unwrap();
+        assert_eq!(err.index(), Some(31));
+        assert_eq!(err.into_row(), row);
     }
 }
 
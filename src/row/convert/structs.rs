@@ -0,0 +1,501 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module implements `FromRow` for structs with named fields
+//! (see [`from_row_struct!`](crate::from_row_struct)).
+
+use crate::row::{new_row, Row};
+
+/// Defines a struct with named fields and implements `FromRow` for it.
+///
+/// Every field is taken from a column with the same name and converted using `FromValue`.
+/// The following field attributes are supported (at most one `#[mysql(..)]` per field):
+///
+/// *   `#[mysql(rename = "name")]` – the field is taken from the column `name`;
+/// *   `#[mysql(default)]` – the field is set to `Default::default()` if there is no such column
+///     (may be combined with `rename`);
+/// *   `#[mysql(skip)]` – the field is not taken from the row, but set to `Default::default()`;
+/// *   `#[mysql(flatten, prefix = "prefix_")]` – the field is converted using `FromRow`
+///     from columns, whose names start with `prefix_` (the prefix is stripped). `prefix`
+///     is optional – without it the field is converted from all the columns, that are not
+///     taken by other fields.
+///
+/// Columns that don't correspond to any field are ignored, so one struct could serve
+/// queries that project different subsets of columns. The conversion fails if a column
+/// is missing or if a value couldn't be converted (the row is returned intact).
+///
+/// Flattened fields are converted after other fields. Values of their columns are cloned,
+/// so that the row could be restored if a subsequent field fails.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use mysql_common::{constants::ColumnType, from_row_struct, packets::Column};
+/// # use mysql_common::{row::{convert::from_row, new_row}, value::Value};
+/// from_row_struct! {
+///     #[derive(Debug, PartialEq)]
+///     pub struct Address {
+///         pub city: String,
+///         pub zip: Option<String>,
+///     }
+/// }
+///
+/// from_row_struct! {
+///     #[derive(Debug, PartialEq)]
+///     pub struct User {
+///         pub id: u64,
+///         #[mysql(rename = "user_name")]
+///         pub name: String,
+///         #[mysql(default)]
+///         pub email: Option<String>,
+///         #[mysql(skip)]
+///         pub cached: Vec<u8>,
+///         #[mysql(flatten, prefix = "addr_")]
+///         pub address: Address,
+///     }
+/// }
+///
+/// let columns = Arc::from(vec![
+///     Column::new(ColumnType::MYSQL_TYPE_LONGLONG).with_name(b"id"),
+///     Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"user_name"),
+///     Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"addr_city"),
+///     Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"addr_zip"),
+/// ]);
+/// let values = vec![Value::Int(1), Value::from("foo"), Value::from("bar"), Value::NULL];
+/// let row = new_row(values, columns);
+///
+/// assert_eq!(
+///     from_row::<User>(row),
+///     User {
+///         id: 1,
+///         name: "foo".into(),
+///         email: None,
+///         cached: vec![],
+///         address: Address { city: "bar".into(), zip: None },
+///     },
+/// );
+/// ```
+#[macro_export]
+macro_rules! from_row_struct {
+    // mysql attribute of a field
+    (
+        @munch $name:ident $head:tt $fields:tt $canon:tt $attrs:tt []
+        #[mysql($($arg:tt)*)] $($rest:tt)*
+    ) => {
+        $crate::from_row_struct!(
+            @munch $name $head $fields $canon $attrs [$($arg)*] $($rest)*
+        );
+    };
+    // other attribute of a field
+    (
+        @munch $name:ident $head:tt $fields:tt $canon:tt [$($attrs:tt)*] $args:tt
+        #[$($attr:tt)*] $($rest:tt)*
+    ) => {
+        $crate::from_row_struct!(
+            @munch $name $head $fields $canon [$($attrs)* #[$($attr)*]] $args $($rest)*
+        );
+    };
+    // a field
+    (
+        @munch $name:ident $head:tt $fields:tt $canon:tt $attrs:tt $args:tt
+        $fvis:vis $field:ident : $ty:ty $(, $($rest:tt)*)?
+    ) => {
+        $crate::from_row_struct!(
+            @field $name $head $fields $canon $attrs $args { $fvis $field : $ty } $($($rest)*)?
+        );
+    };
+    // all fields are processed
+    (@munch $name:ident $head:tt $fields:tt $canon:tt [] []) => {
+        $crate::from_row_struct!(@emit $name $head $fields $canon);
+    };
+
+    // canonical form of flatten args: `[flatten, prefix = "prefix"]`
+    (
+        @field $name:ident $head:tt $fields:tt $canon:tt $attrs:tt
+        [flatten] $($rest:tt)*
+    ) => {
+        $crate::from_row_struct!(
+            @field $name $head $fields $canon $attrs [flatten, prefix = ""] $($rest)*
+        );
+    };
+    (
+        @field $name:ident $head:tt $fields:tt $canon:tt $attrs:tt
+        [prefix = $prefix:literal, flatten] $($rest:tt)*
+    ) => {
+        $crate::from_row_struct!(
+            @field $name $head $fields $canon $attrs [flatten, prefix = $prefix] $($rest)*
+        );
+    };
+    // canonical form of a field: `{ field : type [mysql attribute args] }`
+    (
+        @field $name:ident $head:tt [$($fields:tt)*] [$($canon:tt)*] [$($attrs:tt)*]
+        [$($args:tt)*] { $fvis:vis $field:ident : $ty:ty } $($rest:tt)*
+    ) => {
+        $crate::from_row_struct!(
+            @munch $name $head
+            [$($fields)* $($attrs)* $fvis $field : $ty,]
+            [$($canon)* { $field : $ty [$($args)*] }]
+            [] [] $($rest)*
+        );
+    };
+
+    (
+        @emit $name:ident [$($head:tt)*] [$($fields:tt)*]
+        [$({ $field:ident : $ty:ty [$($args:tt)*] })*]
+    ) => {
+        $($head)* $name {
+            $($fields)*
+        }
+
+        impl $crate::row::convert::FromRow for $name {
+            fn from_row_opt(
+                row: $crate::row::Row,
+            ) -> ::std::result::Result<Self, $crate::row::convert::FromRowError> {
+                <Self as $crate::row::convert::FromRow>::from_row_checked(row)
+                    .map_err(::std::convert::Into::into)
+            }
+
+            #[allow(unused_mut)]
+            fn from_row_checked(
+                mut row: $crate::row::Row,
+            ) -> ::std::result::Result<Self, $crate::row::convert::FromRowColumnError> {
+                $( $crate::from_row_struct!(@find row $field $ty [$($args)*]); )*
+
+                let mut failed: ::std::option::Option<(
+                    ::std::option::Option<usize>,
+                    ::std::option::Option<$crate::value::Value>,
+                    &'static str,
+                )> = None;
+                $( $crate::from_row_struct!(@take row failed $field $ty [$($args)*]); )*
+                $( $crate::from_row_struct!(@take_flatten row failed $field $ty [$($args)*]); )*
+
+                if let Some((index, value, target_type)) = failed {
+                    if let (Some(index), Some(value)) = (index, value) {
+                        row.place(index, value);
+                    }
+                    $( $crate::from_row_struct!(@rollback row $field $ty [$($args)*]); )*
+                    return Err($crate::row::convert::FromRowColumnError::new(
+                        row,
+                        index,
+                        target_type,
+                    ));
+                }
+
+                Ok($name {
+                    $( $field: $crate::from_row_struct!(@commit $field [$($args)*]), )*
+                })
+            }
+        }
+    };
+
+    // column lookup
+    (@find $row:ident $field:ident $ty:ty [skip]) => {};
+    (@find $row:ident $field:ident $ty:ty [flatten, prefix = $prefix:literal]) => {};
+    (@find $row:ident $field:ident $ty:ty [$($args:tt)*]) => {
+        let mut $field = (
+            $crate::row::ColumnIndex::idx(
+                &$crate::from_row_struct!(@column $field [$($args)*]),
+                $row.columns_ref(),
+            ),
+            ::std::option::Option::None::<
+                <$ty as $crate::value::convert::FromValue>::Intermediate
+            >,
+        );
+        $crate::from_row_struct!(@check $row $field [$($args)*]);
+    };
+
+    // fails if a required column is missing
+    (@check $row:ident $field:ident [$(rename = $column:literal,)? default]) => {};
+    (@check $row:ident $field:ident [default, rename = $column:literal]) => {};
+    (@check $row:ident $field:ident [$($args:tt)*]) => {
+        if $field.0.is_none() {
+            return Err($crate::row::convert::FromRowColumnError::new(
+                $row,
+                None,
+                ::std::any::type_name::<Self>(),
+            ));
+        }
+    };
+
+    // value conversion
+    (@take $row:ident $failed:ident $field:ident $ty:ty [skip]) => {};
+    (@take $row:ident $failed:ident $field:ident $ty:ty [flatten, prefix = $prefix:literal]) => {};
+    (@take $row:ident $failed:ident $field:ident $ty:ty [$($args:tt)*]) => {
+        if let (None, Some(index)) = (&$failed, $field.0) {
+            let target_type = ::std::any::type_name::<$ty>();
+            match $row.take::<$crate::value::Value, _>(index) {
+                Some(value) => {
+                    match <$ty as $crate::value::convert::FromValue>::get_intermediate(value) {
+                        Ok(ir) => $field.1 = Some(ir),
+                        Err($crate::value::convert::FromValueError(value)) => {
+                            $failed = Some((Some(index), Some(value), target_type));
+                        }
+                    }
+                }
+                None => $failed = Some((Some(index), None, target_type)),
+            }
+        }
+    };
+
+    // conversion of a flattened field
+    (
+        @take_flatten $row:ident $failed:ident $field:ident $ty:ty
+        [flatten, prefix = $prefix:literal]
+    ) => {
+        let mut $field = None;
+        if $failed.is_none() {
+            let (indices, sub_row) = $crate::row::convert::take_prefixed(&mut $row, $prefix);
+            match <$ty as $crate::row::convert::FromRow>::from_row_checked(sub_row.clone()) {
+                Ok(value) => $field = Some((value, indices, sub_row)),
+                Err(err) => {
+                    let index = err.index().map(|i| indices[i]);
+                    let target_type = err.target_type();
+                    $crate::row::convert::restore_prefixed(&mut $row, &indices, err.into_row());
+                    $failed = Some((index, None, target_type));
+                }
+            }
+        }
+    };
+    (@take_flatten $row:ident $failed:ident $field:ident $ty:ty [$($args:tt)*]) => {};
+
+    // returns converted values back to the row
+    (@rollback $row:ident $field:ident $ty:ty [skip]) => {};
+    (@rollback $row:ident $field:ident $ty:ty [flatten, prefix = $prefix:literal]) => {
+        if let Some((_, indices, sub_row)) = $field.take() {
+            $crate::row::convert::restore_prefixed(&mut $row, &indices, sub_row);
+        }
+    };
+    (@rollback $row:ident $field:ident $ty:ty [$($args:tt)*]) => {
+        if let (Some(index), Some(ir)) = ($field.0, $field.1.take()) {
+            let value = <<$ty as $crate::value::convert::FromValue>::Intermediate
+                as $crate::value::convert::ConvIr<$ty>>::rollback(ir);
+            $row.place(index, value);
+        }
+    };
+
+    // field value
+    (@commit $field:ident [skip]) => {
+        ::std::default::Default::default()
+    };
+    (@commit $field:ident [flatten, prefix = $prefix:literal]) => {
+        match $field {
+            Some((value, _, _)) => value,
+            None => unreachable!("flattened field is not converted"),
+        }
+    };
+    (@commit $field:ident [$(rename = $column:literal,)? default]) => {
+        $crate::from_row_struct!(@commit_or_default $field)
+    };
+    (@commit $field:ident [default, rename = $column:literal]) => {
+        $crate::from_row_struct!(@commit_or_default $field)
+    };
+    (@commit $field:ident [$($args:tt)*]) => {
+        match $field.1 {
+            Some(ir) => $crate::value::convert::ConvIr::commit(ir),
+            None => unreachable!("value of a required column is missing"),
+        }
+    };
+    (@commit_or_default $field:ident) => {
+        match $field.1 {
+            Some(ir) => $crate::value::convert::ConvIr::commit(ir),
+            None => ::std::default::Default::default(),
+        }
+    };
+
+    // column name
+    (@column $field:ident [rename = $column:literal $(, default)?]) => {
+        $column
+    };
+    (@column $field:ident [default, rename = $column:literal]) => {
+        $column
+    };
+    (@column $field:ident [$($args:tt)*]) => {
+        stringify!($field)
+    };
+
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($body:tt)*
+        }
+    ) => {
+        $crate::from_row_struct!(
+            @munch $name [$(#[$meta])* $vis struct] [] [] [] [] $($body)*
+        );
+    };
+}
+
+/// Takes values of columns, whose names start with `prefix`, out of the `row`.
+///
+/// Returns indices of taken columns and a row of taken values (`prefix` is stripped from
+/// names of its columns).
+#[doc(hidden)]
+pub fn take_prefixed(row: &mut Row, prefix: &str) -> (Vec<usize>, Row) {
+    let mut indices = Vec::new();
+    let mut values = Vec::new();
+    let mut columns = Vec::new();
+
+    for (i, column) in row.columns.iter().enumerate() {
+        let name = column.name_ref();
+        if !name.starts_with(prefix.as_bytes()) {
+            continue;
+        }
+        if let Some(value) = row.values[i].take() {
+            indices.push(i);
+            values.push(value);
+            columns.push(column.clone().with_name(&name[prefix.len()..]));
+        }
+    }
+
+    (indices, new_row(values, columns.into()))
+}
+
+/// Returns values taken by [`take_prefixed`] back to the `row`.
+#[doc(hidden)]
+pub fn restore_prefixed(row: &mut Row, indices: &[usize], sub_row: Row) {
+    for (index, value) in indices.iter().zip(sub_row.values) {
+        if value.is_some() {
+            row.values[*index] = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        constants::ColumnType,
+        packets::Column,
+        row::{
+            convert::{from_row, from_row_checked},
+            new_row, Row,
+        },
+        value::Value,
+    };
+
+    from_row_struct! {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Item {
+            /// Identifier.
+            id: u32,
+            #[mysql(rename = "item_name")]
+            name: String,
+            #[mysql(rename = "qty", default)]
+            quantity: u16,
+            #[mysql(default)]
+            note: Option<String>,
+            #[mysql(skip)]
+            row: Vec<u8>,
+        }
+    }
+
+    from_row_struct! {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Order {
+            id: u32,
+            #[mysql(flatten, prefix = "item_")]
+            item: Item,
+            #[mysql(flatten)]
+            rest: Row,
+        }
+    }
+
+    fn row(values: Vec<Value>, names: &[&str]) -> Row {
+        let columns = names
+            .iter()
+            .map(|name| Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(name.as_bytes()))
+            .collect::<Vec<_>>();
+        new_row(values, Arc::from(columns))
+    }
+
+    #[test]
+    fn should_convert_row_to_struct() {
+        let item: Item = from_row(row(
+            vec![
+                Value::from("x"),
+                Value::Int(1),
+                Value::from("foo"),
+                Value::Int(3),
+                Value::NULL,
+            ],
+            &["extra", "id", "item_name", "qty", "note"],
+        ));
+        assert_eq!(
+            item,
+            Item {
+                id: 1,
+                name: "foo".into(),
+                quantity: 3,
+                note: None,
+                row: vec![],
+            }
+        );
+
+        let item: Item = from_row(row(
+            vec![Value::from("bar"), Value::Int(2)],
+            &["item_name", "id"],
+        ));
+        assert_eq!(item.quantity, 0);
+        assert_eq!(item.name, "bar");
+
+        // missing column
+        let original = row(vec![Value::Int(1)], &["id"]);
+        let err = from_row_checked::<Item>(original.clone()).unwrap_err();
+        assert_eq!(err.index(), None);
+        assert_eq!(err.into_row(), original);
+
+        // conversion error
+        let original = row(
+            vec![Value::Int(1), Value::from("foo"), Value::Int(-1)],
+            &["id", "item_name", "qty"],
+        );
+        let err = from_row_checked::<Item>(original.clone()).unwrap_err();
+        assert_eq!(err.index(), Some(2));
+        assert_eq!(err.target_type(), "u16");
+        assert_eq!(err.into_row(), original);
+    }
+
+    #[test]
+    fn should_convert_row_to_flattened_struct() {
+        let order: Order = from_row(row(
+            vec![
+                Value::Int(1),
+                Value::Int(2),
+                Value::from("foo"),
+                Value::Int(3),
+            ],
+            &["id", "item_id", "item_item_name", "extra"],
+        ));
+        assert_eq!(order.id, 1);
+        assert_eq!(order.item.id, 2);
+        assert_eq!(order.item.name, "foo");
+        assert_eq!(order.rest.len(), 1);
+        assert_eq!(order.rest.get::<u8, _>("extra"), Some(3));
+
+        // nested conversion error
+        let original = row(
+            vec![
+                Value::Int(1),
+                Value::Int(2),
+                Value::from("foo"),
+                Value::Int(-1),
+            ],
+            &["id", "item_id", "item_item_name", "item_qty"],
+        );
+        let err = from_row_checked::<Order>(original.clone()).unwrap_err();
+        assert_eq!(err.index(), Some(3));
+        assert_eq!(err.target_type(), "u16");
+        assert_eq!(err.into_row(), original);
+
+        // nested missing column
+        let original = row(vec![Value::Int(1), Value::Int(2)], &["id", "item_id"]);
+        let err = from_row_checked::<Order>(original.clone()).unwrap_err();
+        assert_eq!(err.index(), None);
+        assert_eq!(err.into_row(), original);
+    }
+}
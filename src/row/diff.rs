@@ -0,0 +1,144 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Row diffing (see [`diff`]).
+
+use crate::{packets::Column, value::Value};
+
+use super::Row;
+
+/// Change of a column value (see [`diff`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnChange<'a> {
+    /// Definition of the column (taken from the `after` row, if it has the column).
+    pub column: &'a Column,
+    /// Value before the change (`None` if there is no such column or if the value was taken).
+    pub before: Option<&'a Value>,
+    /// Value after the change (`None` if there is no such column or if the value was taken).
+    pub after: Option<&'a Value>,
+}
+
+impl ColumnChange<'_> {
+    /// Returns the name of the column as a byte slice.
+    pub fn name_ref(&self) -> &[u8] {
+        self.column.name_ref()
+    }
+}
+
+/// Returns columns, whose values differ between the given rows.
+///
+/// Columns are matched by name, so the order of columns may differ (duplicate names are
+/// matched in order of appearance). Columns of the `before` row come first, followed
+/// by columns, that are only present in the `after` row.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use mysql_common::{constants::ColumnType, packets::Column, row::{diff, new_row}};
+/// # use mysql_common::value::Value;
+/// let column = |name: &[u8]| Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(name);
+/// let before = new_row(
+///     vec![Value::Int(1), Value::from("foo")],
+///     Arc::from(vec![column(b"id"), column(b"name")]),
+/// );
+/// let after = new_row(
+///     vec![Value::from("bar"), Value::Int(1)],
+///     Arc::from(vec![column(b"name"), column(b"id")]),
+/// );
+///
+/// let changes = diff(&before, &after);
+/// assert_eq!(changes.len(), 1);
+/// assert_eq!(changes[0].name_ref(), b"name");
+/// assert_eq!(changes[0].before, Some(&Value::from("foo")));
+/// assert_eq!(changes[0].after, Some(&Value::from("bar")));
+/// ```
+pub fn diff<'a>(before: &'a Row, after: &'a Row) -> Vec<ColumnChange<'a>> {
+    let mut matched = vec![false; after.columns.len()];
+    let mut changes = Vec::new();
+
+    for (i, column) in before.columns.iter().enumerate() {
+        let j = (0..after.columns.len())
+            .find(|j| !matched[*j] && after.columns[*j].name_ref() == column.name_ref());
+
+        let old = before.values[i].as_ref();
+        let (column, new) = match j {
+            Some(j) => {
+                matched[j] = true;
+                (&after.columns[j], after.values[j].as_ref())
+            }
+            None => (column, None),
+        };
+
+        if old != new {
+            changes.push(ColumnChange {
+                column,
+                before: old,
+                after: new,
+            });
+        }
+    }
+
+    for (j, column) in after.columns.iter().enumerate() {
+        if !matched[j] && after.values[j].is_some() {
+            changes.push(ColumnChange {
+                column,
+                before: None,
+                after: after.values[j].as_ref(),
+            });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{constants::ColumnType, row::new_row};
+
+    fn row(values: Vec<Value>, names: &[&str]) -> Row {
+        let columns = names
+            .iter()
+            .map(|name| Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(name.as_bytes()))
+            .collect::<Vec<_>>();
+        new_row(values, Arc::from(columns))
+    }
+
+    #[test]
+    fn should_diff_rows() {
+        let before = row(
+            vec![Value::Int(1), Value::Int(2), Value::NULL, Value::Int(4)],
+            &["a", "b", "b", "removed"],
+        );
+        let mut after = row(
+            vec![Value::Int(3), Value::Int(2), Value::Int(0), Value::Int(1)],
+            &["b", "b", "added", "a"],
+        );
+
+        let changes = diff(&before, &after);
+        let summary = changes
+            .iter()
+            .map(|x| (x.column.name_str().into_owned(), x.before, x.after))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("b".to_string(), Some(&Value::Int(2)), Some(&Value::Int(3))),
+                ("b".to_string(), Some(&Value::NULL), Some(&Value::Int(2))),
+                ("removed".to_string(), Some(&Value::Int(4)), None),
+                ("added".to_string(), None, Some(&Value::Int(0))),
+            ]
+        );
+
+        assert!(diff(&before, &before).is_empty());
+
+        after.take::<Value, _>("added");
+        assert_eq!(diff(&before, &after).len(), 3);
+    }
+}
@@ -8,7 +8,7 @@
 
 use crate::{
     io::ParseBuf,
-    misc::unexpected_buf_eof,
+    misc::{ident_eq_ignore_case, unexpected_buf_eof},
     packets::{Column, NullBitmap},
     proto::{Binary, MyDeserialize, Text},
     value::{
@@ -16,9 +16,19 @@ use crate::{
         BinValue, SerializationSide, TextValue, Value, ValueDeserializer,
     },
 };
-use std::{borrow::Cow, fmt, io, marker::PhantomData, ops::Index, sync::Arc};
+use std::{any::type_name, borrow::Cow, fmt, io, marker::PhantomData, mem, ops::Index, sync::Arc};
 
+pub mod column_pool;
 pub mod convert;
+mod diff;
+pub mod raw;
+pub mod result_set;
+mod serializer;
+
+pub use self::{
+    diff::{diff, ColumnChange},
+    serializer::{RowSerializer, RowWriteError},
+};
 
 /// Client side representation of a MySql row.
 ///
@@ -85,6 +95,21 @@ impl Row {
         self.columns.clone()
     }
 
+    /// Returns the memory usage of this row (in bytes), i.e. the size of the row itself plus
+    /// the size of its values (see [`Value::size_in_bytes`]).
+    ///
+    /// Columns aren't accounted because they are shared between rows of a result set.
+    pub fn size_in_bytes(&self) -> usize {
+        mem::size_of::<Row>()
+            + self.values.capacity() * mem::size_of::<Option<Value>>()
+            + self
+                .values
+                .iter()
+                .flatten()
+                .map(Value::heap_size_in_bytes)
+                .sum::<usize>()
+    }
+
     /// Returns reference to the value of a column with index `index` if it exists and wasn't taken
     /// by `Row::take` method.
     ///
@@ -153,6 +178,80 @@ impl Row {
             .map(from_value_opt::<T>)
     }
 
+    /// Will copy value at index `index` then will attempt to convert it to `T`.
+    ///
+    /// Unlike `Row::get_opt` it returns an error that tells a missing (or taken) column,
+    /// an unexpected `NULL` and a failed conversion apart.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use mysql_common::{constants::ColumnType, packets::Column, value::Value};
+    /// # use mysql_common::row::{new_row, GetError};
+    /// let columns = Arc::from(vec![Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id")]);
+    /// let row = new_row(vec![Value::NULL], columns);
+    /// assert_eq!(row.get_checked::<Option<u8>, _>("id"), Ok(None));
+    /// assert!(matches!(row.get_checked::<u8, _>("id"), Err(GetError::UnexpectedNull { .. })));
+    /// assert!(matches!(row.get_checked::<u8, _>("foo"), Err(GetError::NoSuchColumn(_))));
+    /// ```
+    pub fn get_checked<T, I>(&self, index: I) -> Result<T, GetError>
+    where
+        T: FromValue,
+        I: ColumnIndex + fmt::Debug,
+    {
+        let idx = self.column_idx(&index)?;
+        let value = self.values[idx]
+            .clone()
+            .ok_or(GetError::Taken { index: idx })?;
+        self.convert_checked(idx, value)
+    }
+
+    /// Will take value of a column with index `index` then will attempt to convert it to `T`.
+    ///
+    /// See `Row::get_checked`.
+    pub fn take_checked<T, I>(&mut self, index: I) -> Result<T, GetError>
+    where
+        T: FromValue,
+        I: ColumnIndex + fmt::Debug,
+    {
+        let idx = self.column_idx(&index)?;
+        let value = self.values[idx]
+            .take()
+            .ok_or(GetError::Taken { index: idx })?;
+        self.convert_checked(idx, value)
+    }
+
+    fn column_idx<I>(&self, index: &I) -> Result<usize, GetError>
+    where
+        I: ColumnIndex + fmt::Debug,
+    {
+        index
+            .idx(&*self.columns)
+            .filter(|idx| *idx < self.values.len())
+            .ok_or_else(|| GetError::NoSuchColumn(format!("{:?}", index)))
+    }
+
+    fn convert_checked<T: FromValue>(&self, idx: usize, value: Value) -> Result<T, GetError> {
+        let is_null = value == Value::NULL;
+        from_value_opt::<T>(value).map_err(|FromValueError(value)| {
+            let column = self.columns[idx].name_str().into_owned();
+            let target_type = type_name::<T>();
+            if is_null {
+                GetError::UnexpectedNull {
+                    index: idx,
+                    column,
+                    target_type,
+                }
+            } else {
+                GetError::Conversion {
+                    index: idx,
+                    column,
+                    target_type,
+                    value,
+                }
+            }
+        })
+    }
+
     /// Unwraps values of a row.
     ///
     /// # Panics
@@ -177,6 +276,43 @@ impl Row {
     }
 }
 
+/// Error returned by `Row::get_checked` and `Row::take_checked`.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum GetError {
+    /// There is no such column in the row (contains the `Debug` representation of the index).
+    #[error("No such column: {}", _0)]
+    NoSuchColumn(String),
+    /// The value was taken from the row.
+    #[error("Value of the column {} was taken from the row", index)]
+    Taken { index: usize },
+    /// The value is `NULL` but the target type is not an `Option`.
+    #[error(
+        "Column `{}` (index {}) is NULL but {} is not nullable",
+        column,
+        index,
+        target_type
+    )]
+    UnexpectedNull {
+        index: usize,
+        column: String,
+        target_type: &'static str,
+    },
+    /// The value couldn't be converted to the target type.
+    #[error(
+        "Couldn't convert column `{}` (index {}) to {}: {:?}",
+        column,
+        index,
+        target_type,
+        value
+    )]
+    Conversion {
+        index: usize,
+        column: String,
+        target_type: &'static str,
+        value: Value,
+    },
+}
+
 impl Index<usize> for Row {
     type Output = Value;
 
@@ -224,6 +360,97 @@ impl<'a> ColumnIndex for &'a str {
     }
 }
 
+fn name_matches(name: &[u8], expected: &str, ignore_case: bool) -> bool {
+    if name == expected.as_bytes() {
+        true
+    } else if ignore_case {
+        ident_eq_ignore_case(&String::from_utf8_lossy(name), expected)
+    } else {
+        false
+    }
+}
+
+/// Case-insensitive column name.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use mysql_common::{constants::ColumnType, packets::Column, value::Value};
+/// # use mysql_common::row::{new_row, NoCase};
+/// let columns = Arc::from(vec![Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"Id")]);
+/// let row = new_row(vec![Value::Int(1)], columns);
+/// assert_eq!(row.get::<u8, _>("ID"), None);
+/// assert_eq!(row.get::<u8, _>(NoCase("ID")), Some(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoCase<'a>(pub &'a str);
+
+impl ColumnIndex for NoCase<'_> {
+    fn idx(&self, columns: &[Column]) -> Option<usize> {
+        columns
+            .iter()
+            .position(|c| name_matches(c.name_ref(), self.0, true))
+    }
+}
+
+/// Column name qualified with a table name or alias (i.e. `t.id`).
+///
+/// The table is matched against the `table` field of a column definition, so it should be
+/// the alias used in a query, if any.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use mysql_common::{constants::ColumnType, packets::Column, value::Value};
+/// # use mysql_common::row::{new_row, Qualified};
+/// let columns = Arc::from(vec![
+///     Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id").with_table(b"a"),
+///     Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id").with_table(b"b"),
+/// ]);
+/// let row = new_row(vec![Value::Int(1), Value::Int(2)], columns);
+/// assert_eq!(row.get::<u8, _>(Qualified::new("b", "id")), Some(2));
+/// assert_eq!(row.get::<u8, _>(Qualified::parse("B.ID").unwrap().ignore_case()), Some(2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Qualified<'a> {
+    table: &'a str,
+    column: &'a str,
+    ignore_case: bool,
+}
+
+impl<'a> Qualified<'a> {
+    /// Creates a new case-sensitive qualified column name.
+    pub fn new(table: &'a str, column: &'a str) -> Self {
+        Self {
+            table,
+            column,
+            ignore_case: false,
+        }
+    }
+
+    /// Parses `table.column` (splits at the first dot). Returns `None` if there is no dot.
+    pub fn parse(qualified_name: &'a str) -> Option<Self> {
+        let dot = qualified_name.find('.')?;
+        Some(Self::new(
+            &qualified_name[..dot],
+            &qualified_name[dot + 1..],
+        ))
+    }
+
+    /// Makes both the table and the column name matching case-insensitive.
+    pub fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+}
+
+impl ColumnIndex for Qualified<'_> {
+    fn idx(&self, columns: &[Column]) -> Option<usize> {
+        columns.iter().position(|c| {
+            name_matches(c.table_ref(), self.table, self.ignore_case)
+                && name_matches(c.name_ref(), self.column, self.ignore_case)
+        })
+    }
+}
+
 /// Row deserializer.
 ///
 /// `S` – serialization side (see [`SerializationSide`]);
@@ -289,3 +516,104 @@ impl<'de, S: SerializationSide> MyDeserialize<'de> for RowDeserializer<S, Binary
         Ok(Self(Row { values, columns }, PhantomData))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::ColumnType;
+
+    #[test]
+    fn should_lookup_columns() {
+        let columns: Arc<[Column]> = Arc::from(vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG)
+                .with_name(b"ID")
+                .with_table(b"users"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR)
+                .with_name("Имя".as_bytes())
+                .with_table(b"users"),
+            Column::new(ColumnType::MYSQL_TYPE_LONG)
+                .with_name(b"id")
+                .with_table(b"Orders"),
+        ]);
+
+        assert_eq!("id".idx(&columns), Some(2));
+        assert_eq!(NoCase("id").idx(&columns), Some(0));
+        assert_eq!(NoCase("имя").idx(&columns), Some(1));
+        assert_eq!(NoCase("name").idx(&columns), None);
+
+        assert_eq!(Qualified::new("orders", "id").idx(&columns), None);
+        assert_eq!(Qualified::new("Orders", "id").idx(&columns), Some(2));
+        assert_eq!(
+            Qualified::parse("ORDERS.ID")
+                .unwrap()
+                .ignore_case()
+                .idx(&columns),
+            Some(2)
+        );
+        assert_eq!(
+            Qualified::parse("users.id"),
+            Some(Qualified::new("users", "id"))
+        );
+        assert_eq!(Qualified::parse("id"), None);
+    }
+
+    #[test]
+    fn should_get_checked() {
+        let columns: Arc<[Column]> = Arc::from(vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"name"),
+        ]);
+        let mut row = new_row(vec![Value::Int(300), Value::NULL], columns);
+
+        assert_eq!(row.get_checked::<u16, _>("id"), Ok(300));
+        assert_eq!(row.get_checked::<Option<String>, _>(1), Ok(None));
+        assert_eq!(
+            row.get_checked::<u8, _>(0),
+            Err(GetError::Conversion {
+                index: 0,
+                column: "id".into(),
+                target_type: std::any::type_name::<u8>(),
+                value: Value::Int(300),
+            })
+        );
+        assert_eq!(
+            row.get_checked::<String, _>("name"),
+            Err(GetError::UnexpectedNull {
+                index: 1,
+                column: "name".into(),
+                target_type: std::any::type_name::<String>(),
+            })
+        );
+        assert_eq!(
+            row.get_checked::<u8, _>(2),
+            Err(GetError::NoSuchColumn("2".into()))
+        );
+
+        assert_eq!(row.take_checked::<i32, _>(NoCase("ID")), Ok(300));
+        assert_eq!(
+            row.get_checked::<i32, _>("id"),
+            Err(GetError::Taken { index: 0 })
+        );
+    }
+
+    #[test]
+    fn should_account_row_size() {
+        let columns: Arc<[Column]> = Arc::from(vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_BLOB).with_name(b"data"),
+        ]);
+        let data = vec![0_u8; 100];
+        let value_size = mem::size_of::<Value>();
+        assert_eq!(Value::Bytes(data.clone()).size_in_bytes(), value_size + 100);
+        assert_eq!(
+            Value::Date(2021, 1, 1, 0, 0, 0, 0).size_in_bytes(),
+            value_size
+        );
+
+        let mut row = new_row(vec![Value::Int(1), Value::Bytes(data)], columns);
+        let empty_size = mem::size_of::<Row>() + 2 * mem::size_of::<Option<Value>>();
+        assert_eq!(row.size_in_bytes(), empty_size + 100);
+        row.take::<Value, _>(1);
+        assert_eq!(row.size_in_bytes(), empty_size);
+    }
+}
@@ -0,0 +1,296 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Lazily decoded rows.
+
+use std::{borrow::Cow, io, ops::Range, sync::Arc};
+
+use crate::{
+    constants::ColumnType,
+    io::ParseBuf,
+    misc::unexpected_buf_eof,
+    packets::{
+        first_byte::{eat_text_value, NULL_MARKER},
+        Column, NullBitmap,
+    },
+    proto::MyDeserialize,
+    value::{
+        convert::{from_value, from_value_opt, FromValue, FromValueError},
+        ServerSide, TextValue, Value, ValueDeserializer,
+    },
+};
+
+use super::{ColumnIndex, Row};
+
+/// Undecoded row packet.
+///
+/// Cell boundaries are located (and validated) once the row is created, but values
+/// are decoded on demand, so it is cheaper than a [`Row`] if only a few columns are accessed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawRow {
+    packet: Vec<u8>,
+    /// Cell ranges within the packet (`None` for `NULL` in the binary protocol).
+    cells: Vec<Option<Range<usize>>>,
+    columns: Arc<[Column]>,
+    binary: bool,
+}
+
+impl RawRow {
+    /// Creates a row from a text protocol row packet.
+    pub fn text(packet: Vec<u8>, columns: Arc<[Column]>) -> io::Result<Self> {
+        let mut buf = ParseBuf(&packet);
+        let mut cells = Vec::with_capacity(columns.len());
+
+        for _ in 0..columns.len() {
+            let start = packet.len() - buf.len();
+            eat_text_value(&mut buf)?;
+            cells.push(Some(start..packet.len() - buf.len()));
+        }
+
+        Ok(Self {
+            packet,
+            cells,
+            columns,
+            binary: false,
+        })
+    }
+
+    /// Creates a row from a binary protocol row packet.
+    pub fn binary(packet: Vec<u8>, columns: Arc<[Column]>) -> io::Result<Self> {
+        let mut buf = ParseBuf(&packet);
+        buf.checked_eat_u8().ok_or_else(unexpected_buf_eof)?;
+
+        let bitmap = NullBitmap::<ServerSide, Cow<'_, [u8]>>::deserialize(columns.len(), &mut buf)?;
+        let mut cells = Vec::with_capacity(columns.len());
+
+        for (i, column) in columns.iter().enumerate() {
+            if bitmap.is_null(i) {
+                cells.push(None);
+            } else {
+                let start = packet.len() - buf.len();
+                skip_bin(column.column_type(), &mut buf)?;
+                cells.push(Some(start..packet.len() - buf.len()));
+            }
+        }
+
+        Ok(Self {
+            packet,
+            cells,
+            columns,
+            binary: true,
+        })
+    }
+
+    /// Returns length of a row.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns true if the row has a length of 0.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns columns of this row.
+    pub fn columns_ref(&self) -> &[Column] {
+        &*self.columns
+    }
+
+    /// Returns columns of this row.
+    pub fn columns(&self) -> Arc<[Column]> {
+        self.columns.clone()
+    }
+
+    /// Returns the row packet.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.packet
+    }
+
+    /// Returns `true` if the value of a column with index `index` is `NULL`
+    /// (`None` if there is no such column). Doesn't decode the value.
+    pub fn is_null<I: ColumnIndex>(&self, index: I) -> Option<bool> {
+        let idx = index.idx(&*self.columns)?;
+        self.cells.get(idx).map(|cell| match cell {
+            Some(range) => !self.binary && self.packet[range.start] == NULL_MARKER,
+            None => true,
+        })
+    }
+
+    /// Decodes the value of a column with index `index` (if it exists).
+    pub fn value<I: ColumnIndex>(&self, index: I) -> Option<Value> {
+        let idx = index.idx(&*self.columns)?;
+        self.cells.get(idx).map(|_| self.decode(idx))
+    }
+
+    /// Decodes the value of a column with index `index` (if it exists), then converts it to `T`.
+    ///
+    /// See [`Row::get`].
+    pub fn get<T, I>(&self, index: I) -> Option<T>
+    where
+        T: FromValue,
+        I: ColumnIndex,
+    {
+        self.value(index).map(from_value::<T>)
+    }
+
+    /// Decodes the value of a column with index `index` (if it exists),
+    /// then attempts to convert it to `T`.
+    ///
+    /// See [`Row::get_opt`].
+    pub fn get_opt<T, I>(&self, index: I) -> Option<Result<T, FromValueError>>
+    where
+        T: FromValue,
+        I: ColumnIndex,
+    {
+        self.value(index).map(from_value_opt::<T>)
+    }
+
+    /// Decodes all the values.
+    pub fn into_row(self) -> Row {
+        let values = (0..self.cells.len())
+            .map(|idx| Some(self.decode(idx)))
+            .collect();
+        Row {
+            values,
+            columns: self.columns,
+        }
+    }
+
+    fn decode(&self, idx: usize) -> Value {
+        let range = match self.cells[idx] {
+            Some(ref range) => range.clone(),
+            None => return Value::NULL,
+        };
+        let mut buf = ParseBuf(&self.packet[range]);
+        let column = &self.columns[idx];
+        let value = if self.binary {
+            Value::deserialize_bin((column.column_type(), column.flags()), &mut buf)
+        } else {
+            ValueDeserializer::<TextValue>::deserialize((), &mut buf).map(|x| x.0)
+        };
+        // cell boundaries are validated, so decoding can't fail
+        value.expect("malformed cell of a validated row")
+    }
+}
+
+impl From<RawRow> for Row {
+    fn from(row: RawRow) -> Self {
+        row.into_row()
+    }
+}
+
+/// Skips a non-`NULL` binary protocol value (see `Value::deserialize_bin`).
+fn skip_bin(column_type: ColumnType, buf: &mut ParseBuf<'_>) -> io::Result<()> {
+    use ColumnType::*;
+
+    let len = match column_type {
+        MYSQL_TYPE_STRING
+        | MYSQL_TYPE_VAR_STRING
+        | MYSQL_TYPE_BLOB
+        | MYSQL_TYPE_TINY_BLOB
+        | MYSQL_TYPE_MEDIUM_BLOB
+        | MYSQL_TYPE_LONG_BLOB
+        | MYSQL_TYPE_SET
+        | MYSQL_TYPE_ENUM
+        | MYSQL_TYPE_DECIMAL
+        | MYSQL_TYPE_VARCHAR
+        | MYSQL_TYPE_BIT
+        | MYSQL_TYPE_NEWDECIMAL
+        | MYSQL_TYPE_GEOMETRY
+        | MYSQL_TYPE_JSON => {
+            buf.checked_eat_lenenc_str()
+                .ok_or_else(unexpected_buf_eof)?;
+            return Ok(());
+        }
+        MYSQL_TYPE_TINY => 1,
+        MYSQL_TYPE_SHORT | MYSQL_TYPE_YEAR => 2,
+        MYSQL_TYPE_LONG | MYSQL_TYPE_INT24 | MYSQL_TYPE_FLOAT => 4,
+        MYSQL_TYPE_LONGLONG | MYSQL_TYPE_DOUBLE => 8,
+        MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_DATE | MYSQL_TYPE_DATETIME | MYSQL_TYPE_TIME => {
+            buf.checked_eat_u8().ok_or_else(unexpected_buf_eof)? as usize
+        }
+        MYSQL_TYPE_NULL => 0,
+        x => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported column type {:?}", x),
+            ))
+        }
+    };
+
+    if buf.checked_skip(len) {
+        Ok(())
+    } else {
+        Err(unexpected_buf_eof())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::ColumnFlags,
+        proto::{Binary, Text},
+        row::RowDeserializer,
+    };
+
+    fn columns() -> Arc<[Column]> {
+        Arc::from(vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG)
+                .with_name(b"id")
+                .with_flags(ColumnFlags::UNSIGNED_FLAG),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"name"),
+            Column::new(ColumnType::MYSQL_TYPE_DATETIME).with_name(b"created"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"note"),
+        ])
+    }
+
+    #[test]
+    fn should_decode_text_row_lazily() {
+        let packet = b"\x0242\x03foo\x132021-01-02 03:04:05\xfb".to_vec();
+        let row = RawRow::text(packet.clone(), columns()).unwrap();
+
+        assert_eq!(row.len(), 4);
+        assert_eq!(row.get::<u32, _>("id"), Some(42));
+        assert_eq!(row.get::<String, _>(1), Some("foo".into()));
+        assert_eq!(row.is_null("note"), Some(true));
+        assert_eq!(row.is_null("name"), Some(false));
+        assert_eq!(row.value(4), None);
+
+        let expected: Row = ParseBuf(&packet)
+            .parse::<RowDeserializer<(), Text>>(columns())
+            .unwrap()
+            .into();
+        assert_eq!(row.into_row(), expected);
+
+        assert!(RawRow::text(b"\x0242\x03fo".to_vec(), columns()).is_err());
+    }
+
+    #[test]
+    fn should_decode_binary_row_lazily() {
+        let packet = b"\x00\x20\x2a\x00\x00\x00\x03foo\x07\xe5\x07\x01\x02\x03\x04\x05".to_vec();
+        let row = RawRow::binary(packet.clone(), columns()).unwrap();
+
+        assert_eq!(row.get::<u32, _>(0), Some(42));
+        assert_eq!(row.get_opt::<String, _>("name"), Some(Ok("foo".into())));
+        assert_eq!(
+            row.value("created"),
+            Some(Value::Date(2021, 1, 2, 3, 4, 5, 0))
+        );
+        assert_eq!(row.is_null(3), Some(true));
+        assert_eq!(row.value(3), Some(Value::NULL));
+
+        let expected: Row = ParseBuf(&packet)
+            .parse::<RowDeserializer<ServerSide, Binary>>(columns())
+            .unwrap()
+            .into();
+        assert_eq!(Row::from(row), expected);
+
+        assert!(RawRow::binary(packet[..packet.len() - 1].to_vec(), columns()).is_err());
+    }
+}
@@ -0,0 +1,746 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Result set representation (columns, rows and a terminating OK packet).
+
+use std::{convert::TryFrom, io, marker::PhantomData, sync::Arc};
+
+use crate::{
+    constants::{CapabilityFlags, StatusFlags},
+    io::ParseBuf,
+    misc::unexpected_buf_eof,
+    packets::{
+        first_byte::{QueryResponseKind, RowPacketKind},
+        Column, ColumnDeserializer, CommonOkPacket, ErrPacket, OkPacket, OkPacketDeserializer,
+        OkPacketKind, ResultSetTerminator,
+    },
+    proto::{DeserializeCtx, FromDeserializeCtx, MyDeserialize},
+    row::{column_pool::ColumnPool, Row, RowDeserializer},
+    value::ServerSide,
+};
+
+/// Owned result set.
+///
+/// Consists of columns, rows and an OK packet that terminates the result set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultSet {
+    columns: Arc<[Column]>,
+    rows: Vec<Row>,
+    ok_packet: OkPacket<'static>,
+}
+
+impl ResultSet {
+    pub fn new(columns: Arc<[Column]>, rows: Vec<Row>, ok_packet: OkPacket<'static>) -> Self {
+        Self {
+            columns,
+            rows,
+            ok_packet,
+        }
+    }
+
+    /// Collects a result set from the given sequence of packet payloads.
+    ///
+    /// `P` is a protocol of a result set (`Text` or `Binary`). See [`ResultSetStream::new`].
+    pub fn from_packets<P, I>(capabilities: CapabilityFlags, packets: I) -> io::Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+        RowDeserializer<ServerSide, P>: for<'de> MyDeserialize<'de, Ctx = Arc<[Column]>>,
+    {
+        let mut stream = ResultSetStream::<P, _>::new(capabilities, packets.into_iter())?;
+        let rows = stream.by_ref().collect::<io::Result<Vec<_>>>()?;
+        let (ok_packet, columns) = stream
+            .into_ok_packet()
+            .expect("result set stream is exhausted at this point");
+        Ok(Self::new(columns, rows, ok_packet))
+    }
+
+    /// Returns columns of this result set.
+    pub fn columns_ref(&self) -> &[Column] {
+        &*self.columns
+    }
+
+    /// Returns columns of this result set.
+    pub fn columns(&self) -> Arc<[Column]> {
+        self.columns.clone()
+    }
+
+    /// Returns rows of this result set.
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Returns an OK packet that terminates this result set.
+    pub fn ok_packet(&self) -> &OkPacket<'static> {
+        &self.ok_packet
+    }
+
+    /// Returns `true` if there is no rows in this result set.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the number of rows in this result set.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Unwraps this result set into columns, rows and an OK packet.
+    pub fn into_parts(self) -> (Arc<[Column]>, Vec<Row>, OkPacket<'static>) {
+        (self.columns, self.rows, self.ok_packet)
+    }
+}
+
+impl IntoIterator for ResultSet {
+    type Item = Row;
+    type IntoIter = std::vec::IntoIter<Row>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.into_iter()
+    }
+}
+
+/// Streaming counterpart of the [`ResultSet`].
+///
+/// Iterates over rows of a result set given as a sequence of packet payloads.
+/// The OK packet is available via [`ResultSetStream::ok_packet`] once the stream is exhausted.
+///
+/// `P` is a protocol of a result set (`Text` or `Binary`).
+#[derive(Debug)]
+pub struct ResultSetStream<P, I> {
+    packets: I,
+    decoder: ResultSetDecoder<P>,
+}
+
+impl<P, I> ResultSetStream<P, I>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+{
+    /// Creates a new stream.
+    ///
+    /// Will consume result set metadata (column count packet, column definitions and
+    /// an EOF packet, if `CLIENT_DEPRECATE_EOF` is not set).
+    ///
+    /// An OK packet in place of the column count packet means an empty result set
+    /// (without columns). An ERR packet will be returned as an error.
+    pub fn new(capabilities: CapabilityFlags, packets: I) -> io::Result<Self> {
+        Self::new_in(capabilities, packets, None)
+    }
+
+    /// Same as [`ResultSetStream::new`], but columns will be interned in the given pool,
+    /// so that result sets with the same metadata will share the same columns.
+    pub fn with_column_pool(
+        capabilities: CapabilityFlags,
+        packets: I,
+        column_pool: &ColumnPool,
+    ) -> io::Result<Self> {
+        Self::new_in(capabilities, packets, Some(column_pool))
+    }
+
+    fn new_in(
+        capabilities: CapabilityFlags,
+        mut packets: I,
+        column_pool: Option<&ColumnPool>,
+    ) -> io::Result<Self> {
+        let mut decoder = ResultSetDecoder::new(capabilities);
+
+        // consume result set metadata (or an OK packet in place of a result set)
+        loop {
+            let packet = packets.next().ok_or_else(unexpected_buf_eof)?;
+            if decoder
+                .push_metadata(packet.as_ref(), column_pool)?
+                .is_some()
+            {
+                break;
+            }
+        }
+
+        Ok(Self { packets, decoder })
+    }
+
+    /// Returns columns of this result set.
+    pub fn columns(&self) -> Arc<[Column]> {
+        self.decoder.columns()
+    }
+
+    /// Returns the terminating OK packet (`None` if the stream is not yet exhausted).
+    pub fn ok_packet(&self) -> Option<&OkPacket<'static>> {
+        self.decoder.ok_packet()
+    }
+
+    /// Returns the terminating OK packet along with columns
+    /// (`None` if the stream is not yet exhausted).
+    pub fn into_ok_packet(self) -> Option<(OkPacket<'static>, Arc<[Column]>)> {
+        let columns = self.decoder.columns;
+        self.decoder.ok_packet.map(|ok_packet| (ok_packet, columns))
+    }
+}
+
+impl<P, I> Iterator for ResultSetStream<P, I>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+    RowDeserializer<ServerSide, P>: for<'de> MyDeserialize<'de, Ctx = Arc<[Column]>>,
+{
+    type Item = io::Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.decoder.ok_packet().is_some() || self.decoder.is_finished() {
+            return None;
+        }
+
+        loop {
+            let packet = match self.packets.next() {
+                Some(packet) => packet,
+                None => return Some(Err(unexpected_buf_eof())),
+            };
+
+            match self.decoder.push(packet.as_ref()) {
+                Ok(Some(ResultSetEvent::Row(row))) => return Some(Ok(row)),
+                Ok(Some(ResultSetEvent::End(_))) => return None,
+                Ok(Some(ResultSetEvent::Columns(_))) => {
+                    return Some(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unexpected result set metadata",
+                    )))
+                }
+                // progress reports are skipped
+                Ok(None) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Output of the [`ResultSetDecoder`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultSetEvent {
+    /// Metadata of a result set is consumed. Rows of the result set follow.
+    Columns(Arc<[Column]>),
+    /// A row of the current result set.
+    Row(Row),
+    /// The current result set (or an OK packet in place of a result set) is terminated
+    /// by the given OK packet.
+    ///
+    /// Another result set (or an OK packet) follows if `SERVER_MORE_RESULTS_EXISTS`
+    /// status flag is set (see [`ResultSetDecoder::more_results_exists`]).
+    End(OkPacket<'static>),
+}
+
+/// State of the [`ResultSetDecoder`].
+#[derive(Debug, Clone)]
+enum DecoderState {
+    /// Expects a column count packet or an OK/ERR packet in place of a result set.
+    ColumnCount,
+    /// Expects the given number of column definitions.
+    Columns(usize, Vec<Column>),
+    /// Expects an EOF packet after column definitions (if `CLIENT_DEPRECATE_EOF` is not set).
+    ColumnsEof,
+    /// Expects rows or the terminating packet.
+    Rows,
+    /// The last result set is terminated (or an ERR packet is received).
+    Finished,
+}
+
+/// Sans-io decoder of a sequence of result sets, i.e. of a server response to a query
+/// or to a prepared statement execution.
+///
+/// Given capabilities it consumes packet payloads (text or binary rows, depending
+/// on the protocol `P`):
+///
+/// *   a column count packet and column definitions (followed by an EOF packet,
+///     if `CLIENT_DEPRECATE_EOF` is not set) – [`ResultSetEvent::Columns`] is emitted;
+/// *   rows – [`ResultSetEvent::Row`] is emitted for every row;
+/// *   an EOF packet or, if `CLIENT_DEPRECATE_EOF` is set, an OK packet with the `0xFE` header
+///     terminates the result set – [`ResultSetEvent::End`] is emitted. An OK packet
+///     in place of a column count packet is an empty result set (without columns);
+/// *   the next result set follows, if `SERVER_MORE_RESULTS_EXISTS` status flag is set;
+/// *   an ERR packet is returned as an error (MariaDB progress reports are skipped).
+///
+/// ```
+/// # use mysql_common::constants::CapabilityFlags;
+/// # use mysql_common::proto::Binary;
+/// # use mysql_common::row::result_set::{ResultSetDecoder, ResultSetEvent};
+/// # use mysql_common::value::Value;
+/// let mut decoder = ResultSetDecoder::<Binary>::new(CapabilityFlags::CLIENT_PROTOCOL_41);
+///
+/// // column count and a LONG column definition
+/// assert!(decoder.push(b"\x01")?.is_none());
+/// let column = b"\x03def\x00\x00\x00\x01a\x00\x0c\x3f\x00\x0b\x00\x00\x00\
+///     \x03\x00\x00\x00\x00\x00";
+/// assert!(decoder.push(column)?.is_none());
+/// assert!(matches!(decoder.push(b"\xfe\x00\x00\x02\x00")?, Some(ResultSetEvent::Columns(_))));
+///
+/// match decoder.push(b"\x00\x00\x2a\x00\x00\x00")? {
+///     Some(ResultSetEvent::Row(row)) => assert_eq!(row[0], Value::Int(42)),
+///     _ => unreachable!(),
+/// }
+///
+/// // EOF packet with `SERVER_MORE_RESULTS_EXISTS`
+/// let event = decoder.push(b"\xfe\x00\x00\x0a\x00")?;
+/// assert!(matches!(event, Some(ResultSetEvent::End(_))));
+/// assert!(decoder.more_results_exists());
+///
+/// // OK packet in place of the next result set
+/// let event = decoder.push(b"\x00\x01\x00\x02\x00\x00\x00")?;
+/// assert!(matches!(event, Some(ResultSetEvent::End(ok)) if ok.affected_rows() == 1));
+/// assert!(decoder.is_finished());
+/// # Ok::<_, std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResultSetDecoder<P> {
+    ctx: DeserializeCtx,
+    state: DecoderState,
+    columns: Arc<[Column]>,
+    ok_packet: Option<OkPacket<'static>>,
+    __protocol: PhantomData<P>,
+}
+
+impl<P> ResultSetDecoder<P> {
+    /// Creates a decoder that expects the first packet of a server response
+    /// (a column count packet or an OK/ERR packet).
+    pub fn new(capabilities: CapabilityFlags) -> Self {
+        Self::with_ctx(DeserializeCtx::new(capabilities))
+    }
+
+    /// Same as [`ResultSetDecoder::new`], but packets will be parsed in the given
+    /// connection-level context (i.e. MariaDB extended type info of columns will be parsed,
+    /// if negotiated, and payload length limits will be applied).
+    pub fn with_ctx(ctx: DeserializeCtx) -> Self {
+        Self {
+            ctx,
+            state: DecoderState::ColumnCount,
+            columns: Vec::new().into(),
+            ok_packet: None,
+            __protocol: PhantomData,
+        }
+    }
+
+    /// Returns columns of the current result set (empty, if metadata is not yet consumed).
+    pub fn columns(&self) -> Arc<[Column]> {
+        self.columns.clone()
+    }
+
+    /// Returns the OK packet that terminates the current result set
+    /// (`None` if the result set is not yet terminated).
+    pub fn ok_packet(&self) -> Option<&OkPacket<'static>> {
+        self.ok_packet.as_ref()
+    }
+
+    /// Returns `true` if the last result set is terminated or an ERR packet is received.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, DecoderState::Finished)
+    }
+
+    /// Returns `true` if the current result set is terminated and `SERVER_MORE_RESULTS_EXISTS`
+    /// status flag is set, i.e. another result set (or an OK packet) follows.
+    pub fn more_results_exists(&self) -> bool {
+        self.ok_packet.as_ref().map_or(false, |ok| {
+            ok.status_flags()
+                .contains(StatusFlags::SERVER_MORE_RESULTS_EXISTS)
+        })
+    }
+
+    /// Terminates the current result set with the given OK packet.
+    fn end(&mut self, ok_packet: OkPacket<'static>) -> ResultSetEvent {
+        self.ok_packet = Some(ok_packet.clone());
+        self.state = if self.more_results_exists() {
+            DecoderState::ColumnCount
+        } else {
+            DecoderState::Finished
+        };
+        ResultSetEvent::End(ok_packet)
+    }
+
+    /// Handles an ERR packet. Returns `None` for a progress report.
+    fn err(&mut self, packet: &[u8]) -> io::Result<Option<ResultSetEvent>> {
+        match ParseBuf(packet).parse_in::<ErrPacket>(&self.ctx)? {
+            ErrPacket::Progress(_) => Ok(None),
+            ErrPacket::Error(err) => {
+                self.state = DecoderState::Finished;
+                Err(io::Error::new(io::ErrorKind::Other, err.into_owned()))
+            }
+        }
+    }
+
+    /// Pushes a packet of result set metadata (must not be called in the rows state).
+    fn push_metadata(
+        &mut self,
+        packet: &[u8],
+        column_pool: Option<&ColumnPool>,
+    ) -> io::Result<Option<ResultSetEvent>> {
+        match self.state {
+            DecoderState::ColumnCount => match QueryResponseKind::parse(packet)? {
+                QueryResponseKind::Ok => {
+                    let ok_packet = ParseBuf(packet)
+                        .parse_in::<OkPacketDeserializer<CommonOkPacket>>(&self.ctx)?
+                        .into_inner()
+                        .into_owned();
+                    self.columns = Vec::new().into();
+                    Ok(Some(self.end(ok_packet)))
+                }
+                QueryResponseKind::Err => self.err(packet),
+                QueryResponseKind::LocalInfile => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "unexpected LOCAL INFILE request",
+                )),
+                QueryResponseKind::ResultSet(column_count) => {
+                    self.ok_packet = None;
+                    self.columns = Vec::new().into();
+                    let column_count = column_count as usize;
+                    self.state = DecoderState::Columns(column_count, Vec::new());
+                    if column_count == 0 {
+                        self.columns_done(Vec::new(), column_pool)
+                    } else {
+                        Ok(None)
+                    }
+                }
+            },
+            DecoderState::Columns(column_count, ref mut columns) => {
+                let column = ParseBuf(packet).parse_in::<ColumnDeserializer>(&self.ctx)?;
+                columns.push(column.into());
+                if columns.len() < column_count {
+                    return Ok(None);
+                }
+                let columns = std::mem::take(columns);
+                self.columns_done(columns, column_pool)
+            }
+            DecoderState::ColumnsEof => {
+                ParseBuf(packet)
+                    .parse_in::<OkPacketDeserializer<ResultSetTerminator>>(&self.ctx)?;
+                self.state = DecoderState::Rows;
+                Ok(Some(ResultSetEvent::Columns(self.columns.clone())))
+            }
+            DecoderState::Rows => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "result set metadata is already consumed",
+            )),
+            DecoderState::Finished => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "result set is already terminated",
+            )),
+        }
+    }
+
+    /// Switches to rows once all column definitions are consumed.
+    fn columns_done(
+        &mut self,
+        columns: Vec<Column>,
+        column_pool: Option<&ColumnPool>,
+    ) -> io::Result<Option<ResultSetEvent>> {
+        self.columns = match column_pool {
+            Some(column_pool) => column_pool.intern(columns),
+            None => columns.into(),
+        };
+        if self
+            .ctx
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_DEPRECATE_EOF)
+        {
+            self.state = DecoderState::Rows;
+            Ok(Some(ResultSetEvent::Columns(self.columns.clone())))
+        } else {
+            self.state = DecoderState::ColumnsEof;
+            Ok(None)
+        }
+    }
+}
+
+impl<P> ResultSetDecoder<P>
+where
+    RowDeserializer<ServerSide, P>: for<'de> MyDeserialize<'de, Ctx = Arc<[Column]>>,
+{
+    /// Pushes the next packet payload.
+    ///
+    /// Returns `None` if the packet doesn't produce an event (i.e. it is a column count packet,
+    /// a column definition, an intermediate EOF packet or a progress report).
+    /// It's an error to push a packet after the decoder is finished.
+    pub fn push(&mut self, packet: &[u8]) -> io::Result<Option<ResultSetEvent>> {
+        self.push_in(packet, None)
+    }
+
+    /// Same as [`ResultSetDecoder::push`], but columns will be interned in the given pool,
+    /// so that result sets with the same metadata will share the same columns.
+    pub fn push_with_column_pool(
+        &mut self,
+        packet: &[u8],
+        column_pool: &ColumnPool,
+    ) -> io::Result<Option<ResultSetEvent>> {
+        self.push_in(packet, Some(column_pool))
+    }
+
+    fn push_in(
+        &mut self,
+        packet: &[u8],
+        column_pool: Option<&ColumnPool>,
+    ) -> io::Result<Option<ResultSetEvent>> {
+        if !matches!(self.state, DecoderState::Rows) {
+            return self.push_metadata(packet, column_pool);
+        }
+
+        self.ctx.check_payload_len(packet.len())?;
+        match RowPacketKind::parse(self.ctx.capabilities, packet) {
+            RowPacketKind::Terminator => {
+                let capabilities = CapabilityFlags::from_deserialize_ctx(&self.ctx);
+                let ok_packet = parse_terminator(capabilities, packet)?;
+                Ok(Some(self.end(ok_packet)))
+            }
+            RowPacketKind::Err => self.err(packet),
+            RowPacketKind::Row => ParseBuf(packet)
+                .parse::<RowDeserializer<ServerSide, P>>(self.columns.clone())
+                .map(|row| Some(ResultSetEvent::Row(row.into()))),
+        }
+    }
+}
+
+/// Parses a packet that terminates a result set.
+///
+/// It's an OK packet with `0xFE` header, if `CLIENT_DEPRECATE_EOF` is set,
+/// and an EOF packet otherwise.
+fn parse_terminator(capabilities: CapabilityFlags, packet: &[u8]) -> io::Result<OkPacket<'static>> {
+    let mut buf = ParseBuf(packet);
+    if capabilities.contains(CapabilityFlags::CLIENT_DEPRECATE_EOF) {
+        buf.skip(1);
+        let body = CommonOkPacket::parse_body(capabilities, &mut buf)?;
+        OkPacket::try_from(body).map(OkPacket::into_owned)
+    } else {
+        buf.parse::<OkPacketDeserializer<ResultSetTerminator>>(capabilities)
+            .map(|x| x.into_inner().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        constants::ColumnType,
+        proto::{Binary, Text},
+        value::Value,
+    };
+
+    const COLUMN: &[u8] = b"\x03def\x06schema\x05table\x09org_table\x04name\
+                            \x08org_name\x0c\x21\x00\x0f\x00\x00\x00\x0f\x00\x00\x00\x00\x00";
+
+    #[test]
+    fn should_collect_text_result_set() {
+        let packets: Vec<&[u8]> = vec![
+            b"\x01",
+            COLUMN,
+            b"\xfe\x00\x00\x02\x00",
+            b"\x03foo",
+            b"\xfb",
+            b"\xfe\x01\x00\x22\x00",
+        ];
+
+        let result_set =
+            ResultSet::from_packets::<Text, _>(CapabilityFlags::CLIENT_PROTOCOL_41, packets)
+                .unwrap();
+
+        assert_eq!(result_set.columns_ref().len(), 1);
+        assert_eq!(
+            result_set.columns_ref()[0].column_type(),
+            ColumnType::MYSQL_TYPE_VARCHAR
+        );
+        assert_eq!(result_set.len(), 2);
+        assert_eq!(result_set.rows()[0][0], Value::Bytes(b"foo".to_vec()));
+        assert_eq!(result_set.rows()[1][0], Value::NULL);
+        assert_eq!(result_set.ok_packet().warnings(), 1);
+        assert!(result_set
+            .ok_packet()
+            .status_flags()
+            .contains(StatusFlags::SERVER_STATUS_AUTOCOMMIT));
+    }
+
+    #[test]
+    fn should_stream_result_set_without_eof() {
+        let capabilities =
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_DEPRECATE_EOF;
+        let packets: Vec<&[u8]> =
+            vec![b"\x01", COLUMN, b"\x03bar", b"\xfe\x00\x00\x02\x00\x00\x00"];
+
+        let mut stream =
+            ResultSetStream::<Text, _>::new(capabilities, packets.into_iter()).unwrap();
+        assert!(stream.ok_packet().is_none());
+        let row = stream.next().unwrap().unwrap();
+        assert_eq!(row[0], Value::Bytes(b"bar".to_vec()));
+        assert!(stream.next().is_none());
+        assert!(stream
+            .ok_packet()
+            .unwrap()
+            .status_flags()
+            .contains(StatusFlags::SERVER_STATUS_AUTOCOMMIT));
+    }
+
+    #[test]
+    fn should_share_pooled_columns() {
+        let capabilities =
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_DEPRECATE_EOF;
+        let packets: Vec<&[u8]> =
+            vec![b"\x01", COLUMN, b"\x03bar", b"\xfe\x00\x00\x02\x00\x00\x00"];
+        let pool = ColumnPool::default();
+
+        let first = ResultSetStream::<Text, _>::with_column_pool(
+            capabilities,
+            packets.clone().into_iter(),
+            &pool,
+        )
+        .unwrap();
+        let second =
+            ResultSetStream::<Text, _>::with_column_pool(capabilities, packets.into_iter(), &pool)
+                .unwrap();
+        assert!(Arc::ptr_eq(&first.columns(), &second.columns()));
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn should_handle_ok_and_err_in_place_of_result_set() {
+        let result_set = ResultSet::from_packets::<Text, _>(
+            CapabilityFlags::CLIENT_PROTOCOL_41,
+            vec![&b"\x00\x01\x00\x02\x00\x00\x00"[..]],
+        )
+        .unwrap();
+        assert!(result_set.is_empty());
+        assert!(result_set.columns_ref().is_empty());
+        assert_eq!(result_set.ok_packet().affected_rows(), 1);
+
+        let err = ResultSet::from_packets::<Text, _>(
+            CapabilityFlags::CLIENT_PROTOCOL_41,
+            vec![&b"\xff\x10\x04#08S01Got packets out of order"[..]],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn should_decode_multiple_result_sets() {
+        let capabilities = CapabilityFlags::CLIENT_PROTOCOL_41
+            | CapabilityFlags::CLIENT_DEPRECATE_EOF
+            | CapabilityFlags::CLIENT_PROGRESS_OBSOLETE;
+        let mut decoder = ResultSetDecoder::<Binary>::new(capabilities);
+
+        assert_eq!(decoder.push(b"\x01").unwrap(), None);
+        let columns = match decoder.push(COLUMN).unwrap() {
+            Some(ResultSetEvent::Columns(columns)) => columns,
+            x => panic!("unexpected event: {:?}", x),
+        };
+        assert_eq!(columns.len(), 1);
+        assert!(Arc::ptr_eq(&columns, &decoder.columns()));
+
+        assert_eq!(
+            decoder.push(b"\x00\x00\x03foo").unwrap(),
+            Some(ResultSetEvent::Row(crate::row::new_row(
+                vec![Value::Bytes(b"foo".to_vec())],
+                columns.clone()
+            )))
+        );
+        // progress report is skipped
+        assert_eq!(
+            decoder
+                .push(b"\xff\xff\xff\x01\x01\x02\x00\x00\x00\x00")
+                .unwrap(),
+            None
+        );
+        // NULL
+        assert!(matches!(
+            decoder.push(b"\x00\x04").unwrap(),
+            Some(ResultSetEvent::Row(row)) if row[0] == Value::NULL
+        ));
+        assert!(decoder.ok_packet().is_none());
+
+        // OK packet with `SERVER_MORE_RESULTS_EXISTS`
+        assert!(matches!(
+            decoder.push(b"\xfe\x00\x00\x0a\x00\x00\x00").unwrap(),
+            Some(ResultSetEvent::End(_))
+        ));
+        assert!(decoder.more_results_exists());
+        assert!(!decoder.is_finished());
+
+        // the next result set shares columns with the first one
+        let pool = ColumnPool::default();
+        pool.intern_arc(columns.clone());
+        assert_eq!(decoder.push_with_column_pool(b"\x01", &pool).unwrap(), None);
+        assert!(decoder.ok_packet().is_none());
+        assert!(matches!(
+            decoder.push_with_column_pool(COLUMN, &pool).unwrap(),
+            Some(ResultSetEvent::Columns(x)) if Arc::ptr_eq(&x, &columns)
+        ));
+        assert!(matches!(
+            decoder.push(b"\xfe\x00\x00\x0a\x00\x00\x00").unwrap(),
+            Some(ResultSetEvent::End(_))
+        ));
+
+        // OK packet in place of the last result set
+        assert!(matches!(
+            decoder.push(b"\x00\x01\x00\x02\x00\x00\x00").unwrap(),
+            Some(ResultSetEvent::End(ok)) if ok.affected_rows() == 1
+        ));
+        assert!(decoder.columns().is_empty());
+        assert!(!decoder.more_results_exists());
+        assert!(decoder.is_finished());
+        let err = decoder.push(b"\x00\x00\x03foo").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        // ERR packet terminates the sequence
+        let mut decoder = ResultSetDecoder::<Text>::new(CapabilityFlags::CLIENT_PROTOCOL_41);
+        assert_eq!(decoder.push(b"\x01").unwrap(), None);
+        assert_eq!(decoder.push(COLUMN).unwrap(), None);
+        assert!(matches!(
+            decoder.push(b"\xfe\x00\x00\x02\x00").unwrap(),
+            Some(ResultSetEvent::Columns(_))
+        ));
+        let err = decoder
+            .push(b"\xff\x10\x04#08S01Got packets out of order")
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert!(decoder.is_finished());
+    }
+
+    #[test]
+    fn should_decode_in_connection_ctx() {
+        use crate::{
+            constants::MariadbCapabilities, packets::MariadbExtendedType, proto::ServerDialect,
+        };
+
+        const EXT_COLUMN: &[u8] = b"\x03def\x00\x01t\x01t\x02id\x02id\x06\x00\x04uuid\
+            \x0c\x08\x00\x28\x00\x00\x00\xfe\x00\x00\x00\x00\x00";
+        let ctx = DeserializeCtx::new(
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_DEPRECATE_EOF,
+        )
+        .with_dialect(ServerDialect::MariaDb)
+        .with_mariadb_capabilities(
+            MariadbCapabilities::MARIADB_CLIENT_EXTENDED_TYPE_INFO
+                | MariadbCapabilities::MARIADB_CLIENT_PROGRESS,
+        );
+
+        let mut decoder = ResultSetDecoder::<Text>::with_ctx(ctx);
+        assert_eq!(decoder.push(b"\x01").unwrap(), None);
+        match decoder.push(EXT_COLUMN).unwrap() {
+            Some(ResultSetEvent::Columns(columns)) => {
+                assert_eq!(
+                    columns[0].mariadb_ext_type(),
+                    Some(MariadbExtendedType::Uuid)
+                )
+            }
+            x => panic!("unexpected event: {:?}", x),
+        }
+        // progress report is recognized given MariaDB capabilities
+        assert_eq!(
+            decoder
+                .push(b"\xff\xff\xff\x01\x01\x02\x00\x00\x00\x00")
+                .unwrap(),
+            None
+        );
+
+        // extended type info is not expected for MySql
+        let mut decoder =
+            ResultSetDecoder::<Text>::with_ctx(ctx.with_dialect(ServerDialect::MySql));
+        assert_eq!(decoder.push(b"\x01").unwrap(), None);
+        assert!(decoder.push(EXT_COLUMN).is_err());
+    }
+}
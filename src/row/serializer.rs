@@ -0,0 +1,343 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use bytes::BufMut;
+
+use std::marker::PhantomData;
+
+use crate::{
+    constants::{ColumnFlags, ColumnType},
+    packets::{Column, NullBitmap},
+    proto::{Binary, MySerialize, Text},
+    value::{
+        convert::{
+            from_value_opt,
+            temporal::{MysqlDateTime, MysqlTime},
+        },
+        ServerSide, TextValue, Value, ValueSerializer,
+    },
+};
+
+/// Error of [`RowSerializer`] (and of [`crate::packets::server::ResultSetWriter`]).
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RowWriteError {
+    /// Number of values doesn't match the number of columns.
+    #[error("Result set has {} columns but {} values given", expected, given)]
+    WrongLength {
+        /// Number of columns.
+        expected: usize,
+        /// Number of values.
+        given: usize,
+    },
+    /// Value can't be represented as a value of a column.
+    #[error(
+        "Value {} ({:?}) can't be represented as a value of a column of type {:?}",
+        index,
+        value,
+        column_type
+    )]
+    IncompatibleValue {
+        /// Index of the value.
+        index: usize,
+        /// The value.
+        value: Value,
+        /// Type of the column.
+        column_type: ColumnType,
+    },
+}
+
+/// Row serializer (the inverse of [`super::RowDeserializer`]).
+///
+/// `P` – protocol.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use mysql_common::{constants::ColumnType, packets::Column, proto::Binary};
+/// # use mysql_common::{io::ParseBuf, row::{RowDeserializer, RowSerializer}};
+/// # use mysql_common::value::{ServerSide, Value};
+/// let columns = vec![
+///     Column::new(ColumnType::MYSQL_TYPE_LONG),
+///     Column::new(ColumnType::MYSQL_TYPE_VAR_STRING),
+/// ];
+/// let row = vec![Value::Int(42), Value::NULL];
+///
+/// let mut buf = Vec::new();
+/// RowSerializer::<Binary>::new(&columns, &row)?.write(&mut buf)?;
+/// assert_eq!(buf, b"\x00\x08\x2a\x00\x00\x00");
+///
+/// let parsed: RowDeserializer<ServerSide, Binary> =
+///     ParseBuf(&buf).parse(Arc::from(columns)).unwrap();
+/// assert_eq!(parsed.into_inner().unwrap(), row);
+/// # Ok::<_, mysql_common::row::RowWriteError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RowSerializer<'a, P> {
+    columns: &'a [Column],
+    row: &'a [Value],
+    __protocol: PhantomData<P>,
+}
+
+impl<'a, P> RowSerializer<'a, P> {
+    /// Creates a serializer of the given row.
+    ///
+    /// Returns an error if the number of values doesn't match the number of columns.
+    pub fn new(columns: &'a [Column], row: &'a [Value]) -> Result<Self, RowWriteError> {
+        if columns.len() != row.len() {
+            return Err(RowWriteError::WrongLength {
+                expected: columns.len(),
+                given: row.len(),
+            });
+        }
+        Ok(Self {
+            columns,
+            row,
+            __protocol: PhantomData,
+        })
+    }
+
+    /// Returns columns of the row.
+    pub fn columns(&self) -> &'a [Column] {
+        self.columns
+    }
+
+    /// Returns values of the row.
+    pub fn row(&self) -> &'a [Value] {
+        self.row
+    }
+}
+
+impl RowSerializer<'_, Text> {
+    /// Writes a text protocol row, i.e. a sequence of length-encoded text representations
+    /// of values (`NULL` is written as `0xFB`).
+    ///
+    /// Temporal values are formatted according to the column type, e.g. `DATE` values
+    /// won't contain the time part. NaN and infinity are written as `NULL`.
+    pub fn write(&self, buf: &mut Vec<u8>) -> Result<(), RowWriteError> {
+        for (column, value) in self.columns.iter().zip(self.row) {
+            ValueSerializer::<TextValue>::new(value)
+                .with_column_type(Some(column.column_type()))
+                .serialize(buf);
+        }
+        Ok(())
+    }
+}
+
+impl RowSerializer<'_, Binary> {
+    /// Writes a binary protocol row, i.e. the `0x00` header, the null-bitmap
+    /// (with the offset of 2 bits) and values of non-null columns.
+    ///
+    /// Values are converted to the representation defined by the column type
+    /// (e.g. `Value::Int` is written as four bytes for a `MYSQL_TYPE_LONG` column).
+    /// Nothing is written if some value can't be represented as a value of its column.
+    pub fn write(&self, buf: &mut Vec<u8>) -> Result<(), RowWriteError> {
+        let mut bitmap = NullBitmap::<ServerSide>::new(self.columns.len());
+        let mut values = Vec::new();
+        for (index, (column, value)) in self.columns.iter().zip(self.row).enumerate() {
+            if is_null(value) {
+                bitmap.set(index, true);
+            } else {
+                put_bin_value(column, value, &mut values).ok_or_else(|| {
+                    RowWriteError::IncompatibleValue {
+                        index,
+                        value: value.clone(),
+                        column_type: column.column_type(),
+                    }
+                })?;
+            }
+        }
+
+        buf.put_u8(0x00);
+        buf.put_slice(bitmap.as_ref());
+        buf.put_slice(&values);
+        Ok(())
+    }
+}
+
+/// NaN and infinity can't be stored in MySql, so they are written as `NULL`
+/// (same as in the text protocol).
+fn is_null(value: &Value) -> bool {
+    match *value {
+        Value::NULL => true,
+        Value::Float(x) => !x.is_finite(),
+        Value::Double(x) => !x.is_finite(),
+        _ => false,
+    }
+}
+
+/// Writes a binary representation of a (non-null) value of the given column.
+///
+/// Returns `None` if the value can't be represented as a value of the column.
+fn put_bin_value(column: &Column, value: &Value, buf: &mut Vec<u8>) -> Option<()> {
+    let unsigned = column.flags().contains(ColumnFlags::UNSIGNED_FLAG);
+    match column.column_type() {
+        ColumnType::MYSQL_TYPE_TINY => put_int(value, unsigned, 1, buf),
+        ColumnType::MYSQL_TYPE_SHORT | ColumnType::MYSQL_TYPE_YEAR => {
+            put_int(value, unsigned, 2, buf)
+        }
+        ColumnType::MYSQL_TYPE_LONG | ColumnType::MYSQL_TYPE_INT24 => {
+            put_int(value, unsigned, 4, buf)
+        }
+        ColumnType::MYSQL_TYPE_LONGLONG => put_int(value, unsigned, 8, buf),
+        ColumnType::MYSQL_TYPE_FLOAT => {
+            let x = match *value {
+                Value::Float(x) => x,
+                Value::Double(x) => x as f32,
+                Value::Int(x) => x as f32,
+                Value::UInt(x) => x as f32,
+                _ => from_value_opt::<f32>(value.clone()).ok()?,
+            };
+            buf.put_f32_le(x);
+            Some(())
+        }
+        ColumnType::MYSQL_TYPE_DOUBLE => {
+            let x = match *value {
+                Value::Int(x) => x as f64,
+                Value::UInt(x) => x as f64,
+                _ => from_value_opt::<f64>(value.clone()).ok()?,
+            };
+            buf.put_f64_le(x);
+            Some(())
+        }
+        ColumnType::MYSQL_TYPE_TIMESTAMP
+        | ColumnType::MYSQL_TYPE_DATE
+        | ColumnType::MYSQL_TYPE_DATETIME => {
+            let x = from_value_opt::<MysqlDateTime>(value.clone()).ok()?;
+            Value::from(x).serialize(buf);
+            Some(())
+        }
+        ColumnType::MYSQL_TYPE_TIME => {
+            let x = from_value_opt::<MysqlTime>(value.clone()).ok()?;
+            Value::from(x).serialize(buf);
+            Some(())
+        }
+        ColumnType::MYSQL_TYPE_STRING
+        | ColumnType::MYSQL_TYPE_VAR_STRING
+        | ColumnType::MYSQL_TYPE_BLOB
+        | ColumnType::MYSQL_TYPE_TINY_BLOB
+        | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+        | ColumnType::MYSQL_TYPE_LONG_BLOB
+        | ColumnType::MYSQL_TYPE_SET
+        | ColumnType::MYSQL_TYPE_ENUM
+        | ColumnType::MYSQL_TYPE_DECIMAL
+        | ColumnType::MYSQL_TYPE_VARCHAR
+        | ColumnType::MYSQL_TYPE_BIT
+        | ColumnType::MYSQL_TYPE_NEWDECIMAL
+        | ColumnType::MYSQL_TYPE_GEOMETRY
+        | ColumnType::MYSQL_TYPE_JSON => {
+            ValueSerializer::<TextValue>::new(value)
+                .with_column_type(Some(column.column_type()))
+                .serialize(buf);
+            Some(())
+        }
+        // other types are not sent by servers
+        _ => None,
+    }
+}
+
+/// Writes an integer using `bytes` bytes (returns `None` if it doesn't fit).
+fn put_int(value: &Value, unsigned: bool, bytes: u8, buf: &mut Vec<u8>) -> Option<()> {
+    let value = match *value {
+        Value::Int(_) | Value::UInt(_) => value.clone(),
+        Value::Bytes(_) if unsigned => Value::UInt(from_value_opt(value.clone()).ok()?),
+        Value::Bytes(_) => Value::Int(from_value_opt(value.clone()).ok()?),
+        _ => return None,
+    };
+
+    match value.int_bytes(unsigned) {
+        Some(len) if len <= bytes => (),
+        _ => return None,
+    }
+
+    let x = match value {
+        Value::Int(x) => x as u64,
+        Value::UInt(x) => x,
+        _ => return None,
+    };
+    buf.put_uint_le(x, usize::from(bytes));
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_text_rows() {
+        let columns = vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG),
+            Column::new(ColumnType::MYSQL_TYPE_DATE),
+            Column::new(ColumnType::MYSQL_TYPE_DATETIME),
+            Column::new(ColumnType::MYSQL_TYPE_TIME),
+            Column::new(ColumnType::MYSQL_TYPE_DOUBLE),
+        ];
+        let row = vec![
+            Value::NULL,
+            Value::Date(2022, 1, 2, 3, 4, 5, 6),
+            Value::Date(2022, 1, 2, 3, 4, 5, 6),
+            Value::Time(true, 1, 2, 3, 4, 0),
+            Value::Double(f64::INFINITY),
+        ];
+
+        let mut buf = Vec::new();
+        RowSerializer::<Text>::new(&columns, &row)
+            .unwrap()
+            .write(&mut buf)
+            .unwrap();
+        assert_eq!(
+            buf,
+            b"\xfb\x0a2022-01-02\x1a2022-01-02 03:04:05.000006\x09-26:03:04\xfb".to_vec()
+        );
+
+        assert!(RowSerializer::<Text>::new(&columns, &[]).is_err());
+    }
+
+    #[test]
+    fn should_write_binary_rows() {
+        let columns = vec![
+            Column::new(ColumnType::MYSQL_TYPE_TINY).with_flags(ColumnFlags::UNSIGNED_FLAG),
+            Column::new(ColumnType::MYSQL_TYPE_DOUBLE),
+            Column::new(ColumnType::MYSQL_TYPE_DATE),
+            Column::new(ColumnType::MYSQL_TYPE_BLOB),
+        ];
+
+        let row = vec![
+            Value::UInt(255),
+            Value::Double(f64::NAN),
+            Value::Date(2022, 1, 2, 0, 0, 0, 0),
+            Value::Bytes(b"foo".to_vec()),
+        ];
+        let mut buf = Vec::new();
+        RowSerializer::<Binary>::new(&columns, &row)
+            .unwrap()
+            .write(&mut buf)
+            .unwrap();
+        assert_eq!(buf, b"\x00\x08\xff\x04\xe6\x07\x01\x02\x03foo".to_vec());
+
+        assert_eq!(
+            RowSerializer::<Binary>::new(&columns, &row[..1]),
+            Err(RowWriteError::WrongLength {
+                expected: 4,
+                given: 1
+            })
+        );
+
+        let row = vec![Value::Int(256), Value::NULL, Value::NULL, Value::NULL];
+        let mut buf = Vec::new();
+        assert_eq!(
+            RowSerializer::<Binary>::new(&columns, &row)
+                .unwrap()
+                .write(&mut buf),
+            Err(RowWriteError::IncompatibleValue {
+                index: 0,
+                value: Value::Int(256),
+                column_type: ColumnType::MYSQL_TYPE_TINY,
+            })
+        );
+        assert!(buf.is_empty());
+    }
+}
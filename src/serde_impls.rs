@@ -0,0 +1,190 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `serde::Serialize` implementations for `Value`, `Column` and `Row`.
+
+use serde::ser::{Serialize, SerializeMap, SerializeStruct, Serializer};
+
+use std::{fmt, str::from_utf8};
+
+use crate::{
+    packets::Column,
+    row::Row,
+    value::{
+        convert::temporal::{MysqlDate, MysqlDateTime, MysqlTime},
+        Value,
+    },
+};
+
+#[cfg(feature = "serde_packets")]
+mod packets;
+
+/// Textual representation of temporal values (same as in MySql text protocol).
+struct Temporal<'a>(&'a Value);
+
+impl fmt::Display for Temporal<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self.0 {
+            Value::Date(year, month, day, 0, 0, 0, 0) => {
+                fmt::Display::fmt(&MysqlDate::new(year, month, day), f)
+            }
+            Value::Date(year, month, day, hour, minute, second, micros) => fmt::Display::fmt(
+                &MysqlDateTime::new(year, month, day, hour, minute, second, micros),
+                f,
+            ),
+            Value::Time(neg, days, hours, minutes, seconds, micros) => fmt::Display::fmt(
+                &MysqlTime::new(neg, days, hours, minutes, seconds, micros),
+                f,
+            ),
+            _ => unreachable!("not a temporal value"),
+        }
+    }
+}
+
+/// Representation:
+///
+/// *   `NULL` is serialized as none (`null` in JSON);
+/// *   numbers are serialized as numbers;
+/// *   `Bytes` are serialized as a string if they're valid UTF-8,
+///     and as bytes otherwise (representation of bytes depends on the format,
+///     i.e. JSON uses an array of numbers);
+/// *   `Date` is serialized as `YYYY-MM-DD` or `YYYY-MM-DD hh:mm:ss[.ffffff]` string;
+/// *   `Time` is serialized as `[-]hh:mm:ss[.ffffff]` string (hours may exceed 24).
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Value::NULL => serializer.serialize_none(),
+            Value::Bytes(ref bytes) => match from_utf8(bytes) {
+                Ok(string) => serializer.serialize_str(string),
+                Err(_) => serializer.serialize_bytes(bytes),
+            },
+            Value::Int(x) => serializer.serialize_i64(x),
+            Value::UInt(x) => serializer.serialize_u64(x),
+            Value::Float(x) => serializer.serialize_f32(x),
+            Value::Double(x) => serializer.serialize_f64(x),
+            Value::Date(..) | Value::Time(..) => serializer.collect_str(&Temporal(self)),
+        }
+    }
+}
+
+/// Serialized as a struct. Names are lossy converted to UTF-8, `column_type` is serialized
+/// as a string (i.e. `"MYSQL_TYPE_LONG"`), `flags` — as a number.
+impl Serialize for Column {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Column", 10)?;
+        state.serialize_field("schema", &self.schema_str())?;
+        state.serialize_field("table", &self.table_str())?;
+        state.serialize_field("org_table", &self.org_table_str())?;
+        state.serialize_field("name", &self.name_str())?;
+        state.serialize_field("org_name", &self.org_name_str())?;
+        state.serialize_field("column_type", &format!("{:?}", self.column_type()))?;
+        state.serialize_field("character_set", &self.character_set())?;
+        state.serialize_field("column_length", &self.column_length())?;
+        state.serialize_field("flags", &self.flags().bits())?;
+        state.serialize_field("decimals", &self.decimals())?;
+        state.end()
+    }
+}
+
+/// Serialized as a map from column name to the value (see `Serialize` for `Value`).
+///
+/// Columns taken using `Row::take` are skipped. Duplicate column names
+/// are serialized as duplicate keys.
+impl Serialize for Row {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let columns = self.columns_ref();
+        let len = (0..self.len())
+            .filter(|i| self.as_ref(*i).is_some())
+            .count();
+        let mut map = serializer.serialize_map(Some(len))?;
+        for (i, column) in columns.iter().enumerate() {
+            if let Some(value) = self.as_ref(i) {
+                map.serialize_entry(&column.name_str(), value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use std::sync::Arc;
+
+    use crate::{
+        constants::{ColumnFlags, ColumnType},
+        packets::Column,
+        row::new_row,
+        value::Value,
+    };
+
+    #[test]
+    fn should_serialize_values() {
+        let values = vec![
+            Value::NULL,
+            Value::Int(-1),
+            Value::UInt(u64::MAX),
+            Value::Double(0.5),
+            Value::Bytes(b"foo".to_vec()),
+            Value::Bytes(vec![0xff, 0x00]),
+            Value::Date(2021, 1, 2, 0, 0, 0, 0),
+            Value::Date(2021, 1, 2, 3, 4, 5, 60),
+            Value::Time(true, 1, 2, 3, 4, 0),
+        ];
+        assert_eq!(
+            serde_json::to_value(&values).unwrap(),
+            json!([
+                null,
+                -1,
+                u64::MAX,
+                0.5,
+                "foo",
+                [255, 0],
+                "2021-01-02",
+                "2021-01-02 03:04:05.000060",
+                "-26:03:04"
+            ])
+        );
+    }
+
+    #[test]
+    fn should_serialize_row() {
+        let columns = Arc::from(vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG)
+                .with_name(b"id")
+                .with_table(b"t")
+                .with_flags(ColumnFlags::PRI_KEY_FLAG),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"name"),
+        ]);
+        let mut row = new_row(vec![Value::Int(1), Value::Bytes(b"foo".to_vec())], columns);
+
+        assert_eq!(
+            serde_json::to_value(&row).unwrap(),
+            json!({ "id": 1, "name": "foo" })
+        );
+        assert_eq!(
+            serde_json::to_value(&row.columns_ref()[0]).unwrap(),
+            json!({
+                "schema": "",
+                "table": "t",
+                "org_table": "",
+                "name": "id",
+                "org_name": "",
+                "column_type": "MYSQL_TYPE_LONG",
+                "character_set": 0,
+                "column_length": 0,
+                "flags": 2,
+                "decimals": 0,
+            })
+        );
+
+        let _: Option<Value> = row.take("name");
+        assert_eq!(serde_json::to_value(&row).unwrap(), json!({ "id": 1 }));
+    }
+}
@@ -0,0 +1,428 @@
+// Copyright (c) 2022 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `Serialize`/`Deserialize` implementations for parsed packets
+//! (requires the `serde_packets` feature).
+//!
+//! Packets are serialized as structs of their fields. Byte strings are serialized as strings
+//! if they're valid UTF-8 and as bytes otherwise, flags are serialized as numbers.
+//! Borrowed packets are deserialized into their owned (`'static`) versions.
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use std::convert::TryFrom;
+
+use crate::{
+    binlog::events::BinlogEventHeader,
+    constants::{CapabilityFlags, ColumnFlags, ColumnType, MariadbCapabilities, StatusFlags},
+    io::ParseBuf,
+    packets::{Column, ErrPacket, HandshakePacket, OkPacket, ProgressReport, ServerError},
+};
+
+/// Byte string representation.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Bytes {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Bytes::Text(text.into()),
+            Err(_) => Bytes::Binary(bytes.into()),
+        }
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(bytes: Bytes) -> Self {
+        match bytes {
+            Bytes::Text(text) => text.into_bytes(),
+            Bytes::Binary(bytes) => bytes,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "OkPacket")]
+struct OkPacketRepr {
+    affected_rows: u64,
+    last_insert_id: Option<u64>,
+    status_flags: u16,
+    warnings: u16,
+    info: Option<Bytes>,
+    session_state_info: Option<Bytes>,
+}
+
+impl Serialize for OkPacket<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        OkPacketRepr {
+            affected_rows: self.affected_rows(),
+            last_insert_id: self.last_insert_id(),
+            status_flags: self.status_flags().bits(),
+            warnings: self.warnings(),
+            info: self.info_ref().map(Bytes::from),
+            session_state_info: self.session_state_info_ref().map(Bytes::from),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OkPacket<'static> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = OkPacketRepr::deserialize(deserializer)?;
+        Ok(OkPacket::new(
+            repr.affected_rows,
+            repr.last_insert_id,
+            StatusFlags::from_bits_truncate(repr.status_flags),
+            repr.warnings,
+        )
+        .with_info(repr.info.map(Vec::from).unwrap_or_default())
+        .with_session_state_info(repr.session_state_info.map(Vec::from).unwrap_or_default()))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "ServerError")]
+struct ServerErrorRepr {
+    code: u16,
+    state: String,
+    message: Bytes,
+}
+
+impl From<&ServerError<'_>> for ServerErrorRepr {
+    fn from(error: &ServerError<'_>) -> Self {
+        Self {
+            code: error.error_code(),
+            state: error.sql_state_str().into_owned(),
+            message: error.message_ref().into(),
+        }
+    }
+}
+
+impl TryFrom<ServerErrorRepr> for ServerError<'static> {
+    type Error = String;
+
+    fn try_from(repr: ServerErrorRepr) -> Result<Self, Self::Error> {
+        let state = <[u8; 5]>::try_from(repr.state.as_bytes())
+            .map_err(|_| format!("invalid SQL state `{}`", repr.state))?;
+        Ok(ServerError::new(repr.code, state, Vec::from(repr.message)))
+    }
+}
+
+impl Serialize for ServerError<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ServerErrorRepr::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ServerError<'static> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ServerErrorRepr::deserialize(deserializer)
+            .and_then(|repr| ServerError::try_from(repr).map_err(D::Error::custom))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "ProgressReport")]
+struct ProgressReportRepr {
+    stage: u8,
+    max_stage: u8,
+    progress: u32,
+    stage_info: Bytes,
+}
+
+impl From<&ProgressReport<'_>> for ProgressReportRepr {
+    fn from(report: &ProgressReport<'_>) -> Self {
+        Self {
+            stage: report.stage(),
+            max_stage: report.max_stage(),
+            progress: report.progress(),
+            stage_info: report.stage_info_ref().into(),
+        }
+    }
+}
+
+impl From<ProgressReportRepr> for ProgressReport<'static> {
+    fn from(repr: ProgressReportRepr) -> Self {
+        ProgressReport::new(
+            repr.stage,
+            repr.max_stage,
+            repr.progress,
+            Vec::from(repr.stage_info),
+        )
+    }
+}
+
+impl Serialize for ProgressReport<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ProgressReportRepr::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProgressReport<'static> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ProgressReportRepr::deserialize(deserializer).map(Into::into)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "ErrPacket")]
+enum ErrPacketRepr {
+    Error(ServerErrorRepr),
+    Progress(ProgressReportRepr),
+}
+
+/// Serialized as an externally tagged enum (i.e. `{"Error": {...}}` in JSON).
+impl Serialize for ErrPacket<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ErrPacket::Error(error) => ErrPacketRepr::Error(error.into()),
+            ErrPacket::Progress(report) => ErrPacketRepr::Progress(report.into()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrPacket<'static> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match ErrPacketRepr::deserialize(deserializer)? {
+            ErrPacketRepr::Error(repr) => ServerError::try_from(repr)
+                .map(ErrPacket::Error)
+                .map_err(D::Error::custom),
+            ErrPacketRepr::Progress(repr) => Ok(ErrPacket::Progress(repr.into())),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "HandshakePacket")]
+struct HandshakePacketRepr {
+    protocol_version: u8,
+    server_version: Bytes,
+    connection_id: u32,
+    scramble_1: [u8; 8],
+    scramble_2: Option<Vec<u8>>,
+    capabilities: u32,
+    mariadb_capabilities: u32,
+    default_collation: u8,
+    status_flags: u16,
+    auth_plugin_name: Option<Bytes>,
+}
+
+impl Serialize for HandshakePacket<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut scramble_1 = [0_u8; 8];
+        scramble_1.copy_from_slice(self.scramble_1_ref());
+        HandshakePacketRepr {
+            protocol_version: self.protocol_version(),
+            server_version: self.server_version_ref().into(),
+            connection_id: self.connection_id(),
+            scramble_1,
+            scramble_2: self.scramble_2_ref().map(Vec::from),
+            capabilities: self.capabilities().bits(),
+            mariadb_capabilities: self.mariadb_capabilities().bits(),
+            default_collation: self.default_collation(),
+            status_flags: self.status_flags().bits(),
+            auth_plugin_name: self.auth_plugin_name_ref().map(Bytes::from),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for HandshakePacket<'static> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = HandshakePacketRepr::deserialize(deserializer)?;
+        Ok(HandshakePacket::new(
+            repr.protocol_version,
+            Vec::from(repr.server_version),
+            repr.connection_id,
+            repr.scramble_1,
+            repr.scramble_2,
+            CapabilityFlags::from_bits_truncate(repr.capabilities),
+            repr.default_collation,
+            StatusFlags::from_bits_truncate(repr.status_flags),
+            repr.auth_plugin_name.map(Vec::from),
+        )
+        .with_mariadb_capabilities(MariadbCapabilities::from_bits_truncate(
+            repr.mariadb_capabilities,
+        )))
+    }
+}
+
+/// Mirrors `Serialize` implementation for `Column`.
+#[derive(Deserialize)]
+#[serde(rename = "Column")]
+struct ColumnRepr {
+    schema: String,
+    table: String,
+    org_table: String,
+    name: String,
+    org_name: String,
+    column_type: String,
+    character_set: u16,
+    column_length: u32,
+    flags: u16,
+    decimals: u8,
+}
+
+/// Accepts the representation produced by `Serialize` implementation for `Column`.
+impl<'de> Deserialize<'de> for Column {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ColumnRepr::deserialize(deserializer)?;
+        let column_type = (0..=u8::MAX)
+            .filter_map(|x| ColumnType::try_from(x).ok())
+            .find(|x| format!("{:?}", x) == repr.column_type)
+            .ok_or_else(|| {
+                D::Error::custom(format!("unknown column type `{}`", repr.column_type))
+            })?;
+        Ok(Column::new(column_type)
+            .with_schema(repr.schema.as_bytes())
+            .with_table(repr.table.as_bytes())
+            .with_org_table(repr.org_table.as_bytes())
+            .with_name(repr.name.as_bytes())
+            .with_org_name(repr.org_name.as_bytes())
+            .with_character_set(repr.character_set)
+            .with_column_length(repr.column_length)
+            .with_flags(ColumnFlags::from_bits_truncate(repr.flags))
+            .with_decimals(repr.decimals))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "BinlogEventHeader")]
+struct BinlogEventHeaderRepr {
+    timestamp: u32,
+    /// Raw event type (unknown event types are preserved).
+    event_type: u8,
+    server_id: u32,
+    event_size: u32,
+    log_pos: u32,
+    /// Raw flags.
+    flags: u16,
+}
+
+impl Serialize for BinlogEventHeader {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BinlogEventHeaderRepr {
+            timestamp: self.timestamp(),
+            event_type: self.event_type_raw(),
+            server_id: self.server_id(),
+            event_size: self.event_size(),
+            log_pos: self.log_pos(),
+            flags: self.flags_raw(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BinlogEventHeader {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BinlogEventHeaderRepr::deserialize(deserializer)?;
+        // raw event type and flags are only settable through the wire representation
+        let mut buf = Vec::with_capacity(BinlogEventHeader::LEN);
+        buf.extend_from_slice(&repr.timestamp.to_le_bytes());
+        buf.push(repr.event_type);
+        buf.extend_from_slice(&repr.server_id.to_le_bytes());
+        buf.extend_from_slice(&repr.event_size.to_le_bytes());
+        buf.extend_from_slice(&repr.log_pos.to_le_bytes());
+        buf.extend_from_slice(&repr.flags.to_le_bytes());
+        ParseBuf(&buf).parse(()).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        binlog::{
+            consts::{EventFlags, EventType},
+            events::BinlogEventHeader,
+        },
+        constants::{CapabilityFlags, ColumnFlags, ColumnType, StatusFlags},
+        packets::{Column, ErrPacket, HandshakePacket, OkPacket, ProgressReport, ServerError},
+    };
+
+    #[test]
+    fn should_serialize_and_deserialize_packets() {
+        let ok = OkPacket::new(1, Some(42), StatusFlags::SERVER_STATUS_AUTOCOMMIT, 0)
+            .with_info(&b"Rows matched: 1  Changed: 1  Warnings: 0"[..])
+            .with_session_state_info(&[0xff_u8, 0x00][..]);
+        let value = serde_json::to_value(&ok).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "affected_rows": 1,
+                "last_insert_id": 42,
+                "status_flags": 2,
+                "warnings": 0,
+                "info": "Rows matched: 1  Changed: 1  Warnings: 0",
+                "session_state_info": [255, 0],
+            })
+        );
+        assert_eq!(serde_json::from_value::<OkPacket>(value).unwrap(), ok);
+
+        let packets = vec![
+            ErrPacket::Error(ServerError::new(1045, *b"28000", &b"Access denied"[..])),
+            ErrPacket::Progress(ProgressReport::new(1, 2, 500, &b"copy to tmp table"[..])),
+        ];
+        let value = serde_json::to_value(&packets).unwrap();
+        assert_eq!(value[0]["Error"]["state"], json!("28000"));
+        assert_eq!(
+            serde_json::from_value::<Vec<ErrPacket>>(value).unwrap(),
+            packets
+        );
+        assert!(serde_json::from_value::<ServerError>(
+            json!({ "code": 1, "state": "HY", "message": "" })
+        )
+        .is_err());
+
+        let handshake = HandshakePacket::new(
+            10,
+            &b"8.0.27"[..],
+            7,
+            *b"01234567",
+            Some(&b"89abcdefghij\0"[..]),
+            CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_PLUGIN_AUTH,
+            45,
+            StatusFlags::SERVER_STATUS_AUTOCOMMIT,
+            Some(&b"mysql_native_password"[..]),
+        );
+        let value = serde_json::to_value(&handshake).unwrap();
+        assert_eq!(value["server_version"], json!("8.0.27"));
+        assert_eq!(
+            serde_json::from_value::<HandshakePacket>(value).unwrap(),
+            handshake
+        );
+
+        let column = Column::new(ColumnType::MYSQL_TYPE_LONG)
+            .with_name(b"id")
+            .with_flags(ColumnFlags::PRI_KEY_FLAG | ColumnFlags::NOT_NULL_FLAG)
+            .with_column_length(11);
+        let value = serde_json::to_value(&column).unwrap();
+        assert_eq!(serde_json::from_value::<Column>(value).unwrap(), column);
+
+        let header = BinlogEventHeader::new(
+            1_650_000_000,
+            EventType::QUERY_EVENT,
+            1,
+            100,
+            4,
+            EventFlags::LOG_EVENT_BINLOG_IN_USE_F,
+        );
+        let value = serde_json::to_value(&header).unwrap();
+        assert_eq!(value["event_type"], json!(EventType::QUERY_EVENT as u8));
+        assert_eq!(
+            serde_json::from_value::<BinlogEventHeader>(value).unwrap(),
+            header
+        );
+    }
+}
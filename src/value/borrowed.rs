@@ -0,0 +1,184 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use bytes::BufMut;
+
+use crate::{io::BufMutExt, misc::lenenc_str_len, proto::MySerialize};
+
+use super::Value;
+
+/// Borrowed counterpart of a [`Value`].
+///
+/// Used as a parameter to a prepared statement, so that large byte strings
+/// are not cloned before serialization (see [`ParamsRef`]).
+///
+/// [`ParamsRef`]: crate::params::ParamsRef
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum ValueRef<'a> {
+    NULL,
+    Bytes(&'a [u8]),
+    Int(i64),
+    UInt(u64),
+    Float(f32),
+    Double(f64),
+    /// year, month, day, hour, minutes, seconds, micro seconds
+    Date(u16, u8, u8, u8, u8, u8, u32),
+    /// is negative, days, hours, minutes, seconds, micro seconds
+    Time(bool, u32, u8, u8, u8, u32),
+}
+
+impl ValueRef<'_> {
+    /// Returns length in binary serialized form.
+    ///
+    /// NaN and infinite floats are serialized as `NULL` (see [`Value::try_from_f64`]).
+    pub fn bin_len(&self) -> u64 {
+        match self.finite_or_null() {
+            ValueRef::NULL => 0,
+            ValueRef::Bytes(x) => lenenc_str_len(x),
+            ValueRef::Int(_) => 8,
+            ValueRef::UInt(_) => 8,
+            ValueRef::Float(_) => 4,
+            ValueRef::Double(_) => 8,
+            ValueRef::Date(0u16, 0u8, 0u8, 0u8, 0u8, 0u8, 0u32) => 1,
+            ValueRef::Date(_, _, _, 0u8, 0u8, 0u8, 0u32) => 5,
+            ValueRef::Date(_, _, _, _, _, _, 0u32) => 8,
+            ValueRef::Date(_, _, _, _, _, _, _) => 12,
+            ValueRef::Time(_, 0u32, 0u8, 0u8, 0u8, 0u32) => 1,
+            ValueRef::Time(_, _, _, _, _, 0u32) => 9,
+            ValueRef::Time(_, _, _, _, _, _) => 13,
+        }
+    }
+
+    /// Returns `NULL` for NaN and infinite floats (MySql can't store them, so they are sent
+    /// as `NULL` in both text and binary protocols), otherwise returns a copy of this value.
+    pub(crate) fn finite_or_null(&self) -> Self {
+        match *self {
+            ValueRef::Float(x) if !x.is_finite() => ValueRef::NULL,
+            ValueRef::Double(x) if !x.is_finite() => ValueRef::NULL,
+            x => x,
+        }
+    }
+
+    /// Returns an owned copy of this value.
+    pub fn to_value(&self) -> Value {
+        match *self {
+            ValueRef::NULL => Value::NULL,
+            ValueRef::Bytes(x) => Value::Bytes(x.to_vec()),
+            ValueRef::Int(x) => Value::Int(x),
+            ValueRef::UInt(x) => Value::UInt(x),
+            ValueRef::Float(x) => Value::Float(x),
+            ValueRef::Double(x) => Value::Double(x),
+            ValueRef::Date(y, m, d, h, i, s, u) => Value::Date(y, m, d, h, i, s, u),
+            ValueRef::Time(n, d, h, i, s, u) => Value::Time(n, d, h, i, s, u),
+        }
+    }
+}
+
+impl<'a> From<&'a Value> for ValueRef<'a> {
+    fn from(x: &'a Value) -> Self {
+        match *x {
+            Value::NULL => ValueRef::NULL,
+            Value::Bytes(ref x) => ValueRef::Bytes(x),
+            Value::Int(x) => ValueRef::Int(x),
+            Value::UInt(x) => ValueRef::UInt(x),
+            Value::Float(x) => ValueRef::Float(x),
+            Value::Double(x) => ValueRef::Double(x),
+            Value::Date(y, m, d, h, i, s, u) => ValueRef::Date(y, m, d, h, i, s, u),
+            Value::Time(n, d, h, i, s, u) => ValueRef::Time(n, d, h, i, s, u),
+        }
+    }
+}
+
+impl<'a> From<&'a [u8]> for ValueRef<'a> {
+    fn from(x: &'a [u8]) -> Self {
+        ValueRef::Bytes(x)
+    }
+}
+
+impl<'a> From<&'a str> for ValueRef<'a> {
+    fn from(x: &'a str) -> Self {
+        ValueRef::Bytes(x.as_bytes())
+    }
+}
+
+impl From<ValueRef<'_>> for Value {
+    fn from(x: ValueRef<'_>) -> Self {
+        x.to_value()
+    }
+}
+
+impl MySerialize for ValueRef<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        match self.finite_or_null() {
+            ValueRef::NULL => (),
+            ValueRef::Bytes(x) => {
+                buf.put_lenenc_str(x);
+            }
+            ValueRef::Int(x) => {
+                buf.put_i64_le(x);
+            }
+            ValueRef::UInt(x) => {
+                buf.put_u64_le(x);
+            }
+            ValueRef::Float(x) => {
+                buf.put_f32_le(x);
+            }
+            ValueRef::Double(x) => {
+                buf.put_f64_le(x);
+            }
+            ValueRef::Date(0u16, 0u8, 0u8, 0u8, 0u8, 0u8, 0u32) => {
+                buf.put_u8(0);
+            }
+            ValueRef::Date(year, mon, day, 0u8, 0u8, 0u8, 0u32) => {
+                buf.put_u8(4);
+                buf.put_u16_le(year);
+                buf.put_u8(mon);
+                buf.put_u8(day);
+            }
+            ValueRef::Date(year, mon, day, hour, min, sec, 0u32) => {
+                buf.put_u8(7);
+                buf.put_u16_le(year);
+                buf.put_u8(mon);
+                buf.put_u8(day);
+                buf.put_u8(hour);
+                buf.put_u8(min);
+                buf.put_u8(sec);
+            }
+            ValueRef::Date(year, mon, day, hour, min, sec, usec) => {
+                buf.put_u8(11);
+                buf.put_u16_le(year);
+                buf.put_u8(mon);
+                buf.put_u8(day);
+                buf.put_u8(hour);
+                buf.put_u8(min);
+                buf.put_u8(sec);
+                buf.put_u32_le(usec);
+            }
+            ValueRef::Time(_, 0u32, 0u8, 0u8, 0u8, 0u32) => {
+                buf.put_u8(0);
+            }
+            ValueRef::Time(neg, d, h, m, s, 0u32) => {
+                buf.put_u8(8);
+                buf.put_u8(if neg { 1 } else { 0 });
+                buf.put_u32_le(d);
+                buf.put_u8(h);
+                buf.put_u8(m);
+                buf.put_u8(s);
+            }
+            ValueRef::Time(neg, days, hours, mins, secs, usecs) => {
+                buf.put_u8(12);
+                buf.put_u8(if neg { 1 } else { 0 });
+                buf.put_u32_le(days);
+                buf.put_u8(hours);
+                buf.put_u8(mins);
+                buf.put_u8(secs);
+                buf.put_u32_le(usecs);
+            }
+        }
+    }
+}
@@ -15,7 +15,9 @@ use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use crate::value::Value;
 
 use super::{
-    parse_mysql_datetime_string, parse_mysql_time_string, ConvIr, FromValueError, ParseIr,
+    parse_mysql_datetime_string, parse_mysql_time_string,
+    rounding::{FracSecsRounding, InexactFracSecsError, RoundFracSecs},
+    ConvIr, FromValueError, ParseIr,
 };
 
 impl ConvIr<NaiveDateTime> for ParseIr<NaiveDateTime> {
@@ -227,7 +229,94 @@ impl From<NaiveTime> for Value {
     }
 }
 
+impl RoundFracSecs for NaiveDateTime {
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let adjustment = rounding.adjustment(self.second() as u64, self.nanosecond(), fsp)?;
+        Ok(self + chrono::Duration::nanoseconds(adjustment))
+    }
+}
+
+impl RoundFracSecs for NaiveTime {
+    /// Note, that the result will wrap around the midnight, if rounded up.
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let adjustment = rounding.adjustment(self.second() as u64, self.nanosecond(), fsp)?;
+        Ok(self + chrono::Duration::nanoseconds(adjustment))
+    }
+}
+
+impl RoundFracSecs for chrono::Duration {
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let subsec_nanos = (self - chrono::Duration::seconds(self.num_seconds()))
+            .num_nanoseconds()
+            .unwrap_or_default();
+        let adjustment = rounding.signed_adjustment(
+            subsec_nanos < 0,
+            self.num_seconds().unsigned_abs(),
+            subsec_nanos.unsigned_abs() as u32,
+            fsp,
+        )?;
+        Ok(self + chrono::Duration::nanoseconds(adjustment))
+    }
+}
+
 impl_from_value!(NaiveDateTime, ParseIr<NaiveDateTime>);
 impl_from_value!(NaiveDate, ParseIr<NaiveDate>);
 impl_from_value!(NaiveTime, ParseIr<NaiveTime>);
 impl_from_value!(chrono::Duration, ParseIr<chrono::Duration>);
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+    use crate::value::convert::rounding::FracSecs;
+
+    #[test]
+    fn should_convert_with_rounding_policy() {
+        let datetime = NaiveDate::from_ymd(2021, 12, 31).and_hms_nano(23, 59, 59, 999_999_600);
+        assert_eq!(
+            Value::from(datetime),
+            Value::Date(2021, 12, 31, 23, 59, 59, 999_999)
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(datetime, 6, FracSecsRounding::RoundHalfEven)),
+            Ok(Value::Date(2022, 1, 1, 0, 0, 0, 0))
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(datetime, 2, FracSecsRounding::Truncate)),
+            Ok(Value::Date(2021, 12, 31, 23, 59, 59, 990_000))
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(datetime, 6, FracSecsRounding::Error)),
+            Err(InexactFracSecsError { fsp: 6 })
+        );
+
+        let time = NaiveTime::from_hms_nano(10, 0, 0, 123_456_500);
+        assert_eq!(
+            Value::try_from(FracSecs::new(time, 6, FracSecsRounding::RoundHalfEven)),
+            Ok(Value::Time(false, 0, 10, 0, 0, 123_456))
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(time, 3, FracSecsRounding::RoundHalfEven)),
+            Ok(Value::Time(false, 0, 10, 0, 0, 123_000))
+        );
+
+        let duration = -chrono::Duration::nanoseconds(1_500_000_000);
+        assert_eq!(
+            duration.round_frac_secs(0, FracSecsRounding::RoundHalfEven),
+            Ok(-chrono::Duration::seconds(2))
+        );
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module implements conversion from/to `Value` for `CompactString`.
+//!
+//! Short strings are stored inline, so converting a typical short `VARCHAR` value
+//! won't lead to an additional heap allocation.
+
+#![cfg(feature = "compact_str")]
+
+use std::str::from_utf8;
+
+use compact_str::CompactString;
+
+use crate::value::Value;
+
+use super::{ConvIr, FromValue, FromValueError};
+
+impl From<CompactString> for Value {
+    fn from(x: CompactString) -> Value {
+        Value::Bytes(x.as_bytes().to_vec())
+    }
+}
+
+/// Intermediate result of a Value-to-CompactString conversion.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CompactStringIr {
+    bytes: Vec<u8>,
+    output: CompactString,
+}
+
+impl ConvIr<CompactString> for CompactStringIr {
+    fn new(v: Value) -> Result<CompactStringIr, FromValueError> {
+        match v {
+            Value::Bytes(bytes) => match from_utf8(&bytes) {
+                Ok(x) => Ok(CompactStringIr {
+                    output: CompactString::new(x),
+                    bytes,
+                }),
+                Err(_) => Err(FromValueError(Value::Bytes(bytes))),
+            },
+            v => Err(FromValueError(v)),
+        }
+    }
+    fn commit(self) -> CompactString {
+        self.output
+    }
+    fn rollback(self) -> Value {
+        Value::Bytes(self.bytes)
+    }
+}
+
+impl FromValue for CompactString {
+    type Intermediate = CompactStringIr;
+}
+
+#[cfg(test)]
+mod tests {
+    use compact_str::CompactString;
+    use proptest::prelude::*;
+
+    use crate::value::{
+        convert::{from_value, from_value_opt},
+        Value,
+    };
+
+    proptest! {
+        #[test]
+        fn compact_string_roundtrip(s: String) {
+            let val = Value::Bytes(s.as_bytes().to_vec());
+            let output = from_value::<CompactString>(val.clone());
+            assert_eq!(output.as_str(), s.as_str());
+            assert_eq!(Value::from(output), val);
+        }
+    }
+
+    #[test]
+    fn should_not_convert_invalid_utf8() {
+        let val = Value::Bytes(vec![0xff]);
+        assert!(from_value_opt::<CompactString>(val).is_err());
+        assert!(from_value_opt::<CompactString>(Value::Int(1)).is_err());
+    }
+}
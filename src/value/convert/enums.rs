@@ -0,0 +1,198 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module implements conversion from/to `Value` for field-less enums
+//! with an integer representation (see [`IntReprEnum`]).
+
+use std::fmt;
+
+use crate::value::Value;
+
+use super::{ConvIr, FromValue, FromValueError};
+
+/// Field-less enum with an integer representation.
+///
+/// Use `#[derive(IntReprEnum)]` to implement this trait (requires the `derive` feature).
+/// Conversion from a `Value` fails if the value is out of range of the representation.
+/// Unknown discriminants will be converted to the `#[mysql(fallback)]` variant, if it is
+/// given, otherwise the conversion fails. The same applies to the derived `TryFrom<Repr>`
+/// implementation (the error is [`UnknownDiscriminant`]).
+///
+/// ```
+/// # use std::convert::TryFrom;
+/// # use mysql_common::{value::{Value, convert::from_value}, IntReprEnum};
+/// /// Status of something.
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, IntReprEnum)]
+/// #[repr(u8)]
+/// pub enum Status {
+///     #[mysql(fallback)]
+///     Unknown = 0,
+///     Active = 1,
+///     Inactive = 2,
+/// }
+///
+/// assert_eq!(from_value::<Status>(Value::Int(1)), Status::Active);
+/// assert_eq!(from_value::<Status>(Value::Bytes(b"2".to_vec())), Status::Inactive);
+/// assert_eq!(from_value::<Status>(Value::Int(42)), Status::Unknown);
+/// assert_eq!(Value::from(Status::Inactive), Value::UInt(2));
+/// assert_eq!(Status::try_from(2), Ok(Status::Inactive));
+/// ```
+///
+/// Use a `#[mysql(other)]` variant instead of the fallback to keep unknown discriminants
+/// (other variants must have explicit discriminants in this case):
+///
+/// ```
+/// # use mysql_common::{value::{Value, convert::from_value}, IntReprEnum};
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, IntReprEnum)]
+/// #[repr(u8)]
+/// pub enum Status {
+///     Active = 1,
+///     Inactive = 2,
+///     #[mysql(other)]
+///     Other(u8),
+/// }
+///
+/// assert_eq!(from_value::<Status>(Value::Int(42)), Status::Other(42));
+/// assert_eq!(Value::from(Status::Other(42)), Value::UInt(42));
+/// assert_eq!(Status::from(1), Status::Active);
+/// assert_eq!(u8::from(Status::Inactive), 2);
+/// ```
+pub trait IntReprEnum: Sized {
+    /// Integer representation of this enum (e.g. `u8` for `#[repr(u8)]`).
+    type Repr: FromValue + Into<Value>;
+
+    /// Returns a variant for the given discriminant (if any).
+    fn from_repr(repr: Self::Repr) -> Option<Self>;
+
+    /// Returns the discriminant of this variant.
+    fn to_repr(&self) -> Self::Repr;
+
+    /// Returns a variant, that should be used for unknown discriminants (if any).
+    fn fallback() -> Option<Self> {
+        None
+    }
+}
+
+/// Error returned by derived `TryFrom` implementations of an [`IntReprEnum`]
+/// for unknown discriminants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnknownDiscriminant<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for UnknownDiscriminant<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown enum discriminant {}", self.0)
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> std::error::Error for UnknownDiscriminant<T> {}
+
+/// Intermediate result of a Value-to-IntReprEnum conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntReprEnumIr<T> {
+    value: Value,
+    output: T,
+}
+
+impl<T: IntReprEnum> ConvIr<T> for IntReprEnumIr<T> {
+    fn new(v: Value) -> Result<IntReprEnumIr<T>, FromValueError> {
+        let value = v.clone();
+        // this will fail if value is out of range of the `T::Repr`
+        let repr = T::Repr::from_value_opt(v)?;
+        match T::from_repr(repr).or_else(T::fallback) {
+            Some(output) => Ok(IntReprEnumIr { value, output }),
+            None => Err(FromValueError(value)),
+        }
+    }
+    fn commit(self) -> T {
+        self.output
+    }
+    fn rollback(self) -> Value {
+        self.value
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::UnknownDiscriminant;
+    use crate::value::{
+        convert::{from_value, from_value_opt},
+        Value,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, crate::IntReprEnum)]
+    #[repr(i32)]
+    enum Strict {
+        Negative = -1,
+        Zero = 0,
+        Large = 100_000,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, crate::IntReprEnum)]
+    #[repr(u8)]
+    enum WithFallback {
+        #[mysql(fallback)]
+        Unknown = 0,
+        One = 1,
+    }
+
+    #[test]
+    fn should_convert_int_repr_enum() {
+        assert_eq!(from_value::<Strict>(Value::Int(-1)), Strict::Negative);
+        assert_eq!(from_value::<Strict>(Value::UInt(0)), Strict::Zero);
+        assert_eq!(
+            from_value::<Strict>(Value::Bytes(b"100000".to_vec())),
+            Strict::Large
+        );
+        assert_eq!(Value::from(Strict::Negative), Value::Int(-1));
+        assert_eq!(Value::from(Strict::Large), Value::Int(100_000));
+        assert!(from_value_opt::<Strict>(Value::Int(1)).is_err());
+        assert!(from_value_opt::<Strict>(Value::Int(i64::MAX)).is_err());
+
+        assert_eq!(from_value::<WithFallback>(Value::Int(1)), WithFallback::One);
+        assert_eq!(
+            from_value::<WithFallback>(Value::Int(42)),
+            WithFallback::Unknown
+        );
+        assert_eq!(Value::from(WithFallback::One), Value::UInt(1));
+        // out of range values are not subject to the fallback
+        assert!(from_value_opt::<WithFallback>(Value::Int(256)).is_err());
+        assert!(from_value_opt::<WithFallback>(Value::Int(-1)).is_err());
+        assert!(from_value_opt::<Option<WithFallback>>(Value::NULL)
+            .unwrap()
+            .is_none());
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, crate::IntReprEnum)]
+    #[repr(u8)]
+    enum WithOther {
+        Zero = 0,
+        One = 1,
+        #[mysql(other)]
+        Other(u8),
+    }
+
+    #[test]
+    fn should_convert_int_repr_enum_with_other() {
+        assert_eq!(Strict::try_from(100_000), Ok(Strict::Large));
+        assert_eq!(Strict::try_from(1), Err(UnknownDiscriminant(1)));
+        assert_eq!(WithFallback::try_from(42), Ok(WithFallback::Unknown));
+
+        assert_eq!(from_value::<WithOther>(Value::Int(1)), WithOther::One);
+        assert_eq!(
+            from_value::<WithOther>(Value::Int(42)),
+            WithOther::Other(42)
+        );
+        assert_eq!(Value::from(WithOther::Other(42)), Value::UInt(42));
+        assert_eq!(Value::from(WithOther::Zero), Value::UInt(0));
+        assert_eq!(WithOther::from(0), WithOther::Zero);
+        assert_eq!(u8::from(WithOther::Other(7)), 7);
+        assert!(from_value_opt::<WithOther>(Value::Int(256)).is_err());
+    }
+}
@@ -0,0 +1,421 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module implements conversion from/to `Value` for `geo_types` types.
+//!
+//! MySql represents a `GEOMETRY` value as a 4-byte little-endian SRID
+//! followed by the WKB (Well-Known Binary) representation of the geometry.
+
+#![cfg(feature = "geo")]
+
+use std::{convert::TryFrom, io};
+
+use geo_types::{
+    Coordinate, Geometry, GeometryCollection, LineString, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon,
+};
+
+use crate::{constants::GeometryType, io::ParseBuf, misc::unexpected_buf_eof, value::Value};
+
+use super::{ConvIr, FromValue, FromValueError};
+
+/// Maximum nesting depth of geometry collections accepted by [`parse_geometry`].
+const MAX_DEPTH: usize = 64;
+
+/// Size of a WKB point (two f64 coordinates).
+const POINT_SIZE: usize = 16;
+
+/// Parses a MySql geometry value (SRID followed by WKB).
+///
+/// Returns the SRID and the geometry.
+pub fn parse_geometry(bytes: &[u8]) -> io::Result<(u32, Geometry<f64>)> {
+    let mut buf = ParseBuf(bytes);
+    let srid = buf.checked_eat_u32_le().ok_or_else(unexpected_buf_eof)?;
+    let geometry = parse_wkb(&mut buf, 0)?;
+    if !buf.is_empty() {
+        return Err(invalid_wkb("trailing bytes after the geometry"));
+    }
+    Ok((srid, geometry))
+}
+
+/// Serializes a geometry into a MySql geometry value (SRID followed by WKB).
+///
+/// `Line`, `Rect` and `Triangle` are serialized as a `LineString` or a `Polygon`.
+pub fn serialize_geometry(srid: u32, geometry: &Geometry<f64>, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&srid.to_le_bytes());
+    write_wkb(geometry, buf);
+}
+
+fn invalid_wkb(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Invalid WKB: {}", msg))
+}
+
+struct WkbReader<'a, 'b> {
+    buf: &'b mut ParseBuf<'a>,
+    big_endian: bool,
+}
+
+impl WkbReader<'_, '_> {
+    fn u32(&mut self) -> io::Result<u32> {
+        if self.big_endian {
+            self.buf.checked_eat_u32_be()
+        } else {
+            self.buf.checked_eat_u32_le()
+        }
+        .ok_or_else(unexpected_buf_eof)
+    }
+
+    fn f64(&mut self) -> io::Result<f64> {
+        if self.big_endian {
+            self.buf.checked_eat_f64_be()
+        } else {
+            self.buf.checked_eat_f64_le()
+        }
+        .ok_or_else(unexpected_buf_eof)
+    }
+
+    /// Reads an element count, that is sane for the remaining buffer.
+    fn count(&mut self, min_elem_size: usize) -> io::Result<usize> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(min_elem_size) > self.buf.len() {
+            return Err(unexpected_buf_eof());
+        }
+        Ok(count)
+    }
+
+    fn coordinate(&mut self) -> io::Result<Coordinate<f64>> {
+        Ok(Coordinate {
+            x: self.f64()?,
+            y: self.f64()?,
+        })
+    }
+
+    fn line_string(&mut self) -> io::Result<LineString<f64>> {
+        let count = self.count(POINT_SIZE)?;
+        (0..count)
+            .map(|_| self.coordinate())
+            .collect::<io::Result<Vec<_>>>()
+            .map(LineString)
+    }
+
+    fn polygon(&mut self) -> io::Result<Polygon<f64>> {
+        let count = self.count(4)?;
+        let mut rings = (0..count)
+            .map(|_| self.line_string())
+            .collect::<io::Result<Vec<_>>>()?;
+        if rings.is_empty() {
+            return Ok(Polygon::new(LineString(Vec::new()), Vec::new()));
+        }
+        let exterior = rings.remove(0);
+        Ok(Polygon::new(exterior, rings))
+    }
+}
+
+fn parse_wkb(buf: &mut ParseBuf<'_>, depth: usize) -> io::Result<Geometry<f64>> {
+    if depth > MAX_DEPTH {
+        return Err(invalid_wkb("geometry collection is nested too deep"));
+    }
+
+    let big_endian = match buf.checked_eat_u8().ok_or_else(unexpected_buf_eof)? {
+        0 => true,
+        1 => false,
+        _ => return Err(invalid_wkb("unknown byte order")),
+    };
+    let mut reader = WkbReader { buf, big_endian };
+    let raw_type = reader.u32()?;
+    let geometry_type = u8::try_from(raw_type)
+        .ok()
+        .and_then(|x| GeometryType::try_from(x).ok())
+        .ok_or_else(|| invalid_wkb("unknown geometry type"))?;
+
+    let geometry = match geometry_type {
+        GeometryType::GEOM_POINT => Geometry::Point(Point(reader.coordinate()?)),
+        GeometryType::GEOM_LINESTRING => Geometry::LineString(reader.line_string()?),
+        GeometryType::GEOM_POLYGON => Geometry::Polygon(reader.polygon()?),
+        GeometryType::GEOM_MULTIPOINT
+        | GeometryType::GEOM_MULTILINESTRING
+        | GeometryType::GEOM_MULTIPOLYGON
+        | GeometryType::GEOM_GEOMETRYCOLLECTION => {
+            // every element is at least a byte order and a type
+            let count = reader.count(5)?;
+            let buf = reader.buf;
+            let elements = (0..count)
+                .map(|_| parse_wkb(buf, depth + 1))
+                .collect::<io::Result<Vec<_>>>()?;
+            collect_multi(geometry_type, elements)?
+        }
+        GeometryType::GEOM_GEOMETRY => return Err(invalid_wkb("unexpected geometry type")),
+    };
+
+    Ok(geometry)
+}
+
+fn collect_multi(
+    geometry_type: GeometryType,
+    elements: Vec<Geometry<f64>>,
+) -> io::Result<Geometry<f64>> {
+    fn collect<T>(
+        elements: Vec<Geometry<f64>>,
+        f: impl Fn(Geometry<f64>) -> Option<T>,
+    ) -> io::Result<Vec<T>> {
+        elements
+            .into_iter()
+            .map(|x| f(x).ok_or_else(|| invalid_wkb("unexpected element of a multi-geometry")))
+            .collect()
+    }
+
+    Ok(match geometry_type {
+        GeometryType::GEOM_MULTIPOINT => {
+            Geometry::MultiPoint(MultiPoint(collect(elements, |x| match x {
+                Geometry::Point(x) => Some(x),
+                _ => None,
+            })?))
+        }
+        GeometryType::GEOM_MULTILINESTRING => {
+            Geometry::MultiLineString(MultiLineString(collect(elements, |x| match x {
+                Geometry::LineString(x) => Some(x),
+                _ => None,
+            })?))
+        }
+        GeometryType::GEOM_MULTIPOLYGON => {
+            Geometry::MultiPolygon(MultiPolygon(collect(elements, |x| match x {
+                Geometry::Polygon(x) => Some(x),
+                _ => None,
+            })?))
+        }
+        _ => Geometry::GeometryCollection(GeometryCollection(elements)),
+    })
+}
+
+fn write_header(geometry_type: GeometryType, buf: &mut Vec<u8>) {
+    // little-endian byte order
+    buf.push(1);
+    buf.extend_from_slice(&(geometry_type as u32).to_le_bytes());
+}
+
+fn write_coordinate(coordinate: &Coordinate<f64>, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&coordinate.x.to_le_bytes());
+    buf.extend_from_slice(&coordinate.y.to_le_bytes());
+}
+
+fn write_line_string_body(line_string: &LineString<f64>, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(line_string.0.len() as u32).to_le_bytes());
+    for coordinate in &line_string.0 {
+        write_coordinate(coordinate, buf);
+    }
+}
+
+fn write_polygon_body(polygon: &Polygon<f64>, buf: &mut Vec<u8>) {
+    if polygon.exterior().0.is_empty() {
+        buf.extend_from_slice(&0_u32.to_le_bytes());
+        return;
+    }
+    buf.extend_from_slice(&(1 + polygon.interiors().len() as u32).to_le_bytes());
+    write_line_string_body(polygon.exterior(), buf);
+    for interior in polygon.interiors() {
+        write_line_string_body(interior, buf);
+    }
+}
+
+fn write_wkb(geometry: &Geometry<f64>, buf: &mut Vec<u8>) {
+    match geometry {
+        Geometry::Point(point) => {
+            write_header(GeometryType::GEOM_POINT, buf);
+            write_coordinate(&point.0, buf);
+        }
+        Geometry::Line(line) => {
+            write_wkb(
+                &Geometry::LineString(LineString(vec![line.start, line.end])),
+                buf,
+            );
+        }
+        Geometry::LineString(line_string) => {
+            write_header(GeometryType::GEOM_LINESTRING, buf);
+            write_line_string_body(line_string, buf);
+        }
+        Geometry::Polygon(polygon) => {
+            write_header(GeometryType::GEOM_POLYGON, buf);
+            write_polygon_body(polygon, buf);
+        }
+        Geometry::MultiPoint(multi_point) => {
+            write_header(GeometryType::GEOM_MULTIPOINT, buf);
+            buf.extend_from_slice(&(multi_point.0.len() as u32).to_le_bytes());
+            for point in &multi_point.0 {
+                write_wkb(&Geometry::Point(*point), buf);
+            }
+        }
+        Geometry::MultiLineString(multi_line_string) => {
+            write_header(GeometryType::GEOM_MULTILINESTRING, buf);
+            buf.extend_from_slice(&(multi_line_string.0.len() as u32).to_le_bytes());
+            for line_string in &multi_line_string.0 {
+                write_header(GeometryType::GEOM_LINESTRING, buf);
+                write_line_string_body(line_string, buf);
+            }
+        }
+        Geometry::MultiPolygon(multi_polygon) => {
+            write_header(GeometryType::GEOM_MULTIPOLYGON, buf);
+            buf.extend_from_slice(&(multi_polygon.0.len() as u32).to_le_bytes());
+            for polygon in &multi_polygon.0 {
+                write_header(GeometryType::GEOM_POLYGON, buf);
+                write_polygon_body(polygon, buf);
+            }
+        }
+        Geometry::GeometryCollection(collection) => {
+            write_header(GeometryType::GEOM_GEOMETRYCOLLECTION, buf);
+            buf.extend_from_slice(&(collection.0.len() as u32).to_le_bytes());
+            for geometry in &collection.0 {
+                write_wkb(geometry, buf);
+            }
+        }
+        Geometry::Rect(rect) => write_wkb(&Geometry::Polygon(rect.to_polygon()), buf),
+        Geometry::Triangle(triangle) => write_wkb(&Geometry::Polygon(triangle.to_polygon()), buf),
+    }
+}
+
+/// Intermediate result of a Value-to-geometry conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeometryIr<T> {
+    bytes: Vec<u8>,
+    output: T,
+}
+
+macro_rules! impl_geometry {
+    ($ty:ident) => {
+        impl From<$ty<f64>> for Value {
+            /// Geometry is serialized with SRID `0`.
+            fn from(x: $ty<f64>) -> Value {
+                let mut buf = Vec::new();
+                serialize_geometry(0, &Geometry::$ty(x), &mut buf);
+                Value::Bytes(buf)
+            }
+        }
+
+        impl ConvIr<$ty<f64>> for GeometryIr<$ty<f64>> {
+            fn new(v: Value) -> Result<Self, FromValueError> {
+                match v {
+                    Value::Bytes(bytes) => match parse_geometry(&bytes).map(|(_, x)| x) {
+                        Ok(Geometry::$ty(output)) => Ok(GeometryIr { bytes, output }),
+                        _ => Err(FromValueError(Value::Bytes(bytes))),
+                    },
+                    v => Err(FromValueError(v)),
+                }
+            }
+            fn commit(self) -> $ty<f64> {
+                self.output
+            }
+            fn rollback(self) -> Value {
+                Value::Bytes(self.bytes)
+            }
+        }
+
+        impl FromValue for $ty<f64> {
+            type Intermediate = GeometryIr<$ty<f64>>;
+        }
+    };
+}
+
+impl_geometry!(Point);
+impl_geometry!(LineString);
+impl_geometry!(Polygon);
+impl_geometry!(MultiPoint);
+impl_geometry!(MultiLineString);
+impl_geometry!(MultiPolygon);
+impl_geometry!(GeometryCollection);
+
+impl From<Geometry<f64>> for Value {
+    /// Geometry is serialized with SRID `0`.
+    fn from(x: Geometry<f64>) -> Value {
+        let mut buf = Vec::new();
+        serialize_geometry(0, &x, &mut buf);
+        Value::Bytes(buf)
+    }
+}
+
+impl ConvIr<Geometry<f64>> for GeometryIr<Geometry<f64>> {
+    fn new(v: Value) -> Result<Self, FromValueError> {
+        match v {
+            Value::Bytes(bytes) => match parse_geometry(&bytes) {
+                Ok((_, output)) => Ok(GeometryIr { bytes, output }),
+                Err(_) => Err(FromValueError(Value::Bytes(bytes))),
+            },
+            v => Err(FromValueError(v)),
+        }
+    }
+    fn commit(self) -> Geometry<f64> {
+        self.output
+    }
+    fn rollback(self) -> Value {
+        Value::Bytes(self.bytes)
+    }
+}
+
+impl FromValue for Geometry<f64> {
+    type Intermediate = GeometryIr<Geometry<f64>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{line_string, point, polygon, Geometry, GeometryCollection, Point, Polygon};
+
+    use super::*;
+    use crate::value::convert::{from_value, from_value_opt};
+
+    #[test]
+    fn should_parse_mysql_point() {
+        // SELECT ST_GeomFromText('POINT(1 -2)', 4326)
+        let bytes = b"\xe6\x10\x00\x00\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\xf0\x3f\x00\x00\x00\x00\x00\x00\x00\xc0";
+        let (srid, geometry) = parse_geometry(bytes).unwrap();
+        assert_eq!(srid, 4326);
+        assert_eq!(geometry, Geometry::Point(point!(x: 1.0, y: -2.0)));
+
+        let mut buf = Vec::new();
+        serialize_geometry(srid, &geometry, &mut buf);
+        assert_eq!(buf, &bytes[..]);
+    }
+
+    #[test]
+    fn should_convert_geometries() {
+        let polygon: Polygon<f64> = polygon![
+            exterior: [(x: 0., y: 0.), (x: 4., y: 0.), (x: 4., y: 4.), (x: 0., y: 0.)],
+            interiors: [[(x: 1., y: 1.), (x: 2., y: 1.), (x: 2., y: 2.), (x: 1., y: 1.)]],
+        ];
+        assert_eq!(
+            from_value::<Polygon<f64>>(Value::from(polygon.clone())),
+            polygon
+        );
+
+        let collection = GeometryCollection(vec![
+            Geometry::Point(point!(x: 1.5, y: 2.5)),
+            Geometry::LineString(line_string![(x: 0., y: 0.), (x: 1., y: 1.)]),
+            Geometry::Polygon(polygon),
+        ]);
+        let value = Value::from(collection.clone());
+        assert_eq!(
+            from_value::<GeometryCollection<f64>>(value.clone()),
+            collection
+        );
+        assert_eq!(
+            from_value::<Geometry<f64>>(value.clone()),
+            Geometry::GeometryCollection(collection)
+        );
+        assert!(from_value_opt::<Point<f64>>(value).is_err());
+    }
+
+    #[test]
+    fn should_not_parse_invalid_wkb() {
+        parse_geometry(b"").unwrap_err();
+        parse_geometry(b"\x00\x00\x00\x00\x02\x01\x00\x00\x00").unwrap_err();
+        // too many points
+        parse_geometry(b"\x00\x00\x00\x00\x01\x02\x00\x00\x00\xff\xff\xff\xff").unwrap_err();
+        // trailing bytes
+        let mut buf = Vec::new();
+        serialize_geometry(0, &Geometry::Point(point!(x: 0., y: 0.)), &mut buf);
+        buf.push(0);
+        parse_geometry(&buf).unwrap_err();
+    }
+}
@@ -10,10 +10,12 @@ use lexical::parse;
 use num_traits::{FromPrimitive, ToPrimitive};
 use regex::bytes::Regex;
 
-use std::{any::type_name, error::Error, fmt, str::from_utf8, time::Duration};
+use std::{any::type_name, convert::TryFrom, error::Error, fmt, str::from_utf8, time::Duration};
 
 use crate::value::Value;
 
+use self::rounding::{FracSecsRounding, RoundFracSecs, MAX_FSP};
+
 macro_rules! impl_from_value {
     ($ty:ty, $ir:ty) => {
         impl crate::value::convert::FromValue for $ty {
@@ -26,10 +28,16 @@ pub mod bigdecimal;
 pub mod bigdecimal03;
 pub mod bigint;
 pub mod chrono;
+pub mod compact_str;
 pub mod decimal;
+pub mod enums;
+pub mod geo;
+pub mod rounding;
+pub mod temporal;
 pub mod time;
 pub mod time03;
 pub mod uuid;
+pub mod wkt;
 
 lazy_static::lazy_static! {
     static ref DATETIME_RE_YMD: Regex = Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
@@ -46,7 +54,6 @@ lazy_static::lazy_static! {
 }
 
 /// Returns (year, month, day, hour, minute, second, micros)
-#[cfg(any(feature = "chrono", all(feature = "time", test)))]
 fn parse_mysql_datetime_string(bytes: &[u8]) -> Option<(u32, u32, u32, u32, u32, u32, u32)> {
     let len = bytes.len();
 
@@ -503,6 +510,61 @@ impl ConvIr<bool> for ParseIr<bool> {
     }
 }
 
+/// Boolean that is only convertible from a value of exactly `0` or `1`.
+///
+/// Accepts `TINYINT(1)` values (given as an integer or as a text) as well as `BIT(1)` values
+/// (given as a single `0x00` or `0x01` byte). Conversion fails on any other value,
+/// so unexpected data is caught instead of being silently truthified.
+///
+/// ```
+/// # use mysql_common::value::{Value, convert::{from_value, from_value_opt, StrictBool}};
+/// assert_eq!(from_value::<StrictBool>(Value::Int(1)), StrictBool(true));
+/// assert_eq!(from_value::<StrictBool>(Value::Bytes(vec![0x00])), StrictBool(false));
+/// assert!(from_value_opt::<StrictBool>(Value::Int(2)).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct StrictBool(pub bool);
+
+impl From<StrictBool> for bool {
+    fn from(x: StrictBool) -> bool {
+        x.0
+    }
+}
+
+impl From<bool> for StrictBool {
+    fn from(x: bool) -> StrictBool {
+        StrictBool(x)
+    }
+}
+
+impl ConvIr<StrictBool> for ParseIr<StrictBool> {
+    fn new(v: Value) -> Result<ParseIr<StrictBool>, FromValueError> {
+        let output = match v {
+            Value::Int(0) | Value::UInt(0) => Some(false),
+            Value::Int(1) | Value::UInt(1) => Some(true),
+            Value::Bytes(ref bytes) => match bytes.as_slice() {
+                b"0" | b"\x00" => Some(false),
+                b"1" | b"\x01" => Some(true),
+                _ => None,
+            },
+            _ => None,
+        };
+        match output {
+            Some(output) => Ok(ParseIr {
+                value: v,
+                output: StrictBool(output),
+            }),
+            None => Err(FromValueError(v)),
+        }
+    }
+    fn commit(self) -> StrictBool {
+        self.output
+    }
+    fn rollback(self) -> Value {
+        self.value
+    }
+}
+
 impl ConvIr<Vec<u8>> for Vec<u8> {
     fn new(v: Value) -> Result<Vec<u8>, FromValueError> {
         match v {
@@ -629,8 +691,12 @@ impl ConvIr<Duration> for ParseIr<Duration> {
 
 impl From<Duration> for Value {
     fn from(x: Duration) -> Value {
+        // sub-microsecond part is rounded, so a carry must go to the seconds
+        let x = x
+            .round_frac_secs(MAX_FSP, FracSecsRounding::RoundHalfEven)
+            .unwrap_or(x);
         let mut secs_total = x.as_secs();
-        let micros = (f64::from(x.subsec_nanos()) / 1000_f64).round() as u32;
+        let micros = x.subsec_micros();
         let seconds = (secs_total % 60) as u8;
         secs_total -= u64::from(seconds);
         let minutes = ((secs_total % (60 * 60)) / 60) as u8;
@@ -651,6 +717,7 @@ impl From<Duration> for Value {
 impl_from_value!(String, Vec<u8>);
 impl_from_value!(Vec<u8>, Vec<u8>);
 impl_from_value!(bool, ParseIr<bool>);
+impl_from_value!(StrictBool, ParseIr<StrictBool>);
 impl_from_value!(i64, ParseIr<i64>);
 impl_from_value!(u64, ParseIr<u64>);
 impl_from_value!(f32, ParseIr<f32>);
@@ -742,15 +809,48 @@ impl From<u128> for Value {
     }
 }
 
+/// NaN or infinite float could not be converted into a `Value` (MySql can't store it).
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+#[error("Non-finite float {0} could not be converted into a MySql value")]
+pub struct NonFiniteFloatError(pub f64);
+
+impl Value {
+    /// Checked conversion of an `f32` into a `Value`.
+    ///
+    /// Unlike `From<f32>` this will return an error for NaN or infinite values.
+    pub fn try_from_f32(x: f32) -> Result<Value, NonFiniteFloatError> {
+        if x.is_finite() {
+            Ok(Value::Float(x))
+        } else {
+            Err(NonFiniteFloatError(x.into()))
+        }
+    }
+
+    /// Checked conversion of an `f64` into a `Value`.
+    ///
+    /// Unlike `From<f64>` this will return an error for NaN or infinite values.
+    pub fn try_from_f64(x: f64) -> Result<Value, NonFiniteFloatError> {
+        if x.is_finite() {
+            Ok(Value::Double(x))
+        } else {
+            Err(NonFiniteFloatError(x))
+        }
+    }
+}
+
+/// NaN and infinite values are converted to `Value::NULL`,
+/// because MySql can't store them (see [`Value::try_from_f32`]).
 impl From<f32> for Value {
     fn from(x: f32) -> Value {
-        Value::Float(x)
+        Value::try_from_f32(x).unwrap_or(Value::NULL)
     }
 }
 
+/// NaN and infinite values are converted to `Value::NULL`,
+/// because MySql can't store them (see [`Value::try_from_f64`]).
 impl From<f64> for Value {
     fn from(x: f64) -> Value {
-        Value::Double(x)
+        Value::try_from_f64(x).unwrap_or(Value::NULL)
     }
 }
 
@@ -760,6 +860,12 @@ impl From<bool> for Value {
     }
 }
 
+impl From<StrictBool> for Value {
+    fn from(x: StrictBool) -> Value {
+        Value::from(x.0)
+    }
+}
+
 impl<'a> From<&'a [u8]> for Value {
     fn from(x: &'a [u8]) -> Value {
         Value::Bytes(x.into())
@@ -785,6 +891,163 @@ impl From<String> for Value {
     }
 }
 
+/// Negative integer could not be bound as unsigned (see [`Unsigned`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Negative integer {0} could not be converted into an unsigned MySql value")]
+pub struct NegativeUnsignedError(pub i64);
+
+/// Wrapper that forces a value to be bound as an unsigned integer.
+///
+/// The `MYSQL_TYPE_*` unsigned flag is set regardless of the signedness of `T`.
+/// Negative integers are not representable, so the conversion fails for them.
+/// Non-integer values are left as is.
+///
+/// ```
+/// # use std::convert::TryFrom;
+/// # use mysql_common::value::{Value, convert::{NegativeUnsignedError, Unsigned}};
+/// assert_eq!(Value::try_from(Unsigned(42_i32)), Ok(Value::UInt(42)));
+/// assert_eq!(Value::try_from(Unsigned(-1_i64)), Err(NegativeUnsignedError(-1)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Unsigned<T>(pub T);
+
+impl<T: Into<Value>> TryFrom<Unsigned<T>> for Value {
+    type Error = NegativeUnsignedError;
+
+    fn try_from(x: Unsigned<T>) -> Result<Value, Self::Error> {
+        match x.0.into() {
+            Value::Int(x) => u64::try_from(x)
+                .map(Value::UInt)
+                .map_err(|_| NegativeUnsignedError(x)),
+            other => Ok(other),
+        }
+    }
+}
+
+/// Wrapper that forces a value to be bound as a signed integer.
+///
+/// The `MYSQL_TYPE_*` unsigned flag is cleared regardless of the signedness of `T`,
+/// i.e. unsigned integers greater than `i64::MAX` are reinterpreted as negative
+/// (two's complement). Non-integer values are left as is.
+///
+/// ```
+/// # use mysql_common::value::{Value, convert::Signed};
+/// assert_eq!(Value::from(Signed(u64::MAX)), Value::Int(-1));
+/// assert_eq!(Value::from(Signed(42_u8)), Value::Int(42));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Signed<T>(pub T);
+
+impl<T: Into<Value>> From<Signed<T>> for Value {
+    fn from(x: Signed<T>) -> Value {
+        match x.0.into() {
+            Value::UInt(x) => Value::Int(x as i64),
+            other => other,
+        }
+    }
+}
+
+/// Value converted to `L` or, if this conversion fails, to `R`.
+///
+/// Useful for columns which representation changed over time:
+///
+/// ```
+/// # use mysql_common::value::{Value, convert::{from_value, Fallback}};
+/// // integer ids were migrated to UUID strings
+/// type Id = Fallback<u64, String>;
+/// assert_eq!(from_value::<Id>(Value::Int(42)), Fallback::Left(42));
+/// assert_eq!(
+///     from_value::<Id>(Value::Bytes(b"67e55044-10b1-426f-9247-bb680e5fe0c8".to_vec())),
+///     Fallback::Right("67e55044-10b1-426f-9247-bb680e5fe0c8".into()),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Fallback<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<L, R> Fallback<L, R> {
+    /// Returns `true` if the value was converted to `L`.
+    pub fn is_left(&self) -> bool {
+        matches!(self, Fallback::Left(_))
+    }
+
+    /// Returns `true` if the value was converted to `R`.
+    pub fn is_right(&self) -> bool {
+        matches!(self, Fallback::Right(_))
+    }
+
+    /// Returns the `L` value, if any.
+    pub fn left(self) -> Option<L> {
+        match self {
+            Fallback::Left(x) => Some(x),
+            Fallback::Right(_) => None,
+        }
+    }
+
+    /// Returns the `R` value, if any.
+    pub fn right(self) -> Option<R> {
+        match self {
+            Fallback::Left(_) => None,
+            Fallback::Right(x) => Some(x),
+        }
+    }
+}
+
+impl<L: Into<Value>, R: Into<Value>> From<Fallback<L, R>> for Value {
+    fn from(x: Fallback<L, R>) -> Value {
+        match x {
+            Fallback::Left(x) => x.into(),
+            Fallback::Right(x) => x.into(),
+        }
+    }
+}
+
+/// Intermediate result of a Value-to-Fallback conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FallbackIr<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<L, R, Il, Ir> ConvIr<Fallback<L, R>> for FallbackIr<Il, Ir>
+where
+    L: FromValue<Intermediate = Il>,
+    R: FromValue<Intermediate = Ir>,
+    Il: ConvIr<L>,
+    Ir: ConvIr<R>,
+{
+    fn new(v: Value) -> Result<Self, FromValueError> {
+        match L::get_intermediate(v) {
+            Ok(ir) => Ok(FallbackIr::Left(ir)),
+            Err(FromValueError(v)) => R::get_intermediate(v).map(FallbackIr::Right),
+        }
+    }
+
+    fn commit(self) -> Fallback<L, R> {
+        match self {
+            FallbackIr::Left(ir) => Fallback::Left(ir.commit()),
+            FallbackIr::Right(ir) => Fallback::Right(ir.commit()),
+        }
+    }
+
+    fn rollback(self) -> Value {
+        match self {
+            FallbackIr::Left(ir) => ir.rollback(),
+            FallbackIr::Right(ir) => ir.rollback(),
+        }
+    }
+}
+
+impl<L, R> FromValue for Fallback<L, R>
+where
+    L: FromValue,
+    R: FromValue,
+{
+    type Intermediate = FallbackIr<L::Intermediate, R::Intermediate>;
+}
+
 macro_rules! from_array_impl {
     ($n:expr) => {
         impl From<[u8; $n]> for Value {
@@ -995,6 +1258,114 @@ mod tests {
     unsigned_primitive_roundtrip!(u32, u32_roundtrip);
     unsigned_primitive_roundtrip!(u64, u64_roundtrip);
 
+    #[test]
+    fn should_handle_non_finite_floats() {
+        assert_eq!(Value::from(f64::NAN), Value::NULL);
+        assert_eq!(Value::from(f32::INFINITY), Value::NULL);
+        assert_eq!(Value::from(f64::NEG_INFINITY), Value::NULL);
+        assert_eq!(Value::from(1.5_f32), Value::Float(1.5));
+        assert_eq!(Value::try_from_f64(-0.5), Ok(Value::Double(-0.5)));
+        assert_eq!(
+            Value::try_from_f32(f32::NEG_INFINITY),
+            Err(NonFiniteFloatError(f64::NEG_INFINITY))
+        );
+        assert!(Value::try_from_f64(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn should_convert_strict_bool() {
+        for (value, expected) in vec![
+            (Value::Int(0), false),
+            (Value::Int(1), true),
+            (Value::UInt(1), true),
+            (Value::Bytes(b"0".to_vec()), false),
+            (Value::Bytes(b"1".to_vec()), true),
+            (Value::Bytes(vec![0x00]), false),
+            (Value::Bytes(vec![0x01]), true),
+        ] {
+            assert_eq!(from_value::<StrictBool>(value), StrictBool(expected));
+        }
+
+        for value in vec![
+            Value::Int(2),
+            Value::Int(-1),
+            Value::UInt(2),
+            Value::Bytes(b"true".to_vec()),
+            Value::Bytes(b"01".to_vec()),
+            Value::Bytes(vec![0x02]),
+            Value::Float(1.0),
+        ] {
+            assert_eq!(
+                from_value_opt::<StrictBool>(value.clone()),
+                Err(FromValueError(value))
+            );
+        }
+
+        assert_eq!(Value::from(StrictBool(true)), Value::Int(1));
+    }
+
+    #[test]
+    fn should_force_signedness() {
+        assert_eq!(
+            Value::try_from(Unsigned(i64::MIN)),
+            Err(NegativeUnsignedError(i64::MIN))
+        );
+        assert_eq!(
+            Value::try_from(Unsigned(i64::MAX)),
+            Ok(Value::UInt(i64::MAX as u64))
+        );
+        assert_eq!(Value::try_from(Unsigned(7_u16)), Ok(Value::UInt(7)));
+        assert_eq!(
+            Value::try_from(Unsigned(Some(-2_i8))),
+            Err(NegativeUnsignedError(-2))
+        );
+        assert_eq!(Value::try_from(Unsigned(Some(2_i8))), Ok(Value::UInt(2)));
+        assert_eq!(Value::try_from(Unsigned(None::<i64>)), Ok(Value::NULL));
+        assert_eq!(
+            Value::try_from(Unsigned("1")),
+            Ok(Value::Bytes(b"1".to_vec()))
+        );
+        assert_eq!(Value::from(Signed(1_u64 << 63)), Value::Int(i64::MIN));
+        assert_eq!(Value::from(Signed(-7_i16)), Value::Int(-7));
+        assert_eq!(Value::from(&Signed(7_u32)), Value::Int(7));
+    }
+
+    #[test]
+    fn should_fall_back() {
+        type Id = Fallback<u64, String>;
+
+        assert_eq!(from_value::<Id>(Value::UInt(42)), Fallback::Left(42));
+        assert_eq!(
+            from_value::<Id>(Value::Bytes(b"42".to_vec())),
+            Fallback::Left(42)
+        );
+        assert_eq!(
+            from_value::<Id>(Value::Bytes(b"abc".to_vec())),
+            Fallback::Right("abc".into())
+        );
+        assert_eq!(
+            from_value_opt::<Id>(Value::Float(0.5)),
+            Err(FromValueError(Value::Float(0.5)))
+        );
+        assert_eq!(
+            Value::from(Fallback::<u64, String>::Right("abc".into())),
+            Value::Bytes(b"abc".to_vec())
+        );
+
+        // rollback restores the original value
+        let ir = <Id as FromValue>::get_intermediate(Value::Bytes(b"abc".to_vec())).unwrap();
+        assert_eq!(
+            <_ as ConvIr<Id>>::rollback(ir),
+            Value::Bytes(b"abc".to_vec())
+        );
+        let ir = <Fallback<u64, i64> as FromValue>::get_intermediate(Value::Int(-1)).unwrap();
+        assert!(matches!(ir, FallbackIr::Right(_)));
+        assert_eq!(
+            <_ as ConvIr<Fallback<u64, i64>>>::rollback(ir),
+            Value::Int(-1)
+        );
+    }
+
     #[test]
     fn from_value_should_fail_on_integer_overflow() {
         let value = Value::Bytes(b"340282366920938463463374607431768211456"[..].into());
@@ -0,0 +1,241 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Control over fractional seconds of temporal values.
+//!
+//! `From` conversions of `chrono` and `time` types into a `Value` truncate nanoseconds
+//! to microseconds (`std::time::Duration` is rounded half to even). Wrap a value into
+//! [`FracSecs`] to convert it using the fractional seconds precision (fsp) of a target
+//! column and a [`FracSecsRounding`] policy of your choice:
+//!
+//! ```ignore
+//! let value = Value::try_from(FracSecs::new(datetime, 3, FracSecsRounding::RoundHalfEven))?;
+//! ```
+
+use std::{cmp::min, convert::TryFrom, time::Duration};
+
+use crate::value::Value;
+
+/// Maximum fractional seconds precision supported by MySql.
+pub const MAX_FSP: u8 = 6;
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Policy to apply if a temporal value has more fractional second digits than required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FracSecsRounding {
+    /// Excess digits are discarded (this is what `From` conversions do).
+    Truncate,
+    /// Value is rounded to the nearest representable value (ties to even).
+    RoundHalfEven,
+    /// [`InexactFracSecsError`] is returned if excess digits are not zero.
+    Error,
+}
+
+impl Default for FracSecsRounding {
+    fn default() -> Self {
+        FracSecsRounding::Truncate
+    }
+}
+
+/// Temporal value has more fractional second digits than required
+/// (see [`FracSecsRounding::Error`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Temporal value has more than {fsp} fractional second digits")]
+pub struct InexactFracSecsError {
+    pub fsp: u8,
+}
+
+impl FracSecsRounding {
+    /// Returns an adjustment (in nanoseconds) that should be added to a non-negative temporal
+    /// value with the given fractional part (`nanos`) to fit it into `fsp` fractional digits.
+    ///
+    /// `seconds` is the whole seconds part of the value (only its parity is relevant).
+    /// `fsp` greater than [`MAX_FSP`] is treated as `MAX_FSP`.
+    pub fn adjustment(
+        self,
+        seconds: u64,
+        nanos: u32,
+        fsp: u8,
+    ) -> Result<i64, InexactFracSecsError> {
+        let fsp = min(fsp, MAX_FSP);
+        let unit = 10_u32.pow(9 - fsp as u32);
+        let nanos = nanos % NANOS_PER_SEC;
+        let rem = nanos % unit;
+
+        if rem == 0 {
+            return Ok(0);
+        }
+
+        match self {
+            FracSecsRounding::Truncate => Ok(-(rem as i64)),
+            FracSecsRounding::RoundHalfEven => {
+                let is_odd = if fsp == 0 {
+                    seconds % 2 == 1
+                } else {
+                    (nanos / unit) % 2 == 1
+                };
+                if rem * 2 > unit || (rem * 2 == unit && is_odd) {
+                    Ok((unit - rem) as i64)
+                } else {
+                    Ok(-(rem as i64))
+                }
+            }
+            FracSecsRounding::Error => Err(InexactFracSecsError { fsp }),
+        }
+    }
+
+    /// Same as [`FracSecsRounding::adjustment`] but also handles negative values
+    /// (given as a sign and a fractional part of the absolute value).
+    ///
+    /// Rounding is applied to the absolute value.
+    pub fn signed_adjustment(
+        self,
+        is_neg: bool,
+        seconds: u64,
+        nanos: u32,
+        fsp: u8,
+    ) -> Result<i64, InexactFracSecsError> {
+        let adjustment = self.adjustment(seconds, nanos, fsp)?;
+        Ok(if is_neg { -adjustment } else { adjustment })
+    }
+}
+
+/// Temporal values which fractional seconds could be rounded to the given precision.
+pub trait RoundFracSecs: Sized {
+    /// Rounds fractional seconds of `self` to `fsp` digits using the given policy.
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError>;
+}
+
+/// Temporal value that is converted into a [`Value`] with the given fractional seconds
+/// precision using the given rounding policy.
+///
+/// The conversion fails with [`InexactFracSecsError`] only for [`FracSecsRounding::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FracSecs<T> {
+    value: T,
+    fsp: u8,
+    rounding: FracSecsRounding,
+}
+
+impl<T> FracSecs<T> {
+    /// Creates a new instance (`fsp` greater than [`MAX_FSP`] is treated as `MAX_FSP`).
+    pub fn new(value: T, fsp: u8, rounding: FracSecsRounding) -> Self {
+        Self {
+            value,
+            fsp,
+            rounding,
+        }
+    }
+
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> TryFrom<FracSecs<T>> for Value
+where
+    T: RoundFracSecs + Into<Value>,
+{
+    type Error = InexactFracSecsError;
+
+    fn try_from(x: FracSecs<T>) -> Result<Self, Self::Error> {
+        x.value.round_frac_secs(x.fsp, x.rounding).map(Into::into)
+    }
+}
+
+impl RoundFracSecs for Duration {
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let adjustment = rounding.adjustment(self.as_secs(), self.subsec_nanos(), fsp)?;
+        if adjustment < 0 {
+            Ok(self - Duration::from_nanos((-adjustment) as u64))
+        } else {
+            Ok(self + Duration::from_nanos(adjustment as u64))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compute_adjustment() {
+        use FracSecsRounding::*;
+
+        assert_eq!(Truncate.adjustment(0, 123_456_789, 6), Ok(-789));
+        assert_eq!(Truncate.adjustment(0, 123_456_789, 0), Ok(-123_456_789));
+        assert_eq!(Truncate.adjustment(0, 123_456_000, 9), Ok(0));
+        assert_eq!(RoundHalfEven.adjustment(0, 123_456_789, 6), Ok(211));
+        assert_eq!(RoundHalfEven.adjustment(0, 123_456_489, 6), Ok(-489));
+        assert_eq!(RoundHalfEven.adjustment(0, 125_000_000, 2), Ok(-5_000_000));
+        assert_eq!(RoundHalfEven.adjustment(0, 135_000_000, 2), Ok(5_000_000));
+        assert_eq!(
+            RoundHalfEven.adjustment(0, 500_000_000, 0),
+            Ok(-500_000_000)
+        );
+        assert_eq!(RoundHalfEven.adjustment(0, 999_999_999, 3), Ok(1));
+        assert_eq!(Error.adjustment(0, 123_000_000, 3), Ok(0));
+        assert_eq!(
+            Error.adjustment(0, 123_400_000, 3),
+            Err(InexactFracSecsError { fsp: 3 })
+        );
+        assert_eq!(RoundHalfEven.adjustment(1, 500_000_000, 0), Ok(500_000_000));
+        assert_eq!(
+            RoundHalfEven.signed_adjustment(true, 0, 999_999_999, 3),
+            Ok(-1)
+        );
+    }
+
+    #[test]
+    fn should_round_duration() {
+        let duration = Duration::new(1, 999_999_500);
+        assert_eq!(
+            duration
+                .round_frac_secs(6, FracSecsRounding::RoundHalfEven)
+                .unwrap(),
+            Duration::new(2, 0)
+        );
+        assert_eq!(
+            duration
+                .round_frac_secs(3, FracSecsRounding::Truncate)
+                .unwrap(),
+            Duration::new(1, 999_000_000)
+        );
+        assert!(duration
+            .round_frac_secs(6, FracSecsRounding::Error)
+            .is_err());
+    }
+
+    #[test]
+    fn should_convert_duration_with_rounding_policy() {
+        let duration = Duration::new(59, 999_999_600);
+        assert_eq!(Value::from(duration), Value::Time(false, 0, 0, 1, 0, 0));
+        assert_eq!(
+            Value::try_from(FracSecs::new(duration, 6, FracSecsRounding::Truncate)),
+            Ok(Value::Time(false, 0, 0, 0, 59, 999_999))
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(duration, 1, FracSecsRounding::Truncate)),
+            Ok(Value::Time(false, 0, 0, 0, 59, 900_000))
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(duration, 6, FracSecsRounding::Error)),
+            Err(InexactFracSecsError { fsp: 6 })
+        );
+    }
+}
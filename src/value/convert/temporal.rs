@@ -0,0 +1,577 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Dependency-free temporal types.
+//!
+//! These types represent exactly what the protocol carries, so the conversion is lossless
+//! even for values that can't be represented by `chrono` or `time` (e.g. `0000-00-00`).
+
+use std::{convert::TryFrom, fmt, str::FromStr, time::Duration};
+
+use crate::value::Value;
+
+use super::{
+    parse_mysql_datetime_string, parse_mysql_time_string, ConvIr, FromValue, FromValueError,
+};
+
+/// Temporal value could not be parsed from a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Invalid MySql {0} value")]
+pub struct ParseTemporalError(&'static str);
+
+/// MySql `DATE` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct MysqlDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl MysqlDate {
+    /// Creates new `MysqlDate` (no validation is performed).
+    pub fn new(year: u16, month: u8, day: u8) -> Self {
+        Self { year, month, day }
+    }
+
+    /// Parses a value in the `YYYY-MM-DD[ hh:mm:ss[.ffffff]]` format (time part is ignored).
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseTemporalError> {
+        MysqlDateTime::parse(bytes)
+            .map(MysqlDateTime::date)
+            .map_err(|_| ParseTemporalError("DATE"))
+    }
+}
+
+impl fmt::Display for MysqlDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for MysqlDate {
+    type Err = ParseTemporalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s.as_bytes())
+    }
+}
+
+impl From<MysqlDate> for Value {
+    fn from(x: MysqlDate) -> Value {
+        Value::Date(x.year, x.month, x.day, 0, 0, 0, 0)
+    }
+}
+
+/// MySql `DATETIME` or `TIMESTAMP` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct MysqlDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub micros: u32,
+}
+
+impl MysqlDateTime {
+    /// Creates new `MysqlDateTime` (no validation is performed).
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        micros: u32,
+    ) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            micros,
+        }
+    }
+
+    /// Returns the date part of this value.
+    pub fn date(self) -> MysqlDate {
+        MysqlDate::new(self.year, self.month, self.day)
+    }
+
+    /// Parses a value in the `YYYY-MM-DD[ hh:mm:ss[.ffffff]]` format.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseTemporalError> {
+        match parse_mysql_datetime_string(bytes) {
+            Some((year, month, day, hour, minute, second, micros)) => Ok(Self::new(
+                year as u16,
+                month as u8,
+                day as u8,
+                hour as u8,
+                minute as u8,
+                second as u8,
+                micros,
+            )),
+            None => Err(ParseTemporalError("DATETIME")),
+        }
+    }
+}
+
+impl fmt::Display for MysqlDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:02}:{:02}:{:02}",
+            self.date(),
+            self.hour,
+            self.minute,
+            self.second
+        )?;
+        if self.micros > 0 {
+            write!(f, ".{:06}", self.micros)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MysqlDateTime {
+    type Err = ParseTemporalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s.as_bytes())
+    }
+}
+
+impl From<MysqlDate> for MysqlDateTime {
+    fn from(x: MysqlDate) -> Self {
+        Self::new(x.year, x.month, x.day, 0, 0, 0, 0)
+    }
+}
+
+impl From<MysqlDateTime> for Value {
+    fn from(x: MysqlDateTime) -> Value {
+        Value::Date(x.year, x.month, x.day, x.hour, x.minute, x.second, x.micros)
+    }
+}
+
+/// MySql `TIME` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MysqlTime {
+    pub is_neg: bool,
+    pub days: u32,
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub micros: u32,
+}
+
+impl MysqlTime {
+    /// Creates new `MysqlTime` (no validation is performed).
+    pub fn new(is_neg: bool, days: u32, hours: u8, minutes: u8, seconds: u8, micros: u32) -> Self {
+        Self {
+            is_neg,
+            days,
+            hours,
+            minutes,
+            seconds,
+            micros,
+        }
+    }
+
+    /// Returns the total number of hours (i.e. `days * 24 + hours`).
+    pub fn total_hours(&self) -> u64 {
+        u64::from(self.days) * 24 + u64::from(self.hours)
+    }
+
+    /// Parses a value in the `[-][h]hh:mm:ss[.ffffff]` format.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseTemporalError> {
+        match parse_mysql_time_string(bytes) {
+            Some((is_neg, hours, minutes, seconds, micros)) => Ok(Self::new(
+                is_neg,
+                hours / 24,
+                (hours % 24) as u8,
+                minutes as u8,
+                seconds as u8,
+                micros,
+            )),
+            None => Err(ParseTemporalError("TIME")),
+        }
+    }
+}
+
+impl fmt::Display for MysqlTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_neg {
+            f.write_str("-")?;
+        }
+        write!(
+            f,
+            "{:02}:{:02}:{:02}",
+            self.total_hours(),
+            self.minutes,
+            self.seconds
+        )?;
+        if self.micros > 0 {
+            write!(f, ".{:06}", self.micros)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MysqlTime {
+    type Err = ParseTemporalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s.as_bytes())
+    }
+}
+
+impl From<MysqlTime> for Value {
+    fn from(x: MysqlTime) -> Value {
+        Value::Time(x.is_neg, x.days, x.hours, x.minutes, x.seconds, x.micros)
+    }
+}
+
+/// Value is out of range of the MySql `TIME` type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Value is out of range of the MySql TIME type (-838:59:59 .. 838:59:59)")]
+pub struct TimeOutOfRangeError;
+
+/// Negative `TimeInterval` could not be converted into a `Duration`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Negative time interval could not be converted into a Duration")]
+pub struct NegativeTimeIntervalError;
+
+/// Signed time interval covering the full range of the MySql `TIME` type
+/// (`-838:59:59.000000` to `838:59:59.000000`).
+///
+/// Unlike `std::time::Duration` this type is able to represent negative `TIME` values.
+///
+/// ```
+/// # use std::{convert::TryFrom, time::Duration};
+/// # use mysql_common::value::{Value, convert::{from_value, temporal::TimeInterval}};
+/// let interval = from_value::<TimeInterval>(Value::Bytes(b"-01:30:00".to_vec()));
+/// assert_eq!(interval.as_micros(), -5_400_000_000);
+/// assert!(Duration::try_from(interval).is_err());
+/// assert_eq!(Duration::try_from(-interval).unwrap(), Duration::from_secs(5_400));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct TimeInterval {
+    micros: i64,
+}
+
+impl TimeInterval {
+    /// Maximum value of the MySql `TIME` type in microseconds (`838:59:59.000000`).
+    pub const MAX_MICROS: i64 = (838 * 3600 + 59 * 60 + 59) * 1_000_000;
+
+    /// Maximum value of the MySql `TIME` type.
+    pub const MAX: TimeInterval = TimeInterval {
+        micros: Self::MAX_MICROS,
+    };
+
+    /// Minimum value of the MySql `TIME` type.
+    pub const MIN: TimeInterval = TimeInterval {
+        micros: -Self::MAX_MICROS,
+    };
+
+    /// Creates a new interval from the given number of microseconds.
+    pub fn from_micros(micros: i64) -> Result<Self, TimeOutOfRangeError> {
+        if (-Self::MAX_MICROS..=Self::MAX_MICROS).contains(&micros) {
+            Ok(Self { micros })
+        } else {
+            Err(TimeOutOfRangeError)
+        }
+    }
+
+    /// Returns the total number of microseconds.
+    pub fn as_micros(&self) -> i64 {
+        self.micros
+    }
+
+    /// Returns `true` if this interval is negative.
+    pub fn is_negative(&self) -> bool {
+        self.micros < 0
+    }
+
+    /// Returns the absolute value of this interval as a `Duration`.
+    pub fn abs_duration(&self) -> Duration {
+        Duration::from_micros(self.micros.unsigned_abs())
+    }
+}
+
+impl std::ops::Neg for TimeInterval {
+    type Output = TimeInterval;
+
+    fn neg(self) -> Self::Output {
+        // range is symmetric, so this can't overflow
+        TimeInterval {
+            micros: -self.micros,
+        }
+    }
+}
+
+impl fmt::Display for TimeInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&MysqlTime::from(*self), f)
+    }
+}
+
+impl FromStr for TimeInterval {
+    type Err = ParseTemporalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let time = MysqlTime::parse(s.as_bytes())?;
+        TimeInterval::try_from(time).map_err(|_| ParseTemporalError("TIME"))
+    }
+}
+
+impl TryFrom<MysqlTime> for TimeInterval {
+    type Error = TimeOutOfRangeError;
+
+    fn try_from(x: MysqlTime) -> Result<Self, Self::Error> {
+        let secs = x.total_hours() * 3600 + u64::from(x.minutes) * 60 + u64::from(x.seconds);
+        let micros = i64::try_from(secs)
+            .ok()
+            .and_then(|secs| secs.checked_mul(1_000_000))
+            .and_then(|micros| micros.checked_add(i64::from(x.micros)))
+            .ok_or(TimeOutOfRangeError)?;
+        TimeInterval::from_micros(if x.is_neg { -micros } else { micros })
+    }
+}
+
+impl From<TimeInterval> for MysqlTime {
+    fn from(x: TimeInterval) -> Self {
+        let abs = x.micros.unsigned_abs();
+        let secs = abs / 1_000_000;
+        MysqlTime::new(
+            x.is_negative(),
+            (secs / (24 * 3600)) as u32,
+            ((secs / 3600) % 24) as u8,
+            ((secs / 60) % 60) as u8,
+            (secs % 60) as u8,
+            (abs % 1_000_000) as u32,
+        )
+    }
+}
+
+impl TryFrom<Duration> for TimeInterval {
+    type Error = TimeOutOfRangeError;
+
+    /// Nanoseconds are truncated to microseconds.
+    fn try_from(x: Duration) -> Result<Self, Self::Error> {
+        let micros = i64::try_from(x.as_micros()).map_err(|_| TimeOutOfRangeError)?;
+        TimeInterval::from_micros(micros)
+    }
+}
+
+impl TryFrom<TimeInterval> for Duration {
+    type Error = NegativeTimeIntervalError;
+
+    fn try_from(x: TimeInterval) -> Result<Self, Self::Error> {
+        if x.is_negative() {
+            Err(NegativeTimeIntervalError)
+        } else {
+            Ok(x.abs_duration())
+        }
+    }
+}
+
+impl From<TimeInterval> for Value {
+    fn from(x: TimeInterval) -> Value {
+        MysqlTime::from(x).into()
+    }
+}
+
+/// Intermediate result of a Value-to-temporal conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemporalIr<T> {
+    value: Value,
+    output: T,
+}
+
+impl ConvIr<MysqlDateTime> for TemporalIr<MysqlDateTime> {
+    fn new(v: Value) -> Result<Self, FromValueError> {
+        let output = match v {
+            Value::Date(year, month, day, hour, minute, second, micros) => Ok(MysqlDateTime::new(
+                year, month, day, hour, minute, second, micros,
+            )),
+            Value::Bytes(ref bytes) => MysqlDateTime::parse(bytes),
+            _ => Err(ParseTemporalError("DATETIME")),
+        };
+        match output {
+            Ok(output) => Ok(TemporalIr { value: v, output }),
+            Err(_) => Err(FromValueError(v)),
+        }
+    }
+    fn commit(self) -> MysqlDateTime {
+        self.output
+    }
+    fn rollback(self) -> Value {
+        self.value
+    }
+}
+
+impl ConvIr<MysqlDate> for TemporalIr<MysqlDate> {
+    fn new(v: Value) -> Result<Self, FromValueError> {
+        let ir = TemporalIr::<MysqlDateTime>::new(v)?;
+        Ok(TemporalIr {
+            output: ir.output.date(),
+            value: ir.value,
+        })
+    }
+    fn commit(self) -> MysqlDate {
+        self.output
+    }
+    fn rollback(self) -> Value {
+        self.value
+    }
+}
+
+impl ConvIr<MysqlTime> for TemporalIr<MysqlTime> {
+    fn new(v: Value) -> Result<Self, FromValueError> {
+        let output = match v {
+            Value::Time(is_neg, days, hours, minutes, seconds, micros) => Ok(MysqlTime::new(
+                is_neg, days, hours, minutes, seconds, micros,
+            )),
+            Value::Bytes(ref bytes) => MysqlTime::parse(bytes),
+            _ => Err(ParseTemporalError("TIME")),
+        };
+        match output {
+            Ok(output) => Ok(TemporalIr { value: v, output }),
+            Err(_) => Err(FromValueError(v)),
+        }
+    }
+    fn commit(self) -> MysqlTime {
+        self.output
+    }
+    fn rollback(self) -> Value {
+        self.value
+    }
+}
+
+impl ConvIr<TimeInterval> for TemporalIr<TimeInterval> {
+    fn new(v: Value) -> Result<Self, FromValueError> {
+        let ir = TemporalIr::<MysqlTime>::new(v)?;
+        match TimeInterval::try_from(ir.output) {
+            Ok(output) => Ok(TemporalIr {
+                value: ir.value,
+                output,
+            }),
+            Err(_) => Err(FromValueError(ir.value)),
+        }
+    }
+    fn commit(self) -> TimeInterval {
+        self.output
+    }
+    fn rollback(self) -> Value {
+        self.value
+    }
+}
+
+impl FromValue for MysqlDate {
+    type Intermediate = TemporalIr<MysqlDate>;
+}
+
+impl FromValue for MysqlDateTime {
+    type Intermediate = TemporalIr<MysqlDateTime>;
+}
+
+impl FromValue for MysqlTime {
+    type Intermediate = TemporalIr<MysqlTime>;
+}
+
+impl FromValue for TimeInterval {
+    type Intermediate = TemporalIr<TimeInterval>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::convert::{from_value, from_value_opt};
+
+    #[test]
+    fn should_convert_zero_date() {
+        let date = from_value::<MysqlDate>(Value::Bytes(b"0000-00-00".to_vec()));
+        assert_eq!(date, MysqlDate::new(0, 0, 0));
+        assert_eq!(date.to_string(), "0000-00-00");
+        assert_eq!(Value::from(date), Value::Date(0, 0, 0, 0, 0, 0, 0));
+
+        let date_time = from_value::<MysqlDateTime>(Value::Date(0, 0, 0, 0, 0, 0, 0));
+        assert_eq!(date_time.to_string(), "0000-00-00 00:00:00");
+    }
+
+    #[test]
+    fn should_convert_date_time() {
+        let date_time: MysqlDateTime = "2021-02-03 04:05:06.000007".parse().unwrap();
+        assert_eq!(date_time, MysqlDateTime::new(2021, 2, 3, 4, 5, 6, 7));
+        assert_eq!(date_time.to_string(), "2021-02-03 04:05:06.000007");
+        assert_eq!(Value::from(date_time), Value::Date(2021, 2, 3, 4, 5, 6, 7));
+        assert_eq!(
+            from_value::<MysqlDateTime>(Value::from(date_time)),
+            date_time
+        );
+        assert_eq!(
+            from_value::<MysqlDate>(Value::from(date_time)),
+            MysqlDate::new(2021, 2, 3)
+        );
+        assert!("2021-02-03T04:05:06".parse::<MysqlDateTime>().is_err());
+        assert!(from_value_opt::<MysqlDateTime>(Value::Int(0)).is_err());
+    }
+
+    #[test]
+    fn should_convert_time() {
+        let time: MysqlTime = "-838:59:59.5".parse().unwrap();
+        assert_eq!(time, MysqlTime::new(true, 34, 22, 59, 59, 500_000));
+        assert_eq!(time.total_hours(), 838);
+        assert_eq!(time.to_string(), "-838:59:59.500000");
+        assert_eq!(
+            Value::from(time),
+            Value::Time(true, 34, 22, 59, 59, 500_000)
+        );
+        assert_eq!(from_value::<MysqlTime>(Value::from(time)), time);
+        assert_eq!(
+            from_value::<MysqlTime>(Value::Bytes(b"01:02:03".to_vec())).to_string(),
+            "01:02:03"
+        );
+        assert!(from_value_opt::<MysqlTime>(Value::Bytes(b"1:2:3".to_vec())).is_err());
+    }
+
+    #[test]
+    fn should_convert_time_interval() {
+        let min = from_value::<TimeInterval>(Value::Time(true, 34, 22, 59, 59, 0));
+        assert_eq!(min, TimeInterval::MIN);
+        assert_eq!(min.to_string(), "-838:59:59");
+        assert_eq!(Value::from(min), Value::Time(true, 34, 22, 59, 59, 0));
+        assert_eq!(
+            "838:59:59".parse::<TimeInterval>().unwrap(),
+            TimeInterval::MAX
+        );
+        assert!("838:59:59.000001".parse::<TimeInterval>().is_err());
+        assert!(from_value_opt::<TimeInterval>(Value::Time(false, 35, 0, 0, 0, 0)).is_err());
+
+        let interval = TimeInterval::try_from(Duration::new(3_661, 1_500)).unwrap();
+        assert_eq!(interval.as_micros(), 3_661_000_001);
+        assert_eq!(interval.to_string(), "01:01:01.000001");
+        assert_eq!(
+            Duration::try_from(interval).unwrap(),
+            Duration::new(3_661, 1_000)
+        );
+        assert_eq!(
+            Duration::try_from(-interval),
+            Err(NegativeTimeIntervalError)
+        );
+        assert_eq!(
+            TimeInterval::try_from(Duration::from_secs(839 * 3600)),
+            Err(TimeOutOfRangeError)
+        );
+        assert_eq!(
+            TimeInterval::from_micros(i64::MIN),
+            Err(TimeOutOfRangeError)
+        );
+    }
+}
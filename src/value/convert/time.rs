@@ -16,7 +16,11 @@ use time::{Date, ParseError, PrimitiveDateTime, Time};
 
 use crate::value::Value;
 
-use super::{parse_mysql_time_string, ConvIr, FromValueError, ParseIr};
+use super::{
+    parse_mysql_time_string,
+    rounding::{FracSecsRounding, InexactFracSecsError, RoundFracSecs},
+    ConvIr, FromValueError, ParseIr,
+};
 
 impl ConvIr<PrimitiveDateTime> for ParseIr<PrimitiveDateTime> {
     fn new(value: Value) -> Result<ParseIr<PrimitiveDateTime>, FromValueError> {
@@ -273,6 +277,46 @@ impl From<time::Duration> for Value {
     }
 }
 
+impl RoundFracSecs for PrimitiveDateTime {
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let adjustment = rounding.adjustment(self.second() as u64, self.nanosecond(), fsp)?;
+        Ok(self + time::Duration::nanoseconds(adjustment))
+    }
+}
+
+impl RoundFracSecs for Time {
+    /// Note, that the result will wrap around the midnight, if rounded up.
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let adjustment = rounding.adjustment(self.second() as u64, self.nanosecond(), fsp)?;
+        Ok(self + time::Duration::nanoseconds(adjustment))
+    }
+}
+
+impl RoundFracSecs for time::Duration {
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let subsec_nanos = self.subsec_nanoseconds();
+        let adjustment = rounding.signed_adjustment(
+            subsec_nanos < 0,
+            self.whole_seconds().unsigned_abs(),
+            subsec_nanos.unsigned_abs(),
+            fsp,
+        )?;
+        Ok(self + time::Duration::nanoseconds(adjustment))
+    }
+}
+
 impl_from_value!(PrimitiveDateTime, ParseIr<PrimitiveDateTime>);
 impl_from_value!(Date, ParseIr<Date>);
 impl_from_value!(Time, ParseIr<Time>);
@@ -280,11 +324,49 @@ impl_from_value!(time::Duration, ParseIr<time::Duration>);
 
 #[cfg(test)]
 mod tests {
-    use crate::value::convert::parse_mysql_datetime_string;
+    use std::convert::TryFrom;
+
+    use crate::value::convert::{parse_mysql_datetime_string, rounding::FracSecs};
 
     use super::*;
     use proptest::prelude::*;
 
+    #[test]
+    fn should_convert_with_rounding_policy() {
+        let datetime = PrimitiveDateTime::new(
+            Date::try_from_ymd(2021, 12, 31).unwrap(),
+            Time::try_from_hms_nano(23, 59, 59, 999_999_600).unwrap(),
+        );
+        assert_eq!(
+            Value::from(datetime),
+            Value::Date(2021, 12, 31, 23, 59, 59, 999_999)
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(datetime, 6, FracSecsRounding::RoundHalfEven)),
+            Ok(Value::Date(2022, 1, 1, 0, 0, 0, 0))
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(datetime, 2, FracSecsRounding::Truncate)),
+            Ok(Value::Date(2021, 12, 31, 23, 59, 59, 990_000))
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(datetime, 6, FracSecsRounding::Error)),
+            Err(InexactFracSecsError { fsp: 6 })
+        );
+
+        let time = Time::try_from_hms_nano(10, 0, 0, 123_456_500).unwrap();
+        assert_eq!(
+            Value::try_from(FracSecs::new(time, 6, FracSecsRounding::RoundHalfEven)),
+            Ok(Value::Time(false, 0, 10, 0, 0, 123_456))
+        );
+
+        let duration = -time::Duration::new(1, 500_000_000);
+        assert_eq!(
+            Value::try_from(FracSecs::new(duration, 0, FracSecsRounding::RoundHalfEven)),
+            Ok(Value::Time(true, 0, 0, 0, 2, 0))
+        );
+    }
+
     proptest! {
         #[test]
         fn parse_mysql_time_string_doesnt_crash(s in r"\PC*") {
@@ -23,7 +23,11 @@ use time03::{
 
 use crate::value::Value;
 
-use super::{parse_mysql_time_string, ConvIr, FromValueError, ParseIr};
+use super::{
+    parse_mysql_time_string,
+    rounding::{FracSecsRounding, InexactFracSecsError, RoundFracSecs},
+    ConvIr, FromValueError, ParseIr,
+};
 
 lazy_static::lazy_static! {
     static ref FULL_YEAR: modifier::Year = {
@@ -373,6 +377,46 @@ impl From<time03::Duration> for Value {
     }
 }
 
+impl RoundFracSecs for PrimitiveDateTime {
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let adjustment = rounding.adjustment(self.second() as u64, self.nanosecond(), fsp)?;
+        Ok(self + time03::Duration::nanoseconds(adjustment))
+    }
+}
+
+impl RoundFracSecs for Time {
+    /// Note, that the result will wrap around the midnight, if rounded up.
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let adjustment = rounding.adjustment(self.second() as u64, self.nanosecond(), fsp)?;
+        Ok(self + time03::Duration::nanoseconds(adjustment))
+    }
+}
+
+impl RoundFracSecs for time03::Duration {
+    fn round_frac_secs(
+        self,
+        fsp: u8,
+        rounding: FracSecsRounding,
+    ) -> Result<Self, InexactFracSecsError> {
+        let subsec_nanos = self.subsec_nanoseconds();
+        let adjustment = rounding.signed_adjustment(
+            subsec_nanos < 0,
+            self.whole_seconds().unsigned_abs(),
+            subsec_nanos.unsigned_abs(),
+            fsp,
+        )?;
+        Ok(self + time03::Duration::nanoseconds(adjustment))
+    }
+}
+
 impl_from_value!(PrimitiveDateTime, ParseIr<PrimitiveDateTime>);
 impl_from_value!(Date, ParseIr<Date>);
 impl_from_value!(Time, ParseIr<Time>);
@@ -384,6 +428,73 @@ mod tests {
     use time03::error::ParseFromDescription;
 
     use super::*;
+    use crate::value::convert::rounding::FracSecs;
+
+    #[test]
+    fn should_round_frac_secs() {
+        let datetime = PrimitiveDateTime::new(
+            Date::from_calendar_date(2021, time03::Month::December, 31).unwrap(),
+            Time::from_hms_nano(23, 59, 59, 999_999_600).unwrap(),
+        );
+
+        assert_eq!(
+            Value::from(
+                datetime
+                    .round_frac_secs(6, FracSecsRounding::RoundHalfEven)
+                    .unwrap()
+            ),
+            Value::Date(2022, 1, 1, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            Value::from(
+                datetime
+                    .round_frac_secs(2, FracSecsRounding::Truncate)
+                    .unwrap()
+            ),
+            Value::Date(2021, 12, 31, 23, 59, 59, 990_000)
+        );
+        assert!(datetime
+            .round_frac_secs(6, FracSecsRounding::Error)
+            .is_err());
+
+        let duration = -time03::Duration::new(1, 500_000_000);
+        assert_eq!(
+            duration.round_frac_secs(0, FracSecsRounding::RoundHalfEven),
+            Ok(-time03::Duration::seconds(2))
+        );
+    }
+
+    #[test]
+    fn should_convert_with_rounding_policy() {
+        let datetime = PrimitiveDateTime::new(
+            Date::from_calendar_date(2021, time03::Month::December, 31).unwrap(),
+            Time::from_hms_nano(23, 59, 59, 999_999_600).unwrap(),
+        );
+        assert_eq!(
+            Value::from(datetime),
+            Value::Date(2021, 12, 31, 23, 59, 59, 999_999)
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(datetime, 6, FracSecsRounding::RoundHalfEven)),
+            Ok(Value::Date(2022, 1, 1, 0, 0, 0, 0))
+        );
+        assert_eq!(
+            Value::try_from(FracSecs::new(datetime, 6, FracSecsRounding::Error)),
+            Err(InexactFracSecsError { fsp: 6 })
+        );
+
+        let time = Time::from_hms_nano(10, 0, 0, 123_456_500).unwrap();
+        assert_eq!(
+            Value::try_from(FracSecs::new(time, 6, FracSecsRounding::RoundHalfEven)),
+            Ok(Value::Time(false, 0, 10, 0, 0, 123_456))
+        );
+
+        let duration = -time03::Duration::new(1, 500_000_000);
+        assert_eq!(
+            Value::try_from(FracSecs::new(duration, 0, FracSecsRounding::RoundHalfEven)),
+            Ok(Value::Time(true, 0, 0, 0, 2, 0))
+        );
+    }
 
     proptest! {
         #[test]
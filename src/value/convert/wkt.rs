@@ -0,0 +1,92 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! This module implements conversion from/to `Value` for geometries in WKT format.
+
+#![cfg(feature = "wkt")]
+
+use std::str::from_utf8;
+
+use geo_types::Geometry;
+use wkt010::{ToWkt, TryFromWkt};
+
+use crate::value::Value;
+
+use super::{ConvIr, FromValue, FromValueError};
+
+/// Geometry represented as a WKT (Well-Known Text) string.
+///
+/// Use it to consume the result of `ST_AsText()` or to pass a parameter to `ST_GeomFromText(?)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WktText(pub Geometry<f64>);
+
+impl From<WktText> for Value {
+    fn from(x: WktText) -> Value {
+        Value::Bytes(x.0.wkt_string().into_bytes())
+    }
+}
+
+/// Intermediate result of a Value-to-WktText conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WktTextIr {
+    bytes: Vec<u8>,
+    output: WktText,
+}
+
+impl ConvIr<WktText> for WktTextIr {
+    fn new(v: Value) -> Result<WktTextIr, FromValueError> {
+        match v {
+            Value::Bytes(bytes) => {
+                let geometry = from_utf8(&bytes)
+                    .ok()
+                    .and_then(|text| Geometry::try_from_wkt_str(text).ok());
+                match geometry {
+                    Some(geometry) => Ok(WktTextIr {
+                        bytes,
+                        output: WktText(geometry),
+                    }),
+                    None => Err(FromValueError(Value::Bytes(bytes))),
+                }
+            }
+            v => Err(FromValueError(v)),
+        }
+    }
+    fn commit(self) -> WktText {
+        self.output
+    }
+    fn rollback(self) -> Value {
+        Value::Bytes(self.bytes)
+    }
+}
+
+impl FromValue for WktText {
+    type Intermediate = WktTextIr;
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{point, Geometry};
+
+    use super::WktText;
+    use crate::value::{
+        convert::{from_value, from_value_opt},
+        Value,
+    };
+
+    #[test]
+    fn should_convert_wkt() {
+        let value = Value::Bytes(b"POINT(1 -2)".to_vec());
+        let WktText(geometry) = from_value(value);
+        assert_eq!(geometry, Geometry::Point(point!(x: 1.0, y: -2.0)));
+
+        let value = Value::from(WktText(geometry.clone()));
+        assert_eq!(from_value::<WktText>(value), WktText(geometry));
+
+        assert!(from_value_opt::<WktText>(Value::Bytes(b"POINT(1".to_vec())).is_err());
+    }
+}
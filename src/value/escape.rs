@@ -0,0 +1,304 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Escaping of string literals.
+//!
+//! Input is scanned eight bytes at a time, so large byte strings (e.g. blobs destined
+//! for interpolated `INSERT`s or dump files) are mostly copied in bulk.
+
+use std::fmt;
+
+/// Escape characters (following the backslash) indexed by byte, `0` means "no escaping".
+const BACKSLASH_ESCAPES: [u8; 256] = backslash_escapes();
+
+const fn backslash_escapes() -> [u8; 256] {
+    let mut table = [0_u8; 256];
+    table[0x00] = b'0';
+    table[b'\n' as usize] = b'n';
+    table[b'\r' as usize] = b'r';
+    table[b'\\' as usize] = b'\\';
+    table[b'\'' as usize] = b'\'';
+    table[b'"' as usize] = b'"';
+    table[0x1a] = b'Z';
+    table
+}
+
+const LO: u64 = 0x0101_0101_0101_0101;
+const HI: u64 = 0x8080_8080_8080_8080;
+
+/// Returns `true` if the word contains the given byte.
+#[inline(always)]
+fn has_byte(word: u64, byte: u8) -> bool {
+    let x = word ^ (LO * u64::from(byte));
+    x.wrapping_sub(LO) & !x & HI != 0
+}
+
+/// Returns `true` if the chunk of eight bytes contains a byte, that needs escaping.
+#[inline(always)]
+fn chunk_needs_escaping(chunk: &[u8], no_backslash_escape: bool) -> bool {
+    let mut bytes = [0_u8; 8];
+    bytes.copy_from_slice(chunk);
+    let word = u64::from_le_bytes(bytes);
+    if no_backslash_escape {
+        has_byte(word, b'\'')
+    } else {
+        has_byte(word, 0x00)
+            | has_byte(word, b'\n')
+            | has_byte(word, b'\r')
+            | has_byte(word, b'\\')
+            | has_byte(word, b'\'')
+            | has_byte(word, b'"')
+            | has_byte(word, 0x1a)
+    }
+}
+
+/// Returns the escape sequence for the given byte (if it needs escaping).
+#[inline(always)]
+fn escape_sequence(byte: u8, no_backslash_escape: bool) -> Option<[u8; 2]> {
+    if no_backslash_escape {
+        if byte == b'\'' {
+            Some([b'\'', b'\''])
+        } else {
+            None
+        }
+    } else {
+        match BACKSLASH_ESCAPES[byte as usize] {
+            0 => None,
+            x => Some([b'\\', x]),
+        }
+    }
+}
+
+/// Returns the position of the next byte, that needs escaping, starting from `from`.
+fn next_escape(input: &[u8], mut from: usize, no_backslash_escape: bool) -> Option<usize> {
+    while from + 8 <= input.len() {
+        if chunk_needs_escaping(&input[from..from + 8], no_backslash_escape) {
+            break;
+        }
+        from += 8;
+    }
+    input[from..]
+        .iter()
+        .position(|x| escape_sequence(*x, no_backslash_escape).is_some())
+        .map(|pos| from + pos)
+}
+
+/// Escapes the given bytes and appends them to the `output` (quotes are not added).
+///
+/// Special characters are escaped using backslashes or, if `no_backslash_escape` is `true`
+/// (see the `NO_BACKSLASH_ESCAPES` SQL mode), only single quotes are escaped by doubling them.
+///
+/// ```
+/// # use mysql_common::value::escape::escape_bytes_into;
+/// let mut output = Vec::new();
+/// escape_bytes_into(&mut output, b"it's a\nblob\0", false);
+/// assert_eq!(output, b"it\\'s a\\nblob\\0");
+///
+/// output.clear();
+/// escape_bytes_into(&mut output, b"it's a\nblob\0", true);
+/// assert_eq!(output, b"it''s a\nblob\0");
+/// ```
+pub fn escape_bytes_into(output: &mut Vec<u8>, input: &[u8], no_backslash_escape: bool) {
+    output.reserve(input.len());
+    let mut start = 0;
+    while let Some(pos) = next_escape(input, start, no_backslash_escape) {
+        output.extend_from_slice(&input[start..pos]);
+        if let Some(sequence) = escape_sequence(input[pos], no_backslash_escape) {
+            output.extend_from_slice(&sequence);
+        }
+        start = pos + 1;
+    }
+    output.extend_from_slice(&input[start..]);
+}
+
+/// Escapes the given string and appends it to the `output` (quotes are not added).
+///
+/// Same as [`escape_bytes_into`] but for strings.
+///
+/// ```
+/// # use mysql_common::value::escape::escape_string_into;
+/// let mut output = String::from("'");
+/// escape_string_into(&mut output, "it's", false);
+/// output.push('\'');
+/// assert_eq!(output, "'it\\'s'");
+/// ```
+pub fn escape_string_into(output: &mut String, input: &str, no_backslash_escape: bool) {
+    output.reserve(input.len());
+    write_escaped_unquoted(output, input, no_backslash_escape)
+        .expect("writing to a string never fails");
+}
+
+/// Escapes wildcards of a `LIKE` pattern (`%` and `_`) and the escape character itself
+/// by prefixing them with the escape character.
+///
+/// The result is a pattern, that matches the given string literally. It still needs
+/// to be escaped as a string literal (or passed as a parameter). Note, that if the
+/// `NO_BACKSLASH_ESCAPES` SQL mode is enabled, then `LIKE` has no default escape character,
+/// so it must be given explicitly using the `ESCAPE` clause.
+///
+/// ```
+/// # use mysql_common::value::escape::escape_like_pattern;
+/// // `SELECT * FROM t WHERE name LIKE CONCAT(?, '%')`
+/// assert_eq!(escape_like_pattern("100%_a\\b", '\\'), "100\\%\\_a\\\\b");
+/// // `SELECT * FROM t WHERE name LIKE CONCAT(?, '%') ESCAPE '|'`
+/// assert_eq!(escape_like_pattern("100%", '|'), "100|%");
+/// ```
+pub fn escape_like_pattern(pattern: &str, escape_char: char) -> String {
+    let mut output = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        if c == '%' || c == '_' || c == escape_char {
+            output.push(escape_char);
+        }
+        output.push(c);
+    }
+    output
+}
+
+/// Quotes the given identifier (e.g. a table or a column name) using backticks.
+///
+/// Backticks within the identifier are doubled. Backticks could be used regardless
+/// of the `ANSI_QUOTES` SQL mode.
+///
+/// ```
+/// # use mysql_common::value::escape::quote_identifier;
+/// assert_eq!(quote_identifier("my table"), "`my table`");
+/// assert_eq!(quote_identifier("a`b"), "`a``b`");
+/// ```
+pub fn quote_identifier(identifier: &str) -> String {
+    let mut output = String::with_capacity(identifier.len() + 2);
+    output.push('`');
+    for c in identifier.chars() {
+        if c == '`' {
+            output.push('`');
+        }
+        output.push(c);
+    }
+    output.push('`');
+    output
+}
+
+/// Writes the given string as a quoted and escaped string literal
+/// (see [`escape_bytes_into`]).
+pub(crate) fn write_escaped<W: fmt::Write>(
+    output: &mut W,
+    input: &str,
+    no_backslash_escape: bool,
+) -> fmt::Result {
+    output.write_char('\'')?;
+    write_escaped_unquoted(output, input, no_backslash_escape)?;
+    output.write_char('\'')
+}
+
+fn write_escaped_unquoted<W: fmt::Write>(
+    output: &mut W,
+    input: &str,
+    no_backslash_escape: bool,
+) -> fmt::Result {
+    let bytes = input.as_bytes();
+    let mut start = 0;
+    while let Some(pos) = next_escape(bytes, start, no_backslash_escape) {
+        // special characters are ASCII, so `pos` is a char boundary
+        output.write_str(&input[start..pos])?;
+        if let Some([a, b]) = escape_sequence(bytes[pos], no_backslash_escape) {
+            output.write_char(a as char)?;
+            output.write_char(b as char)?;
+        }
+        start = pos + 1;
+    }
+    output.write_str(&input[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_escape(input: &[u8], no_backslash_escape: bool) -> Vec<u8> {
+        let mut output = Vec::new();
+        for byte in input {
+            match escape_sequence(*byte, no_backslash_escape) {
+                Some(sequence) => output.extend_from_slice(&sequence),
+                None => output.push(*byte),
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn should_escape_bytes() {
+        let special = b"\x00\n\r\\'\"\x1a";
+        let mut input = Vec::new();
+        for i in 0..300_usize {
+            input.push((i * 7 % 256) as u8);
+            if i % 37 == 0 {
+                input.push(special[i % special.len()]);
+            }
+        }
+
+        for &no_backslash_escape in &[false, true] {
+            for len in 0..input.len() {
+                let mut output = Vec::new();
+                escape_bytes_into(&mut output, &input[..len], no_backslash_escape);
+                assert_eq!(output, naive_escape(&input[..len], no_backslash_escape));
+            }
+        }
+
+        let mut output = String::new();
+        write_escaped(&mut output, "ŻÓŁW's\x1a", false).unwrap();
+        assert_eq!(output, "'ŻÓŁW\\'s\\Z'");
+
+        let mut output = String::new();
+        escape_string_into(&mut output, "ŻÓŁW's\n", true);
+        assert_eq!(output, "ŻÓŁW''s\n");
+    }
+
+    #[test]
+    fn should_escape_like_patterns_and_identifiers() {
+        assert_eq!(escape_like_pattern("", '\\'), "");
+        assert_eq!(escape_like_pattern("a_b%c\\d", '\\'), "a\\_b\\%c\\\\d");
+        assert_eq!(escape_like_pattern("ż_|", '|'), "ż|_||");
+
+        assert_eq!(quote_identifier(""), "``");
+        assert_eq!(quote_identifier("``"), "``````");
+        assert_eq!(quote_identifier("ŻÓŁW"), "`ŻÓŁW`");
+    }
+
+    #[cfg(feature = "nightly")]
+    mod benches {
+        use super::*;
+
+        fn blob() -> Vec<u8> {
+            (0..4 * 1024 * 1024_usize)
+                .map(|i| {
+                    if i % 1000 == 0 {
+                        b'\''
+                    } else {
+                        b'a' + (i % 26) as u8
+                    }
+                })
+                .collect()
+        }
+
+        #[bench]
+        fn bench_escape_bytes(bencher: &mut test::Bencher) {
+            let input = blob();
+            let mut output = Vec::with_capacity(input.len() * 2);
+            bencher.bytes = input.len() as u64;
+            bencher.iter(|| {
+                output.clear();
+                escape_bytes_into(&mut output, &input, false);
+            });
+        }
+
+        #[bench]
+        fn bench_escape_bytes_naive(bencher: &mut test::Bencher) {
+            let input = blob();
+            bencher.bytes = input.len() as u64;
+            bencher.iter(|| naive_escape(&input, false));
+        }
+    }
+}
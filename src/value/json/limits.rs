@@ -0,0 +1,215 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Depth and size limits for JSON conversions.
+//!
+//! JSON documents might be attacker-controlled, so parsing them without limits
+//! could exhaust memory or stack.
+
+use serde::de::DeserializeOwned;
+
+/// JSON document exceeds the configured limits (see [`JsonLimits`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum JsonLimitError {
+    #[error("JSON nesting depth exceeds the limit of {max_depth}")]
+    TooDeep { max_depth: usize },
+    #[error("JSON document size of {size} bytes exceeds the limit of {max_size} bytes")]
+    TooLarge { size: usize, max_size: usize },
+}
+
+/// Error of a limited JSON parsing (see [`JsonLimits::deserialize`]).
+#[derive(Debug, thiserror::Error)]
+pub enum LimitedJsonError {
+    #[error(transparent)]
+    Limit(#[from] JsonLimitError),
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Depth and size limits applied to JSON conversions.
+///
+/// Default limits are bounded both in depth and in size. They are used by `FromValue`
+/// implementations for `serde_json::Value` and `Deserialized<T>`, and by the JSONB to JSON
+/// conversion, so larger documents should be parsed with explicitly relaxed limits.
+///
+/// ```
+/// # use mysql_common::value::json::limits::{JsonLimits, JsonLimitError, LimitedJsonError};
+/// let limits = JsonLimits::new().with_max_depth(2).with_max_size(Some(64));
+///
+/// let json: serde_json::Value = limits.deserialize(br#"{"foo": [1, 2]}"#).unwrap();
+/// assert_eq!(json["foo"][1], 2);
+///
+/// match limits.deserialize::<serde_json::Value>(b"[[[1]]]") {
+///     Err(LimitedJsonError::Limit(JsonLimitError::TooDeep { max_depth: 2 })) => (),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JsonLimits {
+    max_depth: usize,
+    max_size: Option<usize>,
+}
+
+impl JsonLimits {
+    /// Default maximum nesting depth (matches the maximum depth of a MySql JSON document).
+    pub const DEFAULT_MAX_DEPTH: usize = 100;
+
+    /// Default maximum size of a document in bytes (matches the default `max_allowed_packet`
+    /// of MySql 8.0).
+    pub const DEFAULT_MAX_SIZE: usize = 64 * 1024 * 1024;
+
+    /// Creates default limits (depth is limited to [`JsonLimits::DEFAULT_MAX_DEPTH`],
+    /// size is limited to [`JsonLimits::DEFAULT_MAX_SIZE`]).
+    pub fn new() -> Self {
+        Self {
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            max_size: Some(Self::DEFAULT_MAX_SIZE),
+        }
+    }
+
+    /// Defines the maximum nesting depth of arrays and objects.
+    ///
+    /// Note, that `serde_json` will fail to parse a textual document nested 128 levels deep
+    /// or deeper regardless of this limit.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Defines the maximum size of a document in bytes (`None` means unlimited).
+    pub fn with_max_size(mut self, max_size: Option<usize>) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Returns the maximum nesting depth.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Returns the maximum size of a document in bytes (`None` means unlimited).
+    pub fn max_size(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    /// Returns an error if the given nesting depth exceeds the limit.
+    pub fn check_depth(&self, depth: usize) -> Result<(), JsonLimitError> {
+        if depth > self.max_depth {
+            Err(JsonLimitError::TooDeep {
+                max_depth: self.max_depth,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns an error if the given document size exceeds the limit.
+    pub fn check_size(&self, size: usize) -> Result<(), JsonLimitError> {
+        match self.max_size {
+            Some(max_size) if size > max_size => Err(JsonLimitError::TooLarge { size, max_size }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the given textual JSON document against the limits.
+    ///
+    /// This is a cheap non-recursive scan, that doesn't validate the document.
+    pub fn check_text(&self, json: &[u8]) -> Result<(), JsonLimitError> {
+        self.check_size(json.len())?;
+
+        let mut depth = 0_usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        for byte in json {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if *byte == b'\\' {
+                    escaped = true;
+                } else if *byte == b'"' {
+                    in_string = false;
+                }
+            } else {
+                match byte {
+                    b'"' => in_string = true,
+                    b'[' | b'{' => {
+                        depth += 1;
+                        self.check_depth(depth)?;
+                    }
+                    b']' | b'}' => depth = depth.saturating_sub(1),
+                    _ => (),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deserializes the given textual JSON document, if it is within the limits.
+    pub fn deserialize<T: DeserializeOwned>(&self, json: &[u8]) -> Result<T, LimitedJsonError> {
+        self.check_text(json)?;
+        Ok(serde_json::from_slice(json)?)
+    }
+}
+
+impl Default for JsonLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_check_text() {
+        let limits = JsonLimits::new().with_max_depth(2);
+        limits.check_text(br#"{"a": [1, "]]]"], "b": {}}"#).unwrap();
+        limits.check_text(br#"["[[[\"[[["]"#).unwrap();
+        assert_eq!(
+            limits.check_text(br#"{"a": [{"b": 1}]}"#),
+            Err(JsonLimitError::TooDeep { max_depth: 2 })
+        );
+
+        let limits = limits.with_max_size(Some(4));
+        limits.check_text(b"[12]").unwrap();
+        assert_eq!(
+            limits.check_text(b"[123]"),
+            Err(JsonLimitError::TooLarge {
+                size: 5,
+                max_size: 4
+            })
+        );
+    }
+
+    #[test]
+    fn should_deserialize_within_limits() {
+        let limits = JsonLimits::default();
+        assert_eq!(
+            limits.check_size(JsonLimits::DEFAULT_MAX_SIZE + 1),
+            Err(JsonLimitError::TooLarge {
+                size: JsonLimits::DEFAULT_MAX_SIZE + 1,
+                max_size: JsonLimits::DEFAULT_MAX_SIZE,
+            })
+        );
+        let deep = format!("{}{}", "[".repeat(200), "]".repeat(200));
+        assert!(matches!(
+            limits.deserialize::<serde_json::Value>(deep.as_bytes()),
+            Err(LimitedJsonError::Limit(JsonLimitError::TooDeep { .. }))
+        ));
+        assert!(matches!(
+            limits.deserialize::<serde_json::Value>(b"[1,"),
+            Err(LimitedJsonError::Json(_))
+        ));
+        assert_eq!(
+            limits.deserialize::<Vec<u8>>(b"[1, 2]").unwrap(),
+            vec![1, 2]
+        );
+    }
+}
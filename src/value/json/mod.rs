@@ -6,6 +6,7 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+pub mod limits;
 #[cfg(not(feature = "rustc_serialize"))]
 pub mod serde_integration;
 
@@ -20,9 +21,46 @@ pub mod serde_integration;
 /// conn.prep_exec("INSERT INTO table (json_column) VALUES (?)",
 ///                (Serialized(SerializableStruct),));
 /// ```
+///
+/// Use `Serialized<&T>` (see `Serialized::as_ref`) to avoid cloning a large value:
+///
+/// ```ignore
+/// let value = SerializableStruct { /* ... */ };
+/// conn.exec_drop("INSERT INTO table (json_column) VALUES (?)", (Serialized(&value),));
+/// ```
 #[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
 pub struct Serialized<T>(pub T);
 
+impl<T> Serialized<T> {
+    /// Converts `&Serialized<T>` to `Serialized<&T>`.
+    pub fn as_ref(&self) -> Serialized<&T> {
+        Serialized(&self.0)
+    }
+}
+
+/// Same as [`Serialized`] but borrows the value (`T` could be unsized).
+///
+/// ```ignore
+/// let rows: Vec<SerializableStruct> = /* ... */;
+/// conn.exec_drop("INSERT INTO table (json_column) VALUES (?)", (SerializedRef(&rows[..]),));
+/// ```
+#[derive(PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
+pub struct SerializedRef<'a, T: ?Sized>(pub &'a T);
+
+impl<T: ?Sized> Clone for SerializedRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for SerializedRef<'_, T> {}
+
+impl<'a, T> From<&'a Serialized<T>> for SerializedRef<'a, T> {
+    fn from(x: &'a Serialized<T>) -> Self {
+        SerializedRef(&x.0)
+    }
+}
+
 /// Use it to parse `T: Deserialize` from `Value`.
 ///
 /// ```ignore
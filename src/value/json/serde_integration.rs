@@ -6,14 +6,14 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use super::{Deserialized, DeserializedIr, Serialized};
+use super::{limits::JsonLimits, Deserialized, DeserializedIr, Serialized, SerializedRef};
 use crate::value::{
     convert::{ConvIr, FromValue, FromValueError},
     Value,
 };
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{self, Value as Json};
-use std::str::{from_utf8, from_utf8_unchecked};
+use std::str::from_utf8;
 
 impl From<Json> for Value {
     fn from(x: Json) -> Value {
@@ -21,12 +21,47 @@ impl From<Json> for Value {
     }
 }
 
+impl<T: Serialize> Serialized<T> {
+    /// Serializes the wrapped value into a `Value`.
+    ///
+    /// Non-panicking version of the `From<Serialized<T>> for Value` conversion.
+    pub fn try_into_value(self) -> Result<Value, serde_json::Error> {
+        SerializedRef(&self.0).try_into_value()
+    }
+}
+
+impl<T: Serialize + ?Sized> SerializedRef<'_, T> {
+    /// Serializes the borrowed value into a `Value`.
+    ///
+    /// Non-panicking version of the `From<SerializedRef<T>> for Value` conversion.
+    pub fn try_into_value(self) -> Result<Value, serde_json::Error> {
+        serde_json::to_vec(self.0).map(Value::Bytes)
+    }
+}
+
+/// Note, that `T` could be a reference, i.e. `Serialized<&T>` is also convertible.
+///
+/// # Panic
+///
+/// Conversion will panic if `T`'s implementation of `Serialize` decides to fail
+/// (see [`Serialized::try_into_value`]).
 impl<T: Serialize> From<Serialized<T>> for Value {
     fn from(x: Serialized<T>) -> Value {
-        Value::Bytes(serde_json::to_string(&x.0).unwrap().into())
+        x.try_into_value().unwrap()
     }
 }
 
+/// # Panic
+///
+/// Conversion will panic if `T`'s implementation of `Serialize` decides to fail
+/// (see [`SerializedRef::try_into_value`]).
+impl<T: Serialize + ?Sized> From<SerializedRef<'_, T>> for Value {
+    fn from(x: SerializedRef<'_, T>) -> Value {
+        x.try_into_value().unwrap()
+    }
+}
+
+/// Default [`JsonLimits`] are applied to the conversion.
 impl<T: DeserializeOwned> ConvIr<Deserialized<T>> for DeserializedIr<T> {
     fn new(v: Value) -> Result<DeserializedIr<T>, FromValueError> {
         let (output, bytes) = {
@@ -38,7 +73,7 @@ impl<T: DeserializeOwned> ConvIr<Deserialized<T>> for DeserializedIr<T> {
                 v => return Err(FromValueError(v)),
             };
             let output = {
-                match serde_json::from_str(unsafe { from_utf8_unchecked(&*bytes) }) {
+                match JsonLimits::default().deserialize(&*bytes) {
                     Ok(output) => output,
                     Err(_) => return Err(FromValueError(Value::Bytes(bytes))),
                 }
@@ -71,6 +106,7 @@ pub struct JsonIr {
     output: Json,
 }
 
+/// Default [`JsonLimits`] are applied to the conversion.
 impl ConvIr<Json> for JsonIr {
     fn new(v: Value) -> Result<JsonIr, FromValueError> {
         let (output, bytes) = {
@@ -82,7 +118,7 @@ impl ConvIr<Json> for JsonIr {
                 v => return Err(FromValueError(v)),
             };
             let output = {
-                match serde_json::from_str(unsafe { from_utf8_unchecked(&*bytes) }) {
+                match JsonLimits::default().deserialize(&*bytes) {
                     Ok(output) => output,
                     Err(_) => return Err(FromValueError(Value::Bytes(bytes))),
                 }
@@ -104,3 +140,54 @@ impl ConvIr<Json> for JsonIr {
 impl FromValue for Json {
     type Intermediate = JsonIr;
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::value::{
+        convert::{from_value_opt, FromValueError},
+        json::{Serialized, SerializedRef},
+        Value,
+    };
+
+    #[test]
+    fn should_convert_serialized_ref() {
+        let json = json!({ "foo": [1, 2, 3] });
+        let by_ref = Value::from(Serialized(&json));
+        assert_eq!(by_ref, Value::from(Serialized(json.clone()).as_ref()));
+        assert_eq!(by_ref, Value::Bytes(br#"{"foo":[1,2,3]}"#.to_vec()));
+        assert_eq!(by_ref, Value::from(SerializedRef(&json)));
+
+        let items = vec![json.clone(), json];
+        assert_eq!(
+            Value::from(SerializedRef(&items[..])),
+            Value::Bytes(br#"[{"foo":[1,2,3]},{"foo":[1,2,3]}]"#.to_vec())
+        );
+    }
+
+    #[test]
+    fn should_report_serialization_failure() {
+        let mut map = std::collections::HashMap::new();
+        map.insert((1_u8, 2_u8), 3_u8);
+        assert!(SerializedRef(&map).try_into_value().is_err());
+        assert!(Serialized(map).try_into_value().is_err());
+        assert_eq!(
+            Serialized(vec![1, 2]).try_into_value().unwrap(),
+            Value::Bytes(b"[1,2]".to_vec())
+        );
+    }
+
+    #[test]
+    fn should_apply_default_limits() {
+        let deep = format!("{}{}", "[".repeat(101), "]".repeat(101));
+        let value = Value::Bytes(deep.into_bytes());
+        assert_eq!(
+            from_value_opt::<serde_json::Value>(value.clone()),
+            Err(FromValueError(value))
+        );
+
+        let ok = format!("{}{}", "[".repeat(100), "]".repeat(100));
+        from_value_opt::<serde_json::Value>(Value::Bytes(ok.into_bytes())).unwrap();
+    }
+}
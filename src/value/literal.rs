@@ -0,0 +1,156 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Parsers for MySql hexadecimal and bit-value literals.
+//!
+//! Such literals could be found in places like column default metadata
+//! (e.g. `0x1A2B`, `X'1A2B'`, `b'1010'`).
+
+/// Parses a hexadecimal literal (`X'1A2B'`, `x'1a2b'` or `0x1A2B`) into bytes.
+///
+/// Quoted notation requires an even number of digits. Odd number of digits
+/// in the `0x` notation is treated as if it had a leading zero.
+///
+/// ```
+/// # use mysql_common::value::literal::parse_hex_literal;
+/// assert_eq!(parse_hex_literal(b"0x1A2B"), Some(vec![0x1A, 0x2B]));
+/// assert_eq!(parse_hex_literal(b"X'0aff'"), Some(vec![0x0A, 0xFF]));
+/// assert_eq!(parse_hex_literal(b"0xA"), Some(vec![0x0A]));
+/// assert_eq!(parse_hex_literal(b"X'A'"), None);
+/// ```
+pub fn parse_hex_literal(input: &[u8]) -> Option<Vec<u8>> {
+    let digits = match input {
+        [b'0', b'x', digits @ ..] if !digits.is_empty() => digits,
+        [x, b'\'', digits @ .., b'\''] if (*x == b'x' || *x == b'X') && digits.len() % 2 == 0 => {
+            digits
+        }
+        _ => return None,
+    };
+
+    let mut output = Vec::with_capacity((digits.len() + 1) / 2);
+    let (head, tail) = digits.split_at(digits.len() % 2);
+    if let [digit] = head {
+        output.push(hex_digit(*digit)?);
+    }
+    for pair in tail.chunks(2) {
+        output.push(hex_digit(pair[0])? << 4 | hex_digit(pair[1])?);
+    }
+
+    Some(output)
+}
+
+/// Parses a bit-value literal (`b'1010'`, `B'1010'` or `0b1010`) into bytes.
+///
+/// Value is left-padded with zero bits up to a whole number of bytes.
+///
+/// ```
+/// # use mysql_common::value::literal::parse_bit_literal;
+/// assert_eq!(parse_bit_literal(b"b'1010'"), Some(vec![0b1010]));
+/// assert_eq!(parse_bit_literal(b"0b100000001"), Some(vec![0b1, 0b1]));
+/// assert_eq!(parse_bit_literal(b"b'102'"), None);
+/// ```
+pub fn parse_bit_literal(input: &[u8]) -> Option<Vec<u8>> {
+    let digits = match input {
+        [b'0', b'b', digits @ ..] if !digits.is_empty() => digits,
+        [b, b'\'', digits @ .., b'\''] if *b == b'b' || *b == b'B' => digits,
+        _ => return None,
+    };
+
+    let mut output = Vec::with_capacity((digits.len() + 7) / 8);
+    let (head, tail) = digits.split_at(digits.len() % 8);
+    if !head.is_empty() {
+        output.push(bits_to_byte(head)?);
+    }
+    for chunk in tail.chunks(8) {
+        output.push(bits_to_byte(chunk)?);
+    }
+
+    Some(output)
+}
+
+/// Parses a hexadecimal or a bit-value literal into bytes.
+pub fn parse_binary_literal(input: &[u8]) -> Option<Vec<u8>> {
+    parse_hex_literal(input).or_else(|| parse_bit_literal(input))
+}
+
+/// Parses a hexadecimal or a bit-value literal into an unsigned integer.
+///
+/// Returns `None` if the input isn't a literal or if its value doesn't fit into `u64`.
+///
+/// ```
+/// # use mysql_common::value::literal::parse_int_literal;
+/// assert_eq!(parse_int_literal(b"0x1A2B"), Some(0x1A2B));
+/// assert_eq!(parse_int_literal(b"b'1010'"), Some(10));
+/// assert_eq!(parse_int_literal(b"X''"), Some(0));
+/// assert_eq!(parse_int_literal(b"0x010000000000000000"), None);
+/// ```
+pub fn parse_int_literal(input: &[u8]) -> Option<u64> {
+    let bytes = parse_binary_literal(input)?;
+    // leading zero bytes doesn't affect the value
+    let start = bytes.iter().position(|x| *x != 0).unwrap_or(bytes.len());
+    let bytes = &bytes[start..];
+    if bytes.len() > 8 {
+        return None;
+    }
+    let mut buf = [0_u8; 8];
+    buf[8 - bytes.len()..].copy_from_slice(bytes);
+    Some(u64::from_be_bytes(buf))
+}
+
+fn hex_digit(x: u8) -> Option<u8> {
+    match x {
+        b'0'..=b'9' => Some(x - b'0'),
+        b'a'..=b'f' => Some(x - b'a' + 10),
+        b'A'..=b'F' => Some(x - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn bits_to_byte(bits: &[u8]) -> Option<u8> {
+    bits.iter().try_fold(0_u8, |acc, bit| match bit {
+        b'0' => Some(acc << 1),
+        b'1' => Some(acc << 1 | 1),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_hex_literal() {
+        assert_eq!(parse_hex_literal(b"X''"), Some(vec![]));
+        assert_eq!(parse_hex_literal(b"0x0"), Some(vec![0]));
+        assert_eq!(parse_hex_literal(b"0xabc"), Some(vec![0x0A, 0xBC]));
+        assert_eq!(parse_hex_literal(b"0x"), None);
+        assert_eq!(parse_hex_literal(b"0X1A"), None);
+        assert_eq!(parse_hex_literal(b"0x1G"), None);
+        assert_eq!(parse_hex_literal(b"X'1A"), None);
+        assert_eq!(parse_hex_literal(b"1A"), None);
+    }
+
+    #[test]
+    fn should_parse_bit_literal() {
+        assert_eq!(parse_bit_literal(b"b''"), Some(vec![]));
+        assert_eq!(parse_bit_literal(b"B'0'"), Some(vec![0]));
+        assert_eq!(parse_bit_literal(b"b'11111111'"), Some(vec![0xFF]));
+        assert_eq!(parse_bit_literal(b"0b0"), Some(vec![0]));
+        assert_eq!(parse_bit_literal(b"0b"), None);
+        assert_eq!(parse_bit_literal(b"0B1"), None);
+        assert_eq!(parse_bit_literal(b"b'1"), None);
+    }
+
+    #[test]
+    fn should_parse_int_literal() {
+        assert_eq!(parse_int_literal(b"0xFFFFFFFFFFFFFFFF"), Some(u64::MAX));
+        assert_eq!(parse_int_literal(b"0x00FFFFFFFFFFFFFFFF"), Some(u64::MAX));
+        assert_eq!(parse_int_literal(b"b'1'"), Some(1));
+        assert_eq!(parse_int_literal(b"'1'"), None);
+    }
+}
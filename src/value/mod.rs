@@ -8,13 +8,12 @@
 
 use bytes::BufMut;
 
-use std::{convert::TryFrom, fmt, io, marker::PhantomData, str::from_utf8};
+use std::{convert::TryFrom, fmt, io, marker::PhantomData, mem, str::from_utf8};
 
 use crate::{
     constants::{ColumnFlags, ColumnType},
     io::{BufMutExt, ParseBuf},
     misc::{
-        lenenc_str_len,
         raw::bytes::{LenEnc, RawBytes},
         unexpected_buf_eof,
     },
@@ -22,8 +21,13 @@ use crate::{
     value::Value::*,
 };
 
+pub use self::borrowed::ValueRef;
+
+mod borrowed;
 pub mod convert;
+pub mod escape;
 pub mod json;
+pub mod literal;
 
 /// Side of MySql value serialization.
 pub trait SerializationSide {
@@ -71,72 +75,7 @@ pub enum Value {
 
 impl MySerialize for Value {
     fn serialize(&self, buf: &mut Vec<u8>) {
-        match self {
-            Self::NULL => (),
-            Value::Bytes(x) => {
-                buf.put_lenenc_str(&*x);
-            }
-            Value::Int(x) => {
-                buf.put_i64_le(*x);
-            }
-            Value::UInt(x) => {
-                buf.put_u64_le(*x);
-            }
-            Value::Float(x) => {
-                buf.put_f32_le(*x);
-            }
-            Value::Double(x) => {
-                buf.put_f64_le(*x);
-            }
-            Value::Date(0u16, 0u8, 0u8, 0u8, 0u8, 0u8, 0u32) => {
-                buf.put_u8(0);
-            }
-            Value::Date(year, mon, day, 0u8, 0u8, 0u8, 0u32) => {
-                buf.put_u8(4);
-                buf.put_u16_le(*year);
-                buf.put_u8(*mon);
-                buf.put_u8(*day);
-            }
-            Value::Date(year, mon, day, hour, min, sec, 0u32) => {
-                buf.put_u8(7);
-                buf.put_u16_le(*year);
-                buf.put_u8(*mon);
-                buf.put_u8(*day);
-                buf.put_u8(*hour);
-                buf.put_u8(*min);
-                buf.put_u8(*sec);
-            }
-            Value::Date(year, mon, day, hour, min, sec, usec) => {
-                buf.put_u8(11);
-                buf.put_u16_le(*year);
-                buf.put_u8(*mon);
-                buf.put_u8(*day);
-                buf.put_u8(*hour);
-                buf.put_u8(*min);
-                buf.put_u8(*sec);
-                buf.put_u32_le(*usec);
-            }
-            Value::Time(_, 0u32, 0u8, 0u8, 0u8, 0u32) => {
-                buf.put_u8(0);
-            }
-            Value::Time(neg, d, h, m, s, 0u32) => {
-                buf.put_u8(8);
-                buf.put_u8(if *neg { 1 } else { 0 });
-                buf.put_u32_le(*d);
-                buf.put_u8(*h);
-                buf.put_u8(*m);
-                buf.put_u8(*s);
-            }
-            Value::Time(neg, days, hours, mins, secs, usecs) => {
-                buf.put_u8(12);
-                buf.put_u8(if *neg { 1 } else { 0 });
-                buf.put_u32_le(*days);
-                buf.put_u8(*hours);
-                buf.put_u8(*mins);
-                buf.put_u8(*secs);
-                buf.put_u32_le(*usecs);
-            }
-        }
+        ValueRef::from(self).serialize(buf)
     }
 }
 
@@ -166,43 +105,175 @@ impl<'de> MyDeserialize<'de> for ValueDeserializer<BinValue> {
     }
 }
 
-/// Will escape string for SQL depending on `no_backslash_escape` flag.
-fn escaped(input: &str, no_backslash_escape: bool) -> String {
-    let mut output = String::with_capacity(input.len());
-    output.push('\'');
-    if no_backslash_escape {
-        for c in input.chars() {
-            if c == '\'' {
-                output.push('\'');
-                output.push('\'');
-            } else {
-                output.push(c);
-            }
+/// Serializer for a MySql value (the inverse of [`ValueDeserializer`]).
+///
+/// `T` specifies the value representation. Currently only textual representation is
+/// supported (binary representation is available via the `MySerialize` impl of a `Value`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueSerializer<'a, T> {
+    value: &'a Value,
+    column_type: Option<ColumnType>,
+    __repr: PhantomData<T>,
+}
+
+impl<'a, T> ValueSerializer<'a, T> {
+    pub fn new(value: &'a Value) -> Self {
+        Self {
+            value,
+            column_type: None,
+            __repr: PhantomData,
         }
-    } else {
-        for c in input.chars() {
-            if c == '\x00' {
-                output.push('\\');
-                output.push('0');
-            } else if c == '\n' {
-                output.push('\\');
-                output.push('n');
-            } else if c == '\r' {
-                output.push('\\');
-                output.push('r');
-            } else if c == '\\' || c == '\'' || c == '"' {
-                output.push('\\');
-                output.push(c);
-            } else if c == '\x1a' {
-                output.push('\\');
-                output.push('Z');
-            } else {
-                output.push(c);
+    }
+
+    /// Defines the type of a column this value belongs to (defaults to `None`).
+    ///
+    /// Used to choose the format of a textual representation, e.g. `DATE` values
+    /// won't contain the time part.
+    pub fn with_column_type(mut self, column_type: Option<ColumnType>) -> Self {
+        self.column_type = column_type;
+        self
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &'a Value {
+        self.value
+    }
+
+    /// Returns the column type, if defined.
+    pub fn column_type(&self) -> Option<ColumnType> {
+        self.column_type
+    }
+}
+
+impl MySerialize for ValueSerializer<'_, TextValue> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        let text = match *self.value {
+            Value::NULL => {
+                buf.put_u8(0xfb);
+                return;
+            }
+            Value::Bytes(ref bytes) => {
+                buf.put_lenenc_str(bytes);
+                return;
+            }
+            Value::Int(x) => x.to_string(),
+            Value::UInt(x) => x.to_string(),
+            // NaN and infinity can't be stored in MySql
+            Value::Float(x) if !x.is_finite() => {
+                buf.put_u8(0xfb);
+                return;
+            }
+            Value::Double(x) if !x.is_finite() => {
+                buf.put_u8(0xfb);
+                return;
             }
+            Value::Float(x) => x.to_string(),
+            Value::Double(x) => x.to_string(),
+            Value::Date(year, month, day, hour, minute, second, micros) => {
+                let mut text = format!("{:04}-{:02}-{:02}", year, month, day);
+                if self.column_type != Some(ColumnType::MYSQL_TYPE_DATE)
+                    && self.column_type != Some(ColumnType::MYSQL_TYPE_NEWDATE)
+                {
+                    text.push_str(&format!(" {:02}:{:02}:{:02}", hour, minute, second));
+                    if micros > 0 {
+                        text.push_str(&format!(".{:06}", micros));
+                    }
+                }
+                text
+            }
+            Value::Time(neg, days, hours, minutes, seconds, micros) => {
+                let mut text = format!(
+                    "{}{:02}:{:02}:{:02}",
+                    if neg { "-" } else { "" },
+                    u64::from(days) * 24 + u64::from(hours),
+                    minutes,
+                    seconds
+                );
+                if micros > 0 {
+                    text.push_str(&format!(".{:06}", micros));
+                }
+                text
+            }
+        };
+        buf.put_lenenc_str(text.as_bytes());
+    }
+}
+
+/// `Display` adapter that renders a `Value` as an SQL literal (see [`Value::sql_literal`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SqlLiteral<'a> {
+    value: &'a Value,
+    no_backslash_escape: bool,
+    hex_bytes: bool,
+}
+
+impl SqlLiteral<'_> {
+    /// Defines whether to render byte strings as `_binary X'..'` hex literals
+    /// (defaults to `false`).
+    ///
+    /// Hex literals are binary-safe, so arbitrary data could be embedded into generated SQL
+    /// or into logs. If `false`, then only non-UTF-8 byte strings will be rendered as hex.
+    pub fn with_hex_bytes(mut self, hex_bytes: bool) -> Self {
+        self.hex_bytes = hex_bytes;
+        self
+    }
+}
+
+impl fmt::Display for SqlLiteral<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self.value {
+            Value::NULL => f.write_str("NULL"),
+            // NaN and infinity can't be stored in MySql
+            Value::Float(x) if !x.is_finite() => f.write_str("NULL"),
+            Value::Double(x) if !x.is_finite() => f.write_str("NULL"),
+            Value::Int(x) => write!(f, "{}", x),
+            Value::UInt(x) => write!(f, "{}", x),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Double(x) => write!(f, "{}", x),
+            Value::Date(y, m, d, 0, 0, 0, 0) => write!(f, "'{:04}-{:02}-{:02}'", y, m, d),
+            Value::Date(year, month, day, hour, minute, second, 0) => write!(
+                f,
+                "'{:04}-{:02}-{:02} {:02}:{:02}:{:02}'",
+                year, month, day, hour, minute, second
+            ),
+            Value::Date(year, month, day, hour, minute, second, micros) => write!(
+                f,
+                "'{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}'",
+                year, month, day, hour, minute, second, micros
+            ),
+            Value::Time(neg, days, hours, minutes, seconds, micros) => {
+                write!(
+                    f,
+                    "'{}{:03}:{:02}:{:02}",
+                    if neg { "-" } else { "" },
+                    u64::from(days) * 24 + u64::from(hours),
+                    minutes,
+                    seconds,
+                )?;
+                if micros > 0 {
+                    write!(f, ".{:06}", micros)?;
+                }
+                f.write_str("'")
+            }
+            Value::Bytes(ref bytes) if self.hex_bytes => {
+                f.write_str("_binary X'")?;
+                for c in bytes.iter() {
+                    write!(f, "{:02X}", *c)?;
+                }
+                f.write_str("'")
+            }
+            Value::Bytes(ref bytes) => match from_utf8(&*bytes) {
+                Ok(string) => escape::write_escaped(f, string, self.no_backslash_escape),
+                Err(_) => {
+                    f.write_str("0x")?;
+                    for c in bytes.iter() {
+                        write!(f, "{:02X}", *c)?;
+                    }
+                    Ok(())
+                }
+            },
         }
     }
-    output.push('\'');
-    output
 }
 
 macro_rules! de_num {
@@ -224,76 +295,130 @@ macro_rules! de_num {
 impl Value {
     /// Returns length in binary serialized form.
     pub fn bin_len(&self) -> u64 {
+        ValueRef::from(self).bin_len()
+    }
+
+    /// Returns the memory usage of this value (in bytes), i.e. the size of the value itself
+    /// plus the size of its heap allocation (the capacity of `Bytes`).
+    ///
+    /// Numeric and temporal values are stored inline.
+    pub fn size_in_bytes(&self) -> usize {
+        mem::size_of::<Value>() + self.heap_size_in_bytes()
+    }
+
+    /// Returns the size of the heap allocation of this value (in bytes).
+    pub(crate) fn heap_size_in_bytes(&self) -> usize {
         match self {
-            Value::NULL => 0,
-            Value::Bytes(x) => lenenc_str_len(&*x),
-            Value::Int(_) => 8,
-            Value::UInt(_) => 8,
-            Value::Float(_) => 4,
-            Value::Double(_) => 8,
-            Value::Date(0u16, 0u8, 0u8, 0u8, 0u8, 0u8, 0u32) => 1,
-            Value::Date(_, _, _, 0u8, 0u8, 0u8, 0u32) => 5,
-            Value::Date(_, _, _, _, _, _, 0u32) => 8,
-            Value::Date(_, _, _, _, _, _, _) => 12,
-            Value::Time(_, 0u32, 0u8, 0u8, 0u8, 0u32) => 1,
-            Value::Time(_, _, _, _, _, 0u32) => 9,
-            Value::Time(_, _, _, _, _, _) => 13,
+            Value::Bytes(x) => x.capacity(),
+            Value::NULL
+            | Value::Int(_)
+            | Value::UInt(_)
+            | Value::Float(_)
+            | Value::Double(_)
+            | Value::Date(..)
+            | Value::Time(..) => 0,
         }
     }
 
+    /// Returns an SQL literal representing this value.
+    ///
+    /// Strings are quoted and escaped (either using backslashes or, if `no_backslash_escape`
+    /// is `true`, by doubling the single quotes), non-UTF-8 byte strings are rendered
+    /// as hexadecimal literals.
     pub fn as_sql(&self, no_backslash_escape: bool) -> String {
-        match *self {
-            Value::NULL => "NULL".into(),
-            Value::Int(x) => format!("{}", x),
-            Value::UInt(x) => format!("{}", x),
-            Value::Float(x) => format!("{}", x),
-            Value::Double(x) => format!("{}", x),
-            Value::Date(y, m, d, 0, 0, 0, 0) => format!("'{:04}-{:02}-{:02}'", y, m, d),
-            Value::Date(year, month, day, hour, minute, second, 0) => format!(
-                "'{:04}-{:02}-{:02} {:02}:{:02}:{:02}'",
-                year, month, day, hour, minute, second
-            ),
-            Value::Date(year, month, day, hour, minute, second, micros) => format!(
-                "'{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}'",
-                year, month, day, hour, minute, second, micros
-            ),
-            Value::Time(neg, d, h, i, s, 0) => {
-                if neg {
-                    format!("'-{:03}:{:02}:{:02}'", d * 24 + u32::from(h), i, s)
-                } else {
-                    format!("'{:03}:{:02}:{:02}'", d * 24 + u32::from(h), i, s)
-                }
-            }
-            Value::Time(neg, days, hours, minutes, seconds, micros) => {
-                if neg {
-                    format!(
-                        "'-{:03}:{:02}:{:02}.{:06}'",
-                        days * 24 + u32::from(hours),
-                        minutes,
-                        seconds,
-                        micros
-                    )
-                } else {
-                    format!(
-                        "'{:03}:{:02}:{:02}.{:06}'",
-                        days * 24 + u32::from(hours),
-                        minutes,
-                        seconds,
-                        micros
-                    )
-                }
-            }
-            Value::Bytes(ref bytes) => match from_utf8(&*bytes) {
-                Ok(string) => escaped(string, no_backslash_escape),
-                Err(_) => {
-                    let mut s = String::from("0x");
-                    for c in bytes.iter() {
-                        s.extend(format!("{:02X}", *c).chars())
-                    }
-                    s
-                }
-            },
+        self.sql_literal(no_backslash_escape).to_string()
+    }
+
+    /// Returns a `Display` adapter that renders this value as an SQL literal
+    /// (same as [`Value::as_sql`] but without intermediate allocation).
+    pub fn sql_literal(&self, no_backslash_escape: bool) -> SqlLiteral<'_> {
+        SqlLiteral {
+            value: self,
+            no_backslash_escape,
+            hex_bytes: false,
+        }
+    }
+
+    /// Returns precision and scale of a `DECIMAL` column, that is able to store this value
+    /// (`None` if this value is not a number).
+    ///
+    /// `Bytes` are expected to contain a decimal number in text form (e.g. `-12.340`).
+    /// Trailing zeros of the fractional part are significant.
+    ///
+    /// ```
+    /// # use mysql_common::value::Value;
+    /// assert_eq!(Value::from("-12.340").decimal_precision_scale(), Some((5, 3)));
+    /// assert_eq!(Value::Int(100).decimal_precision_scale(), Some((3, 0)));
+    /// assert_eq!(Value::from("1e3").decimal_precision_scale(), None);
+    /// ```
+    pub fn decimal_precision_scale(&self) -> Option<(u32, u32)> {
+        match self {
+            Value::Bytes(x) => decimal_precision_scale(x),
+            Value::Int(x) => decimal_precision_scale(x.to_string().as_bytes()),
+            Value::UInt(x) => decimal_precision_scale(x.to_string().as_bytes()),
+            Value::Float(x) => decimal_precision_scale(x.to_string().as_bytes()),
+            Value::Double(x) => decimal_precision_scale(x.to_string().as_bytes()),
+            Value::NULL | Value::Date(..) | Value::Time(..) => None,
+        }
+    }
+
+    /// Returns the minimal fractional seconds precision of a temporal column, that is able
+    /// to store this value without loss (`None` if this value is not `Date` or `Time`,
+    /// or if its microseconds are out of range, i.e. greater than `999_999`).
+    ///
+    /// ```
+    /// # use mysql_common::value::Value;
+    /// assert_eq!(Value::Date(2021, 1, 2, 3, 4, 5, 0).temporal_fsp(), Some(0));
+    /// assert_eq!(Value::Time(false, 0, 1, 2, 3, 120_000).temporal_fsp(), Some(2));
+    /// ```
+    pub fn temporal_fsp(&self) -> Option<u8> {
+        let mut micros = match self {
+            Value::Date(_, _, _, _, _, _, micros) | Value::Time(_, _, _, _, _, micros) => *micros,
+            _ => return None,
+        };
+
+        if micros > 999_999 {
+            return None;
+        }
+        if micros == 0 {
+            return Some(0);
         }
+
+        let mut fsp = 6;
+        while micros % 10 == 0 {
+            micros /= 10;
+            fsp -= 1;
+        }
+        Some(fsp)
+    }
+
+    /// Returns the minimal storage size (in bytes) of an integer column with the given
+    /// signedness, that is able to store this value, i.e. `1` for `TINYINT`, `2` for `SMALLINT`,
+    /// `3` for `MEDIUMINT`, `4` for `INT` and `8` for `BIGINT`.
+    ///
+    /// Returns `None` if this value is not an integer or doesn't fit into such a column.
+    ///
+    /// ```
+    /// # use mysql_common::value::Value;
+    /// assert_eq!(Value::Int(-129).int_bytes(false), Some(2));
+    /// assert_eq!(Value::Int(255).int_bytes(true), Some(1));
+    /// assert_eq!(Value::Int(-1).int_bytes(true), None);
+    /// ```
+    pub fn int_bytes(&self, unsigned: bool) -> Option<u8> {
+        let x = match *self {
+            Value::Int(x) => i128::from(x),
+            Value::UInt(x) => i128::from(x),
+            _ => return None,
+        };
+
+        [1_u8, 2, 3, 4, 8].iter().copied().find(|bytes| {
+            let bits = u32::from(*bytes) * 8;
+            if unsigned {
+                x >= 0 && x < 1_i128 << bits
+            } else {
+                x >= -(1_i128 << (bits - 1)) && x < 1_i128 << (bits - 1)
+            }
+        })
     }
 
     fn deserialize_text(buf: &mut ParseBuf<'_>) -> io::Result<Self> {
@@ -449,6 +574,29 @@ impl Value {
     }
 }
 
+/// Returns precision and scale of a decimal number in text form.
+fn decimal_precision_scale(text: &[u8]) -> Option<(u32, u32)> {
+    let text = match text.first() {
+        Some(b'-') | Some(b'+') => &text[1..],
+        _ => text,
+    };
+
+    let (int_part, frac_part) = match text.iter().position(|x| *x == b'.') {
+        Some(pos) => (&text[..pos], &text[pos + 1..]),
+        None => (text, &[][..]),
+    };
+
+    let is_digits = |x: &[u8]| x.iter().all(u8::is_ascii_digit);
+    if int_part.is_empty() && frac_part.is_empty() || !is_digits(int_part) || !is_digits(frac_part)
+    {
+        return None;
+    }
+
+    let int_digits = int_part.iter().skip_while(|x| **x == b'0').count() as u32;
+    let scale = frac_part.len() as u32;
+    Some((std::cmp::max(int_digits + scale, 1), scale))
+}
+
 impl fmt::Debug for Value {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -488,9 +636,19 @@ impl fmt::Debug for Value {
             }
             Value::Time(neg, d, h, i, s, 0) => {
                 let format = if neg {
-                    format!("'-{:03}:{:02}:{:02}'", d * 24 + u32::from(h), i, s)
+                    format!(
+                        "'-{:03}:{:02}:{:02}'",
+                        u64::from(d) * 24 + u64::from(h),
+                        i,
+                        s
+                    )
                 } else {
-                    format!("'{:03}:{:02}:{:02}'", d * 24 + u32::from(h), i, s)
+                    format!(
+                        "'{:03}:{:02}:{:02}'",
+                        u64::from(d) * 24 + u64::from(h),
+                        i,
+                        s
+                    )
                 };
                 formatter.debug_tuple("Time").field(&format).finish()
             }
@@ -498,7 +656,7 @@ impl fmt::Debug for Value {
                 let format = if neg {
                     format!(
                         "'-{:03}:{:02}:{:02}.{:06}'",
-                        days * 24 + u32::from(hours),
+                        u64::from(days) * 24 + u64::from(hours),
                         minutes,
                         seconds,
                         micros
@@ -506,7 +664,7 @@ impl fmt::Debug for Value {
                 } else {
                     format!(
                         "'{:03}:{:02}:{:02}.{:06}'",
-                        days * 24 + u32::from(hours),
+                        u64::from(days) * 24 + u64::from(hours),
                         minutes,
                         seconds,
                         micros
@@ -522,7 +680,12 @@ impl fmt::Debug for Value {
 mod test {
     use std::io;
 
-    use crate::{io::ParseBuf, value::Value};
+    use crate::{
+        constants::ColumnType,
+        io::ParseBuf,
+        proto::{MyDeserialize, MySerialize},
+        value::{TextValue, Value, ValueDeserializer, ValueSerializer},
+    };
 
     #[test]
     fn should_escape_string() {
@@ -532,6 +695,118 @@ mod test {
         assert_eq!(r"'?p??\n?p??'", Value::from("?p??\n?p??").as_sql(false));
         assert_eq!(r"'?p??\r?p??'", Value::from("?p??\r?p??").as_sql(false));
         assert_eq!(r"'?p??\0?p??'", Value::from("?p??\x00?p??").as_sql(false));
+        assert_eq!(r"'?p??\Z?p??'", Value::from("?p??\x1a?p??").as_sql(false));
+        assert_eq!(r"'?p??''?p??'", Value::from("?p??'?p??").as_sql(true));
+        assert_eq!(r"'?p??\?p??'", Value::from("?p??\\?p??").as_sql(true));
+    }
+
+    #[test]
+    fn should_format_sql_literal() {
+        assert_eq!(Value::NULL.sql_literal(false).to_string(), "NULL");
+        assert_eq!(Value::Int(-42).sql_literal(false).to_string(), "-42");
+        assert_eq!(Value::UInt(u64::MAX).as_sql(false), "18446744073709551615");
+        assert_eq!(Value::Double(0.5).sql_literal(false).to_string(), "0.5");
+        assert_eq!(
+            Value::Double(f64::NAN).sql_literal(false).to_string(),
+            "NULL"
+        );
+        assert_eq!(
+            Value::Float(f32::NEG_INFINITY)
+                .sql_literal(false)
+                .to_string(),
+            "NULL"
+        );
+        assert_eq!(
+            Value::Date(2021, 1, 2, 0, 0, 0, 0).as_sql(false),
+            "'2021-01-02'"
+        );
+        assert_eq!(
+            Value::Date(2021, 1, 2, 3, 4, 5, 6).as_sql(false),
+            "'2021-01-02 03:04:05.000006'"
+        );
+        assert_eq!(
+            Value::Time(true, 1, 2, 3, 4, 0).as_sql(false),
+            "'-026:03:04'"
+        );
+        assert_eq!(
+            Value::Time(false, 0, 2, 3, 4, 5).as_sql(false),
+            "'002:03:04.000005'"
+        );
+        assert_eq!(Value::Bytes(vec![0xff, 0x00]).as_sql(false), "0xFF00");
+        assert_eq!(
+            format!("SELECT {}", Value::from("a'b").sql_literal(true)),
+            "SELECT 'a''b'"
+        );
+    }
+
+    #[test]
+    fn should_serialize_text_value() {
+        fn text(value: &Value, column_type: Option<ColumnType>) -> Vec<u8> {
+            let mut buf = Vec::new();
+            ValueSerializer::<TextValue>::new(value)
+                .with_column_type(column_type)
+                .serialize(&mut buf);
+            buf
+        }
+
+        assert_eq!(text(&Value::NULL, None), b"\xfb");
+        assert_eq!(text(&Value::Bytes(b"foo".to_vec()), None), b"\x03foo");
+        assert_eq!(text(&Value::Int(-1), None), b"\x02-1");
+        assert_eq!(text(&Value::Double(1.5), None), b"\x031.5");
+        assert_eq!(text(&Value::Double(f64::INFINITY), None), b"\xfb");
+        assert_eq!(
+            text(&Value::Date(2021, 3, 4, 5, 6, 7, 0), None),
+            b"\x132021-03-04 05:06:07"
+        );
+        assert_eq!(
+            text(
+                &Value::Date(2021, 3, 4, 0, 0, 0, 0),
+                Some(ColumnType::MYSQL_TYPE_DATE)
+            ),
+            b"\x0a2021-03-04"
+        );
+        assert_eq!(
+            text(&Value::Time(true, 1, 2, 3, 4, 5), None),
+            b"\x10-26:03:04.000005"
+        );
+
+        for value in &[
+            Value::NULL,
+            Value::Bytes(b"bar".to_vec()),
+            Value::UInt(u64::MAX),
+        ] {
+            let buf = text(value, None);
+            let parsed = ValueDeserializer::<TextValue>::deserialize((), &mut ParseBuf(&buf))
+                .unwrap()
+                .0;
+            match value {
+                Value::UInt(x) => assert_eq!(parsed, Value::Bytes(x.to_string().into_bytes())),
+                _ => assert_eq!(&parsed, value),
+            }
+        }
+    }
+
+    #[test]
+    fn should_format_hex_literal() {
+        let value = Value::Bytes(b"a'\x1b[31m\xff".to_vec());
+        assert_eq!(
+            value.sql_literal(false).with_hex_bytes(true).to_string(),
+            "_binary X'61271B5B33316DFF'"
+        );
+        assert_eq!(
+            Value::Bytes(vec![])
+                .sql_literal(false)
+                .with_hex_bytes(true)
+                .to_string(),
+            "_binary X''"
+        );
+        assert_eq!(
+            Value::Int(1)
+                .sql_literal(false)
+                .with_hex_bytes(true)
+                .to_string(),
+            "1"
+        );
     }
 
     #[cfg(feature = "nightly")]
@@ -657,6 +932,45 @@ mod test {
         }
     }
 
+    #[test]
+    fn should_inspect_values() {
+        assert_eq!(Value::from("0").decimal_precision_scale(), Some((1, 0)));
+        assert_eq!(Value::from("-0.05").decimal_precision_scale(), Some((2, 2)));
+        assert_eq!(Value::from("+007.").decimal_precision_scale(), Some((1, 0)));
+        assert_eq!(Value::from(".5").decimal_precision_scale(), Some((1, 1)));
+        assert_eq!(Value::Double(-1.25).decimal_precision_scale(), Some((3, 2)));
+        assert_eq!(
+            Value::Int(i64::MIN).decimal_precision_scale(),
+            Some((19, 0))
+        );
+        assert_eq!(Value::from(".").decimal_precision_scale(), None);
+        assert_eq!(Value::from("1.2.3").decimal_precision_scale(), None);
+        assert_eq!(Value::Double(f64::NAN).decimal_precision_scale(), None);
+        assert_eq!(Value::NULL.decimal_precision_scale(), None);
+
+        assert_eq!(Value::Date(0, 0, 0, 0, 0, 0, 1).temporal_fsp(), Some(6));
+        assert_eq!(
+            Value::Time(true, 1, 0, 0, 0, 500_000).temporal_fsp(),
+            Some(1)
+        );
+        assert_eq!(Value::from("12:00:00.5").temporal_fsp(), None);
+        assert_eq!(
+            Value::Date(0, 0, 0, 0, 0, 0, 10_000_000).temporal_fsp(),
+            None
+        );
+        assert_eq!(
+            Value::Time(false, 0, 0, 0, 0, 1_000_000).temporal_fsp(),
+            None
+        );
+
+        assert_eq!(Value::Int(8_388_607).int_bytes(false), Some(3));
+        assert_eq!(Value::Int(8_388_608).int_bytes(false), Some(4));
+        assert_eq!(Value::Int(-128).int_bytes(false), Some(1));
+        assert_eq!(Value::UInt(u64::MAX).int_bytes(true), Some(8));
+        assert_eq!(Value::UInt(u64::MAX).int_bytes(false), None);
+        assert_eq!(Value::from("1").int_bytes(false), None);
+    }
+
     #[test]
     fn mysql_simple_issue_284() -> io::Result<()> {
         use Value::*;